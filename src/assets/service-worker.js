@@ -0,0 +1,127 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+// Caches the application shell (index.html and the hashed wasm, js and css
+// bundles it references) so the web admin loads instantly and can be
+// displayed while offline. API requests are never intercepted.
+
+const SHELL_CACHE = "webadmin-shell";
+const SHELL_URL = "/";
+const STATIC_ASSETS = [
+    "/site.webmanifest",
+    "/logo.svg",
+    "/favicon.ico",
+    "/apple-touch-icon.png",
+    "/android-chrome-192x192.png",
+    "/android-chrome-512x512.png",
+];
+const SERVER_PATHS = [
+    "/api/",
+    "/auth/",
+    "/jmap",
+    "/dav/",
+    "/.well-known/",
+    "/healthz/",
+    "/metrics/",
+    "/robots.txt",
+];
+
+// Returns the bundles referenced by an index.html built by Trunk.
+function shellAssets(html) {
+    const assets = new Set();
+    for (const match of html.matchAll(/(?:href|src)="([^"]+\.(?:js|wasm|css))"/g)) {
+        assets.add(new URL(match[1], self.location.origin).pathname);
+    }
+    return [...assets].sort();
+}
+
+// Fetches the latest index.html and caches it along with its bundles,
+// returning whether it references different bundles than the cached one.
+async function updateShell() {
+    const cache = await caches.open(SHELL_CACHE);
+    const response = await fetch(SHELL_URL, { cache: "no-store" });
+    if (!response.ok) {
+        return false;
+    }
+    const html = await response.clone().text();
+    const assets = shellAssets(html);
+
+    const cached = await cache.match(SHELL_URL);
+    const previous = cached ? shellAssets(await cached.text()) : [];
+    if (cached && previous.join() === assets.join()) {
+        return false;
+    }
+
+    // Bundles are stored before the index that references them
+    await cache.addAll([...assets, ...STATIC_ASSETS]);
+    await cache.put(SHELL_URL, response);
+    for (const asset of previous) {
+        if (!assets.includes(asset)) {
+            await cache.delete(asset);
+        }
+    }
+    return cached !== undefined;
+}
+
+async function checkForUpdate() {
+    try {
+        if (await updateShell()) {
+            const clients = await self.clients.matchAll({ type: "window" });
+            for (const client of clients) {
+                client.postMessage({ type: "update-available" });
+            }
+        }
+    } catch (err) {
+        // Offline, the cached shell is kept
+    }
+}
+
+function isServerPath(pathname) {
+    return SERVER_PATHS.some((path) => pathname.startsWith(path));
+}
+
+self.addEventListener("install", (event) => {
+    event.waitUntil(updateShell().then(() => self.skipWaiting()));
+});
+
+self.addEventListener("activate", (event) => {
+    event.waitUntil(self.clients.claim());
+});
+
+self.addEventListener("message", (event) => {
+    if (event.data && event.data.type === "check-update") {
+        event.waitUntil(checkForUpdate());
+    }
+});
+
+self.addEventListener("fetch", (event) => {
+    const request = event.request;
+    const url = new URL(request.url);
+    if (
+        request.method !== "GET" ||
+        url.origin !== self.location.origin ||
+        isServerPath(url.pathname)
+    ) {
+        return;
+    }
+
+    if (request.mode === "navigate") {
+        // Every route of the web admin is served by the same index.html
+        event.respondWith(
+            caches.match(SHELL_URL).then((cached) => {
+                if (cached) {
+                    event.waitUntil(checkForUpdate());
+                    return cached;
+                }
+                return fetch(request);
+            })
+        );
+    } else {
+        event.respondWith(
+            caches.match(url.pathname).then((cached) => cached || fetch(request))
+        );
+    }
+});
@@ -0,0 +1,97 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::*;
+
+use super::{
+    axis_max, format_value, register_chart_data, ChartAxis, Series, CHART_PADDING, CHART_WIDTH,
+};
+
+#[component]
+pub fn BarChart(
+    #[prop(into)] labels: MaybeSignal<Vec<String>>,
+    #[prop(into)] series: MaybeSignal<Vec<Series>>,
+    #[prop(optional)] height: Option<u32>,
+    #[prop(optional)] stacked: bool,
+) -> impl IntoView {
+    let height = height.unwrap_or(200) as f64;
+    register_chart_data(labels.clone(), series.clone());
+
+    view! {
+        <svg
+            class="w-full"
+            data-chart=""
+            xmlns="http://www.w3.org/2000/svg"
+            viewBox=format!("0 0 {CHART_WIDTH} {height}")
+        >
+            {move || {
+                let labels = labels.get();
+                let series = series.get();
+                let total = labels.len().max(1);
+                let max = if stacked {
+                    axis_max(
+                        (0..total)
+                            .map(|idx| {
+                                series
+                                    .iter()
+                                    .map(|s| s.values.get(idx).copied().unwrap_or_default())
+                                    .sum::<f64>()
+                            }),
+                    )
+                } else {
+                    axis_max(series.iter().flat_map(|s| s.values.iter().copied()))
+                };
+                let plot_height = height - CHART_PADDING;
+                let slot_width = (CHART_WIDTH - CHART_PADDING) / total as f64;
+                let bar_width = if stacked {
+                    slot_width * 0.6
+                } else {
+                    slot_width * 0.8 / series.len().max(1) as f64
+                };
+                let mut bars = Vec::new();
+                for idx in 0..total {
+                    let slot_x = CHART_PADDING + slot_width * idx as f64 + slot_width * 0.1;
+                    let mut offset = 0.0;
+                    for (pos, series) in series.iter().enumerate() {
+                        let value = series.values.get(idx).copied().unwrap_or_default();
+                        let bar_height = plot_height * value / max;
+                        let (x, y) = if stacked {
+                            offset += bar_height;
+                            (slot_x + slot_width * 0.1, plot_height - offset)
+                        } else {
+                            (slot_x + bar_width * pos as f64, plot_height - bar_height)
+                        };
+                        let tooltip = format!(
+                            "{}: {}",
+                            series.name,
+                            format_value(value),
+                        );
+                        bars.push(
+                            view! {
+                                <rect
+                                    x=x
+                                    y=y
+                                    width=bar_width
+                                    height=bar_height
+                                    rx="2"
+                                    class=series.color.fill_class()
+                                >
+                                    <title>{tooltip}</title>
+                                </rect>
+                            },
+                        );
+                    }
+                }
+
+                view! {
+                    <ChartAxis height=height max=max labels=labels/>
+                    {bars}
+                }
+            }}
+
+        </svg>
+    }
+}
@@ -0,0 +1,234 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::{document, window};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    js_sys::encode_uri_component,
+    wasm_bindgen::{JsCast, JsValue},
+    CanvasRenderingContext2d, Element, HtmlCanvasElement, HtmlImageElement,
+};
+
+use crate::{core::file::download_url, pages::manage::audit::csv_escape};
+
+use super::{ChartData, CHART_PADDING};
+
+const SVG_NS: &str = "http://www.w3.org/2000/svg";
+
+/// Properties set by the stylesheet that have to be inlined, since it is
+/// not available once the chart leaves the page.
+const STYLE_PROPERTIES: &[&str] = &[
+    "fill",
+    "fill-opacity",
+    "stroke",
+    "stroke-width",
+    "stroke-linejoin",
+    "opacity",
+    "font-family",
+    "font-size",
+    "font-weight",
+];
+const LEGEND_HEIGHT: f64 = 24.0;
+/// Exported images are rendered at twice their size to stay sharp when
+/// pasted into documents.
+const PNG_SCALE: f64 = 2.0;
+
+/// Chart rendered as a standalone SVG document.
+pub(crate) struct SvgImage {
+    pub markup: String,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// Returns the chart data as CSV, one row per label and one column per
+/// series.
+pub(crate) fn to_csv(data: &ChartData) -> String {
+    let mut csv = std::iter::once("label")
+        .chain(data.series.iter().map(|series| series.name.as_str()))
+        .map(csv_escape)
+        .collect::<Vec<_>>()
+        .join(",");
+    csv.push_str("\r\n");
+    for (idx, label) in data.labels.iter().enumerate() {
+        csv.push_str(&csv_escape(label));
+        for series in &data.series {
+            csv.push(',');
+            if let Some(value) = series.values.get(idx) {
+                csv.push_str(&value.to_string());
+            }
+        }
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+/// Copies a chart into a standalone SVG document with a white background
+/// and the legend drawn below the plot.
+pub(crate) fn to_svg(svg: &Element, data: &ChartData) -> Result<SvgImage, String> {
+    let failed = |_| "Failed to export chart".to_string();
+    let (width, height) = svg
+        .get_attribute("viewBox")
+        .and_then(|view_box| {
+            let mut parts = view_box.split_whitespace().skip(2);
+            Some((
+                parts.next()?.parse::<f64>().ok()?,
+                parts.next()?.parse::<f64>().ok()?,
+            ))
+        })
+        .ok_or_else(|| "Failed to obtain chart size".to_string())?;
+    let height = if data.series.is_empty() {
+        height
+    } else {
+        height + LEGEND_HEIGHT
+    };
+
+    let image = svg
+        .clone_node_with_deep(true)
+        .map_err(failed)?
+        .unchecked_into::<Element>();
+    let originals = svg.query_selector_all("*").map_err(failed)?;
+    let copies = image.query_selector_all("*").map_err(failed)?;
+    for idx in 0..originals.length() {
+        let (Some(original), Some(copy)) = (originals.item(idx), copies.item(idx)) else {
+            continue;
+        };
+        let (Ok(original), Ok(copy)) = (original.dyn_into::<Element>(), copy.dyn_into::<Element>())
+        else {
+            continue;
+        };
+        if let Ok(Some(style)) = window().get_computed_style(&original) {
+            let inline = STYLE_PROPERTIES
+                .iter()
+                .filter_map(|property| {
+                    style
+                        .get_property_value(property)
+                        .ok()
+                        .filter(|value| !value.is_empty())
+                        .map(|value| format!("{property}:{value}"))
+                })
+                .collect::<Vec<_>>()
+                .join(";");
+            copy.set_attribute("style", &inline).map_err(failed)?;
+        }
+        let _ = copy.remove_attribute("class");
+    }
+    let _ = image.remove_attribute("class");
+    image
+        .set_attribute("viewBox", &format!("0 0 {width} {height}"))
+        .map_err(failed)?;
+    image
+        .set_attribute("width", &width.to_string())
+        .map_err(failed)?;
+    image
+        .set_attribute("height", &height.to_string())
+        .map_err(failed)?;
+
+    let background = svg_element(
+        "rect",
+        &[("width", "100%"), ("height", "100%"), ("fill", "#ffffff")],
+    )?;
+    image
+        .insert_before(&background, image.first_child().as_ref())
+        .map_err(failed)?;
+
+    let mut x = CHART_PADDING;
+    let y = height - LEGEND_HEIGHT / 2.0;
+    for series in &data.series {
+        let swatch = svg_element(
+            "rect",
+            &[
+                ("x", &x.to_string()),
+                ("y", &(y - 5.0).to_string()),
+                ("width", "10"),
+                ("height", "10"),
+                ("rx", "2"),
+                ("fill", series.color.hex()),
+            ],
+        )?;
+        let label = svg_element(
+            "text",
+            &[
+                ("x", &(x + 14.0).to_string()),
+                ("y", &(y + 4.0).to_string()),
+                ("fill", "#4b5563"),
+                ("font-family", "sans-serif"),
+                ("font-size", "11px"),
+            ],
+        )?;
+        label.set_text_content(Some(&series.name));
+        image.append_child(&swatch).map_err(failed)?;
+        image.append_child(&label).map_err(failed)?;
+        // Approximate width of the label, the text is not measured
+        x += 14.0 + series.name.chars().count() as f64 * 6.5 + 16.0;
+    }
+
+    Ok(SvgImage {
+        markup: image.outer_html(),
+        width,
+        height,
+    })
+}
+
+/// Rasterizes an exported chart and offers it as a PNG download.
+pub(crate) async fn download_png(file_name: &str, image: &SvgImage) -> Result<(), String> {
+    let failed = |_| "Failed to convert chart to PNG".to_string();
+    let element = HtmlImageElement::new().map_err(failed)?;
+    element.set_src(&format!(
+        "data:image/svg+xml;charset=utf-8,{}",
+        encode_uri_component(&image.markup)
+    ));
+    JsFuture::from(element.decode()).await.map_err(failed)?;
+
+    let width = image.width * PNG_SCALE;
+    let height = image.height * PNG_SCALE;
+    let canvas = document()
+        .create_element("canvas")
+        .map_err(failed)?
+        .unchecked_into::<HtmlCanvasElement>();
+    canvas.set_width(width as u32);
+    canvas.set_height(height as u32);
+    let context = canvas
+        .get_context("2d")
+        .ok()
+        .flatten()
+        .and_then(|context| context.dyn_into::<CanvasRenderingContext2d>().ok())
+        .ok_or_else(|| "Failed to convert chart to PNG".to_string())?;
+    context
+        .draw_image_with_html_image_element_and_dw_and_dh(&element, 0.0, 0.0, width, height)
+        .map_err(failed)?;
+    let url = canvas.to_data_url_with_type("image/png").map_err(failed)?;
+
+    download_url(file_name, &url)
+}
+
+/// Builds a file name out of the chart title.
+pub(crate) fn file_name(title: &str, extension: &str) -> String {
+    let mut name = String::with_capacity(title.len());
+    for ch in title.chars() {
+        if ch.is_ascii_alphanumeric() {
+            name.push(ch.to_ascii_lowercase());
+        } else if !name.is_empty() && !name.ends_with('-') {
+            name.push('-');
+        }
+    }
+    let name = name.trim_end_matches('-');
+    format!(
+        "{}.{extension}",
+        if name.is_empty() { "chart" } else { name }
+    )
+}
+
+fn svg_element(name: &str, attributes: &[(&str, &str)]) -> Result<Element, String> {
+    let element = document()
+        .create_element_ns(Some(SVG_NS), name)
+        .map_err(|_| "Failed to export chart".to_string())?;
+    for (attribute, value) in attributes {
+        element
+            .set_attribute(attribute, value)
+            .map_err(|_: JsValue| "Failed to export chart".to_string())?;
+    }
+    Ok(element)
+}
@@ -0,0 +1,67 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::f64::consts::PI;
+
+use leptos::*;
+
+use crate::components::Color;
+
+use super::format_value;
+
+#[component]
+pub fn Gauge(
+    #[prop(into)] value: MaybeSignal<f64>,
+    #[prop(into)] max: MaybeSignal<f64>,
+    #[prop(optional, into)] label: MaybeSignal<String>,
+    #[prop(optional)] color: Option<Color>,
+) -> impl IntoView {
+    let color = color.unwrap_or(Color::Blue);
+    let ratio = create_memo(move |_| {
+        let max = max.get();
+        if max > 0.0 {
+            (value.get() / max).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    });
+
+    view! {
+        <div class="relative flex flex-col items-center">
+            <svg class="w-full max-w-xs" xmlns="http://www.w3.org/2000/svg" viewBox="0 0 120 70">
+                <path
+                    d=arc_path(1.0)
+                    fill="none"
+                    stroke-width="10"
+                    stroke-linecap="round"
+                    class="stroke-gray-200 dark:stroke-gray-700"
+                ></path>
+                <path
+                    d=move || arc_path(ratio.get())
+                    fill="none"
+                    stroke-width="10"
+                    stroke-linecap="round"
+                    class=color.stroke_class()
+                ></path>
+                <text
+                    x="60"
+                    y="58"
+                    text-anchor="middle"
+                    class="fill-gray-800 text-lg font-semibold dark:fill-gray-200"
+                >
+                    {move || format_value(value.get())}
+                </text>
+            </svg>
+            <span class="text-sm text-gray-500">{move || label.get()}</span>
+        </div>
+    }
+}
+
+fn arc_path(ratio: f64) -> String {
+    let angle = PI * (1.0 - ratio);
+    let (x, y) = (60.0 + 50.0 * angle.cos(), 60.0 - 50.0 * angle.sin());
+    format!("M 10 60 A 50 50 0 0 1 {x:.2} {y:.2}")
+}
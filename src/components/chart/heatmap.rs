@@ -0,0 +1,92 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::*;
+
+use crate::components::Color;
+
+use super::format_value;
+
+#[component]
+pub fn Heatmap(
+    #[prop(into)] rows: MaybeSignal<Vec<String>>,
+    #[prop(into)] columns: MaybeSignal<Vec<String>>,
+    #[prop(into)] values: MaybeSignal<Vec<Vec<f64>>>,
+    #[prop(optional)] color: Option<Color>,
+) -> impl IntoView {
+    let color = color.unwrap_or(Color::Blue);
+
+    view! {
+        <div class="overflow-x-auto">
+            <table class="min-w-full text-xs">
+                <thead>
+                    <tr>
+                        <th></th>
+                        {move || {
+                            columns
+                                .get()
+                                .into_iter()
+                                .map(|column| {
+                                    view! {
+                                        <th class="px-1 py-1 font-normal text-gray-500">{column}</th>
+                                    }
+                                })
+                                .collect_view()
+                        }}
+
+                    </tr>
+                </thead>
+                <tbody>
+                    {move || {
+                        let values = values.get();
+                        let max = values
+                            .iter()
+                            .flat_map(|row| row.iter().copied())
+                            .fold(0.0f64, f64::max)
+                            .max(f64::MIN_POSITIVE);
+                        rows.get()
+                            .into_iter()
+                            .enumerate()
+                            .map(|(row_idx, row)| {
+                                let cells = values
+                                    .get(row_idx)
+                                    .cloned()
+                                    .unwrap_or_default()
+                                    .into_iter()
+                                    .map(|value| {
+                                        let opacity = if value > 0.0 {
+                                            0.15 + 0.85 * (value / max)
+                                        } else {
+                                            0.0
+                                        };
+                                        view! {
+                                            <td class="p-0.5">
+                                                <div
+                                                    class=format!("h-5 min-w-5 rounded {}", color.bg_class())
+                                                    style=format!("opacity: {opacity:.2}")
+                                                    title=format_value(value)
+                                                ></div>
+                                            </td>
+                                        }
+                                    })
+                                    .collect_view();
+                                view! {
+                                    <tr>
+                                        <th class="pe-2 text-end font-normal text-gray-500 whitespace-nowrap">
+                                            {row}
+                                        </th>
+                                        {cells}
+                                    </tr>
+                                }
+                            })
+                            .collect_view()
+                    }}
+
+                </tbody>
+            </table>
+        </div>
+    }
+}
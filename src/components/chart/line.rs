@@ -0,0 +1,70 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::*;
+
+use super::{axis_max, register_chart_data, ChartAxis, Series, CHART_PADDING, CHART_WIDTH};
+
+#[component]
+pub fn LineChart(
+    #[prop(into)] labels: MaybeSignal<Vec<String>>,
+    #[prop(into)] series: MaybeSignal<Vec<Series>>,
+    #[prop(optional)] height: Option<u32>,
+) -> impl IntoView {
+    let height = height.unwrap_or(200) as f64;
+    register_chart_data(labels.clone(), series.clone());
+
+    view! {
+        <svg
+            class="w-full"
+            data-chart=""
+            xmlns="http://www.w3.org/2000/svg"
+            viewBox=format!("0 0 {CHART_WIDTH} {height}")
+        >
+            {move || {
+                let labels = labels.get();
+                let series = series.get();
+                let max = axis_max(series.iter().flat_map(|s| s.values.iter().copied()));
+                let plot_height = height - CHART_PADDING;
+                let plot_width = CHART_WIDTH - CHART_PADDING;
+                let total = labels.len().max(1) as f64;
+                let lines = series
+                    .into_iter()
+                    .map(|series| {
+                        let points = series
+                            .values
+                            .iter()
+                            .enumerate()
+                            .map(|(idx, value)| {
+                                format!(
+                                    "{:.1},{:.1}",
+                                    CHART_PADDING + plot_width * (idx as f64 + 0.5) / total,
+                                    plot_height - (plot_height * value / max),
+                                )
+                            })
+                            .collect::<Vec<_>>()
+                            .join(" ");
+                        view! {
+                            <polyline
+                                points=points
+                                fill="none"
+                                stroke-width="2"
+                                stroke-linejoin="round"
+                                class=series.color.stroke_class()
+                            ></polyline>
+                        }
+                    })
+                    .collect_view();
+
+                view! {
+                    <ChartAxis height=height max=max labels=labels/>
+                    {lines}
+                }
+            }}
+
+        </svg>
+    }
+}
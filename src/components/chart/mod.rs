@@ -0,0 +1,368 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+pub mod bar;
+pub mod export;
+pub mod gauge;
+pub mod heatmap;
+pub mod line;
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use leptos::{html::Div, *};
+use leptos_use::use_element_visibility;
+
+use crate::core::{clipboard::copy_to_clipboard, file::download_file};
+
+use self::export::{download_png, file_name, to_csv, to_svg};
+
+use super::{
+    messages::alert::{use_alerts, Alert},
+    Color,
+};
+
+pub(crate) const CHART_WIDTH: f64 = 600.0;
+pub(crate) const CHART_PADDING: f64 = 32.0;
+
+const EXPORT_BUTTON_CLASS: &str = "font-medium text-gray-500 decoration-2 hover:text-blue-600 hover:underline dark:text-gray-400 dark:hover:text-blue-500";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Series {
+    pub name: String,
+    pub color: Color,
+    pub values: Vec<f64>,
+}
+
+/// Labels and series plotted by a chart.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChartData {
+    pub labels: Vec<String>,
+    pub series: Vec<Series>,
+}
+
+/// Data of the chart currently rendered inside a [`LazyChart`], tagged with
+/// the chart that registered it.
+#[derive(Clone, Copy)]
+struct ChartExport(RwSignal<Option<(u64, ChartData)>>);
+
+static NEXT_CHART_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Makes the data plotted by a chart available to the export menu of the
+/// enclosing [`LazyChart`], if any.
+pub(crate) fn register_chart_data(
+    labels: MaybeSignal<Vec<String>>,
+    series: MaybeSignal<Vec<Series>>,
+) {
+    let Some(ChartExport(export)) = use_context::<ChartExport>() else {
+        return;
+    };
+    let id = NEXT_CHART_ID.fetch_add(1, Ordering::Relaxed);
+    create_effect(move |_| {
+        let data = ChartData {
+            labels: labels.get(),
+            series: series.get(),
+        };
+        export.set(Some((id, data)));
+    });
+    // Charts replaced by another one must not clear its data
+    on_cleanup(move || {
+        if export
+            .try_with_untracked(|export| matches!(export, Some((export_id, _)) if *export_id == id))
+            == Some(true)
+        {
+            export.set(None);
+        }
+    });
+}
+
+/// Defers rendering its children until the chart container scrolls into view,
+/// so pages with several charts only pay for the ones being looked at. Charts
+/// rendered inside can be exported as SVG or PNG and their data copied as CSV.
+#[component]
+pub fn LazyChart(
+    #[prop(optional, into)] title: MaybeSignal<String>,
+    #[prop(optional, into)] subtitle: MaybeSignal<String>,
+    children: ChildrenFn,
+) -> impl IntoView {
+    let alert = use_alerts();
+    let export = ChartExport(create_rw_signal(None));
+    provide_context(export);
+    let target = create_node_ref::<Div>();
+    let is_visible = use_element_visibility(target);
+    let was_visible = create_memo(move |was_visible: Option<&bool>| {
+        was_visible.copied().unwrap_or_default() || is_visible.get()
+    });
+    let children = store_value(children);
+    let has_data = move || export.0.with(|export| export.is_some());
+    let no_title = {
+        let title = title.clone();
+        move || title.with(|title| title.is_empty())
+    };
+    let file_title = create_memo({
+        let title = title.clone();
+        move |_| title.get()
+    });
+    let export_image = move |extension: &'static str| {
+        let data = export
+            .0
+            .with_untracked(|export| export.as_ref().map(|(_, data)| data.clone()));
+        let svg = target
+            .get_untracked()
+            .and_then(|target| target.query_selector("svg[data-chart]").ok().flatten());
+        let (Some(data), Some(svg)) = (data, svg) else {
+            return;
+        };
+        let file_name = file_name(&file_title.get_untracked(), extension);
+        match to_svg(&svg, &data) {
+            Ok(image) if extension == "svg" => {
+                if let Err(err) = download_file(&file_name, "image/svg+xml", &image.markup) {
+                    alert.set(Alert::error(err));
+                }
+            }
+            Ok(image) => {
+                spawn_local(async move {
+                    if let Err(err) = download_png(&file_name, &image).await {
+                        alert.set(Alert::error(err));
+                    }
+                });
+            }
+            Err(err) => {
+                alert.set(Alert::error(err));
+            }
+        }
+    };
+
+    view! {
+        <div
+            class="flex flex-col bg-white border shadow-sm rounded-xl p-4 md:p-5 dark:bg-slate-900 dark:border-gray-700"
+            node_ref=target
+        >
+            <div
+                class="mb-4 flex justify-between items-start gap-x-3"
+                class:hidden=move || no_title() && !has_data()
+            >
+                <div>
+                    <h2 class="text-lg font-semibold text-gray-800 dark:text-gray-200">
+                        {move || title.get()}
+                    </h2>
+                    <p class="text-sm text-gray-500">{move || subtitle.get()}</p>
+                </div>
+                <Show when=has_data>
+                    <div class="inline-flex flex-shrink-0 gap-x-3 text-xs">
+                        <button
+                            type="button"
+                            class=EXPORT_BUTTON_CLASS
+                            title="Download as SVG"
+                            on:click=move |_| export_image("svg")
+                        >
+                            SVG
+                        </button>
+                        <button
+                            type="button"
+                            class=EXPORT_BUTTON_CLASS
+                            title="Download as PNG"
+                            on:click=move |_| export_image("png")
+                        >
+                            PNG
+                        </button>
+                        <button
+                            type="button"
+                            class=EXPORT_BUTTON_CLASS
+                            title="Copy data as CSV"
+                            on:click=move |_| {
+                                let csv = export
+                                    .0
+                                    .with_untracked(|export| {
+                                        export.as_ref().map(|(_, data)| to_csv(data))
+                                    })
+                                    .unwrap_or_default();
+                                if copy_to_clipboard(&csv) {
+                                    alert.set(Alert::success("Chart data copied as CSV"));
+                                } else {
+                                    alert.set(Alert::error("Failed to copy to clipboard"));
+                                }
+                            }
+                        >
+
+                            CSV
+                        </button>
+                    </div>
+                </Show>
+            </div>
+            <Show
+                when=move || was_visible.get()
+                fallback=|| {
+                    view! {
+                        <div class="animate-pulse h-48 bg-gray-200 rounded-lg dark:bg-gray-700"></div>
+                    }
+                }
+            >
+
+                {children.with_value(|children| children())}
+            </Show>
+        </div>
+    }
+}
+
+#[component]
+pub fn ChartLegend(#[prop(into)] series: MaybeSignal<Vec<Series>>) -> impl IntoView {
+    view! {
+        <div class="flex justify-center sm:justify-end items-center gap-x-4 mt-3">
+            {move || {
+                series
+                    .get()
+                    .into_iter()
+                    .map(|series| {
+                        view! {
+                            <div class="inline-flex items-center">
+                                <span class=format!(
+                                    "size-2.5 inline-block rounded-sm me-2 {}",
+                                    series.color.bg_class(),
+                                )></span>
+                                <span class="text-[13px] text-gray-600 dark:text-gray-400">
+                                    {series.name}
+                                </span>
+                            </div>
+                        }
+                    })
+                    .collect_view()
+            }}
+
+        </div>
+    }
+}
+
+impl Color {
+    pub(crate) fn stroke_class(&self) -> &'static str {
+        match self {
+            Color::Blue => "stroke-blue-600",
+            Color::Gray => "stroke-gray-400",
+            Color::Red => "stroke-red-500",
+            Color::Yellow => "stroke-yellow-500",
+            Color::Green => "stroke-teal-500",
+        }
+    }
+
+    pub(crate) fn fill_class(&self) -> &'static str {
+        match self {
+            Color::Blue => "fill-blue-600",
+            Color::Gray => "fill-gray-400",
+            Color::Red => "fill-red-500",
+            Color::Yellow => "fill-yellow-500",
+            Color::Green => "fill-teal-500",
+        }
+    }
+
+    /// Color matching the classes above, for charts rendered outside the page.
+    pub(crate) fn hex(&self) -> &'static str {
+        match self {
+            Color::Blue => "#2563eb",
+            Color::Gray => "#9ca3af",
+            Color::Red => "#ef4444",
+            Color::Yellow => "#eab308",
+            Color::Green => "#14b8a6",
+        }
+    }
+
+    pub(crate) fn bg_class(&self) -> &'static str {
+        match self {
+            Color::Blue => "bg-blue-600",
+            Color::Gray => "bg-gray-400",
+            Color::Red => "bg-red-500",
+            Color::Yellow => "bg-yellow-500",
+            Color::Green => "bg-teal-500",
+        }
+    }
+}
+
+/// Rounds the maximum value of an axis up to a human friendly number.
+pub(crate) fn axis_max(values: impl Iterator<Item = f64>) -> f64 {
+    let max = values.fold(0.0f64, f64::max);
+    if max <= 0.0 {
+        return 1.0;
+    }
+    let magnitude = 10f64.powf(max.log10().floor());
+    let normalized = max / magnitude;
+    let nice = if normalized <= 1.0 {
+        1.0
+    } else if normalized <= 2.0 {
+        2.0
+    } else if normalized <= 5.0 {
+        5.0
+    } else {
+        10.0
+    };
+    nice * magnitude
+}
+
+pub(crate) fn format_value(value: f64) -> String {
+    if value >= 1_000_000.0 {
+        format!("{:.1}M", value / 1_000_000.0)
+    } else if value >= 1_000.0 {
+        format!("{:.1}k", value / 1_000.0)
+    } else if value.fract() == 0.0 {
+        format!("{value:.0}")
+    } else {
+        format!("{value:.2}")
+    }
+}
+
+#[component]
+pub(crate) fn ChartAxis(height: f64, max: f64, #[prop(into)] labels: Vec<String>) -> impl IntoView {
+    let plot_height = height - CHART_PADDING;
+    let plot_width = CHART_WIDTH - CHART_PADDING;
+    let grid = (0..=4)
+        .map(|step| {
+            let y = plot_height - (plot_height * step as f64 / 4.0);
+            let value = format_value(max * step as f64 / 4.0);
+            view! {
+                <line
+                    x1=CHART_PADDING
+                    x2=CHART_WIDTH
+                    y1=y
+                    y2=y
+                    class="stroke-gray-200 dark:stroke-gray-700"
+                    stroke-width="1"
+                ></line>
+                <text
+                    x=CHART_PADDING - 4.0
+                    y=y + 4.0
+                    text-anchor="end"
+                    class="fill-gray-500 text-[10px]"
+                >
+                    {value}
+                </text>
+            }
+        })
+        .collect_view();
+    let total_labels = labels.len().max(1);
+    let skip = (total_labels / 8).max(1);
+    let labels = labels
+        .into_iter()
+        .enumerate()
+        .filter(|(idx, _)| idx % skip == 0)
+        .map(|(idx, label)| {
+            let x = CHART_PADDING + plot_width * (idx as f64 + 0.5) / total_labels as f64;
+            view! {
+                <text
+                    x=x
+                    y=height - 8.0
+                    text-anchor="middle"
+                    class="fill-gray-500 text-[10px]"
+                >
+                    {label}
+                </text>
+            }
+        })
+        .collect_view();
+
+    view! {
+        <g>
+            {grid}
+            {labels}
+        </g>
+    }
+}
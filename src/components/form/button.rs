@@ -6,7 +6,10 @@
 
 use leptos::*;
 
-use crate::components::Color;
+use crate::{
+    components::Color,
+    core::permissions::{use_capability, Capability},
+};
 
 #[component]
 pub fn Button(
@@ -14,9 +17,15 @@ pub fn Button(
     #[prop(into)] color: MaybeSignal<Color>,
     #[prop(into)] on_click: Callback<(), ()>,
     #[prop(optional, into)] disabled: MaybeSignal<bool>,
+    /// Disables the button for accounts not allowed to perform the action.
+    #[prop(optional)]
+    capability: Option<Capability>,
     #[prop(attrs)] attrs: Vec<(&'static str, Attribute)>,
     #[prop(optional)] children: Option<Children>,
 ) -> impl IntoView {
+    let capability = capability.map(use_capability);
+    let is_denied = move || capability.is_some_and(|capability| capability.is_denied());
+
     view! {
         <button
             type="button"
@@ -35,7 +44,10 @@ pub fn Button(
                 }
             }
 
-            disabled=move || disabled.get()
+            disabled=move || disabled.get() || is_denied()
+            // Disabled buttons ignore the pointer, which hides the tooltip
+            class=(["disabled:pointer-events-auto", "disabled:cursor-not-allowed"], is_denied)
+            title=move || capability.and_then(|capability| capability.reason())
             on:click=move |_| on_click.call(())
             {..attrs}
         >
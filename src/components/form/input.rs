@@ -196,8 +196,26 @@ pub fn InputPassword(
     }
 }
 
-const UNIT_GB: u64 = 1024 * 1024 * 1024;
+const UNIT_KB: u64 = 1024;
 const UNIT_MB: u64 = 1024 * 1024;
+const UNIT_GB: u64 = 1024 * 1024 * 1024;
+const UNIT_TB: u64 = 1024 * 1024 * 1024 * 1024;
+
+const SIZE_UNITS: &[(u64, &str)] = &[
+    (UNIT_TB, "TB"),
+    (UNIT_GB, "GB"),
+    (UNIT_MB, "MB"),
+    (UNIT_KB, "KB"),
+    (1, "bytes"),
+];
+
+const DURATION_UNITS: &[(&str, &str, u64)] = &[
+    ("d", "days", 24 * 60 * 60 * 1000),
+    ("h", "hours", 60 * 60 * 1000),
+    ("m", "minutes", 60 * 1000),
+    ("s", "seconds", 1000),
+    ("ms", "ms", 1),
+];
 
 #[component]
 pub fn InputSize(
@@ -212,17 +230,19 @@ pub fn InputSize(
             .value::<u64>(element.id)
             .unwrap_or_default()
     });
+    let selected_unit = create_rw_signal(None::<u64>);
 
     let multiplier = create_memo(move |_| {
         let raw_value = value.get();
         if raw_value == 0 {
             0
-        } else if raw_value % UNIT_GB == 0 {
-            UNIT_GB
-        } else if raw_value % UNIT_MB == 0 {
-            UNIT_MB
+        } else if let Some(unit) = selected_unit.get().filter(|unit| raw_value % unit == 0) {
+            unit
         } else {
-            1
+            SIZE_UNITS
+                .iter()
+                .find(|(unit, _)| raw_value % unit == 0)
+                .map_or(1, |(unit, _)| *unit)
         }
     });
     let display_value = create_memo(move |_| {
@@ -246,6 +266,7 @@ pub fn InputSize(
             <input
                 type="text"
                 class="py-2 px-3 block w-full border-gray-200 shadow-sm rounded-lg text-sm focus:z-10 focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                placeholder="e.g. 50 GB"
                 prop:value=move || {
                     match display_value.get() {
                         0 => String::new(),
@@ -257,14 +278,11 @@ pub fn InputSize(
                     element
                         .data
                         .update(|data| {
-                            match event_target_value(&ev).trim().parse::<u64>() {
-                                Ok(new_value) if new_value > 0 => {
-                                    data.update(
-                                        element.id,
-                                        (new_value * multiplier.get()).to_string(),
-                                    );
+                            match parse_size(&event_target_value(&ev), multiplier.get().max(1)) {
+                                Some(new_value) => {
+                                    data.update(element.id, new_value.to_string());
                                 }
-                                _ => {
+                                None => {
                                     data.new_error(element.id, "Invalid size".to_string());
                                 }
                             }
@@ -272,7 +290,7 @@ pub fn InputSize(
                 }
 
                 {..attrs}
-                disabled=move || { disabled.get() || display_value.get() == 0 }
+                disabled=move || disabled.get()
             />
 
             <div class="absolute inset-y-0 end-0 flex items-center text-gray-500 pe-px">
@@ -287,6 +305,7 @@ pub fn InputSize(
                                         data.remove(element.id);
                                     }
                                     new_multiplier => {
+                                        selected_unit.set(Some(new_multiplier));
                                         data.update(
                                             element.id,
                                             (std::cmp::max(display_value.get(), 1) * new_multiplier)
@@ -296,30 +315,45 @@ pub fn InputSize(
                                 }
                             });
                     }
+
+                    disabled=move || disabled.get()
                 >
 
                     <option selected=move || multiplier.get() == 0 value="0">
                         None
                     </option>
-                    <option selected=move || multiplier.get() == 1 value="1">
-                        bytes
-                    </option>
-                    <option selected=move || multiplier.get() == UNIT_MB value=UNIT_MB.to_string()>
-                        MB
-                    </option>
-                    <option selected=move || multiplier.get() == UNIT_GB value=UNIT_GB.to_string()>
-                        GB
-                    </option>
+                    {SIZE_UNITS
+                        .iter()
+                        .rev()
+                        .map(|(unit, label)| {
+                            let unit = *unit;
+                            view! {
+                                <option selected=move || multiplier.get() == unit value=unit.to_string()>
+                                    {*label}
+                                </option>
+                            }
+                        })
+                        .collect_view()}
                 </select>
             </div>
         </div>
 
         {move || {
-            error
-                .get()
-                .map(|error| {
-                    view! { <p class="text-xs text-red-600 mt-2">{error}</p> }
-                })
+            match (error.get(), value.get()) {
+                (Some(error), _) => {
+                    Some(view! { <p class="text-xs text-red-600 mt-2">{error}</p> })
+                }
+                (None, value) if value > 0 && multiplier.get() != 1 => {
+                    Some(
+                        view! {
+                            <p class="text-xs text-gray-500 mt-2">
+                                {format!("= {} bytes", format_number(value))}
+                            </p>
+                        },
+                    )
+                }
+                _ => None,
+            }
         }}
     }
 }
@@ -350,20 +384,20 @@ pub fn InputDuration(
             <input
                 type="text"
                 class="py-2 px-3 block w-full border-gray-200 shadow-sm rounded-lg text-sm focus:z-10 focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                placeholder="e.g. 30 minutes"
                 prop:value=move || { value.get().value }
 
                 on:change=move |ev| {
                     element
                         .data
                         .update(|data| {
-                            match event_target_value(&ev).trim().parse::<u64>() {
-                                Ok(new_value) if new_value > 0 => {
-                                    data.update(
-                                        element.id,
-                                        value.get().value(new_value).to_string(),
-                                    );
+                            let current = value.get();
+                            let unit = if current.unit.is_empty() { "s" } else { &current.unit };
+                            match parse_duration(&event_target_value(&ev), unit) {
+                                Some(new_value) => {
+                                    data.update(element.id, new_value.to_string());
                                 }
-                                _ => {
+                                None => {
                                     data.new_error(element.id, "Invalid duration".to_string());
                                 }
                             }
@@ -371,7 +405,7 @@ pub fn InputDuration(
                 }
 
                 {..attrs}
-                disabled=move || { disabled.get() || value.get().unit.is_empty() }
+                disabled=move || disabled.get()
             />
 
             <div class="absolute inset-y-0 end-0 flex items-center text-gray-500 pe-px">
@@ -401,31 +435,41 @@ pub fn InputDuration(
                     <option selected=move || value.get().unit.is_empty() value="">
                         None
                     </option>
-                    <option selected=move || value.get().unit == "ms" value="ms">
-                        ms
-                    </option>
-                    <option selected=move || value.get().unit == "s" value="s">
-                        seconds
-                    </option>
-                    <option selected=move || value.get().unit == "m" value="m">
-                        minutes
-                    </option>
-                    <option selected=move || value.get().unit == "h" value="h">
-                        hours
-                    </option>
-                    <option selected=move || value.get().unit == "d" value="d">
-                        days
-                    </option>
+                    {DURATION_UNITS
+                        .iter()
+                        .rev()
+                        .map(|(unit, label, _)| {
+                            view! {
+                                <option selected=move || value.get().unit == *unit value=*unit>
+                                    {*label}
+                                </option>
+                            }
+                        })
+                        .collect_view()}
                 </select>
             </div>
         </div>
 
         {move || {
-            error
-                .get()
-                .map(|error| {
-                    view! { <p class="text-xs text-red-600 mt-2">{error}</p> }
-                })
+            match (error.get(), value.get().as_millis()) {
+                (Some(error), _) => {
+                    Some(view! { <p class="text-xs text-red-600 mt-2">{error}</p> })
+                }
+                (None, Some(millis))
+                    if DURATION_UNITS
+                        .iter()
+                        .find(|(_, _, factor)| millis >= *factor)
+                        .is_some_and(|(unit, _, _)| *unit != value.get().unit) => {
+                    Some(
+                        view! {
+                            <p class="text-xs text-gray-500 mt-2">
+                                {format!("= {}", format_duration(millis))}
+                            </p>
+                        },
+                    )
+                }
+                _ => None,
+            }
         }}
     }
 }
@@ -767,6 +811,102 @@ impl std::fmt::Display for Duration {
     }
 }
 
+impl Duration {
+    pub fn as_millis(&self) -> Option<u64> {
+        let factor = DURATION_UNITS
+            .iter()
+            .find(|(unit, _, _)| *unit == self.unit)?
+            .2;
+        self.value.parse::<u64>().ok()?.checked_mul(factor)
+    }
+}
+
+/// Parses a size such as `50`, `50GB` or `1.5 tb`, numbers without a unit
+/// are multiplied by `default_multiplier`.
+fn parse_size(input: &str, default_multiplier: u64) -> Option<u64> {
+    let input = input.trim().to_ascii_lowercase();
+    let (number, unit) = input.split_at(
+        input
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(input.len()),
+    );
+    let multiplier = match unit.trim() {
+        "" => default_multiplier,
+        "b" | "byte" | "bytes" => 1,
+        "k" | "kb" | "kib" => UNIT_KB,
+        "m" | "mb" | "mib" => UNIT_MB,
+        "g" | "gb" | "gib" => UNIT_GB,
+        "t" | "tb" | "tib" => UNIT_TB,
+        _ => return None,
+    };
+    let value = if number.contains('.') {
+        let value = number.parse::<f64>().ok()? * multiplier as f64;
+        (value.is_finite() && value < u64::MAX as f64).then_some(value.round() as u64)?
+    } else {
+        number.parse::<u64>().ok()?.checked_mul(multiplier)?
+    };
+
+    (value > 0).then_some(value)
+}
+
+/// Parses a duration such as `30`, `30m` or `2 days`, numbers without a unit
+/// use `default_unit`.
+fn parse_duration(input: &str, default_unit: &str) -> Option<Duration> {
+    let input = input.trim().to_ascii_lowercase();
+    let (number, unit) = input.split_at(
+        input
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(input.len()),
+    );
+    let unit = match unit.trim() {
+        "" => default_unit,
+        "ms" | "millis" | "milliseconds" => "ms",
+        "s" | "sec" | "secs" | "second" | "seconds" => "s",
+        "m" | "min" | "mins" | "minute" | "minutes" => "m",
+        "h" | "hr" | "hrs" | "hour" | "hours" => "h",
+        "d" | "day" | "days" => "d",
+        _ => return None,
+    };
+
+    match number.parse::<u64>() {
+        Ok(value) if value > 0 => Some(Duration {
+            value: value.to_string(),
+            unit: unit.to_string(),
+        }),
+        _ => None,
+    }
+}
+
+fn format_number(value: u64) -> String {
+    let digits = value.to_string();
+    let mut result = String::with_capacity(digits.len() + digits.len() / 3);
+    for (pos, ch) in digits.chars().enumerate() {
+        if pos > 0 && (digits.len() - pos).is_multiple_of(3) {
+            result.push(',');
+        }
+        result.push(ch);
+    }
+    result
+}
+
+fn format_duration(mut millis: u64) -> String {
+    let mut parts = Vec::new();
+    for (_, label, factor) in DURATION_UNITS {
+        let amount = millis / factor;
+        if amount > 0 {
+            millis %= factor;
+            parts.push(if *label == "ms" {
+                format!("{amount} ms")
+            } else if amount == 1 {
+                format!("1 {}", label.trim_end_matches('s'))
+            } else {
+                format!("{amount} {label}")
+            });
+        }
+    }
+    parts.join(", ")
+}
+
 #[derive(Default, PartialEq, Eq, Clone)]
 pub struct Rate {
     pub amount: String,
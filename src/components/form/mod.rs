@@ -14,10 +14,15 @@ pub mod stacked_input;
 use leptos::*;
 
 use crate::{
-    components::{icon::IconInfo, messages::alert::Alerts},
+    components::{
+        icon::{IconInfo, IconQuestionMarkCircle},
+        messages::alert::Alerts,
+    },
     core::form::FormData,
 };
 
+const DOCS_URL: &str = "https://stalw.art/docs/";
+
 #[derive(Debug, Clone, Copy)]
 pub struct FormElement {
     pub id: &'static str,
@@ -60,14 +65,16 @@ pub fn FormButtonBar(children: Children) -> impl IntoView {
 #[component]
 pub fn FormSection(
     #[prop(optional)] title: Option<String>,
+    #[prop(optional)] docs: Option<&'static str>,
     #[prop(attrs)] attrs: Vec<(&'static str, Attribute)>,
     #[prop(optional, into)] hide: MaybeSignal<bool>,
     children: Children,
 ) -> impl IntoView {
     let title = title.filter(|s| !s.is_empty()).map(|title| {
         view! {
-            <div class="sm:col-span-12">
+            <div class="sm:col-span-12 flex items-center gap-x-2">
                 <h2 class="text-lg font-semibold text-gray-800 dark:text-gray-200">{title}</h2>
+                {docs.filter(|s| !s.is_empty()).map(|docs| view! { <DocsLink path=docs/> })}
             </div>
         }
     });
@@ -91,6 +98,7 @@ pub fn FormSection(
 pub fn FormItem(
     #[prop(into)] label: MaybeSignal<String>,
     #[prop(optional)] tooltip: Option<&'static str>,
+    #[prop(optional)] docs: Option<&'static str>,
     #[prop(optional, into)] hide: MaybeSignal<bool>,
     #[prop(optional, into)] is_optional: MaybeSignal<bool>,
     children: Children,
@@ -136,6 +144,7 @@ pub fn FormItem(
                         </div>
                     }
                 })}
+            {docs.filter(|s| !s.is_empty()).map(|docs| view! { <DocsLink path=docs/> })}
 
             {move || {
                 if is_optional.get() {
@@ -158,6 +167,27 @@ pub fn FormItem(
     }
 }
 
+#[component]
+fn DocsLink(path: &'static str) -> impl IntoView {
+    let url = if path.starts_with("https://") {
+        path.to_string()
+    } else {
+        format!("{DOCS_URL}{}", path.trim_start_matches('/'))
+    };
+
+    view! {
+        <a
+            href=url
+            target="_blank"
+            rel="noopener noreferrer"
+            class="inline-block ms-1 text-gray-400 hover:text-blue-600 dark:text-gray-600 dark:hover:text-blue-500"
+            title="Open documentation"
+        >
+            <IconQuestionMarkCircle size=16 attr:stroke-width="1.5" attr:class="inline-block size-4"/>
+        </a>
+    }
+}
+
 impl FormElement {
     pub fn new(id: &'static str, data: RwSignal<FormData>) -> Self {
         FormElement { id, data }
@@ -7,7 +7,7 @@
 use leptos::*;
 
 use crate::{
-    components::icon::{IconPlus, IconXMark},
+    components::icon::{IconBars2, IconPlus, IconXMark},
     core::form::FormErrorType,
 };
 
@@ -50,6 +50,9 @@ pub fn StackedInput(
         })
     });
 
+    let dragging = create_rw_signal(None::<usize>);
+    let drop_target = create_rw_signal(None::<usize>);
+
     view! {
         <div class="space-y-3">
 
@@ -67,15 +70,70 @@ pub fn StackedInput(
                     let is_err = error.is_some();
                     let error = error.unwrap_or_default();
                     view! {
-                        <div class="space-y-3">
+                        <div
+                            class="space-y-3"
+                            class=(
+                                ["rounded-lg", "ring-2", "ring-blue-500"],
+                                move || {
+                                    drop_target.get() == Some(idx)
+                                        && dragging.get().is_some_and(|from| from != idx)
+                                },
+                            )
+
+                            class:opacity-50=move || dragging.get() == Some(idx)
+                            on:dragover=move |ev: ev::DragEvent| {
+                                if dragging.get_untracked().is_some() {
+                                    ev.prevent_default();
+                                    drop_target.set(Some(idx));
+                                }
+                            }
+
+                            on:dragleave=move |_| {
+                                if drop_target.get_untracked() == Some(idx) {
+                                    drop_target.set(None);
+                                }
+                            }
+
+                            on:drop=move |ev: ev::DragEvent| {
+                                ev.prevent_default();
+                                drop_target.set(None);
+                                if let Some(from) = dragging.try_update(Option::take).flatten() {
+                                    element
+                                        .data
+                                        .update(|data| {
+                                            data.array_move(element.id, from, idx);
+                                        });
+                                }
+                            }
+                        >
                             <div class="relative">
+                                <span
+                                    class="absolute top-0 start-0 p-2.5 cursor-move text-gray-400 dark:text-gray-500"
+                                    title="Drag to reorder"
+                                    draggable="true"
+                                    on:dragstart=move |ev: ev::DragEvent| {
+                                        if let Some(transfer) = ev.data_transfer() {
+                                            transfer.set_effect_allowed("move");
+                                            let _ = transfer.set_data("text/plain", &idx.to_string());
+                                        }
+                                        dragging.set(Some(idx));
+                                    }
+
+                                    on:dragend=move |_| {
+                                        dragging.set(None);
+                                        drop_target.set(None);
+                                    }
+                                >
+
+                                    <IconBars2/>
+                                </span>
                                 <input
                                     type="text"
                                     class=move || {
                                         if !is_err {
-                                            "py-2 px-3 pe-11 block w-full border-gray-200 shadow-sm text-sm rounded-lg focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                                            "py-2 px-3 ps-10 pe-20 block w-full border-gray-200 shadow-sm text-sm rounded-lg focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
                                         } else {
-                                            "py-2 px-3 pe-11 block w-full border-red-500 shadow-sm text-sm rounded-lg focus:border-red-500 focus:ring-red-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                                            "py-2 px-3 ps-10 pe-20 block w-full border-red-500 shadow-sm text-sm rounded-lg focus:border-red-500 focus:ring-red-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
                                         }
                                     }
 
@@ -90,6 +148,23 @@ pub fn StackedInput(
                                     }
                                 />
 
+                                <button
+                                    type="button"
+                                    class="absolute top-0 end-9 p-2.5 dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                                    title="Insert below"
+                                    on:click=move |_| {
+                                        element
+                                            .data
+                                            .update(|data| {
+                                                data.array_insert(element.id, idx + 1, "");
+                                            });
+                                    }
+                                >
+
+                                    <IconPlus/>
+
+                                </button>
+
                                 <button
                                     type="button"
                                     class="absolute top-0 end-0 p-2.5 rounded-e-md dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
@@ -0,0 +1,165 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::*;
+use leptos_router::{use_location, use_navigate};
+use web_sys::wasm_bindgen::{closure::Closure, JsCast, JsValue};
+
+use crate::{
+    components::{
+        badge::Badge,
+        messages::modal::{use_modals, Modal},
+        Color,
+    },
+    core::unsaved::use_unsaved_changes,
+};
+
+#[component]
+pub fn UnsavedChangesGuard() -> impl IntoView {
+    let unsaved = use_unsaved_changes();
+    let modal = use_modals();
+    let navigate = use_navigate();
+    let location = use_location();
+
+    let confirm_discard = move |href: String| {
+        let navigate = navigate.clone();
+        modal.set(
+            Modal::with_title("Unsaved changes")
+                .with_message(concat!(
+                    "You have unsaved changes that will be lost if you leave ",
+                    "this page. Do you want to discard them?"
+                ))
+                .with_button("Discard changes")
+                .with_dangerous_callback(move || {
+                    unsaved.clear();
+                    navigate(&href, Default::default());
+                }),
+        );
+    };
+
+    // Intercept internal links before the router handles them
+    let confirm = confirm_discard.clone();
+    let on_click =
+        Closure::<dyn FnMut(web_sys::MouseEvent)>::new(move |ev: web_sys::MouseEvent| {
+            if !unsaved.is_dirty_untracked()
+                || ev.default_prevented()
+                || ev.button() != 0
+                || ev.meta_key()
+                || ev.ctrl_key()
+                || ev.shift_key()
+                || ev.alt_key()
+            {
+                return;
+            }
+            let Some(anchor) = ev
+                .target()
+                .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+                .and_then(|el| el.closest("a[href]").ok().flatten())
+            else {
+                return;
+            };
+            if anchor.get_attribute("target").as_deref() == Some("_blank")
+                || anchor.has_attribute("download")
+            {
+                return;
+            }
+            let Some(href) = anchor
+                .get_attribute("href")
+                .filter(|href| href.starts_with('/') && !href.starts_with("//"))
+            else {
+                return;
+            };
+
+            ev.prevent_default();
+            ev.stop_propagation();
+            confirm(href);
+        });
+    let _ = document().add_event_listener_with_callback_and_bool(
+        "click",
+        on_click.as_ref().unchecked_ref(),
+        true,
+    );
+
+    // Back and forward buttons. The browser has already moved to the new
+    // entry, so the page is put back before asking. Capturing listeners on
+    // the window run before the one registered by the router.
+    let confirm = confirm_discard.clone();
+    let on_popstate = Closure::<dyn FnMut(web_sys::Event)>::new(move |ev: web_sys::Event| {
+        if !unsaved.is_dirty_untracked() {
+            return;
+        }
+        let current = untrack(|| {
+            let search = location.search.get();
+            format!(
+                "{}{}{search}{}",
+                location.pathname.get(),
+                if search.is_empty() { "" } else { "?" },
+                location.hash.get()
+            )
+        });
+        let url = window().location();
+        let href = format!(
+            "{}{}{}",
+            url.pathname().unwrap_or_default(),
+            url.search().unwrap_or_default(),
+            url.hash().unwrap_or_default()
+        );
+        if href == current {
+            return;
+        }
+
+        ev.stop_immediate_propagation();
+        if let Ok(history) = window().history() {
+            let _ = history.push_state_with_url(&JsValue::NULL, "", Some(&current));
+        }
+        confirm(href);
+    });
+    let _ = window().add_event_listener_with_callback_and_bool(
+        "popstate",
+        on_popstate.as_ref().unchecked_ref(),
+        true,
+    );
+
+    // Programmatic navigation requested through `UnsavedChanges::navigate`
+    create_effect(move |_| {
+        if unsaved.pending_navigation().is_some() {
+            if let Some(href) = unsaved.take_pending_navigation() {
+                confirm_discard(href);
+            }
+        }
+    });
+
+    // Browser reloads and closed tabs
+    let before_unload = window_event_listener_untyped("beforeunload", move |ev| {
+        if unsaved.is_dirty_untracked() {
+            ev.prevent_default();
+        }
+    });
+
+    on_cleanup(move || {
+        let _ = document().remove_event_listener_with_callback_and_bool(
+            "click",
+            on_click.as_ref().unchecked_ref(),
+            true,
+        );
+        let _ = window().remove_event_listener_with_callback_and_bool(
+            "popstate",
+            on_popstate.as_ref().unchecked_ref(),
+            true,
+        );
+        before_unload.remove();
+    });
+
+    view! {
+        <Show when=move || unsaved.is_dirty()>
+            <div class="fixed bottom-4 start-4 lg:start-72 z-40">
+                <Badge color=Color::Yellow large=true>
+                    Unsaved changes
+                </Badge>
+            </div>
+        </Show>
+    }
+}
@@ -9,20 +9,45 @@ use leptos::*;
 use leptos_router::use_navigate;
 
 use crate::{
-    components::icon::{
-        IconAdjustmentsHorizontal, IconHeart, IconPower, IconServer, IconUserCircle,
+    components::{
+        icon::{
+            IconAdjustmentsHorizontal, IconHeart, IconPower, IconServer, IconUserCircle, IconWrench,
+        },
+        layout::search::GlobalSearch,
     },
     core::{
+        http::HttpRequest,
+        maintenance::{use_maintenance, MaintenanceMode},
         oauth::{use_authorization, AuthToken},
-        url::UrlBuilder,
     },
     pages::config::edit::DEFAULT_SETTINGS_URL,
     STATE_STORAGE_KEY, VERSION_NAME,
 };
-use web_sys::wasm_bindgen::JsCast;
 
 #[component]
 pub fn Header(is_admin: MaybeSignal<bool>) -> impl IntoView {
+    let auth = use_authorization();
+    let maintenance = use_maintenance();
+    let _ = create_resource(
+        move || is_admin.get(),
+        move |is_admin| {
+            let auth = auth.get_untracked();
+
+            async move {
+                if is_admin {
+                    // Servers without maintenance mode support leave the state unknown
+                    if let Ok(mode) = HttpRequest::get("/api/maintenance")
+                        .with_authorization(&auth)
+                        .send::<MaintenanceMode>()
+                        .await
+                    {
+                        maintenance.set(Some(mode));
+                    }
+                }
+            }
+        },
+    );
+
     view! {
         <header class="sticky top-0 inset-x-0 flex flex-wrap sm:justify-start sm:flex-nowrap z-[48] w-full bg-white border-b text-sm py-2.5 sm:py-4 lg:ps-64 dark:bg-gray-800 dark:border-gray-700">
             <nav class="flex basis-full items-center w-full mx-auto px-4 sm:px-6 md:px-8">
@@ -58,53 +83,34 @@ pub fn Header(is_admin: MaybeSignal<bool>) -> impl IntoView {
                         </div>
 
                         <div class="hidden sm:block">
-                            <div class="relative">
-                                <div class="absolute inset-y-0 start-0 flex items-center pointer-events-none z-20 ps-4">
-                                    <svg
-                                        class="flex-shrink-0 size-4 text-gray-400"
-                                        xmlns="http://www.w3.org/2000/svg"
-                                        width="24"
-                                        height="24"
-                                        viewBox="0 0 24 24"
-                                        fill="none"
-                                        stroke="currentColor"
-                                        stroke-width="2"
-                                        stroke-linecap="round"
-                                        stroke-linejoin="round"
-                                    >
-                                        <circle cx="11" cy="11" r="8"></circle>
-                                        <path d="m21 21-4.3-4.3"></path>
-                                    </svg>
-                                </div>
-                                <input
-                                    type="text"
-                                    class="py-2 px-4 ps-11 block w-full border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
-                                    placeholder="Search settings"
-                                    on:keyup=move |ev| {
-                                        let key_code = ev
-                                            .unchecked_ref::<web_sys::KeyboardEvent>()
-                                            .key_code();
-                                        if key_code == 13 {
-                                            let filter = event_target_value(&ev);
-                                            let query = filter.trim();
-                                            if !query.is_empty() {
-                                                use_navigate()(
-                                                    &UrlBuilder::new("/settings/search")
-                                                        .with_parameter("query", query)
-                                                        .finish(),
-                                                    Default::default(),
-                                                );
-                                            }
-                                        }
-                                    }
-                                />
-
-                            </div>
+                            <GlobalSearch/>
                         </div>
 
                     </Show>
 
                     <div class="flex flex-row items-center justify-end gap-2">
+                        <Show when=move || {
+                            is_admin.get()
+                                && maintenance.with(|mode| mode.as_ref().is_some_and(|mode| mode.enabled))
+                        }>
+                            <a
+                                href="/manage/maintenance"
+                                class="py-1 px-2.5 inline-flex items-center gap-x-1.5 text-xs font-semibold rounded-full bg-yellow-100 text-yellow-800 hover:bg-yellow-200 dark:bg-yellow-800/30 dark:text-yellow-500"
+                                title=move || {
+                                    maintenance
+                                        .with(|mode| {
+                                            if mode.as_ref().is_some_and(|mode| mode.reject_submissions) {
+                                                "Outbound delivery is paused and new submissions are rejected"
+                                            } else {
+                                                "Outbound delivery is paused"
+                                            }
+                                        })
+                                }
+                            >
+                                <IconWrench attr:class="flex-shrink-0 size-3"/>
+                                Maintenance mode
+                            </a>
+                        </Show>
                         <a
                             href="/manage/directory/accounts"
                             class="w-[2.375rem] h-[2.375rem] inline-flex justify-center items-center gap-x-2 text-sm font-semibold rounded-full border border-transparent text-gray-800 hover:bg-gray-100 disabled:opacity-50 disabled:pointer-events-none dark:text-white dark:hover:bg-gray-700 dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
@@ -125,7 +131,7 @@ pub fn Header(is_admin: MaybeSignal<bool>) -> impl IntoView {
                         </a>
                         <a
                             class="w-[2.375rem] h-[2.375rem] inline-flex justify-center items-center gap-x-2 text-sm font-semibold rounded-full border border-transparent text-gray-800 hover:bg-gray-100 disabled:opacity-50 disabled:pointer-events-none dark:text-white dark:hover:bg-gray-700 dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
-                            href="/account/crypto"
+                            href="/account/overview"
                             title="Account"
                         >
                             <IconUserCircle/>
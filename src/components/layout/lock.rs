@@ -0,0 +1,240 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::{sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use gloo_storage::{SessionStorage, Storage};
+use leptos::*;
+use leptos_meta::Body;
+use leptos_router::use_navigate;
+
+use crate::{
+    components::{
+        form::{
+            input::{InputPassword, InputText},
+            FormElement,
+        },
+        icon::IconLockClosed,
+        messages::alert::{use_alerts, Alert, Alerts},
+    },
+    core::{
+        idle::use_idle_lock,
+        oauth::{
+            oauth_refresh_token, oauth_user_authentication, use_authorization, AuthToken,
+            AuthenticationResult,
+        },
+        schema::Schemas,
+    },
+    STATE_STORAGE_KEY,
+};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(15);
+const ACTIVITY_EVENTS: [&str; 5] = [
+    "pointerdown",
+    "pointermove",
+    "keydown",
+    "wheel",
+    "touchstart",
+];
+
+/// Locks the session once the browser has been idle for longer than the
+/// configured timeout. Wall clock time is used so that time spent with the
+/// computer asleep counts as inactivity.
+#[component]
+pub fn IdleMonitor() -> impl IntoView {
+    let auth = use_authorization();
+    let settings = use_idle_lock();
+    let last_activity = store_value::<DateTime<Utc>>(Utc::now());
+
+    let listeners = ACTIVITY_EVENTS.map(|event| {
+        window_event_listener_untyped(event, move |_| {
+            last_activity.set_value(Utc::now());
+        })
+    });
+    let check = move || {
+        let Some(timeout) = settings.with_untracked(|settings| settings.timeout()) else {
+            return;
+        };
+        let is_idle = last_activity.with_value(|last_activity| {
+            (Utc::now() - *last_activity)
+                .to_std()
+                .is_ok_and(|idle| idle >= timeout)
+        });
+        if is_idle && auth.with_untracked(|auth| auth.is_logged_in() && !auth.is_locked) {
+            auth.update(|auth| {
+                auth.lock();
+                if let Err(err) = SessionStorage::set(STATE_STORAGE_KEY, auth.clone()) {
+                    log::error!("Failed to save state to session storage: {}", err);
+                }
+            });
+        }
+    };
+    let interval = set_interval_with_handle(check, CHECK_INTERVAL).ok();
+
+    on_cleanup(move || {
+        for listener in listeners {
+            listener.remove();
+        }
+        if let Some(interval) = interval {
+            interval.clear();
+        }
+    });
+}
+
+/// Shown instead of the web admin while the session is locked. The password,
+/// and the TOTP code when enabled, are verified before the refresh token is
+/// used to obtain a new access token.
+#[component]
+pub fn LockScreen() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let show_totp = create_rw_signal(false);
+    let username = auth.with_untracked(|auth| auth.username.to_string());
+    let data = expect_context::<Arc<Schemas>>()
+        .build_form("login")
+        .with_value("login", username.clone())
+        .into_signal();
+
+    let unlock_action = create_action(move |password: &String| {
+        let password = password.clone();
+        let (base_url, username, refresh_token) = auth.with_untracked(|auth| {
+            (
+                auth.base_url.clone(),
+                auth.username.clone(),
+                auth.refresh_token.clone(),
+            )
+        });
+
+        async move {
+            // The authorization code is discarded, verifying the credentials
+            // is enough to resume the existing session
+            match oauth_user_authentication(&base_url, &username, &password, "webadmin", None).await
+            {
+                AuthenticationResult::Success(_) => {}
+                AuthenticationResult::TotpRequired => {
+                    show_totp.set(true);
+                    return;
+                }
+                AuthenticationResult::Error(err) => {
+                    alert.set(err);
+                    return;
+                }
+            }
+
+            let Some(grant) = oauth_refresh_token(&base_url, &refresh_token).await else {
+                alert.set(
+                    Alert::warning("Your session has expired")
+                        .with_details("Sign out and sign in again to continue.")
+                        .without_timeout(),
+                );
+                return;
+            };
+            let refresh_token = grant.refresh_token.unwrap_or_default();
+            auth.update(|auth| {
+                auth.access_token = grant.access_token.into();
+                auth.refresh_token = refresh_token.clone().into();
+                auth.is_valid = true;
+                auth.is_locked = false;
+
+                if let Err(err) = SessionStorage::set(STATE_STORAGE_KEY, auth.clone()) {
+                    log::error!("Failed to save state to session storage: {}", err);
+                }
+            });
+
+            // Set timer to refresh token
+            if grant.expires_in > 0 && !refresh_token.is_empty() {
+                set_timeout(
+                    move || {
+                        auth.update(|auth| {
+                            auth.is_valid = false;
+                        });
+                    },
+                    Duration::from_secs(grant.expires_in),
+                );
+            }
+        }
+    });
+
+    view! {
+        <Body class="dark:bg-slate-900 bg-gray-100 flex h-full items-center py-16"/>
+        <main class="w-full max-w-md mx-auto p-6">
+            <div class="mt-7 bg-white border border-gray-200 rounded-xl shadow-sm dark:bg-gray-800 dark:border-gray-700">
+                <div class="p-4 sm:p-7">
+                    <div class="text-center">
+                        <IconLockClosed attr:class="mx-auto size-10 text-gray-400"/>
+                        <h1 class="mt-4 block text-2xl font-bold text-gray-800 dark:text-white">
+                            Session locked
+                        </h1>
+                        <p class="mt-2 text-sm text-gray-600 dark:text-gray-400">
+                            {format!(
+                                "The session of {username} was locked due to inactivity, enter your password to continue.",
+                            )}
+
+                        </p>
+                    </div>
+
+                    <div class="mt-5">
+                        <Alerts/>
+                        <form on:submit=|ev| ev.prevent_default()>
+                            <div class="grid gap-y-4">
+                                <Show when=move || !show_totp.get()>
+                                    <div>
+                                        <label class="block text-sm mb-2 dark:text-white">
+                                            Password
+                                        </label>
+                                        <InputPassword element=FormElement::new("password", data)/>
+                                    </div>
+                                </Show>
+                                <Show when=move || show_totp.get()>
+                                    <div>
+                                        <label class="block text-sm mb-2 dark:text-white">
+                                            TOTP Token
+                                        </label>
+                                        <InputText element=FormElement::new("totp-code", data)/>
+                                    </div>
+                                </Show>
+
+                                <button
+                                    type="submit"
+                                    class="w-full py-3 px-4 inline-flex justify-center items-center gap-x-2 text-sm font-semibold rounded-lg border border-transparent bg-blue-600 text-white hover:bg-blue-700 disabled:opacity-50 disabled:pointer-events-none dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                                    disabled=move || unlock_action.pending().get()
+                                    on:click=move |_| {
+                                        data.update(|data| {
+                                            if data.validate_form() {
+                                                let password = match (
+                                                    data.value::<String>("password").unwrap_or_default(),
+                                                    data.value::<String>("totp-code"),
+                                                ) {
+                                                    (password, Some(totp)) => format!("{}${}", password, totp),
+                                                    (password, None) => password,
+                                                };
+                                                unlock_action.dispatch(password);
+                                            }
+                                        });
+                                    }
+                                >
+                                    Unlock
+                                </button>
+                                <button
+                                    type="button"
+                                    class="w-full py-3 px-4 inline-flex justify-center items-center gap-x-2 text-sm font-semibold rounded-lg border border-gray-200 bg-white text-gray-800 hover:bg-gray-50 dark:bg-slate-900 dark:border-gray-700 dark:text-white dark:hover:bg-gray-800"
+                                    on:click=move |_| {
+                                        SessionStorage::delete(STATE_STORAGE_KEY);
+                                        auth.set(AuthToken::default());
+                                        use_navigate()("/login", Default::default());
+                                    }
+                                >
+                                    Sign out
+                                </button>
+                            </div>
+                        </form>
+                    </div>
+                </div>
+            </div>
+        </main>
+    }
+}
@@ -4,8 +4,14 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
+pub mod guard;
 pub mod header;
+pub mod lock;
+pub mod notifications;
+pub mod request_log;
+pub mod search;
 pub mod sidebar;
+pub mod tasks;
 pub mod toggle;
 
 use std::hash::{DefaultHasher, Hash, Hasher};
@@ -16,10 +22,26 @@ use leptos_router::Outlet;
 
 use crate::{
     components::{
-        layout::{header::Header, sidebar::SideBar, toggle::ToggleNavigation},
-        messages::modal::Modal,
+        layout::{
+            guard::UnsavedChangesGuard,
+            header::Header,
+            lock::{IdleMonitor, LockScreen},
+            notifications::NotificationMonitor,
+            request_log::RequestLogOverlay,
+            sidebar::SideBar,
+            tasks::TaskDrawer,
+            toggle::ToggleNavigation,
+        },
+        messages::{
+            announcement::AnnouncementBanner, connection::ConnectionBanner,
+            impersonation::ImpersonationBanner, modal::Modal,
+        },
+    },
+    core::{
+        oauth::use_authorization,
+        permissions::Capability,
+        schema::{Schema, SchemaType},
     },
-    core::schema::{Schema, SchemaType},
 };
 
 pub struct LayoutBuilder {
@@ -40,6 +62,7 @@ pub struct MenuItem {
     pub route: Option<String>,
     pub icon: Option<View>,
     pub children: Vec<MenuItem>,
+    pub capability: Option<Capability>,
 }
 
 #[component]
@@ -47,18 +70,31 @@ pub fn Layout(
     menu_items: Vec<MenuItem>,
     #[prop(into)] is_admin: MaybeSignal<bool>,
 ) -> impl IntoView {
-    let menu_items_toggle = menu_items.clone();
     let show_sidebar = create_rw_signal(false);
+    let auth = use_authorization();
+    let is_locked = create_memo(move |_| auth.with(|auth| auth.is_locked));
 
+    // Nothing behind the lock screen stays mounted, which also stops any
+    // polling while there is no access token
     view! {
-        <Body class="bg-gray-50 dark:bg-slate-900"/>
-        <Modal/>
-        <Header is_admin/>
-        <ToggleNavigation menu_items show_sidebar/>
-        <SideBar menu_items=menu_items_toggle show_sidebar/>
-        <div class="w-full pt-10 px-4 sm:px-6 md:px-8 lg:ps-72">
-            <Outlet/>
-        </div>
+        <IdleMonitor/>
+        <Show when=move || !is_locked.get() fallback=LockScreen>
+            <Body class="bg-gray-50 dark:bg-slate-900"/>
+            <Modal/>
+            <ConnectionBanner/>
+            <ImpersonationBanner/>
+            <AnnouncementBanner/>
+            <Header is_admin/>
+            <ToggleNavigation menu_items=menu_items.clone() show_sidebar/>
+            <SideBar menu_items=menu_items.clone() show_sidebar/>
+            <div class="w-full pt-10 px-4 sm:px-6 md:px-8 lg:ps-72">
+                <Outlet/>
+            </div>
+            <TaskDrawer/>
+            <NotificationMonitor/>
+            <RequestLogOverlay/>
+            <UnsavedChangesGuard/>
+        </Show>
     }
 }
 
@@ -114,8 +150,19 @@ impl LayoutBuilder {
         self
     }
 
+    /// Hides the item from accounts not allowed to perform the action.
+    pub fn capability(mut self, capability: Capability) -> Self {
+        self.chain.last_mut().unwrap().capability = Some(capability);
+        self
+    }
+
     pub fn insert(mut self) -> Self {
         let menu_item = self.chain.pop().unwrap();
+        if menu_item.capability.is_some_and(|capability| {
+            !use_authorization().with_untracked(|auth| auth.permissions.can(capability))
+        }) {
+            return self;
+        }
         if let Some(parent_menu_item) = self.chain.last_mut() {
             parent_menu_item.children.push(menu_item);
         } else {
@@ -0,0 +1,154 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::Duration;
+
+use ahash::AHashSet;
+use leptos::*;
+
+use crate::{
+    core::{
+        connection::use_connection,
+        notifications::{notify, use_notifications},
+        oauth::use_authorization,
+    },
+    pages::{
+        config::certs::fetch_certificate_problems,
+        manage::dashboard::{metrics_request, MetricsHistory},
+    },
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+/// Certificates are checked once every this many polls.
+const CERTIFICATE_POLLS: u32 = 60;
+const CERTIFICATE_WARNING_DAYS: i64 = 7;
+
+const DELIVERY_FAILED: &str = "delivery.failed";
+/// Failures in the latest interval needed to consider it a spike, and how
+/// many times the average of the previous intervals they must exceed.
+const SPIKE_MIN_FAILURES: f64 = 10.0;
+const SPIKE_FACTOR: f64 = 3.0;
+
+/// Watches for critical events while an administrator has desktop
+/// notifications enabled, polling delivery failures and certificates and
+/// following the connection to the server.
+#[component]
+pub fn NotificationMonitor() -> impl IntoView {
+    let auth = use_authorization();
+    let settings = use_notifications();
+    let connection = use_connection();
+    let notified = store_value(AHashSet::<String>::new());
+    let polls = store_value(0u32);
+    let is_active = move || {
+        settings.with_untracked(|settings| settings.enabled)
+            && auth.with_untracked(|auth| auth.is_admin())
+    };
+
+    // A server that stops answering and comes back has usually restarted
+    create_effect(move |was_lost: Option<bool>| {
+        let is_lost = connection.with(|connection| connection.is_lost());
+        if was_lost.is_some_and(|was_lost| was_lost != is_lost)
+            && is_active()
+            && settings.with_untracked(|settings| settings.server_status)
+        {
+            if is_lost {
+                notify(
+                    "server-status",
+                    "Server unreachable",
+                    "The connection to the mail server has been lost.",
+                );
+            } else {
+                notify(
+                    "server-status",
+                    "Server reachable again",
+                    "The mail server is answering again, it may have been restarted.",
+                );
+            }
+        }
+        is_lost
+    });
+
+    let poll = move || {
+        if !is_active() {
+            return;
+        }
+        let auth = auth.get_untracked();
+        let settings = settings.get_untracked();
+        let check_certificates = polls.with_value(|polls| polls % CERTIFICATE_POLLS == 0);
+        polls.update_value(|polls| *polls = polls.wrapping_add(1));
+
+        spawn_local(async move {
+            let mut events = Vec::new();
+
+            if settings.delivery_failures {
+                if let Ok(history) = metrics_request(&auth, [DELIVERY_FAILED], 1)
+                    .send::<MetricsHistory>()
+                    .await
+                {
+                    if let Some((tag, failures)) = failure_spike(&history) {
+                        events.push((
+                            tag,
+                            "Delivery failures".to_string(),
+                            format!("{failures} messages failed to be delivered recently."),
+                        ));
+                    }
+                }
+            }
+
+            if settings.certificates && check_certificates {
+                if let Ok(problems) =
+                    fetch_certificate_problems(&auth, CERTIFICATE_WARNING_DAYS).await
+                {
+                    for (id, problem) in problems {
+                        events.push((
+                            format!("certificate-{id}"),
+                            "Certificate problem".to_string(),
+                            problem,
+                        ));
+                    }
+                }
+            }
+
+            for (tag, title, body) in events {
+                // Notify each event once per session
+                if notified.try_update_value(|notified| notified.insert(tag.clone())) == Some(true)
+                {
+                    notify(&tag, &title, &body);
+                }
+            }
+        });
+    };
+    if let Ok(handle) = set_interval_with_handle(poll, POLL_INTERVAL) {
+        on_cleanup(move || handle.clear());
+    }
+}
+
+/// Returns a tag identifying the latest interval and its number of failures
+/// when they spike above the previous intervals.
+fn failure_spike(history: &MetricsHistory) -> Option<(String, u64)> {
+    let values = &history
+        .metrics
+        .iter()
+        .find(|metric| metric.id == DELIVERY_FAILED)?
+        .values;
+    let (latest, previous) = values.split_last()?;
+    let average = if previous.is_empty() {
+        0.0
+    } else {
+        previous.iter().sum::<f64>() / previous.len() as f64
+    };
+
+    if *latest >= SPIKE_MIN_FAILURES && *latest > average * SPIKE_FACTOR {
+        let timestamp = history
+            .timestamps
+            .last()
+            .map(|timestamp| timestamp.timestamp())
+            .unwrap_or_default();
+        Some((format!("delivery-{timestamp}"), *latest as u64))
+    } else {
+        None
+    }
+}
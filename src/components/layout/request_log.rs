@@ -0,0 +1,163 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::*;
+
+use crate::{
+    components::{
+        icon::{IconCodeBracket, IconXMark},
+        messages::alert::{use_alerts, Alert},
+    },
+    core::{clipboard::copy_to_clipboard, request_log::use_request_log},
+};
+
+/// Requests slower than this are highlighted in the overlay.
+const SLOW_REQUEST_MS: f64 = 1000.0;
+
+#[component]
+pub fn RequestLogOverlay() -> impl IntoView {
+    let log = use_request_log();
+    let alert = use_alerts();
+    let is_open = create_rw_signal(false);
+    let total_errors = create_memo(move |_| {
+        log.with(|log| {
+            log.entries
+                .iter()
+                .filter(|entry| entry.error.is_some())
+                .count()
+        })
+    });
+
+    view! {
+        <Show when=move || log.with(|log| log.enabled)>
+            <div class="fixed bottom-4 start-4 z-40 w-[32rem] max-w-[calc(100vw-2rem)]">
+                <Show when=move || is_open.get()>
+                    <div class="mb-2 flex flex-col bg-white border shadow-sm rounded-xl dark:bg-slate-900 dark:border-gray-700">
+                        <div class="flex justify-between items-center gap-x-3 py-3 px-4 border-b dark:border-gray-700">
+                            <h3 class="grow text-sm font-semibold text-gray-800 dark:text-white">
+                                API requests
+                            </h3>
+                            <button
+                                type="button"
+                                class="text-xs text-blue-600 decoration-2 hover:underline font-medium"
+                                on:click=move |_| {
+                                    if copy_to_clipboard(&log.with_untracked(|log| log.to_text())) {
+                                        alert.set(Alert::success("Request log copied to clipboard"));
+                                    } else {
+                                        alert.set(Alert::error("Failed to copy to clipboard"));
+                                    }
+                                }
+                            >
+
+                                Copy
+                            </button>
+                            <button
+                                type="button"
+                                class="text-xs text-blue-600 decoration-2 hover:underline font-medium"
+                                on:click=move |_| log.update(|log| log.entries.clear())
+                            >
+                                Clear
+                            </button>
+                            <button
+                                type="button"
+                                class="text-gray-400 hover:text-gray-600"
+                                on:click=move |_| is_open.set(false)
+                            >
+                                <span class="sr-only">Close</span>
+                                <IconXMark attr:class="flex-shrink-0 size-3"/>
+                            </button>
+                        </div>
+                        <div class="max-h-96 overflow-y-auto">
+                            <table class="min-w-full divide-y divide-gray-200 dark:divide-gray-700 text-xs font-mono">
+                                <tbody class="divide-y divide-gray-200 dark:divide-gray-700">
+                                    {move || {
+                                        log.with(|log| {
+                                            log.entries
+                                                .iter()
+                                                .rev()
+                                                .map(|entry| {
+                                                    let status_class = if entry.error.is_some() {
+                                                        "text-red-600 dark:text-red-500"
+                                                    } else {
+                                                        "text-teal-600 dark:text-teal-500"
+                                                    };
+                                                    let duration_class = if entry.duration_ms >= SLOW_REQUEST_MS {
+                                                        "text-yellow-600 dark:text-yellow-500"
+                                                    } else {
+                                                        "text-gray-500"
+                                                    };
+                                                    view! {
+                                                        <tr title=entry.error.clone().unwrap_or_default()>
+                                                            <td class="py-1.5 ps-4 pe-2 text-gray-800 dark:text-gray-200">
+                                                                {entry.method.clone()}
+                                                            </td>
+                                                            <td class="py-1.5 px-2 break-all text-gray-600 dark:text-gray-400">
+                                                                {entry.path.clone()}
+                                                            </td>
+                                                            <td class=format!("py-1.5 px-2 {status_class}")>
+                                                                {entry
+                                                                    .status
+                                                                    .map(|status| status.to_string())
+                                                                    .unwrap_or_else(|| "ERR".to_string())}
+                                                            </td>
+                                                            <td class=format!(
+                                                                "py-1.5 ps-2 pe-4 text-end whitespace-nowrap {duration_class}",
+                                                            )>{format!("{:.0} ms", entry.duration_ms)}</td>
+                                                            {entry
+                                                                .curl
+                                                                .clone()
+                                                                .map(|curl| {
+                                                                    view! {
+                                                                        <td class="py-1.5 pe-4">
+                                                                            <button
+                                                                                type="button"
+                                                                                class="text-blue-600 decoration-2 hover:underline font-medium"
+                                                                                title="Copy as curl"
+                                                                                on:click=move |_| {
+                                                                                    if copy_to_clipboard(&curl) {
+                                                                                        alert.set(Alert::success("curl command copied to clipboard"));
+                                                                                    } else {
+                                                                                        alert.set(Alert::error("Failed to copy to clipboard"));
+                                                                                    }
+                                                                                }
+                                                                            >
+
+                                                                                curl
+                                                                            </button>
+                                                                        </td>
+                                                                    }
+                                                                })}
+                                                        </tr>
+                                                    }
+                                                })
+                                                .collect_view()
+                                        })
+                                    }}
+
+                                </tbody>
+                            </table>
+                        </div>
+                    </div>
+                </Show>
+                <button
+                    type="button"
+                    class="py-2 px-3 inline-flex justify-center items-center gap-x-2 text-sm font-medium rounded-lg border border-gray-200 bg-white text-gray-800 shadow-sm hover:bg-gray-50 dark:bg-slate-900 dark:border-gray-700 dark:text-white dark:hover:bg-gray-800"
+                    on:click=move |_| is_open.update(|is_open| *is_open = !*is_open)
+                >
+                    <IconCodeBracket/>
+                    {move || {
+                        let total = log.with(|log| log.entries.len());
+                        match total_errors.get() {
+                            0 => format!("API requests ({total})"),
+                            errors => format!("API requests ({total}, {errors} failed)"),
+                        }
+                    }}
+
+                </button>
+            </div>
+        </Show>
+    }
+}
@@ -0,0 +1,393 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::{sync::Arc, time::Duration};
+
+use leptos::{html::Div, leptos_dom::helpers::TimeoutHandle, *};
+use leptos_use::on_click_outside;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::{
+        http::{self, HttpRequest},
+        oauth::{use_authorization, AuthToken},
+        schema::Schemas,
+        unsaved::use_unsaved_changes,
+        url::UrlBuilder,
+    },
+    pages::{
+        config::search::{search_query_error, search_settings},
+        directory::PrincipalType,
+        queue::messages::Message,
+        List,
+    },
+};
+
+/// Maximum number of results shown per category.
+const MAX_RESULTS: usize = 5;
+
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+enum Category {
+    Accounts,
+    Groups,
+    Domains,
+    Messages,
+    Settings,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct SearchHit {
+    category: Category,
+    title: String,
+    subtitle: Option<String>,
+    url: String,
+}
+
+/// Search box that looks up accounts, groups, domains, queued messages and
+/// settings at once, showing the results grouped by category. Results can be
+/// browsed with the arrow keys and opened with Enter; pressing Enter without
+/// a selection opens the full settings search.
+#[component]
+pub fn GlobalSearch() -> impl IntoView {
+    let auth = use_authorization();
+    let schemas = expect_context::<Arc<Schemas>>();
+    let input = create_rw_signal(String::new());
+    let query = create_rw_signal(String::new());
+    let is_open = create_rw_signal(false);
+    let selected = create_rw_signal(None::<usize>);
+    let timer = store_value(None::<TimeoutHandle>);
+    let target = create_node_ref::<Div>();
+
+    let _ = on_click_outside(target, move |_| is_open.set(false));
+
+    let remote = [
+        Category::Accounts,
+        Category::Groups,
+        Category::Domains,
+        Category::Messages,
+    ]
+    .map(|category| {
+        create_resource(
+            move || query.get(),
+            move |query| {
+                let auth = auth.get_untracked();
+
+                async move {
+                    if query.is_empty() {
+                        Ok(Vec::new())
+                    } else {
+                        category.search(&auth, &query).await
+                    }
+                }
+            },
+        )
+    });
+
+    let hits = create_memo(move |_| {
+        let mut hits = remote
+            .iter()
+            .flat_map(|resource| match resource.get() {
+                Some(Ok(hits)) => hits,
+                Some(Err(err)) => {
+                    log::debug!("Global search failed: {err:?}");
+                    Vec::new()
+                }
+                None => Vec::new(),
+            })
+            .collect::<Vec<_>>();
+        let query = query.get();
+        if !query.is_empty() {
+            hits.extend(
+                search_settings(&schemas, &query)
+                    .into_iter()
+                    .take(MAX_RESULTS)
+                    .map(|(title, matches, url)| SearchHit {
+                        category: Category::Settings,
+                        subtitle: (matches != title).then_some(matches),
+                        title,
+                        url,
+                    }),
+            );
+        }
+        hits
+    });
+    let is_loading = Signal::derive(move || remote.iter().any(|r| r.loading().get()));
+
+    let unsaved = use_unsaved_changes();
+    let open = move |url: &str| {
+        is_open.set(false);
+        selected.set(None);
+        input.set(String::new());
+        query.set(String::new());
+        unsaved.navigate(url);
+    };
+
+    view! {
+        <div class="relative" node_ref=target>
+            <div class="absolute inset-y-0 start-0 flex items-center pointer-events-none z-20 ps-4">
+                <svg
+                    class="flex-shrink-0 size-4 text-gray-400"
+                    xmlns="http://www.w3.org/2000/svg"
+                    width="24"
+                    height="24"
+                    viewBox="0 0 24 24"
+                    fill="none"
+                    stroke="currentColor"
+                    stroke-width="2"
+                    stroke-linecap="round"
+                    stroke-linejoin="round"
+                >
+                    <circle cx="11" cy="11" r="8"></circle>
+                    <path d="m21 21-4.3-4.3"></path>
+                </svg>
+            </div>
+            <input
+                type="text"
+                class="py-2 px-4 ps-11 block w-full sm:w-80 border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                placeholder="Search accounts, domains, messages, settings"
+                prop:value=input
+                on:focus=move |_| is_open.set(true)
+                on:input=move |ev| {
+                    let value = event_target_value(&ev);
+                    input.set(value.clone());
+                    is_open.set(true);
+                    selected.set(None);
+                    if let Some(handle) = timer.get_value() {
+                        handle.clear();
+                    }
+                    timer
+                        .set_value(
+                            set_timeout_with_handle(
+                                    move || query.set(value.trim().to_string()),
+                                    DEBOUNCE,
+                                )
+                                .ok(),
+                        );
+                }
+
+                on:keydown=move |ev| {
+                    let total = hits.with_untracked(|hits| hits.len());
+                    match ev.key().as_str() {
+                        "ArrowDown" if total > 0 => {
+                            ev.prevent_default();
+                            is_open.set(true);
+                            selected
+                                .update(|selected| {
+                                    *selected = Some(selected.map_or(0, |i| (i + 1) % total));
+                                });
+                        }
+                        "ArrowUp" if total > 0 => {
+                            ev.prevent_default();
+                            selected
+                                .update(|selected| {
+                                    *selected = Some(
+                                        selected.map_or(total - 1, |i| (i + total - 1) % total),
+                                    );
+                                });
+                        }
+                        "Enter" => {
+                            let hit = selected
+                                .get_untracked()
+                                .and_then(|i| hits.with_untracked(|hits| hits.get(i).cloned()));
+                            if let Some(hit) = hit {
+                                open(&hit.url);
+                            } else {
+                                let value = input.get_untracked();
+                                let value = value.trim();
+                                if !value.is_empty() {
+                                    open(
+                                        &UrlBuilder::new("/settings/search")
+                                            .with_parameter("query", value)
+                                            .finish(),
+                                    );
+                                }
+                            }
+                        }
+                        "Escape" => {
+                            is_open.set(false);
+                            selected.set(None);
+                        }
+                        _ => {}
+                    }
+                }
+            />
+
+            <Show when=move || is_open.get() && !query.with(|q| q.is_empty())>
+                <div class="absolute z-50 mt-2 w-full sm:w-[28rem] max-h-[70vh] overflow-y-auto bg-white border border-gray-200 rounded-xl shadow-lg p-2 dark:bg-slate-900 dark:border-gray-700">
+                    {move || {
+                        query
+                            .with(|query| search_query_error(query))
+                            .map(|error| {
+                                view! { <p class="px-3 py-2 text-xs text-red-600">{error}</p> }
+                            })
+                    }}
+                    {move || {
+                        let hits = hits.get();
+                        if hits.is_empty() {
+                            return view! {
+                                <p class="px-3 py-2 text-sm text-gray-500">
+                                    {if is_loading.get() { "Searching..." } else { "No results" }}
+                                </p>
+                            }
+                                .into_view();
+                        }
+                        [
+                            Category::Accounts,
+                            Category::Groups,
+                            Category::Domains,
+                            Category::Messages,
+                            Category::Settings,
+                        ]
+                            .into_iter()
+                            .filter_map(|category| {
+                                let items = hits
+                                    .iter()
+                                    .enumerate()
+                                    .filter(|(_, hit)| hit.category == category)
+                                    .map(|(idx, hit)| {
+                                        let url = hit.url.clone();
+                                        view! {
+                                            <li>
+                                                <a
+                                                    class="flex flex-col px-3 py-1.5 rounded-lg cursor-pointer hover:bg-gray-100 dark:hover:bg-gray-800"
+                                                    class=("bg-gray-100", move || selected.get() == Some(idx))
+                                                    class=(
+                                                        "dark:bg-gray-800",
+                                                        move || selected.get() == Some(idx),
+                                                    )
+                                                    on:mouseenter=move |_| selected.set(Some(idx))
+                                                    on:click=move |_| open(&url)
+                                                >
+                                                    <span class="text-sm text-gray-800 truncate dark:text-gray-200">
+                                                        {hit.title.clone()}
+                                                    </span>
+                                                    {hit
+                                                        .subtitle
+                                                        .clone()
+                                                        .map(|subtitle| {
+                                                            view! {
+                                                                <span class="text-xs text-gray-500 truncate">
+                                                                    {subtitle}
+                                                                </span>
+                                                            }
+                                                        })}
+
+                                                </a>
+                                            </li>
+                                        }
+                                    })
+                                    .collect::<Vec<_>>();
+                                (!items.is_empty())
+                                    .then(|| {
+                                        view! {
+                                            <div class="py-1">
+                                                <h3 class="px-3 py-1 text-xs font-semibold uppercase tracking-wide text-gray-500">
+                                                    {category.title()}
+                                                </h3>
+                                                <ul>{items}</ul>
+                                            </div>
+                                        }
+                                    })
+                            })
+                            .collect_view()
+                    }}
+
+                </div>
+            </Show>
+        </div>
+    }
+}
+
+impl Category {
+    fn title(&self) -> &'static str {
+        match self {
+            Category::Accounts => "Accounts",
+            Category::Groups => "Groups",
+            Category::Domains => "Domains",
+            Category::Messages => "Queued messages",
+            Category::Settings => "Settings",
+        }
+    }
+
+    async fn search(self, auth: &AuthToken, query: &str) -> http::Result<Vec<SearchHit>> {
+        match self {
+            Category::Accounts | Category::Groups => {
+                let typ = if self == Category::Accounts {
+                    PrincipalType::Individual
+                } else {
+                    PrincipalType::Group
+                };
+                HttpRequest::get("/api/principal")
+                    .with_authorization(auth)
+                    .with_parameter("type", typ.id())
+                    .with_parameter("filter", query)
+                    .with_parameter("limit", MAX_RESULTS.to_string())
+                    .send::<List<String>>()
+                    .await
+                    .map(|list| {
+                        list.items
+                            .into_iter()
+                            .map(|name| SearchHit {
+                                category: self,
+                                url: format!(
+                                    "/manage/directory/{}/{name}/edit",
+                                    typ.resource_name()
+                                ),
+                                title: name,
+                                subtitle: None,
+                            })
+                            .collect()
+                    })
+            }
+            Category::Domains => HttpRequest::get("/api/domain")
+                .with_authorization(auth)
+                .with_parameter("filter", query)
+                .with_parameter("limit", MAX_RESULTS.to_string())
+                .send::<List<String>>()
+                .await
+                .map(|list| {
+                    list.items
+                        .into_iter()
+                        .map(|name| SearchHit {
+                            category: self,
+                            url: format!("/manage/directory/domains/{name}/view"),
+                            title: name,
+                            subtitle: None,
+                        })
+                        .collect()
+                }),
+            Category::Messages => HttpRequest::get("/api/queue/messages")
+                .with_authorization(auth)
+                .with_parameter("text", query)
+                .with_parameter("limit", MAX_RESULTS.to_string())
+                .with_parameter("values", "1")
+                .send::<List<Message>>()
+                .await
+                .map(|list| {
+                    list.items
+                        .into_iter()
+                        .map(|message| SearchHit {
+                            category: self,
+                            title: message.return_path().to_string(),
+                            subtitle: Some(
+                                message
+                                    .domains
+                                    .iter()
+                                    .flat_map(|domain| domain.recipients.iter())
+                                    .map(|rcpt| rcpt.address.as_str())
+                                    .collect::<Vec<_>>()
+                                    .join(", "),
+                            ),
+                            url: format!("/manage/queue/message/{}", message.id),
+                        })
+                        .collect()
+                }),
+            Category::Settings => Ok(Vec::new()),
+        }
+    }
+}
@@ -0,0 +1,187 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::Duration;
+
+use chrono_humanize::HumanTime;
+use leptos::*;
+
+use crate::{
+    components::{
+        icon::{IconAlertTriangle, IconCheckCircle, IconClock, IconXMark},
+        messages::alert::Alert,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        tasks::{use_tasks, TaskState, TaskStatus},
+    },
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[component]
+pub fn TaskDrawer() -> impl IntoView {
+    let tasks = use_tasks();
+    let auth = use_authorization();
+    let is_open = create_rw_signal(false);
+    let total_running = create_memo(move |_| tasks.with(|tasks| tasks.running().count()));
+
+    // Keep tasks in session storage so they survive page reloads
+    create_effect(move |_| tasks.with(|tasks| tasks.persist()));
+
+    // Poll the status endpoint of each running task
+    let poll_tasks = move || {
+        let pending = tasks.with_untracked(|tasks| {
+            tasks
+                .running()
+                .filter_map(|task| task.status_url.clone().map(|url| (task.id, url)))
+                .collect::<Vec<_>>()
+        });
+        if pending.is_empty() {
+            return;
+        }
+        let auth = auth.get_untracked();
+
+        spawn_local(async move {
+            for (id, url) in pending {
+                let status = match HttpRequest::get(url)
+                    .with_authorization(&auth)
+                    .send::<TaskStatus>()
+                    .await
+                {
+                    Ok(status) => status,
+                    Err(http::Error::NotFound) => TaskStatus {
+                        state: TaskState::Failed,
+                        message: Some("Task not found".to_string()),
+                        ..Default::default()
+                    },
+                    Err(err @ http::Error::Server(_)) => TaskStatus {
+                        state: TaskState::Failed,
+                        message: Some(Alert::from(err).message),
+                        ..Default::default()
+                    },
+                    Err(_) => continue,
+                };
+                tasks.update(|tasks| tasks.update_status(id, status));
+            }
+        });
+    };
+    if let Ok(handle) = set_interval_with_handle(poll_tasks, POLL_INTERVAL) {
+        on_cleanup(move || handle.clear());
+    }
+
+    view! {
+        <Show when=move || tasks.with(|tasks| !tasks.items.is_empty())>
+            <div class="fixed bottom-4 end-4 z-40 w-80">
+                <Show when=move || is_open.get()>
+                    <div class="mb-2 max-h-96 overflow-y-auto flex flex-col bg-white border shadow-sm rounded-xl dark:bg-slate-900 dark:border-gray-700">
+                        <div class="flex justify-between items-center py-3 px-4 border-b dark:border-gray-700">
+                            <h3 class="text-sm font-semibold text-gray-800 dark:text-white">
+                                Tasks
+                            </h3>
+                            <button
+                                type="button"
+                                class="text-xs text-blue-600 decoration-2 hover:underline font-medium"
+                                on:click=move |_| tasks.update(|tasks| tasks.clear_finished())
+                            >
+                                Clear finished
+                            </button>
+                        </div>
+                        <For
+                            each=move || tasks.get().items
+                            key=|task| (task.id, task.status.clone())
+                            let:task
+                        >
+
+                            {
+                                let id = task.id;
+                                let progress = task.progress();
+                                let icon = match task.status.state {
+                                    TaskState::Running => {
+                                        view! {
+                                            <IconClock attr:class="flex-shrink-0 size-4 text-blue-600"/>
+                                        }
+                                            .into_view()
+                                    }
+                                    TaskState::Completed => {
+                                        view! {
+                                            <IconCheckCircle attr:class="flex-shrink-0 size-4 text-teal-500"/>
+                                        }
+                                            .into_view()
+                                    }
+                                    TaskState::Failed => {
+                                        view! {
+                                            <IconAlertTriangle attr:class="flex-shrink-0 size-4 text-red-500"/>
+                                        }
+                                            .into_view()
+                                    }
+                                };
+                                let details = task
+                                    .status
+                                    .message
+                                    .clone()
+                                    .unwrap_or_else(|| {
+                                        format!("Started {}", HumanTime::from(task.started))
+                                    });
+                                view! {
+                                    <div class="py-3 px-4 border-b last:border-b-0 dark:border-gray-700">
+                                        <div class="flex items-center gap-x-2">
+                                            {icon}
+                                            <span class="grow text-sm font-medium text-gray-800 dark:text-gray-200">
+                                                {task.title}
+                                            </span>
+                                            <button
+                                                type="button"
+                                                class="text-gray-400 hover:text-gray-600"
+                                                on:click=move |_| tasks.update(|tasks| tasks.remove(id))
+                                            >
+                                                <span class="sr-only">Dismiss</span>
+                                                <IconXMark attr:class="flex-shrink-0 size-3"/>
+                                            </button>
+                                        </div>
+                                        {progress
+                                            .map(|progress| {
+                                                view! {
+                                                    <div
+                                                        class="mt-2 flex w-full h-1.5 bg-gray-200 rounded-full overflow-hidden dark:bg-gray-700"
+                                                        role="progressbar"
+                                                        aria-valuenow=progress
+                                                        aria-valuemin="0"
+                                                        aria-valuemax="100"
+                                                    >
+                                                        <div
+                                                            class="flex flex-col justify-center rounded-full overflow-hidden bg-blue-600 transition duration-500"
+                                                            style=format!("width: {progress}%")
+                                                        ></div>
+                                                    </div>
+                                                }
+                                            })}
+                                        <p class="mt-1 text-xs text-gray-500">{details}</p>
+                                    </div>
+                                }
+                            }
+
+                        </For>
+                    </div>
+                </Show>
+                <button
+                    type="button"
+                    class="w-full py-2 px-3 inline-flex justify-center items-center gap-x-2 text-sm font-medium rounded-lg border border-gray-200 bg-white text-gray-800 shadow-sm hover:bg-gray-50 dark:bg-slate-900 dark:border-gray-700 dark:text-white dark:hover:bg-gray-800"
+                    on:click=move |_| is_open.update(|is_open| *is_open = !*is_open)
+                >
+                    <IconClock/>
+                    {move || match total_running.get() {
+                        0 => "All tasks finished".to_string(),
+                        1 => "1 task running".to_string(),
+                        n => format!("{n} tasks running"),
+                    }}
+
+                </button>
+            </div>
+        </Show>
+    }
+}
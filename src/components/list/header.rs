@@ -4,14 +4,32 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
-use std::collections::HashSet;
+use std::{cmp::Ordering, collections::HashSet};
 
 use leptos::*;
 
+/// Column a list is sorted by, given as an index into its headers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SortOrder {
+    pub column: usize,
+    pub ascending: bool,
+}
+
+impl SortOrder {
+    pub fn apply(&self, ordering: Ordering) -> Ordering {
+        if self.ascending {
+            ordering
+        } else {
+            ordering.reverse()
+        }
+    }
+}
+
 #[component]
 pub fn ColumnList(
     #[prop(into)] headers: MaybeSignal<Vec<String>>,
     #[prop(into, optional)] select_all: Option<Callback<(), Vec<String>>>,
+    #[prop(optional)] sort: Option<RwSignal<Option<SortOrder>>>,
     children: Children,
 ) -> impl IntoView {
     let headers_ = headers.clone();
@@ -62,12 +80,45 @@ pub fn ColumnList(
                         } else {
                             "px-6 py-3 text-start"
                         };
+                        let sort = sort.filter(|_| !header.is_empty());
                         view! {
                             <th scope="col" class=class>
-                                <div class="flex items-center gap-x-2">
+                                <div
+                                    class="flex items-center gap-x-2"
+                                    class:cursor-pointer=sort.is_some()
+                                    class:select-none=sort.is_some()
+                                    on:click=move |_| {
+                                        if let Some(sort) = sort {
+                                            sort.update(|sort| {
+                                                *sort = Some(match sort {
+                                                    Some(sort) if sort.column == idx => SortOrder {
+                                                        column: idx,
+                                                        ascending: !sort.ascending,
+                                                    },
+                                                    _ => SortOrder {
+                                                        column: idx,
+                                                        ascending: true,
+                                                    },
+                                                });
+                                            });
+                                        }
+                                    }
+                                >
                                     <span class="text-xs font-semibold uppercase tracking-wide text-gray-800 dark:text-gray-200">
                                         {header}
                                     </span>
+                                    {move || {
+                                        sort.and_then(|sort| sort.get())
+                                            .filter(|sort| sort.column == idx)
+                                            .map(|sort| {
+                                                view! {
+                                                    <span class="text-xs text-gray-500">
+                                                        {if sort.ascending { "▲" } else { "▼" }}
+                                                    </span>
+                                                }
+                                            })
+                                    }}
+
                                 </div>
                             </th>
                         }
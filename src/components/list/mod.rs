@@ -6,9 +6,11 @@
 
 pub mod header;
 pub mod pagination;
+pub mod query;
 pub mod row;
 pub mod table;
 pub mod toolbar;
+pub mod virtualized;
 
 use leptos::*;
 
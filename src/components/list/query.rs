@@ -0,0 +1,184 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use ahash::AHashMap;
+use leptos::*;
+use leptos_router::{use_location, use_navigate, use_route, NavigateOptions, ParamsMap};
+
+use super::header::SortOrder;
+
+/// Last query string of every list visited in this session, restored when
+/// a list is opened again without one.
+#[derive(Clone, Copy)]
+struct ListQueries {
+    saved: StoredValue<AHashMap<String, ParamsMap>>,
+    /// Path reached with the back and forward buttons, which must be shown
+    /// as it is found in the history.
+    traversed: StoredValue<Option<String>>,
+}
+
+/// Filter, sorting and pagination state of a list, kept in the query string
+/// so that it survives navigation and can be shared as a link.
+#[derive(Clone, Copy)]
+pub struct ListQuery {
+    path: Memo<String>,
+    query: Memo<ParamsMap>,
+    queries: ListQueries,
+}
+
+pub fn init_list_queries() {
+    let queries = ListQueries {
+        saved: store_value(AHashMap::new()),
+        traversed: store_value(None),
+    };
+    // Registered before the router, so it runs before the location changes
+    let _ = window_event_listener_untyped("popstate", move |_| {
+        queries
+            .traversed
+            .set_value(window().location().pathname().ok());
+    });
+    provide_context(queries);
+}
+
+pub fn use_list_query() -> ListQuery {
+    let location = use_location();
+    let route = use_route();
+    let list = ListQuery {
+        path: location.pathname,
+        query: location.query,
+        queries: expect_context::<ListQueries>(),
+    };
+
+    create_effect(move |_| {
+        let path = list.path.get();
+        let query = list.query.get();
+        // The location changes before the list is disposed when leaving it
+        if path.trim_end_matches('/') != route.path().trim_end_matches('/') {
+            return;
+        }
+        let is_traversal = list
+            .queries
+            .traversed
+            .try_update_value(|traversed| traversed.take())
+            .flatten()
+            .is_some_and(|traversed| traversed == path);
+        if query.0.is_empty() && !is_traversal {
+            let saved = list
+                .queries
+                .saved
+                .with_value(|saved| saved.get(&path).cloned())
+                .filter(|saved| !saved.0.is_empty());
+            if let Some(saved) = saved {
+                use_navigate()(
+                    &format!("{path}{}", saved.to_query_string()),
+                    NavigateOptions {
+                        replace: true,
+                        ..Default::default()
+                    },
+                );
+                return;
+            }
+        }
+        list.queries.saved.update_value(|saved| {
+            saved.insert(path, query);
+        });
+    });
+
+    list
+}
+
+impl ListQuery {
+    /// Current page, starting at 1.
+    pub fn page(self) -> Memo<u32> {
+        create_memo(move |_| {
+            self.query
+                .with(|q| q.get("page").and_then(|page| page.parse::<u32>().ok()))
+                .filter(|&page| page > 0)
+                .unwrap_or(1)
+        })
+    }
+
+    /// Value of a query parameter, with surrounding whitespace removed and
+    /// empty values treated as missing.
+    pub fn param(self, name: &'static str) -> Memo<Option<String>> {
+        create_memo(move |_| {
+            self.query.with(|q| {
+                q.get(name)
+                    .map(|value| value.trim())
+                    .filter(|value| !value.is_empty())
+                    .map(|value| value.to_string())
+            })
+        })
+    }
+
+    /// Sort order, encoded as the column number prefixed by `-` when
+    /// descending.
+    pub fn sort(self) -> Memo<Option<SortOrder>> {
+        create_memo(move |_| {
+            self.query.with(|q| {
+                let sort = q.get("sort")?;
+                let (column, ascending) = match sort.strip_prefix('-') {
+                    Some(column) => (column, false),
+                    None => (sort.as_str(), true),
+                };
+                Some(SortOrder {
+                    column: column.parse().ok()?,
+                    ascending,
+                })
+            })
+        })
+    }
+
+    /// Applies filter changes, going back to the first page.
+    pub fn set_filters<'x>(self, changes: impl IntoIterator<Item = (&'x str, Option<String>)>) {
+        self.navigate(changes.into_iter().chain(std::iter::once(("page", None))));
+    }
+
+    pub fn set_page(self, page: u32) {
+        self.navigate([(
+            "page",
+            Some(page)
+                .filter(|&page| page > 1)
+                .map(|page| page.to_string()),
+        )]);
+    }
+
+    pub fn set_sort(self, sort: Option<SortOrder>) {
+        self.navigate([(
+            "sort",
+            sort.map(|sort| {
+                if sort.ascending {
+                    sort.column.to_string()
+                } else {
+                    format!("-{}", sort.column)
+                }
+            }),
+        )]);
+    }
+
+    fn navigate<'x>(self, changes: impl IntoIterator<Item = (&'x str, Option<String>)>) {
+        let path = self.path.get_untracked();
+        let mut query = self.query.get_untracked();
+        for (name, value) in changes {
+            match value.filter(|value| !value.trim().is_empty()) {
+                Some(value) => {
+                    query.insert(name.to_string(), value);
+                }
+                None => {
+                    query.remove(name);
+                }
+            }
+        }
+        // Remember cleared filters too, so they are not restored
+        self.queries.saved.update_value(|saved| {
+            saved.insert(path.clone(), query.clone());
+        });
+        use_navigate()(
+            &format!("{path}{}", query.to_query_string()),
+            Default::default(),
+        );
+    }
+}
@@ -7,7 +7,10 @@
 use leptos::*;
 use web_sys::wasm_bindgen::JsCast;
 
-use crate::components::Color;
+use crate::{
+    components::Color,
+    core::permissions::{use_capability, Capability},
+};
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum ButtonIcon {
@@ -20,8 +23,13 @@ pub fn ToolbarButton(
     #[prop(into)] text: MaybeSignal<String>,
     color: Color,
     #[prop(into)] on_click: Callback<(), ()>,
+    /// Disables the button for accounts not allowed to perform the action.
+    #[prop(optional)]
+    capability: Option<Capability>,
     #[prop(optional)] children: Option<Children>,
 ) -> impl IntoView {
+    let capability = capability.map(use_capability);
+    let is_denied = move || capability.is_some_and(|capability| capability.is_denied());
     let class = match color {
         Color::Blue => concat!("py-2 px-3 inline-flex items-center gap-x-2 text-sm font-semibold rounded-lg ","border border-transparent bg-blue-600 text-white hover:bg-blue-700 disabled:opacity-50 ","disabled:pointer-events-none dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"),
         Color::Red => concat!("py-2 px-3 inline-flex items-center gap-x-2 text-sm font-medium rounded-lg ","border border-gray-200 bg-white text-red-500 shadow-sm hover:bg-gray-50 disabled:opacity-50 ","disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:hover:bg-gray-800 ","dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"),
@@ -30,7 +38,14 @@ pub fn ToolbarButton(
     };
 
     view! {
-        <button class=class on:click=move |_| on_click.call(())>
+        <button
+            class=class
+            disabled=is_denied
+            // Disabled buttons ignore the pointer, which hides the tooltip
+            class=(["disabled:pointer-events-auto", "disabled:cursor-not-allowed"], is_denied)
+            title=move || capability.and_then(|capability| capability.reason())
+            on:click=move |_| on_click.call(())
+        >
 
             {children.map(|children| children())}
 
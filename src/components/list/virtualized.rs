@@ -0,0 +1,120 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::hash::Hash;
+
+use leptos::{html::Tr, *};
+use leptos_use::use_window_scroll;
+
+/// Estimated height of a list row, in pixels.
+pub const ROW_HEIGHT: f64 = 57.0;
+
+/// Number of rows rendered above and below the visible part of the list.
+const OVERSCAN: usize = 10;
+
+/// Renders the rows of a table body through a window that follows the page
+/// scroll position, so only the rows currently on screen are in the DOM.
+/// Rows outside the window are replaced by two spacer rows of the same total
+/// height. When the window reaches the last row, `on_end_reached` is called
+/// once so the caller can load the next batch of items.
+#[component]
+pub fn VirtualRows<T, K, KF, CF, IV>(
+    #[prop(into)] each: Signal<Vec<T>>,
+    key: KF,
+    children: CF,
+    #[prop(optional)] row_height: Option<f64>,
+    #[prop(into, optional)] on_end_reached: Option<Callback<()>>,
+) -> impl IntoView
+where
+    T: Clone + 'static,
+    K: Eq + Hash + 'static,
+    KF: Fn(&T) -> K + 'static,
+    CF: Fn(T) -> IV + 'static,
+    IV: IntoView + 'static,
+{
+    let row_height = row_height.unwrap_or(ROW_HEIGHT);
+    let anchor = create_node_ref::<Tr>();
+    let (_, scroll_y) = use_window_scroll();
+    let total = create_memo(move |_| each.with(|items| items.len()));
+
+    let range = create_memo(move |_| {
+        scroll_y.track();
+        let total = total.get();
+        let top = anchor
+            .get()
+            .map_or(0.0, |anchor| anchor.get_bounding_client_rect().top());
+        let height = window()
+            .inner_height()
+            .ok()
+            .and_then(|height| height.as_f64())
+            .unwrap_or_default();
+
+        let first = ((-top).max(0.0) / row_height) as usize;
+        let visible = (height / row_height).ceil() as usize + 1;
+        (
+            first.saturating_sub(OVERSCAN).min(total),
+            (first + visible + OVERSCAN).min(total),
+        )
+    });
+
+    if let Some(on_end_reached) = on_end_reached {
+        let requested_at = store_value(None::<usize>);
+        create_effect(move |_| {
+            let (_, end) = range.get();
+            let total = total.get();
+            if total > 0 && end >= total && requested_at.get_value() != Some(total) {
+                requested_at.set_value(Some(total));
+                on_end_reached.call(());
+            }
+        });
+    }
+
+    view! {
+        <tr
+            node_ref=anchor
+            style=move || format!("height: {}px", range.get().0 as f64 * row_height)
+        ></tr>
+        <For
+            each=move || {
+                let (start, end) = range.get();
+                each.with(|items| items[start.min(items.len())..end.min(items.len())].to_vec())
+            }
+
+            key=key
+            children=children
+        />
+        <tr
+            class:hidden=move || { range.get().1 >= total.get() }
+            style=move || {
+                format!("height: {}px", (total.get() - range.get().1) as f64 * row_height)
+            }
+        ></tr>
+    }
+}
+
+#[component]
+pub fn LoadedResults(
+    #[prop(into)] loaded: MaybeSignal<usize>,
+    #[prop(into)] total_results: MaybeSignal<Option<u32>>,
+) -> impl IntoView {
+    view! {
+        <div class="px-6 py-4 grid gap-3 md:flex md:justify-between md:items-center border-t border-gray-200 dark:border-gray-700">
+            <Show when=move || { total_results.get().is_some_and(|r| r > 0) }>
+                <p class="text-sm text-gray-600 dark:text-gray-400">
+                    "Showing "
+                    <span class="font-semibold text-gray-800 dark:text-gray-200">
+                        {move || loaded.get()}
+                    </span>
+                    " of "
+                    <span class="font-semibold text-gray-800 dark:text-gray-200">
+                        {move || total_results.get().unwrap_or_default()}
+                    </span>
+                    " results."
+                </p>
+            </Show>
+        </div>
+    }
+}
@@ -12,7 +12,10 @@ use crate::{
     components::icon::{
         IconCheckCircle, IconExclamationCircle, IconExclamationTriangle, IconXMark,
     },
-    core::http::{self, ManagementApiError},
+    core::{
+        connection::{is_connection_lost, MAX_RETRIES},
+        http::{self, ManagementApiError},
+    },
     pages::config::{ConfigError, ConfigWarning, ReloadSettings},
 };
 
@@ -246,7 +249,17 @@ impl Alert {
 impl From<http::Error> for Alert {
     fn from(value: http::Error) -> Self {
         match value {
+            // The connection banner already reports the outage
+            http::Error::Network(_) if is_connection_lost() => Alert::disabled(),
             http::Error::Network(details) => Alert::error("Network error").with_details(details),
+            http::Error::Unavailable(details) => {
+                Alert::error("Server unavailable").with_details(details)
+            }
+            http::Error::RetriesExhausted(details) => {
+                Alert::error("Changes could not be saved").with_details(format!(
+                    "The server could not be reached after {MAX_RETRIES} retries and the change was discarded: {details}"
+                ))
+            }
             http::Error::Serializer { error, response } => {
                 log::debug!("Failed to deserialize request: {}", response);
                 Alert::error("Failed to deserialize response").with_details(error)
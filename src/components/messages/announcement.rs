@@ -0,0 +1,106 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use gloo_storage::{SessionStorage, Storage};
+use leptos::*;
+
+use crate::{
+    components::icon::{IconBell, IconXMark},
+    core::{
+        announcement::{Announcement, Severity},
+        oauth::use_authorization,
+    },
+    pages::FormatDateTime,
+};
+
+const DISMISSED_STORAGE_KEY: &str = "webadmin_announcement_dismissed";
+
+/// Shows the announcement composed by an administrator to everyone signed
+/// in. Informational and warning announcements can be dismissed for the
+/// rest of the session.
+#[component]
+pub fn AnnouncementBanner() -> impl IntoView {
+    let auth = use_authorization();
+    let dismissed = create_rw_signal(SessionStorage::get::<String>(DISMISSED_STORAGE_KEY).ok());
+
+    let announcement = create_resource(
+        move || auth.with(|auth| (auth.is_logged_in(), auth.username.clone())),
+        move |(is_logged_in, _)| {
+            let auth = auth.get_untracked();
+
+            async move {
+                if is_logged_in {
+                    Announcement::fetch(&auth).await.ok().flatten()
+                } else {
+                    None
+                }
+            }
+        },
+    );
+    let visible = create_memo(move |_| {
+        announcement.get().flatten().filter(|announcement| {
+            announcement.severity == Severity::Critical
+                || dismissed.with(|dismissed| dismissed.as_ref() != Some(&announcement.message))
+        })
+    });
+
+    view! {
+        {move || {
+            visible
+                .get()
+                .map(|announcement| {
+                    let class = match announcement.severity {
+                        Severity::Info => {
+                            "fixed top-0 inset-x-0 z-50 flex justify-center items-center gap-x-3 py-2 px-4 bg-blue-600 text-sm text-white"
+                        }
+                        Severity::Warning => {
+                            "fixed top-0 inset-x-0 z-50 flex justify-center items-center gap-x-3 py-2 px-4 bg-yellow-500 text-sm text-gray-900"
+                        }
+                        Severity::Critical => {
+                            "fixed top-0 inset-x-0 z-50 flex justify-center items-center gap-x-3 py-2 px-4 bg-red-600 text-sm text-white"
+                        }
+                    };
+                    let message = match announcement.expires {
+                        Some(expires) => {
+                            format!(
+                                "{} (until {})",
+                                announcement.message,
+                                expires.format_date_time(),
+                            )
+                        }
+                        None => announcement.message.clone(),
+                    };
+                    let can_dismiss = announcement.severity != Severity::Critical;
+                    let text = announcement.message;
+                    view! {
+                        <div class=class role="alert">
+                            <IconBell attr:class="flex-shrink-0 size-4"/>
+                            <span>{message}</span>
+                            <button
+                                type="button"
+                                class="inline-flex flex-shrink-0 justify-center items-center size-5 rounded-lg opacity-75 hover:opacity-100"
+                                class:hidden=!can_dismiss
+                                on:click=move |_| {
+                                    if let Err(err) = SessionStorage::set(
+                                        DISMISSED_STORAGE_KEY,
+                                        text.clone(),
+                                    ) {
+                                        log::error!(
+                                            "Failed to save state to session storage: {}", err
+                                        );
+                                    }
+                                    dismissed.set(Some(text.clone()));
+                                }
+                            >
+
+                                <IconXMark attr:class="flex-shrink-0 size-4"/>
+                            </button>
+                        </div>
+                    }
+                })
+        }}
+    }
+}
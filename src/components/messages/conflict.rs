@@ -0,0 +1,73 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::*;
+
+use super::modal::{Modal, ModalStep};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConflictResolution {
+    /// Keep the changes of the other administrator to the fields that were
+    /// not edited, then review the result before saving again.
+    Merge,
+    /// Save the form as it is, discarding the changes of the other
+    /// administrator.
+    Overwrite,
+}
+
+/// Asks how to save a record that was modified by someone else after it was
+/// loaded into the form, `what` describes the record as in "the account 'john'".
+pub fn conflict_modal(what: &str, on_resolve: impl Fn(ConflictResolution) + 'static) -> Modal {
+    let resolution = create_rw_signal(ConflictResolution::Merge);
+    let options = [
+        (
+            ConflictResolution::Merge,
+            "Merge",
+            "Keep their changes to the fields you did not edit and review the result before saving.",
+        ),
+        (
+            ConflictResolution::Overwrite,
+            "Overwrite",
+            "Save your version, discarding their changes.",
+        ),
+    ];
+
+    Modal::with_title("Edit conflict")
+        .with_step(
+            ModalStep::new("Edit conflict")
+                .with_message(format!(
+                    "Someone else modified {what} since you opened it."
+                ))
+                .with_content(move || {
+                    options
+                        .into_iter()
+                        .map(|(option, label, description)| {
+                            view! {
+                                <label class="mt-4 flex gap-x-3 cursor-pointer">
+                                    <input
+                                        type="radio"
+                                        name="conflict-resolution"
+                                        class="shrink-0 mt-0.5 border-gray-200 rounded-full text-blue-600 focus:ring-blue-500 dark:bg-gray-800 dark:border-gray-700 dark:checked:bg-blue-500 dark:checked:border-blue-500 dark:focus:ring-offset-gray-800"
+                                        prop:checked=move || resolution.get() == option
+                                        on:change=move |_| resolution.set(option)
+                                    />
+                                    <span class="text-sm">
+                                        <span class="block font-semibold text-gray-800 dark:text-gray-200">
+                                            {label}
+                                        </span>
+                                        <span class="block text-gray-500 dark:text-gray-400">
+                                            {description}
+                                        </span>
+                                    </span>
+                                </label>
+                            }
+                        })
+                        .collect_view()
+                })
+                .with_button("Continue"),
+        )
+        .with_callback(move || on_resolve(resolution.get_untracked()))
+}
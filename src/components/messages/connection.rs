@@ -0,0 +1,108 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::*;
+
+use crate::{
+    components::{icon::IconExclamationTriangle, messages::alert::Alert},
+    core::{
+        connection::{backoff, use_connection},
+        http::HttpRequest,
+        oauth::use_authorization,
+    },
+    pages::FormatDateTime,
+};
+
+/// Shows a persistent banner while the server is unreachable and probes it
+/// with increasing delays until it answers again.
+#[component]
+pub fn ConnectionBanner() -> impl IntoView {
+    let connection = use_connection();
+    let auth = use_authorization();
+    let alert = expect_context::<RwSignal<Alert>>();
+    let attempt = store_value(0u32);
+
+    let probe = create_action(move |_: &()| {
+        let auth = auth.get_untracked();
+
+        async move {
+            // Any response, even an error status, means the server is reachable
+            let _ = HttpRequest::get("/healthz/live")
+                .with_base_url(&auth)
+                .send_raw()
+                .await;
+        }
+    });
+
+    create_effect(move |was_lost: Option<bool>| {
+        let is_lost = connection.with(|connection| connection.is_lost());
+        if is_lost {
+            if !probe.pending().get() {
+                let delay = backoff(attempt.get_value());
+                attempt.update_value(|attempt| *attempt += 1);
+                set_timeout(
+                    move || {
+                        if connection.with_untracked(|connection| connection.is_lost()) {
+                            probe.dispatch(());
+                        }
+                    },
+                    delay,
+                );
+            }
+        } else {
+            attempt.set_value(0);
+            if was_lost == Some(true) {
+                alert.set(Alert::success("Connection to the server restored"));
+            }
+        }
+        is_lost
+    });
+
+    view! {
+        <Show when=move || connection.with(|connection| connection.is_lost())>
+            <div
+                class="fixed top-0 inset-x-0 z-[70] flex justify-center items-center gap-x-3 py-2 px-4 bg-red-600 text-sm text-white"
+                role="alert"
+            >
+                <IconExclamationTriangle attr:class="flex-shrink-0 size-4"/>
+                <span>
+                    {move || {
+                        connection
+                            .with(|connection| {
+                                let mut message = format!(
+                                    "Connection to the server lost since {}.",
+                                    connection
+                                        .lost_since
+                                        .map(|since| since.format_date_time())
+                                        .unwrap_or_default(),
+                                );
+                                match connection.queued {
+                                    0 => {}
+                                    1 => message.push_str(" 1 change will be sent once it is restored."),
+                                    n => {
+                                        message
+                                            .push_str(
+                                                &format!(" {n} changes will be sent once it is restored."),
+                                            )
+                                    }
+                                }
+                                message
+                            })
+                    }}
+
+                </span>
+                <button
+                    type="button"
+                    class="py-1 px-2 inline-flex items-center text-xs font-semibold rounded-lg border border-white/40 hover:bg-red-700 disabled:opacity-50 disabled:pointer-events-none"
+                    disabled=move || probe.pending().get()
+                    on:click=move |_| probe.dispatch(())
+                >
+                    {move || if probe.pending().get() { "Retrying..." } else { "Retry now" }}
+                </button>
+            </div>
+        </Show>
+    }
+}
@@ -0,0 +1,67 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use gloo_storage::{SessionStorage, Storage};
+use leptos::*;
+use leptos_router::use_navigate;
+
+use crate::{
+    components::icon::IconExclamationTriangle, core::oauth::use_authorization, STATE_STORAGE_KEY,
+};
+
+/// Shows a persistent banner while an administrator is acting on behalf of
+/// another account, with a button to return to the administrator session.
+#[component]
+pub fn ImpersonationBanner() -> impl IntoView {
+    let auth = use_authorization();
+
+    view! {
+        <Show when=move || auth.with(|auth| auth.is_impersonating())>
+            <div
+                class="fixed top-0 inset-x-0 z-[60] flex justify-center items-center gap-x-3 py-2 px-4 bg-yellow-500 text-sm text-gray-900"
+                role="alert"
+            >
+                <IconExclamationTriangle attr:class="flex-shrink-0 size-4"/>
+                <span>
+                    {move || {
+                        auth.with(|auth| {
+                            format!(
+                                "You are acting as {} on behalf of {}.",
+                                auth.username,
+                                auth
+                                    .impersonator
+                                    .as_ref()
+                                    .map(|admin| admin.username.as_str())
+                                    .unwrap_or_default(),
+                            )
+                        })
+                    }}
+
+                </span>
+                <button
+                    type="button"
+                    class="py-1 px-2 inline-flex items-center gap-x-2 text-xs font-semibold rounded-lg border border-gray-900 hover:bg-yellow-400"
+                    on:click=move |_| {
+                        auth.update(|auth| {
+                            if let Some(admin) = auth.impersonator.take() {
+                                *auth = *admin;
+                                // The administrator token may have expired meanwhile
+                                auth.is_valid = false;
+                                if let Err(err) = SessionStorage::set(STATE_STORAGE_KEY, auth.clone()) {
+                                    log::error!("Failed to save state to session storage: {}", err);
+                                }
+                            }
+                        });
+                        use_navigate()("/manage/directory/accounts", Default::default());
+                    }
+                >
+
+                    Exit
+                </button>
+            </div>
+        </Show>
+    }
+}
@@ -5,4 +5,9 @@
  */
 
 pub mod alert;
+pub mod announcement;
+pub mod conflict;
+pub mod connection;
+pub mod impersonation;
 pub mod modal;
+pub mod update;
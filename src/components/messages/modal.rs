@@ -4,24 +4,45 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
-use leptos::{html::Div, *};
+use leptos::{
+    html::{Button, Div},
+    *,
+};
 use leptos_use::on_click_outside;
-use std::sync::Arc;
+use std::{future::Future, pin::Pin, sync::Arc};
+use web_sys::wasm_bindgen::JsCast;
 
 use crate::components::icon::IconXMark;
 
 pub trait ModalCb: Fn() + 'static {}
 
+pub type StepValidator = Arc<dyn Fn() -> Result<(), String>>;
+pub type StepHandler = Arc<dyn Fn() -> Pin<Box<dyn Future<Output = Result<(), String>>>>>;
+pub type ImpactLoader = Arc<dyn Fn() -> Pin<Box<dyn Future<Output = Result<Vec<String>, String>>>>>;
+
 #[derive(Clone)]
 pub struct Modal {
     pub is_open: bool,
     pub title: String,
-    pub message: String,
-    pub button_text: String,
+    pub steps: Vec<ModalStep>,
+    pub current_step: usize,
+    pub error: Option<String>,
     pub danger: bool,
+    pub confirm_text: Option<String>,
+    pub impact: Option<ImpactLoader>,
     pub on_confirm: Arc<dyn Fn()>,
 }
 
+#[derive(Clone)]
+pub struct ModalStep {
+    pub title: String,
+    pub message: String,
+    pub content: Option<Arc<dyn Fn() -> View>>,
+    pub button_text: String,
+    pub validate: Option<StepValidator>,
+    pub handler: Option<StepHandler>,
+}
+
 pub fn init_modals() {
     provide_context(create_rw_signal(Modal::default()));
 }
@@ -36,25 +57,176 @@ pub fn use_modals() -> RwSignal<Modal> {
 pub fn Modal() -> impl IntoView {
     let modal = expect_context::<RwSignal<Modal>>();
     let (processing, set_processing) = create_signal(false);
+    let current_step = create_memo(move |_| modal.with(|modal| modal.current_step));
+    let total_steps = create_memo(move |_| modal.with(|modal| modal.steps.len().max(1)));
+    let is_wizard = create_memo(move |_| total_steps.get() > 1);
+    let is_last_step = create_memo(move |_| current_step.get() + 1 >= total_steps.get());
+    let confirm_button: NodeRef<Button> = create_node_ref::<Button>();
+    let typed_text = create_rw_signal(String::new());
+    let impact = create_rw_signal(None::<Result<Vec<String>, String>>);
+
+    // Destructive operations can require the object name to be typed and
+    // an impact summary to be loaded before they are confirmed
+    let is_open = create_memo(move |_| modal.with(|modal| modal.is_open));
+    create_effect(move |_| {
+        if is_open.get() {
+            typed_text.set(String::new());
+            impact.set(None);
+            if let Some(loader) = modal.with_untracked(|modal| modal.impact.clone()) {
+                spawn_local(async move {
+                    impact.set(Some(loader().await));
+                });
+            }
+        }
+    });
+    let is_confirmed = create_memo(move |_| {
+        modal.with(|modal| {
+            modal
+                .confirm_text
+                .as_ref()
+                .is_none_or(|text| typed_text.with(|typed| typed.trim() == text))
+                && (modal.impact.is_none() || impact.with(|impact| impact.is_some()))
+        })
+    });
+
+    let close = move || {
+        modal.update(|modal| {
+            modal.is_open = false;
+        });
+    };
 
-    // Dismiss modal when "Escape" (or 'q') key is pressed
-    let dismiss_modal_with_keyboard = window_event_listener(ev::keydown, move |ev| {
-        if ev.key() == "Escape" || ev.key() == "q" || ev.key() == "Q" {
+    // Validate the current step, run its handler and then either move to
+    // the next step or, on the last step, invoke the confirmation callback.
+    let advance = Callback::new(move |_: ()| {
+        if processing.get_untracked() {
+            return;
+        }
+        let (validate, handler, is_last) = modal.with_untracked(|modal| {
+            let step = modal.steps.get(modal.current_step);
+            (
+                step.and_then(|step| step.validate.clone()),
+                step.and_then(|step| step.handler.clone()),
+                modal.current_step + 1 >= modal.steps.len(),
+            )
+        });
+        if let Some(Err(err)) = validate.map(|validate| validate()) {
             modal.update(|modal| {
-                modal.is_open = false;
+                modal.error = Some(err);
+            });
+            return;
+        }
+        if is_last && !is_confirmed.get_untracked() {
+            return;
+        }
+
+        let next = move || {
+            if is_last {
+                let on_confirm = modal.with_untracked(|modal| modal.on_confirm.clone());
+                close();
+                on_confirm();
+            } else {
+                modal.update(|modal| {
+                    modal.current_step += 1;
+                    modal.error = None;
+                });
+            }
+        };
+
+        if let Some(handler) = handler {
+            set_processing.set(true);
+            spawn_local(async move {
+                let result = handler().await;
+                set_processing.set(false);
+                match result {
+                    Ok(_) => next(),
+                    Err(err) => modal.update(|modal| {
+                        modal.error = Some(err);
+                    }),
+                }
             });
+        } else {
+            next();
         }
     });
-    on_cleanup(move || dismiss_modal_with_keyboard.remove());
 
-    // Click outside modal to dismiss
+    // Keyboard navigation: "Escape" dismisses the modal and "Enter" confirms the
+    // current step when typed in one of its fields, buttons handle "Enter" on
+    // their own. The 'q' shortcut is only available when the step has no inputs.
     let modal_target: NodeRef<Div> = create_node_ref::<Div>();
-    on_cleanup(on_click_outside(modal_target, move |_| {
-        modal.update(|modal| {
-            modal.is_open = false;
+    let step_body: NodeRef<Div> = create_node_ref::<Div>();
+    let modal_keyboard_navigation = window_event_listener(ev::keydown, move |ev| {
+        let (is_open, has_content) = modal.with_untracked(|modal| {
+            (
+                modal.is_open,
+                modal.confirm_text.is_some()
+                    || modal
+                        .steps
+                        .get(modal.current_step)
+                        .is_some_and(|step| step.content.is_some()),
+            )
         });
+        if !is_open {
+            return;
+        }
+
+        match ev.key().as_str() {
+            "Escape" => close(),
+            "q" | "Q" if !has_content => close(),
+            "Enter" => {
+                let target = event_target::<web_sys::Element>(&ev);
+                let is_field = matches!(target.tag_name().as_str(), "INPUT" | "SELECT")
+                    && step_body
+                        .get_untracked()
+                        .is_some_and(|body| body.contains(Some(&target)));
+                if is_field {
+                    ev.prevent_default();
+                    advance.call(());
+                }
+            }
+            "Tab" => {
+                if let Some(dialog) = modal_target.get_untracked() {
+                    trap_focus(&dialog, &ev);
+                }
+            }
+            _ => {}
+        }
+    });
+    on_cleanup(move || modal_keyboard_navigation.remove());
+
+    // Give the focus back to the element that opened the modal once it closes
+    let opener = store_value(None::<web_sys::HtmlElement>);
+    create_effect(move |was_open: Option<bool>| {
+        let is_open = is_open.get();
+        if is_open && was_open != Some(true) {
+            opener.set_value(
+                document()
+                    .active_element()
+                    .and_then(|element| element.dyn_into::<web_sys::HtmlElement>().ok()),
+            );
+        } else if !is_open && was_open == Some(true) {
+            if let Some(opener) = opener.get_value() {
+                let _ = opener.focus();
+            }
+            opener.set_value(None);
+        }
+        is_open
+    });
+
+    // Click outside modal to dismiss, unless a multi-step flow is in progress
+    on_cleanup(on_click_outside(modal_target, move |_| {
+        if !is_wizard.get_untracked() {
+            close();
+        }
     }));
 
+    // Move the focus to the confirmation button when a step is displayed
+    create_effect(move |_| {
+        current_step.track();
+        if let Some(button) = confirm_button.get() {
+            let _ = button.focus();
+        }
+    });
+
     view! {
         <Show when=move || modal.get().is_open fallback=|| ()>
             <Portal mount=document().get_element_by_id("portal_root").unwrap()>
@@ -63,6 +235,9 @@ pub fn Modal() -> impl IntoView {
                     <div
                         id="hs-vertically-centered-modal"
                         class="open hs-overlay size-full fixed top-0 start-0 z-[80] overflow-x-hidden overflow-y-auto"
+                        role="dialog"
+                        aria-modal="true"
+                        aria-labelledby="modal-title"
                     >
 
                         <div
@@ -71,18 +246,13 @@ pub fn Modal() -> impl IntoView {
                         >
                             <div class="w-full flex flex-col bg-white border shadow-sm rounded-xl dark:bg-gray-800 dark:border-gray-700 dark:shadow-slate-700/[.7]">
                                 <div class="flex justify-between items-center py-3 px-4 border-b dark:border-gray-700">
-                                    <h3 class="font-bold text-gray-800 dark:text-white">
+                                    <h3 id="modal-title" class="font-bold text-gray-800 dark:text-white">
                                         {move || { modal.get().title }}
                                     </h3>
                                     <button
                                         type="button"
                                         class="flex justify-center items-center size-7 text-sm font-semibold rounded-full border border-transparent text-gray-800 hover:bg-gray-100 disabled:opacity-50 disabled:pointer-events-none dark:text-white dark:hover:bg-gray-700 dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
-                                        on:click=move |_| {
-                                            modal
-                                                .update(|modal| {
-                                                    modal.is_open = false;
-                                                });
-                                        }
+                                        on:click=move |_| close()
                                     >
 
                                         <span class="sr-only">Close</span>
@@ -90,27 +260,133 @@ pub fn Modal() -> impl IntoView {
 
                                     </button>
                                 </div>
-                                <div class="p-4 overflow-y-auto">
+                                <Show when=move || is_wizard.get()>
+                                    <ModalProgress
+                                        current_step=current_step
+                                        step_titles=Signal::derive(move || {
+                                            modal
+                                                .with(|modal| {
+                                                    modal
+                                                        .steps
+                                                        .iter()
+                                                        .map(|step| step.title.clone())
+                                                        .collect::<Vec<_>>()
+                                                })
+                                        })
+                                    />
+
+                                </Show>
+                                <div class="p-4 overflow-y-auto" node_ref=step_body>
                                     <p class="text-gray-800 dark:text-gray-400">
-                                        {move || { modal.get().message }}
+                                        {move || {
+                                            modal
+                                                .with(|modal| {
+                                                    modal
+                                                        .steps
+                                                        .get(modal.current_step)
+                                                        .map(|step| step.message.clone())
+                                                        .unwrap_or_default()
+                                                })
+                                        }}
+
                                     </p>
+                                    {move || {
+                                        let step = current_step.get();
+                                        modal
+                                            .with_untracked(|modal| {
+                                                modal.steps.get(step).and_then(|step| step.content.clone())
+                                            })
+                                            .map(|content| content())
+                                    }}
+
+                                    <Show when=move || {
+                                        is_last_step.get() && modal.with(|modal| modal.impact.is_some())
+                                    }>
+                                        <div class="mt-3 p-3 text-sm bg-red-50 border border-red-200 rounded-lg text-red-800 dark:bg-red-800/10 dark:border-red-900 dark:text-red-500">
+                                            {move || match impact.get() {
+                                                None => view! { <p>Calculating impact...</p> }.into_view(),
+                                                Some(Ok(lines)) => {
+                                                    view! {
+                                                        <ul class="list-disc ps-4 space-y-1">
+                                                            {lines
+                                                                .into_iter()
+                                                                .map(|line| view! { <li>{line}</li> })
+                                                                .collect_view()}
+                                                        </ul>
+                                                    }
+                                                        .into_view()
+                                                }
+                                                Some(Err(err)) => {
+                                                    view! {
+                                                        <p>{format!("The impact could not be determined: {err}")}</p>
+                                                    }
+                                                        .into_view()
+                                                }
+                                            }}
+
+                                        </div>
+                                    </Show>
+                                    {move || {
+                                        is_last_step
+                                            .get()
+                                            .then(|| modal.with(|modal| modal.confirm_text.clone()))
+                                            .flatten()
+                                            .map(|text| {
+                                                view! {
+                                                    <label class="block mt-3 mb-2 text-sm text-gray-800 dark:text-gray-400">
+                                                        "Type "
+                                                        <span class="font-mono font-semibold">{text}</span>
+                                                        " to confirm."
+                                                    </label>
+                                                    <input
+                                                        type="text"
+                                                        class="py-2 px-3 block w-full border-gray-200 rounded-lg text-sm focus:border-red-500 focus:ring-red-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400"
+                                                        autocomplete="off"
+                                                        prop:value=move || typed_text.get()
+                                                        on:input=move |ev| typed_text.set(event_target_value(&ev))
+                                                    />
+                                                }
+                                            })
+                                    }}
+
+                                    {move || {
+                                        modal
+                                            .get()
+                                            .error
+                                            .map(|error| {
+                                                view! {
+                                                    <p class="mt-2 text-sm text-red-600" role="alert">
+                                                        {error}
+                                                    </p>
+                                                }
+                                            })
+                                    }}
+
                                 </div>
                                 <div class="flex justify-end items-center gap-x-2 py-3 px-4 border-t dark:border-gray-700">
                                     <button
                                         type="button"
                                         class="py-2 px-3 inline-flex items-center gap-x-2 text-sm font-medium rounded-lg border border-gray-200 bg-white text-gray-800 shadow-sm hover:bg-gray-50 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-white dark:hover:bg-gray-800 dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
                                         on:click=move |_| {
-                                            modal
-                                                .update(|modal| {
-                                                    modal.is_open = false;
-                                                });
+                                            if current_step.get() > 0 {
+                                                modal
+                                                    .update(|modal| {
+                                                        modal.current_step -= 1;
+                                                        modal.error = None;
+                                                    });
+                                            } else {
+                                                close();
+                                            }
                                         }
+
+                                        disabled=move || processing.get()
                                     >
 
-                                        Close
+                                        {move || if current_step.get() > 0 { "Back" } else { "Close" }}
                                     </button>
                                     <button
                                         type="button"
+                                        node_ref=confirm_button
                                         class=move || {
                                             if modal.get().danger {
                                                 "py-3 px-4 inline-flex items-center gap-x-2 text-sm font-semibold rounded-lg border border-transparent bg-red-500 text-white hover:bg-red-600 disabled:opacity-50 disabled:pointer-events-none dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
@@ -119,20 +395,24 @@ pub fn Modal() -> impl IntoView {
                                             }
                                         }
 
-                                        on:click=move |_| {
-                                            modal
-                                                .update(|modal| {
-                                                    set_processing.set(true);
-                                                    (modal.on_confirm)();
-                                                    set_processing.set(false);
-                                                    modal.is_open = false;
-                                                });
-                                        }
+                                        on:click=move |_| advance.call(())
 
-                                        disabled=move || processing.get()
+                                        disabled=move || {
+                                            processing.get() || (is_last_step.get() && !is_confirmed.get())
+                                        }
                                     >
 
-                                        {move || { modal.get().button_text }}
+                                        {move || {
+                                            modal
+                                                .with(|modal| {
+                                                    modal
+                                                        .steps
+                                                        .get(modal.current_step)
+                                                        .map(|step| step.button_text.clone())
+                                                        .unwrap_or_else(|| "Confirm".to_string())
+                                                })
+                                        }}
+
                                     </button>
                                 </div>
                             </div>
@@ -144,24 +424,108 @@ pub fn Modal() -> impl IntoView {
     }
 }
 
+/// Keeps the focus inside the dialog, wrapping around its first and last
+/// focusable elements.
+fn trap_focus(dialog: &web_sys::Element, ev: &ev::KeyboardEvent) {
+    let Ok(elements) = dialog.query_selector_all(
+        "button:not([disabled]), input:not([disabled]), select:not([disabled]), \
+         textarea:not([disabled]), a[href], [tabindex]:not([tabindex='-1'])",
+    ) else {
+        return;
+    };
+    let elements = (0..elements.length())
+        .filter_map(|idx| elements.item(idx)?.dyn_into::<web_sys::HtmlElement>().ok())
+        .collect::<Vec<_>>();
+    let (Some(first), Some(last)) = (elements.first(), elements.last()) else {
+        ev.prevent_default();
+        return;
+    };
+    let active = document().active_element();
+    let is_inside = active
+        .as_ref()
+        .is_some_and(|active| dialog.contains(Some(active)));
+
+    if !is_inside {
+        ev.prevent_default();
+        let _ = first.focus();
+    } else if ev.shift_key() && active.as_deref() == Some(first.as_ref()) {
+        ev.prevent_default();
+        let _ = last.focus();
+    } else if !ev.shift_key() && active.as_deref() == Some(last.as_ref()) {
+        ev.prevent_default();
+        let _ = first.focus();
+    }
+}
+
+#[component]
+fn ModalProgress(
+    #[prop(into)] current_step: Signal<usize>,
+    #[prop(into)] step_titles: Signal<Vec<String>>,
+) -> impl IntoView {
+    view! {
+        <div class="px-4 pt-4">
+            <ul class="relative flex flex-row gap-x-2" aria-label="Progress">
+                {move || {
+                    let current_step = current_step.get();
+                    step_titles
+                        .get()
+                        .into_iter()
+                        .enumerate()
+                        .map(|(idx, title)| {
+                            let class = if idx <= current_step {
+                                "size-7 flex justify-center items-center flex-shrink-0 bg-blue-600 font-medium text-white rounded-full"
+                            } else {
+                                "size-7 flex justify-center items-center flex-shrink-0 bg-gray-100 font-medium text-gray-800 rounded-full dark:bg-gray-700 dark:text-white"
+                            };
+                            let line_class = if idx < current_step {
+                                "ms-2 w-full h-px flex-1 bg-blue-600 group-last:hidden"
+                            } else {
+                                "ms-2 w-full h-px flex-1 bg-gray-200 group-last:hidden dark:bg-gray-700"
+                            };
+                            view! {
+                                <li
+                                    class="flex items-center gap-x-2 shrink basis-0 flex-1 group"
+                                    aria-current=(idx == current_step).then_some("step")
+                                >
+                                    <span class="min-w-7 min-h-7 group inline-flex items-center text-xs align-middle">
+                                        <span class=class>{idx + 1}</span>
+                                        <span class="ms-2 block text-sm font-medium text-gray-800 dark:text-white">
+                                            {title}
+                                        </span>
+                                    </span>
+                                    <div class=line_class></div>
+                                </li>
+                            }
+                        })
+                        .collect_view()
+                }}
+
+            </ul>
+        </div>
+    }
+}
+
 impl Modal {
     pub fn with_title(title: impl Into<String>) -> Self {
         Self {
             is_open: true,
             title: title.into(),
-            message: String::new(),
-            button_text: "Confirm".to_string(),
-            danger: false,
-            on_confirm: Arc::new(|| {}),
+            ..Default::default()
         }
     }
+
     pub fn with_message(mut self, message: impl Into<String>) -> Self {
-        self.message = message.into();
+        self.last_step().message = message.into();
         self
     }
 
     pub fn with_button(mut self, button_text: impl Into<String>) -> Self {
-        self.button_text = button_text.into();
+        self.last_step().button_text = button_text.into();
+        self
+    }
+
+    pub fn with_step(mut self, step: ModalStep) -> Self {
+        self.steps.push(step);
         self
     }
 
@@ -171,11 +535,79 @@ impl Modal {
         self
     }
 
+    /// Requires the given text, usually the name of the affected object, to
+    /// be typed before the last step can be confirmed.
+    pub fn with_confirmation_text(mut self, text: impl Into<String>) -> Self {
+        self.confirm_text = Some(text.into());
+        self
+    }
+
+    /// Loads a summary of what the operation affects when the modal is
+    /// opened, confirmation is only possible once it is available.
+    pub fn with_impact<F>(mut self, loader: impl Fn() -> F + 'static) -> Self
+    where
+        F: Future<Output = Result<Vec<String>, String>> + 'static,
+    {
+        self.impact = Some(Arc::new(
+            move || -> Pin<Box<dyn Future<Output = Result<Vec<String>, String>>>> {
+                Box::pin(loader())
+            },
+        ));
+        self
+    }
+
     pub fn with_callback(mut self, on_confirm: impl Fn() + 'static) -> Self {
         self.danger = false;
         self.on_confirm = Arc::new(on_confirm);
         self
     }
+
+    fn last_step(&mut self) -> &mut ModalStep {
+        if self.steps.is_empty() {
+            self.steps.push(ModalStep::default());
+        }
+        self.steps.last_mut().unwrap()
+    }
+}
+
+impl ModalStep {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            button_text: "Next".to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = message.into();
+        self
+    }
+
+    pub fn with_content<V: IntoView>(mut self, content: impl Fn() -> V + 'static) -> Self {
+        self.content = Some(Arc::new(move || content().into_view()));
+        self
+    }
+
+    pub fn with_button(mut self, button_text: impl Into<String>) -> Self {
+        self.button_text = button_text.into();
+        self
+    }
+
+    pub fn with_validator(mut self, validate: impl Fn() -> Result<(), String> + 'static) -> Self {
+        self.validate = Some(Arc::new(validate));
+        self
+    }
+
+    pub fn with_handler<F>(mut self, handler: impl Fn() -> F + 'static) -> Self
+    where
+        F: Future<Output = Result<(), String>> + 'static,
+    {
+        self.handler = Some(Arc::new(
+            move || -> Pin<Box<dyn Future<Output = Result<(), String>>>> { Box::pin(handler()) },
+        ));
+        self
+    }
 }
 
 impl Default for Modal {
@@ -183,10 +615,26 @@ impl Default for Modal {
         Modal {
             is_open: false,
             title: String::new(),
-            message: String::new(),
-            button_text: "Confirm".to_string(),
+            steps: Vec::new(),
+            current_step: 0,
+            error: None,
             danger: false,
+            confirm_text: None,
+            impact: None,
             on_confirm: Arc::new(|| {}),
         }
     }
 }
+
+impl Default for ModalStep {
+    fn default() -> Self {
+        ModalStep {
+            title: String::new(),
+            message: String::new(),
+            content: None,
+            button_text: "Confirm".to_string(),
+            validate: None,
+            handler: None,
+        }
+    }
+}
@@ -0,0 +1,51 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::*;
+
+use crate::{
+    components::icon::{IconArrowPath, IconXMark},
+    core::service_worker::{use_app_update, AppUpdate},
+};
+
+/// Offers to reload the page once a newly deployed version of the web admin
+/// has been downloaded in the background.
+#[component]
+pub fn UpdatePrompt() -> impl IntoView {
+    let update = use_app_update();
+
+    view! {
+        <Show when=move || update.get().available>
+            <div
+                class="fixed bottom-4 end-4 z-[60] max-w-xs flex items-center gap-x-3 p-4 bg-white border border-gray-200 rounded-xl shadow-lg text-sm text-gray-700 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400"
+                role="alert"
+            >
+                <IconArrowPath attr:class="flex-shrink-0 size-4 text-blue-600"/>
+                <span>A new version of the web admin is available.</span>
+                <button
+                    type="button"
+                    class="flex-shrink-0 text-sm font-semibold text-blue-600 hover:text-blue-800 dark:text-blue-500 dark:hover:text-blue-400"
+                    on:click=move |_| {
+                        let _ = window().location().reload();
+                    }
+                >
+
+                    Reload
+                </button>
+                <button
+                    type="button"
+                    class="inline-flex flex-shrink-0 justify-center items-center size-5 rounded-lg text-gray-500 hover:text-gray-800 dark:hover:text-gray-200"
+                    on:click=move |_| {
+                        update.set(AppUpdate::default());
+                    }
+                >
+
+                    <IconXMark attr:class="flex-shrink-0 size-4"/>
+                </button>
+            </div>
+        </Show>
+    }
+}
@@ -6,6 +6,7 @@
 
 pub mod badge;
 pub mod card;
+pub mod chart;
 pub mod form;
 pub mod icon;
 pub mod layout;
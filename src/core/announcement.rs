@@ -0,0 +1,79 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::pages::config::edit::FetchSettings;
+
+use super::{
+    http::{self, HttpRequest},
+    oauth::AuthToken,
+};
+
+const ANNOUNCEMENT_PREFIX: &str = "webadmin.announcement";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Severity {
+    #[default]
+    Info,
+    Warning,
+    Critical,
+}
+
+/// Message composed by an administrator and shown at the top of the web
+/// admin to everyone signed in until it expires.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Announcement {
+    pub message: String,
+    pub severity: Severity,
+    pub expires: Option<DateTime<Utc>>,
+}
+
+impl Announcement {
+    /// Fetches the current announcement, returning `None` when it is
+    /// disabled, expired or the account is not allowed to read it.
+    pub async fn fetch(auth: &AuthToken) -> http::Result<Option<Announcement>> {
+        let settings = match HttpRequest::get("/api/settings/list")
+            .with_authorization(auth)
+            .with_parameter("prefix", ANNOUNCEMENT_PREFIX)
+            .send::<FetchSettings>()
+            .await
+        {
+            Ok(settings) => settings.items,
+            Err(http::Error::Forbidden | http::Error::NotFound) => return Ok(None),
+            Err(err) => return Err(err),
+        };
+
+        if settings.get("enable").map(|v| v.as_str()) != Some("true") {
+            return Ok(None);
+        }
+        let Some(message) = settings
+            .get("message")
+            .map(|message| message.trim())
+            .filter(|message| !message.is_empty())
+        else {
+            return Ok(None);
+        };
+        let expires = settings
+            .get("expires")
+            .and_then(|expires| DateTime::parse_from_rfc3339(expires.trim()).ok())
+            .map(|expires| expires.to_utc());
+        if expires.is_some_and(|expires| expires <= Utc::now()) {
+            return Ok(None);
+        }
+
+        Ok(Some(Announcement {
+            message: message.to_string(),
+            severity: match settings.get("severity").map(|v| v.as_str()) {
+                Some("warning") => Severity::Warning,
+                Some("critical") => Severity::Critical,
+                _ => Severity::Info,
+            },
+            expires,
+        }))
+    }
+}
@@ -0,0 +1,37 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::document;
+use web_sys::{wasm_bindgen::JsCast, HtmlDocument, HtmlTextAreaElement};
+
+/// Copies text to the clipboard, returns `false` if the browser refused.
+pub fn copy_to_clipboard(text: &str) -> bool {
+    let document = document();
+    let Some(textarea) = document
+        .create_element("textarea")
+        .ok()
+        .and_then(|el| el.dyn_into::<HtmlTextAreaElement>().ok())
+    else {
+        return false;
+    };
+    let Some(body) = document.body() else {
+        return false;
+    };
+
+    textarea.set_value(text);
+    let _ = textarea.set_attribute("style", "position: fixed; top: -1000px; opacity: 0;");
+    if body.append_child(&textarea).is_err() {
+        return false;
+    }
+    textarea.select();
+    let result = document
+        .dyn_ref::<HtmlDocument>()
+        .and_then(|document| document.exec_command("copy").ok())
+        .unwrap_or(false);
+    textarea.remove();
+
+    result
+}
@@ -0,0 +1,100 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use leptos::{
+    create_rw_signal, expect_context, provide_context, use_context, window, RwSignal, SignalUpdate,
+    SignalWithUntracked,
+};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::js_sys::Promise;
+
+/// Number of times a write is retried before giving up.
+pub const MAX_RETRIES: u32 = 8;
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Connection {
+    pub lost_since: Option<DateTime<Utc>>,
+    pub queued: usize,
+    pub last_error: Option<String>,
+}
+
+pub fn init_connection() {
+    provide_context(create_rw_signal(Connection::default()));
+}
+
+pub fn use_connection() -> RwSignal<Connection> {
+    expect_context::<RwSignal<Connection>>()
+}
+
+impl Connection {
+    pub fn is_lost(&self) -> bool {
+        self.lost_since.is_some()
+    }
+}
+
+/// Whether the server is currently considered unreachable.
+pub fn is_connection_lost() -> bool {
+    try_use_connection().map_or(false, |connection| {
+        connection.with_untracked(|c| c.is_lost())
+    })
+}
+
+pub(crate) fn try_use_connection() -> Option<RwSignal<Connection>> {
+    use_context::<RwSignal<Connection>>()
+}
+
+pub(crate) fn report_failure(connection: Option<RwSignal<Connection>>, error: &str) {
+    if let Some(connection) = connection {
+        connection.update(|connection| {
+            if connection.lost_since.is_none() {
+                connection.lost_since = Some(Utc::now());
+            }
+            connection.last_error = Some(error.to_string());
+        });
+    }
+}
+
+pub(crate) fn report_success(connection: Option<RwSignal<Connection>>) {
+    if let Some(connection) = connection {
+        if connection.with_untracked(|c| c.is_lost()) {
+            connection.update(|connection| {
+                connection.lost_since = None;
+                connection.last_error = None;
+            });
+        }
+    }
+}
+
+pub(crate) fn update_queued(connection: Option<RwSignal<Connection>>, delta: isize) {
+    if let Some(connection) = connection {
+        connection.update(|connection| {
+            connection.queued = connection.queued.saturating_add_signed(delta);
+        });
+    }
+}
+
+/// Exponential backoff delay for the given retry attempt, starting at zero.
+pub fn backoff(attempt: u32) -> Duration {
+    INITIAL_BACKOFF
+        .saturating_mul(1 << attempt.min(16))
+        .min(MAX_BACKOFF)
+}
+
+pub async fn sleep(duration: Duration) {
+    let promise = Promise::new(&mut |resolve, _| {
+        let _ = window().set_timeout_with_callback_and_timeout_and_arguments_0(
+            &resolve,
+            duration.as_millis() as i32,
+        );
+    });
+    let _ = JsFuture::from(promise).await;
+}
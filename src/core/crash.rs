@@ -0,0 +1,160 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use chrono::Utc;
+use web_sys::wasm_bindgen::{closure::Closure, JsCast};
+
+use crate::{core::clipboard::copy_to_clipboard, VERSION_NAME};
+
+/// Number of API calls included in diagnostic reports.
+const MAX_RECENT_REQUESTS: usize = 20;
+
+thread_local! {
+    static RECENT_REQUESTS: RefCell<VecDeque<String>> = const { RefCell::new(VecDeque::new()) };
+}
+
+static HAS_CRASHED: AtomicBool = AtomicBool::new(false);
+
+/// Installs a panic hook that replaces the application with a recovery
+/// screen. The screen is plain HTML rendered without Leptos, which can no
+/// longer be trusted after a panic.
+pub fn init_crash_reporter() {
+    std::panic::set_hook(Box::new(|info| {
+        console_error_panic_hook::hook(info);
+
+        if !HAS_CRASHED.swap(true, Ordering::SeqCst) {
+            if let Some(body) = web_sys::window()
+                .and_then(|window| window.document())
+                .and_then(|document| document.body())
+            {
+                body.set_class_name("dark:bg-slate-900 bg-gray-100 h-full");
+                body.set_inner_html(&crash_screen_html(&diagnostic_report(&info.to_string())));
+                attach_crash_screen_listeners(&body);
+            }
+        }
+    }));
+}
+
+/// Remembers an API call so it can be included in diagnostic reports. Only
+/// the path is kept, query strings may contain credentials.
+pub(crate) fn record_request(method: &str, path: &str, outcome: &str) {
+    let _ = RECENT_REQUESTS.try_with(|requests| {
+        if let Ok(mut requests) = requests.try_borrow_mut() {
+            if requests.len() >= MAX_RECENT_REQUESTS {
+                requests.pop_front();
+            }
+            requests.push_back(format!(
+                "{} {method} {path} {outcome}",
+                Utc::now().to_rfc3339()
+            ));
+        }
+    });
+}
+
+/// Builds a plain text report with the version, route and last API calls.
+pub fn diagnostic_report(error: &str) -> String {
+    let window = web_sys::window();
+    let route = window
+        .as_ref()
+        .and_then(|window| window.location().pathname().ok())
+        .unwrap_or_default();
+    let user_agent = window
+        .as_ref()
+        .and_then(|window| window.navigator().user_agent().ok())
+        .unwrap_or_default();
+    let requests = RECENT_REQUESTS
+        .try_with(|requests| {
+            requests
+                .try_borrow()
+                .map(|requests| requests.iter().cloned().collect::<Vec<_>>().join("\n"))
+                .unwrap_or_default()
+        })
+        .unwrap_or_default();
+
+    format!(
+        concat!(
+            "Version: {}\n",
+            "Time: {}\n",
+            "Route: {}\n",
+            "User agent: {}\n\n",
+            "Error:\n{}\n\n",
+            "Recent API calls:\n{}\n"
+        ),
+        VERSION_NAME,
+        Utc::now().to_rfc3339(),
+        route,
+        user_agent,
+        error,
+        if requests.is_empty() {
+            "None"
+        } else {
+            &requests
+        },
+    )
+}
+
+/// Renders the recovery screen, its buttons are wired up by
+/// [`attach_crash_screen_listeners`].
+pub fn crash_screen_html(report: &str) -> String {
+    let report = report
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+
+    format!(
+        r#"<div class="max-w-[50rem] flex flex-col mx-auto size-full py-16 px-4 sm:px-6 lg:px-8">
+    <div class="text-center">
+        <h1 class="block text-2xl font-bold text-gray-800 sm:text-4xl dark:text-white">Something went wrong</h1>
+        <p class="mt-3 text-gray-600 dark:text-gray-400">The management interface stopped working because of an unexpected error.</p>
+        <p class="text-gray-600 dark:text-gray-400">Reloading the page usually fixes it. If the problem persists, please include the report below when contacting support.</p>
+    </div>
+    <textarea id="crash-report" readonly rows="14" class="mt-6 py-2 px-3 block w-full border-gray-200 rounded-lg text-xs font-mono dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400">{report}</textarea>
+    <div class="mt-5 flex flex-col justify-center items-center gap-2 sm:flex-row sm:gap-3">
+        <button type="button" class="w-full sm:w-auto py-3 px-4 inline-flex justify-center items-center gap-x-2 text-sm font-semibold rounded-lg border border-gray-200 bg-white text-gray-800 shadow-sm hover:bg-gray-50 dark:bg-slate-900 dark:border-gray-700 dark:text-white dark:hover:bg-gray-800" id="crash-copy">Copy diagnostic report</button>
+        <button type="button" class="w-full sm:w-auto py-3 px-4 inline-flex justify-center items-center gap-x-2 text-sm font-semibold rounded-lg border border-transparent bg-blue-600 text-white hover:bg-blue-700" id="crash-reload">Reload</button>
+    </div>
+</div>"#
+    )
+}
+
+/// Handles the buttons of a recovery screen rendered inside `container`.
+pub fn attach_crash_screen_listeners(container: &web_sys::Element) {
+    let button = |id: &str| container.query_selector(&format!("#{id}")).ok().flatten();
+
+    if let Some(copy) = button("crash-copy") {
+        let report = container
+            .query_selector("#crash-report")
+            .ok()
+            .flatten()
+            .and_then(|report| report.dyn_into::<web_sys::HtmlTextAreaElement>().ok());
+        let copy_ = copy.clone();
+        let on_click = Closure::<dyn Fn()>::new(move || {
+            if let Some(report) = &report {
+                if copy_to_clipboard(&report.value()) {
+                    copy_.set_text_content(Some("Copied"));
+                }
+            }
+        });
+        let _ = copy.add_event_listener_with_callback("click", on_click.as_ref().unchecked_ref());
+        on_click.forget();
+    }
+
+    if let Some(reload) = button("crash-reload") {
+        let on_click = Closure::<dyn Fn()>::new(|| {
+            if let Some(window) = web_sys::window() {
+                let _ = window.location().reload();
+            }
+        });
+        let _ = reload.add_event_listener_with_callback("click", on_click.as_ref().unchecked_ref());
+        on_click.forget();
+    }
+}
@@ -0,0 +1,575 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+//! Mock API served when the `demo` feature is enabled, so the interface can
+//! run without a Stalwart server. The generated data is deterministic, which
+//! keeps lists and detail pages consistent with each other. Writes succeed
+//! but are not persisted.
+
+use std::{collections::BTreeMap, net::IpAddr};
+
+use ahash::AHashMap;
+use chrono::{DateTime, Duration, Utc};
+use gloo_net::http::Method;
+use serde::Serialize;
+
+use crate::{
+    core::oauth::{OAuthCodeRequest, OAuthCodeResponse, OAuthGrant},
+    pages::{
+        directory::{domains::DnsRecord, Principal, PrincipalType},
+        manage::dashboard::{MetricValues, MetricsHistory},
+        queue::{
+            messages::{Domain, Message, Recipient, Status},
+            reports::{
+                test_dmarc_report, test_tls_report, AggregateReport, AuthFailureType,
+                DeliveryResult, Feedback, FeedbackType, IdentityAlignment, ReportUri, URI,
+            },
+        },
+        reports::IncomingReport,
+        List,
+    },
+};
+
+use super::http::{Error, Result};
+
+const DOMAINS: &[&str] = &["example.org", "example.com", "example.net"];
+const FIRST_NAMES: &[&str] = &[
+    "john", "jane", "mike", "mary", "tom", "lisa", "bill", "anna", "mark", "sara", "paul", "emma",
+    "alex", "nina", "eric", "kate", "ivan", "olga", "luis", "ines", "hugo", "lena", "omar", "zoe",
+];
+const LAST_NAMES: &[&str] = &[
+    "Smith", "Garcia", "Miller", "Brown", "Jones", "Davis", "Wilson", "Moore", "Taylor", "Clark",
+];
+const GROUPS: &[&str] = &["sales", "support", "engineering", "marketing", "finance"];
+const LISTS: &[&str] = &["everyone", "announcements", "newsletter"];
+const RESOURCES: &[&str] = &["projector", "company-car", "laptop-pool"];
+const LOCATIONS: &[&str] = &["room-101", "room-102", "board-room"];
+const REMOTE_DOMAINS: &[&str] = &["gmail.com", "outlook.com", "yahoo.com", "fastmail.com"];
+const REPORTERS: &[(&str, &str)] = &[
+    ("google.com", "noreply-dmarc-support@google.com"),
+    ("outlook.com", "dmarcreport@microsoft.com"),
+    ("yahoo.com", "dmarchelp@yahooinc.com"),
+];
+
+const TOTAL_MESSAGES: u64 = 57;
+const TOTAL_REPORTS: u64 = 24;
+const GB: u64 = 1024 * 1024 * 1024;
+
+/// Answers a request with generated data, or `NotFound` when the endpoint
+/// is not mocked.
+pub fn respond(method: &Method, url: &str, body: Option<&str>) -> Result<Vec<u8>> {
+    let url = url
+        .split_once("://")
+        .and_then(|(_, rest)| rest.find('/').map(|pos| &rest[pos..]))
+        .unwrap_or(url);
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let params = form_urlencoded::parse(query.as_bytes())
+        .into_owned()
+        .collect::<AHashMap<String, String>>();
+    let path = path
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            form_urlencoded::parse(part.as_bytes())
+                .next()
+                .map_or_else(|| part.to_string(), |(part, _)| part.into_owned())
+        })
+        .collect::<Vec<_>>();
+    let path = path.iter().map(String::as_str).collect::<Vec<_>>();
+    let is_read = *method == Method::GET;
+
+    match (is_read, path.as_slice()) {
+        // Authentication
+        (false, ["api", "oauth"]) => {
+            match body.and_then(|body| serde_json::from_str::<OAuthCodeRequest>(body).ok()) {
+                Some(OAuthCodeRequest::Device { .. }) => data(true),
+                _ => data(OAuthCodeResponse {
+                    code: "demo".to_string(),
+                    is_admin: true,
+                    permissions: vec![],
+                }),
+            }
+        }
+        (false, ["auth", "token"]) => serialize(&OAuthGrant {
+            access_token: "demo".to_string(),
+            token_type: "bearer".to_string(),
+            expires_in: 86400,
+            refresh_token: Some("demo".to_string()),
+            scope: None,
+        }),
+
+        // Directory
+        (true, ["api", "principal"]) => {
+            let typ = params.get("type").map(String::as_str);
+            let filter = params.get("filter").map(|filter| filter.to_lowercase());
+            let names = principals()
+                .into_iter()
+                .filter(|principal| {
+                    typ.is_none_or(|typ| principal.typ.unwrap_or_default().id() == typ)
+                        && filter.as_ref().is_none_or(|filter| {
+                            principal
+                                .name
+                                .as_deref()
+                                .unwrap_or_default()
+                                .contains(filter)
+                                || principal.emails.iter().any(|email| email.contains(filter))
+                        })
+                })
+                .filter_map(|principal| principal.name)
+                .collect();
+            data(paginate(names, &params))
+        }
+        (true, ["api", "principal", name]) => principals()
+            .into_iter()
+            .find(|principal| principal.name.as_deref() == Some(name))
+            .map_or(Err(Error::NotFound), data),
+        (false, ["api", "principal"]) => data(1000u32),
+        (true, ["api", "domain"]) => {
+            let filter = params.get("filter").map(|filter| filter.to_lowercase());
+            let domains = DOMAINS
+                .iter()
+                .filter(|domain| filter.as_ref().is_none_or(|f| domain.contains(f.as_str())))
+                .map(|domain| domain.to_string())
+                .collect();
+            data(paginate(domains, &params))
+        }
+        (true, ["api", "domain", name]) if DOMAINS.contains(name) => data(dns_records(name)),
+
+        // Queues
+        (true, ["api", "queue", "messages"]) => {
+            let text = params.get("text").map(|text| text.to_lowercase());
+            let on_hold = params.get("status").is_some_and(|status| status == "hold");
+            let messages = (0..TOTAL_MESSAGES)
+                .map(message)
+                .filter(|message| {
+                    (!on_hold || message.hold)
+                        && text.as_ref().is_none_or(|text| {
+                            message.return_path.contains(text.as_str())
+                                || message.domains.iter().any(|domain| {
+                                    domain
+                                        .recipients
+                                        .iter()
+                                        .any(|rcpt| rcpt.address.contains(text.as_str()))
+                                })
+                        })
+                })
+                .collect();
+            data(paginate(messages, &params))
+        }
+        (true, ["api", "queue", "messages", id]) => id
+            .parse::<u64>()
+            .ok()
+            .and_then(|id| id.checked_sub(1000))
+            .filter(|idx| *idx < TOTAL_MESSAGES)
+            .map_or(Err(Error::NotFound), |idx| data(message(idx))),
+        (true, ["api", "queue", "reports"]) => {
+            let filter = params.get("domain");
+            let ids = queued_report_ids()
+                .into_iter()
+                .filter(|id| filter.is_none_or(|filter| id.contains(filter.as_str())))
+                .collect();
+            data(paginate(ids, &params))
+        }
+        (true, ["api", "queue", "reports", id]) => {
+            queued_report(id).map_or(Err(Error::NotFound), data)
+        }
+        (false, ["api", "queue", ..]) => data(true),
+
+        // Incoming reports
+        (true, ["api", "reports", typ]) if ["dmarc", "tls", "arf"].contains(typ) => {
+            let now = Utc::now();
+            let ids = (0..TOTAL_REPORTS)
+                .map(|idx| {
+                    format!(
+                        "{idx}_{}",
+                        (now - Duration::hours(idx as i64 * 7)).timestamp()
+                    )
+                })
+                .collect();
+            data(paginate(ids, &params))
+        }
+        (true, ["api", "reports", typ, id]) => {
+            let idx = id
+                .split_once('_')
+                .and_then(|(idx, _)| idx.parse::<usize>().ok())
+                .unwrap_or_default();
+            let (reporter, from) = REPORTERS[idx % REPORTERS.len()];
+            let domain = DOMAINS[idx % DOMAINS.len()];
+            let to = vec![format!("postmaster@{domain}")];
+            match *typ {
+                "dmarc" => data(IncomingReport {
+                    from: from.to_string(),
+                    to,
+                    subject: format!("Report domain: {domain} Submitter: {reporter}"),
+                    report: test_dmarc_report(),
+                }),
+                "tls" => data(IncomingReport {
+                    from: from.to_string(),
+                    to,
+                    subject: format!("Report Domain: {domain} Submitter: {reporter}"),
+                    report: test_tls_report(),
+                }),
+                "arf" => data(IncomingReport {
+                    from: format!("abuse@{reporter}"),
+                    to,
+                    subject: format!("Abuse report for {domain}"),
+                    report: feedback(idx, domain),
+                }),
+                _ => Err(Error::NotFound),
+            }
+        }
+        (false, ["api", "reports", ..]) => data(true),
+
+        // Dashboard
+        (true, ["api", "telemetry", "metrics"]) => data(metrics(&params)),
+
+        // Settings are served empty, so every setting shows its default
+        (true, ["api", "settings", "group"]) => data(List::<AHashMap<String, String>>::default()),
+        (true, ["api", "settings", "list"]) => data(serde_json::json!({ "items": {}, "total": 0 })),
+        (true, ["api", "settings", "keys"]) => data(AHashMap::<String, String>::new()),
+
+        (true, _) => Err(Error::NotFound),
+        (false, _) => data(()),
+    }
+}
+
+fn data(data: impl Serialize) -> Result<Vec<u8>> {
+    serialize(&serde_json::json!({ "data": data }))
+}
+
+fn serialize(value: &impl Serialize) -> Result<Vec<u8>> {
+    serde_json::to_vec(value).map_err(Into::into)
+}
+
+fn paginate<T>(items: Vec<T>, params: &AHashMap<String, String>) -> List<T> {
+    let total = items.len() as u64;
+    let page = params
+        .get("page")
+        .and_then(|page| page.parse::<usize>().ok())
+        .unwrap_or(1)
+        .max(1);
+    let items = match params
+        .get("limit")
+        .and_then(|limit| limit.parse::<usize>().ok())
+    {
+        Some(limit) if limit > 0 => items
+            .into_iter()
+            .skip((page - 1) * limit)
+            .take(limit)
+            .collect(),
+        _ => items,
+    };
+
+    List { items, total }
+}
+
+fn principals() -> Vec<Principal> {
+    let mut principals = vec![Principal {
+        id: Some(0),
+        typ: Some(PrincipalType::Superuser),
+        name: Some("admin".to_string()),
+        secrets: vec!["$6$demo".to_string()],
+        emails: vec!["admin@example.org".to_string()],
+        description: Some("System administrator".to_string()),
+        ..Default::default()
+    }];
+
+    for (idx, name) in FIRST_NAMES.iter().enumerate() {
+        let domain = DOMAINS[idx % DOMAINS.len()];
+        let last_name = LAST_NAMES[idx % LAST_NAMES.len()];
+        let quota = [1, 2, 5, 10][idx % 4] * GB;
+        principals.push(Principal {
+            id: Some(principals.len() as u32),
+            typ: Some(PrincipalType::Individual),
+            quota: Some(quota),
+            used_quota: Some(quota / 100 * ((idx as u64 * 37) % 100)),
+            name: Some(name.to_string()),
+            secrets: vec!["$6$demo".to_string()],
+            emails: vec![
+                format!("{name}@{domain}"),
+                format!("{name}.{}@{domain}", last_name.to_lowercase()),
+            ],
+            member_of: vec![GROUPS[idx % GROUPS.len()].to_string()],
+            description: Some(format!(
+                "{}{} {last_name}",
+                name[..1].to_uppercase(),
+                &name[1..]
+            )),
+            ..Default::default()
+        });
+    }
+
+    for (typ, names) in [
+        (PrincipalType::Group, GROUPS),
+        (PrincipalType::List, LISTS),
+        (PrincipalType::Resource, RESOURCES),
+        (PrincipalType::Location, LOCATIONS),
+    ] {
+        for (idx, name) in names.iter().enumerate() {
+            let members = match typ {
+                PrincipalType::Group | PrincipalType::List => FIRST_NAMES
+                    .iter()
+                    .skip(idx)
+                    .step_by(names.len())
+                    .map(|name| name.to_string())
+                    .collect(),
+                _ => vec![],
+            };
+            principals.push(Principal {
+                id: Some(principals.len() as u32),
+                typ: Some(typ),
+                name: Some(name.to_string()),
+                emails: vec![format!("{name}@{}", DOMAINS[0])],
+                members,
+                external_members: if typ == PrincipalType::List {
+                    vec![format!(
+                        "partner@{}",
+                        REMOTE_DOMAINS[idx % REMOTE_DOMAINS.len()]
+                    )]
+                } else {
+                    vec![]
+                },
+                description: Some(format!("{} {}", typ.name(), name.replace('-', " "))),
+                ..Default::default()
+            });
+        }
+    }
+
+    principals
+}
+
+fn dns_records(domain: &str) -> Vec<DnsRecord> {
+    [
+        ("MX", format!("{domain}."), format!("10 mail.{domain}.")),
+        ("TXT", format!("{domain}."), "v=spf1 mx -all".to_string()),
+        (
+            "TXT",
+            format!("_dmarc.{domain}."),
+            format!("v=DMARC1; p=reject; rua=mailto:postmaster@{domain}"),
+        ),
+        (
+            "TXT",
+            format!("default._domainkey.{domain}."),
+            "v=DKIM1; k=ed25519; p=11qYAYKxCrfVS/7TyWQHOg7hcvPapiMlrwIaaPcHURo=".to_string(),
+        ),
+        (
+            "CNAME",
+            format!("autoconfig.{domain}."),
+            format!("mail.{domain}."),
+        ),
+        (
+            "SRV",
+            format!("_imaps._tcp.{domain}."),
+            format!("0 1 993 mail.{domain}."),
+        ),
+    ]
+    .into_iter()
+    .map(|(typ, name, content)| DnsRecord {
+        typ: typ.to_string(),
+        name,
+        content,
+    })
+    .collect()
+}
+
+fn message(idx: u64) -> Message {
+    let now = Utc::now();
+    let created = now - Duration::minutes(idx as i64 * 17);
+    let sender = FIRST_NAMES[idx as usize % FIRST_NAMES.len()];
+    let domains = (0..=(idx % 2))
+        .map(|offset| {
+            let pos = (idx + offset) as usize;
+            let name = REMOTE_DOMAINS[pos % REMOTE_DOMAINS.len()];
+            let status = match pos % 4 {
+                0 => Status::Scheduled,
+                1 => Status::TemporaryFailure(format!("Connection to mx.{name} failed: timed out")),
+                2 => Status::Completed("250 2.0.0 Message accepted".to_string()),
+                _ => Status::PermanentFailure(
+                    "550 5.1.1 The email account does not exist".to_string(),
+                ),
+            };
+            let retry_num = (pos % 5) as u32;
+
+            Domain {
+                name: name.to_string(),
+                recipients: vec![Recipient {
+                    address: format!("{}@{name}", FIRST_NAMES[(pos * 7) % FIRST_NAMES.len()]),
+                    status: status.clone(),
+                    orcpt: None,
+                    queue: None,
+                }],
+                next_retry: matches!(status, Status::Scheduled | Status::TemporaryFailure(_))
+                    .then(|| now + Duration::minutes(5 << retry_num)),
+                next_notify: Some(created + Duration::days(1)),
+                expires: created + Duration::days(5),
+                status,
+                retry_num,
+            }
+        })
+        .collect();
+
+    Message {
+        id: 1000 + idx,
+        return_path: format!("{sender}@{}", DOMAINS[idx as usize % DOMAINS.len()]),
+        domains,
+        created,
+        size: 2048 + (idx as usize * 7919) % 250_000,
+        priority: 0,
+        env_id: None,
+        blob_hash: format!("{:064x}", idx * 0x9e37_79b9_7f4a_7c15),
+        hold: idx % 11 == 5,
+    }
+}
+
+fn queued_report_ids() -> Vec<String> {
+    let now = Utc::now().timestamp();
+    DOMAINS
+        .iter()
+        .enumerate()
+        .flat_map(|(idx, domain)| {
+            let created = now - (idx as i64 + 1) * 3600;
+            [
+                format!("d!{domain}!{}!{created}!{}", 1000 + idx, created + 86400),
+                format!("t!{domain}!{}!{created}!{}", 2000 + idx, created + 86400),
+            ]
+        })
+        .collect()
+}
+
+fn queued_report(id: &str) -> Option<AggregateReport> {
+    let mut parts = id.split('!');
+    let typ = parts.next()?;
+    let domain = parts.next()?.to_string();
+    let _policy = parts.next()?;
+    let range_from = DateTime::from_timestamp(parts.next()?.parse().ok()?, 0)?;
+    let range_to = DateTime::from_timestamp(parts.next()?.parse().ok()?, 0)?;
+    let id = id.to_string();
+
+    match typ {
+        "d" => Some(AggregateReport::Dmarc {
+            rua: vec![URI {
+                uri: format!("mailto:dmarc@{domain}"),
+                max_size: 0,
+            }],
+            id,
+            domain,
+            range_from,
+            range_to,
+            report: test_dmarc_report(),
+        }),
+        "t" => Some(AggregateReport::Tls {
+            rua: vec![ReportUri::Mail(format!("tls-reports@{domain}"))],
+            id,
+            domain,
+            range_from,
+            range_to,
+            report: test_tls_report(),
+        }),
+        _ => None,
+    }
+}
+
+fn feedback(idx: usize, domain: &str) -> Feedback {
+    let source_ip = format!("203.0.113.{}", idx % 250 + 1)
+        .parse::<IpAddr>()
+        .ok();
+    let sender = FIRST_NAMES[idx % FIRST_NAMES.len()];
+    let recipient = FIRST_NAMES[(idx * 7) % FIRST_NAMES.len()];
+    let remote = REMOTE_DOMAINS[idx % REMOTE_DOMAINS.len()];
+    let is_auth_failure = idx.is_multiple_of(3);
+    let headers = format!(
+        concat!(
+            "From: {sender}@{domain}\r\n",
+            "To: {recipient}@{remote}\r\n",
+            "Subject: Limited time offer\r\n",
+            "Message-ID: <{idx}.demo@{domain}>\r\n\r\n"
+        ),
+        sender = sender,
+        domain = domain,
+        recipient = recipient,
+        remote = remote,
+        idx = idx,
+    );
+
+    Feedback {
+        feedback_type: if is_auth_failure {
+            FeedbackType::AuthFailure
+        } else {
+            FeedbackType::Abuse
+        },
+        arrival_date: Some((Utc::now() - Duration::hours(idx as i64 * 7)).timestamp()),
+        authentication_results: vec![format!(
+            "mx.{remote}; spf=pass smtp.mailfrom={domain}; dkim=pass header.d={domain}"
+        )],
+        incidents: (idx % 4) as u32 + 1,
+        original_mail_from: Some(format!("{sender}@{domain}")),
+        original_rcpt_to: Some(format!("{recipient}@{remote}")),
+        reported_domain: vec![domain.to_string()],
+        reporting_mta: Some(format!("mx.{remote}")),
+        source_ip,
+        user_agent: Some("DemoFBL/1.0".to_string()),
+        version: 1,
+        auth_failure: if is_auth_failure {
+            AuthFailureType::Dmarc
+        } else {
+            AuthFailureType::Unspecified
+        },
+        delivery_result: DeliveryResult::Delivered,
+        identity_alignment: IdentityAlignment::DkimSpf,
+        headers: Some(headers),
+        ..Default::default()
+    }
+}
+
+fn metrics(params: &AHashMap<String, String>) -> MetricsHistory {
+    const MAX_POINTS: i64 = 48;
+
+    let now = Utc::now();
+    let after = params
+        .get("after")
+        .and_then(|after| DateTime::parse_from_rfc3339(after).ok())
+        .map_or_else(|| now - Duration::hours(24), |after| after.to_utc());
+    let step = ((now - after).num_seconds() / MAX_POINTS).max(3600);
+    let timestamps = (0..)
+        .map(|pos| after + Duration::seconds(pos * step))
+        .take_while(|timestamp| *timestamp <= now)
+        .collect::<Vec<_>>();
+    let nodes = if params.get("group_by").is_some_and(|group| group == "node") {
+        vec![Some(1), Some(2), Some(3)]
+    } else {
+        vec![params.get("node").and_then(|node| node.parse::<u64>().ok())]
+    };
+
+    let metrics = params
+        .get("metrics")
+        .into_iter()
+        .flat_map(|metrics| metrics.split(','))
+        .filter(|id| !id.is_empty())
+        .flat_map(|id| {
+            let seed = id.bytes().map(u64::from).sum::<u64>();
+            let base = (seed % 90 + 10) as f64;
+            let timestamps = &timestamps;
+            nodes.iter().map(move |node_id| MetricValues {
+                id: id.to_string(),
+                node_id: *node_id,
+                labels: BTreeMap::new(),
+                values: timestamps
+                    .iter()
+                    .map(|timestamp| {
+                        let hour = timestamp.timestamp() as f64 / 3600.0;
+                        let node = node_id.unwrap_or(1) as f64;
+                        (base * node * (1.5 + (hour / 24.0 * std::f64::consts::TAU).sin())).round()
+                    })
+                    .collect(),
+            })
+        })
+        .collect();
+
+    MetricsHistory {
+        timestamps,
+        metrics,
+    }
+}
@@ -0,0 +1,65 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use serde::Deserialize;
+
+use super::http::HttpRequest;
+
+const DOH_URL: &str = "https://cloudflare-dns.com/dns-query";
+
+pub const RCODE_NO_ERROR: u32 = 0;
+pub const RCODE_NX_DOMAIN: u32 = 3;
+pub const TYPE_TXT: u32 = 16;
+pub const TYPE_MX: u32 = 15;
+
+#[derive(Debug, Deserialize)]
+pub struct DohResponse {
+    #[serde(rename = "Status")]
+    pub status: u32,
+    #[serde(rename = "Answer")]
+    #[serde(default)]
+    pub answer: Vec<DohAnswer>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DohAnswer {
+    #[serde(rename = "type")]
+    pub typ: u32,
+    #[serde(default)]
+    pub data: String,
+}
+
+/// Resolves a record using DNS-over-HTTPS from the browser, which shows what
+/// the rest of the world sees rather than what the server resolver caches.
+pub async fn doh_lookup(name: &str, typ: &str) -> Result<DohResponse, String> {
+    let bytes = HttpRequest::get(DOH_URL)
+        .with_parameter("name", name.trim_end_matches('.'))
+        .with_parameter("type", typ)
+        .with_header("accept", "application/dns-json")
+        .send_raw()
+        .await
+        .map_err(|err| format!("{err:?}"))?;
+    serde_json::from_slice::<DohResponse>(&bytes).map_err(|err| err.to_string())
+}
+
+impl DohResponse {
+    /// Returns the TXT records in the answer with their character strings
+    /// joined.
+    pub fn txt_records(&self) -> Vec<String> {
+        self.answer
+            .iter()
+            .filter(|answer| answer.typ == TYPE_TXT)
+            .map(|answer| {
+                let data = answer.data.trim();
+                if data.starts_with('"') {
+                    data.split('"').skip(1).step_by(2).collect::<String>()
+                } else {
+                    data.to_string()
+                }
+            })
+            .collect()
+    }
+}
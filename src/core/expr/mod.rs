@@ -347,3 +347,14 @@ impl ParseValue for Duration {
         })
     }
 }
+
+/// Writes a value as a single-quoted string literal.
+pub fn quote_string(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// Reverses the escaping applied by [`quote_string`] to the contents of a
+/// string literal.
+pub fn unquote_string(value: &str) -> String {
+    value.replace("\\'", "'").replace("\\\\", "\\")
+}
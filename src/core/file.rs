@@ -0,0 +1,57 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::Duration;
+
+use leptos::{document, set_timeout};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    js_sys::{Array, Uint8Array},
+    wasm_bindgen::{JsCast, JsValue},
+    Blob, BlobPropertyBag, File, HtmlAnchorElement, Url,
+};
+
+/// Reads the contents of a file selected by the user.
+pub async fn read_file(file: &File) -> Result<Vec<u8>, String> {
+    JsFuture::from(file.array_buffer())
+        .await
+        .map(|buffer| Uint8Array::new(&buffer).to_vec())
+        .map_err(|err| {
+            err.as_string()
+                .unwrap_or_else(|| format!("Failed to read {}", file.name()))
+        })
+}
+
+/// Offers the given contents to the user as a file download.
+pub fn download_file(file_name: &str, content_type: &str, contents: &str) -> Result<(), String> {
+    let parts = Array::of1(&JsValue::from_str(contents));
+    let mut options = BlobPropertyBag::new();
+    options.type_(content_type);
+    let blob = Blob::new_with_str_sequence_and_options(&parts, &options)
+        .map_err(|_| "Failed to create file".to_string())?;
+    let url = Url::create_object_url_with_blob(&blob)
+        .map_err(|_| "Failed to create download URL".to_string())?;
+    download_url(file_name, &url)?;
+    set_timeout(
+        move || {
+            let _ = Url::revoke_object_url(&url);
+        },
+        Duration::from_secs(1),
+    );
+    Ok(())
+}
+
+/// Offers the resource at the given URL to the user as a file download.
+pub fn download_url(file_name: &str, url: &str) -> Result<(), String> {
+    let anchor = document()
+        .create_element("a")
+        .map_err(|_| "Failed to create download link".to_string())?
+        .unchecked_into::<HtmlAnchorElement>();
+    anchor.set_href(url);
+    anchor.set_download(file_name);
+    anchor.click();
+    Ok(())
+}
@@ -11,15 +11,13 @@ use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 
-use ahash::AHashMap;
+use ahash::{AHashMap, AHashSet};
 use leptos::RwSignal;
 
 use crate::pages::config::{Settings, SettingsValues};
 
-use super::expr::parser::ExpressionParser;
-use super::expr::tokenizer::Tokenizer;
-use super::expr::{Constant, ParseValue, Token};
-use super::schema::{NumberType, SchemaType, Type};
+use super::expr::ParseValue;
+use super::schema::{Constraint, NumberType, SchemaType, Section, Type};
 
 use super::schema::{InputCheck, Schema, Transformer, Validator};
 
@@ -32,6 +30,9 @@ pub struct FormData {
     pub external_sources: Arc<ExternalSources>,
     pub schema: Arc<Schema>,
     pub is_update: bool,
+    pub snapshot: Option<Arc<AHashMap<String, FormValue>>>,
+    pub defaulted: AHashSet<String>,
+    pub violations: Vec<Constraint>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -107,6 +108,67 @@ impl FormData {
         );
     }
 
+    /// Records the current values as the saved state of the form.
+    pub fn mark_clean(&mut self) {
+        self.snapshot = Some(Arc::new(self.values.clone()));
+    }
+
+    /// Returns `true` if any value differs from the last saved state.
+    pub fn is_dirty(&self) -> bool {
+        let Some(snapshot) = &self.snapshot else {
+            return false;
+        };
+
+        // Treat empty values the same as missing ones
+        let is_empty = |value: &FormValue| match value {
+            FormValue::Value(v) => v.is_empty(),
+            FormValue::Array(v) => v.is_empty(),
+            FormValue::Expression(v) => v.is_empty(),
+        };
+        self.values
+            .iter()
+            .any(|(id, value)| match snapshot.get(id) {
+                Some(saved) => saved != value,
+                None => !is_empty(value),
+            })
+            || snapshot
+                .iter()
+                .any(|(id, saved)| !self.values.contains_key(id) && !is_empty(saved))
+    }
+
+    /// Returns `true` if the stored record no longer matches the values it
+    /// had when the form was loaded, meaning that someone else changed it.
+    pub fn is_outdated(&self, latest: &FormData) -> bool {
+        self.snapshot
+            .as_ref()
+            .is_some_and(|snapshot| **snapshot != latest.values)
+    }
+
+    /// Moves the unsaved changes on top of the latest stored version of the
+    /// record. Values edited since loading the form are kept, all others are
+    /// replaced with the stored ones.
+    pub fn rebase(&mut self, latest: FormData) {
+        let snapshot = self.snapshot.take().unwrap_or_default();
+        let mut values = latest.values.clone();
+        for id in self.values.keys().chain(snapshot.keys()) {
+            let value = self.values.get(id);
+            if value != snapshot.get(id) {
+                match value {
+                    Some(value) => {
+                        values.insert(id.clone(), value.clone());
+                    }
+                    None => {
+                        values.remove(id);
+                    }
+                }
+            }
+        }
+        self.values = values;
+        self.snapshot = Some(Arc::new(latest.values));
+        self.errors.clear();
+        self.violations.clear();
+    }
+
     pub fn value<T: FromStr>(&self, id: &str) -> Option<T> {
         self.values.get(id).and_then(|v| match v {
             FormValue::Value(v) => T::from_str(v.as_str()).ok(),
@@ -147,6 +209,40 @@ impl FormData {
         self.errors.remove(id);
     }
 
+    /// Returns the schema default of a field, if any.
+    pub fn default_value(&self, id: &str) -> Option<FormValue> {
+        let field = self.schema.fields.get(id)?;
+        Some(match (&field.typ_, field.default(self)?) {
+            (Type::Expression, FormValue::Value(default)) => FormValue::Expression(Expression {
+                else_: default.to_string(),
+                ..Default::default()
+            }),
+            (_, default) => default.clone(),
+        })
+    }
+
+    /// Restores the schema default of a field and removes its key on save,
+    /// so that the server default applies.
+    pub fn reset_to_default(&mut self, id: &str) {
+        match self.default_value(id) {
+            Some(default) => {
+                self.set(id, default);
+                self.errors.remove(id);
+            }
+            None => self.remove(id),
+        }
+        self.defaulted.insert(id.to_string());
+    }
+
+    /// Returns `true` if the field was reset and still holds its default.
+    pub fn uses_default(&self, id: &str) -> bool {
+        self.defaulted.contains(id)
+            && match self.default_value(id) {
+                Some(default) => self.values.get(id) == Some(&default),
+                None => self.value_is_empty(id),
+            }
+    }
+
     pub fn reset(&mut self) {
         self.values.clear();
         self.errors.clear();
@@ -221,6 +317,31 @@ impl FormData {
         self.errors.remove(id);
     }
 
+    pub fn array_insert(&mut self, id: &str, idx: usize, value: impl Into<String>) {
+        let v = self
+            .values
+            .entry(id.to_string())
+            .or_insert_with(|| FormValue::Array(vec![]));
+
+        if let FormValue::Value(val) = v {
+            *v = FormValue::Array(vec![std::mem::take(val)]);
+        }
+        if let FormValue::Array(arr) = v {
+            arr.insert(idx.min(arr.len()), value.into());
+        }
+        self.errors.remove(id);
+    }
+
+    pub fn array_move(&mut self, id: &str, from: usize, to: usize) {
+        if let Some(FormValue::Array(values)) = self.values.get_mut(id) {
+            if from < values.len() && to < values.len() && from != to {
+                let value = values.remove(from);
+                values.insert(to, value);
+                self.errors.remove(id);
+            }
+        }
+    }
+
     pub fn expr_if_thens<'x>(
         &'x self,
         id: &str,
@@ -443,6 +564,7 @@ impl FormData {
     }
 
     pub fn validate_form(&mut self) -> bool {
+        self.violations.clear();
         if !self.errors.is_empty() {
             log::debug!("Skipping validation, form has errors: {:#?}", self.errors);
             return false;
@@ -561,28 +683,7 @@ impl FormData {
                                 })
                                 .chain([(ExpressionError::Else, &expr.else_)])
                             {
-                                match ExpressionParser::new(Tokenizer::new(expr_value, |token| {
-                                    if validator.variables.contains(&token) {
-                                        Ok(Token::Variable(0))
-                                    } else if validator.constants.contains(&token) {
-                                        Ok(Token::Constant(Constant::Integer(0)))
-                                    } else {
-                                        Duration::parse_value(token)
-                                            .map(|d| {
-                                                Token::Constant(Constant::Integer(
-                                                    d.as_secs() as i64
-                                                ))
-                                            })
-                                            .ok_or_else(|| {
-                                                format!(
-                                                    "Invalid variable or function name {:?}",
-                                                    token
-                                                )
-                                            })
-                                    }
-                                }))
-                                .parse()
-                                {
+                                match validator.parse(expr_value) {
                                     Ok(expr) => {
                                         if matches!(expr_item, ExpressionError::Else) {
                                             has_expression = true;
@@ -628,7 +729,57 @@ impl FormData {
             }
         }
 
-        self.errors.is_empty()
+        // Cross-field rules are only meaningful once every field is valid
+        if self.errors.is_empty() {
+            for section in &schema.form.sections {
+                if section.display(self) {
+                    for constraint in &section.constraints {
+                        if constraint.check(self).is_err() {
+                            self.violations.push(*constraint);
+                        }
+                    }
+                }
+            }
+        }
+
+        self.errors.is_empty() && self.violations.is_empty()
+    }
+
+    /// Rules of a section that failed the last validation and have not
+    /// been fixed since.
+    pub fn section_errors(&self, section: &Section) -> Vec<String> {
+        section
+            .constraints
+            .iter()
+            .filter(|constraint| self.violations.contains(constraint))
+            .filter_map(|constraint| constraint.check(self).err())
+            .collect()
+    }
+
+    /// Durations of a field holding a single duration or a list of them,
+    /// such as "5d" or "[2m, 5m, 10m]".
+    fn durations(&self, id: &str) -> Option<Vec<Duration>> {
+        let value = match self.values.get(id)? {
+            FormValue::Value(value) => value,
+            FormValue::Expression(expr) if expr.if_thens.is_empty() => &expr.else_,
+            _ => return None,
+        };
+        value
+            .trim()
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .split(',')
+            .map(|value| Duration::parse_value(value.trim()))
+            .collect()
+    }
+
+    fn is_set(&self, id: &str) -> bool {
+        match self.values.get(id) {
+            Some(FormValue::Value(value)) => !value.is_empty(),
+            Some(FormValue::Array(values)) => !values.is_empty(),
+            Some(FormValue::Expression(expr)) => !expr.is_empty(),
+            None => false,
+        }
     }
 
     pub fn from_settings(schema: Arc<Schema>, settings: Option<Settings>) -> Self {
@@ -758,6 +909,54 @@ impl FormData {
     }
 }
 
+impl Constraint {
+    pub fn check(&self, data: &FormData) -> Result<(), String> {
+        let label = |id: &str| {
+            data.schema
+                .fields
+                .get(id)
+                .map_or(id, |field| field.label_form)
+                .to_string()
+        };
+
+        match *self {
+            Constraint::RequiredWith { field, other } => {
+                if data.is_set(other) && !data.is_set(field) {
+                    return Err(format!(
+                        "{} is required when {} is set",
+                        label(field),
+                        label(other)
+                    ));
+                }
+            }
+            Constraint::LongerThan { field, other } => {
+                let (Some(durations), Some(others)) =
+                    (data.durations(field), data.durations(other))
+                else {
+                    return Ok(());
+                };
+                if let (Some(shortest), Some(longest)) =
+                    (durations.iter().min(), others.iter().max())
+                {
+                    if shortest <= longest {
+                        return Err(if others.len() > 1 {
+                            format!(
+                                "{} must be longer than the last {} interval",
+                                label(field),
+                                label(other)
+                            )
+                        } else {
+                            format!("{} must be longer than {}", label(field), label(other))
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl InputCheck {
     pub fn check_value(&self, mut value: String) -> Result<String, Cow<'static, str>> {
         for transformer in &self.transformers {
@@ -871,10 +1070,12 @@ impl InputCheck {
                             return Err("This field must be a valid IP address or network".into());
                         }
                     }
-                    Validator::IsValidExpression { .. }
-                    | Validator::MinItems(_)
-                    | Validator::MaxItems(_)
-                    | Validator::Required => (),
+                    Validator::IsValidExpression(validator) => {
+                        if let Err(err) = validator.parse(&value) {
+                            return Err(err.into());
+                        }
+                    }
+                    Validator::MinItems(_) | Validator::MaxItems(_) | Validator::Required => (),
                 }
             }
         } else if self.validators.contains(&Validator::Required) {
@@ -952,6 +1153,21 @@ impl ExpressionIfThen {
     }
 }
 
+impl FormValue {
+    pub fn to_display_string(&self) -> String {
+        match self {
+            FormValue::Value(value) => value.clone(),
+            FormValue::Array(values) => values.join(", "),
+            FormValue::Expression(expr) if expr.if_thens.is_empty() => expr.else_.clone(),
+            FormValue::Expression(expr) => format!(
+                "{} conditions, otherwise {}",
+                expr.if_thens.len(),
+                expr.else_
+            ),
+        }
+    }
+}
+
 impl Default for FormValue {
     fn default() -> Self {
         FormValue::Value("".to_string())
@@ -6,16 +6,26 @@
 
 use ahash::AHashMap;
 use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Utc;
 use gloo_net::http::{Headers, Method, RequestBuilder};
+use leptos::{RwSignal, SignalWithUntracked};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use web_time::Instant;
 
-use super::{oauth::AuthToken, url::UrlBuilder};
+use super::{
+    connection::{self, MAX_RETRIES},
+    crash,
+    oauth::AuthToken,
+    request_log::{self, RequestLog, RequestLogEntry},
+    url::UrlBuilder,
+};
 
 pub struct HttpRequest {
     method: Method,
     url: UrlBuilder,
     headers: Headers,
     body: Option<String>,
+    is_management: bool,
 }
 
 #[derive(Deserialize)]
@@ -43,6 +53,8 @@ pub enum Error {
     Forbidden,
     NotFound,
     Network(String),
+    Unavailable(String),
+    RetriesExhausted(String),
     Serializer { error: String, response: String },
     Server(ManagementApiError),
 }
@@ -60,6 +72,7 @@ impl<'x> HttpRequest {
             url: url.into_url_builder(),
             headers: Headers::new(),
             body: None,
+            is_management: false,
         }
     }
 
@@ -111,6 +124,7 @@ impl<'x> HttpRequest {
         if !auth_token.base_url.is_empty() {
             result.url.prepend_path(auth_token.base_url.as_str());
         }
+        result.is_management = true;
         result
     }
 
@@ -119,6 +133,7 @@ impl<'x> HttpRequest {
         if !auth_token.base_url.is_empty() {
             self.url.prepend_path(auth_token.base_url.as_str());
         }
+        self.is_management = true;
         self
     }
 
@@ -185,7 +200,26 @@ impl<'x> HttpRequest {
         }
     }
 
+    /// Sends the request, or answers it from the mock API when the `demo`
+    /// feature is enabled.
     pub async fn send_raw(self) -> Result<Vec<u8>> {
+        #[cfg(feature = "demo")]
+        {
+            super::demo::respond(&self.method, &self.url.finish(), self.body.as_deref())
+        }
+        #[cfg(not(feature = "demo"))]
+        {
+            self.send_with_retries().await
+        }
+    }
+
+    /// Requests to the management API report network failures to the
+    /// connection monitor. Writes are held back and retried with exponential
+    /// backoff when the server cannot be reached, while reads are retried
+    /// when a proxy reports the server as unavailable. Writes are never
+    /// retried after a gateway error as the server may have applied them.
+    #[cfg_attr(feature = "demo", allow(dead_code))]
+    async fn send_with_retries(self) -> Result<Vec<u8>> {
         let abort_controller = web_sys::AbortController::new().ok();
         let abort_signal = abort_controller.as_ref().map(|a| a.signal());
 
@@ -196,28 +230,160 @@ impl<'x> HttpRequest {
             }
         });
 
-        let builder = RequestBuilder::new(&self.url.finish())
-            .method(self.method)
-            .headers(self.headers)
-            .abort_signal(abort_signal.as_ref());
-        let req = if let Some(body) = self.body {
-            builder.body(body)
+        let monitor = connection::try_use_connection();
+        let log = self
+            .is_management
+            .then(request_log::try_use_request_log)
+            .flatten();
+        let is_write = self.method != Method::GET;
+        let url = self.url.finish();
+        let mut attempt = 0;
+        loop {
+            let result = self.send_once(&url, abort_signal.as_ref(), log).await;
+            let is_aborted = abort_signal.as_ref().is_some_and(|signal| signal.aborted());
+            let failure = match &result {
+                Err(Error::Network(details)) => Some((details.clone(), is_write)),
+                Err(Error::Unavailable(details)) => Some((details.clone(), !is_write)),
+                _ => None,
+            }
+            .filter(|_| self.is_management && !is_aborted);
+
+            if let Some((details, is_retryable)) = failure {
+                connection::report_failure(monitor, &details);
+                if is_retryable && attempt < MAX_RETRIES {
+                    if attempt == 0 && is_write {
+                        connection::update_queued(monitor, 1);
+                    }
+                    connection::sleep(connection::backoff(attempt)).await;
+                    attempt += 1;
+                    continue;
+                }
+            } else if self.is_management && !is_aborted {
+                connection::report_success(monitor);
+            }
+
+            return if attempt > 0 && is_write {
+                connection::update_queued(monitor, -1);
+                result.map_err(|err| match err {
+                    Error::Network(details) => Error::RetriesExhausted(details),
+                    err => err,
+                })
+            } else {
+                result
+            };
+        }
+    }
+
+    async fn send_once(
+        &self,
+        url: &str,
+        abort_signal: Option<&web_sys::AbortSignal>,
+        log: Option<RwSignal<RequestLog>>,
+    ) -> Result<Vec<u8>> {
+        let headers = Headers::new();
+        for (name, value) in self.headers.entries() {
+            headers.set(&name, &value);
+        }
+        let builder = RequestBuilder::new(url)
+            .method(self.method.clone())
+            .headers(headers)
+            .abort_signal(abort_signal);
+        let req = if let Some(body) = &self.body {
+            builder.body(body.clone())
         } else {
             builder.build()
         }?;
 
-        let response = req.send().await?;
+        let started = Utc::now();
+        let timer = Instant::now();
+        let response = req.send().await;
+        crash::record_request(
+            self.method.as_ref(),
+            url_path(url),
+            &match &response {
+                Ok(response) => response.status().to_string(),
+                Err(err) => err.to_string(),
+            },
+        );
+        if log.is_some() {
+            let (status, error) = match &response {
+                Ok(response) => (
+                    Some(response.status()),
+                    (!response.ok()).then(|| response.status_text()),
+                ),
+                Err(err) => (None, Some(err.to_string())),
+            };
+            request_log::record(
+                log,
+                RequestLogEntry {
+                    started,
+                    method: self.method.to_string(),
+                    path: url_path(url).to_string(),
+                    status,
+                    error,
+                    duration_ms: timer.elapsed().as_secs_f64() * 1000.0,
+                    curl: log
+                        .filter(|log| log.with_untracked(|log| log.show_curl))
+                        .map(|_| self.to_curl(url)),
+                },
+            );
+        }
+        let response = response?;
 
         match response.status() {
             200..=299 => response.binary().await.map_err(Into::into),
             401 => Err(Error::Unauthorized),
             403 => Err(Error::Forbidden),
             404 => Err(Error::NotFound),
+            // The server is restarting or unreachable behind a proxy
+            code @ (502..=504) => Err(Error::Unavailable(format!(
+                "Server unavailable {code}: {}",
+                response.status_text()
+            ))),
             code => Err(Error::Server(ManagementApiError::Other {
                 details: format!("Invalid response code {code}: {}", response.status_text()),
             })),
         }
     }
+
+    /// Renders the request as a curl command, with the access token replaced
+    /// by a shell variable.
+    fn to_curl(&self, url: &str) -> String {
+        let url = if url.starts_with('/') {
+            format!(
+                "{}{url}",
+                leptos::window().location().origin().unwrap_or_default()
+            )
+        } else {
+            url.to_string()
+        };
+        let mut curl = format!("curl -X {} {}", self.method, shell_quote(&url));
+        for (name, value) in self.headers.entries() {
+            if name.eq_ignore_ascii_case("authorization") {
+                let scheme = value.split_once(' ').map_or("Bearer", |(scheme, _)| scheme);
+                curl.push_str(&format!(" -H \"{name}: {scheme} $TOKEN\""));
+            } else {
+                curl.push_str(&format!(" -H {}", shell_quote(&format!("{name}: {value}"))));
+            }
+        }
+        if let Some(body) = &self.body {
+            curl.push_str(&format!(" -d {}", shell_quote(body)));
+        }
+        curl
+    }
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Strips the origin and query string, which may contain credentials.
+fn url_path(url: &str) -> &str {
+    let path = url
+        .split_once("://")
+        .map(|(_, rest)| rest.find('/').map_or("/", |pos| &rest[pos..]))
+        .unwrap_or(url);
+    path.split_once('?').map_or(path, |(path, _)| path)
 }
 
 impl IntoUrlBuilder for String {
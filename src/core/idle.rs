@@ -0,0 +1,42 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::Duration;
+
+use gloo_storage::{LocalStorage, Storage};
+use leptos::{create_rw_signal, expect_context, provide_context, RwSignal};
+use serde::{Deserialize, Serialize};
+
+pub const IDLE_LOCK_STORAGE_KEY: &str = "webadmin_idle_lock";
+
+/// Inactivity after which the session is locked on this browser.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdleLockSettings {
+    /// Minutes of inactivity, zero disables the lock.
+    pub timeout: u64,
+}
+
+pub fn init_idle_lock() {
+    provide_context(create_rw_signal(
+        LocalStorage::get::<IdleLockSettings>(IDLE_LOCK_STORAGE_KEY).unwrap_or_default(),
+    ));
+}
+
+pub fn use_idle_lock() -> RwSignal<IdleLockSettings> {
+    expect_context::<RwSignal<IdleLockSettings>>()
+}
+
+impl IdleLockSettings {
+    pub fn save(&self) {
+        if let Err(err) = LocalStorage::set(IDLE_LOCK_STORAGE_KEY, self) {
+            log::error!("Failed to save idle lock settings: {}", err);
+        }
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        (self.timeout > 0).then(|| Duration::from_secs(self.timeout * 60))
+    }
+}
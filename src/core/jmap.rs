@@ -0,0 +1,153 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use ahash::AHashMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{
+    http::{self, HttpRequest, ManagementApiError},
+    oauth::AuthToken,
+};
+
+const CAPABILITIES: &[&str] = &["urn:ietf:params:jmap:core", "urn:ietf:params:jmap:mail"];
+const BASE32_ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz792013";
+
+#[derive(Serialize)]
+struct Request<'x> {
+    using: &'x [&'x str],
+    #[serde(rename = "methodCalls")]
+    method_calls: Vec<(&'x str, Value, String)>,
+}
+
+#[derive(Deserialize)]
+struct Session {
+    #[serde(rename = "primaryAccounts")]
+    primary_accounts: AHashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    #[serde(rename = "methodResponses")]
+    method_responses: Vec<(String, Value, String)>,
+}
+
+/// Sends a batch of JMAP method calls and returns their arguments in order.
+/// Calls are identified by their position, so result references to an
+/// earlier call use its index as `resultOf`.
+pub async fn jmap_request(auth: &AuthToken, calls: Vec<(&str, Value)>) -> http::Result<Vec<Value>> {
+    jmap_request_using(auth, CAPABILITIES, calls).await
+}
+
+/// Same as [`jmap_request`] for methods defined by other capabilities, such
+/// as quotas, vacation responses or Sieve scripts.
+pub async fn jmap_request_using(
+    auth: &AuthToken,
+    using: &[&str],
+    calls: Vec<(&str, Value)>,
+) -> http::Result<Vec<Value>> {
+    let response = HttpRequest::post("/jmap")
+        .with_authorization(auth)
+        .with_body(Request {
+            using,
+            method_calls: calls
+                .into_iter()
+                .enumerate()
+                .map(|(idx, (method, arguments))| (method, arguments, idx.to_string()))
+                .collect(),
+        })?
+        .send_raw()
+        .await?;
+
+    serde_json::from_slice::<Response>(&response)
+        .map_err(|err| http::Error::Serializer {
+            error: err.to_string(),
+            response: String::from_utf8_lossy(&response).to_string(),
+        })?
+        .method_responses
+        .into_iter()
+        .map(|(method, arguments, _)| {
+            if method == "error" {
+                Err(http::Error::Server(ManagementApiError::Other {
+                    details: format!(
+                        "{}: {}",
+                        arguments["type"].as_str().unwrap_or("serverFail"),
+                        arguments["description"].as_str().unwrap_or_default()
+                    ),
+                }))
+            } else {
+                Ok(arguments)
+            }
+        })
+        .collect()
+}
+
+/// Returns the id of the authenticated user's primary mail account.
+pub async fn jmap_primary_account(auth: &AuthToken) -> http::Result<String> {
+    let response = HttpRequest::get("/jmap/session")
+        .with_authorization(auth)
+        .send_raw()
+        .await?;
+
+    serde_json::from_slice::<Session>(&response)
+        .map_err(|err| http::Error::Serializer {
+            error: err.to_string(),
+            response: String::from_utf8_lossy(&response).to_string(),
+        })?
+        .primary_accounts
+        .remove("urn:ietf:params:jmap:mail")
+        .ok_or(http::Error::NotFound)
+}
+
+/// Downloads a blob, such as the raw contents of a message.
+pub async fn jmap_download(
+    auth: &AuthToken,
+    account_id: &str,
+    blob_id: &str,
+) -> http::Result<Vec<u8>> {
+    HttpRequest::get(format!("/jmap/download/{account_id}/{blob_id}/message.eml"))
+        .with_authorization(auth)
+        .with_parameter("accept", "message/rfc822")
+        .send_raw()
+        .await
+}
+
+/// Uploads a blob, such as a Sieve script, and returns its id.
+pub async fn jmap_upload(
+    auth: &AuthToken,
+    account_id: &str,
+    content_type: &str,
+    contents: String,
+) -> http::Result<String> {
+    let response = HttpRequest::post(format!("/jmap/upload/{account_id}/"))
+        .with_authorization(auth)
+        .with_header("Content-Type", content_type)
+        .with_raw_body(contents)
+        .send_raw()
+        .await?;
+
+    serde_json::from_slice::<Value>(&response)
+        .ok()
+        .and_then(|response| response["blobId"].as_str().map(|id| id.to_string()))
+        .ok_or_else(|| http::Error::Serializer {
+            error: "Missing blobId in upload response".to_string(),
+            response: String::from_utf8_lossy(&response).to_string(),
+        })
+}
+
+/// Encodes a principal id as a JMAP account id.
+pub fn account_id(id: u32) -> String {
+    let mut id = id as u64;
+    let mut result = String::new();
+    loop {
+        result.push(BASE32_ALPHABET[(id & 0x1f) as usize] as char);
+        id >>= 5;
+        if id == 0 {
+            break;
+        }
+    }
+    result
+}
@@ -0,0 +1,48 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use chrono::{DateTime, Utc};
+use leptos::{create_rw_signal, expect_context, provide_context, RwSignal};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    http::{self, HttpRequest},
+    oauth::AuthToken,
+};
+
+/// Maintenance mode as reported by the server. While enabled, outbound
+/// delivery is paused and, optionally, new submissions are rejected with a
+/// temporary failure.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceMode {
+    pub enabled: bool,
+    #[serde(default)]
+    pub reject_submissions: bool,
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl MaintenanceMode {
+    /// Applies this mode on the server and returns the resulting state.
+    pub async fn apply(&self, auth: &AuthToken) -> http::Result<MaintenanceMode> {
+        HttpRequest::post("/api/maintenance")
+            .with_authorization(auth)
+            .with_body(self)
+            .unwrap()
+            .send::<MaintenanceMode>()
+            .await
+    }
+}
+
+/// Last known maintenance mode, `None` until fetched from the server.
+pub fn init_maintenance() {
+    provide_context(create_rw_signal(None::<MaintenanceMode>));
+}
+
+pub fn use_maintenance() -> RwSignal<Option<MaintenanceMode>> {
+    expect_context::<RwSignal<Option<MaintenanceMode>>>()
+}
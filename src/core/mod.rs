@@ -4,9 +4,28 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
+pub mod announcement;
+pub mod clipboard;
+pub mod connection;
+pub mod crash;
+#[cfg(feature = "demo")]
+pub mod demo;
+pub mod dns;
 pub mod expr;
+pub mod file;
 pub mod form;
 pub mod http;
+pub mod idle;
+pub mod jmap;
+pub mod maintenance;
+pub mod notifications;
 pub mod oauth;
+pub mod permissions;
+pub mod request_log;
 pub mod schema;
+pub mod service_worker;
+pub mod stream;
+pub mod tasks;
+pub mod unsaved;
 pub mod url;
+pub mod x509;
@@ -0,0 +1,96 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use gloo_storage::{LocalStorage, Storage};
+use leptos::{create_rw_signal, document, expect_context, provide_context, window, RwSignal};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{js_sys::Reflect, Notification, NotificationOptions, NotificationPermission};
+
+pub const NOTIFICATIONS_STORAGE_KEY: &str = "webadmin_notifications";
+
+/// Desktop notifications an administrator opted into on this browser.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotificationSettings {
+    pub enabled: bool,
+    pub delivery_failures: bool,
+    pub server_status: bool,
+    pub certificates: bool,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        NotificationSettings {
+            enabled: false,
+            delivery_failures: true,
+            server_status: true,
+            certificates: true,
+        }
+    }
+}
+
+pub fn init_notifications() {
+    provide_context(create_rw_signal(
+        LocalStorage::get::<NotificationSettings>(NOTIFICATIONS_STORAGE_KEY).unwrap_or_default(),
+    ));
+}
+
+pub fn use_notifications() -> RwSignal<NotificationSettings> {
+    expect_context::<RwSignal<NotificationSettings>>()
+}
+
+impl NotificationSettings {
+    pub fn save(&self) {
+        if let Err(err) = LocalStorage::set(NOTIFICATIONS_STORAGE_KEY, self) {
+            log::error!("Failed to save notification settings: {}", err);
+        }
+    }
+}
+
+/// Whether the browser implements the Notification API.
+pub fn is_supported() -> bool {
+    Reflect::has(&window(), &"Notification".into()).unwrap_or(false)
+}
+
+pub fn is_permitted() -> bool {
+    is_supported() && Notification::permission() == NotificationPermission::Granted
+}
+
+/// Asks the user for permission to show notifications, returning whether
+/// it was granted.
+pub async fn request_permission() -> bool {
+    if !is_supported() {
+        return false;
+    }
+    match Notification::request_permission() {
+        Ok(promise) => JsFuture::from(promise)
+            .await
+            .ok()
+            .and_then(|permission| permission.as_string())
+            .is_some_and(|permission| permission == "granted"),
+        Err(_) => false,
+    }
+}
+
+/// Shows a notification while the web admin is in a background tab, events
+/// are already visible on screen otherwise. Notifications with the same tag
+/// replace each other.
+pub fn notify(tag: &str, title: &str, body: &str) {
+    if document().hidden() {
+        show_notification(tag, title, body);
+    }
+}
+
+pub fn show_notification(tag: &str, title: &str, body: &str) {
+    if !is_permitted() {
+        return;
+    }
+    let mut options = NotificationOptions::new();
+    options.body(body).tag(tag);
+    if let Err(err) = Notification::new_with_options(title, &options) {
+        log::error!("Failed to show notification: {:?}", err);
+    }
+}
@@ -11,7 +11,10 @@ use serde::{Deserialize, Serialize};
 
 use crate::components::messages::alert::Alert;
 
-use super::http::{self, HttpRequest};
+use super::{
+    http::{self, HttpRequest},
+    permissions::Permissions,
+};
 
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AuthToken {
@@ -21,6 +24,12 @@ pub struct AuthToken {
     pub username: Arc<String>,
     pub is_valid: bool,
     pub is_admin: bool,
+    #[serde(default)]
+    pub impersonator: Option<Box<AuthToken>>,
+    #[serde(default)]
+    pub is_locked: bool,
+    #[serde(default)]
+    pub permissions: Permissions,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +48,8 @@ pub enum OAuthCodeRequest {
 pub struct OAuthCodeResponse {
     pub code: String,
     pub is_admin: bool,
+    #[serde(default)]
+    pub permissions: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -90,6 +101,7 @@ pub enum AuthenticationResult<T> {
 pub struct AuthenticationResponse {
     pub grant: OAuthGrant,
     pub is_admin: bool,
+    pub permissions: Permissions,
 }
 
 pub async fn oauth_authenticate(
@@ -104,12 +116,55 @@ pub async fn oauth_authenticate(
             AuthenticationResult::Error(err) => return AuthenticationResult::Error(err),
         };
     let is_admin = response.is_admin;
+    let permissions = Permissions::new(response.permissions);
+    match oauth_exchange_code(base_url, &response.code).await {
+        Ok(grant) => AuthenticationResult::Success(AuthenticationResponse {
+            grant,
+            is_admin,
+            permissions,
+        }),
+        Err(err) => AuthenticationResult::Error(err),
+    }
+}
+
+/// Obtains a token to act on behalf of another account, which requires the
+/// impersonate permission.
+pub async fn oauth_impersonate(auth: &AuthToken, account: &str) -> Result<OAuthGrant, Alert> {
+    let response = match HttpRequest::post("/api/oauth")
+        .with_authorization(auth)
+        .with_body(serde_json::json!({
+            "type": "Impersonate",
+            "client_id": "webadmin",
+            "account": account,
+        }))
+        .unwrap()
+        .send::<OAuthCodeResponse>()
+        .await
+    {
+        Ok(response) => response,
+        Err(http::Error::Forbidden) => {
+            return Err(Alert::warning(
+                "You do not have permission to impersonate other accounts",
+            ))
+        }
+        Err(http::Error::NotFound) => {
+            return Err(Alert::warning(
+                "This server does not support account impersonation",
+            ))
+        }
+        Err(err) => return Err(Alert::from(err)),
+    };
+
+    oauth_exchange_code(&auth.base_url, &response.code).await
+}
+
+async fn oauth_exchange_code(base_url: &str, code: &str) -> Result<OAuthGrant, Alert> {
     match HttpRequest::post(format!("{base_url}/auth/token"))
         .with_raw_body(
             serde_urlencoded::to_string([
                 ("grant_type", "authorization_code"),
                 ("client_id", "webadmin"),
-                ("code", &response.code),
+                ("code", code),
                 ("redirect_uri", ""),
             ])
             .unwrap(),
@@ -119,14 +174,10 @@ pub async fn oauth_authenticate(
         .and_then(|response| {
             serde_json::from_slice::<OAuthResponse>(response.as_slice()).map_err(Into::into)
         }) {
-        Ok(OAuthResponse::Granted(grant)) => {
-            AuthenticationResult::Success(AuthenticationResponse { grant, is_admin })
-        }
-        Ok(OAuthResponse::Error { error }) => AuthenticationResult::Error(
-            Alert::error("OAuth failure")
-                .with_details(format!("Server returned error code {error:?}")),
-        ),
-        Err(err) => AuthenticationResult::Error(Alert::from(err)),
+        Ok(OAuthResponse::Granted(grant)) => Ok(grant),
+        Ok(OAuthResponse::Error { error }) => Err(Alert::error("OAuth failure")
+            .with_details(format!("Server returned error code {error:?}"))),
+        Err(err) => Err(Alert::from(err)),
     }
 }
 
@@ -217,12 +268,27 @@ pub fn use_authorization() -> RwSignal<AuthToken> {
 
 impl AuthToken {
     pub fn is_logged_in(&self) -> bool {
-        !self.access_token.is_empty()
+        !self.access_token.is_empty() || self.is_locked
     }
 
     pub fn is_admin(&self) -> bool {
         self.is_admin && self.is_logged_in()
     }
+
+    pub fn is_impersonating(&self) -> bool {
+        self.impersonator.is_some()
+    }
+
+    /// Forgets the access token until the password is entered again. Any
+    /// impersonation ends, since only the administrator is able to unlock.
+    pub fn lock(&mut self) {
+        if let Some(admin) = self.impersonator.take() {
+            *self = *admin;
+        }
+        self.access_token = Default::default();
+        self.is_valid = false;
+        self.is_locked = true;
+    }
 }
 
 impl AsRef<AuthToken> for AuthToken {
@@ -0,0 +1,278 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use leptos::{create_memo, Memo, SignalGet, SignalWith};
+use serde::{Deserialize, Serialize};
+
+use super::oauth::use_authorization;
+
+/// Permissions used by mail clients and self-service, which do not grant
+/// access to the management API.
+const USER_PERMISSION_PREFIXES: &[&str] = &[
+    "authenticate",
+    "email-",
+    "imap-",
+    "jmap-",
+    "pop3-",
+    "sieve-",
+    "dav-",
+    "manage-",
+];
+/// Management permissions that only allow looking at the server.
+const READ_PERMISSION_SUFFIXES: &[&str] = &["-list", "-get", "-live", "-view"];
+
+const READ_ONLY_REASON: &str = "Your account has read-only access to this server";
+
+/// Permissions of the signed in account, as reported by the server when
+/// signing in. Servers that do not report them are assumed to grant
+/// everything, leaving it to the API to reject what is not allowed.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Permissions(Option<Arc<Vec<String>>>);
+
+/// Action offered by the web admin that modifies the server. Every control
+/// performing one is disabled when the account lacks its permissions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Capability {
+    PrincipalCreate,
+    PrincipalUpdate,
+    PrincipalDelete,
+    Impersonate,
+    DomainCreate,
+    DomainDelete,
+    DkimSignatureCreate,
+    TrashRestore,
+    SettingsUpdate,
+    SettingsReload,
+    SettingsApply,
+    QueueUpdate,
+    QueueDelete,
+    OutgoingReportDelete,
+    IncomingReportCreate,
+    IncomingReportDelete,
+    SpamFilterTrain,
+    SpamFilterUpdate,
+    QuarantineUpdate,
+    InMemoryStoreDelete,
+    LookupRefresh,
+    WebhookRetry,
+    AcmeRenew,
+    RelayTest,
+    Troubleshoot,
+    MaintenanceMode,
+    HousekeeperRun,
+    Restart,
+    WebadminUpdate,
+}
+
+impl Permissions {
+    pub fn new(permissions: Vec<String>) -> Self {
+        Permissions((!permissions.is_empty()).then(|| Arc::new(permissions)))
+    }
+
+    pub fn is_reported(&self) -> bool {
+        self.0.is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.as_ref().map_or(0, |permissions| permissions.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn has(&self, permission: &str) -> bool {
+        self.0
+            .as_ref()
+            .is_none_or(|permissions| permissions.iter().any(|granted| granted == permission))
+    }
+
+    /// Whether the management permissions are limited to listing and
+    /// fetching, as is the case for auditors.
+    pub fn is_read_only(&self) -> bool {
+        let Some(permissions) = &self.0 else {
+            return false;
+        };
+        let mut can_read = false;
+        for permission in permissions.iter().filter(|permission| {
+            !USER_PERMISSION_PREFIXES
+                .iter()
+                .any(|prefix| permission.starts_with(prefix))
+        }) {
+            if READ_PERMISSION_SUFFIXES
+                .iter()
+                .any(|suffix| permission.ends_with(suffix))
+            {
+                can_read = true;
+            } else {
+                return false;
+            }
+        }
+        can_read
+    }
+
+    pub fn can(&self, capability: Capability) -> bool {
+        self.denied_reason(capability).is_none()
+    }
+
+    /// Explains why an action is not available, `None` when it is.
+    pub fn denied_reason(&self, capability: Capability) -> Option<String> {
+        if !self.is_reported() {
+            return None;
+        }
+        if self.is_read_only() {
+            return Some(READ_ONLY_REASON.to_string());
+        }
+        let required = capability.permissions();
+        if required.is_empty() {
+            return Some("No permission grants this action".to_string());
+        }
+        let missing = required
+            .iter()
+            .filter(|permission| !self.has(permission))
+            .copied()
+            .collect::<Vec<_>>();
+        match missing.as_slice() {
+            [] => None,
+            [permission] => Some(format!("Requires the {permission} permission")),
+            permissions => Some(format!(
+                "Requires the {} permissions",
+                permissions.join(", ")
+            )),
+        }
+    }
+}
+
+impl Capability {
+    pub const ALL: &'static [Capability] = &[
+        Capability::PrincipalCreate,
+        Capability::PrincipalUpdate,
+        Capability::PrincipalDelete,
+        Capability::Impersonate,
+        Capability::DomainCreate,
+        Capability::DomainDelete,
+        Capability::DkimSignatureCreate,
+        Capability::TrashRestore,
+        Capability::SettingsUpdate,
+        Capability::SettingsReload,
+        Capability::SettingsApply,
+        Capability::QueueUpdate,
+        Capability::QueueDelete,
+        Capability::OutgoingReportDelete,
+        Capability::IncomingReportCreate,
+        Capability::IncomingReportDelete,
+        Capability::SpamFilterTrain,
+        Capability::SpamFilterUpdate,
+        Capability::QuarantineUpdate,
+        Capability::InMemoryStoreDelete,
+        Capability::LookupRefresh,
+        Capability::WebhookRetry,
+        Capability::AcmeRenew,
+        Capability::RelayTest,
+        Capability::Troubleshoot,
+        Capability::MaintenanceMode,
+        Capability::HousekeeperRun,
+        Capability::Restart,
+        Capability::WebadminUpdate,
+    ];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Capability::PrincipalCreate => "Create accounts, groups and lists",
+            Capability::PrincipalUpdate => "Edit accounts, groups and lists",
+            Capability::PrincipalDelete => "Delete accounts, groups and lists",
+            Capability::Impersonate => "Act on behalf of other accounts",
+            Capability::DomainCreate => "Create domains",
+            Capability::DomainDelete => "Delete domains",
+            Capability::DkimSignatureCreate => "Create DKIM signatures",
+            Capability::TrashRestore => "Restore deleted accounts and domains",
+            Capability::SettingsUpdate => "Change settings",
+            Capability::SettingsReload => "Reload the configuration",
+            Capability::SettingsApply => "Save settings and reload the configuration",
+            Capability::QueueUpdate => "Hold, release and retry queued messages",
+            Capability::QueueDelete => "Cancel queued messages",
+            Capability::OutgoingReportDelete => "Cancel outgoing reports",
+            Capability::IncomingReportCreate => "Upload incoming reports",
+            Capability::IncomingReportDelete => "Delete incoming reports",
+            Capability::SpamFilterTrain => "Train the spam filter",
+            Capability::SpamFilterUpdate => "Update the spam filter rules",
+            Capability::QuarantineUpdate => "Release and delete quarantined messages",
+            Capability::InMemoryStoreDelete => "Delete in-memory store entries",
+            Capability::LookupRefresh => "Refresh HTTP lookup lists",
+            Capability::WebhookRetry => "Retry webhook deliveries",
+            Capability::AcmeRenew => "Renew ACME certificates",
+            Capability::RelayTest => "Send test messages through relay hosts",
+            Capability::Troubleshoot => "Trace message deliveries",
+            Capability::MaintenanceMode => "Enable and disable maintenance mode",
+            Capability::HousekeeperRun => "Run housekeeping tasks",
+            Capability::Restart => "Restart the server",
+            Capability::WebadminUpdate => "Update the web admin",
+        }
+    }
+
+    /// Permissions the API checks before performing the action. Actions
+    /// without a dedicated permission map to the permissions of the
+    /// settings or stores they change.
+    pub fn permissions(&self) -> &'static [&'static str] {
+        match self {
+            Capability::PrincipalCreate => &["principal-create"],
+            Capability::PrincipalUpdate => &["principal-update"],
+            Capability::PrincipalDelete => &["principal-delete"],
+            Capability::Impersonate => &["impersonate"],
+            Capability::DomainCreate => &["domain-create"],
+            Capability::DomainDelete => &["domain-delete"],
+            Capability::DkimSignatureCreate => &["dkim-signature-create"],
+            Capability::SettingsUpdate => &["settings-update"],
+            Capability::SettingsReload => &["settings-reload"],
+            Capability::SettingsApply => &["settings-update", "settings-reload"],
+            Capability::QueueUpdate => &["message-queue-update"],
+            Capability::QueueDelete => &["message-queue-delete"],
+            Capability::OutgoingReportDelete => &["outgoing-report-delete"],
+            Capability::IncomingReportDelete => &["incoming-report-delete"],
+            Capability::SpamFilterTrain => &["spam-filter-train"],
+            Capability::SpamFilterUpdate => &["spam-filter-update"],
+            Capability::InMemoryStoreDelete => &["purge-in-memory-store"],
+            Capability::Restart => &["restart"],
+            Capability::WebadminUpdate => &["webadmin-update"],
+            Capability::TrashRestore => &["principal-update", "settings-update"],
+            Capability::IncomingReportCreate => &["incoming-report-create"],
+            // Quarantined messages are moved over JMAP on behalf of the account
+            Capability::QuarantineUpdate => &["impersonate"],
+            Capability::LookupRefresh => &["settings-reload"],
+            Capability::WebhookRetry => &["settings-update"],
+            Capability::AcmeRenew => &["settings-reload"],
+            Capability::RelayTest => &["troubleshoot"],
+            Capability::Troubleshoot => &["troubleshoot"],
+            Capability::MaintenanceMode => &["settings-update"],
+            Capability::HousekeeperRun => &["purge-data-store"],
+        }
+    }
+}
+
+/// Whether the signed in account may perform an action, used to disable
+/// the controls that perform it.
+#[derive(Clone, Copy)]
+pub struct CapabilityCheck(Memo<Option<String>>);
+
+pub fn use_capability(capability: Capability) -> CapabilityCheck {
+    let auth = use_authorization();
+    CapabilityCheck(create_memo(move |_| {
+        auth.with(|auth| auth.permissions.denied_reason(capability))
+    }))
+}
+
+impl CapabilityCheck {
+    pub fn is_denied(self) -> bool {
+        self.0.with(|reason| reason.is_some())
+    }
+
+    /// Explanation shown as tooltip on disabled controls.
+    pub fn reason(self) -> Option<String> {
+        self.0.get()
+    }
+}
@@ -0,0 +1,108 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Utc};
+use gloo_storage::{LocalStorage, Storage};
+use leptos::{
+    create_rw_signal, expect_context, provide_context, use_context, RwSignal, SignalUpdate,
+    SignalWithUntracked,
+};
+
+pub const REQUEST_LOG_STORAGE_KEY: &str = "webadmin_request_log";
+pub const REQUEST_LOG_CURL_STORAGE_KEY: &str = "webadmin_request_log_curl";
+
+/// Maximum number of requests kept in the log.
+const MAX_ENTRIES: usize = 250;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RequestLog {
+    pub enabled: bool,
+    pub show_curl: bool,
+    pub entries: VecDeque<RequestLogEntry>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RequestLogEntry {
+    pub started: DateTime<Utc>,
+    pub method: String,
+    pub path: String,
+    pub status: Option<u16>,
+    pub error: Option<String>,
+    pub duration_ms: f64,
+    pub curl: Option<String>,
+}
+
+pub fn init_request_log() {
+    provide_context(create_rw_signal(RequestLog {
+        enabled: LocalStorage::get::<bool>(REQUEST_LOG_STORAGE_KEY).unwrap_or_default(),
+        show_curl: LocalStorage::get::<bool>(REQUEST_LOG_CURL_STORAGE_KEY).unwrap_or_default(),
+        entries: VecDeque::new(),
+    }));
+}
+
+pub fn use_request_log() -> RwSignal<RequestLog> {
+    expect_context::<RwSignal<RequestLog>>()
+}
+
+/// Returns the request log only when recording is enabled.
+pub(crate) fn try_use_request_log() -> Option<RwSignal<RequestLog>> {
+    use_context::<RwSignal<RequestLog>>().filter(|log| log.with_untracked(|log| log.enabled))
+}
+
+pub(crate) fn record(log: Option<RwSignal<RequestLog>>, entry: RequestLogEntry) {
+    if let Some(log) = log {
+        log.update(|log| {
+            if log.entries.len() >= MAX_ENTRIES {
+                log.entries.pop_front();
+            }
+            log.entries.push_back(entry);
+        });
+    }
+}
+
+impl RequestLog {
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.entries.clear();
+        }
+        if let Err(err) = LocalStorage::set(REQUEST_LOG_STORAGE_KEY, enabled) {
+            log::error!("Failed to save request log setting: {}", err);
+        }
+    }
+
+    pub fn set_show_curl(&mut self, show_curl: bool) {
+        self.show_curl = show_curl;
+        if let Err(err) = LocalStorage::set(REQUEST_LOG_CURL_STORAGE_KEY, show_curl) {
+            log::error!("Failed to save request log setting: {}", err);
+        }
+    }
+
+    /// Renders the log as plain text suitable for pasting into a bug report.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for entry in &self.entries {
+            text.push_str(&format!(
+                "{} {} {} {} {:.0}ms",
+                entry.started.to_rfc3339(),
+                entry.method,
+                entry.path,
+                entry
+                    .status
+                    .map(|status| status.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                entry.duration_ms,
+            ));
+            if let Some(error) = &entry.error {
+                text.push_str(&format!(" ({error})"));
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
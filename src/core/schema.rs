@@ -4,11 +4,16 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
-use std::{hash::Hasher, sync::Arc};
+use std::{hash::Hasher, sync::Arc, time::Duration};
 
 use ahash::AHashMap;
 
-use super::form::{FormData, FormValue};
+use super::{
+    expr::{
+        parser::ExpressionParser, tokenizer::Tokenizer, Constant, Expression, ParseValue, Token,
+    },
+    form::{FormData, FormValue},
+};
 
 #[derive(Default)]
 pub struct Schemas {
@@ -45,6 +50,7 @@ pub struct Field {
     pub label_form: &'static str,
     pub label_column: &'static str,
     pub help: Option<&'static str>,
+    pub docs: Option<&'static str>,
     pub checks: Value<InputCheck>,
     pub typ_: Type<Arc<Schema>, Arc<Field>>,
     pub default: Value<FormValue>,
@@ -123,8 +129,10 @@ pub enum Action {
 #[derive(Clone, Default, Debug)]
 pub struct Section {
     pub title: Option<&'static str>,
+    pub docs: Option<&'static str>,
     pub display: Vec<Eval>,
     pub fields: Vec<Arc<Field>>,
+    pub constraints: Vec<Constraint>,
 }
 
 #[derive(Clone, Debug)]
@@ -197,6 +205,23 @@ pub enum Validator {
     IsValidExpression(ExpressionValidator),
 }
 
+/// Rule involving two fields, checked once every field is valid on its own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Constraint {
+    /// The field is required when the other one is set.
+    RequiredWith {
+        field: &'static str,
+        other: &'static str,
+    },
+    /// The durations of the field have to be longer than every duration of
+    /// the other one. Skipped when either is not a list of durations, such
+    /// as expressions with conditions.
+    LongerThan {
+        field: &'static str,
+        other: &'static str,
+    },
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
 pub struct ExpressionValidator {
     pub variables: &'static [&'static str],
@@ -231,6 +256,9 @@ impl From<Arc<Schema>> for FormData {
             external_sources: Default::default(),
             schema,
             is_update: false,
+            snapshot: None,
+            defaulted: Default::default(),
+            violations: Default::default(),
         }
     }
 }
@@ -592,6 +620,11 @@ impl Builder<(Schemas, Schema), Field> {
         self
     }
 
+    pub fn docs(mut self, path: &'static str) -> Self {
+        self.item.docs = Some(path);
+        self
+    }
+
     pub fn readonly(mut self) -> Self {
         self.item.readonly = true;
         self
@@ -779,6 +812,11 @@ impl Builder<(Schemas, Schema), Section> {
         self
     }
 
+    pub fn docs(mut self, path: &'static str) -> Self {
+        self.item.docs = Some(path);
+        self
+    }
+
     pub fn field(mut self, field: &'static str) -> Self {
         self.item.fields.push(
             self.parent
@@ -804,6 +842,19 @@ impl Builder<(Schemas, Schema), Section> {
         builder
     }
 
+    pub fn constraint(mut self, constraint: Constraint) -> Self {
+        for field in constraint.fields() {
+            if !self.parent.1.fields.contains_key(field) {
+                panic!(
+                    "Field {field:?} not found in schema {:?}.",
+                    self.parent.1.id
+                );
+            }
+        }
+        self.item.constraints.push(constraint);
+        self
+    }
+
     fn display_if(
         mut self,
         field: &'static str,
@@ -904,6 +955,16 @@ impl InputCheck {
     }
 }
 
+impl Constraint {
+    pub fn fields(&self) -> [&'static str; 2] {
+        match self {
+            Constraint::RequiredWith { field, other } | Constraint::LongerThan { field, other } => {
+                [*field, *other]
+            }
+        }
+    }
+}
+
 impl ExpressionValidator {
     pub fn new(variables: &'static [&'static str], constants: &'static [&'static str]) -> Self {
         ExpressionValidator {
@@ -921,6 +982,23 @@ impl ExpressionValidator {
         self.variables = variables;
         self
     }
+
+    /// Parses an expression, accepting only the variables and constants
+    /// allowed by this validator.
+    pub fn parse(&self, expr: &str) -> Result<Expression, String> {
+        ExpressionParser::new(Tokenizer::new(expr, |token| {
+            if self.variables.contains(&token) {
+                Ok(Token::Variable(0))
+            } else if self.constants.contains(&token) {
+                Ok(Token::Constant(Constant::Integer(0)))
+            } else {
+                Duration::parse_value(token)
+                    .map(|d| Token::Constant(Constant::Integer(d.as_secs() as i64)))
+                    .ok_or_else(|| format!("Invalid variable or function name {:?}", token))
+            }
+        }))
+        .parse()
+    }
 }
 
 impl From<Type<&'static str, &'static str>> for Type<Arc<Schema>, Arc<Field>> {
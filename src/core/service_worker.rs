@@ -0,0 +1,78 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::Duration;
+
+use leptos::{
+    create_rw_signal, expect_context, provide_context, set_interval, window, RwSignal, SignalSet,
+};
+use web_sys::{
+    js_sys::{Object, Reflect},
+    wasm_bindgen::{closure::Closure, JsCast, JsValue},
+    MessageEvent,
+};
+
+const SERVICE_WORKER_URL: &str = "/service-worker.js";
+/// How often the service worker is asked to look for a new deployment,
+/// besides every page load.
+const UPDATE_CHECK_INTERVAL: Duration = Duration::from_secs(30 * 60);
+
+/// Whether a newer version of the web admin has been downloaded and will
+/// be used after a reload.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AppUpdate {
+    pub available: bool,
+}
+
+pub fn init_service_worker() {
+    let update = create_rw_signal(AppUpdate::default());
+    provide_context(update);
+
+    // Caching the shell gets in the way of development builds
+    if cfg!(debug_assertions) || !is_supported() {
+        return;
+    }
+
+    let container = window().navigator().service_worker();
+    let on_message = Closure::<dyn FnMut(MessageEvent)>::new(move |ev: MessageEvent| {
+        if Reflect::get(&ev.data(), &"type".into())
+            .ok()
+            .and_then(|kind| kind.as_string())
+            .is_some_and(|kind| kind == "update-available")
+        {
+            update.set(AppUpdate { available: true });
+        }
+    });
+    container.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    // The listener lives as long as the application
+    on_message.forget();
+
+    let _ = container.register(SERVICE_WORKER_URL);
+    set_interval(check_for_update, UPDATE_CHECK_INTERVAL);
+}
+
+pub fn use_app_update() -> RwSignal<AppUpdate> {
+    expect_context::<RwSignal<AppUpdate>>()
+}
+
+fn is_supported() -> bool {
+    Reflect::has(&window().navigator(), &"serviceWorker".into()).unwrap_or(false)
+}
+
+/// Asks the service worker to look for a new deployment, which shows the
+/// update prompt once it has been downloaded.
+pub fn check_for_update() {
+    if !is_supported() {
+        return;
+    }
+    if let Some(worker) = window().navigator().service_worker().controller() {
+        let message = Object::new();
+        let _ = Reflect::set(&message, &"type".into(), &"check-update".into());
+        if let Err(err) = worker.post_message(&JsValue::from(message)) {
+            log::error!("Failed to check for updates: {:?}", err);
+        }
+    }
+}
@@ -0,0 +1,58 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use web_sys::{
+    wasm_bindgen::{closure::Closure, JsCast},
+    EventSource, MessageEvent,
+};
+
+/// Server-sent event stream that is closed when dropped.
+pub struct EventStream {
+    source: EventSource,
+    _on_message: Closure<dyn FnMut(MessageEvent)>,
+    _on_error: Closure<dyn FnMut(web_sys::Event)>,
+}
+
+impl EventStream {
+    pub fn open(
+        url: &str,
+        mut on_message: impl FnMut(String) + 'static,
+        mut on_error: impl FnMut() + 'static,
+    ) -> Option<Self> {
+        let source = EventSource::new(url)
+            .map_err(|err| log::error!("Failed to open event stream {url}: {err:?}"))
+            .ok()?;
+
+        let on_message = Closure::<dyn FnMut(MessageEvent)>::new(move |ev: MessageEvent| {
+            if let Some(data) = ev.data().as_string() {
+                on_message(data);
+            }
+        });
+        let on_error = Closure::<dyn FnMut(web_sys::Event)>::new(move |_| {
+            on_error();
+        });
+        source.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        source.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+
+        Some(EventStream {
+            source,
+            _on_message: on_message,
+            _on_error: on_error,
+        })
+    }
+
+    pub fn close(&self) {
+        self.source.close();
+    }
+}
+
+impl Drop for EventStream {
+    fn drop(&mut self) {
+        self.source.set_onmessage(None);
+        self.source.set_onerror(None);
+        self.source.close();
+    }
+}
@@ -0,0 +1,134 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use chrono::{DateTime, Utc};
+use gloo_storage::{SessionStorage, Storage};
+use leptos::{create_rw_signal, expect_context, provide_context, RwSignal};
+use serde::{Deserialize, Serialize};
+
+pub const TASKS_STORAGE_KEY: &str = "webadmin_tasks";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tasks {
+    pub items: Vec<Task>,
+    next_id: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Task {
+    pub id: u64,
+    pub title: String,
+    pub status_url: Option<String>,
+    pub status: TaskStatus,
+    pub started: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TaskStatus {
+    #[serde(default)]
+    pub state: TaskState,
+    #[serde(default)]
+    pub total: Option<u64>,
+    #[serde(default)]
+    pub completed: Option<u64>,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskState {
+    #[default]
+    Running,
+    Completed,
+    Failed,
+}
+
+pub fn init_tasks() {
+    let mut tasks = SessionStorage::get::<Tasks>(TASKS_STORAGE_KEY).unwrap_or_default();
+    for task in &mut tasks.items {
+        if task.status_url.is_none() && task.is_running() {
+            task.status = TaskStatus::untracked();
+        }
+    }
+    provide_context(create_rw_signal(tasks));
+}
+
+pub fn use_tasks() -> RwSignal<Tasks> {
+    expect_context::<RwSignal<Tasks>>()
+}
+
+impl Tasks {
+    /// Starts tracking a long-running server operation. When a status URL is
+    /// provided it is polled until the task completes or fails, otherwise the
+    /// task is listed as completed since its progress cannot be followed.
+    pub fn insert(&mut self, title: String, status_url: Option<String>) -> u64 {
+        self.next_id += 1;
+        let status = if status_url.is_some() {
+            TaskStatus::default()
+        } else {
+            TaskStatus::untracked()
+        };
+        self.items.push(Task {
+            id: self.next_id,
+            title,
+            status_url,
+            status,
+            started: Utc::now(),
+        });
+        self.next_id
+    }
+
+    pub fn update_status(&mut self, id: u64, status: TaskStatus) {
+        if let Some(task) = self.items.iter_mut().find(|task| task.id == id) {
+            task.status = status;
+        }
+    }
+
+    pub fn remove(&mut self, id: u64) {
+        self.items.retain(|task| task.id != id);
+    }
+
+    pub fn clear_finished(&mut self) {
+        self.items.retain(|task| task.is_running());
+    }
+
+    pub fn running(&self) -> impl Iterator<Item = &Task> {
+        self.items.iter().filter(|task| task.is_running())
+    }
+
+    pub fn persist(&self) {
+        if let Err(err) = SessionStorage::set(TASKS_STORAGE_KEY, self) {
+            log::error!("Failed to save tasks to session storage: {}", err);
+        }
+    }
+}
+
+impl TaskStatus {
+    fn untracked() -> Self {
+        TaskStatus {
+            state: TaskState::Completed,
+            message: Some("Progress is not reported for this task.".to_string()),
+            ..Default::default()
+        }
+    }
+}
+
+impl Task {
+    pub fn is_running(&self) -> bool {
+        self.status.state == TaskState::Running
+    }
+
+    pub fn progress(&self) -> Option<u8> {
+        match (self.status.completed, self.status.total) {
+            (Some(completed), Some(total)) if total > 0 => {
+                Some(((completed.min(total) * 100) / total) as u8)
+            }
+            _ if self.status.state == TaskState::Completed => Some(100),
+            _ => None,
+        }
+    }
+}
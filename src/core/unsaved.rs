@@ -0,0 +1,80 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::{
+    create_effect, create_rw_signal, expect_context, on_cleanup, provide_context, RwSignal,
+    SignalGet, SignalGetUntracked, SignalSet, SignalWith,
+};
+use leptos_router::use_navigate;
+
+use super::form::FormData;
+
+/// Tracks whether the form currently on screen has unsaved changes.
+#[derive(Clone, Copy)]
+pub struct UnsavedChanges {
+    dirty: RwSignal<bool>,
+    /// Navigation waiting for the user to confirm that changes can be discarded.
+    pending: RwSignal<Option<String>>,
+}
+
+pub fn init_unsaved_changes() {
+    provide_context(UnsavedChanges {
+        dirty: create_rw_signal(false),
+        pending: create_rw_signal(None),
+    });
+}
+
+pub fn use_unsaved_changes() -> UnsavedChanges {
+    expect_context::<UnsavedChanges>()
+}
+
+impl UnsavedChanges {
+    /// Follows the dirty state of a form until the owning component is disposed.
+    pub fn track(self, data: RwSignal<FormData>) {
+        let flag = self.dirty;
+        create_effect(move |_| {
+            let is_dirty = data.with(|data| data.is_dirty());
+            if flag.get_untracked() != is_dirty {
+                flag.set(is_dirty);
+            }
+        });
+        on_cleanup(move || flag.set(false));
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.get()
+    }
+
+    pub fn is_dirty_untracked(&self) -> bool {
+        self.dirty.get_untracked()
+    }
+
+    pub fn clear(&self) {
+        self.dirty.set(false);
+    }
+
+    /// Navigates to `url`, asking for confirmation first if there are
+    /// unsaved changes.
+    pub fn navigate(&self, url: &str) {
+        if self.is_dirty_untracked() {
+            self.pending.set(Some(url.to_string()));
+        } else {
+            use_navigate()(url, Default::default());
+        }
+    }
+
+    pub fn pending_navigation(&self) -> Option<String> {
+        self.pending.get()
+    }
+
+    pub fn take_pending_navigation(&self) -> Option<String> {
+        let url = self.pending.get_untracked();
+        if url.is_some() {
+            self.pending.set(None);
+        }
+        url
+    }
+}
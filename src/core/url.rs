@@ -57,9 +57,9 @@ impl UrlBuilder {
         self
     }
 
-    pub fn finish(self) -> String {
+    pub fn finish(&self) -> String {
         if self.params.is_empty() {
-            self.path
+            self.path.clone()
         } else {
             format!(
                 "{}?{}",
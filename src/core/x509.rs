@@ -0,0 +1,585 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+//! Minimal DER reader for displaying the details of X.509 certificates,
+//! either on their own or bundled in a PKCS#7 structure. Signatures are not
+//! verified, this is only meant to help users pick the right certificate.
+
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+const TAG_BOOLEAN: u8 = 0x01;
+const TAG_INTEGER: u8 = 0x02;
+const TAG_BIT_STRING: u8 = 0x03;
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_OID: u8 = 0x06;
+const TAG_UTC_TIME: u8 = 0x17;
+const TAG_GENERALIZED_TIME: u8 = 0x18;
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_SET: u8 = 0x31;
+const TAG_SAN_EMAIL: u8 = 0x81;
+const TAG_SAN_DNS: u8 = 0x82;
+const TAG_SAN_IP: u8 = 0x87;
+const TAG_CONTEXT_0: u8 = 0xA0;
+const TAG_EXTENSIONS: u8 = 0xA3;
+
+const OID_COMMON_NAME: &[u8] = &[0x55, 0x04, 0x03];
+const OID_ORGANIZATION: &[u8] = &[0x55, 0x04, 0x0A];
+const OID_SUBJECT_ALT_NAME: &[u8] = &[0x55, 0x1D, 0x11];
+
+const OID_RSA: &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x01, 0x01];
+const OID_EC: &[u8] = &[0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x02, 0x01];
+const OID_ED25519: &[u8] = &[0x2B, 0x65, 0x70];
+const OID_PKCS7_SIGNED_DATA: &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x07, 0x02];
+const CURVES: &[(&[u8], &str, u32)] = &[
+    (
+        &[0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x03, 0x01, 0x07],
+        "P-256",
+        256,
+    ),
+    (&[0x2B, 0x81, 0x04, 0x00, 0x22], "P-384", 384),
+    (&[0x2B, 0x81, 0x04, 0x00, 0x23], "P-521", 521),
+];
+const NAME_ATTRIBUTES: &[(&[u8], &str)] = &[
+    (OID_COMMON_NAME, "CN"),
+    (OID_ORGANIZATION, "O"),
+    (&[0x55, 0x04, 0x0B], "OU"),
+    (&[0x55, 0x04, 0x07], "L"),
+    (&[0x55, 0x04, 0x08], "ST"),
+    (&[0x55, 0x04, 0x06], "C"),
+    (
+        &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x09, 0x01],
+        "emailAddress",
+    ),
+];
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CertificateInfo {
+    pub subject: String,
+    pub issuer: String,
+    /// Common name of the subject.
+    pub subject_name: Option<String>,
+    /// Organization of the issuer, or its common name when missing.
+    pub issuer_name: Option<String>,
+    pub not_before: DateTime<Utc>,
+    pub not_after: DateTime<Utc>,
+    pub sans: Vec<String>,
+    pub key_algorithm: String,
+    pub key_size: Option<u32>,
+}
+
+impl CertificateInfo {
+    /// Parses the leaf certificate of a PEM chain, or a base64 encoded PEM/DER
+    /// certificate as stored by ACME providers. Other PEM blocks, such as
+    /// private keys, are ignored.
+    pub fn parse(value: &str) -> Option<Self> {
+        if value.contains("-----BEGIN") {
+            parse_pem(value, false)
+        } else {
+            let bytes = STANDARD
+                .decode(value.split_whitespace().collect::<String>())
+                .ok()?;
+            match std::str::from_utf8(&bytes) {
+                Ok(pem) if pem.contains("-----BEGIN") => parse_pem(pem, false),
+                _ => parse_der(&bytes),
+            }
+        }
+        .ok()?
+        .into_iter()
+        .next()
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.not_after < Utc::now()
+    }
+
+    pub fn is_not_yet_valid(&self) -> bool {
+        self.not_before > Utc::now()
+    }
+}
+
+/// Parses PEM encoded certificates and PKCS#7 bundles, or a single DER
+/// encoded certificate or bundle.
+pub fn parse_certificates(input: &[u8]) -> Result<Vec<CertificateInfo>, String> {
+    if input.first() == Some(&TAG_SEQUENCE) {
+        return parse_der(input);
+    }
+
+    let text = std::str::from_utf8(input).map_err(|_| "Unrecognized certificate format")?;
+    parse_pem(text, true)
+}
+
+fn parse_pem(text: &str, strict: bool) -> Result<Vec<CertificateInfo>, String> {
+    let mut certs = Vec::new();
+    let mut lines = text.lines().map(|line| line.trim());
+    while let Some(line) = lines.next() {
+        let Some(label) = line
+            .strip_prefix("-----BEGIN ")
+            .and_then(|label| label.strip_suffix("-----"))
+        else {
+            continue;
+        };
+        let end = format!("-----END {label}-----");
+        let body = lines
+            .by_ref()
+            .take_while(|line| *line != end)
+            .filter(|line| !line.contains(':'))
+            .collect::<String>();
+        match label {
+            "CERTIFICATE" | "PKCS7" | "CMS" => {
+                let der = STANDARD
+                    .decode(body)
+                    .map_err(|_| format!("Invalid base64 in {label} block"))?;
+                certs.extend(parse_der(&der)?);
+            }
+            _ if !strict => {}
+            _ if label.starts_with("PGP") => {
+                return Err("This is an OpenPGP key, not an S/MIME certificate".to_string());
+            }
+            _ => return Err(format!("Unsupported PEM block {label:?}")),
+        }
+    }
+
+    if !certs.is_empty() {
+        Ok(certs)
+    } else {
+        Err("No certificates found".to_string())
+    }
+}
+
+/// Returns the PEM label to use for a DER encoded certificate or bundle.
+pub fn pem_label(der: &[u8]) -> Option<&'static str> {
+    let (_, content, _) = read_tlv(der).filter(|(tag, _, _)| *tag == TAG_SEQUENCE)?;
+    let (tag, value, _) = read_tlv(content)?;
+    if tag == TAG_OID && value == OID_PKCS7_SIGNED_DATA {
+        Some("PKCS7")
+    } else {
+        Some("CERTIFICATE")
+    }
+}
+
+/// Encodes DER data as a PEM block.
+pub fn to_pem(label: &str, der: &[u8]) -> String {
+    let encoded = STANDARD.encode(der);
+    let mut pem = format!("-----BEGIN {label}-----\n");
+    for chunk in encoded.as_bytes().chunks(64) {
+        pem.push_str(std::str::from_utf8(chunk).unwrap_or_default());
+        pem.push('\n');
+    }
+    pem.push_str(&format!("-----END {label}-----\n"));
+    pem
+}
+
+fn parse_der(der: &[u8]) -> Result<Vec<CertificateInfo>, String> {
+    match pem_label(der) {
+        Some("PKCS7") => parse_pkcs7(der),
+        Some(_) => parse_certificate(der).map(|cert| vec![cert]),
+        None => Err("Invalid DER encoding"),
+    }
+    .map_err(|err| err.to_string())
+}
+
+fn parse_pkcs7(der: &[u8]) -> Result<Vec<CertificateInfo>, &'static str> {
+    const INVALID: &str = "Invalid PKCS#7 structure";
+
+    let (_, content_info, _) = read_tlv(der).ok_or(INVALID)?;
+    let (_, _, rest) = read_tlv(content_info).ok_or(INVALID)?;
+    let (_, explicit, _) = read_tlv(rest)
+        .filter(|(tag, _, _)| *tag == TAG_CONTEXT_0)
+        .ok_or(INVALID)?;
+    let (_, signed_data, _) = read_tlv(explicit).ok_or(INVALID)?;
+
+    // Skip version, digest algorithms and the encapsulated content
+    let mut rest = signed_data;
+    for _ in 0..3 {
+        rest = read_tlv(rest).ok_or(INVALID)?.2;
+    }
+    let Some((TAG_CONTEXT_0, mut certs, _)) = read_tlv(rest) else {
+        return Err("The PKCS#7 bundle contains no certificates");
+    };
+
+    let mut result = Vec::new();
+    while !certs.is_empty() {
+        let (tag, _, next) = read_tlv(certs).ok_or(INVALID)?;
+        if tag == TAG_SEQUENCE {
+            result.push(parse_certificate(&certs[..certs.len() - next.len()])?);
+        }
+        certs = next;
+    }
+    Ok(result)
+}
+
+fn parse_certificate(der: &[u8]) -> Result<CertificateInfo, &'static str> {
+    const INVALID: &str = "Invalid X.509 certificate";
+
+    let (_, cert, _) = read_tlv(der).ok_or(INVALID)?;
+    let (_, tbs, _) = read_tlv(cert)
+        .filter(|(tag, _, _)| *tag == TAG_SEQUENCE)
+        .ok_or(INVALID)?;
+
+    let mut rest = tbs;
+    if rest.first() == Some(&TAG_CONTEXT_0) {
+        rest = read_tlv(rest).ok_or(INVALID)?.2;
+    }
+    let (_, _, rest) = read_tlv(rest)
+        .filter(|(tag, _, _)| *tag == TAG_INTEGER)
+        .ok_or(INVALID)?;
+    let (_, _, rest) = read_tlv(rest).ok_or(INVALID)?;
+    let (_, issuer, rest) = read_tlv(rest).ok_or(INVALID)?;
+    let (_, validity, rest) = read_tlv(rest).ok_or(INVALID)?;
+    let (_, subject, rest) = read_tlv(rest).ok_or(INVALID)?;
+    let (_, spki, mut rest) = read_tlv(rest).ok_or(INVALID)?;
+
+    let (not_before_tag, not_before, validity) = read_tlv(validity).ok_or(INVALID)?;
+    let (not_after_tag, not_after, _) = read_tlv(validity).ok_or(INVALID)?;
+    let (key_algorithm, key_size) = parse_public_key(spki).ok_or(INVALID)?;
+
+    // Skip the optional unique identifiers until the extensions
+    let mut sans = Vec::new();
+    while let Some((tag, value, next)) = read_tlv(rest) {
+        if tag == TAG_EXTENSIONS {
+            sans = parse_alt_names(value).ok_or(INVALID)?;
+        }
+        rest = next;
+    }
+
+    Ok(CertificateInfo {
+        subject: parse_name(subject),
+        issuer: parse_name(issuer),
+        subject_name: find_name(subject, OID_COMMON_NAME),
+        issuer_name: find_name(issuer, OID_ORGANIZATION)
+            .or_else(|| find_name(issuer, OID_COMMON_NAME)),
+        not_before: parse_time(not_before_tag, not_before).ok_or(INVALID)?,
+        not_after: parse_time(not_after_tag, not_after).ok_or(INVALID)?,
+        sans,
+        key_algorithm,
+        key_size,
+    })
+}
+
+/// Returns the email addresses, DNS names and IP addresses of the Subject
+/// Alternative Name extension.
+fn parse_alt_names(extensions: &[u8]) -> Option<Vec<String>> {
+    let mut sans = Vec::new();
+    let (_, mut extensions, _) = read_tlv(extensions).filter(|(tag, _, _)| *tag == TAG_SEQUENCE)?;
+    while let Some((_, extension, next)) = read_tlv(extensions) {
+        extensions = next;
+        let (_, oid, mut extension) = read_tlv(extension).filter(|(tag, _, _)| *tag == TAG_OID)?;
+        if oid != OID_SUBJECT_ALT_NAME {
+            continue;
+        }
+        if extension.first() == Some(&TAG_BOOLEAN) {
+            extension = read_tlv(extension)?.2;
+        }
+        let (_, value, _) = read_tlv(extension).filter(|(tag, _, _)| *tag == TAG_OCTET_STRING)?;
+        let (_, mut names, _) = read_tlv(value).filter(|(tag, _, _)| *tag == TAG_SEQUENCE)?;
+        while let Some((tag, name, next)) = read_tlv(names) {
+            names = next;
+            match (tag, name.len()) {
+                (TAG_SAN_EMAIL | TAG_SAN_DNS, _) => {
+                    sans.push(String::from_utf8_lossy(name).into_owned())
+                }
+                (TAG_SAN_IP, 4) => {
+                    sans.push(Ipv4Addr::from(<[u8; 4]>::try_from(name).ok()?).to_string())
+                }
+                (TAG_SAN_IP, 16) => {
+                    sans.push(Ipv6Addr::from(<[u8; 16]>::try_from(name).ok()?).to_string())
+                }
+                _ => {}
+            }
+        }
+    }
+    Some(sans)
+}
+
+/// Returns the value of the first attribute of a name with the given OID.
+fn find_name(mut name: &[u8], oid: &[u8]) -> Option<String> {
+    while let Some((TAG_SET, set, next)) = read_tlv(name) {
+        if let Some((TAG_SEQUENCE, attribute, _)) = read_tlv(set) {
+            if let Some(((TAG_OID, attribute_oid, _), (_, value, _))) =
+                read_tlv(attribute).and_then(|oid| read_tlv(oid.2).map(|value| (oid, value)))
+            {
+                if attribute_oid == oid {
+                    return Some(String::from_utf8_lossy(value).into_owned());
+                }
+            }
+        }
+        name = next;
+    }
+    None
+}
+
+fn parse_name(mut name: &[u8]) -> String {
+    let mut parts = Vec::new();
+    while let Some((TAG_SET, set, next)) = read_tlv(name) {
+        if let Some((TAG_SEQUENCE, attribute, _)) = read_tlv(set) {
+            if let Some(((TAG_OID, oid, _), (_, value, _))) =
+                read_tlv(attribute).and_then(|oid| read_tlv(oid.2).map(|value| (oid, value)))
+            {
+                let key = NAME_ATTRIBUTES
+                    .iter()
+                    .find(|(id, _)| *id == oid)
+                    .map(|(_, name)| name.to_string())
+                    .unwrap_or_else(|| format_oid(oid));
+                parts.push(format!("{key}={}", String::from_utf8_lossy(value)));
+            }
+        }
+        name = next;
+    }
+    parts.join(", ")
+}
+
+fn parse_public_key(spki: &[u8]) -> Option<(String, Option<u32>)> {
+    let (_, algorithm, rest) = read_tlv(spki)?;
+    let (_, key, _) = read_tlv(rest).filter(|(tag, _, _)| *tag == TAG_BIT_STRING)?;
+    let (_, oid, params) = read_tlv(algorithm).filter(|(tag, _, _)| *tag == TAG_OID)?;
+
+    Some(if oid == OID_RSA {
+        let (_, rsa_key, _) = read_tlv(key.get(1..)?)?;
+        let (_, modulus, _) = read_tlv(rsa_key).filter(|(tag, _, _)| *tag == TAG_INTEGER)?;
+        let modulus = match modulus.iter().position(|byte| *byte != 0) {
+            Some(pos) => &modulus[pos..],
+            None => &[],
+        };
+        let bits = modulus
+            .first()
+            .map(|first| (modulus.len() as u32 - 1) * 8 + (8 - first.leading_zeros()));
+        ("RSA".to_string(), bits)
+    } else if oid == OID_EC {
+        let curve = read_tlv(params).and_then(|(_, curve, _)| {
+            CURVES
+                .iter()
+                .find(|(id, _, _)| *id == curve)
+                .map(|(_, name, bits)| (*name, *bits))
+        });
+        match curve {
+            Some((name, bits)) => (format!("ECDSA {name}"), Some(bits)),
+            None => ("ECDSA".to_string(), None),
+        }
+    } else if oid == OID_ED25519 {
+        ("Ed25519".to_string(), Some(256))
+    } else {
+        (format_oid(oid), None)
+    })
+}
+
+fn parse_time(tag: u8, value: &[u8]) -> Option<DateTime<Utc>> {
+    let value = std::str::from_utf8(value).ok()?.strip_suffix('Z')?;
+    let (year, rest) = match tag {
+        // Two digit years between 50 and 99 belong to the 20th century
+        TAG_UTC_TIME => {
+            let year = value.get(0..2)?.parse::<i32>().ok()?;
+            (
+                if year < 50 { 2000 + year } else { 1900 + year },
+                &value[2..],
+            )
+        }
+        TAG_GENERALIZED_TIME => (value.get(0..4)?.parse::<i32>().ok()?, &value[4..]),
+        _ => return None,
+    };
+    let part = |range: std::ops::Range<usize>| -> Option<u32> { rest.get(range)?.parse().ok() };
+
+    NaiveDate::from_ymd_opt(year, part(0..2)?, part(2..4)?)?
+        .and_hms_opt(part(4..6)?, part(6..8)?, part(8..10).unwrap_or(0))
+        .map(|date| date.and_utc())
+}
+
+/// Formats an OID in dotted notation, or as hex when an arc does not fit in
+/// 64 bits.
+fn format_oid(oid: &[u8]) -> String {
+    let mut arcs = Vec::new();
+    let mut value = 0u64;
+    for byte in oid {
+        let Some(shifted) = value.checked_mul(128) else {
+            return oid.iter().map(|byte| format!("{byte:02x}")).collect();
+        };
+        value = shifted | (byte & 0x7F) as u64;
+        if byte & 0x80 == 0 {
+            if arcs.is_empty() {
+                // The first subidentifier encodes the first two arcs
+                let first = value.min(80) / 40;
+                arcs.push(first);
+                arcs.push(value - first * 40);
+            } else {
+                arcs.push(value);
+            }
+            value = 0;
+        }
+    }
+    arcs.iter()
+        .map(|arc| arc.to_string())
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Reads a DER tag-length-value, returning the tag, its contents and the
+/// remaining input.
+fn read_tlv(data: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let tag = *data.first()?;
+    let first = *data.get(1)?;
+    let (len, offset) = if first & 0x80 == 0 {
+        (first as usize, 2)
+    } else {
+        let num_bytes = (first & 0x7F) as usize;
+        if num_bytes == 0 || num_bytes > 4 {
+            return None;
+        }
+        let len = data
+            .get(2..2 + num_bytes)?
+            .iter()
+            .fold(0usize, |len, byte| (len << 8) | *byte as usize);
+        (len, 2 + num_bytes)
+    };
+    let end = offset.checked_add(len)?;
+    Some((tag, data.get(offset..end)?, data.get(end..)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    // Self-signed P-256 certificate valid from 2026 (UTCTime) to 2054
+    // (GeneralizedTime), with DNS, IP and email alternative names.
+    const CERTIFICATE: &str = "-----BEGIN CERTIFICATE-----\n\
+MIICHTCCAcOgAwIBAgIUNJxs54NAlNV1YEm5vPKsygWFU0gwCgYIKoZIzj0EAwIw\n\
+PjELMAkGA1UEBhMCVVMxFDASBgNVBAoMC0V4YW1wbGUgT3JnMRkwFwYDVQQDDBBt\n\
+YWlsLmV4YW1wbGUub3JnMCAXDTI2MTAxNzIzMTQxMVoYDzIwNTQwMzA0MjMxNDEx\n\
+WjA+MQswCQYDVQQGEwJVUzEUMBIGA1UECgwLRXhhbXBsZSBPcmcxGTAXBgNVBAMM\n\
+EG1haWwuZXhhbXBsZS5vcmcwWTATBgcqhkjOPQIBBggqhkjOPQMBBwNCAARVGEWr\n\
+Z9zyMJIse9cfb+X9uXnLb8Cd+LDoof5vCkYcPOUYfNUodT3i+pn8wxFpX5dLC5b4\n\
+Q3N6YLtGAKTJfoRlo4GcMIGZMB0GA1UdDgQWBBSeLxllPFpeMCGimDJX7iDznF4Z\n\
+xjAfBgNVHSMEGDAWgBSeLxllPFpeMCGimDJX7iDznF4ZxjAPBgNVHRMBAf8EBTAD\n\
+AQH/MEYGA1UdEQQ/MD2CEG1haWwuZXhhbXBsZS5vcmeCC2V4YW1wbGUub3JnhwTA\n\
+AAIBgRZwb3N0bWFzdGVyQGV4YW1wbGUub3JnMAoGCCqGSM49BAMCA0gAMEUCIHHX\n\
+I1EZcMnWerFcsEPHo9xa2WyVvSFazasdKu6UjVEfAiEAu8EyzSAGTJ24n05GVsyp\n\
+voZVPIZa4Lkn3zpEaN1+NCQ=\n\
+-----END CERTIFICATE-----\n";
+
+    fn certificate_der() -> Vec<u8> {
+        STANDARD
+            .decode(
+                CERTIFICATE
+                    .lines()
+                    .filter(|line| !line.starts_with("-----"))
+                    .collect::<String>(),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn parse_pem_and_der() {
+        let cert = CertificateInfo::parse(CERTIFICATE).unwrap();
+        assert_eq!(cert.subject, "C=US, O=Example Org, CN=mail.example.org");
+        assert_eq!(cert.issuer, cert.subject);
+        assert_eq!(cert.subject_name.as_deref(), Some("mail.example.org"));
+        assert_eq!(cert.issuer_name.as_deref(), Some("Example Org"));
+        assert_eq!(
+            cert.sans,
+            [
+                "mail.example.org",
+                "example.org",
+                "192.0.2.1",
+                "postmaster@example.org"
+            ]
+        );
+        assert_eq!(cert.key_algorithm, "ECDSA P-256");
+        assert_eq!(cert.key_size, Some(256));
+        assert_eq!(
+            cert.not_before,
+            Utc.with_ymd_and_hms(2026, 10, 17, 23, 14, 11).unwrap()
+        );
+        assert_eq!(
+            cert.not_after,
+            Utc.with_ymd_and_hms(2054, 3, 4, 23, 14, 11).unwrap()
+        );
+
+        // ACME providers store base64 encoded PEM or DER certificates
+        assert_eq!(
+            CertificateInfo::parse(&STANDARD.encode(CERTIFICATE)).as_ref(),
+            Some(&cert)
+        );
+        assert_eq!(
+            CertificateInfo::parse(&STANDARD.encode(certificate_der())).as_ref(),
+            Some(&cert)
+        );
+    }
+
+    #[test]
+    fn malformed_length() {
+        // Longer than the input
+        assert_eq!(read_tlv(&[TAG_SEQUENCE, 0x05, 0x00]), None);
+        // Long form without length bytes, with too many or truncated
+        assert_eq!(read_tlv(&[TAG_SEQUENCE, 0x80]), None);
+        assert_eq!(read_tlv(&[TAG_SEQUENCE, 0x85, 0, 0, 0, 0, 1, 0]), None);
+        assert_eq!(read_tlv(&[TAG_SEQUENCE, 0x82, 0x01]), None);
+        assert_eq!(
+            read_tlv(&[TAG_SEQUENCE, 0x84, 0xFF, 0xFF, 0xFF, 0xFF]),
+            None
+        );
+        assert_eq!(
+            read_tlv(&[TAG_SEQUENCE, 0x81, 0x01, 0x00, 0x01]),
+            Some((TAG_SEQUENCE, &[0x00][..], &[0x01][..]))
+        );
+
+        let der = certificate_der();
+        let mut oversized = der.clone();
+        oversized[2] = 0x7F;
+        assert_eq!(
+            parse_certificate(&oversized),
+            Err("Invalid X.509 certificate")
+        );
+        assert_eq!(
+            parse_certificate(&der[..der.len() - 1]),
+            Err("Invalid X.509 certificate")
+        );
+    }
+
+    #[test]
+    fn long_oid_arcs() {
+        assert_eq!(format_oid(OID_RSA), "1.2.840.113549.1.1.1");
+        assert_eq!(
+            format_oid(&[0x2B, 0x06, 0x01, 0x04, 0x01, 0x82, 0x37, 0x15, 0x14]),
+            "1.3.6.1.4.1.311.21.20"
+        );
+        // Multi-byte first subidentifier
+        assert_eq!(format_oid(&[0x88, 0x37, 0x03]), "2.999.3");
+        assert_eq!(
+            format_oid(&[0x2A, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x7F]),
+            "1.2.9223372036854775807"
+        );
+        // Does not fit in 64 bits
+        assert_eq!(
+            format_oid(&[0x2A, 0x82, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x00]),
+            "2a82808080808080808000"
+        );
+    }
+
+    #[test]
+    fn utc_and_generalized_time() {
+        assert_eq!(
+            parse_time(TAG_UTC_TIME, b"491231235959Z"),
+            Utc.with_ymd_and_hms(2049, 12, 31, 23, 59, 59).single()
+        );
+        assert_eq!(
+            parse_time(TAG_UTC_TIME, b"500101000000Z"),
+            Utc.with_ymd_and_hms(1950, 1, 1, 0, 0, 0).single()
+        );
+        assert_eq!(
+            parse_time(TAG_GENERALIZED_TIME, b"20500101000000Z"),
+            Utc.with_ymd_and_hms(2050, 1, 1, 0, 0, 0).single()
+        );
+        assert_eq!(parse_time(TAG_UTC_TIME, b"491231235959"), None);
+        assert_eq!(parse_time(TAG_UTC_TIME, b"20500101000000Z"), None);
+        assert_eq!(parse_time(TAG_GENERALIZED_TIME, b"20501301000000Z"), None);
+        assert_eq!(parse_time(TAG_INTEGER, b"20500101000000Z"), None);
+    }
+}
@@ -10,8 +10,10 @@ use std::{sync::Arc, time::Duration};
 
 use components::{
     icon::{
-        IconAdjustmentsHorizontal, IconDocumentChartBar, IconDocumentText, IconKey, IconLockClosed,
-        IconQueueList, IconShieldCheck, IconSquare2x2, IconUserGroup, IconWrench,
+        IconAdjustmentsHorizontal, IconArrowRightCircle, IconBell, IconChartBar, IconClock,
+        IconCodeBracket, IconDocumentChartBar, IconDocumentText, IconEnvelope, IconId, IconKey,
+        IconLockClosed, IconPaperAirplane, IconQueueList, IconServerStack, IconShieldCheck,
+        IconSquare2x2, IconUserCircle, IconUserGroup, IconWrench,
     },
     layout::MenuItem,
 };
@@ -33,38 +35,82 @@ pub static VERSION_NAME: &str = concat!("Stalwart Management UI v", env!("CARGO_
 use crate::{
     components::{
         layout::{Layout, LayoutBuilder},
-        messages::{alert::init_alerts, modal::init_modals},
+        list::query::init_list_queries,
+        messages::{alert::init_alerts, modal::init_modals, update::UpdatePrompt},
+    },
+    core::{
+        connection::init_connection,
+        crash::{
+            attach_crash_screen_listeners, crash_screen_html, diagnostic_report,
+            init_crash_reporter,
+        },
+        idle::init_idle_lock,
+        maintenance::init_maintenance,
+        notifications::init_notifications,
+        oauth::{oauth_refresh_token, AuthToken},
+        permissions::{use_capability, Capability},
+        request_log::init_request_log,
+        service_worker::init_service_worker,
+        tasks::init_tasks,
+        unsaved::init_unsaved_changes,
     },
-    core::oauth::{oauth_refresh_token, AuthToken},
     pages::{
-        account::{crypto::ManageCrypto, password::ChangePassword},
+        account::{
+            crypto::ManageCrypto, debug::RequestLogSettings, filters::SieveFilters,
+            forwarding::MailForwarding, identities::ManageIdentities,
+            notifications::DesktopNotifications, overview::AccountOverview,
+            password::ChangePassword, session::SessionSettings, vacation::VacationResponder,
+        },
         authorize::Authorize,
-        config::{edit::SettingsEdit, list::SettingsList, search::SettingsSearch},
+        config::{
+            ai_usage::AiModelUsage, certs::CertificateExpiry, edit::SettingsEdit, ip::IpTools,
+            list::SettingsList, search::SettingsSearch, webhook_log::WebhookDeliveries,
+        },
         directory::{
             domains::{display::DomainDisplay, edit::DomainCreate, list::DomainList},
             principals::{edit::PrincipalEdit, list::PrincipalList},
         },
         login::Login,
-        manage::{logs::Logs, maintenance::Maintenance},
+        manage::{
+            api_explorer::ApiExplorer,
+            audit::AuditLog,
+            cluster::ClusterStatus,
+            dashboard::Dashboard,
+            directory_check::DirectoryCheck,
+            live::LiveMetrics,
+            logs::Logs,
+            maintenance::Maintenance,
+            quarantine::SpamQuarantine,
+            self_test::SelfTest,
+            spam_explain::SpamExplain,
+            spans::SpanList,
+            store::StoreInspector,
+            tracking::MessageTracking,
+            troubleshoot::{TroubleshootDelivery, TroubleshootDns},
+        },
         notfound::NotFound,
         queue::{
-            messages::{list::QueueList, manage::QueueManage},
+            messages::{dsn::DsnList, list::QueueList, manage::QueueManage},
             reports::{display::ReportDisplay, list::ReportList},
         },
-        reports::{display::IncomingReportDisplay, list::IncomingReportList},
+        reports::{
+            display::IncomingReportDisplay, list::IncomingReportList, upload::IncomingReportUpload,
+        },
     },
 };
 
 pub mod components;
 pub mod core;
 pub mod pages;
+#[cfg(all(test, target_arch = "wasm32", feature = "demo"))]
+mod tests;
 
 pub const STATE_STORAGE_KEY: &str = "webadmin_state";
 pub const STATE_LOGIN_NAME_KEY: &str = "webadmin_login_name";
 
 fn main() {
     _ = console_log::init_with_level(log::Level::Debug);
-    console_error_panic_hook::set_once();
+    init_crash_reporter();
     leptos::mount_to_body(|| view! { <App/> })
 }
 
@@ -84,6 +130,15 @@ pub fn App() -> impl IntoView {
     provide_context(build_schemas());
     init_alerts();
     init_modals();
+    init_connection();
+    init_maintenance();
+    init_request_log();
+    init_notifications();
+    init_idle_lock();
+    init_service_worker();
+    init_tasks();
+    init_unsaved_changes();
+    init_list_queries();
 
     // Create a resource to refresh the OAuth token
     let _refresh_token_resource = create_resource(
@@ -92,7 +147,11 @@ pub fn App() -> impl IntoView {
             let changed_auth_token = changed_auth_token.clone();
 
             async move {
-                if !changed_auth_token.is_valid && !changed_auth_token.refresh_token.is_empty() {
+                // Locked sessions are only refreshed after the password is entered again
+                if !changed_auth_token.is_valid
+                    && !changed_auth_token.is_locked
+                    && !changed_auth_token.refresh_token.is_empty()
+                {
                     if let Some(grant) = oauth_refresh_token(
                         &changed_auth_token.base_url,
                         &changed_auth_token.refresh_token,
@@ -137,187 +196,374 @@ pub fn App() -> impl IntoView {
 
     let is_logged_in = create_memo(move |_| auth_token.get().is_logged_in());
     let is_admin = create_memo(move |_| auth_token.get().is_admin());
+    let troubleshoot = use_capability(Capability::Troubleshoot);
 
     view! {
-        <Router>
-            <Routes>
-                <ProtectedRoute
-                    path="/manage"
-                    view=move || {
-                        view! { <Layout menu_items=LayoutBuilder::manage() is_admin=is_admin/> }
-                    }
-
-                    redirect_path="/login"
-                    condition=move || is_logged_in.get()
-                >
-                    <ProtectedRoute
-                        path="/directory/domains"
-                        view=DomainList
-                        redirect_path="/login"
-                        condition=move || is_admin.get()
-                    />
-                    <ProtectedRoute
-                        path="/directory/domains/edit"
-                        view=DomainCreate
-                        redirect_path="/login"
-                        condition=move || is_admin.get()
-                    />
+        <UpdatePrompt/>
+        <ErrorBoundary fallback=|errors| {
+            let error = errors
+                .get_untracked()
+                .into_iter()
+                .map(|(_, err)| err.to_string())
+                .collect::<Vec<_>>()
+                .join("\n");
+            let container = create_node_ref::<html::Div>();
+            container.on_load(|container| attach_crash_screen_listeners(&container));
+            view! {
+                <div
+                    node_ref=container
+                    inner_html=crash_screen_html(&diagnostic_report(&error))
+                ></div>
+            }
+        }>
+            <Router>
+                <Routes>
                     <ProtectedRoute
-                        path="/directory/domains/:id/view"
-                        view=DomainDisplay
-                        redirect_path="/login"
-                        condition=move || is_admin.get()
-                    />
+                        path="/manage"
+                        view=move || {
+                            view! { <Layout menu_items=LayoutBuilder::manage() is_admin=is_admin/> }
+                        }
 
-                    <ProtectedRoute
-                        path="/directory/:object"
-                        view=PrincipalList
-                        redirect_path="/login"
-                        condition=move || is_admin.get()
-                    />
-                    <ProtectedRoute
-                        path="/directory/:object/:id?/edit"
-                        view=PrincipalEdit
-                        redirect_path="/login"
-                        condition=move || is_admin.get()
-                    />
-                    <ProtectedRoute
-                        path="/queue/messages"
-                        view=QueueList
-                        redirect_path="/login"
-                        condition=move || is_admin.get()
-                    />
-                    <ProtectedRoute
-                        path="/queue/message/:id"
-                        view=QueueManage
-                        redirect_path="/login"
-                        condition=move || is_admin.get()
-                    />
-                    <ProtectedRoute
-                        path="/queue/reports"
-                        view=ReportList
-                        redirect_path="/login"
-                        condition=move || is_admin.get()
-                    />
-                    <ProtectedRoute
-                        path="/queue/report/:id"
-                        view=ReportDisplay
-                        redirect_path="/login"
-                        condition=move || is_admin.get()
-                    />
-                    <ProtectedRoute
-                        path="/reports/:object"
-                        view=IncomingReportList
-                        redirect_path="/login"
-                        condition=move || is_admin.get()
-                    />
-                    <ProtectedRoute
-                        path="/reports/:object/:id"
-                        view=IncomingReportDisplay
-                        redirect_path="/login"
-                        condition=move || is_admin.get()
-                    />
-                    <ProtectedRoute
-                        path="/logs"
-                        view=Logs
-                        redirect_path="/login"
-                        condition=move || is_admin.get()
-                    />
-                    <ProtectedRoute
-                        path="/spam/train"
-                        view=SpamTrain
-                        redirect_path="/login"
-                        condition=move || is_admin.get()
-                    />
-                    <ProtectedRoute
-                        path="/spam/test"
-                        view=SpamTest
                         redirect_path="/login"
-                        condition=move || is_admin.get()
-                    />
-                    <ProtectedRoute
-                        path="/maintenance"
-                        view=Maintenance
-                        redirect_path="/login"
-                        condition=move || is_admin.get()
-                    />
-                </ProtectedRoute>
-                <ProtectedRoute
-                    path="/settings"
-                    view=move || {
-                        view! { <Layout menu_items=LayoutBuilder::settings() is_admin=is_admin/> }
-                    }
+                        condition=move || is_logged_in.get()
+                    >
+                        <ProtectedRoute
+                            path="/dashboard"
+                            view=Dashboard
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/dashboard/live"
+                            view=LiveMetrics
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/directory/domains"
+                            view=DomainList
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/directory/domains/edit"
+                            view=DomainCreate
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/directory/domains/:id/view"
+                            view=DomainDisplay
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
 
-                    redirect_path="/login"
-                    condition=move || is_admin.get()
-                >
+                        <ProtectedRoute
+                            path="/directory/:object"
+                            view=PrincipalList
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/directory/:object/:id?/edit"
+                            view=PrincipalEdit
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/queue/messages"
+                            view=QueueList
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/queue/message/:id"
+                            view=QueueManage
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/queue/dsn"
+                            view=DsnList
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/queue/reports"
+                            view=ReportList
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/queue/report/:id"
+                            view=ReportDisplay
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/reports/upload"
+                            view=IncomingReportUpload
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/reports/:object"
+                            view=IncomingReportList
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/reports/:object/:id"
+                            view=IncomingReportDisplay
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/tracking/attempts"
+                            view=SpanList
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/tracking"
+                            view=MessageTracking
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/cluster"
+                            view=ClusterStatus
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/audit"
+                            view=AuditLog
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/logs"
+                            view=Logs
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/spam/train"
+                            view=SpamTrain
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/spam/test"
+                            view=SpamTest
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/spam/quarantine"
+                            view=SpamQuarantine
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/spam/explain"
+                            view=SpamExplain
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/maintenance"
+                            view=Maintenance
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/maintenance/self-test"
+                            view=SelfTest
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/api-explorer"
+                            view=ApiExplorer
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/maintenance/directory-check"
+                            view=DirectoryCheck
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/troubleshoot/delivery"
+                            view=TroubleshootDelivery
+                            redirect_path="/login"
+                            condition=move || is_admin.get() && !troubleshoot.is_denied()
+                        />
+                        <ProtectedRoute
+                            path="/troubleshoot/dns"
+                            view=TroubleshootDns
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/troubleshoot/store"
+                            view=StoreInspector
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                    </ProtectedRoute>
                     <ProtectedRoute
-                        path="/:object"
-                        view=SettingsList
-                        redirect_path="/login"
-                        condition=move || is_admin.get()
-                    />
-                    <ProtectedRoute
-                        path="/:object/:id?/edit"
-                        view=SettingsEdit
+                        path="/settings"
+                        view=move || {
+                            view! { <Layout menu_items=LayoutBuilder::settings() is_admin=is_admin/> }
+                        }
+
                         redirect_path="/login"
                         condition=move || is_admin.get()
-                    />
+                    >
+                        <ProtectedRoute
+                            path="/:object"
+                            view=SettingsList
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/:object/:id?/edit"
+                            view=SettingsEdit
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/:object/tools"
+                            view=IpTools
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/web-hooks/deliveries"
+                            view=WebhookDeliveries
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/ai-model/usage"
+                            view=AiModelUsage
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/certificates/expiry"
+                            view=CertificateExpiry
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                        <ProtectedRoute
+                            path="/search"
+                            view=SettingsSearch
+                            redirect_path="/login"
+                            condition=move || is_admin.get()
+                        />
+                    </ProtectedRoute>
                     <ProtectedRoute
-                        path="/search"
-                        view=SettingsSearch
-                        redirect_path="/login"
-                        condition=move || is_admin.get()
-                    />
-                </ProtectedRoute>
-                <ProtectedRoute
-                    path="/account"
-                    view=move || {
-                        view! { <Layout menu_items=LayoutBuilder::account() is_admin=is_admin/> }
-                    }
+                        path="/account"
+                        view=move || {
+                            view! { <Layout menu_items=LayoutBuilder::account() is_admin=is_admin/> }
+                        }
 
-                    redirect_path="/login"
-                    condition=move || is_logged_in.get()
-                >
-                    <ProtectedRoute
-                        path="/crypto"
-                        view=ManageCrypto
-                        redirect_path="/login"
-                        condition=move || is_logged_in.get()
-                    />
-                    <ProtectedRoute
-                        path="/password"
-                        view=ChangePassword
-                        redirect_path="/login"
-                        condition=move || is_logged_in.get()
-                    />
-                    <ProtectedRoute
-                        path="/mfa"
-                        view=ManageMfa
-                        redirect_path="/login"
-                        condition=move || is_logged_in.get()
-                    />
-                    <ProtectedRoute
-                        path="/app-passwords"
-                        view=AppPasswords
                         redirect_path="/login"
                         condition=move || is_logged_in.get()
-                    />
-                    <ProtectedRoute
-                        path="/app-passwords/edit"
-                        view=AppPasswordCreate
-                        redirect_path="/login"
-                        condition=move || is_logged_in.get()
-                    />
+                    >
+                        <ProtectedRoute
+                            path="/overview"
+                            view=AccountOverview
+                            redirect_path="/login"
+                            condition=move || is_logged_in.get()
+                        />
+                        <ProtectedRoute
+                            path="/vacation"
+                            view=VacationResponder
+                            redirect_path="/login"
+                            condition=move || is_logged_in.get()
+                        />
+                        <ProtectedRoute
+                            path="/identities"
+                            view=ManageIdentities
+                            redirect_path="/login"
+                            condition=move || is_logged_in.get()
+                        />
+                        <ProtectedRoute
+                            path="/forwarding"
+                            view=MailForwarding
+                            redirect_path="/login"
+                            condition=move || is_logged_in.get()
+                        />
+                        <ProtectedRoute
+                            path="/filters"
+                            view=SieveFilters
+                            redirect_path="/login"
+                            condition=move || is_logged_in.get()
+                        />
+                        <ProtectedRoute
+                            path="/crypto"
+                            view=ManageCrypto
+                            redirect_path="/login"
+                            condition=move || is_logged_in.get()
+                        />
+                        <ProtectedRoute
+                            path="/password"
+                            view=ChangePassword
+                            redirect_path="/login"
+                            condition=move || is_logged_in.get()
+                        />
+                        <ProtectedRoute
+                            path="/mfa"
+                            view=ManageMfa
+                            redirect_path="/login"
+                            condition=move || is_logged_in.get()
+                        />
+                        <ProtectedRoute
+                            path="/notifications"
+                            view=DesktopNotifications
+                            redirect_path="/login"
+                            condition=move || is_logged_in.get()
+                        />
+                        <ProtectedRoute
+                            path="/session"
+                            view=SessionSettings
+                            redirect_path="/login"
+                            condition=move || is_logged_in.get()
+                        />
+                        <ProtectedRoute
+                            path="/debug"
+                            view=RequestLogSettings
+                            redirect_path="/login"
+                            condition=move || is_logged_in.get()
+                        />
+                        <ProtectedRoute
+                            path="/app-passwords"
+                            view=AppPasswords
+                            redirect_path="/login"
+                            condition=move || is_logged_in.get()
+                        />
+                        <ProtectedRoute
+                            path="/app-passwords/edit"
+                            view=AppPasswordCreate
+                            redirect_path="/login"
+                            condition=move || is_logged_in.get()
+                        />
 
-                </ProtectedRoute>
+                    </ProtectedRoute>
 
-                <Route path="/" view=Login/>
-                <Route path="/login" view=Login/>
-                <Route path="/authorize/:type?" view=Authorize/>
-                <Route path="/*any" view=NotFound/>
-            </Routes>
-        </Router>
+                    <Route path="/" view=Login/>
+                    <Route path="/login" view=Login/>
+                    <Route path="/authorize/:type?" view=Authorize/>
+                    <Route path="/*any" view=NotFound/>
+                </Routes>
+            </Router>
+        </ErrorBoundary>
         <div id="portal_root"></div>
     }
 }
@@ -325,6 +571,10 @@ pub fn App() -> impl IntoView {
 impl LayoutBuilder {
     pub fn manage() -> Vec<MenuItem> {
         LayoutBuilder::new("/manage")
+            .create("Dashboard")
+            .icon(view! { <IconChartBar/> })
+            .route("/dashboard")
+            .insert()
             .create("Directory")
             .icon(view! { <IconUserGroup/> })
             .create("Accounts")
@@ -348,6 +598,12 @@ impl LayoutBuilder {
             .create("Reports")
             .route("/queue/reports")
             .insert()
+            .create("Bounces")
+            .route("/queue/dsn")
+            .insert()
+            .create("Tracking")
+            .route("/tracking")
+            .insert()
             .insert()
             .create("Reports")
             .icon(view! { <IconDocumentChartBar/> })
@@ -369,11 +625,42 @@ impl LayoutBuilder {
             .create("Test")
             .route("/spam/test")
             .insert()
+            .create("Quarantine")
+            .route("/spam/quarantine")
+            .insert()
+            .create("Explain score")
+            .route("/spam/explain")
+            .insert()
+            .insert()
+            .create("Troubleshoot")
+            .icon(view! { <IconPaperAirplane/> })
+            .create("Delivery")
+            .route("/troubleshoot/delivery")
+            .capability(Capability::Troubleshoot)
+            .insert()
+            .create("DNS")
+            .route("/troubleshoot/dns")
+            .insert()
+            .create("In-memory store")
+            .route("/troubleshoot/store")
+            .insert()
+            .insert()
+            .create("Cluster")
+            .icon(view! { <IconServerStack/> })
+            .route("/cluster")
             .insert()
             .create("Logs")
             .icon(view! { <IconDocumentText/> })
             .route("/logs")
             .insert()
+            .create("Audit")
+            .icon(view! { <IconId/> })
+            .route("/audit")
+            .insert()
+            .create("API Explorer")
+            .icon(view! { <IconCodeBracket/> })
+            .route("/api-explorer")
+            .insert()
             .create("Settings")
             .icon(view! { <IconAdjustmentsHorizontal/> })
             .raw_route(DEFAULT_SETTINGS_URL)
@@ -387,6 +674,26 @@ impl LayoutBuilder {
 
     pub fn account() -> Vec<MenuItem> {
         LayoutBuilder::new("/account")
+            .create("Overview")
+            .icon(view! { <IconUserCircle/> })
+            .route("/overview")
+            .insert()
+            .create("Identities")
+            .icon(view! { <IconEnvelope/> })
+            .route("/identities")
+            .insert()
+            .create("Vacation Responder")
+            .icon(view! { <IconPaperAirplane/> })
+            .route("/vacation")
+            .insert()
+            .create("Filters")
+            .icon(view! { <IconAdjustmentsHorizontal/> })
+            .route("/filters")
+            .insert()
+            .create("Forwarding")
+            .icon(view! { <IconArrowRightCircle/> })
+            .route("/forwarding")
+            .insert()
             .create("Encryption-at-rest")
             .icon(view! { <IconLockClosed/> })
             .route("/crypto")
@@ -403,6 +710,18 @@ impl LayoutBuilder {
             .icon(view! { <IconSquare2x2/> })
             .route("/app-passwords")
             .insert()
+            .create("Notifications")
+            .icon(view! { <IconBell/> })
+            .route("/notifications")
+            .insert()
+            .create("Session")
+            .icon(view! { <IconClock/> })
+            .route("/session")
+            .insert()
+            .create("Debugging")
+            .icon(view! { <IconCodeBracket/> })
+            .route("/debug")
+            .insert()
             .menu_items
     }
 }
@@ -410,6 +729,7 @@ impl LayoutBuilder {
 pub fn build_schemas() -> Arc<Schemas> {
     Schemas::builder()
         .build_login()
+        .build_request_log()
         .build_principals()
         .build_domains()
         .build_store()
@@ -420,6 +740,8 @@ pub fn build_schemas() -> Arc<Schemas> {
         .build_server()
         .build_listener()
         .build_tracing()
+        .build_metrics()
+        .build_alerts()
         .build_smtp_inbound()
         .build_smtp_outbound()
         .build_mail_auth()
@@ -428,11 +750,22 @@ pub fn build_schemas() -> Arc<Schemas> {
         .build_sieve()
         .build_spam_lists()
         .build_spam_manage()
+        .build_ai()
+        .build_ip_tools()
+        .build_troubleshoot()
+        .build_queue_simulator()
+        .build_report_preview()
+        .build_throttle_simulator()
         .build_password_change()
         .build_crypto()
         .build_authorize()
         .build_mfa()
         .build_app_passwords()
+        .build_vacation()
+        .build_identity()
+        .build_forwarding()
+        .build_notifications()
+        .build_session()
         .build()
         .into()
 }
@@ -6,7 +6,7 @@
 
 use std::{collections::HashSet, sync::Arc};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use chrono_humanize::HumanTime;
 use leptos::*;
 use leptos_router::{use_navigate, use_query_map};
@@ -17,9 +17,8 @@ use serde::{Deserialize, Serialize};
 use crate::{
     components::{
         form::{
-            button::Button,
-            input::InputText,
-            Form, FormButtonBar, FormElement, FormItem, FormSection,
+            button::Button, input::InputText, Form, FormButtonBar, FormElement, FormItem,
+            FormSection,
         },
         icon::{IconAdd, IconTrash},
         list::{
@@ -43,8 +42,8 @@ use crate::{
         url::UrlBuilder,
     },
     pages::{
-        account::{AccountAuthRequest, AccountAuthResponse},
-        maybe_plural, List,
+        account::{AccountAuthRequest, AccountAuthResponse, AppPasswordUsage},
+        maybe_plural, FormatDateTime, List,
     },
 };
 
@@ -55,10 +54,21 @@ struct AppPassword {
     id: String,
     name: String,
     created: Option<DateTime<Utc>>,
+    usage: Option<AppPasswordUsage>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct AppPasswordPage {
+    list: List<AppPassword>,
+    // None when the server does not track app password usage
+    unused: Option<Vec<String>>,
 }
 
 const PAGE_SIZE: u32 = 10;
 
+/// Passwords not used for this many days are offered for revocation.
+const UNUSED_DAYS: i64 = 90;
+
 #[component]
 pub fn AppPasswords() -> impl IntoView {
     let query = use_query_map();
@@ -98,11 +108,17 @@ pub fn AppPasswords() -> impl IntoView {
                     .send::<AccountAuthResponse>()
                     .await?;
                 let mut items = Vec::with_capacity(response.app_passwords.len());
+                let mut unused = response.app_passwords_usage.is_some().then(Vec::new);
                 let mut offset = PAGE_SIZE * page.saturating_sub(1);
                 let total = response.app_passwords.len() as u64;
+                let mut usage = response.app_passwords_usage;
+                let unused_since = Utc::now() - Duration::days(UNUSED_DAYS);
 
                 for id in response.app_passwords {
                     let mut app_password = AppPassword {
+                        usage: usage
+                            .as_mut()
+                            .map(|usage| usage.remove(&id).unwrap_or_default()),
                         id,
                         name: String::new(),
                         created: None,
@@ -129,14 +145,23 @@ pub fn AppPasswords() -> impl IntoView {
                         app_password.name.clone_from(&app_password.id);
                     }
 
+                    if let (Some(unused), Some(usage)) = (&mut unused, &app_password.usage) {
+                        if usage
+                            .last_used
+                            .or(app_password.created)
+                            .map_or(true, |last_used| last_used < unused_since)
+                        {
+                            unused.push(app_password.id.clone());
+                        }
+                    }
+
                     if filter
                         .as_ref()
                         .map_or(true, |filter| app_password.name.contains(filter))
                     {
                         if offset == 0 {
-                            items.push(app_password);
-                            if items.len() as u32 >= PAGE_SIZE {
-                                break;
+                            if (items.len() as u32) < PAGE_SIZE {
+                                items.push(app_password);
                             }
                         } else {
                             offset -= 1;
@@ -144,7 +169,10 @@ pub fn AppPasswords() -> impl IntoView {
                     }
                 }
 
-                Ok(Arc::new(List { items, total }))
+                Ok(Arc::new(AppPasswordPage {
+                    list: List { items, total },
+                    unused,
+                }))
             }
         },
     );
@@ -233,6 +261,56 @@ pub fn AppPasswords() -> impl IntoView {
                         <IconTrash/>
                     </ToolbarButton>
 
+                    <ToolbarButton
+                        text="Revoke unused"
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| {
+                            let Some(unused) = passwords
+                                .get()
+                                .and_then(|result| result.ok())
+                                .and_then(|page| page.unused.clone())
+                            else {
+                                alert
+                                    .set(
+                                        Alert::warning(
+                                            "This server does not report when app passwords were last used.",
+                                        ),
+                                    );
+                                return;
+                            };
+                            if unused.is_empty() {
+                                alert
+                                    .set(
+                                        Alert::success(
+                                            format!(
+                                                "All passwords were used in the last {UNUSED_DAYS} days.",
+                                            ),
+                                        ),
+                                    );
+                                return;
+                            }
+                            let text = maybe_plural(unused.len(), "password", "passwords");
+                            modal
+                                .set(
+                                    Modal::with_title("Revoke unused passwords")
+                                        .with_message(
+                                            format!(
+                                                "Are you sure you want to revoke {text} that have not been used in the last {UNUSED_DAYS} days? Applications still using them will need a new password.",
+                                            ),
+                                        )
+                                        .with_button(format!("Revoke {text}"))
+                                        .with_dangerous_callback(move || {
+                                            selected.set(HashSet::new());
+                                            delete_action
+                                                .dispatch(Arc::new(unused.iter().cloned().collect()));
+                                        }),
+                                )
+                        })
+                    >
+
+                        <IconTrash/>
+                    </ToolbarButton>
+
                     <ToolbarButton
                         text=format!("Add {}", "password")
                         color=Color::Blue
@@ -258,26 +336,31 @@ pub fn AppPasswords() -> impl IntoView {
                             alert.set(Alert::from(err));
                             Some(view! { <Skeleton/> }.into_view())
                         }
-                        Some(Ok(passwords)) if !passwords.items.is_empty() => {
-                            total_results.set(Some(passwords.total as u32));
+                        Some(Ok(passwords)) if !passwords.list.items.is_empty() => {
+                            total_results.set(Some(passwords.list.total as u32));
                             let passwords_ = passwords.clone();
                             Some(
                                 view! {
                                     <ColumnList
-                                        headers=vec!["Name".to_string(), "Created".to_string()]
+                                        headers=vec![
+                                            "Name".to_string(),
+                                            "Created".to_string(),
+                                            "Last used".to_string(),
+                                        ]
 
                                         select_all=Callback::new(move |_| {
                                             passwords_
+                                                .list
                                                 .items
                                                 .iter()
-                                                .map(|p| p.name.to_string())
+                                                .map(|p| p.id.to_string())
                                                 .collect::<Vec<_>>()
                                         })
                                     >
 
                                         <For
-                                            each=move || passwords.items.clone()
-                                            key=|password| password.name.clone()
+                                            each=move || passwords.list.items.clone()
+                                            key=|password| password.id.clone()
                                             let:password
                                         >
                                             <PasswordItem password/>
@@ -358,10 +441,38 @@ fn PasswordItem(password: AppPassword) -> impl IntoView {
             </ListItem>
 
             <ListItem subclass="px-6 py-1.5">
-                {password
-                    .created
-                    .map(|created| HumanTime::from(created).to_string())
-                    .unwrap_or_default()}
+                <span
+                    class="text-sm text-gray-500"
+                    title=password.created.map(|created| created.format_date_time())
+                >
+                    {password
+                        .created
+                        .map(|created| HumanTime::from(created).to_string())
+                        .unwrap_or_default()}
+                </span>
+            </ListItem>
+
+            <ListItem subclass="px-6 py-1.5">
+                {match password.usage.map(|usage| (usage.last_used, usage.last_ip)) {
+                    Some((Some(last_used), last_ip)) => {
+                        view! {
+                            <div class="flex flex-col">
+                                <span class="text-sm text-gray-800 dark:text-gray-200" title=last_used.format_date_time()>
+                                    {HumanTime::from(last_used).to_string()}
+                                </span>
+                                <span class="text-xs text-gray-500 font-mono">
+                                    {last_ip.unwrap_or_default()}
+                                </span>
+                            </div>
+                        }
+                            .into_view()
+                    }
+                    Some((None, _)) => {
+                        view! { <span class="text-sm text-gray-500">Never</span> }.into_view()
+                    }
+                    None => view! { <span class="text-sm text-gray-500">-</span> }.into_view(),
+                }}
+
             </ListItem>
 
         </tr>
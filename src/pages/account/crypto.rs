@@ -12,22 +12,29 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     components::{
+        badge::Badge,
         form::{
             button::Button,
             input::{InputPassword, InputText, TextArea},
             select::Select,
             Form, FormButtonBar, FormElement, FormItem, FormSection,
         },
-        messages::alert::{use_alerts, Alert},
+        messages::{
+            alert::{use_alerts, Alert},
+            modal::{use_modals, Modal},
+        },
         skeleton::Skeleton,
         Color,
     },
     core::{
+        file::read_file,
         form::FormData,
         http::{self, Error, HttpRequest},
         oauth::use_authorization,
         schema::{Builder, Schemas, Source, Transformer, Type, Validator},
+        x509::{parse_certificates, pem_label, to_pem, CertificateInfo},
     },
+    pages::FormatDateTime,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -194,6 +201,8 @@ pub fn ManageCrypto() -> impl IntoView {
                                         >
                                             <TextArea element=FormElement::new("certs", data)/>
                                         </FormItem>
+
+                                        <SmimeCertificates data=data/>
                                     </Show>
 
                                 </FormSection>
@@ -236,6 +245,302 @@ pub fn ManageCrypto() -> impl IntoView {
     }
 }
 
+/// Encryption-at-rest settings of another account, for administrators that
+/// need to disable encryption or replace the keys of a user who lost theirs.
+#[component]
+pub fn PrincipalCrypto(name: String) -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let modal = use_modals();
+    let name = store_value(name);
+    let fetch_crypto = create_resource(
+        move || (),
+        move |_| {
+            let auth = auth.get_untracked();
+            let name = name.get_value();
+
+            async move {
+                HttpRequest::get(("/api/principal/crypto", &name))
+                    .with_authorization(&auth)
+                    .send::<EncryptionType>()
+                    .await
+            }
+        },
+    );
+
+    let data = expect_context::<Arc<Schemas>>()
+        .build_form("principal-crypto")
+        .into_signal();
+
+    let save_changes = create_action(move |changes: &EncryptionType| {
+        let changes = changes.clone();
+        let auth = auth.get();
+        let name = name.get_value();
+
+        async move {
+            let is_disable = matches!(changes, EncryptionType::Disabled);
+            let result = HttpRequest::post(("/api/principal/crypto", &name))
+                .with_authorization(&auth)
+                .with_body(changes)
+                .unwrap()
+                .send::<Option<u32>>()
+                .await;
+
+            alert.set(match result {
+                Ok(_) => {
+                    fetch_crypto.refetch();
+                    if !is_disable {
+                        Alert::success(format!("Encryption keys of {name:?} updated"))
+                    } else {
+                        Alert::success(format!("Encryption-at-rest disabled for {name:?}"))
+                            .with_details(concat!(
+                                "Incoming messages for this account will be stored ",
+                                "in their original form."
+                            ))
+                    }
+                }
+                Err(Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                    return;
+                }
+                Err(err) => Alert::from(err),
+            });
+        }
+    });
+
+    view! {
+        <Transition>
+            {move || match fetch_crypto.get() {
+                None => None,
+                Some(Err(Error::NotFound)) => None,
+                Some(Err(err)) => {
+                    alert.set(Alert::from(err));
+                    None
+                }
+                Some(Ok(crypto)) => {
+                    let (color, status) = match &crypto {
+                        EncryptionType::PGP { algo, .. } => {
+                            (Color::Green, format!("OpenPGP ({})", algo.name()))
+                        }
+                        EncryptionType::SMIME { algo, .. } => {
+                            (Color::Green, format!("S/MIME ({})", algo.name()))
+                        }
+                        EncryptionType::Disabled => (Color::Gray, "Disabled".to_string()),
+                    };
+                    let is_enabled = !matches!(crypto, EncryptionType::Disabled);
+                    data.update(|data| {
+                        data.from_encryption_params(&crypto);
+                    });
+                    let has_no_crypto = create_memo(move |_| {
+                        data.get().value::<EncryptionMethod>("type").is_none()
+                    });
+                    Some(
+                        view! {
+                            <FormSection title="Encryption-at-rest".to_string()>
+                                <FormItem label="Status">
+                                    <Badge color=color>{status}</Badge>
+                                </FormItem>
+                                <FormItem
+                                    label="Encryption type"
+                                    tooltip="Whether to use OpenPGP or S/MIME for encryption."
+                                >
+                                    <Select element=FormElement::new("type", data)/>
+                                </FormItem>
+                                <FormItem
+                                    label="Algorithm"
+                                    tooltip="The encryption algorithms to use"
+                                    hide=has_no_crypto
+                                >
+                                    <Select element=FormElement::new("algo", data)/>
+                                </FormItem>
+                                <FormItem
+                                    label="Certificates"
+                                    tooltip="The armored OpenPGP certificate or S/MIME certificate in PEM format."
+                                    hide=has_no_crypto
+                                >
+                                    <TextArea element=FormElement::new("certs", data)/>
+                                </FormItem>
+                                <SmimeCertificates data=data/>
+                                <FormItem label="">
+                                    <div class="flex gap-x-2">
+                                        <Button
+                                            text="Update encryption"
+                                            color=Color::Blue
+                                            on_click=move |_| {
+                                                data.update(|data| {
+                                                    if let Some(changes) = data.to_encryption_params() {
+                                                        save_changes.dispatch(changes);
+                                                    }
+                                                });
+                                            }
+
+                                            disabled=save_changes.pending()
+                                        />
+                                        <Show when=move || is_enabled>
+                                            <Button
+                                                text="Disable encryption"
+                                                color=Color::Red
+                                                on_click=move |_| {
+                                                    modal
+                                                        .set(
+                                                            Modal::with_title("Disable encryption-at-rest")
+                                                                .with_message(
+                                                                    format!(
+                                                                        "Are you sure you want to disable encryption-at-rest for {:?}? Messages received from now on will be stored unencrypted.",
+                                                                        name.get_value(),
+                                                                    ),
+                                                                )
+                                                                .with_button("Disable")
+                                                                .with_dangerous_callback(move || {
+                                                                    save_changes.dispatch(EncryptionType::Disabled);
+                                                                }),
+                                                        )
+                                                }
+
+                                                disabled=save_changes.pending()
+                                            />
+                                        </Show>
+                                    </div>
+                                </FormItem>
+                            </FormSection>
+                        }
+                            .into_view(),
+                    )
+                }
+            }}
+
+        </Transition>
+    }
+}
+
+/// Upload button and parsed details of the S/MIME certificates entered in
+/// the encryption form.
+#[component]
+fn SmimeCertificates(data: RwSignal<FormData>) -> impl IntoView {
+    let alert = use_alerts();
+    let is_smime = create_memo(move |_| {
+        data.with(|data| data.value::<EncryptionMethod>("type") == Some(EncryptionMethod::SMIME))
+    });
+    let certificates = create_memo(move |_| {
+        data.with(|data| {
+            data.value_as_str("certs")
+                .map(|certs| certs.trim())
+                .filter(|certs| !certs.is_empty())
+                .map(|certs| parse_certificates(certs.as_bytes()))
+        })
+    });
+
+    let upload_action = create_action(move |file: &web_sys::File| {
+        let file = file.clone();
+
+        async move {
+            match read_file(&file).await {
+                Ok(contents) => {
+                    // Binary certificates are converted to PEM before submission
+                    let certs = match pem_label(&contents) {
+                        Some(label) if contents.first() == Some(&0x30) => to_pem(label, &contents),
+                        _ => String::from_utf8_lossy(&contents).into_owned(),
+                    };
+                    data.update(|data| data.update("certs", certs));
+                }
+                Err(err) => {
+                    alert.set(Alert::error("Failed to read certificate file").with_details(err));
+                }
+            }
+        }
+    });
+
+    view! {
+        <FormItem
+            label="Certificate details"
+            tooltip="Upload a PEM, DER or PKCS#7 file, or paste the certificates above."
+            hide=Signal::derive(move || !is_smime.get())
+        >
+            <input
+                type="file"
+                accept=".pem,.crt,.cer,.der,.p7b,.p7c"
+                class="block w-full text-sm text-gray-500 file:me-4 file:py-2 file:px-4 file:rounded-lg file:border-0 file:text-sm file:font-semibold file:bg-blue-600 file:text-white hover:file:bg-blue-700 file:disabled:opacity-50 file:disabled:pointer-events-none dark:text-neutral-500"
+                on:change=move |ev| {
+                    let input = event_target::<web_sys::HtmlInputElement>(&ev);
+                    if let Some(file) = input.files().and_then(|files| files.get(0)) {
+                        upload_action.dispatch(file);
+                    }
+                    input.set_value("");
+                }
+            />
+
+            {move || match certificates.get() {
+                None => None,
+                Some(Err(err)) => {
+                    Some(
+                        view! {
+                            <p class="mt-3 text-sm text-red-600 dark:text-red-500">{err}</p>
+                        }
+                            .into_view(),
+                    )
+                }
+                Some(Ok(certs)) => {
+                    Some(
+                        certs
+                            .into_iter()
+                            .map(|cert| view! { <CertificateCard cert=cert/> })
+                            .collect_view(),
+                    )
+                }
+            }}
+
+        </FormItem>
+    }
+}
+
+#[component]
+fn CertificateCard(cert: CertificateInfo) -> impl IntoView {
+    let (color, status) = if cert.is_expired() {
+        (Color::Red, "Expired")
+    } else if cert.is_not_yet_valid() {
+        (Color::Yellow, "Not yet valid")
+    } else {
+        (Color::Green, "Valid")
+    };
+    let key = match cert.key_size {
+        Some(bits) => format!("{} ({bits} bits)", cert.key_algorithm),
+        None => cert.key_algorithm.clone(),
+    };
+    let is_expired = cert.is_expired();
+    let has_sans = !cert.sans.is_empty();
+    let sans = cert.sans.join(", ");
+
+    view! {
+        <div class="mt-3 p-3 border border-gray-200 rounded-lg text-sm dark:border-gray-700">
+            <div class="flex items-center gap-x-2">
+                <Badge color=color>{status}</Badge>
+                <span class="font-semibold text-gray-800 dark:text-gray-200 break-all">
+                    {cert.subject}
+                </span>
+            </div>
+            <dl class="mt-2 grid grid-cols-[auto_1fr] gap-x-3 gap-y-1 text-gray-500">
+                <dt>Issuer</dt>
+                <dd class="break-all">{cert.issuer}</dd>
+                <Show when=move || has_sans>
+                    <dt>Alternative names</dt>
+                    <dd class="break-all">{sans.clone()}</dd>
+                </Show>
+                <dt>Valid from</dt>
+                <dd>{cert.not_before.format_date_time()}</dd>
+                <dt>Expires</dt>
+                <dd>{cert.not_after.format_date_time()}</dd>
+                <dt>Key</dt>
+                <dd>{key}</dd>
+            </dl>
+            <Show when=move || is_expired>
+                <p class="mt-2 text-sm text-red-600 dark:text-red-500">
+                    This certificate has expired, messages encrypted with it may not be readable by all clients.
+                </p>
+            </Show>
+        </div>
+    }
+}
+
 #[allow(clippy::wrong_self_convention)]
 impl FormData {
     fn from_encryption_params(&mut self, params: &EncryptionType) {
@@ -263,10 +568,17 @@ impl FormData {
                     algo: self.value("algo").unwrap(),
                     certs: self.value("certs").unwrap(),
                 },
-                Some(EncryptionMethod::SMIME) => EncryptionType::SMIME {
-                    algo: self.value("algo").unwrap(),
-                    certs: self.value("certs").unwrap(),
-                },
+                Some(EncryptionMethod::SMIME) => {
+                    let certs = self.value::<String>("certs").unwrap();
+                    if let Err(err) = parse_certificates(certs.as_bytes()) {
+                        self.new_error("certs", err);
+                        return None;
+                    }
+                    EncryptionType::SMIME {
+                        algo: self.value("algo").unwrap(),
+                        certs,
+                    }
+                }
                 None => EncryptionType::Disabled,
             }
             .into()
@@ -284,51 +596,62 @@ impl Builder<Schemas, ()> {
             ("", "Disabled"),
         ];
         const ALGOS: &[(&str, &str)] = &[
-            (Algorithm::Aes128.as_str(), "AES-128"),
-            (Algorithm::Aes256.as_str(), "AES-256"),
+            (Algorithm::Aes128.as_str(), Algorithm::Aes128.name()),
+            (Algorithm::Aes256.as_str(), Algorithm::Aes256.name()),
         ];
 
-        self.new_schema("crypto-at-rest")
-            .new_field("type")
-            .typ(Type::Select {
-                source: Source::Static(METHODS),
-                multi: false,
-            })
-            .default("")
-            .build()
-            .new_field("algo")
-            .typ(Type::Select {
-                source: Source::Static(ALGOS),
-                multi: false,
-            })
-            .default(Algorithm::Aes256.as_str())
-            .display_if_eq(
-                "type",
-                [
-                    EncryptionMethod::PGP.as_str(),
-                    EncryptionMethod::SMIME.as_str(),
-                ],
-            )
-            .build()
-            .new_field("certs")
-            .typ(Type::Text)
-            .input_check([], [Validator::Required])
-            .display_if_eq(
-                "type",
-                [
-                    EncryptionMethod::PGP.as_str(),
-                    EncryptionMethod::SMIME.as_str(),
-                ],
-            )
-            .build()
-            .new_field("password")
-            .typ(Type::Text)
-            .input_check([], [Validator::Required])
-            .build()
-            .new_field("totp-code")
-            .input_check([Transformer::Trim], [])
-            .build()
-            .build()
+        let mut builder = self;
+        for schema_id in ["crypto-at-rest", "principal-crypto"] {
+            let mut schema = builder
+                .new_schema(schema_id)
+                .new_field("type")
+                .typ(Type::Select {
+                    source: Source::Static(METHODS),
+                    multi: false,
+                })
+                .default("")
+                .build()
+                .new_field("algo")
+                .typ(Type::Select {
+                    source: Source::Static(ALGOS),
+                    multi: false,
+                })
+                .default(Algorithm::Aes256.as_str())
+                .display_if_eq(
+                    "type",
+                    [
+                        EncryptionMethod::PGP.as_str(),
+                        EncryptionMethod::SMIME.as_str(),
+                    ],
+                )
+                .build()
+                .new_field("certs")
+                .typ(Type::Text)
+                .input_check([], [Validator::Required])
+                .display_if_eq(
+                    "type",
+                    [
+                        EncryptionMethod::PGP.as_str(),
+                        EncryptionMethod::SMIME.as_str(),
+                    ],
+                )
+                .build();
+
+            // Administrators changing the settings of another account are
+            // not asked for the account's password
+            if schema_id == "crypto-at-rest" {
+                schema = schema
+                    .new_field("password")
+                    .typ(Type::Text)
+                    .input_check([], [Validator::Required])
+                    .build()
+                    .new_field("totp-code")
+                    .input_check([Transformer::Trim], [])
+                    .build();
+            }
+            builder = schema.build();
+        }
+        builder
     }
 }
 
@@ -360,6 +683,13 @@ impl Algorithm {
             Algorithm::Aes256 => "aes256",
         }
     }
+
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Algorithm::Aes128 => "AES-128",
+            Algorithm::Aes256 => "AES-256",
+        }
+    }
 }
 
 impl FromStr for Algorithm {
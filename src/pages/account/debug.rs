@@ -0,0 +1,185 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use leptos::*;
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{button::Button, input::InputSwitch, Form, FormElement, FormItem, FormSection},
+        list::table::{Table, TableRow},
+        messages::alert::{use_alerts, Alert},
+        Color,
+    },
+    core::{
+        clipboard::copy_to_clipboard,
+        oauth::use_authorization,
+        permissions::Capability,
+        request_log::use_request_log,
+        schema::{Builder, Schemas, Type},
+    },
+};
+
+#[component]
+pub fn RequestLogSettings() -> impl IntoView {
+    let log = use_request_log();
+    let alert = use_alerts();
+    let auth = use_authorization();
+    let schemas = expect_context::<Arc<Schemas>>();
+    let data = schemas.build_form("request-log").into_signal();
+    data.update(|data| {
+        let enabled = log.with_untracked(|log| log.enabled);
+        data.update("enabled", if enabled { "true" } else { "false" });
+        let show_curl = log.with_untracked(|log| log.show_curl);
+        data.update("curl", if show_curl { "true" } else { "false" });
+    });
+
+    create_effect(move |_| {
+        let enabled = data.with(|data| data.value::<bool>("enabled").unwrap_or_default());
+        if log.with_untracked(|log| log.enabled) != enabled {
+            log.update(|log| log.set_enabled(enabled));
+        }
+    });
+    create_effect(move |_| {
+        let show_curl = data.with(|data| data.value::<bool>("curl").unwrap_or_default());
+        if log.with_untracked(|log| log.show_curl) != show_curl {
+            log.update(|log| log.set_show_curl(show_curl));
+        }
+    });
+
+    view! {
+        <Form title="Debugging" subtitle="Troubleshoot problems with the management interface">
+            <FormSection>
+                <FormItem
+                    label="Request log"
+                    tooltip="Records every management API call made by this browser and shows them in an overlay"
+                >
+                    <InputSwitch
+                        element=FormElement::new("enabled", data)
+                        label="Show API request overlay".to_string()
+                    />
+                </FormItem>
+                <FormItem
+                    label="curl commands"
+                    tooltip="Records each request as an equivalent curl command that can be copied from the overlay, with the access token replaced by $TOKEN"
+                >
+                    <InputSwitch
+                        element=FormElement::new("curl", data)
+                        label="Record requests as curl commands".to_string()
+                        disabled=Signal::derive(move || log.with(|log| !log.enabled))
+                    />
+                </FormItem>
+                <FormItem label="Recorded requests">
+                    <div class="flex items-center gap-x-2">
+                        <span class="text-sm text-gray-600 dark:text-gray-400 me-2">
+                            {move || log.with(|log| log.entries.len())}
+                        </span>
+                        <Button
+                            text="Copy"
+                            color=Color::Gray
+                            disabled=Signal::derive(move || {
+                                log.with(|log| log.entries.is_empty())
+                            })
+                            on_click=move |_| {
+                                if copy_to_clipboard(&log.with_untracked(|log| log.to_text())) {
+                                    alert.set(Alert::success("Request log copied to clipboard"));
+                                } else {
+                                    alert.set(Alert::error("Failed to copy to clipboard"));
+                                }
+                            }
+                        />
+
+                        <Button
+                            text="Clear"
+                            color=Color::Gray
+                            disabled=Signal::derive(move || {
+                                log.with(|log| log.entries.is_empty())
+                            })
+                            on_click=move |_| log.update(|log| log.entries.clear())
+                        />
+
+                    </div>
+                </FormItem>
+            </FormSection>
+            <FormSection title="Permissions".to_string()>
+                <FormItem
+                    label="Reported permissions"
+                    tooltip="Permissions returned by the server when signing in, which decide the actions available in the web admin"
+                >
+                    <span class="text-sm text-gray-600 dark:text-gray-400">
+                        {move || {
+                            auth.with(|auth| {
+                                let permissions = &auth.permissions;
+                                if !permissions.is_reported() {
+                                    "Not reported by the server, all actions are available"
+                                        .to_string()
+                                } else if permissions.is_read_only() {
+                                    format!("{} permissions, read-only access", permissions.len())
+                                } else {
+                                    format!("{} permissions", permissions.len())
+                                }
+                            })
+                        }}
+
+                    </span>
+                </FormItem>
+                <Table headers=vec![
+                    "Action".to_string(),
+                    "Required permissions".to_string(),
+                    "Status".to_string(),
+                ]>
+                    {move || {
+                        auth.with(|auth| {
+                            Capability::ALL
+                                .iter()
+                                .map(|capability| {
+                                    let required = capability.permissions().join(", ");
+                                    let status = match auth.permissions.denied_reason(*capability) {
+                                        None => {
+                                            view! { <Badge color=Color::Green>Allowed</Badge> }
+                                        }
+                                        Some(reason) => {
+                                            view! {
+                                                <Badge color=Color::Red attr:title=reason>
+                                                    Denied
+                                                </Badge>
+                                            }
+                                        }
+                                    };
+                                    view! {
+                                        <TableRow>
+                                            <span>{capability.description()}</span>
+                                            <span class="font-mono">{required}</span>
+                                            {status}
+                                        </TableRow>
+                                    }
+                                })
+                                .collect_view()
+                        })
+                    }}
+
+                </Table>
+            </FormSection>
+        </Form>
+    }
+}
+
+impl Builder<Schemas, ()> {
+    pub fn build_request_log(self) -> Self {
+        self.new_schema("request-log")
+            .new_field("enabled")
+            .typ(Type::Boolean)
+            .default("false")
+            .build()
+            .new_field("curl")
+            .typ(Type::Boolean)
+            .default("false")
+            .build()
+            .build()
+    }
+}
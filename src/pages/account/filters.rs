@@ -0,0 +1,954 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    components::{
+        form::{button::Button, Form, FormButtonBar, FormItem, FormSection},
+        messages::alert::{use_alerts, Alert},
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        http::{self, ManagementApiError},
+        jmap::{jmap_download, jmap_primary_account, jmap_request_using, jmap_upload},
+        oauth::{use_authorization, AuthToken},
+    },
+};
+
+use super::forwarding::Forwarding;
+
+const USING_SIEVE: &[&str] = &["urn:ietf:params:jmap:core", "urn:ietf:params:jmap:sieve"];
+const SCRIPT_NAME: &str = "webadmin";
+const RULES_MARKER: &str = "# webadmin-rules: ";
+const FORWARDING_MARKER: &str = "# webadmin-forwarding: ";
+
+const INPUT_CLASS: &str = "py-2 px-3 block w-full border-gray-200 shadow-sm text-sm rounded-lg focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600";
+const SELECT_CLASS: &str = "py-2 px-3 pe-9 block border-gray-200 shadow-sm rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600";
+const LINK_CLASS: &str =
+    "text-sm text-blue-600 decoration-2 hover:underline font-medium dark:text-blue-500";
+
+/// Message parts a condition can look at.
+const FIELDS: &[(&str, &str)] = &[
+    ("from", "From"),
+    ("to", "To"),
+    ("cc", "Cc"),
+    ("subject", "Subject"),
+    ("header", "Header"),
+    ("size", "Size"),
+];
+
+const TEXT_OPERATORS: &[(&str, &str)] = &[
+    ("contains", "contains"),
+    ("is", "is"),
+    ("matches", "matches wildcard"),
+];
+
+const SIZE_OPERATORS: &[(&str, &str)] = &[("over", "is larger than"), ("under", "is smaller than")];
+
+/// Actions offered by the rule builder, and the placeholder of their
+/// argument for those that take one.
+const ACTIONS: &[(&str, &str, Option<&str>)] = &[
+    ("fileinto", "Move to folder", Some("Folder")),
+    ("redirect", "Redirect to", Some("Email address")),
+    ("flag", "Flag message", None),
+    ("seen", "Mark as read", None),
+    ("discard", "Discard", None),
+];
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct FilterRule {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    match_any: bool,
+    #[serde(default)]
+    conditions: Vec<Condition>,
+    #[serde(default)]
+    actions: Vec<FilterAction>,
+    #[serde(default)]
+    stop: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct Condition {
+    field: String,
+    #[serde(default)]
+    header: String,
+    operator: String,
+    #[serde(default)]
+    value: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct FilterAction {
+    kind: String,
+    #[serde(default)]
+    value: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActiveScript {
+    pub account_id: String,
+    pub script_id: Option<String>,
+    pub script: String,
+}
+
+/// Contents of a script generated by the web admin, which are embedded in
+/// the script itself.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ManagedScript {
+    rules: Vec<FilterRule>,
+    pub forwarding: Option<Forwarding>,
+}
+
+impl Default for FilterRule {
+    fn default() -> Self {
+        FilterRule {
+            name: String::new(),
+            match_any: false,
+            conditions: vec![Condition::default()],
+            actions: vec![FilterAction::default()],
+            stop: false,
+        }
+    }
+}
+
+impl Default for Condition {
+    fn default() -> Self {
+        Condition {
+            field: FIELDS[0].0.to_string(),
+            header: String::new(),
+            operator: TEXT_OPERATORS[0].0.to_string(),
+            value: String::new(),
+        }
+    }
+}
+
+impl Default for FilterAction {
+    fn default() -> Self {
+        FilterAction {
+            kind: ACTIONS[0].0.to_string(),
+            value: String::new(),
+        }
+    }
+}
+
+/// Manages the active Sieve script of the account. Scripts created by the
+/// rule builder embed their rules so they can be edited again, any other
+/// script is opened in the raw editor.
+#[component]
+pub fn SieveFilters() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let rules = create_rw_signal(Vec::<FilterRule>::new());
+    let raw = create_rw_signal(String::new());
+    let advanced = create_rw_signal(false);
+    let forwarding = create_rw_signal(None::<Forwarding>);
+    let managed = move || ManagedScript {
+        rules: rules.get(),
+        forwarding: forwarding.get(),
+    };
+
+    let active = create_resource(
+        move || (),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move { fetch_active_script(&auth).await }
+        },
+    );
+
+    let save_action = create_action(
+        move |(account_id, script_id, script): &(String, Option<String>, String)| {
+            let auth = auth.get();
+            let account_id = account_id.clone();
+            let script_id = script_id.clone();
+            let script = script.clone();
+
+            async move {
+                match save_script(&auth, &account_id, script_id.as_deref(), script).await {
+                    Ok(_) => {
+                        alert.set(Alert::success("Filters saved"));
+                        active.refetch();
+                    }
+                    Err(http::Error::Unauthorized) => {
+                        use_navigate()("/login", Default::default());
+                    }
+                    Err(err) => {
+                        alert.set(Alert::from(err));
+                    }
+                }
+            }
+        },
+    );
+
+    view! {
+        <Form title="Filters" subtitle="Sort, flag or forward incoming messages automatically">
+            <Transition fallback=Skeleton>
+                {move || match active.get() {
+                    None => None,
+                    Some(Err(http::Error::Unauthorized)) => {
+                        use_navigate()("/login", Default::default());
+                        Some(view! { <div></div> }.into_view())
+                    }
+                    Some(Err(err)) => {
+                        alert.set(Alert::from(err));
+                        Some(view! { <Skeleton/> }.into_view())
+                    }
+                    Some(Ok(active)) => {
+                        let parsed = parse_script(&active.script);
+                        advanced.set(!active.script.trim().is_empty() && parsed.is_none());
+                        let parsed = parsed.unwrap_or_default();
+                        rules.set(parsed.rules);
+                        forwarding.set(parsed.forwarding);
+                        raw.set(active.script);
+                        let target = store_value((active.account_id, active.script_id));
+                        Some(
+                            view! {
+                                <FormSection>
+                                    <Show
+                                        when=move || !advanced.get()
+                                        fallback=move || {
+                                            view! {
+                                                <FormItem
+                                                    label="Script"
+                                                    tooltip="Sieve script that is run on every incoming message"
+                                                >
+                                                    <textarea
+                                                        class=format!("{INPUT_CLASS} font-mono")
+                                                        rows=20
+                                                        spellcheck="false"
+                                                        prop:value=move || raw.get()
+                                                        on:input=move |ev| raw.set(event_target_value(&ev))
+                                                    ></textarea>
+                                                </FormItem>
+                                            }
+                                        }
+                                    >
+
+                                        <FormItem
+                                            label="Rules"
+                                            tooltip="Rules are applied in order to every incoming message"
+                                        >
+                                            <div class="space-y-4">
+                                                <For
+                                                    each=move || 0..rules.with(|rules| rules.len())
+                                                    key=|idx| *idx
+                                                    let:idx
+                                                >
+                                                    <RuleCard rules=rules idx=idx/>
+                                                </For>
+                                            </div>
+                                            <p
+                                                class="mt-2.5 text-sm text-gray-500"
+                                                class:hidden=move || {
+                                                    rules.with(|rules| !rules.is_empty())
+                                                }
+                                            >
+
+                                                No filters have been created.
+                                            </p>
+                                            {move || {
+                                                forwarding
+                                                    .get()
+                                                    .filter(|forwarding| forwarding.is_active())
+                                                    .map(|forwarding| {
+                                                        view! {
+                                                            <p class="mt-2.5 text-sm text-gray-500">
+                                                                {if forwarding.keep_copy {
+                                                                    format!(
+                                                                        "A copy of every message is forwarded to {}. ",
+                                                                        forwarding.address,
+                                                                    )
+                                                                } else {
+                                                                    format!(
+                                                                        "Messages are forwarded to {} and these rules are not applied. ",
+                                                                        forwarding.address,
+                                                                    )
+                                                                }}
+                                                                <a class=LINK_CLASS href="/account/forwarding">
+                                                                    Manage forwarding
+                                                                </a>
+                                                            </p>
+                                                        }
+                                                    })
+                                            }}
+                                            <button
+                                                type="button"
+                                                class=format!("mt-2 {LINK_CLASS}")
+                                                on:click=move |_| {
+                                                    rules.update(|rules| rules.push(FilterRule::default()));
+                                                }
+                                            >
+
+                                                Add rule
+                                            </button>
+                                        </FormItem>
+                                    </Show>
+                                    <div class="sm:col-span-12">
+                                        <button
+                                            type="button"
+                                            class=LINK_CLASS
+                                            on:click=move |_| {
+                                                if advanced.get() {
+                                                    match raw.with(|raw| parse_script(raw)) {
+                                                        Some(parsed) => {
+                                                            rules.set(parsed.rules);
+                                                            forwarding.set(parsed.forwarding);
+                                                        }
+                                                        None if raw.with(|raw| !raw.trim().is_empty()) => {
+                                                            alert
+                                                                .set(
+                                                                    Alert::warning(
+                                                                        "This script was not created by the rule builder, saving from the builder will replace it",
+                                                                    ),
+                                                                );
+                                                        }
+                                                        None => {}
+                                                    }
+                                                    advanced.set(false);
+                                                } else {
+                                                    raw.set(generate_script(&managed()));
+                                                    advanced.set(true);
+                                                }
+                                            }
+                                        >
+
+                                            {move || {
+                                                if advanced.get() {
+                                                    "Use the rule builder"
+                                                } else {
+                                                    "Edit as Sieve script"
+                                                }
+                                            }}
+
+                                        </button>
+                                    </div>
+                                </FormSection>
+
+                                <FormButtonBar>
+                                    <Button
+                                        text="Save changes"
+                                        color=Color::Blue
+                                        on_click=Callback::new(move |_| {
+                                            let script = if advanced.get() {
+                                                raw.get()
+                                            } else {
+                                                match rules.with(|rules| validate_rules(rules)) {
+                                                    Ok(_) => generate_script(&managed()),
+                                                    Err(err) => {
+                                                        alert.set(Alert::warning(err));
+                                                        return;
+                                                    }
+                                                }
+                                            };
+                                            let (account_id, script_id) = target.get_value();
+                                            save_action.dispatch((account_id, script_id, script));
+                                        })
+
+                                        disabled=save_action.pending()
+                                    />
+                                </FormButtonBar>
+                            }
+                                .into_view(),
+                        )
+                    }
+                }}
+
+            </Transition>
+        </Form>
+    }
+}
+
+#[component]
+fn RuleCard(rules: RwSignal<Vec<FilterRule>>, idx: usize) -> impl IntoView {
+    let rule = create_memo(move |_| rules.with(|rules| rules.get(idx).cloned()));
+    let update = move |f: &dyn Fn(&mut FilterRule)| {
+        rules.update(|rules| {
+            if let Some(rule) = rules.get_mut(idx) {
+                f(rule);
+            }
+        });
+    };
+    let num_conditions = move || rule.with(|rule| rule.as_ref().map_or(0, |r| r.conditions.len()));
+    let num_actions = move || rule.with(|rule| rule.as_ref().map_or(0, |r| r.actions.len()));
+
+    view! {
+        <div class="p-4 space-y-3 border border-gray-200 rounded-lg dark:border-gray-700">
+            <div class="flex gap-x-2">
+                <input
+                    type="text"
+                    class=INPUT_CLASS
+                    placeholder=format!("Rule {}", idx + 1)
+                    prop:value=move || rule.get().map(|r| r.name).unwrap_or_default()
+                    on:input=move |ev| {
+                        let value = event_target_value(&ev);
+                        update(&|rule| rule.name = value.clone());
+                    }
+                />
+                <select
+                    class=SELECT_CLASS
+                    on:change=move |ev| {
+                        let match_any = event_target_value(&ev) == "any";
+                        update(&|rule| rule.match_any = match_any);
+                    }
+                >
+                    <option value="all" selected=move || rule.get().is_some_and(|r| !r.match_any)>
+                        All conditions
+                    </option>
+                    <option value="any" selected=move || rule.get().is_some_and(|r| r.match_any)>
+                        Any condition
+                    </option>
+                </select>
+                <button
+                    type="button"
+                    class="py-2 px-3 text-sm text-gray-500 hover:text-red-600"
+                    on:click=move |_| {
+                        rules
+                            .update(|rules| {
+                                if idx < rules.len() {
+                                    rules.remove(idx);
+                                }
+                            });
+                    }
+                >
+
+                    Remove
+                </button>
+            </div>
+
+            <p class="text-xs font-semibold uppercase text-gray-500">Conditions</p>
+            <div class="space-y-2">
+                <For each=move || 0..num_conditions() key=|idx| *idx let:condition>
+                    <ConditionRow rules=rules rule=idx idx=condition/>
+                </For>
+            </div>
+            <p class="text-sm text-gray-500" class:hidden=move || num_conditions() != 0>
+                Applies to all messages.
+            </p>
+            <button
+                type="button"
+                class=LINK_CLASS
+                on:click=move |_| update(&|rule| rule.conditions.push(Condition::default()))
+            >
+                Add condition
+            </button>
+
+            <p class="text-xs font-semibold uppercase text-gray-500">Actions</p>
+            <div class="space-y-2">
+                <For each=move || 0..num_actions() key=|idx| *idx let:action>
+                    <ActionRow rules=rules rule=idx idx=action/>
+                </For>
+            </div>
+            <button
+                type="button"
+                class=LINK_CLASS
+                on:click=move |_| update(&|rule| rule.actions.push(FilterAction::default()))
+            >
+                Add action
+            </button>
+
+            <label class="flex items-center gap-x-2 text-sm text-gray-800 dark:text-gray-200">
+                <input
+                    type="checkbox"
+                    class="shrink-0 border-gray-200 rounded text-blue-600 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:checked:bg-blue-500 dark:checked:border-blue-500"
+                    prop:checked=move || rule.get().is_some_and(|r| r.stop)
+                    on:change=move |ev| {
+                        let stop = event_target_checked(&ev);
+                        update(&|rule| rule.stop = stop);
+                    }
+                />
+                Stop processing further rules
+            </label>
+        </div>
+    }
+}
+
+#[component]
+fn ConditionRow(rules: RwSignal<Vec<FilterRule>>, rule: usize, idx: usize) -> impl IntoView {
+    let condition = create_memo(move |_| {
+        rules.with(|rules| {
+            rules
+                .get(rule)
+                .and_then(|rule| rule.conditions.get(idx))
+                .cloned()
+        })
+    });
+    let update = move |f: &dyn Fn(&mut Condition)| {
+        rules.update(|rules| {
+            if let Some(condition) = rules
+                .get_mut(rule)
+                .and_then(|rule| rule.conditions.get_mut(idx))
+            {
+                f(condition);
+            }
+        });
+    };
+    let field = move || condition.get().map(|c| c.field).unwrap_or_default();
+
+    view! {
+        <div class="flex gap-x-2">
+            <select
+                class=SELECT_CLASS
+                on:change=move |ev| {
+                    let value = event_target_value(&ev);
+                    if let Some((field, _)) = FIELDS.iter().find(|(id, _)| *id == value) {
+                        let operators = operators(field);
+                        update(
+                            &|condition| {
+                                condition.field = field.to_string();
+                                if !operators.iter().any(|(op, _)| *op == condition.operator) {
+                                    condition.operator = operators[0].0.to_string();
+                                }
+                            },
+                        );
+                    }
+                }
+            >
+
+                {FIELDS
+                    .iter()
+                    .map(|(id, label)| {
+                        let id = *id;
+                        view! {
+                            <option value=id selected=move || field() == id>
+                                {*label}
+                            </option>
+                        }
+                    })
+                    .collect_view()}
+            </select>
+            <input
+                type="text"
+                class=INPUT_CLASS
+                class:hidden=move || field() != "header"
+                placeholder="X-Header"
+                prop:value=move || condition.get().map(|c| c.header).unwrap_or_default()
+                on:input=move |ev| {
+                    let value = event_target_value(&ev);
+                    update(&|condition| condition.header = value.clone());
+                }
+            />
+            <select
+                class=SELECT_CLASS
+                on:change=move |ev| {
+                    let value = event_target_value(&ev);
+                    update(&|condition| condition.operator = value.clone());
+                }
+            >
+
+                {move || {
+                    let operator = condition.get().map(|c| c.operator).unwrap_or_default();
+                    operators(&field())
+                        .iter()
+                        .map(|(op, label)| {
+                            view! {
+                                <option value=*op selected=*op == operator>
+                                    {*label}
+                                </option>
+                            }
+                        })
+                        .collect_view()
+                }}
+
+            </select>
+            <input
+                type="text"
+                class=INPUT_CLASS
+                placeholder=move || if field() == "size" { "10M" } else { "Value" }
+                prop:value=move || condition.get().map(|c| c.value).unwrap_or_default()
+                on:input=move |ev| {
+                    let value = event_target_value(&ev);
+                    update(&|condition| condition.value = value.clone());
+                }
+            />
+            <button
+                type="button"
+                class="py-2 px-3 text-sm text-gray-500 hover:text-red-600"
+                on:click=move |_| {
+                    rules
+                        .update(|rules| {
+                            if let Some(rule) = rules.get_mut(rule) {
+                                if idx < rule.conditions.len() {
+                                    rule.conditions.remove(idx);
+                                }
+                            }
+                        });
+                }
+            >
+
+                Remove
+            </button>
+        </div>
+    }
+}
+
+#[component]
+fn ActionRow(rules: RwSignal<Vec<FilterRule>>, rule: usize, idx: usize) -> impl IntoView {
+    let action = create_memo(move |_| {
+        rules.with(|rules| {
+            rules
+                .get(rule)
+                .and_then(|rule| rule.actions.get(idx))
+                .cloned()
+        })
+    });
+    let update = move |f: &dyn Fn(&mut FilterAction)| {
+        rules.update(|rules| {
+            if let Some(action) = rules
+                .get_mut(rule)
+                .and_then(|rule| rule.actions.get_mut(idx))
+            {
+                f(action);
+            }
+        });
+    };
+    let kind = move || action.get().map(|a| a.kind).unwrap_or_default();
+    let placeholder = move || {
+        let kind = kind();
+        ACTIONS
+            .iter()
+            .find(|(id, _, _)| *id == kind)
+            .and_then(|(_, _, placeholder)| *placeholder)
+    };
+
+    view! {
+        <div class="flex gap-x-2">
+            <select
+                class=SELECT_CLASS
+                on:change=move |ev| {
+                    let value = event_target_value(&ev);
+                    update(&|action| action.kind = value.clone());
+                }
+            >
+
+                {ACTIONS
+                    .iter()
+                    .map(|(id, label, _)| {
+                        let id = *id;
+                        view! {
+                            <option value=id selected=move || kind() == id>
+                                {*label}
+                            </option>
+                        }
+                    })
+                    .collect_view()}
+            </select>
+            <input
+                type="text"
+                class=INPUT_CLASS
+                class:invisible=move || placeholder().is_none()
+                placeholder=move || placeholder().unwrap_or_default()
+                prop:value=move || action.get().map(|a| a.value).unwrap_or_default()
+                on:input=move |ev| {
+                    let value = event_target_value(&ev);
+                    update(&|action| action.value = value.clone());
+                }
+            />
+            <button
+                type="button"
+                class="py-2 px-3 text-sm text-gray-500 hover:text-red-600 disabled:opacity-50 disabled:pointer-events-none"
+                disabled=move || {
+                    rules.with(|rules| rules.get(rule).map_or(0, |r| r.actions.len()) < 2)
+                }
+
+                on:click=move |_| {
+                    rules
+                        .update(|rules| {
+                            if let Some(rule) = rules.get_mut(rule) {
+                                if idx < rule.actions.len() {
+                                    rule.actions.remove(idx);
+                                }
+                            }
+                        });
+                }
+            >
+
+                Remove
+            </button>
+        </div>
+    }
+}
+
+fn operators(field: &str) -> &'static [(&'static str, &'static str)] {
+    if field == "size" {
+        SIZE_OPERATORS
+    } else {
+        TEXT_OPERATORS
+    }
+}
+
+/// Fetches the active Sieve script of the primary account, returning an
+/// empty script when none is active.
+pub async fn fetch_active_script(auth: &AuthToken) -> http::Result<ActiveScript> {
+    let account_id = jmap_primary_account(auth).await?;
+    let response = jmap_request_using(
+        auth,
+        USING_SIEVE,
+        vec![(
+            "SieveScript/get",
+            json!({"accountId": account_id, "ids": null}),
+        )],
+    )
+    .await?;
+    let active = response.first().and_then(|response| {
+        response["list"].as_array()?.iter().find_map(|script| {
+            if script["isActive"].as_bool() == Some(true) {
+                Some((
+                    script["id"].as_str()?.to_string(),
+                    script["blobId"].as_str()?.to_string(),
+                ))
+            } else {
+                None
+            }
+        })
+    });
+
+    match active {
+        Some((script_id, blob_id)) => {
+            let contents = jmap_download(auth, &account_id, &blob_id).await?;
+            Ok(ActiveScript {
+                account_id,
+                script_id: Some(script_id),
+                script: String::from_utf8_lossy(&contents).into_owned(),
+            })
+        }
+        None => Ok(ActiveScript {
+            account_id,
+            ..Default::default()
+        }),
+    }
+}
+
+/// Replaces the contents of the active script, or creates and activates a
+/// new one when the account has no active script.
+pub async fn save_script(
+    auth: &AuthToken,
+    account_id: &str,
+    script_id: Option<&str>,
+    script: String,
+) -> http::Result<()> {
+    let blob_id = jmap_upload(auth, account_id, "application/sieve", script).await?;
+    let (arguments, not_saved, id) = match script_id {
+        Some(script_id) => (
+            json!({
+                "accountId": account_id,
+                "update": {script_id: {"blobId": blob_id}}
+            }),
+            "notUpdated",
+            script_id,
+        ),
+        None => (
+            json!({
+                "accountId": account_id,
+                "create": {"filters": {"name": SCRIPT_NAME, "blobId": blob_id}},
+                "onSuccessActivateScript": "#filters"
+            }),
+            "notCreated",
+            "filters",
+        ),
+    };
+    let response =
+        jmap_request_using(auth, USING_SIEVE, vec![("SieveScript/set", arguments)]).await?;
+
+    match response.first().map(|response| &response[not_saved][id]) {
+        Some(error) if !error.is_null() => Err(http::Error::Server(ManagementApiError::Other {
+            details: error["description"]
+                .as_str()
+                .unwrap_or("Failed to save the filters")
+                .to_string(),
+        })),
+        _ => Ok(()),
+    }
+}
+
+/// Returns the contents embedded in a script generated by the web admin,
+/// provided it has not been modified since.
+pub fn parse_script(script: &str) -> Option<ManagedScript> {
+    let script = script.replace("\r\n", "\n");
+    let rules = script
+        .lines()
+        .find_map(|line| line.strip_prefix(RULES_MARKER))
+        .and_then(|rules| serde_json::from_str::<Vec<FilterRule>>(rules).ok())?;
+    let forwarding = match script
+        .lines()
+        .find_map(|line| line.strip_prefix(FORWARDING_MARKER))
+    {
+        Some(forwarding) => Some(serde_json::from_str::<Forwarding>(forwarding).ok()?),
+        None => None,
+    };
+    let managed = ManagedScript { rules, forwarding };
+
+    (generate_script(&managed).trim() == script.trim()).then_some(managed)
+}
+
+pub fn generate_script(managed: &ManagedScript) -> String {
+    let rules = &managed.rules;
+    let forwarding = managed
+        .forwarding
+        .as_ref()
+        .filter(|forwarding| forwarding.is_active());
+    let mut script = String::from("# Generated by the filter rule builder, do not edit\n");
+    script.push_str(RULES_MARKER);
+    script.push_str(&serde_json::to_string(rules).unwrap_or_default());
+    script.push('\n');
+    if let Some(forwarding) = &managed.forwarding {
+        script.push_str(FORWARDING_MARKER);
+        script.push_str(&serde_json::to_string(forwarding).unwrap_or_default());
+        script.push('\n');
+    }
+
+    let uses = |kinds: &[&str]| {
+        rules
+            .iter()
+            .flat_map(|rule| &rule.actions)
+            .any(|action| kinds.contains(&action.kind.as_str()))
+    };
+    let extensions = [
+        ("\"fileinto\"", uses(&["fileinto"])),
+        ("\"imap4flags\"", uses(&["flag", "seen"])),
+        (
+            "\"copy\"",
+            forwarding.is_some_and(|forwarding| forwarding.keep_copy),
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(extension, used)| used.then_some(extension))
+    .collect::<Vec<_>>();
+    if !extensions.is_empty() {
+        script.push_str(&format!("require [{}];\n", extensions.join(", ")));
+    }
+
+    if let Some(forwarding) = forwarding {
+        let address = quote(&forwarding.address);
+        if forwarding.keep_copy {
+            script.push_str(&format!("\n# Forwarding\nredirect :copy {address};\n"));
+        } else {
+            script.push_str(&format!("\n# Forwarding\nredirect {address};\nstop;\n"));
+        }
+    }
+
+    for (idx, rule) in rules.iter().enumerate() {
+        let name = rule.name.replace(['\r', '\n'], " ");
+        let name = name.trim();
+        if name.is_empty() {
+            script.push_str(&format!("\n# Rule {}\n", idx + 1));
+        } else {
+            script.push_str(&format!("\n# {name}\n"));
+        }
+
+        let tests = rule.conditions.iter().map(test).collect::<Vec<_>>();
+        let test = match tests.len() {
+            0 => "true".to_string(),
+            1 => tests[0].clone(),
+            _ => format!(
+                "{} ({})",
+                if rule.match_any { "anyof" } else { "allof" },
+                tests.join(", ")
+            ),
+        };
+        script.push_str(&format!("if {test} {{\n"));
+        for action in &rule.actions {
+            let value = quote(action.value.trim());
+            let command = match action.kind.as_str() {
+                "fileinto" => format!("fileinto {value}"),
+                "redirect" => format!("redirect {value}"),
+                "flag" => "addflag \"\\\\Flagged\"".to_string(),
+                "seen" => "addflag \"\\\\Seen\"".to_string(),
+                _ => "discard".to_string(),
+            };
+            script.push_str(&format!("    {command};\n"));
+        }
+        if rule.stop {
+            script.push_str("    stop;\n");
+        }
+        script.push_str("}\n");
+    }
+
+    script
+}
+
+fn test(condition: &Condition) -> String {
+    let operator = &condition.operator;
+    let value = quote(&condition.value);
+    match condition.field.as_str() {
+        "size" => format!(
+            "size :{operator} {}",
+            condition.value.trim().to_ascii_uppercase()
+        ),
+        "subject" => format!("header :{operator} \"subject\" {value}"),
+        "header" => format!(
+            "header :{operator} {} {value}",
+            quote(condition.header.trim())
+        ),
+        field => format!("address :{operator} \"{field}\" {value}"),
+    }
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn validate_rules(rules: &[FilterRule]) -> Result<(), String> {
+    for (idx, rule) in rules.iter().enumerate() {
+        let name = if rule.name.trim().is_empty() {
+            format!("Rule {}", idx + 1)
+        } else {
+            rule.name.trim().to_string()
+        };
+
+        for condition in &rule.conditions {
+            let value = condition.value.trim();
+            if condition.field == "size" {
+                let digits = value.trim_end_matches(['k', 'K', 'm', 'M', 'g', 'G']);
+                if digits.is_empty()
+                    || value.len() - digits.len() > 1
+                    || !digits.chars().all(|ch| ch.is_ascii_digit())
+                {
+                    return Err(format!(
+                        "{name}: sizes are entered in bytes with an optional K, M or G suffix"
+                    ));
+                }
+                continue;
+            }
+            let header = condition.header.trim();
+            if condition.field == "header"
+                && (header.is_empty()
+                    || !header
+                        .chars()
+                        .all(|ch| ch.is_ascii_alphanumeric() || ch == '-'))
+            {
+                return Err(format!("{name}: enter a valid header name"));
+            }
+            if value.is_empty() {
+                return Err(format!("{name}: conditions must have a value"));
+            }
+        }
+
+        if rule.actions.is_empty() {
+            return Err(format!("{name}: add at least one action"));
+        }
+        for action in &rule.actions {
+            let value = action.value.trim();
+            match action.kind.as_str() {
+                "fileinto" if value.is_empty() => {
+                    return Err(format!("{name}: enter the folder to move messages to"));
+                }
+                "redirect" if !value.contains('@') => {
+                    return Err(format!("{name}: enter a valid redirect address"));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(())
+}
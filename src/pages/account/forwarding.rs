@@ -0,0 +1,502 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use leptos::*;
+use leptos_router::use_navigate;
+use pwhash::sha512_crypt;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    components::{
+        form::{
+            button::Button,
+            input::{InputSwitch, InputText},
+            Form, FormButtonBar, FormElement, FormItem, FormSection,
+        },
+        messages::alert::{use_alerts, Alert},
+        report::{ReportItem, ReportSection, ReportTextValue, ReportView},
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        http::{self, ManagementApiError},
+        jmap::jmap_request_using,
+        oauth::{use_authorization, AuthToken},
+        schema::{Builder, Schemas, Transformer, Type, Validator},
+    },
+    pages::FormatDateTime,
+};
+
+use super::filters::{
+    fetch_active_script, generate_script, parse_script, save_script, ActiveScript,
+};
+
+const USING_SUBMISSION: &[&str] = &[
+    "urn:ietf:params:jmap:core",
+    "urn:ietf:params:jmap:mail",
+    "urn:ietf:params:jmap:submission",
+];
+const CODE_VALIDITY_HOURS: i64 = 24;
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Forwarding {
+    pub address: String,
+    pub keep_copy: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending: Option<PendingConfirmation>,
+}
+
+/// Confirmation code sent to the destination address, which has to be
+/// entered before messages are forwarded.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingConfirmation {
+    pub code_hash: String,
+    pub expires: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug)]
+enum ForwardingChange {
+    Enable {
+        address: String,
+        keep_copy: bool,
+        confirm: bool,
+    },
+    Resend,
+    Confirm(String),
+    Disable,
+}
+
+impl Forwarding {
+    pub fn is_active(&self) -> bool {
+        self.pending.is_none()
+    }
+}
+
+/// Forwards incoming messages to another address through a Sieve redirect
+/// in the script managed by the filter rule builder.
+#[component]
+pub fn MailForwarding() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let data = expect_context::<Arc<Schemas>>()
+        .build_form("forwarding")
+        .into_signal();
+
+    let active = create_resource(
+        move || (),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move { fetch_active_script(&auth).await }
+        },
+    );
+
+    let change_action =
+        create_action(move |(script, change): &(ActiveScript, ForwardingChange)| {
+            let auth = auth.get();
+            let script = script.clone();
+            let change = change.clone();
+
+            async move {
+                match apply_change(&auth, &script, change).await {
+                    Ok(message) => {
+                        data.update(|data| data.mark_clean());
+                        alert.set(Alert::success(message));
+                        active.refetch();
+                    }
+                    Err(http::Error::Unauthorized) => {
+                        use_navigate()("/login", Default::default());
+                    }
+                    Err(err) => {
+                        alert.set(Alert::from(err));
+                    }
+                }
+            }
+        });
+
+    view! {
+        <Form title="Forwarding" subtitle="Forward incoming messages to another address">
+            <Transition fallback=Skeleton>
+                {move || match active.get() {
+                    None => None,
+                    Some(Err(http::Error::Unauthorized)) => {
+                        use_navigate()("/login", Default::default());
+                        Some(view! { <div></div> }.into_view())
+                    }
+                    Some(Err(err)) => {
+                        alert.set(Alert::from(err));
+                        Some(view! { <Skeleton/> }.into_view())
+                    }
+                    Some(Ok(script)) => {
+                        let managed = if script.script.trim().is_empty() {
+                            Some(Default::default())
+                        } else {
+                            parse_script(&script.script)
+                        };
+                        let script = store_value(script);
+                        let dispatch = move |change| {
+                            change_action.dispatch((script.get_value(), change));
+                        };
+                        Some(match managed.map(|managed| managed.forwarding) {
+                            None => {
+                                view! {
+                                    <ReportView>
+                                        <ReportSection title="Forwarding">
+                                            <ReportItem label="Status">
+                                                <ReportTextValue value="Your filters are maintained as a custom Sieve script, add a redirect command to it to forward messages."/>
+                                                <a
+                                                    class="text-sm text-blue-600 decoration-2 hover:underline font-medium dark:text-blue-500"
+                                                    href="/account/filters"
+                                                >
+                                                    Edit filters
+                                                </a>
+                                            </ReportItem>
+                                        </ReportSection>
+                                    </ReportView>
+                                }
+                                    .into_view()
+                            }
+                            Some(None) => {
+                                view! {
+                                    <FormSection>
+                                        <FormItem label="Forward to">
+                                            <InputText
+                                                element=FormElement::new("address", data)
+                                                placeholder="jane@example.org"
+                                            />
+                                        </FormItem>
+                                        <FormItem label="Keep a copy">
+                                            <InputSwitch
+                                                element=FormElement::new("keep-copy", data)
+                                                label="Also deliver forwarded messages to this account".to_string()
+                                            />
+                                        </FormItem>
+                                        <FormItem
+                                            label="Confirmation"
+                                            tooltip="Emails a code to the destination address, forwarding starts once the code is entered"
+                                        >
+                                            <InputSwitch
+                                                element=FormElement::new("confirm", data)
+                                                label="Confirm the destination address".to_string()
+                                            />
+                                        </FormItem>
+                                    </FormSection>
+
+                                    <FormButtonBar>
+                                        <Button
+                                            text="Enable forwarding"
+                                            color=Color::Blue
+                                            on_click=Callback::new(move |_| {
+                                                data.update(|data| {
+                                                    if data.validate_form() {
+                                                        dispatch(ForwardingChange::Enable {
+                                                            address: data.value::<String>("address").unwrap_or_default(),
+                                                            keep_copy: data.value::<bool>("keep-copy").unwrap_or_default(),
+                                                            confirm: data.value::<bool>("confirm").unwrap_or_default(),
+                                                        });
+                                                    }
+                                                });
+                                            })
+
+                                            disabled=change_action.pending()
+                                        />
+                                    </FormButtonBar>
+                                }
+                                    .into_view()
+                            }
+                            Some(Some(forwarding)) => {
+                                let status = match &forwarding.pending {
+                                    Some(pending) => {
+                                        format!(
+                                            "Waiting for confirmation, the code sent to {} expires on {}",
+                                            forwarding.address,
+                                            pending.expires.format_date_time(),
+                                        )
+                                    }
+                                    None => format!("Forwarding to {}", forwarding.address),
+                                };
+                                let is_pending = forwarding.pending.is_some();
+                                view! {
+                                    <FormSection>
+                                        <FormItem label="Status">
+                                            <p class="mt-2.5 text-sm text-gray-800 dark:text-gray-200">
+                                                {status}
+                                            </p>
+                                        </FormItem>
+                                        <FormItem label="Keep a copy">
+                                            <p class="mt-2.5 text-sm text-gray-800 dark:text-gray-200">
+                                                {if forwarding.keep_copy { "Yes" } else { "No" }}
+                                            </p>
+                                        </FormItem>
+                                        <FormItem label="Confirmation code" hide=!is_pending>
+                                            <InputText
+                                                element=FormElement::new("code", data)
+                                                placeholder="ABC123"
+                                            />
+                                        </FormItem>
+                                    </FormSection>
+
+                                    <FormButtonBar>
+                                        <Show when=move || is_pending>
+                                            <Button
+                                                text="Resend code"
+                                                color=Color::Gray
+                                                on_click=Callback::new(move |_| {
+                                                    dispatch(ForwardingChange::Resend);
+                                                })
+
+                                                disabled=change_action.pending()
+                                            />
+                                            <Button
+                                                text="Confirm"
+                                                color=Color::Blue
+                                                on_click=Callback::new(move |_| {
+                                                    let code = data
+                                                        .with(|data| data.value::<String>("code"))
+                                                        .unwrap_or_default()
+                                                        .trim()
+                                                        .to_uppercase();
+                                                    if code.is_empty() {
+                                                        data.update(|data| {
+                                                            data.new_error("code", "Enter the code sent to the destination address");
+                                                        });
+                                                    } else {
+                                                        dispatch(ForwardingChange::Confirm(code));
+                                                    }
+                                                })
+
+                                                disabled=change_action.pending()
+                                            />
+                                        </Show>
+                                        <Button
+                                            text=if is_pending { "Cancel" } else { "Disable forwarding" }
+                                            color=Color::Red
+                                            on_click=Callback::new(move |_| {
+                                                dispatch(ForwardingChange::Disable);
+                                            })
+
+                                            disabled=change_action.pending()
+                                        />
+                                    </FormButtonBar>
+                                }
+                                    .into_view()
+                            }
+                        })
+                    }
+                }}
+
+            </Transition>
+        </Form>
+    }
+}
+
+/// Applies a change to the forwarding settings and saves the resulting
+/// script, returning the message to display on success.
+async fn apply_change(
+    auth: &AuthToken,
+    script: &ActiveScript,
+    change: ForwardingChange,
+) -> http::Result<&'static str> {
+    let mut managed = parse_script(&script.script).unwrap_or_default();
+    let message = match change {
+        ForwardingChange::Enable {
+            address,
+            keep_copy,
+            confirm,
+        } => {
+            let pending = if confirm {
+                Some(send_confirmation(auth, &script.account_id, &address).await?)
+            } else {
+                None
+            };
+            managed.forwarding = Some(Forwarding {
+                address,
+                keep_copy,
+                pending,
+            });
+            if confirm {
+                "A confirmation code has been sent to the destination address"
+            } else {
+                "Forwarding enabled"
+            }
+        }
+        ForwardingChange::Resend => {
+            let Some(forwarding) = managed.forwarding.as_mut() else {
+                return Err(http::Error::NotFound);
+            };
+            forwarding.pending =
+                Some(send_confirmation(auth, &script.account_id, &forwarding.address).await?);
+            "A new confirmation code has been sent to the destination address"
+        }
+        ForwardingChange::Confirm(code) => {
+            let Some(forwarding) = managed.forwarding.as_mut() else {
+                return Err(http::Error::NotFound);
+            };
+            match &forwarding.pending {
+                Some(pending)
+                    if pending.expires > Utc::now()
+                        && sha512_crypt::verify(&code, &pending.code_hash) =>
+                {
+                    forwarding.pending = None;
+                }
+                _ => {
+                    return Err(http::Error::Server(ManagementApiError::Other {
+                        details: "The confirmation code is invalid or has expired".to_string(),
+                    }));
+                }
+            }
+            "Forwarding enabled"
+        }
+        ForwardingChange::Disable => {
+            managed.forwarding = None;
+            "Forwarding disabled"
+        }
+    };
+
+    save_script(
+        auth,
+        &script.account_id,
+        script.script_id.as_deref(),
+        generate_script(&managed),
+    )
+    .await?;
+
+    Ok(message)
+}
+
+/// Emails a new confirmation code to the destination address from the
+/// first identity of the account.
+async fn send_confirmation(
+    auth: &AuthToken,
+    account_id: &str,
+    address: &str,
+) -> http::Result<PendingConfirmation> {
+    let mut rng = rand::thread_rng();
+    let code = (0..6)
+        .map(|_| CODE_ALPHABET[rng.gen_range(0..CODE_ALPHABET.len())] as char)
+        .collect::<String>();
+
+    let response = jmap_request_using(
+        auth,
+        USING_SUBMISSION,
+        vec![
+            (
+                "Identity/get",
+                json!({"accountId": account_id, "ids": null}),
+            ),
+            (
+                "Mailbox/query",
+                json!({"accountId": account_id, "filter": {"role": "drafts"}}),
+            ),
+        ],
+    )
+    .await?;
+    let identity = response
+        .first()
+        .map(|response| &response["list"][0])
+        .filter(|identity| identity["id"].is_string())
+        .ok_or_else(|| {
+            other_error("No identity is available to send the confirmation code from")
+        })?;
+    let mailbox_id = response
+        .get(1)
+        .and_then(|response| response["ids"][0].as_str())
+        .ok_or_else(|| {
+            other_error("No drafts folder is available to send the confirmation code from")
+        })?;
+    let from = identity["email"].as_str().unwrap_or_default();
+
+    let response = jmap_request_using(
+        auth,
+        USING_SUBMISSION,
+        vec![
+            (
+                "Email/set",
+                json!({
+                    "accountId": account_id,
+                    "create": {
+                        "message": {
+                            "mailboxIds": {mailbox_id: true},
+                            "keywords": {"$draft": true, "$seen": true},
+                            "from": [{"name": identity["name"], "email": from}],
+                            "to": [{"email": address}],
+                            "subject": "Confirm mail forwarding",
+                            "bodyValues": {"body": {"value": format!(
+                                "{from} has requested to forward incoming messages to this address.\n\n\
+                                 To confirm, enter the following code:\n\n    {code}\n\n\
+                                 The code expires in {CODE_VALIDITY_HOURS} hours. If you did not \
+                                 expect this message you can ignore it, no messages will be forwarded.\n"
+                            )}},
+                            "textBody": [{"partId": "body", "type": "text/plain"}]
+                        }
+                    }
+                }),
+            ),
+            (
+                "EmailSubmission/set",
+                json!({
+                    "accountId": account_id,
+                    "create": {
+                        "submission": {"identityId": identity["id"], "emailId": "#message"}
+                    },
+                    "onSuccessDestroyEmail": ["#submission"]
+                }),
+            ),
+        ],
+    )
+    .await?;
+    for (response, id) in response.iter().zip(["message", "submission"]) {
+        let error = &response["notCreated"][id];
+        if !error.is_null() {
+            return Err(other_error(
+                error["description"]
+                    .as_str()
+                    .unwrap_or("Failed to send the confirmation code"),
+            ));
+        }
+    }
+
+    Ok(PendingConfirmation {
+        code_hash: sha512_crypt::hash(&code).map_err(|err| other_error(&err.to_string()))?,
+        expires: Utc::now() + Duration::hours(CODE_VALIDITY_HOURS),
+    })
+}
+
+fn other_error(details: &str) -> http::Error {
+    http::Error::Server(ManagementApiError::Other {
+        details: details.to_string(),
+    })
+}
+
+impl Builder<Schemas, ()> {
+    pub fn build_forwarding(self) -> Self {
+        self.new_schema("forwarding")
+            .new_field("address")
+            .input_check(
+                [Transformer::Trim, Transformer::Lowercase],
+                [Validator::Required, Validator::IsEmail],
+            )
+            .build()
+            .new_field("keep-copy")
+            .typ(Type::Boolean)
+            .default("true")
+            .build()
+            .new_field("confirm")
+            .typ(Type::Boolean)
+            .default("true")
+            .build()
+            .new_field("code")
+            .input_check([Transformer::Trim], [])
+            .build()
+            .build()
+    }
+}
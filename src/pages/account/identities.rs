@@ -0,0 +1,547 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{
+            button::Button,
+            input::{InputText, TextArea},
+            Form, FormButtonBar, FormElement, FormItem, FormSection,
+        },
+        messages::alert::{use_alerts, Alert},
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        form::FormData,
+        http::{self, HttpRequest, ManagementApiError},
+        jmap::{jmap_primary_account, jmap_request_using},
+        oauth::{use_authorization, AuthToken},
+        schema::{Builder, Schemas, Transformer, Type, Validator},
+    },
+    pages::directory::Principal,
+};
+
+const USING_SUBMISSION: &[&str] = &[
+    "urn:ietf:params:jmap:core",
+    "urn:ietf:params:jmap:mail",
+    "urn:ietf:params:jmap:submission",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Identity {
+    id: String,
+    name: String,
+    email: String,
+    reply_to: Option<String>,
+    text_signature: String,
+    html_signature: String,
+    may_delete: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct AccountIdentities {
+    account_id: String,
+    aliases: Vec<String>,
+    identities: Vec<Identity>,
+}
+
+/// Lists the addresses of the account and manages the JMAP identities used
+/// when submitting messages. Identities can only be created for addresses
+/// assigned to the account by the administrator.
+#[component]
+pub fn ManageIdentities() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let data = expect_context::<Arc<Schemas>>()
+        .build_form("identity")
+        .into_signal();
+    // None when not editing, Some(None) when creating a new identity
+    let editing = create_rw_signal(None::<Option<String>>);
+
+    let identities = create_resource(
+        move || (),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move { fetch_identities(&auth).await }
+        },
+    );
+
+    let save_action = create_action(
+        move |(account_id, id, identity): &(String, Option<String>, Value)| {
+            let auth = auth.get();
+            let account_id = account_id.clone();
+            let id = id.clone();
+            let identity = identity.clone();
+
+            async move {
+                match set_identity(&auth, &account_id, id.as_deref(), Some(identity)).await {
+                    Ok(_) => {
+                        editing.set(None);
+                        data.update(|data| data.mark_clean());
+                        alert.set(Alert::success("Identity saved"));
+                        identities.refetch();
+                    }
+                    Err(http::Error::Unauthorized) => {
+                        use_navigate()("/login", Default::default());
+                    }
+                    Err(err) => {
+                        alert.set(Alert::from(err));
+                    }
+                }
+            }
+        },
+    );
+
+    let delete_action = create_action(move |(account_id, id): &(String, String)| {
+        let auth = auth.get();
+        let account_id = account_id.clone();
+        let id = id.clone();
+
+        async move {
+            match set_identity(&auth, &account_id, Some(&id), None).await {
+                Ok(_) => {
+                    alert.set(Alert::success("Identity deleted"));
+                    identities.refetch();
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    view! {
+        <Form
+            title="Identities"
+            subtitle="Manage the names, addresses and signatures you send messages with"
+        >
+            <Transition fallback=Skeleton>
+                {move || match identities.get() {
+                    None => None,
+                    Some(Err(http::Error::Unauthorized)) => {
+                        use_navigate()("/login", Default::default());
+                        Some(view! { <div></div> }.into_view())
+                    }
+                    Some(Err(err)) => {
+                        alert.set(Alert::from(err));
+                        Some(view! { <Skeleton/> }.into_view())
+                    }
+                    Some(Ok(account)) => {
+                        let has_aliases = !account.aliases.is_empty();
+                        let default_email = account
+                            .aliases
+                            .iter()
+                            .find(|alias| !alias.starts_with('@'))
+                            .cloned()
+                            .unwrap_or_default();
+                        let account_id = store_value(account.account_id);
+                        let aliases = store_value(account.aliases);
+                        let items = account.identities;
+                        let has_identities = !items.is_empty();
+                        Some(
+                            view! {
+                                <FormSection title="Addresses".to_string()>
+                                    <FormItem
+                                        label="Email addresses"
+                                        tooltip="Addresses assigned to your account by the administrator"
+                                    >
+                                        {if has_aliases {
+                                            view! {
+                                                <div class="mt-2.5 flex flex-wrap gap-2">
+                                                    {aliases
+                                                        .get_value()
+                                                        .into_iter()
+                                                        .map(|alias| view! { <Badge color=Color::Gray>{alias}</Badge> })
+                                                        .collect_view()}
+                                                </div>
+                                            }
+                                                .into_view()
+                                        } else {
+                                            view! {
+                                                <p class="mt-2.5 text-sm text-gray-500">
+                                                    No addresses are assigned to your account.
+                                                </p>
+                                            }
+                                                .into_view()
+                                        }}
+
+                                    </FormItem>
+                                </FormSection>
+
+                                <FormSection title="Identities".to_string()>
+                                    <div class="sm:col-span-12 divide-y divide-gray-200 dark:divide-gray-700">
+                                        {items
+                                            .into_iter()
+                                            .map(|identity| {
+                                                let edit_identity = identity.clone();
+                                                let id = identity.id.clone();
+                                                view! {
+                                                    <div class="py-3 flex justify-between items-start gap-x-3">
+                                                        <div>
+                                                            <p class="text-sm font-semibold text-gray-800 dark:text-gray-200">
+                                                                {if identity.name.is_empty() {
+                                                                    identity.email.clone()
+                                                                } else {
+                                                                    format!("{} <{}>", identity.name, identity.email)
+                                                                }}
+
+                                                            </p>
+                                                            {identity
+                                                                .reply_to
+                                                                .map(|reply_to| {
+                                                                    view! {
+                                                                        <p class="text-xs text-gray-500">
+                                                                            {format!("Replies to {reply_to}")}
+                                                                        </p>
+                                                                    }
+                                                                })}
+
+                                                            <p class="text-xs text-gray-500 whitespace-pre-wrap">
+                                                                {identity.text_signature}
+                                                            </p>
+                                                        </div>
+                                                        <div class="flex gap-x-2">
+                                                            <button
+                                                                type="button"
+                                                                class="text-sm text-blue-600 decoration-2 hover:underline font-medium dark:text-blue-500"
+                                                                on:click=move |_| {
+                                                                    data.update(|data| {
+                                                                        load_form(data, Some(&edit_identity), "");
+                                                                        data.mark_clean();
+                                                                    });
+                                                                    editing.set(Some(Some(edit_identity.id.clone())));
+                                                                }
+                                                            >
+
+                                                                Edit
+                                                            </button>
+                                                            <button
+                                                                type="button"
+                                                                class="text-sm text-red-600 decoration-2 hover:underline font-medium disabled:opacity-50 disabled:pointer-events-none"
+                                                                class:hidden=!identity.may_delete
+                                                                disabled=delete_action.pending()
+                                                                on:click=move |_| {
+                                                                    delete_action
+                                                                        .dispatch((account_id.get_value(), id.clone()));
+                                                                }
+                                                            >
+
+                                                                Delete
+                                                            </button>
+                                                        </div>
+                                                    </div>
+                                                }
+                                            })
+                                            .collect_view()}
+                                    </div>
+                                    <p
+                                        class="sm:col-span-12 text-sm text-gray-500"
+                                        class:hidden=has_identities
+                                    >
+                                        No identities have been created.
+                                    </p>
+                                    <div class="sm:col-span-12">
+                                        <button
+                                            type="button"
+                                            class="text-sm text-blue-600 decoration-2 hover:underline font-medium dark:text-blue-500 disabled:opacity-50 disabled:pointer-events-none"
+                                            disabled=!has_aliases
+                                            on:click=move |_| {
+                                                data.update(|data| {
+                                                    load_form(data, None, &default_email);
+                                                    data.mark_clean();
+                                                });
+                                                editing.set(Some(None));
+                                            }
+                                        >
+
+                                            Add identity
+                                        </button>
+                                    </div>
+                                </FormSection>
+
+                                <FormSection
+                                    title="Identity".to_string()
+                                    hide=Signal::derive(move || editing.get().is_none())
+                                >
+                                    <FormItem label="Name">
+                                        <InputText
+                                            element=FormElement::new("name", data)
+                                            placeholder="Jane Doe"
+                                        />
+                                    </FormItem>
+                                    <FormItem
+                                        label="Email"
+                                        tooltip="Address messages are sent from, which cannot be changed once the identity is created"
+                                    >
+                                        <InputText
+                                            element=FormElement::new("email", data)
+                                            disabled=Signal::derive(move || {
+                                                matches!(editing.get(), Some(Some(_)))
+                                            })
+                                        />
+                                    </FormItem>
+                                    <FormItem label="Reply-to" is_optional=true>
+                                        <InputText element=FormElement::new("reply-to", data)/>
+                                    </FormItem>
+                                    <FormItem label="Signature" is_optional=true>
+                                        <TextArea element=FormElement::new("text-signature", data)/>
+                                    </FormItem>
+                                    <FormItem
+                                        label="HTML signature"
+                                        tooltip="Signature used by clients that compose messages in HTML"
+                                        is_optional=true
+                                    >
+                                        <TextArea element=FormElement::new("html-signature", data)/>
+                                    </FormItem>
+                                </FormSection>
+
+                                <FormButtonBar>
+                                    <Show when=move || editing.get().is_some()>
+                                        <Button
+                                            text="Cancel"
+                                            color=Color::Gray
+                                            on_click=Callback::new(move |_| {
+                                                editing.set(None);
+                                                data.update(|data| data.mark_clean());
+                                            })
+                                        />
+                                        <Button
+                                            text="Save identity"
+                                            color=Color::Blue
+                                            on_click=Callback::new(move |_| {
+                                                let id = editing.get().flatten();
+                                                data.update(|data| {
+                                                    if let Some(identity) = aliases
+                                                        .with_value(|aliases| read_form(data, aliases, id.is_none()))
+                                                    {
+                                                        save_action
+                                                            .dispatch((account_id.get_value(), id.clone(), identity));
+                                                    }
+                                                });
+                                            })
+
+                                            disabled=save_action.pending()
+                                        />
+                                    </Show>
+                                </FormButtonBar>
+                            }
+                                .into_view(),
+                        )
+                    }
+                }}
+
+            </Transition>
+        </Form>
+    }
+}
+
+/// Fetches the identities of the primary account along with the addresses
+/// the account is allowed to send from.
+async fn fetch_identities(auth: &AuthToken) -> http::Result<AccountIdentities> {
+    let account_id = jmap_primary_account(auth).await?;
+    let mut response = jmap_request_using(
+        auth,
+        USING_SUBMISSION,
+        vec![(
+            "Identity/get",
+            json!({"accountId": account_id, "ids": null}),
+        )],
+    )
+    .await?;
+    let mut identities = match response.pop().map(|mut response| response["list"].take()) {
+        Some(Value::Array(list)) => list.iter().filter_map(parse_identity).collect(),
+        _ => Vec::new(),
+    };
+    identities
+        .sort_by(|a: &Identity, b: &Identity| a.email.cmp(&b.email).then(a.name.cmp(&b.name)));
+
+    // Accounts that cannot read their own principal fall back to the
+    // addresses of their existing identities
+    let aliases = match HttpRequest::get(("/api/principal", auth.username.as_str()))
+        .with_authorization(auth)
+        .send::<Principal>()
+        .await
+    {
+        Ok(principal) => principal.emails,
+        Err(http::Error::Unauthorized) => return Err(http::Error::Unauthorized),
+        Err(_) => {
+            let mut aliases = identities
+                .iter()
+                .map(|identity| identity.email.clone())
+                .collect::<Vec<_>>();
+            aliases.dedup();
+            aliases
+        }
+    };
+
+    Ok(AccountIdentities {
+        account_id,
+        aliases,
+        identities,
+    })
+}
+
+/// Creates an identity when no id is provided, updates it when both an id
+/// and its properties are provided, and destroys it otherwise.
+async fn set_identity(
+    auth: &AuthToken,
+    account_id: &str,
+    id: Option<&str>,
+    identity: Option<Value>,
+) -> http::Result<()> {
+    let (arguments, not_saved, id) = match (id, identity) {
+        (Some(id), Some(identity)) => (
+            json!({"accountId": account_id, "update": {id: identity}}),
+            "notUpdated",
+            id,
+        ),
+        (Some(id), None) => (
+            json!({"accountId": account_id, "destroy": [id]}),
+            "notDestroyed",
+            id,
+        ),
+        (None, identity) => (
+            json!({"accountId": account_id, "create": {"identity": identity}}),
+            "notCreated",
+            "identity",
+        ),
+    };
+    let response =
+        jmap_request_using(auth, USING_SUBMISSION, vec![("Identity/set", arguments)]).await?;
+
+    match response.first().map(|response| &response[not_saved][id]) {
+        Some(error) if !error.is_null() => Err(http::Error::Server(ManagementApiError::Other {
+            details: error["description"]
+                .as_str()
+                .or_else(|| error["type"].as_str())
+                .unwrap_or("Failed to update the identity")
+                .to_string(),
+        })),
+        _ => Ok(()),
+    }
+}
+
+fn parse_identity(identity: &Value) -> Option<Identity> {
+    Some(Identity {
+        id: identity["id"].as_str()?.to_string(),
+        name: identity["name"].as_str().unwrap_or_default().to_string(),
+        email: identity["email"].as_str()?.to_string(),
+        reply_to: identity["replyTo"][0]["email"]
+            .as_str()
+            .map(|email| email.to_string()),
+        text_signature: identity["textSignature"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        html_signature: identity["htmlSignature"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        may_delete: identity["mayDelete"].as_bool().unwrap_or(true),
+    })
+}
+
+fn load_form(data: &mut FormData, identity: Option<&Identity>, default_email: &str) {
+    match identity {
+        Some(identity) => {
+            data.update("name", identity.name.clone());
+            data.update("email", identity.email.clone());
+            for (id, value) in [
+                ("reply-to", identity.reply_to.as_deref().unwrap_or_default()),
+                ("text-signature", identity.text_signature.as_str()),
+                ("html-signature", identity.html_signature.as_str()),
+            ] {
+                if value.is_empty() {
+                    data.remove(id);
+                } else {
+                    data.update(id, value.to_string());
+                }
+            }
+        }
+        None => {
+            data.update("email", default_email.to_string());
+            for id in ["name", "reply-to", "text-signature", "html-signature"] {
+                data.remove(id);
+            }
+        }
+    }
+}
+
+/// Returns the JMAP properties of the identity being edited, making sure
+/// new identities use one of the addresses of the account. Addresses
+/// starting with `@` allow sending from any address of that domain.
+fn read_form(data: &mut FormData, aliases: &[String], is_new: bool) -> Option<Value> {
+    if !data.validate_form() {
+        return None;
+    }
+
+    let reply_to = data
+        .value::<String>("reply-to")
+        .filter(|email| !email.is_empty())
+        .map(|email| json!([{"name": null, "email": email}]))
+        .unwrap_or(Value::Null);
+    let mut identity = json!({
+        "name": data.value::<String>("name").unwrap_or_default(),
+        "replyTo": reply_to,
+        "textSignature": data.value::<String>("text-signature").unwrap_or_default(),
+        "htmlSignature": data.value::<String>("html-signature").unwrap_or_default(),
+    });
+
+    if is_new {
+        let email = data.value::<String>("email").unwrap_or_default();
+        if !aliases.iter().any(|alias| {
+            alias.eq_ignore_ascii_case(&email)
+                || (alias.starts_with('@') && email.to_lowercase().ends_with(&alias.to_lowercase()))
+        }) {
+            data.new_error("email", "This address is not assigned to your account");
+            return None;
+        }
+        identity["email"] = Value::String(email);
+    }
+
+    Some(identity)
+}
+
+impl Builder<Schemas, ()> {
+    pub fn build_identity(self) -> Self {
+        self.new_schema("identity")
+            .new_field("name")
+            .input_check([Transformer::Trim], [Validator::MaxLength(255)])
+            .build()
+            .new_field("email")
+            .input_check(
+                [Transformer::Trim, Transformer::Lowercase],
+                [Validator::Required, Validator::IsEmail],
+            )
+            .build()
+            .new_field("reply-to")
+            .input_check(
+                [Transformer::Trim, Transformer::Lowercase],
+                [Validator::IsEmail],
+            )
+            .build()
+            .new_field("text-signature")
+            .typ(Type::Text)
+            .build()
+            .new_field("html-signature")
+            .typ(Type::Text)
+            .build()
+            .build()
+    }
+}
@@ -4,12 +4,22 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
+use ahash::AHashMap;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 pub mod app_password;
 pub mod crypto;
+pub mod debug;
+pub mod filters;
+pub mod forwarding;
+pub mod identities;
 pub mod mfa;
+pub mod notifications;
+pub mod overview;
 pub mod password;
+pub mod session;
+pub mod vacation;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(tag = "type")]
@@ -30,4 +40,17 @@ pub struct AccountAuthResponse {
     pub is_admin: bool,
     #[serde(rename = "appPasswords")]
     pub app_passwords: Vec<String>,
+    #[serde(rename = "appPasswordsUsage")]
+    #[serde(default)]
+    pub app_passwords_usage: Option<AHashMap<String, AppPasswordUsage>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct AppPasswordUsage {
+    #[serde(rename = "lastUsed")]
+    #[serde(default)]
+    pub last_used: Option<DateTime<Utc>>,
+    #[serde(rename = "lastIp")]
+    #[serde(default)]
+    pub last_ip: Option<String>,
 }
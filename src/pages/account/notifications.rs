@@ -0,0 +1,182 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use leptos::*;
+
+use crate::{
+    components::{
+        form::{button::Button, input::InputSwitch, Form, FormElement, FormItem, FormSection},
+        messages::alert::{use_alerts, Alert},
+        Color,
+    },
+    core::{
+        notifications::{
+            is_permitted, is_supported, request_permission, show_notification, use_notifications,
+        },
+        oauth::use_authorization,
+        schema::{Builder, Schemas, Type},
+    },
+};
+
+const FIELDS: [&str; 4] = ["enabled", "delivery", "server", "certificates"];
+
+#[component]
+pub fn DesktopNotifications() -> impl IntoView {
+    let settings = use_notifications();
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let permitted = create_rw_signal(is_permitted());
+    let data = expect_context::<Arc<Schemas>>()
+        .build_form("notifications")
+        .into_signal();
+    data.update(|data| {
+        let settings = settings.get_untracked();
+        for (id, value) in FIELDS.iter().zip([
+            settings.enabled,
+            settings.delivery_failures,
+            settings.server_status,
+            settings.certificates,
+        ]) {
+            data.update(id, if value { "true" } else { "false" });
+        }
+    });
+
+    let permission_action = create_action(move |_: &()| async move {
+        if request_permission().await {
+            permitted.set(true);
+        } else {
+            alert.set(Alert::error(
+                "Notifications are blocked, allow them in the browser's site settings",
+            ));
+            data.update(|data| data.update("enabled", "false"));
+        }
+    });
+
+    create_effect(move |_| {
+        let [enabled, delivery_failures, server_status, certificates] =
+            FIELDS.map(|id| data.with(|data| data.value::<bool>(id).unwrap_or_default()));
+        if enabled && !permitted.get_untracked() && !permission_action.pending().get_untracked() {
+            permission_action.dispatch(());
+        }
+        let updated = settings.with_untracked(|settings| {
+            let mut updated = settings.clone();
+            updated.enabled = enabled;
+            updated.delivery_failures = delivery_failures;
+            updated.server_status = server_status;
+            updated.certificates = certificates;
+            (updated != *settings).then_some(updated)
+        });
+        if let Some(updated) = updated {
+            updated.save();
+            settings.set(updated);
+        }
+    });
+
+    let is_disabled = Signal::derive(move || {
+        data.with(|data| !data.value::<bool>("enabled").unwrap_or_default())
+    });
+
+    view! {
+        <Form
+            title="Notifications"
+            subtitle="Receive desktop notifications about critical events while the web admin is in a background tab"
+        >
+            <FormSection>
+                <FormItem
+                    label="Desktop notifications"
+                    tooltip="Notifications are shown by this browser only, and only for administrator accounts"
+                >
+                    <InputSwitch
+                        element=FormElement::new("enabled", data)
+                        label="Show desktop notifications".to_string()
+                        disabled=Signal::derive(move || {
+                            !is_supported() || !auth.with(|auth| auth.is_admin())
+                        })
+                    />
+                    <p class="mt-2 text-sm text-gray-500">
+                        {move || {
+                            if !is_supported() {
+                                "This browser does not support desktop notifications."
+                            } else if !auth.with(|auth| auth.is_admin()) {
+                                "Desktop notifications are only available to administrators."
+                            } else if permitted.get() {
+                                "Notifications are allowed by the browser."
+                            } else {
+                                "The browser will ask for permission when notifications are enabled."
+                            }
+                        }}
+
+                    </p>
+                </FormItem>
+                <FormItem
+                    label="Delivery failures"
+                    tooltip="Checked every minute against the number of failures in the previous hour"
+                >
+                    <InputSwitch
+                        element=FormElement::new("delivery", data)
+                        label="Notify when delivery failures spike".to_string()
+                        disabled=is_disabled
+                    />
+                </FormItem>
+                <FormItem label="Server status">
+                    <InputSwitch
+                        element=FormElement::new("server", data)
+                        label="Notify when the server becomes unreachable or restarts".to_string()
+                        disabled=is_disabled
+                    />
+                </FormItem>
+                <FormItem label="Certificates" tooltip="Checked once every hour">
+                    <InputSwitch
+                        element=FormElement::new("certificates", data)
+                        label="Notify about invalid certificates or certificates expiring within a week"
+                            .to_string()
+                        disabled=is_disabled
+                    />
+                </FormItem>
+                <FormItem label="Test">
+                    <Button
+                        text="Send test notification"
+                        color=Color::Gray
+                        disabled=Signal::derive(move || is_disabled.get() || !permitted.get())
+                        on_click=move |_| {
+                            show_notification(
+                                "test",
+                                "Stalwart Mail Server",
+                                "Desktop notifications are working.",
+                            );
+                        }
+                    />
+
+                </FormItem>
+            </FormSection>
+        </Form>
+    }
+}
+
+impl Builder<Schemas, ()> {
+    pub fn build_notifications(self) -> Self {
+        self.new_schema("notifications")
+            .new_field("enabled")
+            .typ(Type::Boolean)
+            .default("false")
+            .build()
+            .new_field("delivery")
+            .typ(Type::Boolean)
+            .default("true")
+            .build()
+            .new_field("server")
+            .typ(Type::Boolean)
+            .default("true")
+            .build()
+            .new_field("certificates")
+            .typ(Type::Boolean)
+            .default("true")
+            .build()
+            .build()
+    }
+}
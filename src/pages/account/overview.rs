@@ -0,0 +1,497 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use humansize::{format_size, DECIMAL};
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::button::Button,
+        messages::alert::{use_alerts, Alert},
+        report::{ReportItem, ReportSection, ReportTextValue, ReportView},
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        jmap::{jmap_primary_account, jmap_request_using},
+        oauth::{use_authorization, AuthToken},
+    },
+    pages::{
+        account::{
+            vacation::{fetch_vacation, set_vacation, VacationResponse},
+            AccountAuthResponse,
+        },
+        directory::Principal,
+        FormatDateTime,
+    },
+};
+
+const USING_QUOTA: &[&str] = &["urn:ietf:params:jmap:core", "urn:ietf:params:jmap:quota"];
+const USING_IDENTITY: &[&str] = &[
+    "urn:ietf:params:jmap:core",
+    "urn:ietf:params:jmap:mail",
+    "urn:ietf:params:jmap:submission",
+];
+const USING_SIEVE: &[&str] = &["urn:ietf:params:jmap:core", "urn:ietf:params:jmap:sieve"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AccountOverviewData {
+    account_id: String,
+    quota: Option<QuotaUsage>,
+    addresses: Vec<String>,
+    member_of: Option<Vec<String>>,
+    auth: Option<AccountAuthResponse>,
+    vacation: Option<VacationResponse>,
+    scripts: Option<Vec<SieveScript>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct QuotaUsage {
+    used: u64,
+    limit: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct SieveScript {
+    name: String,
+    active: bool,
+}
+
+/// Summarizes the settings of the signed in account. Data is fetched from
+/// the management API when the account is allowed to read its own
+/// principal, and over JMAP otherwise.
+#[component]
+pub fn AccountOverview() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+
+    let overview = create_resource(
+        move || (),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move { fetch_overview(&auth).await }
+        },
+    );
+
+    let vacation_action = create_action(move |(account_id, enabled): &(String, bool)| {
+        let auth = auth.get();
+        let account_id = account_id.clone();
+        let enabled = *enabled;
+
+        async move {
+            match set_vacation(&auth, &account_id, json!({"isEnabled": enabled})).await {
+                Ok(_) => {
+                    overview.refetch();
+                    alert.set(Alert::success(if enabled {
+                        "Vacation responder enabled"
+                    } else {
+                        "Vacation responder disabled"
+                    }));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    view! {
+        <Transition fallback=Skeleton>
+            {move || match overview.get() {
+                None => None,
+                Some(Err(http::Error::Unauthorized)) => {
+                    use_navigate()("/login", Default::default());
+                    Some(view! { <div></div> }.into_view())
+                }
+                Some(Err(err)) => {
+                    alert.set(Alert::from(err));
+                    Some(view! { <Skeleton/> }.into_view())
+                }
+                Some(Ok(overview)) => {
+                    let account_id = overview.account_id.clone();
+                    Some(
+                        view! {
+                            <ReportView>
+                                <ReportSection title="Storage">
+                                    <ReportItem label="Usage">
+                                        <QuotaBar quota=overview.quota/>
+                                    </ReportItem>
+                                </ReportSection>
+
+                                <ReportSection title="Addresses">
+                                    <ReportItem label="E-mail addresses">
+                                        <ValueList
+                                            values=overview.addresses
+                                            empty="No addresses are assigned to your account."
+                                        />
+                                    </ReportItem>
+                                    <ReportItem label="Member of">
+                                        {match overview.member_of {
+                                            Some(member_of) => {
+                                                view! {
+                                                    <ValueList
+                                                        values=member_of
+                                                        empty="Your account is not a member of any group or mailing list."
+                                                    />
+                                                }
+                                                    .into_view()
+                                            }
+                                            None => {
+                                                view! {
+                                                    <ReportTextValue value="Not available to your account"/>
+                                                }
+                                                    .into_view()
+                                            }
+                                        }}
+
+                                    </ReportItem>
+                                </ReportSection>
+
+                                <ReportSection title="Devices and sign-in">
+                                    {overview
+                                        .auth
+                                        .map(|auth| {
+                                            view! {
+                                                <ReportItem label="Two-factor authentication">
+                                                    <div class="mt-2">
+                                                        {if auth.otp_auth {
+                                                            view! { <Badge color=Color::Green>Enabled</Badge> }
+                                                        } else {
+                                                            view! { <Badge color=Color::Gray>Disabled</Badge> }
+                                                        }}
+                                                        <a
+                                                            class="ms-3 text-sm text-blue-600 decoration-2 hover:underline font-medium dark:text-blue-500"
+                                                            href="/account/mfa"
+                                                        >
+                                                            Manage
+                                                        </a>
+                                                    </div>
+                                                </ReportItem>
+                                                <ReportItem label="App passwords">
+                                                    <ValueList
+                                                        values=auth.app_passwords
+                                                        empty="No app passwords have been created."
+                                                    />
+                                                    <a
+                                                        class="text-sm text-blue-600 decoration-2 hover:underline font-medium dark:text-blue-500"
+                                                        href="/account/app-passwords"
+                                                    >
+                                                        Manage app passwords
+                                                    </a>
+                                                </ReportItem>
+                                            }
+                                        })}
+
+                                </ReportSection>
+
+                                <ReportSection title="Vacation responder">
+                                    {match overview.vacation {
+                                        Some(vacation) => {
+                                            let enabled = vacation.is_enabled;
+                                            let period = match (vacation.from_date, vacation.to_date) {
+                                                (Some(from), Some(to)) => {
+                                                    format!(
+                                                        "From {} to {}",
+                                                        from.format_date(),
+                                                        to.format_date(),
+                                                    )
+                                                }
+                                                (Some(from), None) => {
+                                                    format!("From {}", from.format_date())
+                                                }
+                                                (None, Some(to)) => {
+                                                    format!("Until {}", to.format_date())
+                                                }
+                                                (None, None) => "Always".to_string(),
+                                            };
+                                            view! {
+                                                <ReportItem label="Status">
+                                                    <div class="mt-2 flex items-center gap-x-3">
+                                                        {if enabled {
+                                                            view! { <Badge color=Color::Green>Enabled</Badge> }
+                                                        } else {
+                                                            view! { <Badge color=Color::Gray>Disabled</Badge> }
+                                                        }}
+                                                        <Button
+                                                            text=if enabled { "Disable" } else { "Enable" }
+                                                            color=Color::Gray
+                                                            on_click=move |_| {
+                                                                vacation_action
+                                                                    .dispatch((account_id.clone(), !enabled));
+                                                            }
+
+                                                            disabled=vacation_action.pending()
+                                                        />
+                                                    </div>
+                                                </ReportItem>
+                                                <ReportItem label="Period">
+                                                    <ReportTextValue value=period/>
+                                                </ReportItem>
+                                                <ReportItem label="Subject">
+                                                    <ReportTextValue value=vacation
+                                                        .subject
+                                                        .unwrap_or_else(|| "N/A".to_string())/>
+                                                </ReportItem>
+                                                <ReportItem label="Message">
+                                                    <p class="mt-2.5 text-sm text-gray-500 whitespace-pre-wrap">
+                                                        {vacation.text_body.unwrap_or_default()}
+                                                    </p>
+                                                    <a
+                                                        class="text-sm text-blue-600 decoration-2 hover:underline font-medium dark:text-blue-500"
+                                                        href="/account/vacation"
+                                                    >
+                                                        Edit vacation responder
+                                                    </a>
+                                                </ReportItem>
+                                            }
+                                                .into_view()
+                                        }
+                                        None => {
+                                            view! {
+                                                <ReportItem label="Status">
+                                                    <ReportTextValue value="Not available to your account"/>
+                                                </ReportItem>
+                                            }
+                                                .into_view()
+                                        }
+                                    }}
+
+                                </ReportSection>
+
+                                <ReportSection title="Filters">
+                                    <ReportItem label="Sieve scripts">
+                                        {match overview.scripts {
+                                            Some(scripts) if !scripts.is_empty() => {
+                                                scripts
+                                                    .into_iter()
+                                                    .map(|script| {
+                                                        view! {
+                                                            <div class="mt-2 flex items-center gap-x-3">
+                                                                <span class="text-sm font-semibold text-gray-500">
+                                                                    {script.name}
+                                                                </span>
+                                                                {script
+                                                                    .active
+                                                                    .then(|| {
+                                                                        view! { <Badge color=Color::Green>Active</Badge> }
+                                                                    })}
+                                                            </div>
+                                                        }
+                                                    })
+                                                    .collect_view()
+                                            }
+                                            Some(_) => {
+                                                view! { <ReportTextValue value="No filters have been created."/> }
+                                                    .into_view()
+                                            }
+                                            None => {
+                                                view! {
+                                                    <ReportTextValue value="Not available to your account"/>
+                                                }
+                                                    .into_view()
+                                            }
+                                        }}
+
+                                        <a
+                                            class="text-sm text-blue-600 decoration-2 hover:underline font-medium dark:text-blue-500"
+                                            href="/account/filters"
+                                        >
+                                            Edit filters
+                                        </a>
+                                    </ReportItem>
+                                </ReportSection>
+                            </ReportView>
+                        }
+                            .into_view(),
+                    )
+                }
+            }}
+
+        </Transition>
+    }
+}
+
+#[component]
+fn QuotaBar(quota: Option<QuotaUsage>) -> impl IntoView {
+    match quota {
+        Some(QuotaUsage {
+            used,
+            limit: Some(limit),
+        }) if limit > 0 => {
+            let pct = ((used as f64 / limit as f64) * 100.0).clamp(0.0, 100.0);
+            let color = if pct >= 90.0 {
+                "bg-red-600"
+            } else if pct >= 75.0 {
+                "bg-yellow-500"
+            } else {
+                "bg-blue-600"
+            };
+            view! {
+                <div class="mt-2.5">
+                    <div class="flex w-full h-2 bg-gray-200 rounded-full overflow-hidden dark:bg-gray-700">
+                        <div class=format!("{color} rounded-full") style=format!("width: {pct:.1}%")></div>
+                    </div>
+                    <p class="mt-2 text-sm text-gray-500">
+                        {format!(
+                            "{} of {} used ({}%)",
+                            format_size(used, DECIMAL),
+                            format_size(limit, DECIMAL),
+                            pct.round() as u8,
+                        )}
+                    </p>
+                </div>
+            }
+            .into_view()
+        }
+        Some(QuotaUsage { used, .. }) => view! {
+            <ReportTextValue value=format!("{} used, no quota limit", format_size(used, DECIMAL))/>
+        }
+        .into_view(),
+        None => view! { <ReportTextValue value="Not available to your account"/> }.into_view(),
+    }
+}
+
+#[component]
+fn ValueList(values: Vec<String>, empty: &'static str) -> impl IntoView {
+    if values.is_empty() {
+        view! { <ReportTextValue value=empty/> }.into_view()
+    } else {
+        view! {
+            <div class="mt-2.5 flex flex-wrap gap-2">
+                {values
+                    .into_iter()
+                    .map(|value| view! { <Badge color=Color::Gray>{value}</Badge> })
+                    .collect_view()}
+            </div>
+        }
+        .into_view()
+    }
+}
+
+async fn fetch_overview(auth: &AuthToken) -> http::Result<AccountOverviewData> {
+    let account_id = jmap_primary_account(auth).await?;
+
+    // Accounts with access to their own principal get the full details
+    let principal = optional(
+        HttpRequest::get(("/api/principal", auth.username.as_str()))
+            .with_authorization(auth)
+            .send::<Principal>()
+            .await,
+    )?;
+    let auth_info = optional(
+        HttpRequest::get("/api/account/auth")
+            .with_authorization(auth)
+            .send::<AccountAuthResponse>()
+            .await,
+    )?;
+
+    let quota = match &principal {
+        Some(principal) if principal.used_quota.is_some() => Some(QuotaUsage {
+            used: principal.used_quota.unwrap_or_default(),
+            limit: principal.quota.filter(|quota| *quota > 0),
+        }),
+        _ => optional(
+            jmap_get(auth, USING_QUOTA, "Quota/get", &account_id)
+                .await
+                .map(|list| {
+                    list.iter()
+                        .find(|quota| quota["resourceType"] == "octets")
+                        .map(|quota| QuotaUsage {
+                            used: quota["used"].as_u64().unwrap_or_default(),
+                            limit: quota["hardLimit"].as_u64().filter(|limit| *limit > 0),
+                        })
+                }),
+        )?
+        .flatten(),
+    };
+
+    let addresses = match &principal {
+        Some(principal) => principal.emails.clone(),
+        None => optional(
+            jmap_get(auth, USING_IDENTITY, "Identity/get", &account_id)
+                .await
+                .map(|list| {
+                    let mut addresses = list
+                        .iter()
+                        .filter_map(|identity| identity["email"].as_str())
+                        .map(|email| email.to_string())
+                        .collect::<Vec<_>>();
+                    addresses.sort_unstable();
+                    addresses.dedup();
+                    addresses
+                }),
+        )?
+        .unwrap_or_default(),
+    };
+
+    let vacation = optional(fetch_vacation(auth).await)?.map(|(_, vacation)| vacation);
+
+    let scripts = optional(
+        jmap_get(auth, USING_SIEVE, "SieveScript/get", &account_id)
+            .await
+            .map(|list| {
+                list.iter()
+                    .map(|script| SieveScript {
+                        name: script["name"].as_str().unwrap_or_default().to_string(),
+                        active: script["isActive"].as_bool().unwrap_or_default(),
+                    })
+                    .collect::<Vec<_>>()
+            }),
+    )?;
+
+    Ok(AccountOverviewData {
+        account_id,
+        quota,
+        addresses,
+        member_of: principal.map(|principal| principal.member_of),
+        auth: auth_info,
+        vacation,
+        scripts,
+    })
+}
+
+async fn jmap_get(
+    auth: &AuthToken,
+    using: &[&str],
+    method: &str,
+    account_id: &str,
+) -> http::Result<Vec<Value>> {
+    let mut response = jmap_request_using(
+        auth,
+        using,
+        vec![(method, json!({"accountId": account_id, "ids": null}))],
+    )
+    .await?;
+
+    Ok(
+        match response.pop().map(|mut response| response["list"].take()) {
+            Some(Value::Array(list)) => list,
+            _ => Vec::new(),
+        },
+    )
+}
+
+/// Treats sections that the account has no access to, or that the server
+/// does not support, as unavailable instead of failing the whole page.
+fn optional<T>(result: http::Result<T>) -> http::Result<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(http::Error::Unauthorized) => Err(http::Error::Unauthorized),
+        Err(err) => {
+            log::debug!("Account overview section unavailable: {err:?}");
+            Ok(None)
+        }
+    }
+}
@@ -0,0 +1,78 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use leptos::*;
+
+use crate::{
+    components::form::{select::Select, Form, FormElement, FormItem, FormSection},
+    core::{
+        idle::use_idle_lock,
+        schema::{Builder, Schemas, Source, Type},
+    },
+};
+
+const IDLE_TIMEOUTS: &[(&str, &str)] = &[
+    ("0", "Never"),
+    ("5", "After 5 minutes"),
+    ("15", "After 15 minutes"),
+    ("30", "After 30 minutes"),
+    ("60", "After 1 hour"),
+    ("240", "After 4 hours"),
+];
+
+#[component]
+pub fn SessionSettings() -> impl IntoView {
+    let settings = use_idle_lock();
+    let data = expect_context::<Arc<Schemas>>()
+        .build_form("session")
+        .into_signal();
+    data.update(|data| {
+        let timeout = settings.with_untracked(|settings| settings.timeout);
+        data.update("idle-timeout", timeout.to_string());
+    });
+
+    create_effect(move |_| {
+        let timeout = data.with(|data| data.value::<u64>("idle-timeout").unwrap_or_default());
+        if settings.with_untracked(|settings| settings.timeout) != timeout {
+            settings.update(|settings| {
+                settings.timeout = timeout;
+                settings.save();
+            });
+        }
+    });
+
+    view! {
+        <Form
+            title="Session"
+            subtitle="Protect the web admin on shared workstations by locking it when left unattended"
+        >
+            <FormSection>
+                <FormItem
+                    label="Lock when idle"
+                    tooltip="Applies to this browser only. Once locked, the password and the two-factor authentication code, if enabled, are required to continue"
+                >
+                    <Select element=FormElement::new("idle-timeout", data)/>
+                </FormItem>
+            </FormSection>
+        </Form>
+    }
+}
+
+impl Builder<Schemas, ()> {
+    pub fn build_session(self) -> Self {
+        self.new_schema("session")
+            .new_field("idle-timeout")
+            .typ(Type::Select {
+                source: Source::Static(IDLE_TIMEOUTS),
+                multi: false,
+            })
+            .default("0")
+            .build()
+            .build()
+    }
+}
@@ -0,0 +1,410 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    components::{
+        form::{
+            button::Button,
+            input::{InputSwitch, InputText, TextArea},
+            Form, FormButtonBar, FormElement, FormItem, FormSection,
+        },
+        messages::alert::{use_alerts, Alert},
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        form::FormData,
+        http::{self, ManagementApiError},
+        jmap::{jmap_primary_account, jmap_request_using},
+        oauth::{use_authorization, AuthToken},
+        schema::{Builder, Schemas, Transformer, Type, Validator},
+    },
+};
+
+const USING_VACATION: &[&str] = &[
+    "urn:ietf:params:jmap:core",
+    "urn:ietf:params:jmap:vacationresponse",
+];
+const DATE_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VacationResponse {
+    #[serde(default)]
+    pub is_enabled: bool,
+    #[serde(default)]
+    pub from_date: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub to_date: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub subject: Option<String>,
+    #[serde(default)]
+    pub text_body: Option<String>,
+    #[serde(default)]
+    pub html_body: Option<String>,
+}
+
+#[component]
+pub fn VacationResponder() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let data = expect_context::<Arc<Schemas>>()
+        .build_form("vacation")
+        .into_signal();
+
+    let vacation = create_resource(
+        move || (),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move { fetch_vacation(&auth).await }
+        },
+    );
+
+    let save_action = create_action(move |(account_id, response): &(String, VacationResponse)| {
+        let auth = auth.get();
+        let account_id = account_id.clone();
+        let response = response.clone();
+
+        async move {
+            match set_vacation(
+                &auth,
+                &account_id,
+                serde_json::to_value(&response).unwrap_or_default(),
+            )
+            .await
+            {
+                Ok(_) => {
+                    data.update(|data| data.mark_clean());
+                    alert.set(Alert::success("Vacation responder saved"));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    view! {
+        <Form
+            title="Vacation Responder"
+            subtitle="Automatically reply to incoming messages while you are away"
+        >
+            <Transition fallback=Skeleton>
+                {move || match vacation.get() {
+                    None => None,
+                    Some(Err(http::Error::Unauthorized)) => {
+                        use_navigate()("/login", Default::default());
+                        Some(view! { <div></div> }.into_view())
+                    }
+                    Some(Err(err)) => {
+                        alert.set(Alert::from(err));
+                        Some(view! { <Skeleton/> }.into_view())
+                    }
+                    Some(Ok((account_id, response))) => {
+                        data.update(|data| {
+                            load_form(data, &response);
+                            data.mark_clean();
+                        });
+                        let account_id = store_value(account_id);
+                        Some(
+                            view! {
+                                <FormSection>
+                                    <FormItem label="Status">
+                                        <InputSwitch
+                                            element=FormElement::new("enabled", data)
+                                            label="Reply automatically to incoming messages".to_string()
+                                        />
+                                    </FormItem>
+                                    <FormItem
+                                        label="Start date"
+                                        tooltip="Local date and time from which replies are sent, leave blank to start immediately"
+                                    >
+                                        <InputText
+                                            element=FormElement::new("from-date", data)
+                                            placeholder="YYYY-MM-DD HH:MM"
+                                        />
+                                    </FormItem>
+                                    <FormItem
+                                        label="End date"
+                                        tooltip="Local date and time after which replies are no longer sent, leave blank to reply until disabled"
+                                    >
+                                        <InputText
+                                            element=FormElement::new("to-date", data)
+                                            placeholder="YYYY-MM-DD HH:MM"
+                                        />
+                                    </FormItem>
+                                    <FormItem label="Subject">
+                                        <InputText
+                                            element=FormElement::new("subject", data)
+                                            placeholder="Out of office"
+                                        />
+                                    </FormItem>
+                                    <FormItem label="Message">
+                                        <TextArea element=FormElement::new("text-body", data)/>
+                                    </FormItem>
+                                    <FormItem
+                                        label="HTML message"
+                                        tooltip="Optional HTML version of the message, sent to clients that can display it"
+                                    >
+                                        <TextArea element=FormElement::new("html-body", data)/>
+                                    </FormItem>
+                                    <FormItem label="Preview">
+                                        <VacationPreview data=data/>
+                                    </FormItem>
+                                </FormSection>
+
+                                <FormButtonBar>
+                                    <Button
+                                        text="Save changes"
+                                        color=Color::Blue
+                                        on_click=Callback::new(move |_| {
+                                            data.update(|data| {
+                                                if let Some(response) = read_form(data) {
+                                                    save_action
+                                                        .dispatch((account_id.get_value(), response));
+                                                }
+                                            });
+                                        })
+
+                                        disabled=save_action.pending()
+                                    />
+                                </FormButtonBar>
+                            }
+                                .into_view(),
+                        )
+                    }
+                }}
+
+            </Transition>
+        </Form>
+    }
+}
+
+/// Shows the reply as it will be received, rendering the HTML version in a
+/// sandboxed frame when one is provided.
+#[component]
+fn VacationPreview(data: RwSignal<FormData>) -> impl IntoView {
+    let subject = create_memo(move |_| {
+        data.with(|data| {
+            data.value::<String>("subject")
+                .filter(|subject| !subject.is_empty())
+                .unwrap_or_else(|| "Auto: (original subject)".to_string())
+        })
+    });
+    let html_body = create_memo(move |_| {
+        data.with(|data| data.value::<String>("html-body"))
+            .filter(|body| !body.trim().is_empty())
+    });
+    let text_body = create_memo(move |_| {
+        data.with(|data| data.value::<String>("text-body"))
+            .unwrap_or_default()
+    });
+
+    view! {
+        <div class="border border-gray-200 rounded-lg overflow-hidden dark:border-gray-700">
+            <div class="px-4 py-2 bg-gray-50 border-b border-gray-200 dark:bg-slate-800 dark:border-gray-700">
+                <p class="text-xs text-gray-500">Subject</p>
+                <p class="text-sm font-semibold text-gray-800 dark:text-gray-200">
+                    {move || subject.get()}
+                </p>
+            </div>
+            {move || match html_body.get() {
+                Some(html) => {
+                    view! {
+                        <iframe
+                            class="w-full h-64 bg-white"
+                            sandbox=""
+                            srcdoc=html
+                        ></iframe>
+                    }
+                        .into_view()
+                }
+                None => {
+                    view! {
+                        <p class="px-4 py-3 text-sm text-gray-800 whitespace-pre-wrap dark:text-gray-200">
+                            {text_body.get()}
+                        </p>
+                    }
+                        .into_view()
+                }
+            }}
+
+        </div>
+    }
+}
+
+/// Fetches the vacation response of the primary account along with the
+/// account id.
+pub async fn fetch_vacation(auth: &AuthToken) -> http::Result<(String, VacationResponse)> {
+    let account_id = jmap_primary_account(auth).await?;
+    let mut response = jmap_request_using(
+        auth,
+        USING_VACATION,
+        vec![(
+            "VacationResponse/get",
+            json!({"accountId": account_id, "ids": null}),
+        )],
+    )
+    .await?;
+    let vacation = response
+        .pop()
+        .and_then(|mut response| match response["list"][0].take() {
+            Value::Null => None,
+            vacation => serde_json::from_value(vacation).ok(),
+        })
+        .unwrap_or_default();
+
+    Ok((account_id, vacation))
+}
+
+/// Updates the properties of the vacation response singleton included in
+/// the patch.
+pub async fn set_vacation(auth: &AuthToken, account_id: &str, patch: Value) -> http::Result<()> {
+    let response = jmap_request_using(
+        auth,
+        USING_VACATION,
+        vec![(
+            "VacationResponse/set",
+            json!({
+                "accountId": account_id,
+                "update": {"singleton": patch}
+            }),
+        )],
+    )
+    .await?;
+
+    match response
+        .first()
+        .map(|response| &response["notUpdated"]["singleton"])
+    {
+        Some(error) if !error.is_null() => Err(http::Error::Server(ManagementApiError::Other {
+            details: error["description"]
+                .as_str()
+                .unwrap_or("Failed to update the vacation responder")
+                .to_string(),
+        })),
+        _ => Ok(()),
+    }
+}
+
+fn load_form(data: &mut FormData, response: &VacationResponse) {
+    data.update(
+        "enabled",
+        if response.is_enabled { "true" } else { "false" },
+    );
+    for (id, date) in [
+        ("from-date", response.from_date),
+        ("to-date", response.to_date),
+    ] {
+        match date {
+            Some(date) => data.update(
+                id,
+                date.with_timezone(&Local).format(DATE_FORMAT).to_string(),
+            ),
+            None => data.remove(id),
+        }
+    }
+    for (id, value) in [
+        ("subject", &response.subject),
+        ("text-body", &response.text_body),
+        ("html-body", &response.html_body),
+    ] {
+        match value {
+            Some(value) => data.update(id, value.clone()),
+            None => data.remove(id),
+        }
+    }
+}
+
+fn read_form(data: &mut FormData) -> Option<VacationResponse> {
+    if !data.validate_form() {
+        return None;
+    }
+
+    let mut dates = [None, None];
+    for (date, id) in dates.iter_mut().zip(["from-date", "to-date"]) {
+        if let Some(value) = data.value::<String>(id).filter(|value| !value.is_empty()) {
+            match parse_local_date(&value) {
+                Some(value) => *date = Some(value),
+                None => {
+                    data.new_error(id, "Invalid date, use the format YYYY-MM-DD HH:MM");
+                    return None;
+                }
+            }
+        }
+    }
+    let [from_date, to_date] = dates;
+    if let (Some(from_date), Some(to_date)) = (from_date, to_date) {
+        if to_date <= from_date {
+            data.new_error("to-date", "The end date must be after the start date");
+            return None;
+        }
+    }
+
+    Some(VacationResponse {
+        is_enabled: data.value::<bool>("enabled").unwrap_or_default(),
+        from_date,
+        to_date,
+        subject: data.value::<String>("subject"),
+        text_body: data.value::<String>("text-body"),
+        html_body: data
+            .value::<String>("html-body")
+            .filter(|body| !body.trim().is_empty()),
+    })
+}
+
+/// Parses a date entered in the browser's time zone, with the time being
+/// optional.
+fn parse_local_date(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    NaiveDateTime::parse_from_str(value, DATE_FORMAT)
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+        .and_then(|date| Local.from_local_datetime(&date).earliest())
+        .map(|date| date.to_utc())
+}
+
+impl Builder<Schemas, ()> {
+    pub fn build_vacation(self) -> Self {
+        self.new_schema("vacation")
+            .new_field("enabled")
+            .typ(Type::Boolean)
+            .default("false")
+            .build()
+            .new_field("from-date")
+            .input_check([Transformer::Trim], [])
+            .build()
+            .new_field("to-date")
+            .input_check([Transformer::Trim], [])
+            .build()
+            .new_field("subject")
+            .input_check([Transformer::Trim], [Validator::MaxLength(255)])
+            .build()
+            .new_field("text-body")
+            .typ(Type::Text)
+            .build()
+            .new_field("html-body")
+            .typ(Type::Text)
+            .build()
+            .build()
+    }
+}
@@ -0,0 +1,251 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use chrono::{DateTime, Utc};
+use chrono_humanize::HumanTime;
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{button::Button, FormItem, FormSection},
+        icon::IconRefresh,
+        messages::alert::{use_alerts, Alert},
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        permissions::Capability,
+        tasks::use_tasks,
+        x509::CertificateInfo,
+    },
+    pages::FormatDateTime,
+};
+
+use super::{certs::EXPIRY_WARNING_DAYS, edit::FetchSettings};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct AcmeStatus {
+    #[serde(default)]
+    last_order: Option<AcmeOrder>,
+    #[serde(default)]
+    next_renewal: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct AcmeOrder {
+    status: String,
+    date: DateTime<Utc>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct AcmeState {
+    status: Option<AcmeStatus>,
+    certificate: Option<CertificateInfo>,
+}
+
+#[component]
+pub fn AcmeStatusPanel(#[prop(into)] id: String) -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let tasks = use_tasks();
+    let id = store_value(id);
+
+    let state = create_resource(
+        move || (),
+        move |_| {
+            let auth = auth.get_untracked();
+            let id = id.get_value();
+
+            async move {
+                // Older servers do not expose the order status
+                let status = match HttpRequest::get(format!("/api/acme/{id}/status"))
+                    .with_authorization(&auth)
+                    .send::<AcmeStatus>()
+                    .await
+                {
+                    Ok(status) => Some(status),
+                    Err(http::Error::NotFound) => None,
+                    Err(err) => return Err(err),
+                };
+                let certificate = HttpRequest::get("/api/settings/list")
+                    .with_authorization(&auth)
+                    .with_parameter("prefix", format!("acme.{id}"))
+                    .send::<FetchSettings>()
+                    .await?
+                    .items
+                    .get("cert")
+                    .and_then(|cert| CertificateInfo::parse(cert));
+
+                Ok(AcmeState {
+                    status,
+                    certificate,
+                })
+            }
+        },
+    );
+
+    let renew_action = create_action(move |_: &()| {
+        let auth = auth.get();
+        let id = id.get_value();
+
+        async move {
+            match HttpRequest::post(format!("/api/acme/{id}/renew"))
+                .with_authorization(&auth)
+                .send::<Option<String>>()
+                .await
+            {
+                Ok(status_url) => {
+                    if let Some(status_url) = status_url {
+                        tasks.update(|tasks| {
+                            tasks.insert(
+                                format!("Renew ACME certificate for {id}"),
+                                Some(status_url),
+                            );
+                        });
+                    }
+                    alert.set(Alert::success(format!(
+                        "Requested certificate renewal for {id}."
+                    )));
+                    state.refetch();
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    view! {
+        <FormSection title="Status".to_string()>
+            <Transition>
+                {move || match state.get() {
+                    None => None,
+                    Some(Err(http::Error::Unauthorized)) => {
+                        use_navigate()("/login", Default::default());
+                        None
+                    }
+                    Some(Err(err)) => {
+                        alert.set(Alert::from(err));
+                        None
+                    }
+                    Some(Ok(state)) => {
+                        let status = state.status.unwrap_or_default();
+                        let last_order = match status.last_order {
+                            Some(order) => {
+                                let color = match order.status.as_str() {
+                                    "valid" | "ready" => Color::Green,
+                                    "invalid" => Color::Red,
+                                    _ => Color::Yellow,
+                                };
+                                view! {
+                                    <div class="flex items-center gap-x-2">
+                                        <Badge color=color>{order.status}</Badge>
+                                        <span>{order.date.format_date_time()}</span>
+                                    </div>
+                                    {order
+                                        .error
+                                        .map(|error| {
+                                            view! {
+                                                <p class="mt-1 text-red-600 dark:text-red-500">
+                                                    {error}
+                                                </p>
+                                            }
+                                        })}
+                                }
+                                    .into_view()
+                            }
+                            None => "No orders placed yet".into_view(),
+                        };
+                        let certificate = match state.certificate {
+                            Some(cert) => {
+                                let days_left = (cert.not_after - Utc::now()).num_days();
+                                let color = if days_left < 0 {
+                                    Color::Red
+                                } else if days_left < EXPIRY_WARNING_DAYS {
+                                    Color::Yellow
+                                } else {
+                                    Color::Green
+                                };
+                                view! {
+                                    <div class="flex items-center gap-x-2">
+                                        <Badge color=color>
+                                            {format!("Expires {}", HumanTime::from(cert.not_after))}
+                                        </Badge>
+                                        <span>{cert.not_after.format_date_time()}</span>
+                                    </div>
+                                    <p class="mt-1">
+                                        {format!(
+                                            "Issued by {} for {}",
+                                            cert.issuer_name.unwrap_or_else(|| "unknown issuer".to_string()),
+                                            cert.sans.join(", "),
+                                        )}
+                                    </p>
+                                }
+                                    .into_view()
+                            }
+                            None => "No certificate acquired".into_view(),
+                        };
+                        let next_renewal = status
+                            .next_renewal
+                            .map(|date| {
+                                format!(
+                                    "{} ({})",
+                                    date.format_date_time(),
+                                    HumanTime::from(date),
+                                )
+                            })
+                            .unwrap_or_else(|| "Not scheduled".to_string());
+
+                        Some(
+                            view! {
+                                <FormItem label="Last order">
+                                    <div class="pt-2.5 text-sm text-gray-600 dark:text-gray-400">
+                                        {last_order}
+                                    </div>
+                                </FormItem>
+                                <FormItem label="Certificate">
+                                    <div class="pt-2.5 text-sm text-gray-600 dark:text-gray-400">
+                                        {certificate}
+                                    </div>
+                                </FormItem>
+                                <FormItem label="Next renewal">
+                                    <div class="pt-2.5 text-sm text-gray-600 dark:text-gray-400">
+                                        {next_renewal}
+                                    </div>
+                                </FormItem>
+                            }
+                                .into_view(),
+                        )
+                    }
+                }}
+
+            </Transition>
+            <FormItem label="">
+                <Button
+                    text="Renew now"
+                    color=Color::Gray
+                    on_click=Callback::new(move |_| {
+                        renew_action.dispatch(());
+                    })
+
+                    disabled=renew_action.pending()
+                    capability=Capability::AcmeRenew
+                >
+                    <IconRefresh/>
+                </Button>
+            </FormItem>
+        </FormSection>
+    }
+}
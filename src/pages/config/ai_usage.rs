@@ -0,0 +1,385 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        badge::Badge,
+        icon::IconRefresh,
+        list::{
+            header::ColumnList, toolbar::ToolbarButton, Footer, ListItem, ListSection, ListTable,
+            ListTextItem, Toolbar, ZeroResults,
+        },
+        messages::alert::{use_alerts, Alert},
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+    },
+    pages::{
+        config::{
+            llm_console::{test_model, LlmTestRequest},
+            Settings,
+        },
+        manage::dashboard::{metrics_request, MetricsHistory, PERIODS},
+        maybe_plural, List,
+    },
+};
+
+const SELECT_CLASS: &str = "py-2 px-3 pe-9 block border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600";
+
+const METRIC_REQUESTS: &str = "ai.llm-request";
+const METRIC_ERRORS: &str = "ai.llm-error";
+const METRIC_LATENCY: &str = "ai.llm-response-time";
+
+const TEST_PROMPT: &str = "Reply with the single word OK.";
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+struct ModelUsage {
+    id: String,
+    model: String,
+    requests: u64,
+    errors: u64,
+    latency: Option<f64>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum TestResult {
+    Success(u128),
+    Failed,
+}
+
+/// Request counts, error rates and average latency of each configured AI
+/// model, as reported by the metrics API.
+#[component]
+pub fn AiModelUsage() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let period = create_rw_signal(24u32);
+    let test_results = create_rw_signal(Vec::<(String, TestResult)>::new());
+
+    let usage = create_resource(
+        move || period.get(),
+        move |period| {
+            let auth = auth.get_untracked();
+
+            async move {
+                let models = HttpRequest::get("/api/settings/group")
+                    .with_authorization(&auth)
+                    .with_parameter("prefix", "enterprise.ai")
+                    .with_parameter("suffix", "url")
+                    .with_parameter("field", "_id")
+                    .send::<List<Settings>>()
+                    .await?
+                    .items;
+                let history = match metrics_request(
+                    &auth,
+                    [METRIC_REQUESTS, METRIC_ERRORS, METRIC_LATENCY],
+                    period,
+                )
+                .send::<MetricsHistory>()
+                .await
+                {
+                    Ok(history) => history,
+                    Err(http::Error::NotFound) => MetricsHistory::default(),
+                    Err(err) => return Err(err),
+                };
+
+                Ok(models
+                    .into_iter()
+                    .filter_map(|mut model| {
+                        let id = model.remove("_id")?;
+                        Some(ModelUsage::new(
+                            &history,
+                            model.remove("model").unwrap_or_default(),
+                            id,
+                        ))
+                    })
+                    .collect::<Vec<_>>())
+            }
+        },
+    );
+
+    let test_action = create_action(move |id: &String| {
+        let auth = auth.get();
+        let id = id.clone();
+
+        async move {
+            let request = LlmTestRequest {
+                model: id.clone(),
+                prompt: TEST_PROMPT.to_string(),
+                ..Default::default()
+            };
+            let result = match test_model(&auth, request).await {
+                Ok((_, latency)) => TestResult::Success(latency),
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                    return;
+                }
+                Err(http::Error::NotFound) => {
+                    alert.set(Alert::warning(
+                        "This server does not support testing AI models.",
+                    ));
+                    return;
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                    TestResult::Failed
+                }
+            };
+            test_results.update(|results| {
+                results.retain(|(model, _)| *model != id);
+                results.push((id, result));
+            });
+        }
+    });
+
+    let total_results = create_rw_signal(None::<usize>);
+
+    view! {
+        <ListSection>
+            <ListTable
+                title="AI Model Usage"
+                subtitle="Requests, errors and latency of the configured language models"
+            >
+                <Toolbar slot>
+                    <select
+                        class=SELECT_CLASS
+                        on:change=move |ev| {
+                            period.set(event_target_value(&ev).parse().unwrap_or(24));
+                        }
+                    >
+
+                        {PERIODS
+                            .iter()
+                            .map(|(hours, label)| {
+                                let hours = *hours;
+                                view! {
+                                    <option
+                                        value=hours.to_string()
+                                        selected=move || period.get() == hours
+                                    >
+                                        {*label}
+                                    </option>
+                                }
+                            })
+                            .collect_view()}
+
+                    </select>
+                    <ToolbarButton
+                        text="Refresh"
+                        color=Color::Gray
+                        on_click=move |_| {
+                            usage.refetch();
+                        }
+                    >
+
+                        <IconRefresh/>
+                    </ToolbarButton>
+
+                </Toolbar>
+
+                <Transition fallback=Skeleton>
+                    {move || match usage.get() {
+                        None => None,
+                        Some(Err(http::Error::Unauthorized)) => {
+                            use_navigate()("/login", Default::default());
+                            Some(view! { <div></div> }.into_view())
+                        }
+                        Some(Err(err)) => {
+                            total_results.set(Some(0));
+                            alert.set(Alert::from(err));
+                            Some(view! { <Skeleton/> }.into_view())
+                        }
+                        Some(Ok(models)) if !models.is_empty() => {
+                            total_results.set(Some(models.len()));
+                            Some(
+                                view! {
+                                    <ColumnList headers=vec![
+                                        "Model".to_string(),
+                                        "Requests".to_string(),
+                                        "Errors".to_string(),
+                                        "Error rate".to_string(),
+                                        "Avg. latency".to_string(),
+                                        "Test".to_string(),
+                                    ]>
+
+                                        <For
+                                            each=move || models.clone()
+                                            key=|model| model.id.clone()
+                                            let:model
+                                        >
+                                            <ModelUsageItem
+                                                test_result=Signal::derive({
+                                                    let id = model.id.clone();
+                                                    move || {
+                                                        test_results
+                                                            .get()
+                                                            .into_iter()
+                                                            .find(|(model, _)| *model == id)
+                                                            .map(|(_, result)| result)
+                                                    }
+                                                })
+
+                                                model
+                                                on_test=Callback::new(move |id| test_action.dispatch(id))
+                                                test_pending=test_action.pending()
+                                            />
+                                        </For>
+
+                                    </ColumnList>
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Ok(_)) => {
+                            total_results.set(Some(0));
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="No AI models"
+                                        subtitle="Add a model under Settings > AI Models to start tracking its usage."
+                                        button_text="Add model".to_string()
+                                        button_action=Callback::new(move |_| {
+                                            use_navigate()(
+                                                "/settings/ai-model/edit",
+                                                Default::default(),
+                                            );
+                                        })
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                    }}
+
+                </Transition>
+
+                <Footer slot>
+                    <div class="px-6 py-4 border-t border-gray-200 dark:border-gray-700">
+                        <p class="text-sm text-gray-600 dark:text-gray-400">
+                            {move || {
+                                total_results
+                                    .get()
+                                    .map(|total| maybe_plural(total, "model", "models"))
+                                    .unwrap_or_default()
+                            }}
+
+                        </p>
+                    </div>
+                </Footer>
+            </ListTable>
+        </ListSection>
+    }
+}
+
+#[component]
+fn ModelUsageItem(
+    model: ModelUsage,
+    #[prop(into)] test_result: Signal<Option<TestResult>>,
+    on_test: Callback<String, ()>,
+    test_pending: ReadSignal<bool>,
+) -> impl IntoView {
+    let error_rate = (model.requests > 0).then(|| model.errors as f64 / model.requests as f64);
+    let error_color = match error_rate {
+        Some(rate) if rate >= 0.1 => Color::Red,
+        Some(rate) if rate > 0.0 => Color::Yellow,
+        Some(_) => Color::Green,
+        None => Color::Gray,
+    };
+    let id = model.id.clone();
+
+    view! {
+        <tr>
+            <ListItem>
+                <div class="flex flex-col">
+                    <a
+                        class="text-sm font-semibold text-blue-600 decoration-2 hover:underline dark:text-blue-500"
+                        href=format!("/settings/ai-model/{}/edit", model.id)
+                    >
+                        {model.id.clone()}
+                    </a>
+                    <span class="text-xs text-gray-500">{model.model}</span>
+                </div>
+            </ListItem>
+            <ListTextItem>{model.requests}</ListTextItem>
+            <ListTextItem>{model.errors}</ListTextItem>
+            <ListItem>
+                <Badge color=error_color>
+                    {error_rate
+                        .map(|rate| format!("{:.1}%", rate * 100.0))
+                        .unwrap_or_else(|| "-".to_string())}
+                </Badge>
+            </ListItem>
+            <ListTextItem>
+                {model
+                    .latency
+                    .map(|latency| format!("{latency:.0} ms"))
+                    .unwrap_or_else(|| "-".to_string())}
+            </ListTextItem>
+            <ListItem subclass="px-6 py-1.5">
+                <div class="flex items-center gap-x-2">
+                    <button
+                        type="button"
+                        class="inline-flex items-center gap-x-1 text-sm text-blue-600 decoration-2 hover:underline font-medium disabled:opacity-50 disabled:pointer-events-none dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                        disabled=move || test_pending.get()
+                        on:click=move |_| on_test.call(id.clone())
+                    >
+                        Test
+                    </button>
+                    {move || {
+                        test_result
+                            .get()
+                            .map(|result| match result {
+                                TestResult::Success(latency) => {
+                                    view! {
+                                        <Badge color=Color::Green>{format!("OK {latency} ms")}</Badge>
+                                    }
+                                }
+                                TestResult::Failed => {
+                                    view! { <Badge color=Color::Red>Failed</Badge> }
+                                }
+                            })
+                    }}
+
+                </div>
+            </ListItem>
+        </tr>
+    }
+}
+
+impl ModelUsage {
+    fn new(history: &MetricsHistory, model: String, id: String) -> Self {
+        let series = |metric: &str| {
+            history
+                .metrics
+                .iter()
+                .filter(|entry| {
+                    entry.id == metric && entry.labels.get("model").is_some_and(|m| *m == id)
+                })
+                .flat_map(|entry| entry.values.iter().copied())
+                .collect::<Vec<_>>()
+        };
+        let latencies = series(METRIC_LATENCY)
+            .into_iter()
+            .filter(|value| *value > 0.0)
+            .collect::<Vec<_>>();
+
+        ModelUsage {
+            requests: series(METRIC_REQUESTS).iter().sum::<f64>() as u64,
+            errors: series(METRIC_ERRORS).iter().sum::<f64>() as u64,
+            latency: (!latencies.is_empty())
+                .then(|| latencies.iter().sum::<f64>() / latencies.len() as f64),
+            model,
+            id,
+        }
+    }
+}
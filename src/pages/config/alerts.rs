@@ -0,0 +1,443 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use ahash::AHashMap;
+use leptos::*;
+use leptos_router::use_navigate;
+use regex::Regex;
+
+use crate::{
+    components::{
+        chart::{line::LineChart, ChartLegend, Series},
+        form::{input::InputText, FormElement, FormItem, FormSection},
+        Color,
+    },
+    core::{form::FormData, http, oauth::use_authorization},
+    pages::manage::dashboard::{
+        format_labels, format_metric, metrics_request, MetricInfo, MetricUnit, MetricsHistory,
+        METRIC_CATALOG,
+    },
+};
+
+pub const ALERT_SCHEMA: &str = "alert";
+
+const INPUT_CLASS: &str = "py-2 px-3 block w-full border-gray-200 shadow-sm text-sm rounded-lg focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600";
+const SELECT_CLASS: &str = "py-2 px-3 pe-9 block border-gray-200 shadow-sm rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600";
+
+const PREVIEW_PERIOD: u32 = 24;
+
+const COMPARISONS: &[(&str, &str)] = &[
+    (">", "is greater than"),
+    (">=", "is at least"),
+    ("<", "is less than"),
+    ("<=", "is at most"),
+    ("==", "is equal to"),
+    ("!=", "is not equal to"),
+];
+
+/// Guided editor for the alert condition, replacing the free-form expression
+/// with a metric, a comparison and a threshold. Conditions that cannot be
+/// represented this way are edited as raw expressions.
+#[component]
+pub fn AlertConditionBuilder(data: RwSignal<FormData>) -> impl IntoView {
+    let auth = use_authorization();
+
+    let current = data.with_untracked(|data| {
+        data.value_as_str("condition")
+            .unwrap_or_default()
+            .trim()
+            .to_string()
+    });
+    let parsed = parse_condition(&current);
+    let advanced = create_rw_signal(!current.is_empty() && parsed.is_none());
+    let (metric, comparison, threshold, multiplier) = match parsed {
+        Some((metric, comparison, value)) => {
+            let (threshold, multiplier) = split_unit(metric.unit, value);
+            (Some(metric), comparison, threshold, multiplier)
+        }
+        None => (None, ">", String::new(), 1),
+    };
+    let metric = create_rw_signal(metric);
+    let comparison = create_rw_signal(comparison);
+    let threshold = create_rw_signal(threshold);
+    let multiplier = create_rw_signal(multiplier);
+    let search = create_rw_signal(String::new());
+
+    // Keep the stored condition in sync with the builder
+    create_effect(move |prev: Option<()>| {
+        let expr = if !advanced.get() {
+            metric.get().map(|metric| {
+                threshold.get().trim().parse::<f64>().ok().map(|value| {
+                    format!(
+                        "{} {} {}",
+                        variable_name(metric.id),
+                        comparison.get(),
+                        value * multiplier.get() as f64
+                    )
+                })
+            })
+        } else {
+            None
+        };
+        if prev.is_some() {
+            if let Some(expr) = expr {
+                data.update(|data| data.update("condition", expr.unwrap_or_default()));
+            }
+        }
+    });
+
+    let fetch_values = create_resource(
+        move || (),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move {
+                metrics_request(&auth, METRIC_CATALOG.iter().map(|m| m.id), 1)
+                    .send::<MetricsHistory>()
+                    .await
+                    .map(|history| {
+                        let mut values: AHashMap<String, f64> = AHashMap::new();
+                        for entry in history.metrics {
+                            if let Some(value) = entry.values.last() {
+                                *values.entry(entry.id).or_default() += value;
+                            }
+                        }
+                        values
+                    })
+            }
+        },
+    );
+    let current_values = create_memo(move |_| match fetch_values.get() {
+        Some(Ok(values)) => values,
+        Some(Err(http::Error::Unauthorized)) => {
+            use_navigate()("/login", Default::default());
+            AHashMap::new()
+        }
+        _ => AHashMap::new(),
+    });
+
+    let history = create_resource(
+        move || metric.get().map(|metric| metric.id),
+        move |metric_id| {
+            let auth = auth.get_untracked();
+
+            async move {
+                match metric_id {
+                    Some(metric_id) => metrics_request(&auth, [metric_id], PREVIEW_PERIOD)
+                        .send::<MetricsHistory>()
+                        .await
+                        .ok(),
+                    None => None,
+                }
+            }
+        },
+    );
+
+    let matching_metrics = create_memo(move |_| {
+        let search = search.get().trim().to_lowercase();
+        METRIC_CATALOG
+            .iter()
+            .filter(|m| {
+                search.is_empty()
+                    || m.id.contains(&search)
+                    || m.description.to_lowercase().contains(&search)
+            })
+            .collect::<Vec<_>>()
+    });
+
+    let condition_error = create_memo(move |_| {
+        data.with(|data| data.error_string("condition").map(|err| err.to_string()))
+    });
+
+    view! {
+        <FormSection
+            title="Condition".to_string()
+            docs="telemetry/alerts#condition"
+        >
+            <Show
+                when=move || !advanced.get()
+                fallback=move || {
+                    view! {
+                        <FormItem
+                            label="Expression"
+                            tooltip="Expression that triggers the alert when it evaluates to true"
+                            docs="configuration/expressions/overview"
+                        >
+                            <InputText
+                                element=FormElement::new("condition", data)
+                                placeholder="server_memory > 1073741824"
+                            />
+                            <AdvancedToggle advanced=advanced/>
+                        </FormItem>
+                    }
+                }
+            >
+
+                <FormItem label="Metric" tooltip="Metric that is compared against the threshold">
+                    <input
+                        type="text"
+                        class=INPUT_CLASS
+                        placeholder="Search metrics"
+                        prop:value=move || search.get()
+                        on:input=move |ev| search.set(event_target_value(&ev))
+                    />
+                    <div class="mt-2 max-h-64 overflow-y-auto border border-gray-200 rounded-lg divide-y divide-gray-200 dark:border-gray-700 dark:divide-gray-700">
+                        <For
+                            each=move || matching_metrics.get()
+                            key=|m| m.id
+                            let:info
+                        >
+                            <button
+                                type="button"
+                                class=move || {
+                                    if metric.get() == Some(info) {
+                                        "w-full flex justify-between items-center gap-x-3 py-2 px-3 text-start bg-blue-50 dark:bg-slate-800"
+                                    } else {
+                                        "w-full flex justify-between items-center gap-x-3 py-2 px-3 text-start hover:bg-gray-100 dark:hover:bg-slate-800"
+                                    }
+                                }
+
+                                on:click=move |_| {
+                                    if metric.get_untracked().map_or(true, |m| m.unit != info.unit) {
+                                        multiplier.set(1);
+                                    }
+                                    metric.set(Some(info));
+                                }
+                            >
+
+                                <div class="min-w-0">
+                                    <p class="text-sm font-mono text-gray-800 dark:text-gray-200">
+                                        {variable_name(info.id)}
+                                    </p>
+                                    <p class="text-xs text-gray-500">{info.description}</p>
+                                </div>
+                                <span class="text-sm text-gray-600 dark:text-gray-400 whitespace-nowrap">
+                                    {move || {
+                                        current_values
+                                            .with(|values| values.get(info.id).copied())
+                                            .map(|value| format_metric(info.unit, value))
+                                            .unwrap_or_else(|| "-".to_string())
+                                    }}
+
+                                </span>
+                            </button>
+                        </For>
+                    </div>
+                </FormItem>
+
+                <FormItem label="Threshold" tooltip="Value the metric is compared against">
+                    <div class="flex gap-x-2">
+                        <select
+                            class=SELECT_CLASS
+                            on:change=move |ev| {
+                                let value = event_target_value(&ev);
+                                if let Some((op, _)) = COMPARISONS
+                                    .iter()
+                                    .find(|(op, _)| *op == value)
+                                {
+                                    comparison.set(*op);
+                                }
+                            }
+                        >
+
+                            {COMPARISONS
+                                .iter()
+                                .map(|(op, label)| {
+                                    let op = *op;
+                                    view! {
+                                        <option value=op selected=move || comparison.get() == op>
+                                            {*label}
+                                        </option>
+                                    }
+                                })
+                                .collect_view()}
+                        </select>
+                        <input
+                            type="number"
+                            min="0"
+                            step="any"
+                            class=INPUT_CLASS
+                            prop:value=move || threshold.get()
+                            on:input=move |ev| threshold.set(event_target_value(&ev))
+                        />
+                        <select
+                            class=SELECT_CLASS
+                            class:hidden=move || {
+                                unit_multipliers(metric.get().map_or(MetricUnit::Count, |m| m.unit))
+                                    .len() < 2
+                            }
+
+                            on:change=move |ev| {
+                                multiplier.set(event_target_value(&ev).parse().unwrap_or(1));
+                            }
+                        >
+
+                            {move || {
+                                unit_multipliers(metric.get().map_or(MetricUnit::Count, |m| m.unit))
+                                    .iter()
+                                    .map(|(value, label)| {
+                                        let value = *value;
+                                        view! {
+                                            <option
+                                                value=value.to_string()
+                                                selected=move || multiplier.get() == value
+                                            >
+                                                {*label}
+                                            </option>
+                                        }
+                                    })
+                                    .collect_view()
+                            }}
+
+                        </select>
+                    </div>
+                    {move || {
+                        condition_error
+                            .get()
+                            .map(|error| {
+                                view! { <p class="text-xs text-red-600 mt-2">{error}</p> }
+                            })
+                    }}
+
+                    <AdvancedToggle advanced=advanced/>
+                </FormItem>
+
+                <FormItem label="History" tooltip="Values of the metric over the last 24 hours">
+                    {move || match (metric.get(), history.get()) {
+                        (Some(info), Some(Some(history))) => {
+                            let values = history
+                                .metrics
+                                .into_iter()
+                                .filter(|entry| entry.id == info.id)
+                                .fold(
+                                    Vec::<f64>::new(),
+                                    |mut totals, entry| {
+                                        if totals.len() < entry.values.len() {
+                                            totals.resize(entry.values.len(), 0.0);
+                                        }
+                                        for (total, value) in totals.iter_mut().zip(entry.values) {
+                                            *total += value;
+                                        }
+                                        totals
+                                    },
+                                );
+                            let mut series = vec![
+                                Series {
+                                    name: variable_name(info.id),
+                                    color: Color::Blue,
+                                    values,
+                                },
+                            ];
+                            if let Ok(value) = threshold.get().trim().parse::<f64>() {
+                                series
+                                    .push(Series {
+                                        name: "Threshold".to_string(),
+                                        color: Color::Red,
+                                        values: vec![
+                                            value * multiplier.get() as f64;
+                                            history.timestamps.len()
+                                        ],
+                                    });
+                            }
+                            let labels = format_labels(&history.timestamps, PREVIEW_PERIOD);
+                            view! {
+                                <LineChart labels=labels series=series.clone() height=160/>
+                                <ChartLegend series=series/>
+                            }
+                                .into_view()
+                        }
+                        (Some(_), Some(None)) => {
+                            view! {
+                                <p class="text-sm text-gray-500">
+                                    No history is available for this metric.
+                                </p>
+                            }
+                                .into_view()
+                        }
+                        (None, _) => {
+                            view! {
+                                <p class="text-sm text-gray-500">
+                                    Select a metric to preview its recent values.
+                                </p>
+                            }
+                                .into_view()
+                        }
+                        (Some(_), None) => {
+                            view! {
+                                <div class="animate-pulse h-40 bg-gray-200 rounded-lg dark:bg-gray-700"></div>
+                            }
+                                .into_view()
+                        }
+                    }}
+
+                </FormItem>
+            </Show>
+        </FormSection>
+    }
+}
+
+#[component]
+pub(crate) fn AdvancedToggle(advanced: RwSignal<bool>) -> impl IntoView {
+    view! {
+        <button
+            type="button"
+            class="mt-2 text-sm text-blue-600 decoration-2 hover:underline font-medium dark:text-blue-500"
+            on:click=move |_| advanced.update(|advanced| *advanced = !*advanced)
+        >
+            {move || {
+                if advanced.get() { "Use the condition builder" } else { "Edit as expression" }
+            }}
+
+        </button>
+    }
+}
+
+/// Parses conditions of the form `<metric> <comparison> <number>`.
+fn parse_condition(expr: &str) -> Option<(&'static MetricInfo, &'static str, f64)> {
+    let re = Regex::new(r"^([a-z0-9_]+)\s*(>=|<=|==|!=|>|<)\s*([0-9]+(?:\.[0-9]+)?)$").ok()?;
+    let captures = re.captures(expr.trim())?;
+    let metric = METRIC_CATALOG
+        .iter()
+        .find(|m| variable_name(m.id) == captures[1])?;
+    let comparison = COMPARISONS
+        .iter()
+        .find(|(op, _)| *op == &captures[2])
+        .map(|(op, _)| *op)?;
+    let value = captures[3].parse().ok()?;
+
+    Some((metric, comparison, value))
+}
+
+/// Metric names use underscores in expressions.
+fn variable_name(id: &str) -> String {
+    id.replace(['.', '-'], "_")
+}
+
+fn unit_multipliers(unit: MetricUnit) -> &'static [(u64, &'static str)] {
+    match unit {
+        MetricUnit::Count => &[(1, "")],
+        MetricUnit::Bytes => &[
+            (1, "bytes"),
+            (1_000, "KB"),
+            (1_000_000, "MB"),
+            (1_000_000_000, "GB"),
+        ],
+        MetricUnit::Milliseconds => &[(1, "ms"), (1_000, "seconds"), (60_000, "minutes")],
+    }
+}
+
+/// Expresses a raw threshold in the largest unit that divides it evenly.
+fn split_unit(unit: MetricUnit, value: f64) -> (String, u64) {
+    unit_multipliers(unit)
+        .iter()
+        .rev()
+        .find(|(multiplier, _)| {
+            let multiplier = *multiplier as f64;
+            value >= multiplier && value % multiplier == 0.0
+        })
+        .map(|(multiplier, _)| ((value / *multiplier as f64).to_string(), *multiplier))
+        .unwrap_or_else(|| (value.to_string(), 1))
+}
@@ -0,0 +1,353 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use ahash::AHashMap;
+use chrono::Utc;
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        badge::Badge,
+        icon::IconRefresh,
+        list::{
+            header::ColumnList, toolbar::ToolbarButton, Footer, ListItem, ListSection, ListTable,
+            ListTextItem, Toolbar, ZeroResults,
+        },
+        messages::alert::{use_alerts, Alert},
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::{use_authorization, AuthToken},
+        x509::CertificateInfo,
+    },
+    pages::{maybe_plural, FormatDateTime},
+};
+
+use super::edit::FetchSettings;
+
+pub const EXPIRY_WARNING_DAYS: i64 = 30;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct CertificateEntry {
+    id: String,
+    source: CertificateSource,
+    subjects: Vec<String>,
+    info: Result<CertificateInfo, String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+enum CertificateSource {
+    Manual,
+    Acme,
+}
+
+#[component]
+pub fn CertificateExpiry() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+
+    let certificates = create_resource(
+        move || (),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move {
+                let mut entries = fetch_certificates(&auth).await?;
+
+                // Show the certificates closest to expiring first
+                entries.sort_by(|a, b| match (&a.info, &b.info) {
+                    (Ok(a), Ok(b)) => a.not_after.cmp(&b.not_after),
+                    (Ok(_), Err(_)) => std::cmp::Ordering::Greater,
+                    (Err(_), Ok(_)) => std::cmp::Ordering::Less,
+                    (Err(_), Err(_)) => a.id.cmp(&b.id),
+                });
+
+                Ok(entries)
+            }
+        },
+    );
+
+    let summary = create_memo(move |_| {
+        certificates
+            .get()
+            .and_then(|result| result.ok())
+            .map(|entries| {
+                let now = Utc::now();
+                let expiring = entries
+                    .iter()
+                    .filter(|entry| {
+                        entry.info.as_ref().map_or(false, |info| {
+                            (info.not_after - now).num_days() < EXPIRY_WARNING_DAYS
+                        })
+                    })
+                    .count();
+                format!(
+                    "{}, {} expired or expiring within {EXPIRY_WARNING_DAYS} days.",
+                    maybe_plural(entries.len(), "certificate", "certificates"),
+                    expiring
+                )
+            })
+            .unwrap_or_default()
+    });
+
+    view! {
+        <ListSection>
+            <ListTable
+                title="Certificate expiry"
+                subtitle="Expiration dates of configured TLS certificates and ACME providers"
+            >
+                <Toolbar slot>
+                    <ToolbarButton
+                        text="Refresh"
+
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| {
+                            certificates.refetch();
+                        })
+                    >
+
+                        <IconRefresh/>
+                    </ToolbarButton>
+                </Toolbar>
+
+                <Transition fallback=Skeleton>
+                    {move || match certificates.get() {
+                        None => None,
+                        Some(Err(http::Error::Unauthorized)) => {
+                            use_navigate()("/login", Default::default());
+                            Some(view! { <div></div> }.into_view())
+                        }
+                        Some(Err(err)) => {
+                            alert.set(Alert::from(err));
+                            Some(view! { <Skeleton/> }.into_view())
+                        }
+                        Some(Ok(entries)) if !entries.is_empty() => {
+                            Some(
+                                view! {
+                                    <ColumnList headers=vec![
+                                        "Id".to_string(),
+                                        "Source".to_string(),
+                                        "Subject Names".to_string(),
+                                        "Issuer".to_string(),
+                                        "Expires".to_string(),
+                                        "Days Left".to_string(),
+                                        "Status".to_string(),
+                                    ]>
+
+                                        <For
+                                            each=move || entries.clone()
+                                            key=|entry| (entry.id.clone(), entry.source as u8)
+                                            let:entry
+                                        >
+                                            <CertificateItem entry/>
+                                        </For>
+
+                                    </ColumnList>
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Ok(_)) => {
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="No certificates"
+                                        subtitle="There are no TLS certificates or ACME providers configured."
+                                        button_text="Add a certificate"
+                                        button_action=Callback::new(move |_| {
+                                            use_navigate()(
+                                                "/settings/certificate/edit",
+                                                Default::default(),
+                                            );
+                                        })
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                    }}
+
+                </Transition>
+
+                <Footer slot>
+                    <div class="px-6 py-4 text-sm text-gray-600 dark:text-gray-400">
+                        {move || summary.get()}
+                    </div>
+                </Footer>
+            </ListTable>
+        </ListSection>
+    }
+}
+
+#[component]
+fn CertificateItem(entry: CertificateEntry) -> impl IntoView {
+    let (source, edit_url) = match entry.source {
+        CertificateSource::Manual => (
+            "Certificate",
+            format!("/settings/certificate/{}/edit", entry.id),
+        ),
+        CertificateSource::Acme => ("ACME", format!("/settings/acme/{}/edit", entry.id)),
+    };
+
+    let (sans, issuer, expires, days_left, status) = match entry.info {
+        Ok(info) => {
+            let days_left = (info.not_after - Utc::now()).num_days();
+            let status = if info.not_after <= Utc::now() {
+                view! { <Badge color=Color::Red>Expired</Badge> }
+            } else if days_left < EXPIRY_WARNING_DAYS {
+                view! { <Badge color=Color::Yellow>Expiring soon</Badge> }
+            } else if info.not_before > Utc::now() {
+                view! { <Badge color=Color::Gray>Not yet valid</Badge> }
+            } else {
+                view! { <Badge color=Color::Green>Valid</Badge> }
+            };
+            let sans = if !info.sans.is_empty() {
+                info.sans
+            } else {
+                info.subject_name.into_iter().collect()
+            };
+
+            (
+                sans,
+                info.issuer_name.unwrap_or_default(),
+                info.not_after.format_date(),
+                days_left.max(0).to_string(),
+                status,
+            )
+        }
+        Err(reason) => (
+            entry.subjects,
+            String::new(),
+            reason,
+            String::new(),
+            view! { <Badge color=Color::Gray>Unknown</Badge> },
+        ),
+    };
+    let sans = sans
+        .into_iter()
+        .map(|san| view! { <span class="block">{san}</span> })
+        .collect_view();
+
+    view! {
+        <tr>
+            <ListItem>
+                <a
+                    class="text-sm font-medium text-blue-600 decoration-2 hover:underline"
+                    href=edit_url
+                >
+                    {entry.id}
+                </a>
+            </ListItem>
+            <ListTextItem>{source}</ListTextItem>
+            <ListTextItem>{sans}</ListTextItem>
+            <ListTextItem>{issuer}</ListTextItem>
+            <ListTextItem>{expires}</ListTextItem>
+            <ListTextItem>{days_left}</ListTextItem>
+            <ListItem>{status}</ListItem>
+        </tr>
+    }
+}
+
+async fn fetch_certificates(auth: &AuthToken) -> http::Result<Vec<CertificateEntry>> {
+    let mut entries = Vec::new();
+    for (prefix, source) in [
+        ("certificate", CertificateSource::Manual),
+        ("acme", CertificateSource::Acme),
+    ] {
+        let settings = HttpRequest::get("/api/settings/list")
+            .with_authorization(auth)
+            .with_parameter("prefix", prefix)
+            .send::<FetchSettings>()
+            .await?;
+        entries.extend(CertificateEntry::parse_all(settings, source));
+    }
+
+    Ok(entries)
+}
+
+/// Returns the id and a description of the certificates that could not be
+/// parsed or expire within the given number of days.
+pub(crate) async fn fetch_certificate_problems(
+    auth: &AuthToken,
+    days: i64,
+) -> http::Result<Vec<(String, String)>> {
+    let now = Utc::now();
+
+    Ok(fetch_certificates(auth)
+        .await?
+        .into_iter()
+        .filter_map(|entry| {
+            let problem = match &entry.info {
+                Err(err) => format!("Certificate {} is invalid: {err}", entry.id),
+                Ok(info) if info.not_after <= now => format!(
+                    "Certificate {} expired on {}",
+                    entry.id,
+                    info.not_after.format_date_time()
+                ),
+                Ok(info) if (info.not_after - now).num_days() < days => format!(
+                    "Certificate {} expires on {}",
+                    entry.id,
+                    info.not_after.format_date_time()
+                ),
+                Ok(_) => return None,
+            };
+            Some((entry.id, problem))
+        })
+        .collect())
+}
+
+impl CertificateEntry {
+    fn parse_all(settings: FetchSettings, source: CertificateSource) -> Vec<Self> {
+        let mut certs: AHashMap<String, (Option<String>, Vec<String>)> = AHashMap::new();
+        let subjects_key = match source {
+            CertificateSource::Manual => "subjects",
+            CertificateSource::Acme => "domains",
+        };
+
+        for (key, value) in settings.items {
+            let Some((id, property)) = key.split_once('.') else {
+                continue;
+            };
+            let entry = certs.entry(id.to_string()).or_default();
+            if property == "cert" {
+                entry.0 = Some(value);
+            } else if property == subjects_key
+                || property
+                    .strip_prefix(subjects_key)
+                    .map_or(false, |p| p.starts_with('.'))
+            {
+                entry.1.push(value);
+            }
+        }
+
+        certs
+            .into_iter()
+            .filter(|(_, (cert, subjects))| cert.is_some() || !subjects.is_empty())
+            .map(|(id, (cert, mut subjects))| {
+                subjects.sort();
+                let info = match cert {
+                    Some(cert) if cert.trim_start().starts_with("%{") => {
+                        Err("Loaded from an external source".to_string())
+                    }
+                    Some(cert) => CertificateInfo::parse(&cert)
+                        .ok_or_else(|| "Could not decode certificate".to_string()),
+                    None if source == CertificateSource::Acme => Err("Not issued yet".to_string()),
+                    None => Err("No certificate".to_string()),
+                };
+                CertificateEntry {
+                    id,
+                    source,
+                    subjects,
+                    info,
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,83 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::*;
+
+use crate::{
+    components::form::{FormItem, FormSection},
+    core::form::FormData,
+};
+
+pub const DISCLAIMER_SCHEMA: &str = "disclaimer";
+
+const SAMPLE_TEXT: &str =
+    "Hi Jane,\n\nPlease find the quarterly report attached.\n\nBest regards,\nJohn";
+const SAMPLE_HTML: &str =
+    "<p>Hi Jane,</p><p>Please find the quarterly report attached.</p><p>Best regards,<br>John</p>";
+
+/// Shows how the disclaimer being edited is added to the plain text and HTML
+/// parts of a sample message.
+#[component]
+pub fn DisclaimerPreview(data: RwSignal<FormData>) -> impl IntoView {
+    let preview = create_memo(move |_| {
+        data.with(|data| {
+            let is_header = data.value_as_str("position") == Some("header");
+            let text = data.value_as_str("text").unwrap_or_default().to_string();
+            let html = data
+                .value_as_str("html")
+                .filter(|html| !html.trim().is_empty())
+                .map(|html| html.to_string())
+                .unwrap_or_else(|| text_to_html(&text));
+
+            if is_header {
+                (
+                    format!("{text}\n\n{SAMPLE_TEXT}"),
+                    format!("<div>{html}</div><hr>{SAMPLE_HTML}"),
+                )
+            } else {
+                (
+                    format!("{SAMPLE_TEXT}\n\n-- \n{text}"),
+                    format!("{SAMPLE_HTML}<hr><div>{html}</div>"),
+                )
+            }
+        })
+    });
+
+    view! {
+        <FormSection title="Preview".to_string()>
+            <FormItem label="Plain text">
+                <pre class="p-3 text-sm whitespace-pre-wrap bg-gray-50 border border-gray-200 rounded-lg text-gray-800 dark:bg-slate-800 dark:border-gray-700 dark:text-gray-200">
+                    {move || preview.get().0}
+                </pre>
+            </FormItem>
+            <FormItem label="HTML">
+                // Scripts are never run in the preview
+                <iframe
+                    class="w-full h-64 bg-white border border-gray-200 rounded-lg dark:border-gray-700"
+                    sandbox=""
+                    srcdoc=move || preview.get().1
+                ></iframe>
+            </FormItem>
+        </FormSection>
+    }
+}
+
+/// Converts the plain text disclaimer to HTML, used when no HTML version has
+/// been provided.
+fn text_to_html(text: &str) -> String {
+    let mut html = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\n' => html.push_str("<br>"),
+            _ => html.push(ch),
+        }
+    }
+    html
+}
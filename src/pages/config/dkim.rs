@@ -0,0 +1,222 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::*;
+use leptos_router::use_navigate;
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{button::Button, FormSection},
+        messages::alert::{use_alerts, Alert},
+        Color,
+    },
+    core::{
+        dns::{doh_lookup, RCODE_NO_ERROR, RCODE_NX_DOMAIN},
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+    },
+    pages::directory::domains::DnsRecord,
+};
+
+pub const SIGNATURE_SCHEMA: &str = "signature";
+
+const CODE_CLASS: &str = "p-3 text-xs font-mono whitespace-pre-wrap break-all bg-gray-50 border border-gray-200 rounded-lg text-gray-800 dark:bg-slate-800 dark:border-gray-700 dark:text-gray-300";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum KeyStatus {
+    Match,
+    Mismatch(String),
+    Revoked,
+    Missing,
+    Failed(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct KeyCheck {
+    name: String,
+    expected: String,
+    status: KeyStatus,
+}
+
+/// Compares the DKIM public keys the server derives from the configured
+/// private keys with the ones published in DNS. Lookups are done over
+/// DNS-over-HTTPS so the result reflects what receiving servers see.
+#[component]
+pub fn DkimKeyCheck(
+    #[prop(into)] domain: Signal<String>,
+    #[prop(optional, into)] selector: MaybeSignal<Option<String>>,
+) -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let results = create_rw_signal(None::<Vec<KeyCheck>>);
+
+    let verify_action = create_action(move |_: &()| {
+        let auth = auth.get();
+        let domain = domain.get().trim().to_lowercase();
+        let selector = selector.get().filter(|selector| !selector.is_empty());
+
+        async move {
+            let records = match HttpRequest::get(("/api/domain", &domain))
+                .with_authorization(&auth)
+                .send::<Vec<DnsRecord>>()
+                .await
+            {
+                Ok(records) => records,
+                Err(http::Error::NotFound) => Vec::new(),
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                    return;
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                    return;
+                }
+            };
+
+            let mut checks = Vec::new();
+            for record in records.into_iter().filter(|record| {
+                record.typ == "TXT"
+                    && record.name.contains("._domainkey.")
+                    && selector.as_ref().map_or(true, |selector| {
+                        record.name.starts_with(&format!("{selector}._domainkey."))
+                    })
+            }) {
+                let status = match doh_lookup(&record.name, "TXT").await {
+                    Ok(response) if response.status == RCODE_NX_DOMAIN => KeyStatus::Missing,
+                    Ok(response) if response.status == RCODE_NO_ERROR => {
+                        let expected = dkim_tag(&record.content, "p").unwrap_or_default();
+                        let published = response
+                            .txt_records()
+                            .into_iter()
+                            .filter_map(|txt| dkim_tag(&txt, "p"))
+                            .collect::<Vec<_>>();
+                        if published.is_empty() {
+                            KeyStatus::Missing
+                        } else if published.contains(&expected) {
+                            KeyStatus::Match
+                        } else if published.iter().all(|key| key.is_empty()) {
+                            KeyStatus::Revoked
+                        } else {
+                            KeyStatus::Mismatch(published.join(", "))
+                        }
+                    }
+                    Ok(response) => {
+                        KeyStatus::Failed(format!("DNS error code {}", response.status))
+                    }
+                    Err(err) => KeyStatus::Failed(err),
+                };
+                checks.push(KeyCheck {
+                    name: record.name,
+                    expected: record.content,
+                    status,
+                });
+            }
+            results.set(Some(checks));
+        }
+    });
+
+    view! {
+        <FormSection title="Key verification".to_string()>
+            <div class="flex flex-col gap-4">
+                <p class="text-sm text-gray-600 dark:text-gray-400">
+                    Check that the public key published in DNS matches the configured private key.
+                    A mismatch causes DKIM verification to fail for all outgoing messages.
+                </p>
+                <div>
+                    <Button
+                        text=Signal::derive(move || {
+                            if verify_action.pending().get() {
+                                "Verifying...".to_string()
+                            } else {
+                                "Verify DNS records".to_string()
+                            }
+                        })
+
+                        color=Color::Gray
+                        disabled=Signal::derive(move || {
+                            verify_action.pending().get() || domain.get().trim().is_empty()
+                        })
+
+                        on_click=move |_| verify_action.dispatch(())
+                    />
+                </div>
+                {move || {
+                    results
+                        .get()
+                        .map(|checks| {
+                            if checks.is_empty() {
+                                view! {
+                                    <p class="text-sm text-gray-500">
+                                        No DKIM records were found for this domain. Save the signature before verifying it.
+                                    </p>
+                                }
+                                    .into_view()
+                            } else {
+                                checks.into_iter().map(|check| view! { <KeyCheckItem check/> }).collect_view()
+                            }
+                        })
+                }}
+
+            </div>
+        </FormSection>
+    }
+}
+
+#[component]
+fn KeyCheckItem(check: KeyCheck) -> impl IntoView {
+    let show_expected = matches!(
+        check.status,
+        KeyStatus::Mismatch(_) | KeyStatus::Revoked | KeyStatus::Missing
+    );
+    let (color, label, details) = match check.status {
+        KeyStatus::Match => (Color::Green, "Match", None),
+        KeyStatus::Mismatch(published) => (
+            Color::Red,
+            "Mismatch",
+            Some(format!(
+                "The published key does not match the configured private key: {published}"
+            )),
+        ),
+        KeyStatus::Revoked => (
+            Color::Red,
+            "Revoked",
+            Some("The published record has an empty public key.".to_string()),
+        ),
+        KeyStatus::Missing => (
+            Color::Yellow,
+            "Not published",
+            Some("No DKIM record was found at this name.".to_string()),
+        ),
+        KeyStatus::Failed(err) => (Color::Gray, "Lookup failed", Some(err)),
+    };
+
+    view! {
+        <div class="flex flex-col gap-2 p-4 border border-gray-200 rounded-lg dark:border-gray-700">
+            <div class="flex items-center gap-x-2">
+                <span class="text-sm font-mono text-gray-800 dark:text-gray-200">{check.name}</span>
+                <Badge color=color>{label}</Badge>
+            </div>
+            {details.map(|details| view! { <p class="text-sm text-gray-500 break-all">{details}</p> })}
+            {show_expected
+                .then(|| {
+                    view! {
+                        <p class="text-sm text-gray-600 dark:text-gray-400">Expected record:</p>
+                        <pre class=CODE_CLASS>{check.expected}</pre>
+                    }
+                })}
+
+        </div>
+    }
+}
+
+/// Returns the value of a tag in a DKIM key record with whitespace removed.
+fn dkim_tag(record: &str, tag: &str) -> Option<String> {
+    record.split(';').find_map(|part| {
+        let (name, value) = part.split_once('=')?;
+        (name.trim() == tag).then(|| value.chars().filter(|ch| !ch.is_whitespace()).collect())
+    })
+}
@@ -8,7 +8,7 @@ use std::sync::Arc;
 
 use ahash::AHashMap;
 use leptos::*;
-use leptos_router::{use_navigate, use_params_map};
+use leptos_router::{use_navigate, use_params_map, use_query_map};
 use serde::{Deserialize, Serialize};
 
 use crate::{
@@ -27,24 +27,46 @@ use crate::{
         icon::IconRefresh,
         messages::{
             alert::{use_alerts, Alert},
+            conflict::{conflict_modal, ConflictResolution},
             modal::{use_modals, Modal},
         },
         skeleton::Skeleton,
         Color,
     },
     core::{
-        form::{ExternalSources, FormData},
+        form::{ExternalSources, FormData, FormValue},
         http::{self, HttpRequest},
-        oauth::use_authorization,
+        oauth::{use_authorization, AuthToken},
+        permissions::Capability,
+        unsaved::use_unsaved_changes,
     },
     pages::{
-        config::{ReloadSettings, Schema, SchemaType, Schemas, Settings, Type, UpdateSettings},
+        config::{
+            acme::AcmeStatusPanel,
+            alerts::{AlertConditionBuilder, ALERT_SCHEMA},
+            disclaimer::{DisclaimerPreview, DISCLAIMER_SCHEMA},
+            dkim::{DkimKeyCheck, SIGNATURE_SCHEMA},
+            hook_test::{HookConnectionTest, MILTER_SCHEMA, MTA_HOOK_SCHEMA},
+            http_lookup::{HttpListViewer, HTTP_LOOKUP_SCHEMA},
+            listener::{find_bind_conflicts, ListenerReachabilityCheck, LISTENER_SCHEMA},
+            llm_console::{LlmPromptConsole, SPAM_LLM_SCHEMA},
+            metrics_export::{MetricsExportPreview, METRICS_SCHEMA},
+            oidc::{OidcSetupWizard, DIRECTORY_SCHEMA},
+            queue_sim::{QueueSimulator, QUEUE_SIMULATOR_SCHEMAS},
+            relay::{RelayHostTools, REMOTE_HOST_SCHEMA},
+            reload::ReloadReport,
+            report_preview::{ReportPreview, REPORT_PREVIEW_SCHEMAS},
+            rewrite::{AddressRewriteRules, SMTP_IN_MAIL_SCHEMA, SMTP_IN_RCPT_SCHEMA},
+            schema::{V_RECIPIENT, V_SENDER},
+            spam_rules::{SpamRuleBuilder, SPAM_RULE_SCHEMA},
+            ReloadSettings, Schema, SchemaType, Schemas, Settings, Type, UpdateSettings,
+        },
         List,
     },
 };
 
 #[derive(Clone, Serialize, Deserialize, Default)]
-struct FetchSettings {
+pub(crate) struct FetchSettings {
     pub items: Settings,
     pub total: u64,
 }
@@ -58,6 +80,11 @@ enum FetchResult {
     Create {
         external_sources: ExternalSources,
     },
+    Duplicate {
+        source: String,
+        settings: Settings,
+        external_sources: ExternalSources,
+    },
     NotFound,
 }
 
@@ -68,9 +95,11 @@ pub fn SettingsEdit() -> impl IntoView {
     let auth = use_authorization();
     let alert = use_alerts();
     let params = use_params_map();
+    let query = use_query_map();
     let modal = use_modals();
 
     let schemas = expect_context::<Arc<Schemas>>();
+    let schemas_ = schemas.clone();
     let current_schema = create_memo(move |_| {
         if let Some(schema) = params
             .get()
@@ -85,11 +114,19 @@ pub fn SettingsEdit() -> impl IntoView {
     });
 
     let fetch_settings = create_resource(
-        move || params.get().get("id").cloned().unwrap_or_default(),
-        move |name| {
+        move || {
+            (
+                params.get().get("id").cloned().unwrap_or_default(),
+                query.get().get("duplicate").cloned(),
+            )
+        },
+        move |(name, duplicate)| {
             let auth = auth.get_untracked();
             let schema = current_schema.get();
             let is_create = name.is_empty();
+            let duplicate = duplicate.filter(|source| {
+                is_create && !source.is_empty() && matches!(schema.typ, SchemaType::Record { .. })
+            });
 
             async move {
                 // Fetch external sources
@@ -125,95 +162,41 @@ pub fn SettingsEdit() -> impl IntoView {
                     }
                 }
 
-                // Fetch settings
-                match schema.typ {
-                    SchemaType::Record { prefix, .. } => {
-                        if !is_create {
-                            HttpRequest::get("/api/settings/list")
-                                .with_authorization(&auth)
-                                .with_parameter("prefix", format!("{prefix}.{name}"))
-                                .send::<FetchSettings>()
-                                .await
-                                .map(|mut list| {
-                                    if !list.items.is_empty() {
-                                        list.items.insert("_id".to_string(), name.to_string());
-                                        FetchResult::Update {
-                                            settings: list.items,
-                                            external_sources,
-                                        }
-                                    } else {
-                                        FetchResult::NotFound
-                                    }
-                                })
-                        } else {
-                            Ok(FetchResult::Create { external_sources })
-                        }
-                    }
-                    SchemaType::Entry { prefix } => {
-                        if !is_create {
-                            HttpRequest::get("/api/settings/keys")
-                                .with_authorization(&auth)
-                                .with_parameter("keys", format!("{prefix}.{name}"))
-                                .send::<AHashMap<String, Option<String>>>()
-                                .await
-                                .map(|list| {
-                                    if let Some(value) = list.into_values().next().flatten() {
-                                        let mut settings = Settings::new();
-                                        settings.insert("_id".to_string(), name.to_string());
-                                        settings.insert("_value".to_string(), value);
-                                        FetchResult::Update {
-                                            settings,
-                                            external_sources,
-                                        }
-                                    } else {
-                                        FetchResult::NotFound
-                                    }
-                                })
-                        } else {
-                            Ok(FetchResult::Create { external_sources })
-                        }
-                    }
-                    SchemaType::List => {
-                        let mut keys = Vec::new();
-                        let mut prefixes = Vec::new();
-
-                        for field in schema.fields.values() {
-                            if field.is_multivalue() {
-                                prefixes.push(field.id);
-                                keys.push(field.id);
-                            } else {
-                                keys.push(field.id);
-                            }
-                        }
-
-                        HttpRequest::get("/api/settings/keys")
-                            .with_authorization(&auth)
-                            .with_parameter("keys", keys.join(","))
-                            .with_parameter("prefixes", prefixes.join(","))
-                            .send::<Settings>()
-                            .await
-                            .map(|mut list| {
-                                let mut settings = Settings::new();
-                                for (name, value) in list.drain() {
-                                    settings.insert(name, value);
-                                }
+                // Fetch the record to duplicate
+                if let Some(source) = duplicate {
+                    return Ok(match fetch_record(&auth, &schema, &source).await? {
+                        Some(settings) => FetchResult::Duplicate {
+                            source,
+                            settings,
+                            external_sources,
+                        },
+                        None => FetchResult::NotFound,
+                    });
+                }
 
-                                if !settings.is_empty() {
-                                    FetchResult::Update {
-                                        settings,
-                                        external_sources,
-                                    }
-                                } else {
-                                    FetchResult::Create { external_sources }
-                                }
-                            })
-                    }
+                // Fetch settings
+                if is_create && schema.typ != SchemaType::List {
+                    return Ok(FetchResult::Create { external_sources });
                 }
+                fetch_record(&auth, &schema, &name)
+                    .await
+                    .map(|settings| match settings {
+                        Some(settings) => FetchResult::Update {
+                            settings,
+                            external_sources,
+                        },
+                        None if schema.typ == SchemaType::List => {
+                            FetchResult::Create { external_sources }
+                        }
+                        None => FetchResult::NotFound,
+                    })
             }
         },
     );
     let (pending, set_pending) = create_signal(false);
     let data = FormData::default().into_signal();
+    let unsaved = use_unsaved_changes();
+    unsaved.track(data);
 
     let save_changes = create_action(
         move |(changes, reload): &(Arc<Vec<UpdateSettings>>, bool)| {
@@ -221,18 +204,57 @@ pub fn SettingsEdit() -> impl IntoView {
             let reload = *reload;
             let auth = auth.get();
             let schema = current_schema.get();
+            let schemas = schemas_.clone();
+            let listener = (schema.id == LISTENER_SCHEMA).then(|| {
+                data.with_untracked(|data| {
+                    (
+                        data.value_as_str("_id").unwrap_or_default().to_string(),
+                        data.array_value("bind")
+                            .map(|bind| bind.to_string())
+                            .collect::<Vec<_>>(),
+                    )
+                })
+            });
 
             async move {
                 set_pending.set(true);
+
+                // Make sure no other listener is bound to the same address
+                if let Some((listener_id, binds)) = &listener {
+                    match find_bind_conflicts(&auth, listener_id, binds).await {
+                        Ok(conflicts) if !conflicts.is_empty() => {
+                            set_pending.set(false);
+                            data.update(|data| {
+                                data.new_error("bind", conflicts.join("; "));
+                            });
+                            alert.set(
+                                Alert::error("Bind address conflict").with_details_list(conflicts),
+                            );
+                            return;
+                        }
+                        Ok(_) => {}
+                        Err(http::Error::Unauthorized) => {
+                            use_navigate()("/login", Default::default());
+                            return;
+                        }
+                        Err(err) => {
+                            set_pending.set(false);
+                            alert.set(Alert::from(err));
+                            return;
+                        }
+                    }
+                }
+
                 match HttpRequest::post("/api/settings")
                     .with_authorization(&auth)
-                    .with_body(changes)
+                    .with_body(changes.clone())
                     .unwrap()
                     .send::<Option<String>>()
                     .await
                     .map(|_| ())
                 {
                     Ok(_) => {
+                        data.update(|data| data.mark_clean());
                         if reload {
                             match HttpRequest::get(format!(
                                 "/api/reload/{}",
@@ -244,7 +266,18 @@ pub fn SettingsEdit() -> impl IntoView {
                             {
                                 Ok(result) => {
                                     set_pending.set(false);
-                                    if result.errors.is_empty() {
+                                    let report = ReloadReport::new(&result, &changes, &schemas);
+                                    if report.needs_attention() {
+                                        let list_path =
+                                            schema.list_path().filter(|_| !report.has_errors());
+                                        modal.set(report.into_modal(modal).with_callback(
+                                            move || {
+                                                if let Some(url) = &list_path {
+                                                    use_navigate()(url, Default::default());
+                                                }
+                                            },
+                                        ));
+                                    } else if result.errors.is_empty() {
                                         match schema.list_path() {
                                             Some(url) => use_navigate()(&url, Default::default()),
                                             None => {
@@ -306,12 +339,99 @@ pub fn SettingsEdit() -> impl IntoView {
         },
     );
 
+    // Make sure nobody else modified the record since it was loaded
+    let check_and_save = create_action(
+        move |(changes, reload): &(Arc<Vec<UpdateSettings>>, bool)| {
+            let changes = changes.clone();
+            let reload = *reload;
+            let auth = auth.get();
+            let schema = current_schema.get();
+            let name = params.get().get("id").cloned().unwrap_or_default();
+            let is_update = data.with_untracked(|data| data.is_update);
+
+            async move {
+                if !is_update {
+                    save_changes.dispatch((changes, reload));
+                    return;
+                }
+
+                set_pending.set(true);
+                let latest = match fetch_record(&auth, &schema, &name).await {
+                    Ok(settings) => FormData::from_settings(schema.clone(), settings),
+                    Err(http::Error::Unauthorized) => {
+                        use_navigate()("/login", Default::default());
+                        return;
+                    }
+                    Err(err) => {
+                        set_pending.set(false);
+                        alert.set(Alert::from(err));
+                        return;
+                    }
+                };
+                if !data.with_untracked(|data| data.is_outdated(&latest)) {
+                    save_changes.dispatch((changes, reload));
+                    return;
+                }
+
+                set_pending.set(false);
+                let what = if name.is_empty() {
+                    "these settings".to_string()
+                } else {
+                    format!("the {} '{name}'", schema.name_singular)
+                };
+                let latest = store_value(latest);
+                modal.set(conflict_modal(&what, move |resolution| match resolution {
+                    ConflictResolution::Merge => {
+                        data.update(|data| data.rebase(latest.get_value()));
+                        alert.set(
+                            Alert::warning("Changes merged")
+                                .with_details("Review the merged settings and save them again."),
+                        );
+                    }
+                    ConflictResolution::Overwrite => {
+                        save_changes.dispatch((changes.clone(), reload));
+                    }
+                }));
+            }
+        },
+    );
+
     view! {
         <Form
             title=Signal::derive(move || current_schema.get().form.title.to_string())
             subtitle=Signal::derive(move || current_schema.get().form.subtitle.to_string())
         >
 
+            {move || {
+                let schema = current_schema.get();
+                params
+                    .get()
+                    .get("id")
+                    .filter(|id| schema.id == "acme" && !id.is_empty())
+                    .map(|id| view! { <AcmeStatusPanel id=id.clone()/> })
+            }}
+
+            {move || {
+                let schema = current_schema.get();
+                params
+                    .get()
+                    .get("id")
+                    .filter(|id| schema.id == HTTP_LOOKUP_SCHEMA && !id.is_empty())
+                    .map(|id| view! { <HttpListViewer id=id.clone()/> })
+            }}
+
+            {move || {
+                let schema = current_schema.get();
+                let schema_id = [MILTER_SCHEMA, MTA_HOOK_SCHEMA]
+                    .into_iter()
+                    .find(|id| *id == schema.id)?;
+                params
+                    .get()
+                    .get("id")
+                    .filter(|id| !id.is_empty())
+                    .map(|id| view! { <HookConnectionTest schema=schema_id id=id.clone()/> })
+            }}
+
             <Transition fallback=Skeleton set_pending>
 
                 {move || match fetch_settings.get() {
@@ -330,29 +450,43 @@ pub fn SettingsEdit() -> impl IntoView {
                         Some(view! { <div></div> }.into_view())
                     }
                     Some(Ok(result)) => {
-                        let (is_create, settings, external_sources) = match result {
+                        let (is_create, settings, source, external_sources) = match result {
                             FetchResult::Update { settings, external_sources } => {
-                                (false, Some(settings), external_sources)
+                                (false, Some(settings), None, external_sources)
                             }
                             FetchResult::Create { external_sources } => {
-                                (true, None, external_sources)
+                                (true, None, None, external_sources)
+                            }
+                            FetchResult::Duplicate { source, settings, external_sources } => {
+                                (true, Some(settings), Some(source), external_sources)
                             }
                             FetchResult::NotFound => unreachable!(),
                         };
                         let schema = current_schema.get();
                         let sections = schema.form.sections.iter().cloned();
-                        data.set(
-                            FormData::from_settings(schema.clone(), settings)
-                                .with_external_sources(external_sources),
-                        );
+                        let mut form = FormData::from_settings(schema.clone(), settings)
+                            .with_external_sources(external_sources);
+                        form.mark_clean();
+                        if let Some(source) = source {
+                            form.is_update = false;
+                            form.set("_id", format!("{source}-copy"));
+                        }
+                        data.set(form);
+                        let schema_id = schema.id;
                         Some(
                             sections
-                                .map(|section| {
+                                .enumerate()
+                                .map(|(idx, section)| {
                                     let title = section.title.map(|s| s.to_string());
+                                    let docs = section.docs;
                                     let section_ = section.clone();
                                     let hide_section = create_memo(move |_| {
                                         !section_.display(&data.get())
                                     });
+                                    let section_ = section.clone();
+                                    let section_errors = create_memo(move |_| {
+                                        data.with(|data| data.section_errors(&section_))
+                                    });
                                     let components = section
                                         .fields
                                         .iter()
@@ -361,6 +495,10 @@ pub fn SettingsEdit() -> impl IntoView {
                                             let is_disabled = field.readonly && !is_create;
                                             let field_label = field.label_form;
                                             let help = field.help;
+                                            let docs = field.docs.or_else(|| {
+                                                matches!(field.typ_, Type::Expression)
+                                                    .then_some("configuration/expressions/overview")
+                                            });
                                             let field_ = field.clone();
                                             let hide_label = create_memo(move |_| {
                                                 !field_.display(&data.get())
@@ -370,6 +508,7 @@ pub fn SettingsEdit() -> impl IntoView {
                                                 !field_.is_required(&data.get())
                                             });
                                             let is_switch = matches!(field.typ_, Type::Boolean);
+                                            let field_id = field.id;
                                             let component = match field.typ_ {
                                                 Type::Input => {
                                                     view! {
@@ -480,14 +619,25 @@ pub fn SettingsEdit() -> impl IntoView {
                                                         hide=hide_label
                                                         is_optional=is_optional
                                                         tooltip=help.unwrap_or_default()
+                                                        docs=docs.unwrap_or_default()
                                                     >
                                                         {component}
+                                                        <DefaultValueHint
+                                                            field_id=field_id
+                                                            data=data
+                                                            disabled=is_disabled
+                                                        />
                                                     </FormItem>
                                                 }
                                             } else {
                                                 view! {
                                                     <FormItem label="" hide=hide_label is_optional=is_optional>
                                                         {component}
+                                                        <DefaultValueHint
+                                                            field_id=field_id
+                                                            data=data
+                                                            disabled=is_disabled
+                                                        />
                                                     </FormItem>
                                                 }
                                             }
@@ -496,10 +646,75 @@ pub fn SettingsEdit() -> impl IntoView {
                                     view! {
                                         <FormSection
                                             title=title.unwrap_or_default()
+                                            docs=docs.unwrap_or_default()
                                             hide=hide_section
                                         >
                                             {components}
+                                            <Show when=move || {
+                                                section_errors.with(|errors| !errors.is_empty())
+                                            }>
+                                                <div class="sm:col-span-12 p-3 text-sm bg-red-50 border border-red-200 rounded-lg text-red-800 dark:bg-red-800/10 dark:border-red-900 dark:text-red-500">
+                                                    <ul class="list-disc list-inside space-y-1">
+                                                        {move || {
+                                                            section_errors
+                                                                .get()
+                                                                .into_iter()
+                                                                .map(|error| view! { <li>{error}</li> })
+                                                                .collect_view()
+                                                        }}
+
+                                                    </ul>
+                                                </div>
+                                            </Show>
                                         </FormSection>
+
+                                        {(idx == 0 && schema_id == ALERT_SCHEMA)
+                                            .then(|| view! { <AlertConditionBuilder data=data/> })}
+                                        {(idx == 0 && schema_id == SPAM_RULE_SCHEMA)
+                                            .then(|| view! { <SpamRuleBuilder data=data/> })}
+                                        {(idx == 0 && schema_id == SPAM_LLM_SCHEMA)
+                                            .then(|| view! { <LlmPromptConsole data=data/> })}
+                                        {(idx == 0 && schema_id == SMTP_IN_MAIL_SCHEMA)
+                                            .then(|| {
+                                                view! {
+                                                    <AddressRewriteRules
+                                                        data=data
+                                                        field="session.mail.rewrite"
+                                                        variable=V_SENDER
+                                                    />
+                                                }
+                                            })}
+                                        {(idx == 1 && schema_id == SMTP_IN_RCPT_SCHEMA)
+                                            .then(|| {
+                                                view! {
+                                                    <AddressRewriteRules
+                                                        data=data
+                                                        field="session.rcpt.rewrite"
+                                                        variable=V_RECIPIENT
+                                                    />
+                                                }
+                                            })}
+                                        {(idx == 0 && schema_id == LISTENER_SCHEMA && !is_create)
+                                            .then(|| view! { <ListenerReachabilityCheck data=data/> })}
+                                        {(idx == 0 && schema_id == DIRECTORY_SCHEMA)
+                                            .then(|| view! { <OidcSetupWizard data=data/> })}
+                                        {(idx == 2 && schema_id == REMOTE_HOST_SCHEMA)
+                                            .then(|| view! { <RelayHostTools data=data/> })}
+                                        {(idx == 1 && schema_id == DISCLAIMER_SCHEMA)
+                                            .then(|| view! { <DisclaimerPreview data=data/> })}
+                                        {(idx == 1 && schema_id == SIGNATURE_SCHEMA)
+                                            .then(|| {
+                                                view! {
+                                                    <DkimKeyCheck
+                                                        domain=Signal::derive(move || {
+                                                            data.get().value::<String>("domain").unwrap_or_default()
+                                                        })
+                                                        selector=Signal::derive(move || {
+                                                            data.get().value::<String>("selector")
+                                                        })
+                                                    />
+                                                }
+                                            })}
                                     }
                                         .into_view()
                                 })
@@ -510,15 +725,31 @@ pub fn SettingsEdit() -> impl IntoView {
 
             </Transition>
 
+            {move || {
+                QUEUE_SIMULATOR_SCHEMAS
+                    .contains(&current_schema.get().id)
+                    .then(|| view! { <QueueSimulator/> })
+            }}
+
+            {move || {
+                let schema_id = current_schema.get().id;
+                REPORT_PREVIEW_SCHEMAS
+                    .iter()
+                    .find(|(id, _)| *id == schema_id)
+                    .map(|(_, report_type)| view! { <ReportPreview report_type=*report_type/> })
+            }}
+
+            {move || {
+                (current_schema.get().id == METRICS_SCHEMA)
+                    .then(|| view! { <MetricsExportPreview data=data/> })
+            }}
+
             <FormButtonBar>
                 <Button
                     text="Cancel"
                     color=Color::Gray
                     on_click=move |_| {
-                        use_navigate()(
-                            &current_schema.get().list_path_or_default(),
-                            Default::default(),
-                        );
+                        unsaved.navigate(&current_schema.get().list_path_or_default());
                     }
                 />
 
@@ -528,12 +759,13 @@ pub fn SettingsEdit() -> impl IntoView {
                     on_click=Callback::new(move |_| {
                         data.update(|data| {
                             if data.validate_form() {
-                                save_changes.dispatch((Arc::new(data.build_update()), true));
+                                check_and_save.dispatch((Arc::new(data.build_update()), true));
                             }
                         });
                     })
 
                     disabled=pending
+                    capability=Capability::SettingsApply
                 >
 
                     <IconRefresh/>
@@ -545,12 +777,13 @@ pub fn SettingsEdit() -> impl IntoView {
                     on_click=Callback::new(move |_| {
                         data.update(|data| {
                             if data.validate_form() {
-                                save_changes.dispatch((Arc::new(data.build_update()), false));
+                                check_and_save.dispatch((Arc::new(data.build_update()), false));
                             }
                         });
                     })
 
                     disabled=pending
+                    capability=Capability::SettingsUpdate
                 />
             </FormButtonBar>
 
@@ -558,6 +791,115 @@ pub fn SettingsEdit() -> impl IntoView {
     }
 }
 
+/// Fetches the stored settings of a record, `None` when it does not exist.
+async fn fetch_record(
+    auth: &AuthToken,
+    schema: &Schema,
+    name: &str,
+) -> http::Result<Option<Settings>> {
+    match schema.typ {
+        SchemaType::Record { prefix, .. } => HttpRequest::get("/api/settings/list")
+            .with_authorization(auth)
+            .with_parameter("prefix", format!("{prefix}.{name}"))
+            .send::<FetchSettings>()
+            .await
+            .map(|mut list| {
+                (!list.items.is_empty()).then(|| {
+                    list.items.insert("_id".to_string(), name.to_string());
+                    list.items
+                })
+            }),
+        SchemaType::Entry { prefix } => HttpRequest::get("/api/settings/keys")
+            .with_authorization(auth)
+            .with_parameter("keys", format!("{prefix}.{name}"))
+            .send::<AHashMap<String, Option<String>>>()
+            .await
+            .map(|list| {
+                list.into_values().next().flatten().map(|value| {
+                    let mut settings = Settings::new();
+                    settings.insert("_id".to_string(), name.to_string());
+                    settings.insert("_value".to_string(), value);
+                    settings
+                })
+            }),
+        SchemaType::List => {
+            let mut keys = Vec::new();
+            let mut prefixes = Vec::new();
+
+            for field in schema.fields.values() {
+                if field.is_multivalue() {
+                    prefixes.push(field.id);
+                    keys.push(field.id);
+                } else {
+                    keys.push(field.id);
+                }
+            }
+
+            HttpRequest::get("/api/settings/keys")
+                .with_authorization(auth)
+                .with_parameter("keys", keys.join(","))
+                .with_parameter("prefixes", prefixes.join(","))
+                .send::<Settings>()
+                .await
+                .map(|mut list| {
+                    let mut settings = Settings::new();
+                    for (name, value) in list.drain() {
+                        settings.insert(name, value);
+                    }
+
+                    (!settings.is_empty()).then_some(settings)
+                })
+        }
+    }
+}
+
+#[component]
+fn DefaultValueHint(
+    field_id: &'static str,
+    data: RwSignal<FormData>,
+    disabled: bool,
+) -> impl IntoView {
+    let hint = create_memo(move |_| {
+        data.with(|data| {
+            let default = data.default_value(field_id)?;
+            let label = match &default {
+                FormValue::Value(value) => data
+                    .schema
+                    .fields
+                    .get(field_id)
+                    .map(|field| field.typ_.label(value).to_string())
+                    .unwrap_or_else(|| value.clone()),
+                default => default.to_display_string(),
+            };
+            Some((label, data.values.get(field_id) != Some(&default)))
+        })
+    });
+
+    move || {
+        hint.get()
+            .filter(|(label, _)| !disabled && !label.is_empty())
+            .map(|(label, is_modified)| {
+                view! {
+                    <p class="mt-1 text-xs text-gray-400 dark:text-gray-500">
+                        {format!("Default: {label}")}
+                        <Show when=move || is_modified>
+                            <button
+                                type="button"
+                                class="ms-2 text-blue-600 hover:underline dark:text-blue-500"
+                                on:click=move |_| {
+                                    data.update(|data| data.reset_to_default(field_id));
+                                }
+                            >
+
+                                Reset to default
+                            </button>
+                        </Show>
+                    </p>
+                }
+            })
+    }
+}
+
 impl Schema {
     fn list_path(&self) -> Option<String> {
         if !matches!(self.typ, SchemaType::List) {
@@ -0,0 +1,186 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{button::Button, FormItem, FormSection},
+        icon::IconArrowRightCircle,
+        messages::alert::{use_alerts, Alert},
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+    },
+};
+
+pub const MILTER_SCHEMA: &str = "milter";
+pub const MTA_HOOK_SCHEMA: &str = "mta-hooks";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct ConnectionTest {
+    #[serde(default)]
+    success: bool,
+    #[serde(default)]
+    protocol_version: Option<String>,
+    #[serde(default)]
+    latency_ms: Option<u64>,
+    #[serde(default)]
+    tls: Option<TlsDetails>,
+    #[serde(default)]
+    response: Option<String>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct TlsDetails {
+    #[serde(default)]
+    version: Option<String>,
+    #[serde(default)]
+    cipher: Option<String>,
+    #[serde(default)]
+    certificate: Option<String>,
+}
+
+/// Asks the server to connect to a configured milter or MTA hook. Milters
+/// are tested with a protocol handshake, hooks with a synthetic request.
+#[component]
+pub fn HookConnectionTest(schema: &'static str, #[prop(into)] id: String) -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let id = store_value(id);
+    let result = create_rw_signal(None::<ConnectionTest>);
+    let kind = if schema == MILTER_SCHEMA {
+        "milter"
+    } else {
+        "hook"
+    };
+
+    let test_action = create_action(move |_: &()| {
+        let auth = auth.get();
+        let id = id.get_value();
+
+        async move {
+            match HttpRequest::post(format!("/api/smtp/{kind}/{id}/test"))
+                .with_authorization(&auth)
+                .send::<ConnectionTest>()
+                .await
+            {
+                Ok(test) => {
+                    result.set(Some(test));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(http::Error::NotFound) => {
+                    alert.set(Alert::warning(format!(
+                        "This server does not support testing {kind} connections."
+                    )));
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    view! {
+        <FormSection title="Connection test".to_string()>
+            <FormItem label="">
+                <Button
+                    text="Test connection"
+                    color=Color::Gray
+                    on_click=Callback::new(move |_| {
+                        test_action.dispatch(());
+                    })
+
+                    disabled=test_action.pending()
+                >
+                    <IconArrowRightCircle/>
+                </Button>
+            </FormItem>
+            {move || {
+                result
+                    .get()
+                    .map(|test| {
+                        let (color, label) = if test.success {
+                            (Color::Green, "Connected")
+                        } else {
+                            (Color::Red, "Failed")
+                        };
+                        let tls = test
+                            .tls
+                            .map(|tls| {
+                                [tls.version, tls.cipher]
+                                    .into_iter()
+                                    .flatten()
+                                    .collect::<Vec<_>>()
+                                    .join(", ")
+                                    + &tls
+                                        .certificate
+                                        .map(|cert| format!(" ({cert})"))
+                                        .unwrap_or_default()
+                            })
+                            .filter(|tls| !tls.is_empty())
+                            .unwrap_or_else(|| "Not used".to_string());
+                        view! {
+                            <FormItem label="Status">
+                                <div class="pt-2.5 text-sm text-gray-600 dark:text-gray-400">
+                                    <Badge color=color>{label}</Badge>
+                                    {test
+                                        .error
+                                        .map(|error| {
+                                            view! {
+                                                <p class="mt-1 text-red-600 dark:text-red-500">
+                                                    {error}
+                                                </p>
+                                            }
+                                        })}
+
+                                </div>
+                            </FormItem>
+                            <FormItem label="Latency">
+                                <div class="pt-2.5 text-sm text-gray-600 dark:text-gray-400">
+                                    {test
+                                        .latency_ms
+                                        .map(|ms| format!("{ms} ms"))
+                                        .unwrap_or_else(|| "-".to_string())}
+                                </div>
+                            </FormItem>
+                            <FormItem label="Protocol">
+                                <div class="pt-2.5 text-sm text-gray-600 dark:text-gray-400">
+                                    {test.protocol_version.unwrap_or_else(|| "-".to_string())}
+                                </div>
+                            </FormItem>
+                            <FormItem label="TLS">
+                                <div class="pt-2.5 text-sm text-gray-600 dark:text-gray-400">
+                                    {tls}
+                                </div>
+                            </FormItem>
+                            {test
+                                .response
+                                .map(|response| {
+                                    view! {
+                                        <FormItem label="Response">
+                                            <pre class="p-3 text-xs whitespace-pre-wrap bg-gray-50 border border-gray-200 rounded-lg text-gray-800 dark:bg-slate-800 dark:border-gray-700 dark:text-gray-200">
+                                                {response}
+                                            </pre>
+                                        </FormItem>
+                                    }
+                                })}
+                        }
+                    })
+            }}
+
+        </FormSection>
+    }
+}
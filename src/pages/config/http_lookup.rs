@@ -0,0 +1,278 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use chrono::{DateTime, Utc};
+use chrono_humanize::HumanTime;
+use humansize::{format_size, DECIMAL};
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{button::Button, FormItem, FormSection},
+        icon::IconRefresh,
+        messages::alert::{use_alerts, Alert},
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        permissions::Capability,
+    },
+    pages::FormatDateTime,
+};
+
+pub const HTTP_LOOKUP_SCHEMA: &str = "http-lookup";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct HttpListStatus {
+    #[serde(default)]
+    entries: u64,
+    #[serde(default)]
+    size: u64,
+    #[serde(default)]
+    last_refresh: Option<DateTime<Utc>>,
+    #[serde(default)]
+    next_refresh: Option<DateTime<Utc>>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct HttpListEntry {
+    found: bool,
+    #[serde(default)]
+    value: Option<String>,
+}
+
+/// Shows what the server actually loaded from a remote list, which is
+/// otherwise only visible in the logs.
+#[component]
+pub fn HttpListViewer(#[prop(into)] id: String) -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let id = store_value(id);
+    let query = create_rw_signal(String::new());
+    let lookup_result = create_rw_signal(None::<(String, HttpListEntry)>);
+
+    let status = create_resource(
+        move || (),
+        move |_| {
+            let auth = auth.get_untracked();
+            let id = id.get_value();
+
+            async move {
+                match HttpRequest::get(("/api/lookup/http", &id))
+                    .with_authorization(&auth)
+                    .send::<HttpListStatus>()
+                    .await
+                {
+                    Ok(status) => Ok(Some(status)),
+                    // Not loaded yet or not supported by the server
+                    Err(http::Error::NotFound) => Ok(None),
+                    Err(err) => Err(err),
+                }
+            }
+        },
+    );
+
+    let lookup_action = create_action(move |key: &String| {
+        let auth = auth.get();
+        let id = id.get_value();
+        let key = key.clone();
+
+        async move {
+            match HttpRequest::get(format!("/api/lookup/http/{id}/entry"))
+                .with_authorization(&auth)
+                .with_parameter("key", &key)
+                .send::<HttpListEntry>()
+                .await
+            {
+                Ok(entry) => {
+                    lookup_result.set(Some((key, entry)));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    let refresh_action = create_action(move |_: &()| {
+        let auth = auth.get();
+        let id = id.get_value();
+
+        async move {
+            match HttpRequest::post(format!("/api/lookup/http/{id}/refresh"))
+                .with_authorization(&auth)
+                .send::<()>()
+                .await
+            {
+                Ok(_) => {
+                    alert.set(Alert::success(format!("Reloaded list {id}.")));
+                    lookup_result.set(None);
+                    status.refetch();
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    view! {
+        <FormSection title="List contents".to_string()>
+            <Transition>
+                {move || match status.get() {
+                    None => None,
+                    Some(Err(http::Error::Unauthorized)) => {
+                        use_navigate()("/login", Default::default());
+                        None
+                    }
+                    Some(Err(err)) => {
+                        alert.set(Alert::from(err));
+                        None
+                    }
+                    Some(Ok(None)) => {
+                        Some(
+                            view! {
+                                <FormItem label="Status">
+                                    <div class="pt-2.5 text-sm text-gray-600 dark:text-gray-400">
+                                        <Badge color=Color::Gray>Not loaded</Badge>
+                                    </div>
+                                </FormItem>
+                            }
+                                .into_view(),
+                        )
+                    }
+                    Some(Ok(Some(status))) => {
+                        let (color, label) = if status.error.is_some() {
+                            (Color::Red, "Failed")
+                        } else if status.last_refresh.is_none() {
+                            (Color::Yellow, "Pending")
+                        } else {
+                            (Color::Green, "Loaded")
+                        };
+                        let format_date = |date: Option<DateTime<Utc>>, default: &str| {
+                            date.map(|date| {
+                                    format!("{} ({})", date.format_date_time(), HumanTime::from(date))
+                                })
+                                .unwrap_or_else(|| default.to_string())
+                        };
+                        Some(
+                            view! {
+                                <FormItem label="Status">
+                                    <div class="pt-2.5 text-sm text-gray-600 dark:text-gray-400">
+                                        <Badge color=color>{label}</Badge>
+                                        {status
+                                            .error
+                                            .map(|error| {
+                                                view! {
+                                                    <p class="mt-1 text-red-600 dark:text-red-500">
+                                                        {error}
+                                                    </p>
+                                                }
+                                            })}
+
+                                    </div>
+                                </FormItem>
+                                <FormItem label="Entries">
+                                    <div class="pt-2.5 text-sm text-gray-600 dark:text-gray-400">
+                                        {format!(
+                                            "{} entries ({})",
+                                            status.entries,
+                                            format_size(status.size, DECIMAL),
+                                        )}
+                                    </div>
+                                </FormItem>
+                                <FormItem label="Last refresh">
+                                    <div class="pt-2.5 text-sm text-gray-600 dark:text-gray-400">
+                                        {format_date(status.last_refresh, "Never")}
+                                    </div>
+                                </FormItem>
+                                <FormItem label="Next refresh">
+                                    <div class="pt-2.5 text-sm text-gray-600 dark:text-gray-400">
+                                        {format_date(status.next_refresh, "Not scheduled")}
+                                    </div>
+                                </FormItem>
+                            }
+                                .into_view(),
+                        )
+                    }
+                }}
+
+            </Transition>
+            <FormItem label="Search">
+                <div class="flex flex-col gap-2">
+                    <div class="flex gap-x-2">
+                        <input
+                            type="text"
+                            class="py-2 px-3 pe-9 block w-full border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                            placeholder="Entry to look up"
+                            prop:value=move || query.get()
+                            on:input=move |ev| query.set(event_target_value(&ev))
+                        />
+                        <Button
+                            text="Look up"
+                            color=Color::Gray
+                            on_click=Callback::new(move |_| {
+                                let key = query.get().trim().to_string();
+                                if !key.is_empty() {
+                                    lookup_action.dispatch(key);
+                                }
+                            })
+
+                            disabled=lookup_action.pending()
+                        />
+                    </div>
+                    {move || {
+                        lookup_result
+                            .get()
+                            .map(|(key, entry)| {
+                                let (color, label) = if entry.found {
+                                    (Color::Green, "Listed")
+                                } else {
+                                    (Color::Gray, "Not listed")
+                                };
+                                view! {
+                                    <div class="flex items-center gap-x-2 text-sm text-gray-600 dark:text-gray-400">
+                                        <Badge color=color>{label}</Badge>
+                                        <span class="font-mono">{key}</span>
+                                        {entry
+                                            .value
+                                            .map(|value| view! { <span>{format!("= {value}")}</span> })}
+                                    </div>
+                                }
+                            })
+                    }}
+
+                </div>
+            </FormItem>
+            <FormItem label="">
+                <Button
+                    text="Refresh now"
+                    color=Color::Gray
+                    on_click=Callback::new(move |_| {
+                        refresh_action.dispatch(());
+                    })
+
+                    disabled=refresh_action.pending()
+                    capability=Capability::LookupRefresh
+                >
+                    <IconRefresh/>
+                </Button>
+            </FormItem>
+        </FormSection>
+    }
+}
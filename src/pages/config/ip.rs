@@ -0,0 +1,570 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::{
+    fmt::Display,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+    sync::Arc,
+};
+
+use ahash::AHashSet;
+use chrono::{DateTime, Utc};
+use chrono_humanize::HumanTime;
+use leptos::*;
+use leptos_router::{use_navigate, use_params_map};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        form::{
+            button::Button,
+            input::{InputText, TextArea},
+            Form, FormButtonBar, FormElement, FormItem, FormSection,
+        },
+        messages::alert::{use_alerts, Alert},
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        permissions::Capability,
+        schema::{Builder, Schemas, Transformer, Type, Validator},
+    },
+    pages::{
+        config::{edit::FetchSettings, Schema, UpdateSettings},
+        maybe_plural, FormatDateTime,
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct IpNetwork {
+    pub addr: IpAddr,
+    pub mask: u8,
+}
+
+#[component]
+pub fn IpTools() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let params = use_params_map();
+    let schemas = expect_context::<Arc<Schemas>>();
+    let current_schema =
+        create_memo(
+            move |_| match params.get().get("object").map(|id| id.as_str()) {
+                Some(id @ ("blocked-ip" | "allowed-ip")) => schemas.get(id),
+                _ => {
+                    use_navigate()("/404", Default::default());
+                    Arc::new(Schema::default())
+                }
+            },
+        );
+
+    let (pending, set_pending) = create_signal(false);
+    let data = expect_context::<Arc<Schemas>>()
+        .build_form("ip-tools")
+        .into_signal();
+
+    let entries = create_resource(
+        move || current_schema.get(),
+        move |schema| {
+            let auth = auth.get_untracked();
+
+            async move {
+                if schema.id.is_empty() {
+                    return Ok(Vec::new());
+                }
+
+                HttpRequest::get("/api/settings/list")
+                    .with_authorization(&auth)
+                    .with_parameter("prefix", schema.unwrap_prefix())
+                    .send::<FetchSettings>()
+                    .await
+                    .map(|list| {
+                        list.items
+                            .into_iter()
+                            .filter_map(|(key, value)| {
+                                key.parse::<IpNetwork>()
+                                    .ok()
+                                    .map(|network| (key, network, value))
+                            })
+                            .collect::<Vec<_>>()
+                    })
+            }
+        },
+    );
+
+    let import_action = create_action(
+        move |(remove, add, skipped): &(Vec<String>, Vec<String>, ImportSkipped)| {
+            let auth = auth.get();
+            let schema = current_schema.get();
+            let prefix = schema.unwrap_prefix();
+            let remove = remove.clone();
+            let add = add.clone();
+            let skipped = *skipped;
+
+            let mut updates = Vec::with_capacity(2);
+            if !remove.is_empty() {
+                updates.push(UpdateSettings::Delete {
+                    keys: remove.iter().map(|key| format!("{prefix}.{key}")).collect(),
+                });
+            }
+            if !add.is_empty() {
+                updates.push(UpdateSettings::Insert {
+                    prefix: Some(prefix.to_string()),
+                    values: add.iter().map(|key| (key.clone(), String::new())).collect(),
+                    assert_empty: false,
+                });
+            }
+
+            async move {
+                set_pending.set(true);
+                let result = HttpRequest::post("/api/settings")
+                    .with_authorization(&auth)
+                    .with_body(updates)
+                    .unwrap()
+                    .send::<Option<String>>()
+                    .await;
+                set_pending.set(false);
+
+                match result {
+                    Ok(_) => {
+                        entries.refetch();
+                        data.update(|data| {
+                            data.remove("networks");
+                        });
+                        let mut details = vec![format!(
+                            "Added {}.",
+                            maybe_plural(add.len(), "entry", "entries")
+                        )];
+                        if !remove.is_empty() {
+                            details.push(format!(
+                                "Removed {} now covered by a wider range.",
+                                maybe_plural(remove.len(), "entry", "entries")
+                            ));
+                        }
+                        if skipped.duplicates > 0 {
+                            details.push(format!(
+                                "Skipped {} listed more than once.",
+                                maybe_plural(skipped.duplicates, "entry", "entries")
+                            ));
+                        }
+                        if skipped.merged > 0 {
+                            details.push(format!(
+                                "Merged {} into a wider range.",
+                                maybe_plural(skipped.merged, "entry", "entries")
+                            ));
+                        }
+                        if skipped.existing > 0 {
+                            details.push(format!(
+                                "Skipped {} already listed.",
+                                maybe_plural(skipped.existing, "entry", "entries")
+                            ));
+                        }
+                        alert.set(
+                            Alert::success(format!(
+                                "Successfully updated {}. Reload the configuration to apply the changes.",
+                                schema.name_plural
+                            ))
+                            .with_details_list(details)
+                            .without_timeout(),
+                        );
+                    }
+                    Err(http::Error::Unauthorized) => {
+                        use_navigate()("/login", Default::default());
+                    }
+                    Err(err) => {
+                        alert.set(Alert::from(err));
+                    }
+                }
+            }
+        },
+    );
+
+    let lookup = create_memo(move |_| {
+        let network = data
+            .get()
+            .value::<String>("lookup")
+            .and_then(|value| value.parse::<IpNetwork>().ok())?;
+        let matches = entries
+            .get()
+            .and_then(|entries| entries.ok())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(_, entry, _)| entry.covers(&network) || network.covers(entry))
+            .collect::<Vec<_>>();
+        Some((network, matches))
+    });
+
+    view! {
+        <Form
+            title=Signal::derive(move || current_schema.get().list.title.to_string())
+            subtitle="Look up, bulk import and merge IP addresses and networks"
+        >
+
+            <FormSection title="Lookup".to_string()>
+                <FormItem
+                    label="IP Address"
+                    tooltip="IP address or network to look up in the list"
+                    is_optional=true
+                >
+                    <InputText
+                        placeholder="192.0.2.1"
+                        element=FormElement::new("lookup", data)
+                    />
+                    {move || {
+                        let schema = current_schema.get();
+                        lookup
+                            .get()
+                            .map(|(network, matches)| {
+                                if matches.is_empty() {
+                                    view! {
+                                        <p class="mt-2 text-sm text-gray-600 dark:text-gray-400">
+                                            {format!(
+                                                "{network} is not included in the {}.",
+                                                schema.list.title.to_lowercase(),
+                                            )}
+                                        </p>
+                                    }
+                                        .into_view()
+                                } else {
+                                    let matches = matches
+                                        .into_iter()
+                                        .map(|(key, entry, value)| {
+                                            let relation = if entry.covers(&network) {
+                                                "Listed by"
+                                            } else {
+                                                "Contains"
+                                            };
+                                            view! {
+                                                <li>
+                                                    {format!(
+                                                        "{relation} {key} ({})",
+                                                        format_expiry(&value),
+                                                    )}
+                                                </li>
+                                            }
+                                        })
+                                        .collect_view();
+                                    view! {
+                                        <ul class="mt-2 list-disc list-inside text-sm text-gray-600 dark:text-gray-400">
+                                            {matches}
+                                        </ul>
+                                    }
+                                        .into_view()
+                                }
+                            })
+                    }}
+
+                </FormItem>
+            </FormSection>
+
+            <FormSection title="Bulk import".to_string()>
+                <FormItem
+                    label="Addresses"
+                    tooltip="IP addresses or networks in CIDR notation, separated by spaces, commas or new lines"
+                >
+                    <TextArea
+                        placeholder="192.0.2.0/24\n198.51.100.7\n2001:db8::/32"
+                        element=FormElement::new("networks", data)
+                    />
+                    {move || {
+                        entries
+                            .get()
+                            .and_then(|entries| entries.ok())
+                            .map(|entries| {
+                                let expiring = entries
+                                    .iter()
+                                    .filter(|(_, _, value)| parse_expiry(value).is_some())
+                                    .count();
+                                view! {
+                                    <p class="mt-2 text-sm text-gray-500">
+                                        {format!(
+                                            "{} currently listed, {} with an expiration date.",
+                                            maybe_plural(entries.len(), "entry", "entries"),
+                                            expiring,
+                                        )}
+                                    </p>
+                                }
+                            })
+                    }}
+
+                </FormItem>
+            </FormSection>
+
+            <FormButtonBar>
+                <Button
+                    text="Cancel"
+                    color=Color::Gray
+                    on_click=move |_| {
+                        use_navigate()(
+                            &format!("/settings/{}", current_schema.get().id),
+                            Default::default(),
+                        );
+                    }
+                />
+
+                <Button
+                    text="Import"
+                    color=Color::Blue
+                    on_click=Callback::new(move |_| {
+                        let existing = entries
+                            .get()
+                            .and_then(|entries| entries.ok())
+                            .unwrap_or_default();
+                        data.update(|data| {
+                            if !data.validate_form() {
+                                return;
+                            }
+                            let mut invalid = Vec::new();
+                            let mut networks = Vec::new();
+                            for item in data
+                                .value::<String>("networks")
+                                .unwrap_or_default()
+                                .split(|c: char| c.is_whitespace() || c == ',' || c == ';')
+                                .filter(|item| !item.is_empty())
+                            {
+                                match item.parse::<IpNetwork>() {
+                                    Ok(network) => networks.push(network),
+                                    Err(_) => invalid.push(item.to_string()),
+                                }
+                            }
+                            if !invalid.is_empty() {
+                                data.new_error(
+                                    "networks",
+                                    format!("Invalid entries: {}", invalid.join(", ")),
+                                );
+                                return;
+                            }
+                            let total = networks.len();
+                            let mut unique = AHashSet::with_capacity(total);
+                            networks.retain(|network| unique.insert(*network));
+                            let mut skipped = ImportSkipped {
+                                duplicates: total - networks.len(),
+                                merged: networks.len(),
+                                existing: 0,
+                            };
+                            let networks = merge_networks(networks);
+                            skipped.merged -= networks.len();
+                            let mut add = Vec::new();
+                            let mut remove = Vec::new();
+                            for network in networks {
+                                if existing.iter().any(|(_, entry, _)| entry.covers(&network)) {
+                                    skipped.existing += 1;
+                                    continue;
+                                }
+                                for (key, entry, _) in &existing {
+                                    if network.covers(entry) && !remove.contains(key) {
+                                        remove.push(key.clone());
+                                    }
+                                }
+                                add.push(network.to_string());
+                            }
+                            if !add.is_empty() {
+                                import_action.dispatch((remove, add, skipped));
+                            } else {
+                                data.new_error(
+                                    "networks",
+                                    "All entries are already listed",
+                                );
+                            }
+                        });
+                    })
+
+                    disabled=pending
+                    capability=Capability::SettingsUpdate
+                />
+            </FormButtonBar>
+
+        </Form>
+    }
+}
+
+impl IpNetwork {
+    fn max_mask(&self) -> u8 {
+        if self.addr.is_ipv4() {
+            32
+        } else {
+            128
+        }
+    }
+
+    fn bits(addr: &IpAddr) -> u128 {
+        match addr {
+            IpAddr::V4(addr) => u32::from(*addr) as u128,
+            IpAddr::V6(addr) => u128::from(*addr),
+        }
+    }
+
+    fn mask_bits(&self, mask: u8) -> u128 {
+        let max_mask = self.max_mask();
+        if mask == 0 {
+            0
+        } else {
+            let all = if max_mask == 32 {
+                u32::MAX as u128
+            } else {
+                u128::MAX
+            };
+            (all << (max_mask - mask)) & all
+        }
+    }
+
+    fn with_bits(&self, bits: u128, mask: u8) -> Self {
+        IpNetwork {
+            addr: if self.addr.is_ipv4() {
+                IpAddr::V4(Ipv4Addr::from(bits as u32))
+            } else {
+                IpAddr::V6(Ipv6Addr::from(bits))
+            },
+            mask,
+        }
+    }
+
+    pub fn contains(&self, addr: &IpAddr) -> bool {
+        self.addr.is_ipv4() == addr.is_ipv4()
+            && Self::bits(addr) & self.mask_bits(self.mask) == Self::bits(&self.addr)
+    }
+
+    pub fn covers(&self, other: &IpNetwork) -> bool {
+        self.mask <= other.mask && self.contains(&other.addr)
+    }
+
+    fn sibling(&self) -> Self {
+        self.with_bits(
+            Self::bits(&self.addr) ^ (1u128 << (self.max_mask() - self.mask)),
+            self.mask,
+        )
+    }
+
+    fn parent(&self) -> Self {
+        self.with_bits(
+            Self::bits(&self.addr) & self.mask_bits(self.mask - 1),
+            self.mask - 1,
+        )
+    }
+}
+
+/// Entries left out of an import, by reason.
+#[derive(Clone, Copy)]
+struct ImportSkipped {
+    duplicates: usize,
+    merged: usize,
+    existing: usize,
+}
+
+/// Removes networks already covered by a wider entry and combines adjacent
+/// networks of the same size into their parent network.
+pub fn merge_networks(networks: impl IntoIterator<Item = IpNetwork>) -> Vec<IpNetwork> {
+    let mut networks = networks.into_iter().collect::<Vec<_>>();
+    networks.sort_unstable_by_key(|network| network.mask);
+
+    let mut merged = AHashSet::with_capacity(networks.len());
+    let mut widest: Vec<IpNetwork> = Vec::with_capacity(networks.len());
+    for network in networks {
+        if !widest.iter().any(|entry| entry.covers(&network)) {
+            widest.push(network);
+            merged.insert(network);
+        }
+    }
+
+    loop {
+        let mut has_changes = false;
+        for network in merged.iter().copied().collect::<Vec<_>>() {
+            if network.mask == 0 || !merged.contains(&network) {
+                continue;
+            }
+            let sibling = network.sibling();
+            if merged.remove(&sibling) {
+                merged.remove(&network);
+                merged.insert(network.parent());
+                has_changes = true;
+            }
+        }
+        if !has_changes {
+            break;
+        }
+    }
+
+    let mut merged = merged.into_iter().collect::<Vec<_>>();
+    merged.sort_unstable_by_key(|network| {
+        (
+            network.addr.is_ipv6(),
+            IpNetwork::bits(&network.addr),
+            network.mask,
+        )
+    });
+    merged
+}
+
+fn parse_expiry(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(timestamp) = value.parse::<i64>() {
+        DateTime::from_timestamp(timestamp, 0)
+    } else {
+        DateTime::parse_from_rfc3339(value)
+            .ok()
+            .map(|dt| dt.to_utc())
+    }
+}
+
+fn format_expiry(value: &str) -> String {
+    match parse_expiry(value) {
+        Some(expires) if expires > Utc::now() => format!(
+            "expires {}, {}",
+            HumanTime::from(expires),
+            expires.format_date_time()
+        ),
+        Some(expires) => format!("expired on {}", expires.format_date_time()),
+        None => "never expires".to_string(),
+    }
+}
+
+impl FromStr for IpNetwork {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, mask) = s.trim().split_once('/').unwrap_or((s.trim(), ""));
+        let addr = addr.parse::<IpAddr>().map_err(|_| ())?;
+        let max_mask = if addr.is_ipv4() { 32 } else { 128 };
+        let mask = if !mask.is_empty() {
+            mask.parse::<u8>()
+                .ok()
+                .filter(|mask| *mask <= max_mask)
+                .ok_or(())?
+        } else {
+            max_mask
+        };
+        let network = IpNetwork { addr, mask };
+
+        Ok(network.with_bits(IpNetwork::bits(&addr) & network.mask_bits(mask), mask))
+    }
+}
+
+impl Display for IpNetwork {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.mask == self.max_mask() {
+            self.addr.fmt(f)
+        } else {
+            write!(f, "{}/{}", self.addr, self.mask)
+        }
+    }
+}
+
+impl Builder<Schemas, ()> {
+    pub fn build_ip_tools(self) -> Self {
+        self.new_schema("ip-tools")
+            .new_field("lookup")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::IsIpOrMask])
+            .build()
+            .new_field("networks")
+            .typ(Type::Text)
+            .input_check([Transformer::Trim], [Validator::Required])
+            .build()
+            .build()
+    }
+}
@@ -11,7 +11,7 @@ use leptos_router::*;
 
 use crate::{
     components::{
-        icon::{IconAdd, IconRefresh, IconTrash},
+        icon::{IconAdd, IconDocumentText, IconRefresh, IconShieldCheck, IconTrash},
         list::{
             header::ColumnList,
             pagination::Pagination,
@@ -29,10 +29,15 @@ use crate::{
     core::{
         http::{self, HttpRequest},
         oauth::use_authorization,
+        permissions::Capability,
         url::UrlBuilder,
     },
     pages::{
-        config::{ReloadSettings, SchemaType, Schemas, SettingsValues},
+        config::{
+            notify::{test_notification_type, TestNotificationButton},
+            throttle_sim::{ThrottleSimulator, THROTTLE_SIMULATOR_SCHEMA},
+            ReloadSettings, SchemaType, Schemas, SettingsValues,
+        },
         maybe_plural, List,
     },
 };
@@ -231,6 +236,8 @@ pub fn SettingsList() -> impl IntoView {
                                     )
                             }
                         })
+
+                        capability=Capability::SettingsUpdate
                     >
 
                         <IconTrash/>
@@ -243,11 +250,46 @@ pub fn SettingsList() -> impl IntoView {
                         on_click=Callback::new(move |_| {
                             reload_config_action.dispatch(());
                         })
+
+                        capability=Capability::SettingsReload
                     >
 
                         <IconRefresh/>
                     </ToolbarButton>
 
+                    <Show when=move || {
+                        matches!(current_schema.get().id, "blocked-ip" | "allowed-ip")
+                    }>
+                        <ToolbarButton
+                            text="CIDR tools"
+
+                            color=Color::Gray
+                            on_click=move |_| {
+                                use_navigate()(
+                                    &format!("/settings/{}/tools", current_schema.get().id),
+                                    Default::default(),
+                                );
+                            }
+                        >
+
+                            <IconShieldCheck/>
+                        </ToolbarButton>
+                    </Show>
+
+                    <Show when=move || current_schema.get().id == "web-hooks">
+                        <ToolbarButton
+                            text="Deliveries"
+
+                            color=Color::Gray
+                            on_click=move |_| {
+                                use_navigate()("/settings/web-hooks/deliveries", Default::default());
+                            }
+                        >
+
+                            <IconDocumentText/>
+                        </ToolbarButton>
+                    </Show>
+
                     <ToolbarButton
                         text=Signal::derive(move || {
                             format!("Create {}", current_schema.get().name_singular)
@@ -260,6 +302,8 @@ pub fn SettingsList() -> impl IntoView {
                                 Default::default(),
                             );
                         }
+
+                        capability=Capability::SettingsUpdate
                     >
 
                         <IconAdd size=16 attr:class="flex-shrink-0 size-3"/>
@@ -374,6 +418,12 @@ pub fn SettingsList() -> impl IntoView {
 
                 </Footer>
             </ListTable>
+
+            {move || {
+                (current_schema.get().id == THROTTLE_SIMULATOR_SCHEMA)
+                    .then(|| view! { <ThrottleSimulator/> })
+            }}
+
         </ListSection>
     }
 }
@@ -395,14 +445,34 @@ fn SettingsItem(settings: Settings, schema: Arc<Schema>) -> impl IntoView {
         .unwrap_or_default();
     let edit_link = if schema.can_edit() {
         let edit_url = format!("/settings/{}/{}/edit", schema.id, setting_id);
+        let duplicate_url = matches!(schema.typ, SchemaType::Record { .. }).then(|| {
+            UrlBuilder::new(format!("/settings/{}/edit", schema.id))
+                .with_parameter("duplicate", &setting_id)
+                .finish()
+        });
+        let test_button = test_notification_type(schema.id)
+            .map(|typ| view! { <TestNotificationButton typ=typ id=setting_id.clone()/> });
         Some(view! {
-            <ListItem subclass="px-6 py-1.5">
+            <ListItem subclass="px-6 py-1.5 inline-flex gap-x-3">
                 <a
                     class="inline-flex items-center gap-x-1 text-sm text-blue-600 decoration-2 hover:underline font-medium dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
                     href=edit_url
                 >
                     Edit
                 </a>
+                {duplicate_url
+                    .map(|duplicate_url| {
+                        view! {
+                            <a
+                                class="inline-flex items-center gap-x-1 text-sm text-blue-600 decoration-2 hover:underline font-medium dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                                href=duplicate_url
+                            >
+                                Duplicate
+                            </a>
+                        }
+                    })}
+
+                {test_button}
             </ListItem>
         })
     } else {
@@ -0,0 +1,204 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::net::SocketAddr;
+
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        form::{button::Button, FormSection},
+        messages::alert::{use_alerts, Alert},
+        Color,
+    },
+    core::{
+        form::FormData,
+        http::{self, HttpRequest},
+        oauth::{use_authorization, AuthToken},
+        permissions::Capability,
+    },
+};
+
+use super::{Settings, SettingsValues};
+
+pub const LISTENER_SCHEMA: &str = "listener";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortProbe {
+    pub reachable: bool,
+    #[serde(default)]
+    pub elapsed: Option<u64>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// Returns a description of every bind address of the listener being edited
+/// that overlaps with an address already used by another listener.
+pub async fn find_bind_conflicts(
+    auth: &AuthToken,
+    listener_id: &str,
+    binds: &[String],
+) -> http::Result<Vec<String>> {
+    let listeners = HttpRequest::get("/api/settings/group")
+        .with_authorization(auth)
+        .with_parameter("prefix", "server.listener")
+        .with_parameter("suffix", "protocol")
+        .send::<crate::pages::List<Settings>>()
+        .await?
+        .items;
+
+    let mut conflicts = Vec::new();
+    for (idx, bind) in binds.iter().enumerate() {
+        let Ok(addr) = bind.parse::<SocketAddr>() else {
+            continue;
+        };
+
+        // Duplicates within the listener itself
+        if binds[..idx]
+            .iter()
+            .filter_map(|other| other.parse::<SocketAddr>().ok())
+            .any(|other| bind_overlaps(addr, other))
+        {
+            conflicts.push(format!("{bind} is listed more than once"));
+            continue;
+        }
+
+        for listener in &listeners {
+            let other_id = listener.get("_id").map(|s| s.as_str()).unwrap_or_default();
+            if other_id == listener_id {
+                continue;
+            }
+            for (_, other) in listener.array_values("bind") {
+                if other
+                    .parse::<SocketAddr>()
+                    .is_ok_and(|other| bind_overlaps(addr, other))
+                {
+                    conflicts.push(format!(
+                        "{bind} conflicts with {other} on listener '{other_id}'"
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok(conflicts)
+}
+
+/// Asks the server to open a connection to each bind address, returning the
+/// addresses that are not accepting connections or `None` if the server does
+/// not support port probes.
+pub async fn probe_binds(auth: &AuthToken, binds: &[String]) -> http::Result<Option<Vec<String>>> {
+    let mut unreachable = Vec::new();
+    for bind in binds {
+        match probe_bind(auth, bind).await? {
+            Some(probe) if probe.reachable => {}
+            Some(probe) => {
+                unreachable.push(match probe.error {
+                    Some(error) => format!("{bind}: {error}"),
+                    None => format!("{bind}: not accepting connections"),
+                });
+            }
+            None => return Ok(None),
+        }
+    }
+
+    Ok(Some(unreachable))
+}
+
+/// Probes a single bind address, returns `None` if the server does not
+/// support port probes.
+pub async fn probe_bind(auth: &AuthToken, bind: &str) -> http::Result<Option<PortProbe>> {
+    HttpRequest::get("/api/troubleshoot/port")
+        .with_authorization(auth)
+        .with_parameter("address", bind)
+        .try_send::<PortProbe>()
+        .await
+}
+
+/// Checks on request whether the saved bind addresses of a listener accept
+/// connections.
+#[component]
+pub fn ListenerReachabilityCheck(data: RwSignal<FormData>) -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+
+    let check_action = create_action(move |_: &()| {
+        let auth = auth.get();
+        let binds = data.with_untracked(|data| {
+            data.array_value("bind")
+                .map(|bind| bind.to_string())
+                .collect::<Vec<_>>()
+        });
+
+        async move {
+            match probe_binds(&auth, &binds).await {
+                Ok(Some(unreachable)) if unreachable.is_empty() => {
+                    alert.set(Alert::success(
+                        "All bind addresses are accepting connections.",
+                    ));
+                }
+                Ok(Some(unreachable)) => {
+                    alert.set(
+                        Alert::warning(concat!(
+                            "Some bind addresses are not accepting ",
+                            "connections, a server restart may be required"
+                        ))
+                        .with_details_list(unreachable)
+                        .without_timeout(),
+                    );
+                }
+                Ok(None) => {
+                    alert.set(Alert::warning(
+                        "This server does not support reachability checks.",
+                    ));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    view! {
+        <FormSection title="Reachability".to_string()>
+            <div class="flex flex-col gap-4">
+                <p class="text-sm text-gray-600 dark:text-gray-400">
+                    Check that the server accepts connections on the bind addresses above.
+                    New bind addresses may only be used after a server restart.
+                </p>
+                <div>
+                    <Button
+                        text=Signal::derive(move || {
+                            if check_action.pending().get() {
+                                "Checking...".to_string()
+                            } else {
+                                "Check reachability".to_string()
+                            }
+                        })
+
+                        color=Color::Gray
+                        disabled=check_action.pending()
+                        capability=Capability::Troubleshoot
+                        on_click=move |_| check_action.dispatch(())
+                    />
+                </div>
+            </div>
+        </FormSection>
+    }
+}
+
+fn bind_overlaps(a: SocketAddr, b: SocketAddr) -> bool {
+    a.port() == b.port()
+        && (a.ip() == b.ip()
+            || (a.ip().is_unspecified() && a.is_ipv4() == b.is_ipv4())
+            || (b.ip().is_unspecified() && a.is_ipv4() == b.is_ipv4())
+            || (a.ip().is_unspecified() && b.ip().is_unspecified()))
+}
@@ -0,0 +1,196 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+use web_time::Instant;
+
+use crate::{
+    components::{
+        form::{button::Button, FormItem, FormSection},
+        icon::IconPaperAirplane,
+        messages::alert::{use_alerts, Alert},
+        Color,
+    },
+    core::{
+        form::FormData,
+        http::{self, HttpRequest},
+        oauth::{use_authorization, AuthToken},
+    },
+};
+
+pub const SPAM_LLM_SCHEMA: &str = "spam-llm";
+
+const SAMPLE_MESSAGE: &str = "From: \"Account Security\" <security@examp1e-bank.com>\r\nTo: john@example.org\r\nSubject: Urgent: verify your account\r\n\r\nYour account has been suspended. Click the link below within 24 hours to restore access.\r\n";
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub(crate) struct LlmTestRequest {
+    pub model: String,
+    pub prompt: String,
+    pub temperature: Option<f64>,
+    pub categories: Vec<String>,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct LlmTestResponse {
+    #[serde(default)]
+    pub response: String,
+    #[serde(default)]
+    pub usage: Option<TokenUsage>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct TokenUsage {
+    #[serde(default)]
+    pub prompt_tokens: u64,
+    #[serde(default)]
+    pub completion_tokens: u64,
+    #[serde(default)]
+    pub total_tokens: u64,
+}
+
+/// Sends a sample message to the configured model through the server using
+/// the prompt currently in the form, so the classifier can be tuned without
+/// saving the settings or sending real mail.
+#[component]
+pub fn LlmPromptConsole(data: RwSignal<FormData>) -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let message = create_rw_signal(SAMPLE_MESSAGE.to_string());
+    let result = create_rw_signal(None::<(LlmTestResponse, u128)>);
+
+    let run_action = create_action(move |request: &LlmTestRequest| {
+        let auth = auth.get();
+        let request = request.clone();
+
+        async move {
+            match test_model(&auth, request).await {
+                Ok(response) => {
+                    result.set(Some(response));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(http::Error::NotFound) => {
+                    alert.set(Alert::warning(
+                        "This server does not support testing AI models.",
+                    ));
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    let run = move |_| {
+        let request = data.with_untracked(|data| LlmTestRequest {
+            model: data
+                .value::<String>("spam-filter.llm.model")
+                .unwrap_or_default(),
+            prompt: data
+                .value::<String>("spam-filter.llm.prompt")
+                .unwrap_or_default(),
+            temperature: data.value::<f64>("spam-filter.llm.temperature"),
+            categories: data
+                .array_value("spam-filter.llm.categories")
+                .map(|category| category.to_string())
+                .collect(),
+            message: message.get_untracked(),
+        });
+
+        if request.model.is_empty() {
+            alert.set(Alert::warning("Select a model before running the test."));
+        } else if request.prompt.trim().is_empty() {
+            alert.set(Alert::warning("Enter a prompt before running the test."));
+        } else if request.message.trim().is_empty() {
+            alert.set(Alert::warning("Enter a sample message to classify."));
+        } else {
+            run_action.dispatch(request);
+        }
+    };
+
+    view! {
+        <FormSection
+            title="Prompt console".to_string()
+            hide=Signal::derive(move || {
+                !data.get().value::<bool>("spam-filter.llm.enable").unwrap_or_default()
+            })
+        >
+
+            <FormItem label="Sample message">
+                <textarea
+                    class="py-3 px-4 block w-full border-gray-200 rounded-lg text-sm font-mono focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                    rows="8"
+                    prop:value=move || message.get()
+                    on:input=move |ev| message.set(event_target_value(&ev))
+                ></textarea>
+            </FormItem>
+            <FormItem label="">
+                <Button
+                    text="Run"
+                    color=Color::Gray
+                    on_click=Callback::new(run)
+                    disabled=run_action.pending()
+                >
+                    <IconPaperAirplane/>
+                </Button>
+            </FormItem>
+            {move || {
+                result
+                    .get()
+                    .map(|(response, latency)| {
+                        view! {
+                            <FormItem label="Response">
+                                <pre class="p-3 text-xs whitespace-pre-wrap bg-gray-50 border border-gray-200 rounded-lg text-gray-800 dark:bg-slate-800 dark:border-gray-700 dark:text-gray-200">
+                                    {response.response}
+                                </pre>
+                            </FormItem>
+                            <FormItem label="Latency">
+                                <div class="pt-2.5 text-sm text-gray-600 dark:text-gray-400">
+                                    {format!("{latency} ms")}
+                                </div>
+                            </FormItem>
+                            <FormItem label="Token usage">
+                                <div class="pt-2.5 text-sm text-gray-600 dark:text-gray-400">
+                                    {response
+                                        .usage
+                                        .map(|usage| {
+                                            format!(
+                                                "{} prompt + {} completion = {} tokens",
+                                                usage.prompt_tokens,
+                                                usage.completion_tokens,
+                                                usage.total_tokens,
+                                            )
+                                        })
+                                        .unwrap_or_else(|| "Not reported by the model".to_string())}
+                                </div>
+                            </FormItem>
+                        }
+                    })
+            }}
+
+        </FormSection>
+    }
+}
+
+/// Sends a test completion to a model through the server, returning the
+/// model response along with the round-trip latency in milliseconds.
+pub(crate) async fn test_model(
+    auth: &AuthToken,
+    request: LlmTestRequest,
+) -> http::Result<(LlmTestResponse, u128)> {
+    let started = Instant::now();
+    HttpRequest::post("/api/ai/test")
+        .with_authorization(auth)
+        .with_body(request)
+        .unwrap()
+        .send::<LlmTestResponse>()
+        .await
+        .map(|response| (response, started.elapsed().as_millis()))
+}
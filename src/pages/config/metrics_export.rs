@@ -0,0 +1,310 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use leptos::*;
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{button::Button, FormItem, FormSection},
+        messages::alert::{use_alerts, Alert},
+        Color,
+    },
+    core::{
+        clipboard::copy_to_clipboard,
+        form::FormData,
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+    },
+};
+
+pub const METRICS_SCHEMA: &str = "metrics";
+
+const PROMETHEUS_PATH: &str = "/metrics/prometheus";
+const MAX_SAMPLES: usize = 20;
+
+const INPUT_CLASS: &str = "py-2 px-3 block w-full border-gray-200 shadow-sm text-sm rounded-lg focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600";
+const CODE_CLASS: &str = "p-3 text-xs font-mono whitespace-pre-wrap break-all bg-gray-50 border border-gray-200 rounded-lg text-gray-800 dark:bg-slate-800 dark:border-gray-700 dark:text-gray-300";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct MetricFamily {
+    name: String,
+    typ: String,
+    help: String,
+    samples: Vec<(String, String)>,
+}
+
+/// Fetches the Prometheus endpoint with the credentials entered in the form
+/// and shows the parsed output alongside a ready to use scrape configuration.
+#[component]
+pub fn MetricsExportPreview(data: RwSignal<FormData>) -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let families = create_rw_signal(None::<Vec<MetricFamily>>);
+    let search = create_rw_signal(String::new());
+
+    let credentials = create_memo(move |_| {
+        data.with(|data| {
+            data.value::<String>("metrics.prometheus.auth.username")
+                .filter(|username| !username.is_empty())
+                .map(|username| {
+                    (
+                        username,
+                        data.value::<String>("metrics.prometheus.auth.secret")
+                            .unwrap_or_default(),
+                    )
+                })
+        })
+    });
+    let base_url = create_memo(move |_| {
+        let base_url = auth.get().base_url.trim_end_matches('/').to_string();
+        if !base_url.is_empty() {
+            base_url
+        } else {
+            window().location().origin().unwrap_or_default()
+        }
+    });
+
+    let fetch_action = create_action(move |_: &()| {
+        let auth = auth.get();
+        let credentials = credentials.get();
+
+        async move {
+            let mut request = HttpRequest::get(PROMETHEUS_PATH).with_base_url(&auth);
+            if let Some((username, secret)) = &credentials {
+                request = request.with_basic_authorization(username, secret);
+            }
+
+            match request.send_raw().await {
+                Ok(bytes) => {
+                    families.set(Some(parse_exposition(&String::from_utf8_lossy(&bytes))));
+                }
+                Err(http::Error::NotFound) => {
+                    families.set(None);
+                    alert.set(Alert::warning(concat!(
+                        "The Prometheus endpoint is not available, make sure it ",
+                        "is enabled and the settings have been reloaded."
+                    )));
+                }
+                Err(http::Error::Unauthorized | http::Error::Forbidden) => {
+                    families.set(None);
+                    alert.set(Alert::error(
+                        "The Prometheus endpoint rejected the configured credentials.",
+                    ));
+                }
+                Err(err) => {
+                    families.set(None);
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    let snippet = create_memo(move |_| scrape_config(&base_url.get(), credentials.get()));
+
+    view! {
+        <FormSection
+            title="Exposition preview".to_string()
+            hide=Signal::derive(move || {
+                data.with(|data| data.value::<bool>("metrics.prometheus.enable") != Some(true))
+            })
+        >
+
+            <FormItem label="Scrape config" tooltip="Prometheus configuration for this server">
+                <pre class=CODE_CLASS>{move || snippet.get()}</pre>
+                <div class="mt-2">
+                    <Button
+                        text="Copy scrape config"
+                        color=Color::Gray
+                        on_click=move |_| {
+                            if copy_to_clipboard(&snippet.get()) {
+                                alert.set(Alert::success("Scrape configuration copied to clipboard"));
+                            } else {
+                                alert.set(Alert::error("Failed to copy to clipboard"));
+                            }
+                        }
+                    />
+
+                </div>
+            </FormItem>
+
+            <FormItem label="Metrics" tooltip="Current output of the Prometheus endpoint">
+                <div class="flex gap-x-2">
+                    <input
+                        type="text"
+                        class=INPUT_CLASS
+                        placeholder="Search metrics"
+                        prop:value=move || search.get()
+                        on:input=move |ev| search.set(event_target_value(&ev))
+                    />
+                    <Button
+                        text="Fetch"
+                        color=Color::Gray
+                        on_click=move |_| fetch_action.dispatch(())
+                        disabled=fetch_action.pending()
+                    />
+                </div>
+
+                {move || {
+                    families
+                        .get()
+                        .map(|families| {
+                            let query = search.get().trim().to_lowercase();
+                            let families = families
+                                .into_iter()
+                                .filter(|family| {
+                                    query.is_empty()
+                                        || family.name.contains(&query)
+                                        || family.help.to_lowercase().contains(&query)
+                                })
+                                .collect::<Vec<_>>();
+                            if families.is_empty() {
+                                return view! {
+                                    <p class="mt-3 text-sm text-gray-500">No metrics found.</p>
+                                }
+                                    .into_view();
+                            }
+                            view! {
+                                <div class="mt-3 max-h-[32rem] overflow-y-auto space-y-3">
+                                    {families
+                                        .into_iter()
+                                        .map(|family| view! { <MetricFamilyView family/> })
+                                        .collect_view()}
+                                </div>
+                            }
+                                .into_view()
+                        })
+                }}
+
+            </FormItem>
+        </FormSection>
+    }
+}
+
+#[component]
+fn MetricFamilyView(family: MetricFamily) -> impl IntoView {
+    let more = family.samples.len().saturating_sub(MAX_SAMPLES);
+    let samples = family
+        .samples
+        .into_iter()
+        .take(MAX_SAMPLES)
+        .map(|(series, value)| format!("{series} {value}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    view! {
+        <div class="border border-gray-200 rounded-lg p-3 dark:border-gray-700">
+            <div class="flex items-center gap-x-2">
+                <span class="text-sm font-mono font-semibold text-gray-800 dark:text-gray-200">
+                    {family.name}
+                </span>
+                {(!family.typ.is_empty())
+                    .then(|| view! { <Badge color=Color::Gray>{family.typ}</Badge> })}
+            </div>
+            <p class="text-xs text-gray-500">{family.help}</p>
+            <pre class=format!("mt-2 {CODE_CLASS}")>{samples}</pre>
+            {(more > 0)
+                .then(|| {
+                    view! { <p class="mt-1 text-xs text-gray-500">{format!("and {more} more")}</p> }
+                })}
+
+        </div>
+    }
+}
+
+/// Groups the lines of a Prometheus text exposition into metric families.
+fn parse_exposition(text: &str) -> Vec<MetricFamily> {
+    let mut families: Vec<MetricFamily> = Vec::new();
+
+    for line in text
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+    {
+        if let Some(comment) = line.strip_prefix('#') {
+            let mut parts = comment.trim_start().splitn(3, ' ');
+            let (Some(kind @ ("HELP" | "TYPE")), Some(name)) = (parts.next(), parts.next()) else {
+                continue;
+            };
+            let text = parts.next().unwrap_or_default().trim().to_string();
+            let idx = match families.iter().rposition(|f| f.name == name) {
+                Some(idx) => idx,
+                None => {
+                    families.push(MetricFamily {
+                        name: name.to_string(),
+                        ..Default::default()
+                    });
+                    families.len() - 1
+                }
+            };
+            let family = &mut families[idx];
+            if kind == "HELP" {
+                family.help = text;
+            } else {
+                family.typ = text;
+            }
+            continue;
+        }
+
+        let series_end = match line.rfind('}') {
+            Some(pos) if line.contains('{') => pos + 1,
+            _ => line.find(char::is_whitespace).unwrap_or(line.len()),
+        };
+        let (series, value) = line.split_at(series_end);
+        let value = value.split_whitespace().next().unwrap_or_default();
+        let name = series.split('{').next().unwrap_or_default();
+
+        // Histogram and summary samples carry a suffix on the family name
+        match families.iter_mut().rfind(|f| {
+            name == f.name
+                || name
+                    .strip_prefix(f.name.as_str())
+                    .is_some_and(|suffix| suffix.starts_with('_'))
+        }) {
+            Some(family) => family.samples.push((series.to_string(), value.to_string())),
+            None => families.push(MetricFamily {
+                name: name.to_string(),
+                samples: vec![(series.to_string(), value.to_string())],
+                ..Default::default()
+            }),
+        }
+    }
+
+    families
+}
+
+fn scrape_config(base_url: &str, credentials: Option<(String, String)>) -> String {
+    let (scheme, host) = base_url.split_once("://").unwrap_or(("https", base_url));
+    let host = host.split('/').next().unwrap_or_default();
+    let mut config = format!(
+        concat!(
+            "scrape_configs:\n",
+            "  - job_name: \"stalwart\"\n",
+            "    metrics_path: \"{}\"\n",
+            "    scheme: \"{}\"\n",
+            "    static_configs:\n",
+            "      - targets: [\"{}\"]\n",
+        ),
+        PROMETHEUS_PATH, scheme, host
+    );
+    if let Some((username, secret)) = credentials {
+        config.push_str(&format!(
+            concat!(
+                "    basic_auth:\n",
+                "      username: \"{}\"\n",
+                "      password: \"{}\"\n",
+                "\n",
+                "# Other scrapers can send this header instead:\n",
+                "# Authorization: Basic {}\n",
+            ),
+            username,
+            secret,
+            STANDARD.encode(format!("{username}:{secret}").as_bytes())
+        ));
+    }
+    config
+}
@@ -4,10 +4,32 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
+pub mod acme;
+pub mod ai_usage;
+pub mod alerts;
+pub mod certs;
+pub mod disclaimer;
+pub mod dkim;
 pub mod edit;
+pub mod hook_test;
+pub mod http_lookup;
+pub mod ip;
 pub mod list;
+pub mod listener;
+pub mod llm_console;
+pub mod metrics_export;
+pub mod notify;
+pub mod oidc;
+pub mod queue_sim;
+pub mod relay;
+pub mod reload;
+pub mod report_preview;
+pub mod rewrite;
 pub mod schema;
 pub mod search;
+pub mod spam_rules;
+pub mod throttle_sim;
+pub mod webhook_log;
 
 use std::{collections::BTreeMap, str::FromStr};
 
@@ -107,7 +129,7 @@ impl FormData {
                                 prefix: format!("{}.", field.id),
                             });
                             delete_keys.push(field.id.to_string());
-                        } else if self.value_is_empty(field.id) {
+                        } else if self.value_is_empty(field.id) || self.uses_default(field.id) {
                             delete_keys.push(field.id.to_string());
                         }
                     }
@@ -121,7 +143,7 @@ impl FormData {
 
         let mut key_values = Vec::new();
         for (key, value) in &self.values {
-            if key.starts_with('_') {
+            if key.starts_with('_') || self.uses_default(key) {
                 continue;
             }
 
@@ -289,23 +311,43 @@ impl LayoutBuilder {
             .create("Defaults")
             .route("/tls/edit")
             .insert()
+            .create("Expiry")
+            .route("/certificates/expiry")
+            .insert()
             .insert()
             // System
             .create("Cluster")
             .route("/cluster/edit")
             .insert()
-            // Logging
+            // Telemetry
+            .create("Telemetry")
             .create("Logging & Tracing")
             .route("/tracing")
             .insert()
+            .create("Metrics")
+            .route("/metrics/edit")
+            .insert()
+            .create("Alerts")
+            .route("/alert")
+            .insert()
+            .insert()
             // Logging
             .create("Webhooks")
+            .create("Endpoints")
             .route("/web-hooks")
             .insert()
+            .create("Deliveries")
+            .route("/web-hooks/deliveries")
+            .insert()
+            .insert()
             // Cache
             .create("Cache")
             .route("/cache/edit")
             .insert()
+            // Announcement
+            .create("Announcement")
+            .route("/announcement/edit")
+            .insert()
             // Security
             .create("Security")
             // Blocked IPs
@@ -317,6 +359,15 @@ impl LayoutBuilder {
             .route("/allowed-ip")
             .insert()
             .insert()
+            // AI Models
+            .create("AI Models")
+            .create("Models")
+            .route("/ai-model")
+            .insert()
+            .create("Usage")
+            .route("/ai-model/usage")
+            .insert()
+            .insert()
             .insert()
             // Storage
             .create("Storage")
@@ -327,6 +378,12 @@ impl LayoutBuilder {
             .create("Stores")
             .route("/store")
             .insert()
+            .create("HTTP Lists")
+            .route("/http-lookup")
+            .insert()
+            .create("Undelete")
+            .route("/undelete/edit")
+            .insert()
             .insert()
             // Authentication
             .create("Authentication")
@@ -337,6 +394,9 @@ impl LayoutBuilder {
             .create("Directories")
             .route("/directory")
             .insert()
+            .create("Quota Templates")
+            .route("/quota-template")
+            .insert()
             .create("OAuth")
             .route("/oauth/edit")
             .insert()
@@ -473,6 +533,12 @@ impl LayoutBuilder {
             .create("Settings")
             .route("/spam-settings/edit")
             .insert()
+            .create("Rules")
+            .route("/spam-rule")
+            .insert()
+            .create("LLM Classifier")
+            .route("/spam-llm/edit")
+            .insert()
             .create("Scores")
             .route("/spam-scores")
             .insert()
@@ -513,6 +579,9 @@ impl LayoutBuilder {
             .create("Scripts")
             .route("/script")
             .insert()
+            .create("Disclaimers")
+            .route("/disclaimer")
+            .insert()
             .insert()
             .create("Management")
             .icon(view! { <IconServer/> })
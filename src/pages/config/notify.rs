@@ -0,0 +1,108 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::messages::alert::{use_alerts, Alert},
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+    },
+};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct DeliveryResult {
+    target: String,
+    success: bool,
+    #[serde(default)]
+    details: Option<String>,
+}
+
+/// Returns the notification type tested for records of a settings page, if
+/// the records can send notifications.
+pub fn test_notification_type(schema_id: &str) -> Option<&'static str> {
+    match schema_id {
+        "alert" => Some("alert"),
+        "web-hooks" => Some("webhook"),
+        _ => None,
+    }
+}
+
+#[component]
+pub fn TestNotificationButton(typ: &'static str, id: String) -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+
+    let test_action = create_action(move |id: &String| {
+        let auth = auth.get();
+        let id = id.clone();
+
+        async move {
+            let url = format!("/api/telemetry/test/{typ}");
+            match HttpRequest::post((url.as_str(), &id))
+                .with_authorization(&auth)
+                .send::<Vec<DeliveryResult>>()
+                .await
+            {
+                Ok(results) => {
+                    let failed = results.iter().any(|result| !result.success);
+                    let details = results
+                        .into_iter()
+                        .map(|result| {
+                            let status = if result.success {
+                                "delivered"
+                            } else {
+                                "failed"
+                            };
+                            match result.details {
+                                Some(details) => {
+                                    format!("{}: {status} ({details})", result.target)
+                                }
+                                None => format!("{}: {status}", result.target),
+                            }
+                        })
+                        .collect::<Vec<_>>();
+                    alert.set(if details.is_empty() {
+                        Alert::warning(format!(
+                            "The {typ} {id:?} has no notification targets enabled."
+                        ))
+                    } else if failed {
+                        Alert::error("Test notification failed")
+                            .with_details_list(details)
+                            .without_timeout()
+                    } else {
+                        Alert::success("Test notification sent").with_details_list(details)
+                    });
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(http::Error::NotFound) => {
+                    alert.set(Alert::warning(
+                        "This server does not support sending test notifications.",
+                    ));
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    view! {
+        <button
+            type="button"
+            class="inline-flex items-center gap-x-1 text-sm text-blue-600 decoration-2 hover:underline font-medium disabled:opacity-50 disabled:pointer-events-none dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+            disabled=move || test_action.pending().get()
+            on:click=move |_| test_action.dispatch(id.clone())
+        >
+            {move || if test_action.pending().get() { "Sending..." } else { "Test" }}
+        </button>
+    }
+}
@@ -0,0 +1,601 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::{collections::BTreeMap, time::Duration};
+
+use leptos::{leptos_dom::helpers::IntervalHandle, *};
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{button::Button, FormItem, FormSection},
+        messages::alert::{use_alerts, Alert},
+        Color,
+    },
+    core::{
+        form::FormData,
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+    },
+};
+
+pub const DIRECTORY_SCHEMA: &str = "directory";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+const MAX_POLLS: u32 = 150;
+const DEFAULT_SCOPES: &[&str] = &["openid", "email", "profile"];
+
+const CODE_CLASS: &str = "p-3 text-xs font-mono whitespace-pre-wrap break-all bg-gray-50 border border-gray-200 rounded-lg text-gray-800 dark:bg-slate-800 dark:border-gray-700 dark:text-gray-300";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+struct DiscoveryDocument {
+    issuer: String,
+    authorization_endpoint: String,
+    #[serde(default)]
+    token_endpoint: Option<String>,
+    #[serde(default)]
+    userinfo_endpoint: Option<String>,
+    #[serde(default)]
+    jwks_uri: Option<String>,
+    #[serde(default)]
+    scopes_supported: Vec<String>,
+    #[serde(default)]
+    response_types_supported: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+struct OidcTestRequest {
+    issuer_url: String,
+    client_id: String,
+    client_secret: Option<String>,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    userinfo_endpoint: Option<String>,
+    redirect_uri: String,
+    scopes: Vec<String>,
+    username_claim: Option<String>,
+    email_claim: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+struct OidcTestSession {
+    id: String,
+    authorization_url: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum OidcTestStatus {
+    Pending,
+    Success {
+        #[serde(default)]
+        username: Option<String>,
+        #[serde(default)]
+        email: Option<String>,
+        #[serde(default)]
+        claims: BTreeMap<String, serde_json::Value>,
+    },
+    Failed {
+        reason: String,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum TestState {
+    Idle,
+    Waiting { id: String, polls: u32 },
+    Done(OidcTestStatus),
+}
+
+/// Guided setup for OpenID Connect directories. Endpoints are filled in from
+/// the provider's discovery document, redirect URIs are checked as they are
+/// typed and the whole login flow can be tried out in a popup before saving.
+#[component]
+pub fn OidcSetupWizard(data: RwSignal<FormData>) -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let discovery = create_rw_signal(None::<DiscoveryDocument>);
+    let test_state = create_rw_signal(TestState::Idle);
+    let popup = store_value(None::<web_sys::Window>);
+    let poller = store_value(None::<IntervalHandle>);
+
+    let issuer = create_memo(move |_| {
+        data.with(|data| {
+            data.value::<String>("issuer-url")
+                .map(|issuer| issuer.trim().trim_end_matches('/').to_string())
+                .filter(|issuer| !issuer.is_empty())
+        })
+    });
+    let redirect_uris = create_memo(move |_| {
+        data.with(|data| {
+            let mut seen = Vec::new();
+            data.array_value("redirect-uris")
+                .filter(|uri| !uri.trim().is_empty())
+                .map(|uri| {
+                    let uri = uri.trim().to_string();
+                    let result = if seen.contains(&uri) {
+                        Err("Duplicate redirect URI")
+                    } else {
+                        validate_redirect_uri(&uri)
+                    };
+                    seen.push(uri.clone());
+                    (uri, result)
+                })
+                .collect::<Vec<_>>()
+        })
+    });
+
+    let stop_test = move || {
+        if let Some(handle) = poller.get_value() {
+            handle.clear();
+            poller.set_value(None);
+        }
+        popup.update_value(|popup| {
+            if let Some(popup) = popup.take() {
+                let _ = popup.close();
+            }
+        });
+    };
+    on_cleanup(stop_test);
+
+    let discover_action = create_action(move |issuer: &String| {
+        let url = format!("{issuer}/.well-known/openid-configuration");
+        let issuer = issuer.clone();
+
+        async move {
+            let document = match HttpRequest::get(url).send_raw().await {
+                Ok(bytes) => match serde_json::from_slice::<DiscoveryDocument>(&bytes) {
+                    Ok(document) => document,
+                    Err(err) => {
+                        alert.set(
+                            Alert::error("The provider returned an invalid discovery document")
+                                .with_details(err.to_string()),
+                        );
+                        return;
+                    }
+                },
+                Err(http::Error::NotFound) => {
+                    alert.set(Alert::error(format!(
+                        "No discovery document was found for {issuer:?}, check the issuer URL."
+                    )));
+                    return;
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                    return;
+                }
+            };
+
+            data.update(|data| {
+                data.update(
+                    "endpoint.authorization",
+                    document.authorization_endpoint.clone(),
+                );
+                for (key, value) in [
+                    ("endpoint.token", &document.token_endpoint),
+                    ("endpoint.url", &document.userinfo_endpoint),
+                    ("endpoint.jwks", &document.jwks_uri),
+                ] {
+                    if let Some(value) = value {
+                        data.update(key, value.clone());
+                    }
+                }
+                if data.array_value("scopes").next().is_none() {
+                    data.array_set(
+                        "scopes",
+                        DEFAULT_SCOPES.iter().copied().filter(|scope| {
+                            *scope == "openid"
+                                || document.scopes_supported.is_empty()
+                                || document.scopes_supported.iter().any(|s| s == scope)
+                        }),
+                    );
+                }
+            });
+            alert.set(Alert::success(
+                "Endpoints updated from the discovery document",
+            ));
+            discovery.set(Some(document));
+        }
+    });
+
+    let poll_action = create_action(move |id: &String| {
+        let auth = auth.get();
+        let id = id.clone();
+
+        async move {
+            let result = HttpRequest::get(("/api/directory/oidc/test", &id))
+                .with_authorization(&auth)
+                .send::<OidcTestStatus>()
+                .await;
+            let TestState::Waiting { polls, .. } = test_state.get_untracked() else {
+                return;
+            };
+            let popup_closed = popup.with_value(|popup| {
+                popup
+                    .as_ref()
+                    .map_or(true, |popup| popup.closed().unwrap_or(true))
+            });
+
+            match result {
+                Ok(OidcTestStatus::Pending) if polls >= MAX_POLLS => {
+                    stop_test();
+                    test_state.set(TestState::Done(OidcTestStatus::Failed {
+                        reason: "The login did not complete in time.".to_string(),
+                    }));
+                }
+                Ok(OidcTestStatus::Pending) if popup_closed => {
+                    stop_test();
+                    test_state.set(TestState::Done(OidcTestStatus::Failed {
+                        reason: "The login window was closed before the login completed."
+                            .to_string(),
+                    }));
+                }
+                Ok(OidcTestStatus::Pending) => {
+                    test_state.set(TestState::Waiting {
+                        id,
+                        polls: polls + 1,
+                    });
+                }
+                Ok(status) => {
+                    stop_test();
+                    test_state.set(TestState::Done(status));
+                }
+                Err(http::Error::Unauthorized) => {
+                    stop_test();
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    stop_test();
+                    test_state.set(TestState::Idle);
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    let test_action = create_action(move |request: &OidcTestRequest| {
+        let auth = auth.get();
+        let request = request.clone();
+
+        async move {
+            stop_test();
+            let session = match HttpRequest::post("/api/directory/oidc/test")
+                .with_authorization(&auth)
+                .with_body(request)
+                .unwrap()
+                .send::<OidcTestSession>()
+                .await
+            {
+                Ok(session) => session,
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                    return;
+                }
+                Err(http::Error::NotFound) => {
+                    alert.set(Alert::warning(
+                        "This server does not support testing OpenID Connect logins.",
+                    ));
+                    return;
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                    return;
+                }
+            };
+
+            match window()
+                .open_with_url_and_target_and_features(
+                    &session.authorization_url,
+                    "oidc-test",
+                    "popup,width=520,height=680",
+                )
+                .ok()
+                .flatten()
+            {
+                Some(window) => {
+                    popup.set_value(Some(window));
+                }
+                None => {
+                    alert.set(Alert::warning(
+                        "The login window was blocked, allow popups for this site and try again.",
+                    ));
+                    return;
+                }
+            }
+
+            test_state.set(TestState::Waiting {
+                id: session.id,
+                polls: 0,
+            });
+            poller.set_value(
+                set_interval_with_handle(
+                    move || {
+                        if let TestState::Waiting { id, .. } = test_state.get_untracked() {
+                            if !poll_action.pending().get_untracked() {
+                                poll_action.dispatch(id);
+                            }
+                        }
+                    },
+                    POLL_INTERVAL,
+                )
+                .ok(),
+            );
+        }
+    });
+
+    let test_request = create_memo(move |_| {
+        let redirect_uri = redirect_uris.with(|uris| {
+            uris.iter()
+                .find(|(_, result)| result.is_ok())
+                .map(|(uri, _)| uri.clone())
+        })?;
+        data.with(|data| {
+            let value = |key: &str| {
+                data.value::<String>(key)
+                    .map(|value| value.trim().to_string())
+                    .filter(|value| !value.is_empty())
+            };
+
+            Some(OidcTestRequest {
+                issuer_url: issuer.get()?,
+                client_id: value("client-id")?,
+                client_secret: value("client-secret"),
+                authorization_endpoint: value("endpoint.authorization")?,
+                token_endpoint: value("endpoint.token")?,
+                userinfo_endpoint: value("endpoint.url"),
+                redirect_uri,
+                scopes: data.array_value("scopes").map(|s| s.to_string()).collect(),
+                username_claim: value("fields.username"),
+                email_claim: value("fields.email"),
+            })
+        })
+    });
+
+    view! {
+        <FormSection
+            title="Setup wizard".to_string()
+            hide=Signal::derive(move || {
+                data.with(|data| data.value::<String>("type").as_deref() != Some("oidc"))
+            })
+        >
+
+            <FormItem label="Discovery" tooltip="Fill in the endpoints from the provider's discovery document">
+                <div class="flex items-center gap-x-2">
+                    <Button
+                        text="Discover endpoints"
+                        color=Color::Gray
+                        on_click=move |_| {
+                            if let Some(issuer) = issuer.get() {
+                                discover_action.dispatch(issuer);
+                            }
+                        }
+
+                        disabled=Signal::derive(move || {
+                            issuer.get().is_none() || discover_action.pending().get()
+                        })
+                    />
+
+                    <Show when=move || issuer.get().is_none()>
+                        <span class="text-sm text-gray-500">Enter the issuer URL first.</span>
+                    </Show>
+                </div>
+                {move || {
+                    discovery
+                        .get()
+                        .map(|document| {
+                            let issuer_matches = issuer
+                                .get()
+                                .is_some_and(|issuer| {
+                                    issuer == document.issuer.trim_end_matches('/')
+                                });
+                            let warnings = [
+                                (!issuer_matches)
+                                    .then(|| {
+                                        format!(
+                                            "The provider identifies itself as {:?}, which does not match the issuer URL.",
+                                            document.issuer,
+                                        )
+                                    }),
+                                (!document.response_types_supported.is_empty()
+                                    && !document.response_types_supported.iter().any(|t| t == "code"))
+                                    .then(|| {
+                                        "The provider does not support the authorization code flow."
+                                            .to_string()
+                                    }),
+                                document
+                                    .userinfo_endpoint
+                                    .is_none()
+                                    .then(|| {
+                                        "The provider does not publish a user info endpoint, enter it manually."
+                                            .to_string()
+                                    }),
+                            ]
+                                .into_iter()
+                                .flatten()
+                                .collect::<Vec<_>>();
+                            view! {
+                                <ul class="mt-3 space-y-1 text-sm">
+                                    {warnings
+                                        .into_iter()
+                                        .map(|warning| {
+                                            view! {
+                                                <li class="flex items-center gap-x-2 text-gray-800 dark:text-gray-200">
+                                                    <Badge color=Color::Yellow>Warning</Badge>
+                                                    {warning}
+                                                </li>
+                                            }
+                                        })
+                                        .collect_view()}
+                                    <li class="text-gray-500">
+                                        {format!(
+                                            "Supported scopes: {}",
+                                            if document.scopes_supported.is_empty() {
+                                                "not advertised".to_string()
+                                            } else {
+                                                document.scopes_supported.join(", ")
+                                            },
+                                        )}
+
+                                    </li>
+                                </ul>
+                            }
+                        })
+                }}
+
+            </FormItem>
+
+            <FormItem label="Redirect URIs" tooltip="Checks the redirect URIs entered below">
+                {move || {
+                    let uris = redirect_uris.get();
+                    if uris.is_empty() {
+                        return view! {
+                            <p class="text-sm text-gray-500">No redirect URIs configured.</p>
+                        }
+                            .into_view();
+                    }
+                    view! {
+                        <ul class="space-y-1 text-sm">
+                            {uris
+                                .into_iter()
+                                .map(|(uri, result)| {
+                                    let (color, status) = match result {
+                                        Ok(_) => (Color::Green, "Valid"),
+                                        Err(reason) => (Color::Red, reason),
+                                    };
+                                    view! {
+                                        <li class="flex items-center gap-x-2">
+                                            <Badge color=color>{status}</Badge>
+                                            <span class="font-mono text-gray-800 dark:text-gray-200 break-all">
+                                                {uri}
+                                            </span>
+                                        </li>
+                                    }
+                                })
+                                .collect_view()}
+                        </ul>
+                    }
+                        .into_view()
+                }}
+
+            </FormItem>
+
+            <FormItem label="Test login" tooltip="Log in with the provider using the settings above">
+                <div class="flex items-center gap-x-2">
+                    <Button
+                        text="Test login"
+                        color=Color::Blue
+                        on_click=move |_| {
+                            if let Some(request) = test_request.get() {
+                                test_action.dispatch(request);
+                            }
+                        }
+
+                        disabled=Signal::derive(move || {
+                            test_request.with(|request| request.is_none())
+                                || test_action.pending().get()
+                                || matches!(test_state.get(), TestState::Waiting { .. })
+                        })
+                    />
+
+                    <Show when=move || matches!(test_state.get(), TestState::Waiting { .. })>
+                        <span class="text-sm text-gray-500">
+                            Waiting for the login to complete...
+                        </span>
+                        <Button
+                            text="Cancel"
+                            color=Color::Gray
+                            on_click=move |_| {
+                                stop_test();
+                                test_state.set(TestState::Idle);
+                            }
+                        />
+
+                    </Show>
+                    <Show when=move || test_request.with(|request| request.is_none())>
+                        <span class="text-sm text-gray-500">
+                            Complete the issuer, client, endpoints and redirect URIs first.
+                        </span>
+                    </Show>
+                </div>
+                {move || match test_state.get() {
+                    TestState::Done(OidcTestStatus::Success { username, email, claims }) => {
+                        let claims = claims
+                            .into_iter()
+                            .map(|(name, value)| format!("{name}: {value}"))
+                            .collect::<Vec<_>>()
+                            .join("\n");
+                        Some(
+                            view! {
+                                <div class="mt-3 space-y-2">
+                                    <div class="flex items-center gap-x-2 text-sm text-gray-800 dark:text-gray-200">
+                                        <Badge color=Color::Green>Success</Badge>
+                                        {format!(
+                                            "Logged in as {} ({})",
+                                            username.as_deref().unwrap_or("unknown user"),
+                                            email.as_deref().unwrap_or("no e-mail"),
+                                        )}
+
+                                    </div>
+                                    {(!claims.is_empty())
+                                        .then(|| view! { <pre class=CODE_CLASS>{claims}</pre> })}
+                                </div>
+                            }
+                                .into_view(),
+                        )
+                    }
+                    TestState::Done(OidcTestStatus::Failed { reason }) => {
+                        Some(
+                            view! {
+                                <div class="mt-3 flex items-center gap-x-2 text-sm text-gray-800 dark:text-gray-200">
+                                    <Badge color=Color::Red>Failed</Badge>
+                                    {reason}
+                                </div>
+                            }
+                                .into_view(),
+                        )
+                    }
+                    _ => None,
+                }}
+
+            </FormItem>
+        </FormSection>
+    }
+}
+
+fn validate_redirect_uri(uri: &str) -> Result<(), &'static str> {
+    let Some((scheme, rest)) = uri.split_once("://") else {
+        return Err("Not an absolute URI");
+    };
+    if uri.contains('#') {
+        return Err("Fragments are not allowed");
+    }
+    if uri.contains('*') {
+        return Err("Wildcards are not allowed");
+    }
+    let authority = rest.split(['/', '?']).next().unwrap_or_default();
+    let host = if let Some(host) = authority.strip_prefix('[') {
+        host.split(']').next().unwrap_or_default()
+    } else {
+        authority
+            .rsplit('@')
+            .next()
+            .unwrap_or_default()
+            .split(':')
+            .next()
+            .unwrap_or_default()
+    };
+    if host.is_empty() {
+        return Err("Missing host");
+    }
+    match scheme.to_ascii_lowercase().as_str() {
+        "https" => Ok(()),
+        "http" if matches!(host, "localhost" | "127.0.0.1" | "::1") => Ok(()),
+        "http" => Err("HTTPS is required outside localhost"),
+        _ => Err("Unsupported scheme"),
+    }
+}
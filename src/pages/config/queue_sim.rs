@@ -0,0 +1,224 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        form::{button::Button, input::InputText, FormElement, FormItem, FormSection},
+        list::table::{Table, TableRow},
+        messages::alert::{use_alerts, Alert},
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        schema::{Builder, Schemas, Transformer, Type, Validator},
+    },
+};
+
+/// Settings pages whose expressions are evaluated by the simulator.
+pub const QUEUE_SIMULATOR_SCHEMAS: &[&str] = &[
+    "smtp-out-queue",
+    "smtp-out-routing",
+    "smtp-out-limits",
+    "smtp-out-tls",
+];
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct SimulatedMessage {
+    sender: String,
+    rcpt: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    remote_ip: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct EvaluatedSetting {
+    key: String,
+    #[serde(default)]
+    value: Option<String>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[component]
+pub fn QueueSimulator() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let schemas = expect_context::<Arc<Schemas>>();
+    let data = schemas.build_form("queue-simulator").into_signal();
+    let results = create_rw_signal(None::<Vec<EvaluatedSetting>>);
+
+    let simulate = create_action(move |message: &SimulatedMessage| {
+        let auth = auth.get();
+        let message = message.clone();
+
+        async move {
+            match HttpRequest::post("/api/troubleshoot/queue")
+                .with_authorization(&auth)
+                .with_body(message)
+                .unwrap()
+                .send::<Vec<EvaluatedSetting>>()
+                .await
+            {
+                Ok(evaluated) => {
+                    results.set(Some(evaluated));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(http::Error::NotFound) => {
+                    alert.set(Alert::warning(
+                        "This server does not support queue simulations.",
+                    ));
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    view! {
+        <FormSection title="Simulator".to_string()>
+            <FormItem
+                label="Sender"
+                tooltip="Envelope sender of the hypothetical message, leave empty for a bounce"
+                is_optional=true
+            >
+                <InputText
+                    element=FormElement::new("sender", data)
+                    placeholder="jane@example.org"
+                />
+            </FormItem>
+            <FormItem label="Recipient" tooltip="Envelope recipient of the hypothetical message">
+                <InputText element=FormElement::new("rcpt", data) placeholder="john@example.com"/>
+            </FormItem>
+            <FormItem
+                label="Source IP"
+                tooltip="IP address the message was received from"
+                is_optional=true
+            >
+                <InputText element=FormElement::new("remote_ip", data) placeholder="192.0.2.1"/>
+            </FormItem>
+            <FormItem label="">
+                <Button
+                    text="Simulate"
+                    color=Color::Gray
+                    on_click=Callback::new(move |_| {
+                        let message = data
+                            .try_update(|data| {
+                                data.validate_form()
+                                    .then(|| SimulatedMessage {
+                                        sender: data.value::<String>("sender").unwrap_or_default(),
+                                        rcpt: data.value::<String>("rcpt").unwrap_or_default(),
+                                        remote_ip: data
+                                            .value::<String>("remote_ip")
+                                            .filter(|ip| !ip.is_empty()),
+                                    })
+                            })
+                            .flatten();
+                        if let Some(message) = message {
+                            simulate.dispatch(message);
+                        }
+                    })
+
+                    disabled=simulate.pending()
+                />
+            </FormItem>
+
+            {move || {
+                results
+                    .get()
+                    .map(|results| {
+                        let schemas = schemas.clone();
+                        view! {
+                            <div class="sm:col-span-12">
+                                <Table headers=vec![
+                                    "Strategy".to_string(),
+                                    "Setting".to_string(),
+                                    "Result".to_string(),
+                                ]>
+                                    {results
+                                        .into_iter()
+                                        .map(|result| {
+                                            let (value, class) = match (result.value, result.error) {
+                                                (_, Some(error)) => (error, "text-red-600 dark:text-red-500"),
+                                                (Some(value), None) => (value, ""),
+                                                (None, None) => ("Not set".to_string(), "text-gray-500"),
+                                            };
+                                            let strategy = strategy_name(&result.key);
+                                            let setting = setting_label(&schemas, &result.key);
+                                            view! {
+                                                <TableRow>
+                                                    <span>{strategy}</span>
+                                                    <span>{setting}</span>
+                                                    <span class=class>{value}</span>
+                                                </TableRow>
+                                            }
+                                        })
+                                        .collect_view()}
+                                </Table>
+                            </div>
+                        }
+                    })
+            }}
+
+        </FormSection>
+    }
+}
+
+fn strategy_name(key: &str) -> &'static str {
+    if key.starts_with("queue.schedule.") {
+        "Schedule"
+    } else if key.starts_with("queue.outbound.tls.") {
+        "TLS"
+    } else if key.starts_with("queue.outbound.limits.")
+        || key.starts_with("queue.outbound.timeouts.")
+    {
+        "Connection"
+    } else if key.starts_with("queue.outbound.") {
+        "Routing"
+    } else {
+        "Other"
+    }
+}
+
+fn setting_label(schemas: &Schemas, key: &str) -> String {
+    QUEUE_SIMULATOR_SCHEMAS
+        .iter()
+        .filter_map(|id| schemas.schemas.get(id))
+        .find_map(|schema| schema.fields.get(key))
+        .map(|field| field.label_form.to_string())
+        .filter(|label| !label.is_empty())
+        .unwrap_or_else(|| key.to_string())
+}
+
+impl Builder<Schemas, ()> {
+    pub fn build_queue_simulator(self) -> Self {
+        self.new_schema("queue-simulator")
+            .new_field("sender")
+            .typ(Type::Input)
+            .input_check(
+                [Transformer::Trim, Transformer::Lowercase],
+                [Validator::IsEmail],
+            )
+            .new_field("rcpt")
+            .input_check(
+                [Transformer::Trim, Transformer::Lowercase],
+                [Validator::Required, Validator::IsEmail],
+            )
+            .new_field("remote_ip")
+            .input_check([Transformer::Trim], [Validator::IsIpOrMask])
+            .build()
+            .build()
+    }
+}
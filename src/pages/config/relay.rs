@@ -0,0 +1,251 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{button::Button, FormItem, FormSection},
+        icon::IconPaperAirplane,
+        messages::alert::{use_alerts, Alert},
+        Color,
+    },
+    core::{
+        form::FormData,
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        permissions::Capability,
+    },
+};
+
+pub const REMOTE_HOST_SCHEMA: &str = "smtp-out-remote";
+
+const INPUT_CLASS: &str = "py-2 px-3 block w-full border-gray-200 shadow-sm text-sm rounded-lg focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600";
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+struct RelayTestRequest {
+    from: String,
+    to: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct RelayTestResult {
+    #[serde(default)]
+    success: bool,
+    #[serde(default)]
+    transcript: Vec<String>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+/// Credential management for a remote host: shows the stored secret masked,
+/// lets it be replaced without revealing the old one, and asks the server to
+/// relay a probe message through the host to verify the configuration.
+#[component]
+pub fn RelayHostTools(data: RwSignal<FormData>) -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let new_secret = create_rw_signal(None::<String>);
+    let probe_from = create_rw_signal(String::new());
+    let probe_to = create_rw_signal(String::new());
+    let result = create_rw_signal(None::<RelayTestResult>);
+
+    let masked_secret = create_memo(move |_| {
+        data.with(|data| {
+            data.value_as_str("auth.secret")
+                .filter(|secret| !secret.is_empty())
+                .map(mask_secret)
+        })
+    });
+
+    let test_action = create_action(move |request: &RelayTestRequest| {
+        let auth = auth.get();
+        let request = request.clone();
+        let id = data.with_untracked(|data| data.value::<String>("_id").unwrap_or_default());
+
+        async move {
+            match HttpRequest::post(format!("/api/smtp/remote/{id}/test"))
+                .with_authorization(&auth)
+                .with_body(request)
+                .unwrap()
+                .send::<RelayTestResult>()
+                .await
+            {
+                Ok(test) => {
+                    result.set(Some(test));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(http::Error::NotFound) => {
+                    alert.set(Alert::warning(
+                        "This server does not support sending test messages through a relay host.",
+                    ));
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    view! {
+        <FormSection title="Credentials".to_string()>
+            <FormItem label="Stored secret">
+                <div class="pt-2.5 flex items-center gap-x-3 text-sm text-gray-600 dark:text-gray-400">
+                    <span class="font-mono">
+                        {move || masked_secret.get().unwrap_or_else(|| "Not set".to_string())}
+                    </span>
+                    <Show when=move || new_secret.get().is_none()>
+                        <button
+                            type="button"
+                            class="text-sm text-blue-600 decoration-2 hover:underline font-medium dark:text-blue-500"
+                            on:click=move |_| new_secret.set(Some(String::new()))
+                        >
+                            Rotate
+                        </button>
+                    </Show>
+                </div>
+            </FormItem>
+            <Show when=move || new_secret.get().is_some()>
+                <FormItem
+                    label="New secret"
+                    tooltip="The new secret replaces the stored one once the settings are saved"
+                >
+                    <div class="flex items-center gap-x-2">
+                        <input
+                            type="password"
+                            class=INPUT_CLASS
+                            autocomplete="new-password"
+                            prop:value=move || new_secret.get().unwrap_or_default()
+                            on:input=move |ev| new_secret.set(Some(event_target_value(&ev)))
+                        />
+                        <Button
+                            text="Apply"
+                            color=Color::Gray
+                            on_click=Callback::new(move |_| {
+                                let secret = new_secret.get().unwrap_or_default();
+                                if !secret.is_empty() {
+                                    data.update(|data| data.update("auth.secret", secret));
+                                    new_secret.set(None);
+                                    alert
+                                        .set(
+                                            Alert::warning(
+                                                "Secret replaced, save the changes to store it.",
+                                            ),
+                                        );
+                                }
+                            })
+                        />
+
+                        <button
+                            type="button"
+                            class="text-sm text-gray-600 decoration-2 hover:underline font-medium dark:text-gray-400"
+                            on:click=move |_| new_secret.set(None)
+                        >
+                            Cancel
+                        </button>
+                    </div>
+                </FormItem>
+            </Show>
+        </FormSection>
+
+        <FormSection title="Test message".to_string()>
+            <FormItem label="Sender" tooltip="Envelope sender of the probe message">
+                <input
+                    type="text"
+                    class=INPUT_CLASS
+                    placeholder="postmaster@example.org"
+                    prop:value=move || probe_from.get()
+                    on:input=move |ev| probe_from.set(event_target_value(&ev))
+                />
+            </FormItem>
+            <FormItem label="Recipient" tooltip="Address the probe message is relayed to">
+                <input
+                    type="text"
+                    class=INPUT_CLASS
+                    placeholder="john@example.com"
+                    prop:value=move || probe_to.get()
+                    on:input=move |ev| probe_to.set(event_target_value(&ev))
+                />
+            </FormItem>
+            <FormItem label="">
+                <Button
+                    text="Send test message"
+                    color=Color::Gray
+                    on_click=Callback::new(move |_| {
+                        let request = RelayTestRequest {
+                            from: probe_from.get().trim().to_string(),
+                            to: probe_to.get().trim().to_string(),
+                        };
+                        if data.with_untracked(|data| data.is_dirty()) {
+                            alert
+                                .set(
+                                    Alert::warning(
+                                        "Save the changes before sending a test message, the stored configuration is used.",
+                                    ),
+                                );
+                        } else if request.from.is_empty() || request.to.is_empty() {
+                            alert.set(Alert::warning("Enter a sender and a recipient address."));
+                        } else {
+                            test_action.dispatch(request);
+                        }
+                    })
+
+                    disabled=test_action.pending()
+                    capability=Capability::RelayTest
+                >
+                    <IconPaperAirplane/>
+                </Button>
+            </FormItem>
+            {move || {
+                result
+                    .get()
+                    .map(|test| {
+                        let (color, label) = if test.success {
+                            (Color::Green, "Relayed")
+                        } else {
+                            (Color::Red, "Failed")
+                        };
+                        view! {
+                            <FormItem label="Result">
+                                <div class="pt-2.5 text-sm text-gray-600 dark:text-gray-400">
+                                    <Badge color=color>{label}</Badge>
+                                    {test
+                                        .error
+                                        .map(|error| {
+                                            view! {
+                                                <p class="mt-1 text-red-600 dark:text-red-500">
+                                                    {error}
+                                                </p>
+                                            }
+                                        })}
+
+                                </div>
+                            </FormItem>
+                            <FormItem label="Transcript">
+                                <pre class="p-3 text-xs whitespace-pre-wrap bg-gray-50 border border-gray-200 rounded-lg text-gray-800 dark:bg-slate-800 dark:border-gray-700 dark:text-gray-200">
+                                    {test.transcript.join("\n")}
+                                </pre>
+                            </FormItem>
+                        }
+                    })
+            }}
+
+        </FormSection>
+    }
+}
+
+/// Hides all but the last characters of a secret.
+fn mask_secret(secret: &str) -> String {
+    let len = secret.chars().count();
+    let visible = if len > 8 { 4 } else { 0 };
+    let tail = secret.chars().skip(len - visible).collect::<String>();
+    format!("{}{tail}", "•".repeat(8))
+}
@@ -0,0 +1,277 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use leptos::*;
+
+use crate::{
+    components::{
+        badge::Badge,
+        messages::modal::{Modal, ModalStep},
+        Color,
+    },
+    pages::config::{
+        search::setting_link, ConfigError, ConfigWarning, ReloadSettings, Schemas, UpdateSettings,
+    },
+};
+
+/// Friendly names for the top-level configuration prefixes.
+const SUBSYSTEMS: &[(&str, &str)] = &[
+    ("acme", "ACME"),
+    ("auth", "Mail authentication"),
+    ("authentication", "Authentication"),
+    ("certificate", "TLS certificates"),
+    ("cluster", "Cluster"),
+    ("directory", "Directories"),
+    ("http-lookup", "HTTP lists"),
+    ("imap", "IMAP"),
+    ("jmap", "JMAP"),
+    ("lookup", "Lookups"),
+    ("metrics", "Metrics"),
+    ("queue", "Outbound queue"),
+    ("report", "Reporting"),
+    ("server", "Server"),
+    ("session", "SMTP sessions"),
+    ("sieve", "Sieve"),
+    ("spam-filter", "SPAM filter"),
+    ("storage", "Storage"),
+    ("store", "Stores"),
+    ("tracer", "Tracing"),
+    ("webhook", "Webhooks"),
+];
+
+#[derive(Clone, Debug, Default)]
+pub struct ReloadReport {
+    reconfigured: BTreeSet<&'static str>,
+    errors: Vec<ReportEntry>,
+    own_warnings: Vec<ReportEntry>,
+    other_warnings: Vec<ReportEntry>,
+}
+
+#[derive(Clone, Debug)]
+struct ReportEntry {
+    subsystem: &'static str,
+    key: String,
+    message: String,
+    link: Option<(String, String)>,
+}
+
+impl ReloadReport {
+    /// Classifies the reload result, separating the warnings caused by the
+    /// keys that were just changed from the ones that were already there.
+    pub fn new(result: &ReloadSettings, changes: &[UpdateSettings], schemas: &Schemas) -> Self {
+        let changed = changes
+            .iter()
+            .flat_map(|change| match change {
+                UpdateSettings::Delete { keys } => keys.clone(),
+                UpdateSettings::Clear { prefix } => vec![prefix.clone()],
+                UpdateSettings::Insert { prefix, values, .. } => values
+                    .iter()
+                    .map(|(key, _)| match prefix {
+                        Some(prefix) => format!("{prefix}.{key}"),
+                        None => key.clone(),
+                    })
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+        let is_changed = |key: &str| {
+            changed.iter().any(|changed| {
+                key == changed
+                    || key
+                        .strip_prefix(changed.as_str())
+                        .map_or(false, |rest| rest.starts_with('.'))
+                    || changed
+                        .strip_prefix(key)
+                        .map_or(false, |rest| rest.starts_with('.'))
+            })
+        };
+        let entry = |key: &String, message: String| ReportEntry {
+            subsystem: subsystem(key),
+            key: key.clone(),
+            message,
+            link: setting_link(schemas, key),
+        };
+
+        let mut report = ReloadReport {
+            reconfigured: changed.iter().map(|key| subsystem(key)).collect(),
+            ..Default::default()
+        };
+        for (key, error) in &result.errors {
+            report.errors.push(entry(key, error_message(error)));
+        }
+        for (key, warning) in &result.warnings {
+            let item = entry(key, warning_message(warning));
+            if is_changed(key) {
+                report.own_warnings.push(item);
+            } else {
+                report.other_warnings.push(item);
+            }
+        }
+        report
+    }
+
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    /// Whether the report contains anything the user should act on.
+    pub fn needs_attention(&self) -> bool {
+        !self.errors.is_empty() || !self.own_warnings.is_empty()
+    }
+
+    pub fn into_modal(self, modal: RwSignal<Modal>) -> Modal {
+        let (title, message) = if self.has_errors() {
+            (
+                "Failed to reload settings",
+                "Your changes were saved but the configuration could not be reloaded.",
+            )
+        } else {
+            (
+                "Settings reloaded with warnings",
+                "Your changes were saved and applied, please review the warnings below.",
+            )
+        };
+        let report = store_value(self);
+
+        Modal::with_title(title).with_step(
+            ModalStep::new(title)
+                .with_message(message)
+                .with_button("OK")
+                .with_content(move || {
+                    report.with_value(|report| {
+                        let reconfigured = report
+                            .reconfigured
+                            .iter()
+                            .copied()
+                            .map(|name| view! { <Badge color=Color::Blue>{name}</Badge> })
+                            .collect_view();
+                        view! {
+                            <div class="mt-4 flex flex-col gap-4 text-sm">
+                                <div class="flex flex-wrap items-center gap-2">
+                                    <span class="text-gray-600 dark:text-gray-400">
+                                        Reconfigured:
+                                    </span>
+                                    {reconfigured}
+                                </div>
+                                <ReportGroup
+                                    title="Errors"
+                                    color=Color::Red
+                                    entries=report.errors.clone()
+                                    modal=modal
+                                />
+                                <ReportGroup
+                                    title="Warnings caused by your changes"
+                                    color=Color::Yellow
+                                    entries=report.own_warnings.clone()
+                                    modal=modal
+                                />
+                                <ReportGroup
+                                    title="Other warnings"
+                                    color=Color::Gray
+                                    entries=report.other_warnings.clone()
+                                    modal=modal
+                                />
+                            </div>
+                        }
+                    })
+                }),
+        )
+    }
+}
+
+#[component]
+fn ReportGroup(
+    title: &'static str,
+    color: Color,
+    entries: Vec<ReportEntry>,
+    modal: RwSignal<Modal>,
+) -> impl IntoView {
+    if entries.is_empty() {
+        return None;
+    }
+
+    let mut groups: BTreeMap<&'static str, Vec<ReportEntry>> = BTreeMap::new();
+    for entry in entries {
+        groups.entry(entry.subsystem).or_default().push(entry);
+    }
+
+    Some(view! {
+        <div class="flex flex-col gap-2">
+            <div class="flex items-center gap-x-2">
+                <h4 class="font-semibold text-gray-800 dark:text-gray-200">{title}</h4>
+            </div>
+            {groups
+                .into_iter()
+                .map(|(subsystem, entries)| {
+                    view! {
+                        <div class="ps-2 border-s-2 border-gray-200 dark:border-gray-700">
+                            <div class="mb-1">
+                                <Badge color=color>{subsystem}</Badge>
+                            </div>
+                            <ul class="flex flex-col gap-1">
+                                {entries
+                                    .into_iter()
+                                    .map(|entry| {
+                                        let link = entry
+                                            .link
+                                            .map(|(title, url)| {
+                                                view! {
+                                                    <a
+                                                        href=url
+                                                        class="text-blue-600 decoration-2 hover:underline font-medium dark:text-blue-500"
+                                                        on:click=move |_| {
+                                                            modal.update(|modal| modal.is_open = false)
+                                                        }
+                                                    >
+
+                                                        {title}
+                                                    </a>
+                                                }
+                                            });
+                                        view! {
+                                            <li class="text-gray-600 dark:text-gray-400">
+                                                <span class="font-mono text-gray-800 dark:text-gray-200">
+                                                    {entry.key}
+                                                </span>
+                                                {format!(": {} ", entry.message)}
+                                                {link}
+                                            </li>
+                                        }
+                                    })
+                                    .collect_view()}
+                            </ul>
+                        </div>
+                    }
+                })
+                .collect_view()}
+        </div>
+    })
+}
+
+fn subsystem(key: &str) -> &'static str {
+    let prefix = key.split('.').next().unwrap_or_default();
+    SUBSYSTEMS
+        .iter()
+        .find(|(id, _)| *id == prefix)
+        .map(|(_, name)| *name)
+        .unwrap_or("Other")
+}
+
+fn error_message(error: &ConfigError) -> String {
+    match error {
+        ConfigError::Parse { error } => format!("parse error, {error}"),
+        ConfigError::Build { error } => format!("build error, {error}"),
+        ConfigError::Macro { error } => format!("macro error, {error}"),
+    }
+}
+
+fn warning_message(warning: &ConfigWarning) -> String {
+    match warning {
+        ConfigWarning::Missing => "missing setting".to_string(),
+        ConfigWarning::AppliedDefault { default } => format!("applied default {default:?}"),
+    }
+}
@@ -0,0 +1,266 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{button::Button, input::InputText, FormElement, FormItem, FormSection},
+        list::table::{Table, TableRow},
+        messages::alert::{use_alerts, Alert},
+        Color,
+    },
+    core::{
+        dns::{doh_lookup, RCODE_NO_ERROR},
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        schema::{Builder, Schemas, Transformer, Type, Validator},
+    },
+};
+
+/// Settings pages holding aggregate report options, with the report type
+/// each one configures.
+pub const REPORT_PREVIEW_SCHEMAS: &[(&str, ReportType)] = &[
+    ("dmarc", ReportType::Dmarc),
+    ("smtp-out-tls", ReportType::Tls),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportType {
+    Dmarc,
+    Tls,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+struct PreviewRequest {
+    #[serde(rename = "type")]
+    typ: ReportType,
+    domain: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+struct EvaluatedSetting {
+    key: String,
+    #[serde(default)]
+    value: Option<String>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Preview {
+    recipients: Result<Vec<String>, String>,
+    settings: Vec<EvaluatedSetting>,
+}
+
+/// Evaluates the aggregate report expressions for a sample domain and looks
+/// up the reporting addresses it publishes, showing which report would be
+/// generated and who would receive it.
+#[component]
+pub fn ReportPreview(report_type: ReportType) -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let schemas = expect_context::<Arc<Schemas>>();
+    let data = schemas.build_form("report-preview").into_signal();
+    let preview = create_rw_signal(None::<Preview>);
+
+    let evaluate = create_action(move |domain: &String| {
+        let auth = auth.get();
+        let domain = domain.clone();
+
+        async move {
+            let settings = match HttpRequest::post("/api/troubleshoot/report")
+                .with_authorization(&auth)
+                .with_body(PreviewRequest {
+                    typ: report_type,
+                    domain: domain.clone(),
+                })
+                .unwrap()
+                .send::<Vec<EvaluatedSetting>>()
+                .await
+            {
+                Ok(settings) => settings,
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                    return;
+                }
+                Err(http::Error::NotFound) => {
+                    alert.set(Alert::warning(
+                        "This server does not support report previews.",
+                    ));
+                    return;
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                    return;
+                }
+            };
+
+            preview.set(Some(Preview {
+                recipients: lookup_recipients(report_type, &domain).await,
+                settings,
+            }));
+        }
+    });
+
+    let report_name = match report_type {
+        ReportType::Dmarc => "DMARC",
+        ReportType::Tls => "TLS",
+    };
+
+    view! {
+        <FormSection title="Report preview".to_string()>
+            <FormItem
+                label="Domain"
+                tooltip="Domain whose aggregate reports should be previewed"
+            >
+                <InputText element=FormElement::new("domain", data) placeholder="example.com"/>
+            </FormItem>
+            <FormItem label="">
+                <Button
+                    text="Preview"
+                    color=Color::Gray
+                    on_click=Callback::new(move |_| {
+                        let domain = data
+                            .try_update(|data| {
+                                data.validate_form()
+                                    .then(|| data.value::<String>("domain").unwrap_or_default())
+                            })
+                            .flatten();
+                        if let Some(domain) = domain {
+                            evaluate.dispatch(domain);
+                        }
+                    })
+
+                    disabled=evaluate.pending()
+                />
+            </FormItem>
+
+            {move || {
+                preview
+                    .get()
+                    .map(|preview| {
+                        let value = |suffix: &str| {
+                            preview
+                                .settings
+                                .iter()
+                                .find(|setting| setting.key.ends_with(suffix))
+                                .and_then(|setting| setting.value.clone())
+                        };
+                        let frequency = value(".aggregate.send").unwrap_or_default();
+                        let from = value(".aggregate.from-address").unwrap_or_default();
+                        let (color, summary) = match (&preview.recipients, frequency.as_str()) {
+                            (_, "" | "disable" | "false") => {
+                                (
+                                    Color::Gray,
+                                    format!("{report_name} aggregate reports are disabled."),
+                                )
+                            }
+                            (Ok(recipients), _) if !recipients.is_empty() => {
+                                (
+                                    Color::Green,
+                                    format!(
+                                        "A {frequency} {report_name} aggregate report would be sent from {from} to {}.",
+                                        recipients.join(", "),
+                                    ),
+                                )
+                            }
+                            (Ok(_), _) => {
+                                (
+                                    Color::Yellow,
+                                    format!(
+                                        "No report would be sent, the domain does not publish a {report_name} reporting address.",
+                                    ),
+                                )
+                            }
+                            (Err(err), _) => {
+                                (
+                                    Color::Yellow,
+                                    format!("Reporting addresses could not be looked up: {err}"),
+                                )
+                            }
+                        };
+                        view! {
+                            <div class="sm:col-span-12 flex flex-col gap-4">
+                                <div class="flex items-center gap-x-2">
+                                    <Badge color=color>{report_name}</Badge>
+                                    <span class="text-sm text-gray-800 dark:text-gray-200">
+                                        {summary}
+                                    </span>
+                                </div>
+                                <Table headers=vec!["Setting".to_string(), "Result".to_string()]>
+                                    {preview
+                                        .settings
+                                        .into_iter()
+                                        .map(|setting| {
+                                            let (value, class) = match (setting.value, setting.error) {
+                                                (_, Some(error)) => (error, "text-red-600 dark:text-red-500"),
+                                                (Some(value), None) => (value, ""),
+                                                (None, None) => ("Not set".to_string(), "text-gray-500"),
+                                            };
+                                            view! {
+                                                <TableRow>
+                                                    <span class="font-mono">{setting.key}</span>
+                                                    <span class=class>{value}</span>
+                                                </TableRow>
+                                            }
+                                        })
+                                        .collect_view()}
+                                </Table>
+                            </div>
+                        }
+                    })
+            }}
+
+        </FormSection>
+    }
+}
+
+/// Returns the aggregate report destinations published by a domain.
+async fn lookup_recipients(report_type: ReportType, domain: &str) -> Result<Vec<String>, String> {
+    let name = match report_type {
+        ReportType::Dmarc => format!("_dmarc.{domain}"),
+        ReportType::Tls => format!("_smtp._tls.{domain}"),
+    };
+    let response = doh_lookup(&name, "TXT").await?;
+    if response.status != RCODE_NO_ERROR {
+        return Ok(Vec::new());
+    }
+
+    Ok(response
+        .txt_records()
+        .iter()
+        .flat_map(|record| record.split(';'))
+        .filter_map(|tag| tag.split_once('='))
+        .filter(|(name, _)| name.trim().eq_ignore_ascii_case("rua"))
+        .flat_map(|(_, value)| value.split(','))
+        .map(|uri| {
+            let uri = uri.trim();
+            uri.strip_prefix("mailto:").unwrap_or(uri).to_string()
+        })
+        .filter(|uri| !uri.is_empty())
+        .collect())
+}
+
+impl Builder<Schemas, ()> {
+    pub fn build_report_preview(self) -> Self {
+        self.new_schema("report-preview")
+            .new_field("domain")
+            .typ(Type::Input)
+            .input_check(
+                [Transformer::Trim, Transformer::Lowercase],
+                [Validator::Required, Validator::IsDomain],
+            )
+            .build()
+            .build()
+    }
+}
@@ -0,0 +1,308 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::*;
+use regex::Regex;
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{FormItem, FormSection},
+        Color,
+    },
+    core::{
+        expr::{quote_string, unquote_string},
+        form::{Expression, ExpressionIfThen, FormData, FormValue},
+    },
+};
+
+pub const SMTP_IN_MAIL_SCHEMA: &str = "smtp-in-mail";
+pub const SMTP_IN_RCPT_SCHEMA: &str = "smtp-in-rcpt";
+
+const INPUT_CLASS: &str = "py-2 px-3 block w-full border-gray-200 shadow-sm text-sm rounded-lg focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct RewriteRule {
+    pattern: String,
+    replacement: String,
+}
+
+/// Table of `pattern → rewrite` rules that is compiled into the sender or
+/// recipient rewriting expression, along with a tester that shows how an
+/// address is transformed by the rules.
+#[component]
+pub fn AddressRewriteRules(
+    data: RwSignal<FormData>,
+    field: &'static str,
+    variable: &'static str,
+) -> impl IntoView {
+    let parsed = data.with_untracked(|data| parse_rules(data, field, variable));
+    let is_custom = create_rw_signal(parsed.is_none());
+    let rules = create_rw_signal(parsed.unwrap_or_default());
+    let test_address = create_rw_signal(String::new());
+
+    // Keep the stored expression in sync with the rules
+    create_effect(move |prev: Option<()>| {
+        let expr =
+            (!is_custom.get()).then(|| rules.with(|rules| build_expression(rules, variable)));
+        if prev.is_some() {
+            if let Some(expr) = expr {
+                data.update(|data| data.update(field, FormValue::Expression(expr)));
+            }
+        }
+    });
+
+    let test_result = create_memo(move |_| {
+        let address = test_address.get().trim().to_lowercase();
+        (!address.is_empty()).then(|| rules.with(|rules| apply_rules(rules, &address)))
+    });
+
+    view! {
+        <FormSection title="Rewriting rules".to_string()>
+            <Show
+                when=move || !is_custom.get()
+                fallback=move || {
+                    view! {
+                        <FormItem label="Rules">
+                            <p class="mt-2.5 text-sm text-gray-500">
+                                The rewriting expression was written by hand and cannot be edited as a list of rules.
+                            </p>
+                            <button
+                                type="button"
+                                class="mt-2 text-sm text-blue-600 decoration-2 hover:underline font-medium dark:text-blue-500"
+                                on:click=move |_| {
+                                    rules.set(Vec::new());
+                                    is_custom.set(false);
+                                }
+                            >
+
+                                Replace with rules
+                            </button>
+                        </FormItem>
+                    }
+                }
+            >
+
+                <FormItem
+                    label="Rules"
+                    tooltip="Addresses are matched against each pattern in order, capture groups can be used in the rewrite as $1, $2, etc."
+                >
+                    <div class="space-y-2">
+                        <For
+                            each=move || 0..rules.with(|rules| rules.len())
+                            key=|idx| *idx
+                            let:idx
+                        >
+                            <RuleRow rules=rules idx=idx/>
+                        </For>
+                    </div>
+                    <button
+                        type="button"
+                        class="mt-2 text-sm text-blue-600 decoration-2 hover:underline font-medium dark:text-blue-500"
+                        on:click=move |_| {
+                            rules.update(|rules| rules.push(RewriteRule::default()));
+                        }
+                    >
+
+                        Add rule
+                    </button>
+                    {move || {
+                        rules
+                            .with(|rules| {
+                                rules
+                                    .iter()
+                                    .filter(|rule| !rule.pattern.is_empty())
+                                    .find_map(|rule| Regex::new(&rule.pattern).err())
+                                    .map(|err| {
+                                        view! {
+                                            <p class="text-xs text-red-600 mt-2">{err.to_string()}</p>
+                                        }
+                                    })
+                            })
+                    }}
+
+                </FormItem>
+            </Show>
+            <FormItem label="Test" tooltip="Enter an address to see how the rules rewrite it">
+                <input
+                    type="text"
+                    class=INPUT_CLASS
+                    placeholder="john.doe@example.org"
+                    prop:value=move || test_address.get()
+                    on:input=move |ev| test_address.set(event_target_value(&ev))
+                />
+                {move || {
+                    test_result
+                        .get()
+                        .map(|result| match result {
+                            Some((idx, rewritten)) => {
+                                view! {
+                                    <div class="mt-2 flex items-center gap-x-2 text-sm text-gray-600 dark:text-gray-400">
+                                        <Badge color=Color::Green>
+                                            {format!("Rule {}", idx + 1)}
+                                        </Badge>
+                                        <span class="font-mono">{rewritten}</span>
+                                    </div>
+                                }
+                            }
+                            None => {
+                                view! {
+                                    <div class="mt-2 flex items-center gap-x-2 text-sm text-gray-600 dark:text-gray-400">
+                                        <Badge color=Color::Gray>No match</Badge>
+                                        <span>The address is not rewritten.</span>
+                                    </div>
+                                }
+                            }
+                        })
+                }}
+
+            </FormItem>
+        </FormSection>
+    }
+}
+
+#[component]
+fn RuleRow(rules: RwSignal<Vec<RewriteRule>>, idx: usize) -> impl IntoView {
+    let rule =
+        create_memo(move |_| rules.with(|rules| rules.get(idx).cloned().unwrap_or_default()));
+    let update = move |f: &dyn Fn(&mut RewriteRule)| {
+        rules.update(|rules| {
+            if let Some(rule) = rules.get_mut(idx) {
+                f(rule);
+            }
+        });
+    };
+
+    view! {
+        <div class="flex items-center gap-x-2">
+            <input
+                type="text"
+                class=INPUT_CLASS
+                placeholder="^([^.]+)\\.([^.]+)@(.+)$"
+                prop:value=move || rule.get().pattern
+                on:change=move |ev| {
+                    let value = event_target_value(&ev);
+                    update(&|rule| rule.pattern.clone_from(&value));
+                }
+            />
+            <span class="text-gray-500">"→"</span>
+            <input
+                type="text"
+                class=INPUT_CLASS
+                placeholder="$1+$2@$3"
+                prop:value=move || rule.get().replacement
+                on:change=move |ev| {
+                    let value = event_target_value(&ev);
+                    update(&|rule| rule.replacement.clone_from(&value));
+                }
+            />
+            <button
+                type="button"
+                class="text-sm text-red-600 decoration-2 hover:underline font-medium dark:text-red-500"
+                on:click=move |_| {
+                    rules
+                        .update(|rules| {
+                            if idx < rules.len() {
+                                rules.remove(idx);
+                            }
+                        });
+                }
+            >
+
+                Remove
+            </button>
+        </div>
+    }
+}
+
+fn build_expression(rules: &[RewriteRule], variable: &str) -> Expression {
+    Expression {
+        if_thens: rules
+            .iter()
+            .filter(|rule| !rule.pattern.is_empty())
+            .map(|rule| ExpressionIfThen {
+                if_: format!("matches({}, {variable})", quote_string(&rule.pattern)),
+                then_: build_template(&rule.replacement),
+            })
+            .collect(),
+        else_: "false".to_string(),
+    }
+}
+
+/// Converts a `$1@example.org` style template into a concatenation of
+/// capture group references and string literals.
+fn build_template(template: &str) -> String {
+    let group = Regex::new(r"\$([0-9]+)").unwrap();
+    let mut parts = Vec::new();
+    let mut last = 0;
+    for captures in group.captures_iter(template) {
+        let range = captures.get(0).unwrap().range();
+        if range.start > last {
+            parts.push(quote_string(&template[last..range.start]));
+        }
+        parts.push(format!("${}", &captures[1]));
+        last = range.end;
+    }
+    if last < template.len() || parts.is_empty() {
+        parts.push(quote_string(&template[last..]));
+    }
+    parts.join(" + ")
+}
+
+/// Parses an expression produced by the rule table back into rules,
+/// returning `None` for expressions that were written by hand.
+fn parse_rules(data: &FormData, field: &str, variable: &str) -> Option<Vec<RewriteRule>> {
+    let else_ = data.expr_else(field).unwrap_or("false").trim();
+    if else_ != "false" && !else_.is_empty() {
+        return None;
+    }
+    let string = r#"'((?:[^'\\]|\\.)*)'"#;
+    let condition = Regex::new(&format!(
+        r"^matches\(\s*{string}\s*,\s*{}\s*\)$",
+        regex::escape(variable)
+    ))
+    .ok()?;
+    let part = Regex::new(&format!(r"^\s*(?:\$([0-9]+)|{string})\s*(?:\+|$)")).ok()?;
+
+    data.expr_if_thens(field)
+        .map(|if_then| {
+            let pattern = unquote_string(&condition.captures(if_then.if_.trim())?[1]);
+            let mut replacement = String::new();
+            let mut then_ = if_then.then_.trim();
+            while !then_.is_empty() {
+                let captures = part.captures(then_)?;
+                if let Some(group) = captures.get(1) {
+                    replacement.push('$');
+                    replacement.push_str(group.as_str());
+                } else {
+                    replacement.push_str(&unquote_string(&captures[2]));
+                }
+                then_ = &then_[captures.get(0)?.end()..];
+            }
+            Some(RewriteRule {
+                pattern,
+                replacement,
+            })
+        })
+        .collect()
+}
+
+/// Returns the index of the first rule matching the address along with the
+/// rewritten address.
+fn apply_rules(rules: &[RewriteRule], address: &str) -> Option<(usize, String)> {
+    rules.iter().enumerate().find_map(|(idx, rule)| {
+        let captures = Regex::new(&rule.pattern).ok()?.captures(address)?;
+        let group = Regex::new(r"\$([0-9]+)").unwrap();
+        let rewritten = group.replace_all(&rule.replacement, |c: &regex::Captures| {
+            c[1].parse::<usize>()
+                .ok()
+                .and_then(|n| captures.get(n))
+                .map_or("", |m| m.as_str())
+                .to_string()
+        });
+        Some((idx, rewritten.into_owned()))
+    })
+}
@@ -0,0 +1,152 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use super::*;
+
+impl Builder<Schemas, ()> {
+    pub fn build_ai(self) -> Self {
+        // AI models
+        self.new_schema("ai-model")
+            .prefix("enterprise.ai")
+            .suffix("url")
+            .names("model", "models")
+            .new_id_field()
+            .label("Model Id")
+            .help("Unique identifier for this model")
+            .build()
+            .new_field("type")
+            .label("Type")
+            .help("Whether the endpoint exposes a chat or a text completion API")
+            .default("chat")
+            .typ(Type::Select {
+                multi: false,
+                source: Source::Static(&[("chat", "Chat completion"), ("text", "Text completion")]),
+            })
+            .input_check([], [Validator::Required])
+            .build()
+            .new_field("url")
+            .label("Endpoint URL")
+            .help("URL of the OpenAI compatible API endpoint")
+            .placeholder("https://api.openai.com/v1/chat/completions")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::Required, Validator::IsUrl])
+            .build()
+            .new_field("model")
+            .label("Model")
+            .help("Name of the model to request from the endpoint")
+            .placeholder("gpt-4o-mini")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::Required])
+            .build()
+            .new_field("auth.token")
+            .label("API Token")
+            .help("Bearer token used to authenticate with the endpoint")
+            .typ(Type::Secret)
+            .build()
+            .new_field("timeout")
+            .label("Timeout")
+            .help("Maximum amount of time to wait for a response from the model")
+            .default("2m")
+            .typ(Type::Duration)
+            .input_check([], [Validator::Required])
+            .build()
+            .new_field("allow-invalid-certs")
+            .label("Allow Invalid Certs")
+            .help("Whether to connect to an endpoint that has an invalid TLS certificate")
+            .default("false")
+            .typ(Type::Boolean)
+            .build()
+            .new_field("headers")
+            .typ(Type::Array)
+            .label("HTTP Headers")
+            .help("Additional headers to send with each request")
+            .build()
+            .new_form_section()
+            .title("Model")
+            .docs("enterprise/ai-models")
+            .fields(["_id", "type", "url", "model"])
+            .build()
+            .new_form_section()
+            .title("Authentication")
+            .fields(["auth.token"])
+            .build()
+            .new_form_section()
+            .title("Options")
+            .fields(["timeout", "allow-invalid-certs", "headers"])
+            .build()
+            .list_title("AI Models")
+            .list_subtitle("Manage the language models available to the server")
+            .list_fields(["_id", "model", "url"])
+            .build()
+            // LLM spam classifier
+            .new_schema("spam-llm")
+            .reload_prefix("spam-filter")
+            .new_field("spam-filter.llm.enable")
+            .label("Enable LLM classifier")
+            .help("Whether to ask a language model to classify incoming messages")
+            .default("false")
+            .typ(Type::Boolean)
+            .build()
+            .new_field("spam-filter.llm.model")
+            .label("Model")
+            .help("The AI model used to classify messages")
+            .typ(Type::Select {
+                source: Source::Dynamic {
+                    schema: "ai-model",
+                    field: "url",
+                    filter: Default::default(),
+                },
+                multi: false,
+            })
+            .input_check([], [Validator::Required])
+            .display_if_eq("spam-filter.llm.enable", ["true"])
+            .build()
+            .new_field("spam-filter.llm.temperature")
+            .label("Temperature")
+            .help("Sampling temperature, lower values produce more consistent answers")
+            .default("0.5")
+            .typ(Type::Input)
+            .input_check(
+                [Transformer::Trim],
+                [
+                    Validator::Required,
+                    Validator::MinValue(0.0.into()),
+                    Validator::MaxValue(1.0.into()),
+                ],
+            )
+            .display_if_eq("spam-filter.llm.enable", ["true"])
+            .build()
+            .new_field("spam-filter.llm.prompt")
+            .label("Prompt")
+            .help("Instructions sent to the model, followed by the message to classify")
+            .typ(Type::Text)
+            .input_check([], [Validator::Required])
+            .display_if_eq("spam-filter.llm.enable", ["true"])
+            .build()
+            .new_field("spam-filter.llm.categories")
+            .label("Categories")
+            .help("Categories the model may answer with, each one is added as a spam tag")
+            .typ(Type::Array)
+            .input_check(
+                [Transformer::RemoveSpaces, Transformer::Uppercase],
+                [Validator::IsId],
+            )
+            .display_if_eq("spam-filter.llm.enable", ["true"])
+            .build()
+            .new_form_section()
+            .title("LLM Classifier")
+            .docs("spamfilter/llm")
+            .fields([
+                "spam-filter.llm.enable",
+                "spam-filter.llm.model",
+                "spam-filter.llm.temperature",
+                "spam-filter.llm.prompt",
+                "spam-filter.llm.categories",
+            ])
+            .build()
+            .build()
+    }
+}
@@ -131,6 +131,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("DKIM Signature")
+            .docs("mta/authentication/dkim/signing")
             .fields([
                 "_id",
                 "algorithm",
@@ -241,6 +242,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("DKIM Verification")
+            .docs("mta/authentication/dkim/overview")
             .fields(["auth.dkim.verify", "auth.dkim.strict"])
             .build()
             .new_form_section()
@@ -288,6 +290,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("ARC Verification")
+            .docs("mta/authentication/arc")
             .fields(["auth.arc.verify"])
             .build()
             .new_form_section()
@@ -370,6 +373,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("SPF Verification")
+            .docs("mta/authentication/spf")
             .fields(["auth.spf.verify.ehlo", "auth.spf.verify.mail-from"])
             .build()
             .new_form_section()
@@ -508,6 +512,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("DMARC Verification")
+            .docs("mta/authentication/dmarc")
             .fields(["auth.dmarc.verify"])
             .build()
             .new_form_section()
@@ -587,6 +592,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("Inbound Report Analysis")
+            .docs("mta/reports/overview")
             .fields([
                 "report.analysis.addresses",
                 "report.analysis.store",
@@ -78,6 +78,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("Authentication")
+            .docs("auth/authentication/overview")
             .fields(["storage.directory"])
             .build()
             .new_form_section()
@@ -148,6 +149,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("OAuth Settings")
+            .docs("auth/authentication/oauth")
             .fields(["oauth.key"])
             .fields(["oauth.auth.max-attempts"])
             .build()
@@ -31,6 +31,7 @@ impl Builder<Schemas, ()> {
                     ("lmtp", "LMTP Server"),
                     ("smtp", "SMTP Server"),
                     ("imap", "IMAP4 Server"),
+                    ("oidc", "OpenID Connect"),
                 ]),
                 multi: false,
             })
@@ -206,9 +207,79 @@ impl Builder<Schemas, ()> {
             .label("Timeout")
             .help("Connection timeout to the server")
             .typ(Type::Duration)
-            .display_if_eq("type", ["ldap", "smtp", "lmtp", "imap"])
+            .display_if_eq("type", ["ldap", "smtp", "lmtp", "imap", "oidc"])
             .default("15s")
             .build()
+            // OpenID Connect settings
+            .new_field("issuer-url")
+            .label("Issuer URL")
+            .help(concat!(
+                "URL of the OpenID Connect provider, used to fetch its ",
+                "discovery document"
+            ))
+            .display_if_eq("type", ["oidc"])
+            .placeholder("https://accounts.example.org")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::Required, Validator::IsUrl])
+            .new_field("client-id")
+            .label("Client Id")
+            .help("Client identifier registered with the provider")
+            .input_check([Transformer::Trim], [Validator::Required])
+            .new_field("client-secret")
+            .label("Client Secret")
+            .help("Client secret registered with the provider")
+            .typ(Type::Secret)
+            .input_check([], [])
+            .new_field("endpoint.authorization")
+            .label("Authorization")
+            .help("Endpoint where users are sent to log in")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::Required, Validator::IsUrl])
+            .new_field("endpoint.token")
+            .label("Token")
+            .help("Endpoint used to exchange authorization codes for tokens")
+            .new_field("endpoint.url")
+            .label("User info")
+            .help("Endpoint used to obtain the claims of an authenticated user")
+            .new_field("endpoint.jwks")
+            .label("Key set")
+            .help("Endpoint publishing the keys used to sign ID tokens")
+            .input_check([Transformer::Trim], [Validator::IsUrl])
+            .build()
+            .new_field("redirect-uris")
+            .label("Redirect URIs")
+            .help(concat!(
+                "URIs the provider may redirect users to after logging in, ",
+                "these must match the ones registered with the provider"
+            ))
+            .display_if_eq("type", ["oidc"])
+            .typ(Type::Array)
+            .input_check([Transformer::Trim], [Validator::Required, Validator::IsUrl])
+            .build()
+            .new_field("scopes")
+            .label("Scopes")
+            .help("Scopes requested during login")
+            .display_if_eq("type", ["oidc"])
+            .typ(Type::Array)
+            .input_check([Transformer::Trim], [])
+            .build()
+            .new_field("fields.username")
+            .label("Username")
+            .help("Claim containing the login name of the user")
+            .display_if_eq("type", ["oidc"])
+            .default("preferred_username")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::Required])
+            .new_field("fields.email")
+            .label("E-mail")
+            .help("Claim containing the e-mail address of the user")
+            .default("email")
+            .new_field("fields.full-name")
+            .label("Full name")
+            .help("Claim containing the full name of the user")
+            .default("name")
+            .input_check([Transformer::Trim], [])
+            .build()
             // LDAP settings
             .new_field("url")
             .label("URL")
@@ -341,11 +412,40 @@ impl Builder<Schemas, ()> {
             // Form layouts
             .new_form_section()
             .title("Configuration")
+            .docs("auth/backend/overview")
             .fields([
-                "_id", "type", "store", "url", "base-dn", "host", "port", "timeout",
+                "_id",
+                "type",
+                "store",
+                "url",
+                "base-dn",
+                "host",
+                "port",
+                "issuer-url",
+                "timeout",
             ])
             .build()
             .new_form_section()
+            .title("OpenID Connect")
+            .display_if_eq("type", ["oidc"])
+            .fields(["client-id", "client-secret", "redirect-uris", "scopes"])
+            .build()
+            .new_form_section()
+            .title("Endpoints")
+            .display_if_eq("type", ["oidc"])
+            .fields([
+                "endpoint.authorization",
+                "endpoint.token",
+                "endpoint.url",
+                "endpoint.jwks",
+            ])
+            .build()
+            .new_form_section()
+            .title("Claims")
+            .display_if_eq("type", ["oidc"])
+            .fields(["fields.username", "fields.email", "fields.full-name"])
+            .build()
+            .new_form_section()
             .title("Binding")
             .display_if_eq("type", ["ldap"])
             .fields(["bind.dn", "bind.secret", "bind.auth.enable", "bind.auth.dn"])
@@ -418,5 +518,34 @@ impl Builder<Schemas, ()> {
             .list_subtitle("Manage directories")
             .list_fields(["_id", "type"])
             .build()
+            // Quota templates
+            .new_schema("quota-template")
+            .prefix("directory.quota-template")
+            .suffix("quota")
+            .names("template", "templates")
+            .new_id_field()
+            .label("Template Id")
+            .help("Unique identifier for the quota template")
+            .build()
+            .new_field("name")
+            .label("Name")
+            .help("Name shown when selecting the template, for example 'Standard 5GB'")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::Required])
+            .build()
+            .new_field("quota")
+            .label("Disk quota")
+            .help("Maximum disk space assigned to accounts using this template")
+            .typ(Type::Size)
+            .input_check([], [Validator::Required])
+            .build()
+            .new_form_section()
+            .title("Quota Template")
+            .fields(["_id", "name", "quota"])
+            .build()
+            .list_title("Quota Templates")
+            .list_subtitle("Manage the disk quotas that can be assigned to accounts")
+            .list_fields(["_id", "name", "quota"])
+            .build()
     }
 }
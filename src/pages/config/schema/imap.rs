@@ -31,6 +31,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("Authentication settings")
+            .docs("imap/settings")
             .fields(["imap.auth.max-failures", "imap.auth.allow-plain-text"])
             .build()
             .build()
@@ -51,6 +52,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("Rate Limiting")
+            .docs("imap/settings")
             .fields(["imap.rate-limit.requests", "imap.rate-limit.concurrent"])
             .build()
             .build()
@@ -95,6 +97,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("Limits")
+            .docs("imap/settings")
             .fields(["imap.request.max-size"])
             .build()
             .new_form_section()
@@ -231,6 +234,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("Inbox")
+            .docs("imap/folders")
             .fields([
                 "jmap.folders.inbox.name",
                 "jmap.folders.inbox.create",
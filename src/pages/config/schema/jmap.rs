@@ -138,6 +138,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("Request Limits")
+            .docs("jmap/settings")
             .fields([
                 "jmap.protocol.request.max-concurrent",
                 "jmap.protocol.request.max-size",
@@ -209,6 +210,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("JMAP Session")
+            .docs("jmap/settings")
             .fields(["cache.session.ttl", "jmap.session.purge.frequency"])
             .build()
             .build()
@@ -232,6 +234,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("Rate Limit")
+            .docs("jmap/settings")
             .fields(["jmap.rate-limit.account", "jmap.rate-limit.anonymous"])
             .build()
             .build()
@@ -313,6 +316,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("Push Subscriptions")
+            .docs("jmap/push")
             .fields([
                 "jmap.push.max-total",
                 "jmap.push.throttle",
@@ -361,6 +365,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("JMAP over WebSocket")
+            .docs("jmap/websockets")
             .fields([
                 "jmap.web-sockets.throttle",
                 "jmap.web-sockets.timeout",
@@ -78,6 +78,7 @@ impl Builder<Schemas, ()> {
             // Forms
             .new_form_section()
             .title("Listener settings")
+            .docs("server/listener")
             .fields(["_id", "protocol", "bind"])
             .build()
             .new_form_section()
@@ -4,6 +4,7 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
+pub mod ai;
 pub mod auth;
 pub mod authentication;
 pub mod directory;
@@ -42,6 +43,16 @@ pub const V_QUEUE_NOTIFY_NUM: &str = "notify_num";
 pub const V_QUEUE_EXPIRES_IN: &str = "expires_in";
 pub const V_QUEUE_LAST_STATUS: &str = "last_status";
 pub const V_QUEUE_LAST_ERROR: &str = "last_error";
+pub const V_ACCOUNT: &str = "account";
+pub const V_TENANT: &str = "tenant";
+pub const V_ASN: &str = "asn";
+pub const V_COUNTRY: &str = "country";
+pub const V_FROM: &str = "from";
+pub const V_FROM_NAME: &str = "from_name";
+pub const V_FROM_DOMAIN: &str = "from_domain";
+pub const V_REPLY_TO: &str = "reply_to";
+pub const V_SUBJECT: &str = "subject";
+pub const V_BODY: &str = "body";
 
 pub const CONNECTION_VARS: &[&str] = &[
     V_LISTENER,
@@ -143,3 +154,23 @@ pub const SMTP_QUEUE_MX_VARS: &[&str] = &[
     V_QUEUE_LAST_STATUS,
     V_QUEUE_LAST_ERROR,
 ];
+
+pub const UNDELETE_VARS: &[&str] = &[V_ACCOUNT, V_TENANT];
+
+pub const SPAM_FILTER_VARS: &[&str] = &[
+    V_REMOTE_IP,
+    V_ASN,
+    V_COUNTRY,
+    V_HELO_DOMAIN,
+    V_AUTHENTICATED_AS,
+    V_TLS,
+    V_SENDER,
+    V_SENDER_DOMAIN,
+    V_RECIPIENTS,
+    V_FROM,
+    V_FROM_NAME,
+    V_FROM_DOMAIN,
+    V_REPLY_TO,
+    V_SUBJECT,
+    V_BODY,
+];
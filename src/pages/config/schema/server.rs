@@ -90,6 +90,7 @@ impl Builder<Schemas, ()> {
             // Forms
             .new_form_section()
             .title("Network settings")
+            .docs("server/general")
             .fields([
                 "lookup.default.hostname",
                 "server.max-connections",
@@ -179,6 +180,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("Local configuration keys")
+            .docs("server/general")
             .fields(["config.local-keys"])
             .build()
             .new_form_section()
@@ -244,6 +246,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("Cache settings")
+            .docs("server/cache")
             .fields(["cache.capacity", "cache.shard"])
             .build()
             .new_form_section()
@@ -255,6 +258,55 @@ impl Builder<Schemas, ()> {
             ])
             .build()
             .build()
+            // Announcement
+            .new_schema("announcement")
+            .new_field("webadmin.announcement.enable")
+            .label("Enable")
+            .help("Whether to show the announcement to everyone signed in to the web admin")
+            .default("false")
+            .typ(Type::Boolean)
+            .build()
+            .new_field("webadmin.announcement.message")
+            .label("Message")
+            .help("Text of the announcement, such as the details of a planned maintenance")
+            .typ(Type::Text)
+            .input_check(
+                [Transformer::Trim],
+                [Validator::Required, Validator::MaxLength(500)],
+            )
+            .display_if_eq("webadmin.announcement.enable", ["true"])
+            .build()
+            .new_field("webadmin.announcement.severity")
+            .label("Severity")
+            .help("Determines the color of the announcement banner")
+            .default("info")
+            .typ(Type::Select {
+                multi: false,
+                source: Source::Static(ANNOUNCEMENT_SEVERITIES),
+            })
+            .display_if_eq("webadmin.announcement.enable", ["true"])
+            .build()
+            .new_field("webadmin.announcement.expires")
+            .label("Expires")
+            .help(concat!(
+                "Date and time in RFC 3339 format after which the announcement is ",
+                "no longer shown, leave blank to show it until disabled"
+            ))
+            .placeholder("2024-12-31T22:00:00Z")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [])
+            .display_if_eq("webadmin.announcement.enable", ["true"])
+            .build()
+            .new_form_section()
+            .title("Announcement")
+            .fields([
+                "webadmin.announcement.enable",
+                "webadmin.announcement.message",
+                "webadmin.announcement.severity",
+                "webadmin.announcement.expires",
+            ])
+            .build()
+            .build()
             // Blocked IP addresses
             .new_schema("blocked-ip")
             .reload_prefix("server.blocked-ip")
@@ -269,6 +321,7 @@ impl Builder<Schemas, ()> {
             )
             .build()
             .new_form_section()
+            .docs("server/auto-ban")
             .field("_id")
             .build()
             .list_title("Blocked IP addresses")
@@ -290,6 +343,7 @@ impl Builder<Schemas, ()> {
             )
             .build()
             .new_form_section()
+            .docs("server/allowed-ip")
             .field("_id")
             .build()
             .list_title("Allowed IP addresses")
@@ -367,6 +421,7 @@ impl Builder<Schemas, ()> {
             // Forms
             .new_form_section()
             .title("Cluster settings")
+            .docs("cluster/overview")
             .fields(["cluster.node-id"])
             .build()
             .new_form_section()
@@ -466,6 +521,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("Webhook settings")
+            .docs("telemetry/webhooks")
             .fields(["_id", "url", "signature-key", "allow-invalid-certs"])
             .build()
             .new_form_section()
@@ -487,6 +543,12 @@ impl Builder<Schemas, ()> {
     }
 }
 
+pub static ANNOUNCEMENT_SEVERITIES: &[(&str, &str)] = &[
+    ("info", "Information"),
+    ("warning", "Warning"),
+    ("critical", "Critical"),
+];
+
 pub static WEBHOOK_EVENTS: &[(&str, &str)] = &[
     ("auth.success", "Authentication success"),
     ("auth.failure", "Authentication failure"),
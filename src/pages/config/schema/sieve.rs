@@ -143,6 +143,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("Untrusted Interpreter")
+            .docs("sieve/overview")
             .fields([
                 "sieve.untrusted.notification-uris",
                 "sieve.untrusted.protected-headers",
@@ -280,6 +281,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("Untrusted Limits")
+            .docs("sieve/limits")
             .fields([
                 "sieve.untrusted.limits.name-length",
                 "sieve.untrusted.limits.max-scripts",
@@ -335,11 +337,72 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("Sieve Script")
+            .docs("sieve/scripts")
             .fields(["_id", "name", "contents"])
             .build()
             .list_title("Sieve scripts")
             .list_subtitle("Manage Sieve scripts executed by the trusted interpreter")
             .list_fields(["_id", "name"])
             .build()
+            // Disclaimers
+            .new_schema("disclaimer")
+            .prefix("session.data.disclaimer")
+            .suffix("domain")
+            .names("disclaimer", "disclaimers")
+            .new_id_field()
+            .label("Disclaimer Id")
+            .help("Unique identifier for the disclaimer")
+            .build()
+            .new_field("domain")
+            .label("Domain")
+            .help("Sender domain whose outgoing messages receive this disclaimer")
+            .typ(Type::Input)
+            .input_check(
+                [Transformer::Trim, Transformer::Lowercase],
+                [Validator::Required, Validator::IsDomain],
+            )
+            .build()
+            .new_field("enable")
+            .label("Enable")
+            .help("Whether to append this disclaimer to outgoing messages")
+            .default("true")
+            .typ(Type::Boolean)
+            .build()
+            .new_field("position")
+            .label("Position")
+            .help("Whether the disclaimer is added before or after the message body")
+            .default("footer")
+            .typ(Type::Select {
+                multi: false,
+                source: Source::Static(&[("footer", "Footer"), ("header", "Header")]),
+            })
+            .build()
+            .new_field("text")
+            .label("Plain text")
+            .help("Disclaimer appended to text/plain message parts")
+            .typ(Type::Text)
+            .input_check([], [Validator::Required])
+            .build()
+            .new_field("html")
+            .label("HTML")
+            .help(concat!(
+                "Disclaimer appended to text/html message parts, ",
+                "the plain text version is used when empty"
+            ))
+            .typ(Type::Text)
+            .build()
+            .new_form_section()
+            .title("Disclaimer")
+            .docs("sieve/disclaimers")
+            .fields(["_id", "domain", "enable", "position"])
+            .build()
+            .new_form_section()
+            .title("Content")
+            .fields(["text", "html"])
+            .build()
+            .list_title("Disclaimers")
+            .list_subtitle("Manage disclaimers appended to outgoing messages")
+            .list_fields(["_id", "domain", "position"])
+            .build()
     }
 }
@@ -80,11 +80,20 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("Queue Schedule")
+            .docs("mta/outbound/queue")
             .fields([
                 "queue.schedule.retry",
                 "queue.schedule.notify",
                 "queue.schedule.expire",
             ])
+            .constraint(Constraint::LongerThan {
+                field: "queue.schedule.expire",
+                other: "queue.schedule.retry",
+            })
+            .constraint(Constraint::LongerThan {
+                field: "queue.schedule.expire",
+                other: "queue.schedule.notify",
+            })
             .build()
             .new_form_section()
             .title("Delivery Status Notifications (DSN) Reports")
@@ -146,6 +155,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("Routing")
+            .docs("mta/outbound/routing")
             .fields([
                 "queue.outbound.next-hop",
                 "queue.outbound.ip-strategy",
@@ -267,6 +277,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("TLS Security")
+            .docs("mta/outbound/tls")
             .fields([
                 "queue.outbound.tls.starttls",
                 "queue.outbound.tls.dane",
@@ -363,6 +374,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("Limits")
+            .docs("mta/outbound/timeouts")
             .fields([
                 "queue.outbound.limits.mx",
                 "queue.outbound.limits.multihomed",
@@ -512,6 +524,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("DNS Resolver settings")
+            .docs("mta/outbound/dns")
             .fields([
                 "resolver.type",
                 "resolver.custom",
@@ -610,6 +623,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("Server Details")
+            .docs("mta/outbound/routing")
             .fields(["_id", "address", "port", "protocol"])
             .build()
             .new_form_section()
@@ -619,6 +633,14 @@ impl Builder<Schemas, ()> {
             .new_form_section()
             .title("Authentication")
             .fields(["auth.username", "auth.secret"])
+            .constraint(Constraint::RequiredWith {
+                field: "auth.secret",
+                other: "auth.username",
+            })
+            .constraint(Constraint::RequiredWith {
+                field: "auth.username",
+                other: "auth.secret",
+            })
             .build()
             .list_title("Remote SMTP Servers")
             .list_subtitle("Manage remote SMTP and LMTP servers for message delivery")
@@ -694,6 +716,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("Throttle")
+            .docs("mta/outbound/throttle")
             .fields(["_id", "key", "concurrency", "rate", "match", "enable"])
             .build()
             .list_title("Outbound Throttles")
@@ -768,6 +791,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("Quota")
+            .docs("mta/outbound/quota")
             .fields(["_id", "key", "messages", "size", "match", "enable"])
             .build()
             .list_title("Quota Queues")
@@ -823,6 +847,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("Connect Stage")
+            .docs("mta/inbound/connect")
             .fields([
                 "session.connect.hostname",
                 "session.connect.greeting",
@@ -873,6 +898,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("EHLO Stage")
+            .docs("mta/inbound/ehlo")
             .fields([
                 "session.ehlo.require",
                 "session.ehlo.reject-non-fqdn",
@@ -905,6 +931,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("SMTP Session Limits")
+            .docs("mta/inbound/general")
             .fields([
                 "session.timeout",
                 "session.transfer-limit",
@@ -1035,6 +1062,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("SMTP Extensions")
+            .docs("mta/inbound/ehlo")
             .fields([
                 "session.extensions.pipelining",
                 "session.extensions.chunking",
@@ -1122,6 +1150,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("AUTH Stage")
+            .docs("mta/inbound/auth")
             .fields([
                 "session.auth.directory",
                 "session.auth.require",
@@ -1155,6 +1184,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("MAIL FROM Stage")
+            .docs("mta/inbound/mail")
             .fields(["session.mail.rewrite", "session.mail.script"])
             .build()
             .build()
@@ -1216,6 +1246,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("RCPT TO Stage")
+            .docs("mta/inbound/rcpt")
             .fields([
                 "session.rcpt.directory",
                 "session.rcpt.relay",
@@ -1296,6 +1327,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("DATA Stage")
+            .docs("mta/inbound/data")
             .fields(["session.data.script"])
             .build()
             .new_form_section()
@@ -1388,6 +1420,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("Throttle")
+            .docs("mta/inbound/throttle")
             .fields(["_id", "key", "concurrency", "rate", "match", "enable"])
             .build()
             .list_title("Inbound Throttles")
@@ -1534,6 +1567,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("Milter settings")
+            .docs("mta/filter/milter")
             .fields([
                 "_id",
                 "hostname",
@@ -1662,6 +1696,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("MTA Hook settings")
+            .docs("mta/filter/mtahooks")
             .fields(["_id", "url", "enable", "allow-invalid-certs"])
             .build()
             .new_form_section()
@@ -1715,6 +1750,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("Pipe settings")
+            .docs("mta/filter/pipes")
             .fields(["_id", "command", "arguments", "timeout"])
             .build()
             .list_title("Pipes")
@@ -1756,6 +1792,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("MTA-STS Policy")
+            .docs("mta/inbound/mta-sts")
             .fields([
                 "session.mta-sts.mode",
                 "session.mta-sts.max-age",
@@ -181,6 +181,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("Header")
+            .docs("spamfilter/overview")
             .fields([
                 "spam.header.is-spam",
                 "lookup.spam-config.add-spam",
@@ -390,6 +391,80 @@ impl Builder<Schemas, ()> {
             .list_subtitle("Manage scores assigned to spam tags")
             .list_fields(["_id", "_value"])
             .build()
+            // Rules
+            .new_schema("spam-rule")
+            .reload_prefix("spam-filter")
+            .names("rule", "rules")
+            .prefix("spam-filter.rule")
+            .suffix("condition")
+            .new_id_field()
+            .label("Rule Id")
+            .help("Unique identifier for the rule")
+            .input_check(
+                [Transformer::RemoveSpaces, Transformer::Uppercase],
+                [Validator::Required, Validator::IsId],
+            )
+            .build()
+            .new_field("description")
+            .label("Description")
+            .help("A short description of what this rule detects")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [])
+            .build()
+            .new_field("enable")
+            .typ(Type::Boolean)
+            .label("Enable this rule")
+            .help("Whether this rule is evaluated")
+            .default("true")
+            .build()
+            .new_field("condition")
+            .typ(Type::Input)
+            .label("Condition")
+            .help("Expression that fires the rule when it evaluates to true")
+            .input_check(
+                [Transformer::Trim],
+                [
+                    Validator::Required,
+                    Validator::IsValidExpression(ExpressionValidator::new(SPAM_FILTER_VARS, &[])),
+                ],
+            )
+            .build()
+            .new_field("score")
+            .label("Score")
+            .help("Score added to the message when the rule fires, use negative values for ham")
+            .default("0")
+            .typ(Type::Input)
+            .input_check(
+                [Transformer::Trim],
+                [
+                    Validator::Required,
+                    Validator::MinValue((-100.0).into()),
+                    Validator::MaxValue(100.0.into()),
+                ],
+            )
+            .build()
+            .new_field("tags")
+            .typ(Type::Array)
+            .label("Tags")
+            .help("Tags added to the message when the rule fires")
+            .input_check(
+                [Transformer::RemoveSpaces, Transformer::Uppercase],
+                [Validator::IsId],
+            )
+            .build()
+            .new_form_section()
+            .title("Rule")
+            .docs("spamfilter/rules")
+            .fields(["_id", "description", "enable"])
+            .build()
+            .new_form_section()
+            .title("Scoring")
+            .fields(["score", "tags"])
+            .build()
+            .list_title("SPAM Rules")
+            .list_subtitle("Manage the rules used to score incoming messages")
+            .list_fields(["_id", "description", "score", "enable"])
+            .build()
             // MIME-types
             .new_schema("spam-mime")
             .reload_prefix("lookup")
@@ -155,6 +155,7 @@ impl Builder<Schemas, ()> {
             .build()
             .new_form_section()
             .title("Data Store")
+            .docs("storage/overview")
             .fields([
                 "storage.data",
                 "storage.encryption.enable",
@@ -182,5 +183,30 @@ impl Builder<Schemas, ()> {
             ])
             .build()
             .build()
+            // Undelete
+            .new_schema("undelete")
+            .new_field("storage.undelete.retention")
+            .label("Hold period")
+            .help(concat!(
+                "How long deleted messages are held before being permanently ",
+                "removed, evaluated for each account. Use the tenant and account ",
+                "variables to set a different period per tenant or class of ",
+                "account, or return false to disable undelete"
+            ))
+            .default("false")
+            .typ(Type::Expression)
+            .input_check(
+                [],
+                [
+                    Validator::Required,
+                    Validator::IsValidExpression(ExpressionValidator::new(UNDELETE_VARS, &[])),
+                ],
+            )
+            .build()
+            .new_form_section()
+            .title("Undelete")
+            .fields(["storage.undelete.retention"])
+            .build()
+            .build()
     }
 }
@@ -498,6 +498,7 @@ impl Builder<Schemas, ()> {
             // Form layouts
             .new_form_section()
             .title("Configuration")
+            .docs("storage/backends/overview")
             .fields([
                 "_id",
                 "type",
@@ -579,6 +580,10 @@ impl Builder<Schemas, ()> {
                 "retry.max-wait",
                 "retry.min-wait",
             ])
+            .constraint(Constraint::LongerThan {
+                field: "retry.max-wait",
+                other: "retry.min-wait",
+            })
             .build()
             .new_form_section()
             .title("Cluster Ids")
@@ -616,5 +621,151 @@ impl Builder<Schemas, ()> {
             .list_subtitle("Manage data, blob, full-text, and lookup stores")
             .list_fields(["_id", "type"])
             .build()
+            // HTTP lists
+            .new_schema("http-lookup")
+            .names("HTTP list", "HTTP lists")
+            .prefix("http-lookup")
+            .suffix("url")
+            .new_id_field()
+            .label("List Id")
+            .help("Unique identifier for the list")
+            .build()
+            .new_field("enable")
+            .label("Enable")
+            .help("Whether to fetch and use this list")
+            .default("true")
+            .typ(Type::Boolean)
+            .build()
+            .new_field("url")
+            .label("URL")
+            .help("URL to download the list from")
+            .placeholder("https://example.org/blocklist.txt")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::Required, Validator::IsUrl])
+            .build()
+            .new_field("format")
+            .label("Format")
+            .help("Format of the downloaded list")
+            .default("list")
+            .typ(Type::Select {
+                multi: false,
+                source: Source::Static(&[("list", "One entry per line"), ("csv", "CSV")]),
+            })
+            .input_check([], [Validator::Required])
+            .build()
+            .new_field("separator")
+            .label("Separator")
+            .help("Character used to separate CSV fields")
+            .default(",")
+            .typ(Type::Input)
+            .input_check([], [Validator::Required])
+            .display_if_eq("format", ["csv"])
+            .build()
+            .new_field("index.key")
+            .label("Key column")
+            .help("Position of the column holding the entry key, starting at zero")
+            .default("0")
+            .typ(Type::Input)
+            .input_check(
+                [Transformer::Trim],
+                [Validator::Required, Validator::MinValue(0.into())],
+            )
+            .display_if_eq("format", ["csv"])
+            .build()
+            .new_field("index.value")
+            .label("Value column")
+            .help("Position of the column holding the entry value, leave empty for none")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::MinValue(0.into())])
+            .display_if_eq("format", ["csv"])
+            .build()
+            .new_field("skip-first")
+            .label("Skip header")
+            .help("Whether to skip the first line of the file")
+            .default("false")
+            .typ(Type::Boolean)
+            .display_if_eq("format", ["csv"])
+            .build()
+            .new_field("gzipped")
+            .label("Gzipped")
+            .help("Whether the list is compressed with gzip")
+            .default("false")
+            .typ(Type::Boolean)
+            .build()
+            .new_field("refresh")
+            .label("Refresh")
+            .help("How often to download the list again")
+            .default("12h")
+            .typ(Type::Duration)
+            .input_check([], [Validator::Required])
+            .build()
+            .new_field("retry")
+            .label("Retry")
+            .help("How long to wait before retrying a failed download")
+            .default("1h")
+            .typ(Type::Duration)
+            .input_check([], [Validator::Required])
+            .build()
+            .new_field("timeout")
+            .label("Timeout")
+            .help("Maximum time to wait for the download to complete")
+            .default("30s")
+            .typ(Type::Duration)
+            .input_check([], [Validator::Required])
+            .build()
+            .new_field("limits.size")
+            .label("Max size")
+            .help("Maximum size of the downloaded file")
+            .default("104857600")
+            .typ(Type::Size)
+            .input_check([], [Validator::Required])
+            .build()
+            .new_field("limits.entries")
+            .label("Max entries")
+            .help("Maximum number of entries to load from the list")
+            .default("100000")
+            .typ(Type::Input)
+            .input_check(
+                [Transformer::Trim],
+                [Validator::Required, Validator::MinValue(1.into())],
+            )
+            .build()
+            .new_field("limits.entry-size")
+            .label("Max entry size")
+            .help("Entries longer than this are ignored")
+            .default("512")
+            .typ(Type::Input)
+            .input_check(
+                [Transformer::Trim],
+                [Validator::Required, Validator::MinValue(1.into())],
+            )
+            .build()
+            .new_form_section()
+            .title("List settings")
+            .fields(["_id", "url", "enable"])
+            .build()
+            .new_form_section()
+            .title("Format")
+            .fields([
+                "format",
+                "separator",
+                "index.key",
+                "index.value",
+                "skip-first",
+                "gzipped",
+            ])
+            .build()
+            .new_form_section()
+            .title("Refresh")
+            .fields(["refresh", "retry", "timeout"])
+            .build()
+            .new_form_section()
+            .title("Limits")
+            .fields(["limits.size", "limits.entries", "limits.entry-size"])
+            .build()
+            .list_title("HTTP lists")
+            .list_subtitle("Manage remote lists used in lookups")
+            .list_fields(["_id", "url", "enable"])
+            .build()
     }
 }
@@ -209,6 +209,7 @@ impl Builder<Schemas, ()> {
             // Form
             .new_form_section()
             .title("ACME provider")
+            .docs("server/tls/acme")
             .fields([
                 "_id",
                 "directory",
@@ -286,6 +287,7 @@ impl Builder<Schemas, ()> {
             .list_fields(["_id", "subjects", "default"])
             .new_form_section()
             .title("TLS certificate")
+            .docs("server/tls/certificates")
             .fields(["_id", "cert", "private-key", "subjects", "default"])
             .build()
             .build()
@@ -296,6 +298,7 @@ impl Builder<Schemas, ()> {
             // Forms
             .new_form_section()
             .title("Default TLS options")
+            .docs("server/tls/overview")
             .fields([
                 "server.tls.disable-protocols",
                 "server.tls.disable-ciphers",
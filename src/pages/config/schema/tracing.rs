@@ -137,6 +137,7 @@ impl Builder<Schemas, ()> {
             // Forms
             .new_form_section()
             .title("Logger settings")
+            .docs("telemetry/tracing/overview")
             .fields([
                 "_id",
                 "type",
@@ -156,4 +157,155 @@ impl Builder<Schemas, ()> {
             .list_fields(["_id", "type", "level", "enable"])
             .build()
     }
+
+    pub fn build_metrics(self) -> Self {
+        self.new_schema("metrics")
+            // Prometheus
+            .new_field("metrics.prometheus.enable")
+            .typ(Type::Boolean)
+            .label("Enable Prometheus endpoint")
+            .help("Whether to expose metrics in the Prometheus exposition format")
+            .default("false")
+            .build()
+            .new_field("metrics.prometheus.auth.username")
+            .typ(Type::Input)
+            .label("Username")
+            .help("Username required to scrape the Prometheus endpoint")
+            .input_check([Transformer::Trim], [])
+            .display_if_eq("metrics.prometheus.enable", ["true"])
+            .build()
+            .new_field("metrics.prometheus.auth.secret")
+            .typ(Type::Secret)
+            .label("Secret")
+            .help("Secret required to scrape the Prometheus endpoint")
+            .input_check([Transformer::Trim], [])
+            .display_if_eq("metrics.prometheus.enable", ["true"])
+            .build()
+            .new_form_section()
+            .title("Prometheus")
+            .docs("telemetry/metrics/prometheus")
+            .fields([
+                "metrics.prometheus.enable",
+                "metrics.prometheus.auth.username",
+                "metrics.prometheus.auth.secret",
+            ])
+            .build()
+            .build()
+    }
+
+    pub fn build_alerts(self) -> Self {
+        self.new_schema("alert")
+            .names("alert", "alerts")
+            .prefix("metrics.alerts")
+            .suffix("condition")
+            // Id
+            .new_id_field()
+            .label("Alert Id")
+            .help("Unique identifier for the alert")
+            .build()
+            // Enable
+            .new_field("enable")
+            .typ(Type::Boolean)
+            .label("Enable this alert")
+            .help("Whether this alert is evaluated")
+            .default("true")
+            .build()
+            // Condition
+            .new_field("condition")
+            .typ(Type::Input)
+            .label("Condition")
+            .help("Expression that triggers the alert when it evaluates to true")
+            .input_check([Transformer::Trim], [Validator::Required])
+            .build()
+            // Event notification
+            .new_field("notify.event.enable")
+            .typ(Type::Boolean)
+            .label("Emit an event")
+            .help("Whether to emit an alert event that can be picked up by tracers and webhooks")
+            .default("true")
+            .build()
+            .new_field("notify.event.message")
+            .typ(Type::Input)
+            .label("Message")
+            .help("Message included in the alert event")
+            .placeholder("Alert triggered")
+            .input_check([Transformer::Trim], [])
+            .display_if_eq("notify.event.enable", ["true"])
+            .build()
+            // Email notification
+            .new_field("notify.email.enable")
+            .typ(Type::Boolean)
+            .label("Send an e-mail")
+            .help("Whether to send an e-mail message when the alert is triggered")
+            .default("false")
+            .build()
+            .new_field("notify.email.from-name")
+            .typ(Type::Input)
+            .label("From name")
+            .help("Name of the sender of the alert message")
+            .placeholder("Alert Service")
+            .input_check([Transformer::Trim], [])
+            .display_if_eq("notify.email.enable", ["true"])
+            .build()
+            .new_field("notify.email.from-addr")
+            .label("From address")
+            .help("E-mail address of the sender of the alert message")
+            .placeholder("alert@example.org")
+            .input_check(
+                [Transformer::Trim, Transformer::Lowercase],
+                [Validator::Required, Validator::IsEmail],
+            )
+            .display_if_eq("notify.email.enable", ["true"])
+            .build()
+            .new_field("notify.email.to")
+            .typ(Type::Array)
+            .label("Recipients")
+            .help("E-mail addresses that receive the alert message")
+            .input_check(
+                [Transformer::Trim, Transformer::Lowercase],
+                [Validator::Required, Validator::IsEmail],
+            )
+            .display_if_eq("notify.email.enable", ["true"])
+            .build()
+            .new_field("notify.email.subject")
+            .typ(Type::Input)
+            .label("Subject")
+            .help("Subject of the alert message")
+            .placeholder("Alert triggered")
+            .input_check([Transformer::Trim], [Validator::Required])
+            .display_if_eq("notify.email.enable", ["true"])
+            .build()
+            .new_field("notify.email.body")
+            .typ(Type::Text)
+            .label("Body")
+            .help("Contents of the alert message")
+            .input_check([], [Validator::Required])
+            .display_if_eq("notify.email.enable", ["true"])
+            .build()
+            // Forms
+            .new_form_section()
+            .title("Alert")
+            .docs("telemetry/alerts")
+            .fields(["_id", "enable"])
+            .build()
+            .new_form_section()
+            .title("Event notification")
+            .fields(["notify.event.enable", "notify.event.message"])
+            .build()
+            .new_form_section()
+            .title("E-mail notification")
+            .fields([
+                "notify.email.enable",
+                "notify.email.from-name",
+                "notify.email.from-addr",
+                "notify.email.to",
+                "notify.email.subject",
+                "notify.email.body",
+            ])
+            .build()
+            .list_title("Alerts")
+            .list_subtitle("Manage alerts triggered by metric thresholds")
+            .list_fields(["_id", "condition", "enable"])
+            .build()
+    }
 }
@@ -7,102 +7,179 @@
 use std::sync::Arc;
 
 use leptos::*;
-use leptos_router::use_query_map;
+use leptos_router::{use_navigate, use_query_map};
+use regex::{Regex, RegexBuilder};
 
 use crate::{
-    components::{list::ZeroResults, report::ReportView},
+    components::{
+        list::ZeroResults,
+        messages::alert::{use_alerts, Alert},
+        report::ReportView,
+    },
+    core::http::{self, HttpRequest},
+    core::oauth::use_authorization,
     pages::config::Schemas,
 };
 
-use super::{Field, Form, Section};
+use super::{edit::FetchSettings, Field, Form, Schema, SchemaType, Section, Type};
+
+const MAX_VALUE_MATCHES: usize = 5;
+
+#[derive(Clone)]
+enum SearchQuery {
+    Terms(Vec<String>),
+    Prefix(String),
+    Regex(Regex),
+}
+
+#[derive(Clone, PartialEq, Eq)]
+struct SearchResult {
+    url: String,
+    title: String,
+    matches: Vec<String>,
+}
 
 #[component]
 pub fn SettingsSearch() -> impl IntoView {
     let query = use_query_map();
     let schemas = expect_context::<Arc<Schemas>>();
+    let auth = use_authorization();
+    let alert = use_alerts();
 
-    let results = create_memo(move |_| {
-        let params = query.with(|q| {
+    let search_query = create_memo(move |_| {
+        query.with(|q| {
             q.get("query")
-                .map(|s| {
-                    s.split_whitespace()
-                        .map(|s| s.to_lowercase())
-                        .collect::<Vec<_>>()
-                })
-                .unwrap_or_default()
-        });
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+        })
+    });
 
-        schemas
-            .schemas
-            .values()
-            .filter_map(|s| {
-                let (title, matches) = s.form.contains_string(&params)?;
+    let settings = create_resource(
+        move || search_query.get().is_some(),
+        move |has_query| {
+            let auth = auth.get_untracked();
 
-                Some((s.id, title, matches))
-            })
-            .collect::<Vec<_>>()
+            async move {
+                if has_query {
+                    HttpRequest::get("/api/settings/list")
+                        .with_authorization(&auth)
+                        .send::<FetchSettings>()
+                        .await
+                        .map(|list| list.items)
+                } else {
+                    Ok(Default::default())
+                }
+            }
+        },
+    );
+
+    let schemas_ = schemas.clone();
+    let results = create_memo(move |_| {
+        let Some(query) = search_query
+            .get()
+            .and_then(|q| SearchQuery::parse(&q).ok().flatten())
+        else {
+            return Vec::new();
+        };
+
+        find_schemas(&schemas_, &query)
+    });
+
+    let value_results = create_memo(move |_| {
+        let Some(query) = search_query
+            .get()
+            .and_then(|q| SearchQuery::parse(&q).ok().flatten())
+        else {
+            return Vec::new();
+        };
+        let settings = match settings.get() {
+            Some(Ok(settings)) => settings,
+            Some(Err(http::Error::Unauthorized)) => {
+                use_navigate()("/login", Default::default());
+                return Vec::new();
+            }
+            Some(Err(err)) => {
+                alert.set(Alert::from(err));
+                return Vec::new();
+            }
+            None => return Vec::new(),
+        };
+
+        let mut keys = settings.keys().collect::<Vec<_>>();
+        keys.sort();
+
+        let mut results: Vec<SearchResult> = Vec::new();
+        for key in keys {
+            let value = &settings[key];
+            let Some((schema, name, field)) = find_setting(&schemas, key) else {
+                continue;
+            };
+            let is_secret = matches!(field.typ_, Type::Secret);
+            if !query.matches(key) && (is_secret || !query.matches(value)) {
+                continue;
+            }
+
+            let url = setting_url(schema, name);
+            let value = if is_secret {
+                "********"
+            } else {
+                value.as_str()
+            };
+            let hit = format!("{key} = {value}");
+
+            if let Some(result) = results.iter_mut().find(|r| r.url == url) {
+                result.matches.push(hit);
+            } else {
+                let title = setting_title(schema, name);
+                results.push(SearchResult {
+                    url,
+                    title,
+                    matches: vec![hit],
+                });
+            }
+        }
+
+        for result in &mut results {
+            if result.matches.len() > MAX_VALUE_MATCHES {
+                let more = result.matches.len() - MAX_VALUE_MATCHES;
+                result.matches.truncate(MAX_VALUE_MATCHES);
+                result.matches.push(format!("and {more} more"));
+            }
+        }
+
+        results
     });
 
     view! {
         {move || {
             let results = results.get();
-            let has_results = !results.is_empty();
-            let results = results
-                .into_iter()
-                .map(|(id, title, matches)| {
-                    let url = format!("/settings/{id}/edit");
-                    view! {
-                        <a
-                            class="group flex flex-col bg-white border shadow-sm rounded-xl hover:shadow-md transition dark:bg-slate-900 dark:border-gray-800"
-                            href=url
-                        >
-                            <div class="p-4 md:p-5">
-                                <div class="flex justify-between items-center">
-                                    <div>
-                                        <h3 class="group-hover:text-blue-600 font-semibold text-gray-800 dark:group-hover:text-gray-400 dark:text-gray-200">
-                                            {title}
-                                        </h3>
-                                        <p class="text-sm text-gray-500">{matches}</p>
-                                    </div>
-                                    <div class="ps-3">
-                                        <svg
-                                            class="flex-shrink-0 size-5"
-                                            xmlns="http://www.w3.org/2000/svg"
-                                            width="24"
-                                            height="24"
-                                            viewBox="0 0 24 24"
-                                            fill="none"
-                                            stroke="currentColor"
-                                            stroke-width="2"
-                                            stroke-linecap="round"
-                                            stroke-linejoin="round"
-                                        >
-                                            <path d="m9 18 6-6-6-6"></path>
-                                        </svg>
-                                    </div>
-                                </div>
-                            </div>
-                        </a>
-                    }
-                        .into_view()
-                })
-                .collect_view();
+            let value_results = value_results.get();
+            let has_results = !results.is_empty() || !value_results.is_empty();
             if has_results {
                 view! {
                     <div class="max-w-[85rem] px-4 py-10 sm:px-6 lg:px-8 lg:py-14 mx-auto">
-                        <div class="grid sm:grid-cols-2 md:grid-cols-3 xl:grid-cols-4 gap-3 sm:gap-6">
-                            {results}
-                        </div>
-
+                        <SearchResults title="Configured values" results=value_results/>
+                        <SearchResults title="Settings" results=results/>
                     </div>
                 }
                     .into_view()
+            } else if let Some(error) = search_query.get().and_then(|q| search_query_error(&q)) {
+                view! {
+                    <ReportView>
+                        <ZeroResults title="Invalid search" subtitle=error/>
+                    </ReportView>
+                }
+                    .into_view()
             } else {
                 view! {
                     <ReportView>
                         <ZeroResults
                             title="No results"
-                            subtitle="No search settings were found with the selected criteria."
+                            subtitle=concat!(
+                                "No settings were found with the selected criteria. ",
+                                "Use /pattern/ for regular expressions or end the ",
+                                "query with * for prefix matches.",
+                            )
                         />
                     </ReportView>
                 }
@@ -112,12 +189,211 @@ pub fn SettingsSearch() -> impl IntoView {
     }
 }
 
+#[component]
+fn SearchResults(title: &'static str, results: Vec<SearchResult>) -> impl IntoView {
+    if results.is_empty() {
+        return None;
+    }
+
+    let results = results
+        .into_iter()
+        .map(|result| {
+            view! {
+                <a
+                    class="group flex flex-col bg-white border shadow-sm rounded-xl hover:shadow-md transition dark:bg-slate-900 dark:border-gray-800"
+                    href=result.url
+                >
+                    <div class="p-4 md:p-5">
+                        <div class="flex justify-between items-center">
+                            <div class="min-w-0">
+                                <h3 class="group-hover:text-blue-600 font-semibold text-gray-800 dark:group-hover:text-gray-400 dark:text-gray-200">
+                                    {result.title}
+                                </h3>
+
+                                {result
+                                    .matches
+                                    .into_iter()
+                                    .map(|m| {
+                                        view! { <p class="text-sm text-gray-500 truncate">{m}</p> }
+                                    })
+                                    .collect_view()}
+
+                            </div>
+                            <div class="ps-3">
+                                <svg
+                                    class="flex-shrink-0 size-5"
+                                    xmlns="http://www.w3.org/2000/svg"
+                                    width="24"
+                                    height="24"
+                                    viewBox="0 0 24 24"
+                                    fill="none"
+                                    stroke="currentColor"
+                                    stroke-width="2"
+                                    stroke-linecap="round"
+                                    stroke-linejoin="round"
+                                >
+                                    <path d="m9 18 6-6-6-6"></path>
+                                </svg>
+                            </div>
+                        </div>
+                    </div>
+                </a>
+            }
+        })
+        .collect_view();
+
+    Some(view! {
+        <h2 class="mb-4 text-lg font-semibold text-gray-800 dark:text-gray-200">{title}</h2>
+        <div class="mb-10 grid sm:grid-cols-2 md:grid-cols-3 xl:grid-cols-4 gap-3 sm:gap-6">
+            {results}
+        </div>
+    })
+}
+
+fn find_schemas(schemas: &Schemas, query: &SearchQuery) -> Vec<SearchResult> {
+    let mut results = schemas
+        .schemas
+        .values()
+        .filter_map(|s| {
+            let (title, matches) = s.form.contains_string(query)?;
+
+            Some(SearchResult {
+                url: format!("/settings/{}/edit", s.id),
+                title: title.to_string(),
+                matches: vec![matches.to_string()],
+            })
+        })
+        .collect::<Vec<_>>();
+    results.sort_by(|a, b| a.title.cmp(&b.title));
+    results
+}
+
+/// Returns the title, matched text and edit page of the settings forms
+/// matching a query.
+pub(crate) fn search_settings(schemas: &Schemas, query: &str) -> Vec<(String, String, String)> {
+    SearchQuery::parse(query)
+        .ok()
+        .flatten()
+        .map(|query| {
+            find_schemas(schemas, &query)
+                .into_iter()
+                .map(|mut result| (result.title, result.matches.remove(0), result.url))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Returns the reason a search query cannot be used, if any.
+pub(crate) fn search_query_error(query: &str) -> Option<String> {
+    SearchQuery::parse(query)
+        .err()
+        .map(|err| format!("Invalid regular expression: {err}"))
+}
+
+/// Finds the schema a configuration key belongs to, returning the record name
+/// for list schemas and the field the key is stored under.
+fn find_setting<'x>(
+    schemas: &'x Schemas,
+    key: &'x str,
+) -> Option<(&'x Arc<Schema>, Option<&'x str>, &'x Arc<Field>)> {
+    schemas
+        .schemas
+        .values()
+        .find_map(|schema| match schema.typ {
+            SchemaType::Record { prefix, .. } | SchemaType::Entry { prefix } => {
+                let (name, field) = key
+                    .strip_prefix(prefix)?
+                    .strip_prefix('.')?
+                    .split_once('.')?;
+                find_field(schema, field).map(|field| (schema, Some(name), field))
+            }
+            SchemaType::List => find_field(schema, key).map(|field| (schema, None, field)),
+        })
+}
+
+/// Returns the title and edit page of the settings a configuration key
+/// belongs to.
+pub(crate) fn setting_link(schemas: &Schemas, key: &str) -> Option<(String, String)> {
+    find_setting(schemas, key)
+        .map(|(schema, name, _)| (setting_title(schema, name), setting_url(schema, name)))
+}
+
+fn setting_url(schema: &Schema, name: Option<&str>) -> String {
+    match name {
+        Some(name) => format!("/settings/{}/{name}/edit", schema.id),
+        None => format!("/settings/{}/edit", schema.id),
+    }
+}
+
+fn setting_title(schema: &Schema, name: Option<&str>) -> String {
+    match name {
+        Some(name) => format!("{} {name}", schema.name_singular),
+        None => schema.form.title.to_string(),
+    }
+}
+
+fn find_field<'x>(schema: &'x Schema, key: &str) -> Option<&'x Arc<Field>> {
+    schema.fields.get(key).or_else(|| {
+        // Arrays and expressions are stored under sub-keys
+        schema.fields.iter().find_map(|(id, field)| {
+            key.strip_prefix(id)
+                .filter(|rest| rest.starts_with('.'))
+                .map(|_| field)
+        })
+    })
+}
+
+impl SearchQuery {
+    /// Parses a search query, failing with a description of the problem
+    /// when a `/pattern/` query is not a valid regular expression.
+    fn parse(query: &str) -> Result<Option<Self>, String> {
+        if let Some(pattern) = query
+            .strip_prefix('/')
+            .and_then(|q| q.strip_suffix('/'))
+            .filter(|q| !q.is_empty())
+        {
+            RegexBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .map(|regex| Some(SearchQuery::Regex(regex)))
+                .map_err(|err| match err {
+                    // Keep the last line, the rest points at the error position
+                    regex::Error::Syntax(err) => err
+                        .lines()
+                        .last()
+                        .map(|line| line.trim_start_matches("error: ").to_string())
+                        .unwrap_or(err),
+                    err => err.to_string(),
+                })
+        } else if let Some(prefix) = query.strip_suffix('*').filter(|q| !q.is_empty()) {
+            Ok(Some(SearchQuery::Prefix(prefix.to_lowercase())))
+        } else {
+            let terms = query
+                .split_whitespace()
+                .map(|s| s.to_lowercase())
+                .collect::<Vec<_>>();
+            Ok((!terms.is_empty()).then_some(SearchQuery::Terms(terms)))
+        }
+    }
+
+    fn matches(&self, text: &str) -> bool {
+        match self {
+            SearchQuery::Terms(terms) => {
+                let text = text.to_lowercase();
+                terms.iter().all(|t| text.contains(t))
+            }
+            SearchQuery::Prefix(prefix) => text.to_lowercase().starts_with(prefix),
+            SearchQuery::Regex(regex) => regex.is_match(text),
+        }
+    }
+}
+
 trait ContainsString {
-    fn contains_string(&self, query: &[String]) -> Option<&'static str>;
+    fn contains_string(&self, query: &SearchQuery) -> Option<&'static str>;
 }
 
 impl Form {
-    fn contains_string(&self, query: &[String]) -> Option<(&'static str, &'static str)> {
+    fn contains_string(&self, query: &SearchQuery) -> Option<(&'static str, &'static str)> {
         self.title
             .contains_string(query)
             .or_else(|| self.subtitle.contains_string(query))
@@ -132,7 +408,7 @@ impl Form {
 }
 
 impl ContainsString for Section {
-    fn contains_string(&self, query: &[String]) -> Option<&'static str> {
+    fn contains_string(&self, query: &SearchQuery) -> Option<&'static str> {
         self.title
             .as_ref()
             .and_then(|t| t.contains_string(query))
@@ -141,7 +417,7 @@ impl ContainsString for Section {
 }
 
 impl ContainsString for Field {
-    fn contains_string(&self, query: &[String]) -> Option<&'static str> {
+    fn contains_string(&self, query: &SearchQuery) -> Option<&'static str> {
         self.label_form
             .contains_string(query)
             .or_else(|| self.help.as_ref().and_then(|h| h.contains_string(query)))
@@ -150,9 +426,8 @@ impl ContainsString for Field {
 }
 
 impl ContainsString for &'static str {
-    fn contains_string(&self, query: &[String]) -> Option<&'static str> {
-        let s = self.to_ascii_lowercase();
-        if query.iter().all(|q| s.contains(q)) {
+    fn contains_string(&self, query: &SearchQuery) -> Option<&'static str> {
+        if query.matches(self) {
             Some(self)
         } else {
             None
@@ -0,0 +1,589 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use ahash::{AHashMap, AHashSet};
+use leptos::*;
+use leptos_router::use_navigate;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{input::InputText, FormElement, FormItem, FormSection},
+        Color,
+    },
+    core::{
+        expr::{quote_string, unquote_string},
+        form::FormData,
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+    },
+    pages::{
+        config::{alerts::AdvancedToggle, schema::SPAM_FILTER_VARS, Settings},
+        List,
+    },
+};
+
+pub const SPAM_RULE_SCHEMA: &str = "spam-rule";
+
+const INPUT_CLASS: &str = "py-2 px-3 block w-full border-gray-200 shadow-sm text-sm rounded-lg focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600";
+const SELECT_CLASS: &str = "py-2 px-3 pe-9 block border-gray-200 shadow-sm rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600";
+
+const THRESHOLD_KEY: &str = "lookup.spam-config.threshold-spam";
+
+/// Operators offered by the condition builder, and whether they take a value.
+const OPERATORS: &[(&str, &str, bool)] = &[
+    ("==", "equals", true),
+    ("!=", "does not equal", true),
+    ("contains", "contains", true),
+    ("starts_with", "starts with", true),
+    ("ends_with", "ends with", true),
+    ("matches", "matches regex", true),
+    ("is_empty", "is empty", false),
+    ("!is_empty", "is not empty", false),
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Clause {
+    variable: &'static str,
+    operator: &'static str,
+    value: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+struct RuleSet {
+    rules: Vec<ExistingRule>,
+    threshold: Option<f64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct ExistingRule {
+    id: String,
+    condition: String,
+    score: f64,
+    enabled: bool,
+}
+
+/// Guided editor for spam filter rules. The condition is assembled from
+/// clauses over the spam filter variables, and the rule is checked against
+/// the existing rules for duplicates and for the combined score of the rules
+/// that look at the same variables.
+#[component]
+pub fn SpamRuleBuilder(data: RwSignal<FormData>) -> impl IntoView {
+    let auth = use_authorization();
+
+    let current = data.with_untracked(|data| {
+        data.value_as_str("condition")
+            .unwrap_or_default()
+            .trim()
+            .to_string()
+    });
+    let parsed = parse_condition(&current);
+    let advanced = create_rw_signal(!current.is_empty() && parsed.is_none());
+    let (match_any, clauses) = parsed.unwrap_or_else(|| {
+        (
+            false,
+            vec![Clause {
+                variable: SPAM_FILTER_VARS[0],
+                operator: OPERATORS[0].0,
+                value: String::new(),
+            }],
+        )
+    });
+    let match_any = create_rw_signal(match_any);
+    let clauses = create_rw_signal(clauses);
+
+    // Keep the stored condition in sync with the builder
+    create_effect(move |prev: Option<()>| {
+        let expr = (!advanced.get())
+            .then(|| clauses.with(|clauses| build_condition(clauses, match_any.get())));
+        if prev.is_some() {
+            if let Some(expr) = expr {
+                data.update(|data| data.update("condition", expr));
+            }
+        }
+    });
+
+    let rule_set = create_resource(
+        move || (),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move {
+                let rules = HttpRequest::get("/api/settings/group")
+                    .with_authorization(&auth)
+                    .with_parameter("prefix", "spam-filter.rule")
+                    .with_parameter("suffix", "condition")
+                    .with_parameter("field", "_id")
+                    .send::<List<Settings>>()
+                    .await?
+                    .items
+                    .into_iter()
+                    .filter_map(|mut rule| {
+                        Some(ExistingRule {
+                            id: rule.remove("_id")?,
+                            condition: rule.remove("condition")?,
+                            score: rule
+                                .get("score")
+                                .and_then(|score| score.parse().ok())
+                                .unwrap_or_default(),
+                            enabled: rule.get("enable").is_none_or(|v| v == "true"),
+                        })
+                    })
+                    .collect();
+                let threshold = HttpRequest::get("/api/settings/keys")
+                    .with_authorization(&auth)
+                    .with_parameter("keys", THRESHOLD_KEY)
+                    .send::<AHashMap<String, Option<String>>>()
+                    .await?
+                    .remove(THRESHOLD_KEY)
+                    .flatten()
+                    .and_then(|value| value.parse().ok());
+
+                Ok(RuleSet { rules, threshold })
+            }
+        },
+    );
+
+    let other_rules = create_memo(move |_| {
+        let id = data.with(|data| data.value::<String>("_id").unwrap_or_default());
+        match rule_set.get() {
+            Some(Ok(rule_set)) => RuleSet {
+                rules: rule_set
+                    .rules
+                    .into_iter()
+                    .filter(|rule| !rule.id.eq_ignore_ascii_case(&id))
+                    .collect(),
+                threshold: rule_set.threshold,
+            },
+            Some(Err(http::Error::Unauthorized)) => {
+                use_navigate()("/login", Default::default());
+                RuleSet::default()
+            }
+            _ => RuleSet::default(),
+        }
+    });
+
+    let duplicates = create_memo(move |_| {
+        let condition =
+            normalize(&data.with(|data| data.value::<String>("condition").unwrap_or_default()));
+        if condition.is_empty() {
+            return Vec::new();
+        }
+        other_rules.with(|rule_set| {
+            rule_set
+                .rules
+                .iter()
+                .filter(|rule| normalize(&rule.condition) == condition)
+                .map(|rule| rule.id.clone())
+                .collect::<Vec<_>>()
+        })
+    });
+
+    let condition_error = create_memo(move |_| {
+        data.with(|data| data.error_string("condition").map(|err| err.to_string()))
+    });
+
+    view! {
+        <FormSection title="Condition".to_string() docs="spamfilter/rules#condition">
+            <Show
+                when=move || !advanced.get()
+                fallback=move || {
+                    view! {
+                        <FormItem
+                            label="Expression"
+                            tooltip="Expression that fires the rule when it evaluates to true"
+                            docs="configuration/expressions/overview"
+                        >
+                            <InputText
+                                element=FormElement::new("condition", data)
+                                placeholder="contains(subject, 'viagra')"
+                            />
+                            <AdvancedToggle advanced=advanced/>
+                        </FormItem>
+                    }
+                }
+            >
+
+                <FormItem label="Match" tooltip="Whether all or any of the clauses must match">
+                    <select
+                        class=SELECT_CLASS
+                        on:change=move |ev| match_any.set(event_target_value(&ev) == "any")
+                    >
+                        <option value="all" selected=move || !match_any.get()>
+                            All of the following
+                        </option>
+                        <option value="any" selected=move || match_any.get()>
+                            Any of the following
+                        </option>
+                    </select>
+                    <div class="mt-2 space-y-2">
+                        <For
+                            each=move || 0..clauses.with(|clauses| clauses.len())
+                            key=|idx| *idx
+                            let:idx
+                        >
+                            <ClauseRow clauses=clauses idx=idx/>
+                        </For>
+                    </div>
+                    <button
+                        type="button"
+                        class="mt-2 text-sm text-blue-600 decoration-2 hover:underline font-medium dark:text-blue-500"
+                        on:click=move |_| {
+                            clauses
+                                .update(|clauses| {
+                                    clauses
+                                        .push(Clause {
+                                            variable: SPAM_FILTER_VARS[0],
+                                            operator: OPERATORS[0].0,
+                                            value: String::new(),
+                                        });
+                                });
+                        }
+                    >
+
+                        Add clause
+                    </button>
+                    {move || {
+                        condition_error
+                            .get()
+                            .map(|error| {
+                                view! { <p class="text-xs text-red-600 mt-2">{error}</p> }
+                            })
+                    }}
+
+                    <p class="mt-2 text-xs font-mono text-gray-500 break-all">
+                        {move || data.with(|data| data.value::<String>("condition").unwrap_or_default())}
+                    </p>
+                    <AdvancedToggle advanced=advanced/>
+                </FormItem>
+            </Show>
+
+            <FormItem label="Duplicates" tooltip="Existing rules with the same condition">
+                {move || {
+                    let duplicates = duplicates.get();
+                    if duplicates.is_empty() {
+                        view! {
+                            <p class="mt-2.5 text-sm text-gray-500">
+                                No other rule has the same condition.
+                            </p>
+                        }
+                            .into_view()
+                    } else {
+                        view! {
+                            <div class="mt-2 flex flex-wrap items-center gap-2">
+                                <span class="text-sm text-gray-600 dark:text-gray-400">
+                                    Same condition as
+                                </span>
+                                {duplicates
+                                    .into_iter()
+                                    .map(|id| view! { <Badge color=Color::Yellow>{id}</Badge> })
+                                    .collect_view()}
+                            </div>
+                        }
+                            .into_view()
+                    }
+                }}
+
+            </FormItem>
+
+            <FormItem
+                label="Score preview"
+                tooltip="Score of this rule combined with the enabled rules that look at the same variables"
+            >
+                {move || {
+                    let score = data
+                        .with(|data| data.value::<f64>("score"))
+                        .unwrap_or_default();
+                    let variables = data
+                        .with(|data| {
+                            referenced_variables(
+                                data.value_as_str("condition").unwrap_or_default(),
+                            )
+                        });
+                    let (related, related_score, threshold) = other_rules
+                        .with(|rule_set| {
+                            let related = rule_set
+                                .rules
+                                .iter()
+                                .filter(|rule| {
+                                    rule.enabled
+                                        && !referenced_variables(&rule.condition)
+                                            .is_disjoint(&variables)
+                                })
+                                .collect::<Vec<_>>();
+                            (
+                                related.len(),
+                                related.iter().map(|rule| rule.score).sum::<f64>(),
+                                rule_set.threshold,
+                            )
+                        });
+                    let combined = score + related_score;
+                    let progress = threshold
+                        .filter(|threshold| *threshold > 0.0)
+                        .map_or(0.0, |threshold| (combined / threshold).clamp(0.0, 1.0) * 100.0);
+                    view! {
+                        <div class="mt-2.5 grid grid-cols-3 gap-3 text-sm">
+                            <div>
+                                <p class="text-xs text-gray-500">This rule</p>
+                                <p class="font-semibold text-gray-800 dark:text-gray-200">
+                                    {format!("{score:+}")}
+                                </p>
+                            </div>
+                            <div>
+                                <p class="text-xs text-gray-500">
+                                    {format!(
+                                        "{related} related {}",
+                                        if related == 1 { "rule" } else { "rules" },
+                                    )}
+
+                                </p>
+                                <p class="font-semibold text-gray-800 dark:text-gray-200">
+                                    {format!("{related_score:+}")}
+                                </p>
+                            </div>
+                            <div>
+                                <p class="text-xs text-gray-500">Combined</p>
+                                <p class="font-semibold text-gray-800 dark:text-gray-200">
+                                    {format!("{combined:+}")}
+                                </p>
+                            </div>
+                        </div>
+                        {threshold
+                            .map(|threshold| {
+                                let is_spam = combined >= threshold;
+                                view! {
+                                    <div class="mt-2 flex w-full h-1.5 bg-gray-200 rounded-full overflow-hidden dark:bg-gray-700">
+                                        <div
+                                            class="flex flex-col justify-center rounded-full overflow-hidden transition duration-500"
+                                            class=("bg-red-600", is_spam)
+                                            class=("bg-blue-600", !is_spam)
+                                            style=format!("width: {progress:.1}%")
+                                        ></div>
+                                    </div>
+                                    <p class="mt-1 text-xs text-gray-500">
+                                        {if is_spam {
+                                            format!(
+                                                "Messages matching these rules reach the spam threshold of {threshold}.",
+                                            )
+                                        } else {
+                                            format!("Spam threshold is {threshold}.")
+                                        }}
+
+                                    </p>
+                                }
+                            })}
+                    }
+                }}
+
+            </FormItem>
+        </FormSection>
+    }
+}
+
+#[component]
+fn ClauseRow(clauses: RwSignal<Vec<Clause>>, idx: usize) -> impl IntoView {
+    let clause = create_memo(move |_| clauses.with(|clauses| clauses.get(idx).cloned()));
+    let takes_value = move || {
+        clause.get().is_some_and(|clause| {
+            OPERATORS
+                .iter()
+                .any(|(op, _, takes_value)| *op == clause.operator && *takes_value)
+        })
+    };
+    let update = move |f: &dyn Fn(&mut Clause)| {
+        clauses.update(|clauses| {
+            if let Some(clause) = clauses.get_mut(idx) {
+                f(clause);
+            }
+        });
+    };
+
+    view! {
+        <div class="flex gap-x-2">
+            <select
+                class=SELECT_CLASS
+                on:change=move |ev| {
+                    let value = event_target_value(&ev);
+                    if let Some(variable) = SPAM_FILTER_VARS.iter().find(|v| **v == value) {
+                        update(&|clause| clause.variable = variable);
+                    }
+                }
+            >
+
+                {SPAM_FILTER_VARS
+                    .iter()
+                    .map(|variable| {
+                        let variable = *variable;
+                        view! {
+                            <option
+                                value=variable
+                                selected=move || clause.get().is_some_and(|c| c.variable == variable)
+                            >
+                                {variable}
+                            </option>
+                        }
+                    })
+                    .collect_view()}
+            </select>
+            <select
+                class=SELECT_CLASS
+                on:change=move |ev| {
+                    let value = event_target_value(&ev);
+                    if let Some((op, _, _)) = OPERATORS.iter().find(|(op, _, _)| *op == value) {
+                        update(&|clause| clause.operator = op);
+                    }
+                }
+            >
+
+                {OPERATORS
+                    .iter()
+                    .map(|(op, label, _)| {
+                        let op = *op;
+                        view! {
+                            <option
+                                value=op
+                                selected=move || clause.get().is_some_and(|c| c.operator == op)
+                            >
+                                {*label}
+                            </option>
+                        }
+                    })
+                    .collect_view()}
+            </select>
+            <input
+                type="text"
+                class=INPUT_CLASS
+                class:invisible=move || !takes_value()
+                prop:value=move || clause.get().map(|c| c.value).unwrap_or_default()
+                on:input=move |ev| {
+                    let value = event_target_value(&ev);
+                    update(&|clause| clause.value = value.clone());
+                }
+            />
+            <button
+                type="button"
+                class="py-2 px-3 text-sm text-gray-500 hover:text-red-600 disabled:opacity-50 disabled:pointer-events-none"
+                disabled=move || clauses.with(|clauses| clauses.len() < 2)
+                on:click=move |_| {
+                    clauses
+                        .update(|clauses| {
+                            if idx < clauses.len() {
+                                clauses.remove(idx);
+                            }
+                        });
+                }
+            >
+
+                Remove
+            </button>
+        </div>
+    }
+}
+
+fn build_condition(clauses: &[Clause], match_any: bool) -> String {
+    clauses
+        .iter()
+        .map(|clause| {
+            let var = clause.variable;
+            let value = quote(&clause.value);
+            match clause.operator {
+                "==" | "!=" => format!("{var} {} {value}", clause.operator),
+                "matches" => format!("matches({value}, {var})"),
+                "is_empty" => format!("is_empty({var})"),
+                "!is_empty" => format!("!is_empty({var})"),
+                op => format!("{op}({var}, {value})"),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(if match_any { " || " } else { " && " })
+}
+
+/// Parses conditions produced by the builder back into clauses, returning
+/// `None` for expressions that cannot be represented as a list of clauses.
+fn parse_condition(expr: &str) -> Option<(bool, Vec<Clause>)> {
+    if expr.is_empty() {
+        return None;
+    }
+    let match_any = expr.contains("||");
+    if match_any && expr.contains("&&") {
+        return None;
+    }
+    let string = r#"'((?:[^'\\]|\\.)*)'"#;
+    let empty = Regex::new(r"^(!?)is_empty\(([a-z_]+)\)$").ok()?;
+    let function = Regex::new(&format!(
+        r"^(contains|starts_with|ends_with)\(([a-z_]+),\s*{string}\)$"
+    ))
+    .ok()?;
+    let matches = Regex::new(&format!(r"^matches\({string},\s*([a-z_]+)\)$")).ok()?;
+    let compare = Regex::new(&format!(
+        r"^([a-z_]+)\s*(==|!=)\s*(?:{string}|(-?[0-9]+(?:\.[0-9]+)?))$"
+    ))
+    .ok()?;
+
+    expr.split(if match_any { "||" } else { "&&" })
+        .map(|clause| {
+            let clause = clause.trim();
+            let (variable, operator, value) = if let Some(c) = empty.captures(clause) {
+                let op = if c[1].is_empty() {
+                    "is_empty"
+                } else {
+                    "!is_empty"
+                };
+                (c[2].to_string(), op.to_string(), String::new())
+            } else if let Some(c) = function.captures(clause) {
+                (c[2].to_string(), c[1].to_string(), unquote_string(&c[3]))
+            } else if let Some(c) = matches.captures(clause) {
+                (
+                    c[2].to_string(),
+                    "matches".to_string(),
+                    unquote_string(&c[1]),
+                )
+            } else if let Some(c) = compare.captures(clause) {
+                let value = c
+                    .get(3)
+                    .map(|value| unquote_string(value.as_str()))
+                    .or_else(|| c.get(4).map(|value| value.as_str().to_string()))?;
+                (c[1].to_string(), c[2].to_string(), value)
+            } else {
+                return None;
+            };
+
+            Some(Clause {
+                variable: SPAM_FILTER_VARS.iter().find(|v| **v == variable)?,
+                operator: OPERATORS
+                    .iter()
+                    .find(|(op, _, _)| *op == operator)
+                    .map(|(op, _, _)| *op)?,
+                value,
+            })
+        })
+        .collect::<Option<Vec<_>>>()
+        .map(|clauses| (match_any, clauses))
+}
+
+/// Numbers are left as they are, anything else becomes a string literal.
+fn quote(value: &str) -> String {
+    if value.parse::<f64>().is_ok() {
+        value.to_string()
+    } else {
+        quote_string(value)
+    }
+}
+
+/// Strips whitespace so that conditions differing only in formatting compare
+/// as equal.
+fn normalize(condition: &str) -> String {
+    condition.chars().filter(|ch| !ch.is_whitespace()).collect()
+}
+
+fn referenced_variables(condition: &str) -> AHashSet<&'static str> {
+    condition
+        .split(|ch: char| !ch.is_ascii_alphanumeric() && ch != '_')
+        .filter_map(|word| SPAM_FILTER_VARS.iter().find(|v| **v == word).copied())
+        .collect()
+}
@@ -0,0 +1,283 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{button::Button, input::InputText, Form, FormElement, FormItem, FormSection},
+        list::table::{Table, TableRow},
+        messages::alert::{use_alerts, Alert},
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        schema::{Builder, Schemas, Transformer, Type, Validator},
+    },
+};
+
+pub const THROTTLE_SIMULATOR_SCHEMA: &str = "smtp-out-throttle";
+
+/// Usage ratio above which a throttle is reported as close to its limit.
+const NEAR_LIMIT_RATIO: f64 = 0.8;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct SimulatedEnvelope {
+    sender: String,
+    rcpt: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mx: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    remote_ip: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    local_ip: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+struct ThrottleResult {
+    id: String,
+    matched: bool,
+    #[serde(default)]
+    key: Option<String>,
+    #[serde(default)]
+    concurrency: Option<Usage>,
+    #[serde(default)]
+    rate: Option<Usage>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+struct Usage {
+    current: u64,
+    limit: u64,
+    #[serde(default)]
+    period: Option<String>,
+}
+
+/// Evaluates the outbound throttles against a sample envelope and reports
+/// how much of each limit the derived keys have already consumed.
+#[component]
+pub fn ThrottleSimulator() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let schemas = expect_context::<Arc<Schemas>>();
+    let data = schemas.build_form("throttle-simulator").into_signal();
+    let results = create_rw_signal(None::<Vec<ThrottleResult>>);
+
+    let simulate = create_action(move |envelope: &SimulatedEnvelope| {
+        let auth = auth.get();
+        let envelope = envelope.clone();
+
+        async move {
+            match HttpRequest::post("/api/troubleshoot/throttle")
+                .with_authorization(&auth)
+                .with_body(envelope)
+                .unwrap()
+                .send::<Vec<ThrottleResult>>()
+                .await
+            {
+                Ok(evaluated) => {
+                    results.set(Some(evaluated));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(http::Error::NotFound) => {
+                    alert.set(Alert::warning(
+                        "This server does not support throttle simulations.",
+                    ));
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    view! {
+        <Form title="Throttle simulator" subtitle="Check which throttles apply to a message">
+            <FormSection>
+                <FormItem
+                    label="Sender"
+                    tooltip="Envelope sender of the hypothetical message, leave empty for a bounce"
+                    is_optional=true
+                >
+                    <InputText
+                        element=FormElement::new("sender", data)
+                        placeholder="jane@example.org"
+                    />
+                </FormItem>
+                <FormItem label="Recipient" tooltip="Envelope recipient of the hypothetical message">
+                    <InputText
+                        element=FormElement::new("rcpt", data)
+                        placeholder="john@example.com"
+                    />
+                </FormItem>
+                <FormItem
+                    label="MX Host"
+                    tooltip="MX host the message is delivered to"
+                    is_optional=true
+                >
+                    <InputText element=FormElement::new("mx", data) placeholder="mx.example.com"/>
+                </FormItem>
+                <FormItem
+                    label="Remote IP"
+                    tooltip="IP address of the remote server"
+                    is_optional=true
+                >
+                    <InputText element=FormElement::new("remote_ip", data) placeholder="192.0.2.1"/>
+                </FormItem>
+                <FormItem
+                    label="Local IP"
+                    tooltip="IP address the connection is made from"
+                    is_optional=true
+                >
+                    <InputText
+                        element=FormElement::new("local_ip", data)
+                        placeholder="198.51.100.1"
+                    />
+                </FormItem>
+                <FormItem label="">
+                    <Button
+                        text="Simulate"
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| {
+                            let envelope = data
+                                .try_update(|data| {
+                                    if !data.validate_form() {
+                                        return None;
+                                    }
+                                    let optional = |id: &str| {
+                                        data.value::<String>(id).filter(|value| !value.is_empty())
+                                    };
+                                    Some(SimulatedEnvelope {
+                                        sender: data.value::<String>("sender").unwrap_or_default(),
+                                        rcpt: data.value::<String>("rcpt").unwrap_or_default(),
+                                        mx: optional("mx"),
+                                        remote_ip: optional("remote_ip"),
+                                        local_ip: optional("local_ip"),
+                                    })
+                                })
+                                .flatten();
+                            if let Some(envelope) = envelope {
+                                simulate.dispatch(envelope);
+                            }
+                        })
+
+                        disabled=simulate.pending()
+                    />
+                </FormItem>
+
+                {move || {
+                    results
+                        .get()
+                        .map(|results| {
+                            view! {
+                                <div class="sm:col-span-12">
+                                    <Table headers=vec![
+                                        "Throttle".to_string(),
+                                        "Status".to_string(),
+                                        "Key".to_string(),
+                                        "Concurrency".to_string(),
+                                        "Rate".to_string(),
+                                    ]>
+                                        {results
+                                            .into_iter()
+                                            .map(|result| {
+                                                let (color, status) = result.status();
+                                                view! {
+                                                    <TableRow>
+                                                        <span>{result.id}</span>
+                                                        <Badge color=color>{status}</Badge>
+                                                        <span class="font-mono">
+                                                            {result.key.unwrap_or_default()}
+                                                        </span>
+                                                        <span>{format_usage(result.concurrency)}</span>
+                                                        <span>{format_usage(result.rate)}</span>
+                                                    </TableRow>
+                                                }
+                                            })
+                                            .collect_view()}
+                                    </Table>
+                                </div>
+                            }
+                        })
+                }}
+
+            </FormSection>
+        </Form>
+    }
+}
+
+impl ThrottleResult {
+    fn status(&self) -> (Color, String) {
+        if let Some(error) = &self.error {
+            return (Color::Red, error.clone());
+        } else if !self.matched {
+            return (Color::Gray, "Not matched".to_string());
+        }
+
+        let ratio = [&self.concurrency, &self.rate]
+            .into_iter()
+            .flatten()
+            .map(|usage| usage.current as f64 / usage.limit.max(1) as f64)
+            .fold(0.0, f64::max);
+        if ratio >= 1.0 {
+            (Color::Red, "Would be throttled".to_string())
+        } else if ratio >= NEAR_LIMIT_RATIO {
+            (Color::Yellow, "Near limit".to_string())
+        } else {
+            (Color::Green, "Within limits".to_string())
+        }
+    }
+}
+
+fn format_usage(usage: Option<Usage>) -> String {
+    match usage {
+        Some(Usage {
+            current,
+            limit,
+            period: Some(period),
+        }) => format!("{current} / {limit} per {period}"),
+        Some(Usage { current, limit, .. }) => format!("{current} / {limit}"),
+        None => "-".to_string(),
+    }
+}
+
+impl Builder<Schemas, ()> {
+    pub fn build_throttle_simulator(self) -> Self {
+        self.new_schema("throttle-simulator")
+            .new_field("sender")
+            .typ(Type::Input)
+            .input_check(
+                [Transformer::Trim, Transformer::Lowercase],
+                [Validator::IsEmail],
+            )
+            .new_field("rcpt")
+            .input_check(
+                [Transformer::Trim, Transformer::Lowercase],
+                [Validator::Required, Validator::IsEmail],
+            )
+            .new_field("mx")
+            .input_check(
+                [Transformer::Trim, Transformer::Lowercase],
+                [Validator::IsHost],
+            )
+            .new_field("remote_ip")
+            .input_check([Transformer::Trim], [Validator::IsIpOrMask])
+            .new_field("local_ip")
+            .input_check([Transformer::Trim], [Validator::IsIpOrMask])
+            .build()
+            .build()
+    }
+}
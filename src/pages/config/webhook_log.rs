@@ -0,0 +1,325 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use chrono::{DateTime, Utc};
+use leptos::*;
+use leptos_router::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        badge::Badge,
+        icon::IconRefresh,
+        list::{
+            header::ColumnList,
+            pagination::Pagination,
+            toolbar::{SearchBox, ToolbarButton},
+            Footer, ListItem, ListSection, ListTable, ListTextItem, Toolbar, ZeroResults,
+        },
+        messages::alert::{use_alerts, Alert},
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        permissions::{use_capability, Capability},
+        url::UrlBuilder,
+    },
+    pages::{FormatDateTime, List},
+};
+
+const PAGE_SIZE: u32 = 50;
+const MAX_RESPONSE_LEN: usize = 120;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+struct WebhookDelivery {
+    id: String,
+    webhook_id: String,
+    url: String,
+    #[serde(default)]
+    events: Vec<String>,
+    #[serde(default)]
+    status: Option<u16>,
+    #[serde(default)]
+    latency_ms: Option<u64>,
+    #[serde(default)]
+    response: Option<String>,
+    #[serde(default)]
+    error: Option<String>,
+    created_at: DateTime<Utc>,
+}
+
+#[component]
+pub fn WebhookDeliveries() -> impl IntoView {
+    let query = use_query_map();
+    let page = create_memo(move |_| {
+        query
+            .with(|q| q.get("page").and_then(|page| page.parse::<u32>().ok()))
+            .filter(|&page| page > 0)
+            .unwrap_or(1)
+    });
+    let filter = create_memo(move |_| {
+        query.with(|q| {
+            q.get("filter").and_then(|s| {
+                let s = s.trim();
+                if !s.is_empty() {
+                    Some(s.to_string())
+                } else {
+                    None
+                }
+            })
+        })
+    });
+
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let deliveries = create_resource(
+        move || (page.get(), filter.get()),
+        move |(page, filter)| {
+            let auth = auth.get_untracked();
+
+            async move {
+                HttpRequest::get("/api/telemetry/webhooks/deliveries")
+                    .with_authorization(&auth)
+                    .with_parameter("page", page.to_string())
+                    .with_parameter("limit", PAGE_SIZE.to_string())
+                    .with_optional_parameter("filter", filter)
+                    .send::<List<WebhookDelivery>>()
+                    .await
+            }
+        },
+    );
+
+    let retry_action = create_action(move |id: &String| {
+        let auth = auth.get();
+        let id = id.clone();
+
+        async move {
+            match HttpRequest::post(("/api/telemetry/webhooks/deliveries", &id))
+                .with_authorization(&auth)
+                .send::<WebhookDelivery>()
+                .await
+            {
+                Ok(delivery) => {
+                    deliveries.refetch();
+                    alert.set(match (delivery.status, delivery.error) {
+                        (_, Some(error)) => Alert::error("Retry failed").with_details(error),
+                        (Some(status), None) if !(200..300).contains(&status) => {
+                            Alert::error(format!("Retry failed with HTTP status {status}"))
+                        }
+                        _ => Alert::success("Webhook delivered successfully"),
+                    });
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    let total_results = create_rw_signal(None::<u32>);
+
+    view! {
+        <ListSection>
+            <ListTable
+                title="Webhook deliveries"
+                subtitle="Recent delivery attempts made by the configured webhooks"
+            >
+                <Toolbar slot>
+                    <SearchBox
+                        value=filter
+                        on_search=move |value| {
+                            use_navigate()(
+                                &UrlBuilder::new("/settings/web-hooks/deliveries")
+                                    .with_parameter("filter", value)
+                                    .finish(),
+                                Default::default(),
+                            );
+                        }
+                    />
+
+                    <ToolbarButton
+                        text="Refresh"
+                        color=Color::Gray
+                        on_click=move |_| {
+                            deliveries.refetch();
+                        }
+                    >
+
+                        <IconRefresh/>
+                    </ToolbarButton>
+
+                </Toolbar>
+
+                <Transition fallback=Skeleton>
+                    {move || match deliveries.get() {
+                        None => None,
+                        Some(Err(http::Error::Unauthorized)) => {
+                            use_navigate()("/login", Default::default());
+                            Some(view! { <div></div> }.into_view())
+                        }
+                        Some(Err(http::Error::NotFound)) => {
+                            total_results.set(Some(0));
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="Delivery log not available"
+                                        subtitle="This server does not record webhook deliveries."
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Err(err)) => {
+                            total_results.set(Some(0));
+                            alert.set(Alert::from(err));
+                            Some(view! { <Skeleton/> }.into_view())
+                        }
+                        Some(Ok(deliveries)) if !deliveries.items.is_empty() => {
+                            total_results.set(Some(deliveries.total as u32));
+                            Some(
+                                view! {
+                                    <ColumnList headers=vec![
+                                        "Date".to_string(),
+                                        "Webhook".to_string(),
+                                        "Events".to_string(),
+                                        "Status".to_string(),
+                                        "Latency".to_string(),
+                                        "Response".to_string(),
+                                        "".to_string(),
+                                    ]>
+
+                                        <For
+                                            each=move || deliveries.items.clone()
+                                            key=|delivery| delivery.id.clone()
+                                            let:delivery
+                                        >
+                                            <DeliveryItem
+                                                delivery
+                                                on_retry=Callback::new(move |id| retry_action.dispatch(id))
+                                                retry_pending=retry_action.pending()
+                                            />
+                                        </For>
+
+                                    </ColumnList>
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Ok(_)) => {
+                            total_results.set(Some(0));
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="No results"
+                                        subtitle="No webhook deliveries were found with the selected criteria."
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                    }}
+
+                </Transition>
+
+                <Footer slot>
+
+                    <Pagination
+                        current_page=page
+                        total_results=total_results.read_only()
+                        page_size=PAGE_SIZE
+                        on_page_change=move |page: u32| {
+                            use_navigate()(
+                                &UrlBuilder::new("/settings/web-hooks/deliveries")
+                                    .with_parameter("page", page.to_string())
+                                    .with_optional_parameter("filter", filter.get())
+                                    .finish(),
+                                Default::default(),
+                            );
+                        }
+                    />
+
+                </Footer>
+            </ListTable>
+        </ListSection>
+    }
+}
+
+#[component]
+fn DeliveryItem(
+    delivery: WebhookDelivery,
+    on_retry: Callback<String, ()>,
+    retry_pending: ReadSignal<bool>,
+) -> impl IntoView {
+    let capability = use_capability(Capability::WebhookRetry);
+    let (color, status) = match delivery.status {
+        Some(status) if (200..300).contains(&status) => (Color::Green, status.to_string()),
+        Some(status) => (Color::Red, status.to_string()),
+        None => (Color::Red, "Failed".to_string()),
+    };
+    let latency = delivery
+        .latency_ms
+        .map(|ms| format!("{ms} ms"))
+        .unwrap_or_else(|| "-".to_string());
+    let response = delivery
+        .error
+        .or(delivery.response)
+        .map(|text| {
+            if text.chars().count() > MAX_RESPONSE_LEN {
+                format!(
+                    "{}...",
+                    text.chars().take(MAX_RESPONSE_LEN).collect::<String>()
+                )
+            } else {
+                text
+            }
+        })
+        .unwrap_or_default();
+    let id = delivery.id;
+
+    view! {
+        <tr>
+            <ListItem>
+                <span class="text-sm text-gray-500">{delivery.created_at.format_date_time()}</span>
+            </ListItem>
+            <ListItem>
+                <div class="flex flex-col">
+                    <a
+                        class="text-sm font-semibold text-blue-600 decoration-2 hover:underline dark:text-blue-500"
+                        href=format!("/settings/web-hooks/{}/edit", delivery.webhook_id)
+                    >
+                        {delivery.webhook_id.clone()}
+                    </a>
+                    <span class="text-xs text-gray-500">{delivery.url}</span>
+                </div>
+            </ListItem>
+            <ListTextItem>{delivery.events.join(", ")}</ListTextItem>
+            <ListItem>
+                <Badge color=color>{status}</Badge>
+            </ListItem>
+            <ListTextItem>{latency}</ListTextItem>
+            <ListItem>
+                <span class="text-sm text-gray-500 text-wrap font-mono">{response}</span>
+            </ListItem>
+            <ListItem subclass="px-6 py-1.5">
+                <button
+                    type="button"
+                    class="inline-flex items-center gap-x-1 text-sm text-blue-600 decoration-2 hover:underline font-medium disabled:opacity-50 disabled:pointer-events-none dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                    disabled=move || capability.is_denied() || retry_pending.get()
+                    title=move || capability.reason()
+                    class=(["disabled:pointer-events-auto", "disabled:cursor-not-allowed"], move || capability.is_denied())
+                    on:click=move |_| on_retry.call(id.clone())
+                >
+                    Retry
+                </button>
+            </ListItem>
+        </tr>
+    }
+}
@@ -0,0 +1,264 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        form::button::Button,
+        list::table::{Table, TableRow},
+        messages::alert::{use_alerts, Alert},
+        Color,
+    },
+    core::{clipboard::copy_to_clipboard, http::HttpRequest, oauth::use_authorization},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct ClientSetting {
+    source: String,
+    protocol: String,
+    hostname: String,
+    port: String,
+    security: String,
+    username: String,
+}
+
+pub const AUTODISCOVER_REQUEST: &str = concat!(
+    "<?xml version=\"1.0\" encoding=\"utf-8\"?>",
+    "<Autodiscover xmlns=\"http://schemas.microsoft.com/exchange/autodiscover/outlook/requestschema/2006\">",
+    "<Request><EMailAddress>{email}</EMailAddress>",
+    "<AcceptableResponseSchema>http://schemas.microsoft.com/exchange/autodiscover/outlook/responseschema/2006a</AcceptableResponseSchema>",
+    "</Request></Autodiscover>"
+);
+
+#[component]
+pub fn ClientConfigPreview(#[prop(into)] domain: String) -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let email = format!("user@{domain}");
+
+    let settings = create_resource(
+        move || email.clone(),
+        move |email| {
+            let auth = auth.get_untracked();
+
+            async move {
+                let mut settings = Vec::new();
+
+                // Thunderbird autoconfig
+                if let Ok(response) = HttpRequest::get("/mail/config-v1.1.xml")
+                    .with_base_url(&auth)
+                    .with_parameter("emailaddress", email.as_str())
+                    .send_raw()
+                    .await
+                {
+                    let response = String::from_utf8_lossy(&response);
+                    for tag in ["incomingServer", "outgoingServer"] {
+                        for (attributes, server) in xml_elements(&response, tag) {
+                            settings.push(ClientSetting {
+                                source: "Autoconfig".to_string(),
+                                protocol: xml_attribute(attributes, "type")
+                                    .unwrap_or_default()
+                                    .to_uppercase(),
+                                hostname: xml_text(server, "hostname").unwrap_or_default(),
+                                port: xml_text(server, "port").unwrap_or_default(),
+                                security: match xml_text(server, "socketType").as_deref() {
+                                    Some("SSL") => "SSL/TLS".to_string(),
+                                    Some("STARTTLS") => "STARTTLS".to_string(),
+                                    Some(other) => other.to_string(),
+                                    None => "None".to_string(),
+                                },
+                                username: xml_text(server, "username")
+                                    .unwrap_or_default()
+                                    .replace("%EMAILADDRESS%", "Full e-mail address"),
+                            });
+                        }
+                    }
+                }
+
+                // Outlook autodiscover
+                if let Ok(response) = HttpRequest::post("/autodiscover/autodiscover.xml")
+                    .with_base_url(&auth)
+                    .with_header("Content-Type", "text/xml")
+                    .with_raw_body(AUTODISCOVER_REQUEST.replace("{email}", &email))
+                    .send_raw()
+                    .await
+                {
+                    let response = String::from_utf8_lossy(&response);
+                    for (_, protocol) in xml_elements(&response, "Protocol") {
+                        let ssl = xml_text(protocol, "SSL").map_or(false, |v| v == "on");
+                        settings.push(ClientSetting {
+                            source: "Autodiscover".to_string(),
+                            protocol: xml_text(protocol, "Type").unwrap_or_default(),
+                            hostname: xml_text(protocol, "Server").unwrap_or_default(),
+                            port: xml_text(protocol, "Port").unwrap_or_default(),
+                            security: match xml_text(protocol, "Encryption").as_deref() {
+                                Some("TLS") => "STARTTLS".to_string(),
+                                Some("SSL") => "SSL/TLS".to_string(),
+                                Some(other) => other.to_string(),
+                                None if ssl => "SSL/TLS".to_string(),
+                                None => "None".to_string(),
+                            },
+                            username: xml_text(protocol, "LoginName")
+                                .map(|_| "Full e-mail address".to_string())
+                                .unwrap_or_default(),
+                        });
+                    }
+                }
+
+                settings
+            }
+        },
+    );
+
+    view! {
+        <div class="gap-2 sm:gap-4 py-8 first:pt-0 last:pb-0 border-t first:border-transparent border-gray-200 dark:border-gray-700 dark:first:border-transparent">
+            <div class="sm:col-span-12 pb-4 flex justify-between items-center">
+                <div>
+                    <h2 class="text-lg font-semibold text-gray-800 dark:text-gray-200">
+                        Client Configuration
+                    </h2>
+                    <p class="text-sm text-gray-500">
+                        Settings served to mail clients through autoconfig and autodiscover
+                    </p>
+                </div>
+                <Button
+                    text="Copy instructions"
+                    color=Color::Gray
+                    on_click=move |_| {
+                        let instructions = settings
+                            .get()
+                            .map(|settings| manual_instructions(&settings))
+                            .unwrap_or_default();
+                        if copy_to_clipboard(&instructions) {
+                            alert.set(Alert::success("Instructions copied to clipboard"));
+                        } else {
+                            alert.set(Alert::error("Failed to copy to clipboard"));
+                        }
+                    }
+                />
+
+            </div>
+            <Transition>
+                {move || {
+                    settings
+                        .get()
+                        .map(|settings| {
+                            if settings.is_empty() {
+                                view! {
+                                    <p class="text-sm text-gray-500">
+                                        The server did not return any client configuration for this domain.
+                                    </p>
+                                }
+                                    .into_view()
+                            } else {
+                                view! {
+                                    <Table headers=vec![
+                                        "Source".to_string(),
+                                        "Protocol".to_string(),
+                                        "Hostname".to_string(),
+                                        "Port".to_string(),
+                                        "Security".to_string(),
+                                        "Username".to_string(),
+                                    ]>
+                                        {settings
+                                            .into_iter()
+                                            .map(|setting| {
+                                                view! {
+                                                    <TableRow>
+                                                        <span>{setting.source}</span>
+                                                        <span>{setting.protocol}</span>
+                                                        <span>{setting.hostname}</span>
+                                                        <span>{setting.port}</span>
+                                                        <span>{setting.security}</span>
+                                                        <span>{setting.username}</span>
+                                                    </TableRow>
+                                                }
+                                            })
+                                            .collect_view()}
+                                    </Table>
+                                }
+                                    .into_view()
+                            }
+                        })
+                }}
+
+            </Transition>
+        </div>
+    }
+}
+
+/// Plain text instructions for end users configuring their client manually.
+fn manual_instructions(settings: &[ClientSetting]) -> String {
+    let mut instructions = String::new();
+    let source = if settings.iter().any(|s| s.source == "Autoconfig") {
+        "Autoconfig"
+    } else {
+        "Autodiscover"
+    };
+
+    for setting in settings.iter().filter(|s| s.source == source) {
+        let direction = if setting.protocol == "SMTP" {
+            "Outgoing mail"
+        } else {
+            "Incoming mail"
+        };
+        instructions.push_str(&format!(
+            "{direction} ({})\n  Server: {}\n  Port: {}\n  Security: {}\n  Username: {}\n\n",
+            setting.protocol,
+            setting.hostname,
+            setting.port,
+            setting.security,
+            if setting.username.is_empty() {
+                "Full e-mail address"
+            } else {
+                setting.username.as_str()
+            },
+        ));
+    }
+
+    instructions.trim_end().to_string()
+}
+
+fn xml_elements<'x>(xml: &'x str, tag: &str) -> Vec<(&'x str, &'x str)> {
+    let open = format!("<{tag}");
+    let close = format!("</{tag}>");
+    let mut elements = Vec::new();
+    let mut rest = xml;
+
+    while let Some(start) = rest.find(&open) {
+        rest = &rest[start + open.len()..];
+        // Skip tags that share the same prefix
+        if !rest.starts_with(['>', ' ', '\t', '\r', '\n']) {
+            continue;
+        }
+        let Some(attributes_end) = rest.find('>') else {
+            break;
+        };
+        let attributes = &rest[..attributes_end];
+        rest = &rest[attributes_end + 1..];
+        let Some(end) = rest.find(&close) else {
+            break;
+        };
+        elements.push((attributes, &rest[..end]));
+        rest = &rest[end + close.len()..];
+    }
+
+    elements
+}
+
+fn xml_text(xml: &str, tag: &str) -> Option<String> {
+    xml_elements(xml, tag)
+        .into_iter()
+        .next()
+        .map(|(_, text)| text.trim().to_string())
+}
+
+fn xml_attribute(attributes: &str, name: &str) -> Option<String> {
+    let (_, value) = attributes.split_once(&format!("{name}=\""))?;
+    value.split_once('"').map(|(value, _)| value.to_string())
+}
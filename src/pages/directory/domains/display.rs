@@ -6,7 +6,6 @@
 
 use leptos::*;
 use leptos_router::{use_navigate, use_params_map};
-use serde::{Deserialize, Serialize};
 
 use crate::{
     components::{
@@ -22,17 +21,12 @@ use crate::{
     core::{
         http::{self, HttpRequest},
         oauth::use_authorization,
+        url::UrlBuilder,
     },
-    pages::List,
+    pages::{config::dkim::DkimKeyCheck, List},
 };
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-struct DnsRecord {
-    #[serde(rename = "type")]
-    typ: String,
-    name: String,
-    content: String,
-}
+use super::{autoconfig::ClientConfigPreview, DnsRecord};
 
 #[component]
 pub fn DomainDisplay() -> impl IntoView {
@@ -138,7 +132,33 @@ pub fn DomainDisplay() -> impl IntoView {
 
                                 </div>
 
-                                <div class="flex justify-end">
+                                <DkimKeyCheck domain=Signal::derive(move || {
+                                    params.get().get("id").cloned().unwrap_or_default()
+                                })/>
+
+                                <ClientConfigPreview domain=params
+                                    .get()
+                                    .get("id")
+                                    .cloned()
+                                    .unwrap_or_default()/>
+
+                                <div class="flex justify-end gap-x-2">
+
+                                    <Button
+                                        text="Check DNS"
+                                        color=Color::Gray
+                                        on_click=move |_| {
+                                            use_navigate()(
+                                                &UrlBuilder::new("/manage/troubleshoot/dns")
+                                                    .with_parameter(
+                                                        "domain",
+                                                        params.get().get("id").cloned().unwrap_or_default(),
+                                                    )
+                                                    .finish(),
+                                                Default::default(),
+                                            );
+                                        }
+                                    />
 
                                     <Button
                                         text="Close"
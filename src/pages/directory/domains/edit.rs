@@ -21,7 +21,8 @@ use crate::{
     },
     core::{
         http::{Error, HttpRequest, ManagementApiError},
-        oauth::use_authorization,
+        oauth::{use_authorization, AuthToken},
+        permissions::Capability,
         schema::{Builder, Schemas, Transformer, Type, Validator},
     },
 };
@@ -66,26 +67,7 @@ pub fn DomainCreate() -> impl IntoView {
 
             // Create DKIM keys
             if result.is_ok() {
-                for algo in [Algorithm::Ed25519, Algorithm::Rsa] {
-                    result = HttpRequest::post("/api/dkim")
-                        .with_authorization(&auth)
-                        .with_body(DkimSignature {
-                            algorithm: algo,
-                            domain: name.clone(),
-                            ..Default::default()
-                        })
-                        .unwrap()
-                        .send::<()>()
-                        .await
-                        .map(|_| ());
-
-                    if !matches!(
-                        result,
-                        Ok(_) | Err(Error::Server(ManagementApiError::FieldAlreadyExists { .. }))
-                    ) {
-                        break;
-                    }
-                }
+                result = create_dkim_signatures(&auth, &name).await;
             }
 
             set_pending.set(false);
@@ -139,6 +121,7 @@ pub fn DomainCreate() -> impl IntoView {
                     })
 
                     disabled=pending
+                    capability=Capability::DomainCreate
                 />
             </FormButtonBar>
 
@@ -146,6 +129,30 @@ pub fn DomainCreate() -> impl IntoView {
     }
 }
 
+/// Creates the Ed25519 and RSA DKIM signatures for a domain, skipping the
+/// ones that already exist.
+pub(crate) async fn create_dkim_signatures(auth: &AuthToken, domain: &str) -> Result<(), Error> {
+    for algo in [Algorithm::Ed25519, Algorithm::Rsa] {
+        let result = HttpRequest::post("/api/dkim")
+            .with_authorization(auth)
+            .with_body(DkimSignature {
+                algorithm: algo,
+                domain: domain.to_string(),
+                ..Default::default()
+            })
+            .unwrap()
+            .send::<()>()
+            .await;
+
+        match result {
+            Ok(_) | Err(Error::Server(ManagementApiError::FieldAlreadyExists { .. })) => (),
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(())
+}
+
 impl Builder<Schemas, ()> {
     pub fn build_domains(self) -> Self {
         self.new_schema("domains")
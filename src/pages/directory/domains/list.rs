@@ -30,15 +30,23 @@ use crate::{
     core::{
         http::{self, HttpRequest},
         oauth::use_authorization,
+        permissions::{use_capability, Capability},
         url::UrlBuilder,
     },
-    pages::{maybe_plural, List},
+    pages::{
+        directory::trash::{
+            delete_permanently, expired_names, fetch_trash, move_to_trash, restore, Trash,
+            TrashEntry, TrashKind, GRACE_PERIOD_DAYS,
+        },
+        maybe_plural, FormatDateTime, List,
+    },
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct Domain {
     name: String,
     addresses: u32,
+    deleted: Option<TrashEntry>,
 }
 
 const PAGE_SIZE: u32 = 10;
@@ -70,6 +78,8 @@ pub fn DomainList() -> impl IntoView {
     let modal = use_modals();
     let selected = create_rw_signal::<HashSet<String>>(HashSet::new());
     provide_context(selected);
+    let trashed = create_rw_signal::<HashSet<String>>(HashSet::new());
+    let expired = create_rw_signal::<Vec<String>>(Vec::new());
 
     let domains = create_resource(
         move || (page.get(), filter.get()),
@@ -77,6 +87,18 @@ pub fn DomainList() -> impl IntoView {
             let auth = auth.get_untracked();
 
             async move {
+                // Accounts without access to the settings can still list domains
+                let mut trash = match fetch_trash(&auth, TrashKind::Domain).await {
+                    Ok(trash) => trash,
+                    Err(http::Error::Unauthorized) => return Err(http::Error::Unauthorized),
+                    Err(err) => {
+                        log::debug!("Failed to fetch trashed domains: {err:?}");
+                        Trash::new()
+                    }
+                };
+                trashed.set(trash.keys().cloned().collect());
+                expired.set(expired_names(&trash));
+
                 let domain_names = HttpRequest::get("/api/domain")
                     .with_authorization(&auth)
                     .with_parameter("page", page.to_string())
@@ -93,6 +115,7 @@ pub fn DomainList() -> impl IntoView {
                         .send::<List<String>>()
                         .await?;
                     items.push(Domain {
+                        deleted: trash.remove(&name),
                         name,
                         addresses: records.total as u32,
                     });
@@ -111,15 +134,10 @@ pub fn DomainList() -> impl IntoView {
         let auth = auth.get();
 
         async move {
-            for item in items.iter() {
-                if let Err(err) = HttpRequest::delete(("/api/domain", item))
-                    .with_authorization(&auth)
-                    .send::<()>()
-                    .await
-                {
-                    alert.set(Alert::from(err));
-                    return;
-                }
+            let names = items.iter().cloned().collect::<Vec<_>>();
+            if let Err(err) = delete_permanently(&auth, TrashKind::Domain, &names).await {
+                alert.set(Alert::from(err));
+                return;
             }
             domains.refetch();
             alert.set(Alert::success(format!(
@@ -129,6 +147,54 @@ pub fn DomainList() -> impl IntoView {
         }
     });
 
+    let trash_action = create_action(move |names: &Arc<Vec<String>>| {
+        let names = names.clone();
+        let auth = auth.get();
+
+        async move {
+            match move_to_trash(&auth, TrashKind::Domain, &names).await {
+                Ok(remove_at) => {
+                    domains.refetch();
+                    alert.set(Alert::success(format!(
+                        "Moved {} to the trash, permanent removal is scheduled for {}.",
+                        maybe_plural(names.len(), "domain", "domains"),
+                        remove_at.format_date()
+                    )));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    let restore_action = create_action(move |(name, entry): &(String, TrashEntry)| {
+        let name = name.clone();
+        let entry = entry.clone();
+        let auth = auth.get();
+
+        async move {
+            match restore(&auth, TrashKind::Domain, &name, &entry).await {
+                Ok(_) => {
+                    domains.refetch();
+                    alert.set(Alert::success(format!("{name} has been restored.")));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+    let on_restore = Callback::new(move |item: (String, TrashEntry)| {
+        restore_action.dispatch(item);
+    });
+
     let total_results = create_rw_signal(None::<u32>);
 
     view! {
@@ -155,8 +221,33 @@ pub fn DomainList() -> impl IntoView {
 
                         color=Color::Red
                         on_click=Callback::new(move |_| {
+                            let to_trash = trashed
+                                .with(|trashed| {
+                                    selected
+                                        .get()
+                                        .into_iter()
+                                        .filter(|name| !trashed.contains(name))
+                                        .collect::<Vec<_>>()
+                                });
                             let to_delete = selected.get().len();
-                            if to_delete > 0 {
+                            if !to_trash.is_empty() {
+                                let text = maybe_plural(to_trash.len(), "domain", "domains");
+                                let to_trash = Arc::new(to_trash);
+                                modal
+                                    .set(
+                                        Modal::with_title("Move to trash")
+                                            .with_message(
+                                                format!(
+                                                    "{text} will be deleted permanently in {GRACE_PERIOD_DAYS} days. Until then, they can be restored from this list.",
+                                                ),
+                                            )
+                                            .with_button(format!("Move {text} to trash"))
+                                            .with_dangerous_callback(move || {
+                                                selected.set(HashSet::new());
+                                                trash_action.dispatch(to_trash.clone());
+                                            }),
+                                    )
+                            } else if to_delete > 0 {
                                 let text = maybe_plural(to_delete, "domain", "domains");
                                 modal
                                     .set(
@@ -178,17 +269,54 @@ pub fn DomainList() -> impl IntoView {
                                     )
                             }
                         })
+
+                        capability=Capability::DomainDelete
                     >
 
                         <IconTrash/>
                     </ToolbarButton>
 
+                    <Show when=move || expired.with(|expired| !expired.is_empty())>
+                        <ToolbarButton
+                            text=Signal::derive(move || {
+                                format!("Purge expired ({})", expired.with(|expired| expired.len()))
+                            })
+
+                            color=Color::Red
+                            on_click=Callback::new(move |_| {
+                                let names = expired.get();
+                                let text = maybe_plural(names.len(), "domain", "domains");
+                                modal
+                                    .set(
+                                        Modal::with_title("Purge expired domains")
+                                            .with_message(
+                                                format!(
+                                                    "The grace period of {text} has expired. Are you sure you want to delete them permanently? This action cannot be undone.",
+                                                ),
+                                            )
+                                            .with_button(format!("Delete {text}"))
+                                            .with_dangerous_callback(move || {
+                                                delete_action
+                                                    .dispatch(Arc::new(names.iter().cloned().collect()));
+                                            }),
+                                    )
+                            })
+
+                            capability=Capability::DomainDelete
+                        >
+
+                            <IconTrash/>
+                        </ToolbarButton>
+                    </Show>
+
                     <ToolbarButton
                         text=format!("Add {}", "domain")
                         color=Color::Blue
                         on_click=move |_| {
                             use_navigate()("/manage/directory/domains/edit", Default::default());
                         }
+
+                        capability=Capability::DomainCreate
                     >
 
                         <IconAdd size=16 attr:class="flex-shrink-0 size-3"/>
@@ -234,7 +362,7 @@ pub fn DomainList() -> impl IntoView {
                                             key=|domain| domain.name.clone()
                                             let:domain
                                         >
-                                            <DomainItem domain/>
+                                            <DomainItem domain on_restore=on_restore/>
                                         </For>
 
                                     </ColumnList>
@@ -290,10 +418,13 @@ pub fn DomainList() -> impl IntoView {
 }
 
 #[component]
-fn DomainItem(domain: Domain) -> impl IntoView {
+fn DomainItem(domain: Domain, on_restore: Callback<(String, TrashEntry)>) -> impl IntoView {
+    let capability = use_capability(Capability::TrashRestore);
     let action_url = format!("/manage/directory/accounts?filter={}", domain.name);
     let domain_id = domain.name.clone();
     let manage_url = format!("/manage/directory/domains/{domain_id}/view",);
+    let restore_id = domain_id.clone();
+    let removal_text = domain.deleted.as_ref().map(|entry| entry.removal_text());
 
     view! {
         <tr>
@@ -306,10 +437,18 @@ fn DomainItem(domain: Domain) -> impl IntoView {
             </ListItem>
 
             <ListItem subclass="ps-6 lg:ps-3 xl:ps-0 pe-6 py-3">
-                <div class="flex items-center gap-x-3">
+                <div class="grow">
                     <span class="block text-sm font-semibold text-gray-800 dark:text-gray-200">
                         {domain.name}
                     </span>
+                    {removal_text
+                        .map(|text| {
+                            view! {
+                                <span class="block text-xs text-red-600 dark:text-red-500">
+                                    {text}
+                                </span>
+                            }
+                        })}
                 </div>
             </ListItem>
 
@@ -323,12 +462,37 @@ fn DomainItem(domain: Domain) -> impl IntoView {
             </ListItem>
 
             <ListItem subclass="px-6 py-1.5">
-                <a
-                    class="inline-flex items-center gap-x-1 text-sm text-blue-600 decoration-2 hover:underline font-medium dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
-                    href=manage_url
-                >
-                    DNS Records
-                </a>
+                {match domain.deleted {
+                    Some(entry) => {
+                        view! {
+                            <button
+                                type="button"
+                                class="inline-flex items-center gap-x-1 text-sm text-blue-600 decoration-2 hover:underline font-medium dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                                disabled=move || capability.is_denied()
+                                title=move || capability.reason()
+                                on:click=move |_| {
+                                    on_restore.call((restore_id.clone(), entry.clone()));
+                                }
+                            >
+
+                                Restore
+                            </button>
+                        }
+                            .into_view()
+                    }
+                    None => {
+                        view! {
+                            <a
+                                class="inline-flex items-center gap-x-1 text-sm text-blue-600 decoration-2 hover:underline font-medium dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                                href=manage_url
+                            >
+                                DNS Records
+                            </a>
+                        }
+                            .into_view()
+                    }
+                }}
+
             </ListItem>
         </tr>
     }
@@ -4,6 +4,17 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
+pub mod autoconfig;
 pub mod display;
 pub mod edit;
 pub mod list;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct DnsRecord {
+    #[serde(rename = "type")]
+    pub typ: String,
+    pub name: String,
+    pub content: String,
+}
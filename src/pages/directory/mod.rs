@@ -6,11 +6,16 @@
 
 use std::str::FromStr;
 
-use principals::SpecialSecrets;
+use base64::{engine::general_purpose::STANDARD, Engine};
 use serde::{Deserialize, Serialize};
 
 pub mod domains;
 pub mod principals;
+pub mod trash;
+
+pub const DISABLED_SECRET: &str = "$disabled$";
+pub const APP_PASSWORD_PREFIX: &str = "$app$";
+pub const OTP_AUTH_PREFIX: &str = "otpauth://";
 
 #[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Principal {
@@ -44,6 +49,13 @@ pub struct Principal {
     #[serde(rename = "members")]
     pub members: Vec<String>,
 
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(rename = "externalMembers")]
+    pub external_members: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<String>,
+
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
 }
@@ -85,6 +97,10 @@ pub enum PrincipalField {
     MemberOf,
     #[serde(rename = "members")]
     Members,
+    #[serde(rename = "externalMembers")]
+    ExternalMembers,
+    #[serde(rename = "roles")]
+    Roles,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
@@ -113,6 +129,10 @@ pub enum PrincipalValue {
 }
 
 impl Principal {
+    pub fn is_disabled(&self) -> bool {
+        self.secrets.iter().any(|secret| secret.is_disabled())
+    }
+
     pub fn is_blank(&self) -> bool {
         self.id.is_none()
             && self.typ.is_none()
@@ -121,6 +141,7 @@ impl Principal {
             && self.emails.is_empty()
             && self.member_of.is_empty()
             && self.members.is_empty()
+            && self.external_members.is_empty()
             && self.description.is_none()
     }
 
@@ -148,7 +169,7 @@ impl Principal {
             _ => {}
         }
         match (current.quota, changes.quota) {
-            (Some(current), Some(change)) if current != change => {
+            (current, Some(change)) if current != Some(change) => {
                 updates.push(PrincipalUpdate {
                     action: PrincipalAction::Set,
                     field: PrincipalField::Quota,
@@ -222,6 +243,11 @@ impl Principal {
                 changes.member_of,
             ),
             (PrincipalField::Members, current.members, changes.members),
+            (
+                PrincipalField::ExternalMembers,
+                current.external_members,
+                changes.external_members,
+            ),
         ] {
             for item in &change {
                 if !current.contains(item) {
@@ -248,6 +274,86 @@ impl Principal {
     }
 }
 
+impl PrincipalUpdate {
+    pub fn set(field: PrincipalField, value: PrincipalValue) -> Self {
+        PrincipalUpdate {
+            action: PrincipalAction::Set,
+            field,
+            value,
+        }
+    }
+
+    pub fn add_item(field: PrincipalField, value: impl Into<String>) -> Self {
+        PrincipalUpdate {
+            action: PrincipalAction::AddItem,
+            field,
+            value: PrincipalValue::String(value.into()),
+        }
+    }
+
+    pub fn remove_item(field: PrincipalField, value: impl Into<String>) -> Self {
+        PrincipalUpdate {
+            action: PrincipalAction::RemoveItem,
+            field,
+            value: PrincipalValue::String(value.into()),
+        }
+    }
+
+    /// Adds or removes the secret that prevents a principal from logging in.
+    pub fn set_disabled(disabled: bool) -> Self {
+        if disabled {
+            PrincipalUpdate::add_item(PrincipalField::Secrets, DISABLED_SECRET)
+        } else {
+            PrincipalUpdate::remove_item(PrincipalField::Secrets, DISABLED_SECRET)
+        }
+    }
+}
+
+pub fn parse_app_password(secret: &str) -> Option<(String, &str)> {
+    secret
+        .strip_prefix(APP_PASSWORD_PREFIX)
+        .and_then(|s| s.split_once('$'))
+        .and_then(|(app, password)| {
+            STANDARD
+                .decode(app)
+                .ok()
+                .and_then(|app| String::from_utf8(app).ok())
+                .map(|app| (app, password))
+        })
+}
+
+pub fn build_app_password(app: &str, password: &str) -> String {
+    format!("{APP_PASSWORD_PREFIX}{}${}", STANDARD.encode(app), password)
+}
+
+pub trait SpecialSecrets {
+    fn is_disabled(&self) -> bool;
+    fn is_otp_auth(&self) -> bool;
+    fn is_app_password(&self) -> bool;
+    fn is_password(&self) -> bool;
+}
+
+impl<T> SpecialSecrets for T
+where
+    T: AsRef<str>,
+{
+    fn is_disabled(&self) -> bool {
+        self.as_ref() == DISABLED_SECRET
+    }
+
+    fn is_otp_auth(&self) -> bool {
+        self.as_ref().starts_with(OTP_AUTH_PREFIX)
+    }
+
+    fn is_app_password(&self) -> bool {
+        self.as_ref().starts_with(APP_PASSWORD_PREFIX)
+    }
+
+    fn is_password(&self) -> bool {
+        !self.is_disabled() && !self.is_otp_auth() && !self.is_app_password()
+    }
+}
+
 impl PrincipalType {
     pub const fn id(&self) -> &'static str {
         match self {
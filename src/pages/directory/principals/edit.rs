@@ -21,7 +21,11 @@ use crate::{
             stacked_input::StackedInput,
             Form, FormButtonBar, FormElement, FormItem, FormSection, ValidateCb,
         },
-        messages::alert::{use_alerts, Alert},
+        messages::{
+            alert::{use_alerts, Alert},
+            conflict::{conflict_modal, ConflictResolution},
+            modal::use_modals,
+        },
         skeleton::Skeleton,
         Color,
     },
@@ -29,18 +33,36 @@ use crate::{
         form::FormData,
         http::{self, HttpRequest},
         oauth::use_authorization,
+        permissions::Capability,
         schema::{Builder, Schemas, Source, Transformer, Type, Validator},
+        unsaved::use_unsaved_changes,
+    },
+    pages::{
+        account::crypto::PrincipalCrypto,
+        directory::{
+            build_app_password, parse_app_password, Principal, PrincipalType, SpecialSecrets,
+            DISABLED_SECRET,
+        },
     },
-    pages::directory::{Principal, PrincipalType},
 };
 
-use super::{build_app_password, parse_app_password, SpecialSecrets};
+use super::{
+    graph::MembershipGraph,
+    members::{ExternalMemberCheck, MemberPicker},
+    quota::QuotaTemplateSelect,
+};
 
 #[component]
 pub fn PrincipalEdit() -> impl IntoView {
     let auth = use_authorization();
     let alert = use_alerts();
+    let modal = use_modals();
     let params = use_params_map();
+    let save_capability = if params.with_untracked(|params| params.get("id").is_some()) {
+        Capability::PrincipalUpdate
+    } else {
+        Capability::PrincipalCreate
+    };
     let fetch_principal = create_resource(
         move || params.get().get("id").cloned().unwrap_or_default(),
         move |name| {
@@ -74,9 +96,10 @@ pub fn PrincipalEdit() -> impl IntoView {
     let (pending, set_pending) = create_signal(false);
 
     let current_principal = create_rw_signal(Principal::default());
-    let data = expect_context::<Arc<Schemas>>()
-        .build_form("principals")
-        .into_signal();
+    let schemas = expect_context::<Arc<Schemas>>();
+    let data = schemas.build_form("principals").into_signal();
+    let unsaved = use_unsaved_changes();
+    unsaved.track(data);
 
     let principal_is_valid = create_action(
         move |(name, cb, expected_types): &(String, ValidateCb, Vec<PrincipalType>)| {
@@ -179,6 +202,66 @@ pub fn PrincipalEdit() -> impl IntoView {
         }
         .to_string()
     });
+    // Make sure nobody else modified the principal since it was loaded
+    let check_and_save = create_action(move |changes: &Principal| {
+        let current = current_principal.get();
+        let changes = changes.clone();
+        let auth = auth.get();
+        let selected_type = selected_type.get();
+        let schemas = schemas.clone();
+
+        async move {
+            if current.is_blank() {
+                save_changes.dispatch(changes);
+                return;
+            }
+
+            set_pending.set(true);
+            let name = current.name.clone().unwrap_or_default();
+            let principal = match HttpRequest::get(("/api/principal", &name))
+                .with_authorization(&auth)
+                .send::<Principal>()
+                .await
+            {
+                Ok(principal) => principal,
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                    return;
+                }
+                Err(err) => {
+                    set_pending.set(false);
+                    alert.set(Alert::from(err));
+                    return;
+                }
+            };
+            let mut latest = schemas.build_form("principals");
+            latest.from_principal(&principal, selected_type);
+            if !data.with_untracked(|data| data.is_outdated(&latest)) {
+                save_changes.dispatch(changes);
+                return;
+            }
+
+            set_pending.set(false);
+            let what = format!("the {} '{name}'", selected_type.item_name(false));
+            let latest = store_value((principal, latest));
+            modal.set(conflict_modal(&what, move |resolution| {
+                let (principal, latest) = latest.get_value();
+                current_principal.set(principal);
+                match resolution {
+                    ConflictResolution::Merge => {
+                        data.update(|data| data.rebase(latest));
+                        alert.set(
+                            Alert::warning("Changes merged")
+                                .with_details("Review the merged changes and save them again."),
+                        );
+                    }
+                    ConflictResolution::Overwrite => {
+                        save_changes.dispatch(changes.clone());
+                    }
+                }
+            }));
+        }
+    });
     let title = create_memo(move |_| {
         if let Some(name) = params.get().get("id") {
             match selected_type.get() {
@@ -230,9 +313,11 @@ pub fn PrincipalEdit() -> impl IntoView {
                     Some(Ok(principal)) => {
                         data.update(|data| {
                             data.from_principal(&principal, selected_type.get());
+                            data.mark_clean();
                         });
                         let used_quota = principal.used_quota.unwrap_or_default();
                         let total_quota = principal.quota.unwrap_or_default();
+                        let principal_name = principal.id.and(principal.name.clone());
                         current_principal.set(principal);
                         Some(
                             view! {
@@ -332,6 +417,7 @@ pub fn PrincipalEdit() -> impl IntoView {
                                     <Show when=move || {
                                         matches!(selected_type.get(), PrincipalType::Individual)
                                     }>
+                                        <QuotaTemplateSelect data=data/>
                                         <FormItem label="Disk quota">
                                             <div class="relative">
                                                 <InputSize element=FormElement::new("quota", data)/>
@@ -359,10 +445,7 @@ pub fn PrincipalEdit() -> impl IntoView {
                                     </Show>
 
                                     <Show when=move || {
-                                        matches!(
-                                            selected_type.get(),
-                                            PrincipalType::Group | PrincipalType::List
-                                        )
+                                        selected_type.get() == PrincipalType::Group
                                     }>
                                         <FormItem label="Members">
                                             <StackedBadge
@@ -375,11 +458,7 @@ pub fn PrincipalEdit() -> impl IntoView {
                                                         .dispatch((
                                                             value,
                                                             cb,
-                                                            if selected_type.get() == PrincipalType::Group {
-                                                                vec![PrincipalType::Individual, PrincipalType::Group]
-                                                            } else {
-                                                                vec![PrincipalType::Individual]
-                                                            },
+                                                            vec![PrincipalType::Individual, PrincipalType::Group],
                                                         ));
                                                 })
                                             />
@@ -417,6 +496,33 @@ pub fn PrincipalEdit() -> impl IntoView {
                                         </FormItem>
                                     </Show>
                                 </FormSection>
+
+                                <Show when=move || selected_type.get() == PrincipalType::List>
+                                    <FormSection title="Members".to_string()>
+                                        <MemberPicker data=data member_type=PrincipalType::Individual/>
+                                    </FormSection>
+                                    <FormSection title="External members".to_string()>
+                                        <FormItem
+                                            label="Addresses"
+                                            tooltip="Recipients outside this server that receive messages sent to the list"
+                                        >
+                                            <StackedInput
+                                                element=FormElement::new("external-members", data)
+                                                placeholder="Email"
+                                                add_button_text="Add Email".to_string()
+                                            />
+                                        </FormItem>
+                                        <ExternalMemberCheck data=data/>
+                                    </FormSection>
+                                </Show>
+
+                                {principal_name
+                                    .clone()
+                                    .filter(|_| selected_type.get() == PrincipalType::Group)
+                                    .map(|name| view! { <MembershipGraph name=name/> })}
+                                {principal_name
+                                    .filter(|_| selected_type.get() == PrincipalType::Individual)
+                                    .map(|name| view! { <PrincipalCrypto name=name/> })}
                             }
                                 .into_view(),
                         )
@@ -430,9 +536,8 @@ pub fn PrincipalEdit() -> impl IntoView {
                     text="Cancel"
                     color=Color::Gray
                     on_click=move |_| {
-                        use_navigate()(
+                        unsaved.navigate(
                             &format!("/manage/directory/{}", selected_type.get().resource_name()),
-                            Default::default(),
                         );
                     }
                 />
@@ -443,12 +548,13 @@ pub fn PrincipalEdit() -> impl IntoView {
                     on_click=Callback::new(move |_| {
                         data.update(|data| {
                             if let Some(changes) = data.to_principal() {
-                                save_changes.dispatch(changes);
+                                check_and_save.dispatch(changes);
                             }
                         });
                     })
 
                     disabled=pending
+                    capability=save_capability
                 />
             </FormButtonBar>
 
@@ -479,6 +585,7 @@ impl FormData {
         }
         self.array_set("member-of", principal.member_of.iter());
         self.array_set("members", principal.members.iter());
+        self.array_set("external-members", principal.external_members.iter());
         self.array_set("aliases", principal.emails.iter().skip(1));
 
         let mut app_passwords = vec![];
@@ -503,7 +610,7 @@ impl FormData {
                 .value::<String>("disabled")
                 .map_or(false, |v| v == "true")
             {
-                secrets.push("$disabled$".to_string());
+                secrets.push(DISABLED_SECRET.to_string());
             }
             for app_name in self.array_value("app_passwords") {
                 secrets.push(build_app_password(app_name, ""));
@@ -530,6 +637,10 @@ impl FormData {
                     .map(|m| m.to_string())
                     .collect(),
                 members: self.array_value("members").map(|m| m.to_string()).collect(),
+                external_members: self
+                    .array_value("external-members")
+                    .map(|m| m.to_string())
+                    .collect(),
                 description: self.value("description"),
                 ..Default::default()
             })
@@ -574,6 +685,13 @@ impl Builder<Schemas, ()> {
                 [Validator::IsEmail],
             )
             .build()
+            .new_field("external-members")
+            .typ(Type::Array)
+            .input_check(
+                [Transformer::Trim, Transformer::Lowercase],
+                [Validator::IsEmail],
+            )
+            .build()
             .new_field("description")
             .typ(Type::Input)
             .input_check([Transformer::Trim], [])
@@ -0,0 +1,293 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use ahash::AHashMap;
+use leptos::*;
+use leptos_router::use_navigate;
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{button::Button, FormSection},
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+    },
+    pages::directory::{Principal, PrincipalType},
+};
+
+const MAX_DEPTH: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Direction {
+    Members,
+    MemberOf,
+}
+
+#[derive(Clone, Copy)]
+struct GraphContext {
+    direction: RwSignal<Direction>,
+    expand_all: RwSignal<bool>,
+    cache: StoredValue<AHashMap<String, Option<Principal>>>,
+    cycles: RwSignal<Vec<Vec<String>>>,
+}
+
+/// Expandable tree of the nested memberships of a group, either down through
+/// its members or up through the groups it belongs to. Principals that appear
+/// again in their own chain are flagged as cycles and not expanded further.
+#[component]
+pub fn MembershipGraph(name: String) -> impl IntoView {
+    let ctx = GraphContext {
+        direction: create_rw_signal(Direction::Members),
+        expand_all: create_rw_signal(false),
+        cache: store_value(AHashMap::new()),
+        cycles: create_rw_signal(Vec::new()),
+    };
+    provide_context(ctx);
+    let root = store_value(name);
+
+    view! {
+        <FormSection title="Membership hierarchy".to_string()>
+            <div class="flex flex-wrap items-center gap-2 mb-4">
+                <div class="inline-flex rounded-lg shadow-sm">
+                    {[(Direction::Members, "Members"), (Direction::MemberOf, "Member of")]
+                        .into_iter()
+                        .map(|(direction, label)| {
+                            view! {
+                                <button
+                                    type="button"
+                                    class=move || {
+                                        if ctx.direction.get() == direction {
+                                            "py-2 px-3 text-sm font-medium border border-blue-600 bg-blue-600 text-white first:rounded-s-lg last:rounded-e-lg"
+                                        } else {
+                                            "py-2 px-3 text-sm font-medium border border-gray-200 bg-white text-gray-800 hover:bg-gray-50 first:rounded-s-lg last:rounded-e-lg dark:bg-slate-900 dark:border-gray-700 dark:text-white dark:hover:bg-gray-800"
+                                        }
+                                    }
+
+                                    on:click=move |_| {
+                                        ctx.cycles.set(Vec::new());
+                                        ctx.direction.set(direction);
+                                    }
+                                >
+
+                                    {label}
+                                </button>
+                            }
+                        })
+                        .collect_view()}
+                </div>
+                <Button
+                    text=Signal::derive(move || {
+                        if ctx.expand_all.get() { "Collapse all" } else { "Expand all" }.to_string()
+                    })
+
+                    color=Color::Gray
+                    on_click=move |_| ctx.expand_all.update(|expand| *expand = !*expand)
+                />
+            </div>
+
+            <Show when=move || ctx.cycles.with(|cycles| !cycles.is_empty())>
+                <div class="mb-4 p-3 text-sm border border-red-200 bg-red-50 text-red-800 rounded-lg dark:bg-red-800/10 dark:border-red-900 dark:text-red-500">
+                    <p class="font-semibold">Membership cycles detected</p>
+                    <ul class="mt-1 list-disc ps-5">
+                        {move || {
+                            ctx.cycles
+                                .get()
+                                .into_iter()
+                                .map(|cycle| view! { <li class="font-mono">{cycle.join(" → ")}</li> })
+                                .collect_view()
+                        }}
+
+                    </ul>
+                </div>
+            </Show>
+
+            // Rebuild the tree when switching direction or expanding everything
+            {move || {
+                ctx.direction.track();
+                ctx.expand_all.track();
+                view! {
+                    <ul class="text-sm">
+                        <TreeNode name=root.get_value() path=Vec::new()/>
+                    </ul>
+                }
+            }}
+
+        </FormSection>
+    }
+}
+
+#[component]
+fn TreeNode(name: String, path: Vec<String>) -> impl IntoView {
+    let ctx = expect_context::<GraphContext>();
+    let auth = use_authorization();
+    let depth = path.len();
+    let is_cycle = path.contains(&name);
+    let expanded = create_rw_signal(depth == 0 || ctx.expand_all.get_untracked());
+
+    if is_cycle {
+        let mut cycle = path[path.iter().position(|p| p == &name).unwrap_or_default()..].to_vec();
+        cycle.push(name.clone());
+        ctx.cycles.update(|cycles| {
+            if !cycles.contains(&cycle) {
+                cycles.push(cycle);
+            }
+        });
+    }
+
+    let name_ = name.clone();
+    let principal = create_resource(
+        move || (),
+        move |_| {
+            let auth = auth.get_untracked();
+            let name = name_.clone();
+
+            async move {
+                if let Some(cached) = ctx.cache.with_value(|cache| cache.get(&name).cloned()) {
+                    return Ok(cached);
+                }
+                let result = match HttpRequest::get(("/api/principal", &name))
+                    .with_authorization(&auth)
+                    .send::<Principal>()
+                    .await
+                {
+                    Ok(principal) => Some(principal),
+                    Err(http::Error::NotFound) => None,
+                    Err(err) => return Err(err),
+                };
+                ctx.cache
+                    .update_value(|cache| _ = cache.insert(name, result.clone()));
+                Ok(result)
+            }
+        },
+    );
+
+    let children = create_memo(move |_| {
+        let direction = ctx.direction.get();
+        principal
+            .get()
+            .and_then(|result| result.ok())
+            .flatten()
+            .map(|principal| match direction {
+                Direction::Members => principal.members,
+                Direction::MemberOf => principal.member_of,
+            })
+            .unwrap_or_default()
+    });
+    let typ = create_memo(move |_| {
+        principal
+            .get()
+            .and_then(|result| result.ok())
+            .map(|principal| principal.and_then(|p| p.typ))
+    });
+    let child_path = store_value({
+        let mut path = path;
+        path.push(name.clone());
+        path
+    });
+    let label = name.clone();
+    let href = create_memo(move |_| {
+        typ.get().flatten().and_then(|typ| match typ {
+            PrincipalType::Individual | PrincipalType::Group | PrincipalType::List => Some(
+                format!("/manage/directory/{}/{}/edit", typ.resource_name(), name),
+            ),
+            _ => None,
+        })
+    });
+
+    view! {
+        <li class="py-0.5" style=format!("padding-inline-start: {}rem", if depth > 0 { 1.25 } else { 0.0 })>
+            <div class="flex items-center gap-x-2">
+                <button
+                    type="button"
+                    class="size-5 inline-flex justify-center items-center rounded text-gray-500 hover:bg-gray-100 disabled:opacity-30 dark:hover:bg-gray-800"
+                    disabled=move || { is_cycle || depth >= MAX_DEPTH || children.with(|c| c.is_empty()) }
+                    on:click=move |_| expanded.update(|expanded| *expanded = !*expanded)
+                >
+                    {move || {
+                        if is_cycle || children.with(|c| c.is_empty()) {
+                            "·"
+                        } else if expanded.get() {
+                            "▾"
+                        } else {
+                            "▸"
+                        }
+                    }}
+
+                </button>
+                {move || match href.get() {
+                    Some(href) => {
+                        view! {
+                            <a
+                                class="font-mono text-blue-600 decoration-2 hover:underline dark:text-blue-500"
+                                href=href
+                            >
+                                {label.clone()}
+                            </a>
+                        }
+                            .into_view()
+                    }
+                    None => {
+                        view! {
+                            <span class="font-mono text-gray-800 dark:text-gray-200">
+                                {label.clone()}
+                            </span>
+                        }
+                            .into_view()
+                    }
+                }}
+
+                {move || {
+                    typ.get()
+                        .map(|typ| match typ {
+                            Some(typ) => {
+                                view! { <Badge color=Color::Gray>{typ.item_name(false)}</Badge> }
+                            }
+                            None => view! { <Badge color=Color::Red>not found</Badge> },
+                        })
+                }}
+
+                <Show when=move || is_cycle>
+                    <Badge color=Color::Red>cycle</Badge>
+                </Show>
+                <Show when=move || !is_cycle && expanded.get() && !children.with(|c| c.is_empty())>
+                    <span class="text-xs text-gray-500">
+                        {move || children.with(|c| c.len())}
+                    </span>
+                </Show>
+                {move || match principal.get() {
+                    Some(Err(http::Error::Unauthorized)) => {
+                        use_navigate()("/login", Default::default());
+                        None
+                    }
+                    Some(Err(err)) => {
+                        Some(view! { <Badge color=Color::Red>{format!("{err:?}")}</Badge> })
+                    }
+                    _ => None,
+                }}
+
+            </div>
+            <Show when=move || { !is_cycle && depth < MAX_DEPTH && expanded.get() }>
+                <ul>
+                    {move || {
+                        children
+                            .get()
+                            .into_iter()
+                            .map(|child| {
+                                view! { <TreeNode name=child path=child_path.get_value()/> }
+                                    .into_view()
+                            })
+                            .collect_view()
+                    }}
+
+                </ul>
+            </Show>
+        </li>
+    }
+    .into_view()
+}
@@ -4,8 +4,9 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
-use std::{collections::HashSet, sync::Arc};
+use std::{collections::HashSet, sync::Arc, time::Duration};
 
+use gloo_storage::{SessionStorage, Storage};
 use humansize::{format_size, DECIMAL};
 use leptos::*;
 use leptos_router::*;
@@ -13,33 +14,46 @@ use leptos_router::*;
 use crate::{
     components::{
         badge::Badge,
-        icon::{IconAdd, IconTrash},
+        icon::{IconAdd, IconScale, IconTrash},
         list::{
             header::ColumnList,
             pagination::Pagination,
+            query::use_list_query,
             row::SelectItem,
             toolbar::{SearchBox, ToolbarButton},
             Footer, ListItem, ListSection, ListTable, ListTextItem, Toolbar, ZeroResults,
         },
         messages::{
             alert::{use_alerts, Alert},
-            modal::{use_modals, Modal},
+            modal::{use_modals, Modal, ModalStep},
         },
         skeleton::Skeleton,
         Color,
     },
     core::{
         http::{self, HttpRequest},
-        oauth::use_authorization,
-        url::UrlBuilder,
+        oauth::{oauth_impersonate, use_authorization, AuthToken},
+        permissions::{use_capability, Capability, Permissions},
     },
     pages::{
-        directory::{Principal, PrincipalType},
-        maybe_plural, List,
+        directory::{
+            trash::{
+                delete_permanently, expired_names, fetch_trash, move_to_trash, restore, Trash,
+                TrashEntry, TrashKind, GRACE_PERIOD_DAYS,
+            },
+            Principal, PrincipalField, PrincipalType, PrincipalUpdate, PrincipalValue,
+        },
+        maybe_plural, FormatDateTime, List,
     },
+    STATE_STORAGE_KEY,
 };
 
+use super::quota::{fetch_quota_templates, QuotaTemplate, SELECT_CLASS};
+
 const PAGE_SIZE: u32 = 10;
+/// Accounts fetched when filtering by status, which the server does not index.
+const STATUS_SCAN_LIMIT: u32 = 100;
+const STATUS_SELECT_CLASS: &str = "py-2 px-3 pe-9 block border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400";
 
 #[component]
 pub fn PrincipalList() -> impl IntoView {
@@ -62,41 +76,55 @@ pub fn PrincipalList() -> impl IntoView {
         }
     });
 
-    let query = use_query_map();
-    let page = create_memo(move |_| {
-        query
-            .with(|q| q.get("page").and_then(|page| page.parse::<u32>().ok()))
-            .filter(|&page| page > 0)
-            .unwrap_or(1)
-    });
-    let filter = create_memo(move |_| {
-        query.with(|q| {
-            q.get("filter").and_then(|s| {
-                let s = s.trim();
-                if !s.is_empty() {
-                    Some(s.to_string())
-                } else {
-                    None
-                }
-            })
-        })
+    let list = use_list_query();
+    let page = list.page();
+    let filter = list.param("filter");
+    let status_param = list.param("status");
+    let status = create_memo(move |_| {
+        status_param
+            .get()
+            .filter(|s| matches!(s.as_str(), "active" | "disabled"))
     });
 
     let auth = use_authorization();
     let alert = use_alerts();
     let modal = use_modals();
+    let trash = create_rw_signal(Trash::new());
+    let expired = create_memo(move |_| trash.with(expired_names));
 
     let principals = create_resource(
-        move || (page.get(), filter.get()),
-        move |(page, filter)| {
+        move || (page.get(), filter.get(), status.get()),
+        move |(page, filter, status)| {
             let auth = auth.get_untracked();
             let selected_type = selected_type.get();
+            let status = status.filter(|_| selected_type == PrincipalType::Individual);
 
             async move {
+                // Accounts without access to the settings can still be listed
+                if selected_type == PrincipalType::Individual {
+                    match fetch_trash(&auth, TrashKind::Account).await {
+                        Ok(accounts) => trash.set(accounts),
+                        Err(http::Error::Unauthorized) => return Err(http::Error::Unauthorized),
+                        Err(err) => {
+                            log::debug!("Failed to fetch trashed accounts: {err:?}");
+                            trash.set(Trash::new());
+                        }
+                    }
+                } else {
+                    trash.set(Trash::new());
+                }
+
+                // The status is not indexed, filter a bounded number of
+                // accounts before paginating
+                let (fetch_page, fetch_limit) = if status.is_some() {
+                    (1, STATUS_SCAN_LIMIT)
+                } else {
+                    (page, PAGE_SIZE)
+                };
                 let principal_names = HttpRequest::get("/api/principal")
+                    .with_parameter("page", fetch_page.to_string())
+                    .with_parameter("limit", fetch_limit.to_string())
                     .with_authorization(&auth)
-                    .with_parameter("page", page.to_string())
-                    .with_parameter("limit", PAGE_SIZE.to_string())
                     .with_parameter("type", selected_type.id())
                     .with_optional_parameter("filter", filter)
                     .send::<List<String>>()
@@ -119,10 +147,31 @@ pub fn PrincipalList() -> impl IntoView {
                     }
                 }
 
-                Ok(Arc::new(List {
-                    items,
-                    total: principal_names.total,
-                }))
+                if let Some(status) = status {
+                    if principal_names.total > STATUS_SCAN_LIMIT as u64 {
+                        alert.set(Alert::warning(format!(
+                            concat!(
+                                "Only the first {} accounts were checked, ",
+                                "search for an account to narrow down the list."
+                            ),
+                            STATUS_SCAN_LIMIT
+                        )));
+                    }
+                    let show_disabled = status == "disabled";
+                    items.retain(|principal| principal.is_disabled() == show_disabled);
+                    let total = items.len() as u64;
+                    let items = items
+                        .into_iter()
+                        .skip(((page - 1) * PAGE_SIZE) as usize)
+                        .take(PAGE_SIZE as usize)
+                        .collect();
+                    Ok(Arc::new(List { items, total }))
+                } else {
+                    Ok(Arc::new(List {
+                        items,
+                        total: principal_names.total,
+                    }))
+                }
             }
         },
     );
@@ -132,15 +181,23 @@ pub fn PrincipalList() -> impl IntoView {
         let auth = auth.get();
 
         async move {
-            for item in items.iter() {
-                if let Err(err) = HttpRequest::delete(("/api/principal", item))
-                    .with_authorization(&auth)
-                    .send::<()>()
-                    .await
-                {
+            if selected_type.get() == PrincipalType::Individual {
+                let names = items.iter().cloned().collect::<Vec<_>>();
+                if let Err(err) = delete_permanently(&auth, TrashKind::Account, &names).await {
                     alert.set(Alert::from(err));
                     return;
                 }
+            } else {
+                for item in items.iter() {
+                    if let Err(err) = HttpRequest::delete(("/api/principal", item))
+                        .with_authorization(&auth)
+                        .send::<()>()
+                        .await
+                    {
+                        alert.set(Alert::from(err));
+                        return;
+                    }
+                }
             }
             principals.refetch();
             alert.set(Alert::success(format!(
@@ -154,6 +211,250 @@ pub fn PrincipalList() -> impl IntoView {
         }
     });
 
+    let trash_action = create_action(move |names: &Arc<Vec<String>>| {
+        let names = names.clone();
+        let auth = auth.get();
+
+        async move {
+            match move_to_trash(&auth, TrashKind::Account, &names).await {
+                Ok(remove_at) => {
+                    principals.refetch();
+                    alert.set(Alert::success(format!(
+                        "Moved {} to the trash, permanent removal is scheduled for {}.",
+                        maybe_plural(names.len(), "account", "accounts"),
+                        remove_at.format_date()
+                    )));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    let restore_action = create_action(move |(name, entry): &(String, TrashEntry)| {
+        let name = name.clone();
+        let entry = entry.clone();
+        let auth = auth.get();
+
+        async move {
+            match restore(&auth, TrashKind::Account, &name, &entry).await {
+                Ok(_) => {
+                    principals.refetch();
+                    alert.set(Alert::success(format!("{name} has been restored.")));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+    let on_restore = Callback::new(move |item: (String, TrashEntry)| {
+        restore_action.dispatch(item);
+    });
+
+    let toggle_action = create_action(move |(name, disable): &(String, bool)| {
+        let name = name.clone();
+        let disable = *disable;
+        let auth = auth.get();
+
+        async move {
+            let update = PrincipalUpdate::set_disabled(disable);
+            match HttpRequest::patch(("/api/principal", &name))
+                .with_authorization(&auth)
+                .with_body(vec![update])
+                .unwrap()
+                .send::<()>()
+                .await
+            {
+                Ok(_) => {
+                    principals.refetch();
+                    alert.set(Alert::success(if disable {
+                        format!("{name} has been disabled.")
+                    } else {
+                        format!("{name} has been enabled.")
+                    }));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+    let on_toggle = Callback::new(move |item: (String, bool)| {
+        toggle_action.dispatch(item);
+    });
+
+    let impersonate_action = create_action(move |name: &String| {
+        let name = name.clone();
+        let admin = auth.get();
+
+        async move {
+            match oauth_impersonate(&admin, &name).await {
+                Ok(grant) => {
+                    let refresh_token = grant.refresh_token.unwrap_or_default();
+                    auth.set(AuthToken {
+                        base_url: admin.base_url.clone(),
+                        access_token: grant.access_token.into(),
+                        refresh_token: refresh_token.clone().into(),
+                        username: name.into(),
+                        is_valid: true,
+                        is_admin: false,
+                        impersonator: Some(Box::new(admin)),
+                        is_locked: false,
+                        permissions: Permissions::default(),
+                    });
+                    if let Err(err) = SessionStorage::set(STATE_STORAGE_KEY, auth.get_untracked()) {
+                        log::error!("Failed to save state to session storage: {}", err);
+                    }
+
+                    // Set timer to refresh token
+                    if grant.expires_in > 0 && !refresh_token.is_empty() {
+                        set_timeout(
+                            move || {
+                                auth.update(|auth| {
+                                    auth.is_valid = false;
+                                });
+                            },
+                            Duration::from_secs(grant.expires_in),
+                        );
+                    }
+
+                    use_navigate()("/account/overview", Default::default());
+                }
+                Err(err) => {
+                    alert.set(err);
+                }
+            }
+        }
+    });
+    let on_impersonate = Callback::new(move |name: String| {
+        let text = name.clone();
+        modal.set(
+            Modal::with_title("Act as user")
+                .with_message(format!(
+                    "You will be signed in as {text} to manage their encryption, app passwords and two-factor authentication settings. Use the Exit button on the banner to return to your session."
+                ))
+                .with_button(format!("Act as {text}"))
+                .with_callback(move || {
+                    impersonate_action.dispatch(name.clone());
+                }),
+        );
+    });
+
+    let apply_quota_action = create_action(
+        move |(items, template): &(Arc<HashSet<String>>, QuotaTemplate)| {
+            let items = items.clone();
+            let template = template.clone();
+            let auth = auth.get();
+
+            async move {
+                for item in items.iter() {
+                    if let Err(err) = HttpRequest::patch(("/api/principal", item))
+                        .with_authorization(&auth)
+                        .with_body(vec![PrincipalUpdate::set(
+                            PrincipalField::Quota,
+                            PrincipalValue::Integer(template.quota),
+                        )])
+                        .unwrap()
+                        .send::<()>()
+                        .await
+                    {
+                        alert.set(Alert::from(err));
+                        return;
+                    }
+                }
+                principals.refetch();
+                alert.set(Alert::success(format!(
+                    "Applied template '{}' to {}.",
+                    template.name,
+                    maybe_plural(items.len(), "account", "accounts")
+                )));
+            }
+        },
+    );
+    let apply_quota = move |templates: Vec<QuotaTemplate>| {
+        let to_update = selected.get().len();
+        if to_update == 0 {
+            return;
+        } else if templates.is_empty() {
+            alert.set(Alert::warning(
+                "No quota templates have been defined, add one under Settings > Authentication > Quota Templates.",
+            ));
+            return;
+        }
+        let text = maybe_plural(to_update, "account", "accounts");
+        let templates = store_value(templates);
+        let template_id = create_rw_signal(templates.with_value(|t| t[0].id.clone()));
+        modal.set(
+            Modal::with_title("Apply quota template")
+                .with_step(
+                    ModalStep::new("Apply quota template")
+                        .with_message(format!("Choose the quota template to apply to {text}."))
+                        .with_content(move || {
+                            view! {
+                                <select
+                                    class=format!("mt-4 {SELECT_CLASS}")
+                                    on:change=move |ev| template_id.set(event_target_value(&ev))
+                                >
+                                    {templates
+                                        .get_value()
+                                        .into_iter()
+                                        .map(|template| {
+                                            let id = template.id.clone();
+                                            view! {
+                                                <option
+                                                    value=template.id.clone()
+                                                    selected=move || template_id.get() == id
+                                                >
+                                                    {template.label()}
+                                                </option>
+                                            }
+                                        })
+                                        .collect_view()}
+                                </select>
+                            }
+                        })
+                        .with_button(format!("Apply to {text}")),
+                )
+                .with_callback(move || {
+                    let id = template_id.get_untracked();
+                    if let Some(template) =
+                        templates.with_value(|t| t.iter().find(|t| t.id == id).cloned())
+                    {
+                        apply_quota_action.dispatch((
+                            Arc::new(selected.try_update(std::mem::take).unwrap_or_default()),
+                            template,
+                        ));
+                    }
+                }),
+        );
+    };
+    let load_templates = create_action(move |_: &()| {
+        let auth = auth.get();
+
+        async move {
+            match fetch_quota_templates(&auth).await {
+                Ok(templates) => apply_quota(templates),
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
     let total_results = create_rw_signal(None::<u32>);
     let title = Signal::derive(move || {
         match selected_type.get() {
@@ -182,20 +483,37 @@ pub fn PrincipalList() -> impl IntoView {
                     <SearchBox
                         value=filter
                         on_search=move |value| {
-                            use_navigate()(
-                                &UrlBuilder::new(
-                                        format!(
-                                            "/manage/directory/{}",
-                                            selected_type.get().resource_name(),
-                                        ),
-                                    )
-                                    .with_parameter("filter", value)
-                                    .finish(),
-                                Default::default(),
-                            );
+                            list.set_filters([("filter", Some(value))]);
                         }
                     />
 
+                    <Show when=move || selected_type.get() == PrincipalType::Individual>
+                        <select
+                            class=STATUS_SELECT_CLASS
+                            on:change=move |ev| {
+                                list.set_filters([("status", Some(event_target_value(&ev)))]);
+                            }
+                        >
+
+                            {[("", "All accounts"), ("active", "Active"), ("disabled", "Disabled")]
+                                .into_iter()
+                                .map(|(value, label)| {
+                                    view! {
+                                        <option
+                                            value=value
+                                            selected=move || {
+                                                status.get().as_deref().unwrap_or_default() == value
+                                            }
+                                        >
+
+                                            {label}
+                                        </option>
+                                    }
+                                })
+                                .collect_view()}
+                        </select>
+                    </Show>
+
                     <ToolbarButton
                         text=Signal::derive(move || {
                             let ns = selected.get().len();
@@ -204,13 +522,48 @@ pub fn PrincipalList() -> impl IntoView {
 
                         color=Color::Red
                         on_click=Callback::new(move |_| {
-                            let to_delete = selected.get().len();
-                            if to_delete > 0 {
+                            let names = selected.get().into_iter().collect::<Vec<_>>();
+                            let to_trash = if selected_type.get() == PrincipalType::Individual {
+                                trash
+                                    .with(|trash| {
+                                        names
+                                            .iter()
+                                            .filter(|name| !trash.contains_key(*name))
+                                            .cloned()
+                                            .collect::<Vec<_>>()
+                                    })
+                            } else {
+                                vec![]
+                            };
+                            if !to_trash.is_empty() {
+                                let text = maybe_plural(to_trash.len(), "account", "accounts");
+                                let to_trash = Arc::new(to_trash);
+                                modal
+                                    .set(
+                                        Modal::with_title("Move to trash")
+                                            .with_message(
+                                                format!(
+                                                    "{text} will be disabled and deleted permanently in {GRACE_PERIOD_DAYS} days. Until then, they can be restored from this list.",
+                                                ),
+                                            )
+                                            .with_button(format!("Move {text} to trash"))
+                                            .with_dangerous_callback(move || {
+                                                selected.set(HashSet::new());
+                                                trash_action.dispatch(to_trash.clone());
+                                            }),
+                                    )
+                            } else if !names.is_empty() {
                                 let text = maybe_plural(
-                                    to_delete,
+                                    names.len(),
                                     selected_type.get().item_name(false),
                                     selected_type.get().item_name(true),
                                 );
+                                let confirm_text = if names.len() == 1 {
+                                    names[0].clone()
+                                } else {
+                                    text.clone()
+                                };
+                                let names = Arc::new(names);
                                 modal
                                     .set(
                                         Modal::with_title("Confirm deletion")
@@ -220,6 +573,12 @@ pub fn PrincipalList() -> impl IntoView {
                                                 ),
                                             )
                                             .with_button(format!("Delete {text}"))
+                                            .with_confirmation_text(confirm_text)
+                                            .with_impact(move || {
+                                                let auth = auth.get_untracked();
+                                                let names = names.clone();
+                                                async move { deletion_impact(&auth, &names).await }
+                                            })
                                             .with_dangerous_callback(move || {
                                                 delete_action
                                                     .dispatch(
@@ -231,11 +590,71 @@ pub fn PrincipalList() -> impl IntoView {
                                     )
                             }
                         })
+
+                        capability=Capability::PrincipalDelete
                     >
 
                         <IconTrash/>
                     </ToolbarButton>
 
+                    <Show when=move || expired.with(|expired| !expired.is_empty())>
+                        <ToolbarButton
+                            text=Signal::derive(move || {
+                                format!("Purge expired ({})", expired.with(|expired| expired.len()))
+                            })
+
+                            color=Color::Red
+                            on_click=Callback::new(move |_| {
+                                let names = expired.get();
+                                let text = maybe_plural(names.len(), "account", "accounts");
+                                modal
+                                    .set(
+                                        Modal::with_title("Purge expired accounts")
+                                            .with_message(
+                                                format!(
+                                                    "The grace period of {text} has expired. Are you sure you want to delete them permanently? This action cannot be undone.",
+                                                ),
+                                            )
+                                            .with_button(format!("Delete {text}"))
+                                            .with_dangerous_callback(move || {
+                                                delete_action
+                                                    .dispatch(Arc::new(names.iter().cloned().collect()));
+                                            }),
+                                    )
+                            })
+
+                            capability=Capability::PrincipalDelete
+                        >
+
+                            <IconTrash/>
+                        </ToolbarButton>
+                    </Show>
+
+                    <Show when=move || selected_type.get() == PrincipalType::Individual>
+                        <ToolbarButton
+                            text=Signal::derive(move || {
+                                let ns = selected.get().len();
+                                if ns > 0 {
+                                    format!("Apply quota ({ns})")
+                                } else {
+                                    "Apply quota".to_string()
+                                }
+                            })
+
+                            color=Color::Gray
+                            on_click=Callback::new(move |_| {
+                                if !selected.get().is_empty() {
+                                    load_templates.dispatch(());
+                                }
+                            })
+
+                            capability=Capability::PrincipalUpdate
+                        >
+
+                            <IconScale/>
+                        </ToolbarButton>
+                    </Show>
+
                     <ToolbarButton
                         text=create_memo(move |_| {
                             format!("Create {}", selected_type.get().item_name(false))
@@ -251,6 +670,8 @@ pub fn PrincipalList() -> impl IntoView {
                                 Default::default(),
                             );
                         }
+
+                        capability=Capability::PrincipalCreate
                     >
 
                         <IconAdd size=16 attr:class="flex-shrink-0 size-3"/>
@@ -279,6 +700,7 @@ pub fn PrincipalList() -> impl IntoView {
                                         "Name".to_string(),
                                         "E-mail".to_string(),
                                         "Type".to_string(),
+                                        "Status".to_string(),
                                         "Usage".to_string(),
                                         "Member of".to_string(),
                                         "".to_string(),
@@ -323,7 +745,20 @@ pub fn PrincipalList() -> impl IntoView {
                                             key=|principal| principal.name.clone().unwrap_or_default()
                                             let:principal
                                         >
-                                            <PrincipalItem principal selected_type=selected_type.get()/>
+                                            <PrincipalItem
+                                                deleted=trash
+                                                    .with_untracked(|trash| {
+                                                        principal
+                                                            .name
+                                                            .as_ref()
+                                                            .and_then(|name| trash.get(name).cloned())
+                                                    })
+                                                principal
+                                                selected_type=selected_type.get()
+                                                on_restore=on_restore
+                                                on_toggle=on_toggle
+                                                on_impersonate=on_impersonate
+                                            />
                                         </For>
                                     </ColumnList>
                                 }
@@ -367,18 +802,7 @@ pub fn PrincipalList() -> impl IntoView {
                         total_results=total_results.read_only()
                         page_size=PAGE_SIZE
                         on_page_change=move |page: u32| {
-                            use_navigate()(
-                                &UrlBuilder::new(
-                                        format!(
-                                            "/manage/directory/{}",
-                                            selected_type.get().resource_name(),
-                                        ),
-                                    )
-                                    .with_parameter("page", page.to_string())
-                                    .with_optional_parameter("filter", filter.get())
-                                    .finish(),
-                                Default::default(),
-                            );
+                            list.set_page(page);
                         }
                     />
 
@@ -389,7 +813,16 @@ pub fn PrincipalList() -> impl IntoView {
 }
 
 #[component]
-fn PrincipalItem(principal: Principal, selected_type: PrincipalType) -> impl IntoView {
+fn PrincipalItem(
+    principal: Principal,
+    selected_type: PrincipalType,
+    deleted: Option<TrashEntry>,
+    on_restore: Callback<(String, TrashEntry)>,
+    on_toggle: Callback<(String, bool)>,
+    on_impersonate: Callback<String>,
+) -> impl IntoView {
+    let restore_capability = use_capability(Capability::TrashRestore);
+    let impersonate_capability = use_capability(Capability::Impersonate);
     let name = principal.name.as_deref().unwrap_or("unknown").to_string();
     let display_name = principal
         .description
@@ -408,6 +841,11 @@ fn PrincipalItem(principal: Principal, selected_type: PrincipalType) -> impl Int
         selected_type.resource_name(),
         principal_id
     );
+    let principal_id_ = principal_id.clone();
+    let removal_text = deleted.as_ref().map(|entry| entry.removal_text());
+    let is_deleted = deleted.is_some();
+    let is_disabled = principal.is_disabled();
+    let toggle_id = store_value(principal_id.clone());
     let num_members = principal.members.len();
     let num_member_of = principal.member_of.len();
 
@@ -432,6 +870,14 @@ fn PrincipalItem(principal: Principal, selected_type: PrincipalType) -> impl Int
                             {display_name}
                         </span>
                         <span class="block text-sm text-gray-500">{name}</span>
+                        {removal_text
+                            .map(|text| {
+                                view! {
+                                    <span class="block text-xs text-red-600 dark:text-red-500">
+                                        {text}
+                                    </span>
+                                }
+                            })}
                     </div>
                 </div>
             </ListItem>
@@ -456,9 +902,29 @@ fn PrincipalItem(principal: Principal, selected_type: PrincipalType) -> impl Int
 
                     {principal.typ.unwrap_or(selected_type).name()}
                 </Badge>
+                {is_deleted.then(|| view! { <Badge color=Color::Red>Deleted</Badge> })}
 
             </ListItem>
             <Show when=move || { selected_type == PrincipalType::Individual }>
+                <ListItem>
+                    <div class="flex items-center gap-x-3">
+                        <input
+                            type="checkbox"
+                            title=if is_disabled { "Enable account" } else { "Disable account" }
+                            class="relative w-11 h-6 p-px bg-gray-100 border-transparent text-transparent rounded-full cursor-pointer transition-colors ease-in-out duration-200 focus:ring-blue-600 disabled:opacity-50 disabled:pointer-events-none checked:bg-none checked:text-blue-600 checked:border-blue-600 focus:checked:border-blue-600 dark:bg-gray-800 dark:border-gray-700 dark:checked:bg-blue-500 dark:checked:border-blue-500 dark:focus:ring-offset-gray-600 before:inline-block before:size-5 before:bg-white checked:before:bg-blue-200 before:translate-x-0 checked:before:translate-x-full before:rounded-full before:shadow before:transform before:ring-0 before:transition before:ease-in-out before:duration-200 dark:before:bg-gray-400 dark:checked:before:bg-blue-200"
+                            prop:checked=!is_disabled
+                            disabled=is_deleted
+                            on:change=move |_| on_toggle.call((toggle_id.get_value(), !is_disabled))
+                        />
+
+                        {if is_disabled {
+                            view! { <Badge color=Color::Gray>Disabled</Badge> }
+                        } else {
+                            view! { <Badge color=Color::Green>Active</Badge> }
+                        }}
+
+                    </div>
+                </ListItem>
                 <ListTextItem>
                     {match (principal.quota, principal.used_quota) {
                         (Some(quota), Some(used_quota)) if quota > 0 => {
@@ -485,14 +951,108 @@ fn PrincipalItem(principal: Principal, selected_type: PrincipalType) -> impl Int
                 <ListTextItem>{maybe_plural(num_member_of, "group", "groups")}</ListTextItem>
             </Show>
             <ListItem subclass="px-6 py-1.5">
-                <a
-                    class="inline-flex items-center gap-x-1 text-sm text-blue-600 decoration-2 hover:underline font-medium dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
-                    href=manage_url
-                >
-                    Edit
-                </a>
+                {match deleted {
+                    Some(entry) => {
+                        let principal_id = principal_id_.clone();
+                        view! {
+                            <button
+                                type="button"
+                                class="inline-flex items-center gap-x-1 text-sm text-blue-600 decoration-2 hover:underline font-medium dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                                disabled=move || restore_capability.is_denied()
+                                title=move || restore_capability.reason()
+                                on:click=move |_| {
+                                    on_restore.call((principal_id.clone(), entry.clone()));
+                                }
+                            >
+
+                                Restore
+                            </button>
+                        }
+                            .into_view()
+                    }
+                    None => {
+                        let principal_id = principal_id_.clone();
+                        view! {
+                            <div class="flex items-center gap-x-3">
+                                <a
+                                    class="inline-flex items-center gap-x-1 text-sm text-blue-600 decoration-2 hover:underline font-medium dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                                    href=manage_url
+                                >
+                                    Edit
+                                </a>
+                                {(selected_type == PrincipalType::Individual)
+                                    .then(|| {
+                                        view! {
+                                            <button
+                                                type="button"
+                                                class="inline-flex items-center gap-x-1 text-sm text-blue-600 decoration-2 hover:underline font-medium dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                                                disabled=move || impersonate_capability.is_denied()
+                                                title=move || impersonate_capability.reason()
+                                                on:click=move |_| {
+                                                    on_impersonate.call(principal_id.clone());
+                                                }
+                                            >
+
+                                                Act as user
+                                            </button>
+                                        }
+                                    })}
+
+                            </div>
+                        }
+                            .into_view()
+                    }
+                }}
+
             </ListItem>
 
         </tr>
     }
 }
+
+/// Summarizes the data that is lost when the given principals are deleted.
+async fn deletion_impact(auth: &AuthToken, names: &[String]) -> Result<Vec<String>, String> {
+    let mut used_quota = 0;
+    let mut emails = 0;
+    let mut members = 0;
+    for name in names {
+        let principal = HttpRequest::get(("/api/principal", name))
+            .with_authorization(auth)
+            .send::<Principal>()
+            .await
+            .map_err(|err| Alert::from(err).message)?;
+        used_quota += principal.used_quota.unwrap_or_default();
+        emails += principal.emails.len();
+        members += principal.members.len();
+    }
+
+    let mut impact = Vec::new();
+    let subject = if names.len() == 1 {
+        format!("\"{}\" holds", names[0])
+    } else {
+        "The selected principals hold".to_string()
+    };
+    if used_quota > 0 {
+        impact.push(format!(
+            "{subject} {} of messages and files, which will be deleted permanently.",
+            format_size(used_quota, DECIMAL)
+        ));
+    }
+    if emails > 0 {
+        impact.push(format!(
+            "{} will stop receiving messages.",
+            maybe_plural(emails, "e-mail address", "e-mail addresses")
+        ));
+    }
+    if members > 0 {
+        impact.push(format!(
+            "{} will lose their membership.",
+            maybe_plural(members, "member", "members")
+        ));
+    }
+    if impact.is_empty() {
+        impact.push("No stored data or addresses are affected.".to_string());
+    }
+
+    Ok(impact)
+}
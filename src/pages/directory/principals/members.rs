@@ -0,0 +1,658 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::collections::HashSet;
+
+use ahash::AHashMap;
+use leptos::*;
+use leptos_router::use_navigate;
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{button::Button, FormItem},
+        list::{pagination::Pagination, toolbar::SearchBox},
+        messages::alert::{use_alerts, Alert},
+        Color,
+    },
+    core::{
+        dns::{doh_lookup, RCODE_NO_ERROR, RCODE_NX_DOMAIN, TYPE_MX},
+        form::FormData,
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        permissions::Capability,
+    },
+    pages::{
+        directory::{Principal, PrincipalType},
+        List,
+    },
+};
+
+const PAGE_SIZE: u32 = 20;
+const MAX_DOMAINS: u32 = 1000;
+
+const SELECT_CLASS: &str = "py-2 px-3 pe-9 block w-full border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600";
+const CHECKBOX_CLASS: &str = "shrink-0 border-gray-200 rounded text-blue-600 focus:ring-blue-500 disabled:opacity-50 dark:bg-gray-800 dark:border-gray-700";
+const PANE_CLASS: &str =
+    "flex flex-col border border-gray-200 rounded-xl overflow-hidden dark:border-gray-700";
+
+/// Two pane editor for the members of a principal: accounts in the directory
+/// on one side and current members on the other, both searchable, with bulk
+/// add and remove.
+#[component]
+pub fn MemberPicker(data: RwSignal<FormData>, member_type: PrincipalType) -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+
+    let available_page = create_rw_signal(1u32);
+    let available_filter = create_rw_signal(None::<String>);
+    let available_domain = create_rw_signal(None::<String>);
+    let available_selected = create_rw_signal(HashSet::<String>::new());
+    let member_page = create_rw_signal(1u32);
+    let member_filter = create_rw_signal(None::<String>);
+    let member_selected = create_rw_signal(HashSet::<String>::new());
+
+    let members = create_memo(move |_| {
+        data.with(|data| {
+            data.array_value("members")
+                .map(|member| member.to_string())
+                .collect::<Vec<_>>()
+        })
+    });
+    let filtered_members = create_memo(move |_| {
+        let filter = member_filter.get().map(|filter| filter.to_lowercase());
+        members.with(|members| {
+            members
+                .iter()
+                .filter(|member| {
+                    filter
+                        .as_ref()
+                        .map_or(true, |filter| member.to_lowercase().contains(filter))
+                })
+                .cloned()
+                .collect::<Vec<_>>()
+        })
+    });
+    let member_items = create_memo(move |_| {
+        let offset = ((member_page.get().max(1) - 1) * PAGE_SIZE) as usize;
+        filtered_members.with(|members| {
+            members
+                .iter()
+                .skip(offset)
+                .take(PAGE_SIZE as usize)
+                .cloned()
+                .collect::<Vec<_>>()
+        })
+    });
+
+    let domains = create_resource(
+        move || (),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move {
+                HttpRequest::get("/api/domain")
+                    .with_authorization(&auth)
+                    .with_parameter("page", "1")
+                    .with_parameter("limit", MAX_DOMAINS.to_string())
+                    .send::<List<String>>()
+                    .await
+                    .map(|domains| domains.items)
+                    .unwrap_or_default()
+            }
+        },
+    );
+    let available = create_resource(
+        move || {
+            (
+                available_page.get(),
+                available_filter.get(),
+                available_domain.get(),
+            )
+        },
+        move |(page, filter, domain)| {
+            let auth = auth.get_untracked();
+
+            async move {
+                HttpRequest::get("/api/principal")
+                    .with_authorization(&auth)
+                    .with_parameter("page", page.to_string())
+                    .with_parameter("limit", PAGE_SIZE.to_string())
+                    .with_parameter("type", member_type.id())
+                    .with_optional_parameter("filter", filter)
+                    .with_optional_parameter("domain", domain)
+                    .send::<List<String>>()
+                    .await
+            }
+        },
+    );
+    let available_total = create_memo(move |_| {
+        available
+            .get()
+            .and_then(|result| result.ok())
+            .map(|list| list.total as u32)
+    });
+
+    let add_members = move |names: Vec<String>| {
+        data.update(|data| {
+            for name in names {
+                if !data.array_value("members").any(|member| member == name) {
+                    data.array_push("members", name);
+                }
+            }
+        });
+    };
+    let remove_members = move |names: HashSet<String>| {
+        data.update(|data| {
+            let remaining = data
+                .array_value("members")
+                .filter(|member| !names.contains(*member))
+                .map(|member| member.to_string())
+                .collect::<Vec<_>>();
+            data.array_set("members", remaining);
+        });
+        // Stay within bounds after the list shrinks
+        let pages = (filtered_members.with_untracked(|m| m.len()) as u32).div_ceil(PAGE_SIZE);
+        if member_page.get_untracked() > pages.max(1) {
+            member_page.set(pages.max(1));
+        }
+    };
+
+    view! {
+        <div class="grid md:grid-cols-2 gap-4">
+            <div class=PANE_CLASS>
+                <div class="px-4 py-3 border-b border-gray-200 dark:border-gray-700 space-y-2">
+                    <h3 class="text-sm font-semibold text-gray-800 dark:text-gray-200">
+                        {format!("Available {}", member_type.item_name(true))}
+                    </h3>
+                    <div class="grid grid-cols-2 gap-2">
+                        <SearchBox
+                            value=available_filter
+                            on_search=move |value: String| {
+                                let value = value.trim().to_string();
+                                available_filter.set((!value.is_empty()).then_some(value));
+                                available_page.set(1);
+                            }
+                        />
+
+                        <select
+                            class=SELECT_CLASS
+                            on:change=move |ev| {
+                                let domain = event_target_value(&ev);
+                                available_domain.set((!domain.is_empty()).then_some(domain));
+                                available_page.set(1);
+                            }
+                        >
+
+                            <option value="">All domains</option>
+                            {move || {
+                                domains
+                                    .get()
+                                    .unwrap_or_default()
+                                    .into_iter()
+                                    .map(|domain| {
+                                        view! { <option value=domain.clone()>{domain}</option> }
+                                    })
+                                    .collect_view()
+                            }}
+
+                        </select>
+                    </div>
+                </div>
+                <ul class="flex-1 divide-y divide-gray-200 dark:divide-gray-700 min-h-[20rem]">
+                    <Transition>
+                        {move || match available.get() {
+                            None => None,
+                            Some(Err(http::Error::Unauthorized)) => {
+                                use_navigate()("/login", Default::default());
+                                None
+                            }
+                            Some(Err(err)) => {
+                                alert.set(Alert::from(err));
+                                None
+                            }
+                            Some(Ok(list)) if list.items.is_empty() => {
+                                Some(
+                                    view! {
+                                        <li class="px-4 py-3 text-sm text-gray-500">
+                                            No matching principals found.
+                                        </li>
+                                    }
+                                        .into_view(),
+                                )
+                            }
+                            Some(Ok(list)) => {
+                                Some(
+                                    list
+                                        .items
+                                        .into_iter()
+                                        .map(|name| {
+                                            let is_member = {
+                                                let name = name.clone();
+                                                Signal::derive(move || {
+                                                    members.with(|members| members.contains(&name))
+                                                })
+                                            };
+                                            view! {
+                                                <PickerRow
+                                                    name=name
+                                                    selected=available_selected
+                                                    disabled=is_member
+                                                />
+                                            }
+                                        })
+                                        .collect_view(),
+                                )
+                            }
+                        }}
+
+                    </Transition>
+                </ul>
+                <div class="px-4 py-3 flex justify-end border-t border-gray-200 dark:border-gray-700">
+                    <Button
+                        text=Signal::derive(move || {
+                            match available_selected.get().len() {
+                                0 => "Add selected".to_string(),
+                                n => format!("Add selected ({n})"),
+                            }
+                        })
+
+                        color=Color::Blue
+                        on_click=move |_| {
+                            let mut names = available_selected
+                                .try_update(std::mem::take)
+                                .unwrap_or_default()
+                                .into_iter()
+                                .collect::<Vec<_>>();
+                            names.sort();
+                            add_members(names);
+                        }
+
+                        disabled=Signal::derive(move || available_selected.get().is_empty())
+                        capability=Capability::PrincipalUpdate
+                    />
+                </div>
+                <Pagination
+                    current_page=available_page
+                    total_results=available_total
+                    page_size=PAGE_SIZE
+                    on_page_change=move |page: u32| available_page.set(page)
+                />
+            </div>
+
+            <div class=PANE_CLASS>
+                <div class="px-4 py-3 border-b border-gray-200 dark:border-gray-700 space-y-2">
+                    <h3 class="text-sm font-semibold text-gray-800 dark:text-gray-200">
+                        {move || format!("Members ({})", members.with(|m| m.len()))}
+                    </h3>
+                    <SearchBox
+                        value=member_filter
+                        on_search=move |value: String| {
+                            let value = value.trim().to_string();
+                            member_filter.set((!value.is_empty()).then_some(value));
+                            member_page.set(1);
+                        }
+                    />
+
+                </div>
+                <ul class="flex-1 divide-y divide-gray-200 dark:divide-gray-700 min-h-[20rem]">
+                    {move || {
+                        let items = member_items.get();
+                        if items.is_empty() {
+                            return view! {
+                                <li class="px-4 py-3 text-sm text-gray-500">No members.</li>
+                            }
+                                .into_view();
+                        }
+                        items
+                            .into_iter()
+                            .map(|name| {
+                                view! {
+                                    <PickerRow
+                                        name=name
+                                        selected=member_selected
+                                        disabled=Signal::derive(|| false)
+                                    />
+                                }
+                            })
+                            .collect_view()
+                    }}
+
+                </ul>
+                <div class="px-4 py-3 flex justify-between border-t border-gray-200 dark:border-gray-700">
+                    <Button
+                        text="Select page"
+                        color=Color::Gray
+                        on_click=move |_| {
+                            member_selected
+                                .update(|selected| {
+                                    selected.extend(member_items.get_untracked());
+                                })
+                        }
+                    />
+
+                    <Button
+                        text=Signal::derive(move || {
+                            match member_selected.get().len() {
+                                0 => "Remove selected".to_string(),
+                                n => format!("Remove selected ({n})"),
+                            }
+                        })
+
+                        color=Color::Red
+                        on_click=move |_| {
+                            remove_members(
+                                member_selected.try_update(std::mem::take).unwrap_or_default(),
+                            );
+                        }
+
+                        disabled=Signal::derive(move || member_selected.get().is_empty())
+                        capability=Capability::PrincipalUpdate
+                    />
+                </div>
+                <Pagination
+                    current_page=member_page
+                    total_results=Signal::derive(move || {
+                        Some(filtered_members.with(|members| members.len() as u32))
+                    })
+
+                    page_size=PAGE_SIZE
+                    on_page_change=move |page: u32| member_page.set(page)
+                />
+            </div>
+        </div>
+    }
+}
+
+#[component]
+fn PickerRow(
+    name: String,
+    selected: RwSignal<HashSet<String>>,
+    disabled: Signal<bool>,
+) -> impl IntoView {
+    let name = store_value(name);
+
+    view! {
+        <li>
+            <label class="flex items-center gap-x-3 px-4 py-2 text-sm text-gray-800 hover:bg-gray-50 dark:text-gray-200 dark:hover:bg-slate-800">
+                <input
+                    type="checkbox"
+                    class=CHECKBOX_CLASS
+                    disabled=move || disabled.get()
+                    prop:checked=move || {
+                        disabled.get() || selected.with(|s| name.with_value(|n| s.contains(n)))
+                    }
+
+                    on:change=move |ev| {
+                        let checked = event_target_checked(&ev);
+                        selected
+                            .update(|selected| {
+                                if checked {
+                                    selected.insert(name.get_value());
+                                } else {
+                                    name.with_value(|name| selected.remove(name));
+                                }
+                            });
+                    }
+                />
+
+                <span class="font-mono break-all">{name.get_value()}</span>
+                <Show when=move || disabled.get()>
+                    <span class="ms-auto text-xs text-gray-500">Member</span>
+                </Show>
+            </label>
+        </li>
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum DomainStatus {
+    Mx,
+    NoMx,
+    NotFound,
+    Failed(String),
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Deliverability {
+    // External address to the internal member it belongs to
+    internal: AHashMap<String, String>,
+    domains: AHashMap<String, DomainStatus>,
+}
+
+/// Inline warnings for the external members of a list. Syntax errors and
+/// duplicates are reported as addresses are entered, while checking which
+/// addresses belong to internal members and whether external domains accept
+/// mail is done on request.
+#[component]
+pub fn ExternalMemberCheck(data: RwSignal<FormData>) -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let results = create_rw_signal(None::<Deliverability>);
+
+    let addresses = create_memo(move |_| {
+        data.with(|data| {
+            data.array_value("external-members")
+                .map(|addr| addr.trim().to_lowercase())
+                .filter(|addr| !addr.is_empty())
+                .collect::<Vec<_>>()
+        })
+    });
+    let issues = create_memo(move |_| {
+        let members = data.with(|data| {
+            data.array_value("members")
+                .map(|member| member.to_lowercase())
+                .collect::<HashSet<_>>()
+        });
+        let mut seen = HashSet::new();
+        let mut issues = Vec::new();
+
+        results.with(|results| {
+            for addr in addresses.get() {
+                let issue = if let Err(err) = check_address(&addr) {
+                    Some((Color::Red, err.to_string()))
+                } else if !seen.insert(addr.clone()) {
+                    Some((Color::Yellow, "Listed more than once".to_string()))
+                } else if members.contains(&addr) {
+                    Some((Color::Yellow, "Already an internal member".to_string()))
+                } else if let Some(member) = results
+                    .as_ref()
+                    .and_then(|results| results.internal.get(&addr))
+                {
+                    Some((
+                        Color::Yellow,
+                        format!("Address of internal member {member:?}"),
+                    ))
+                } else {
+                    let domain = addr.rsplit_once('@').map(|(_, domain)| domain);
+                    match results
+                        .as_ref()
+                        .and_then(|results| results.domains.get(domain?))
+                    {
+                        Some(DomainStatus::NotFound) => {
+                            Some((Color::Red, "Domain does not exist".to_string()))
+                        }
+                        Some(DomainStatus::NoMx) => {
+                            Some((Color::Yellow, "Domain has no MX records".to_string()))
+                        }
+                        Some(DomainStatus::Failed(err)) => {
+                            Some((Color::Gray, format!("Could not verify domain: {err}")))
+                        }
+                        Some(DomainStatus::Mx) | None => None,
+                    }
+                };
+                if let Some((color, message)) = issue {
+                    issues.push((addr, color, message));
+                }
+            }
+        });
+
+        issues
+    });
+
+    let verify_action = create_action(move |_: &()| {
+        let auth = auth.get();
+        let members = data.with_untracked(|data| {
+            data.array_value("members")
+                .map(|member| member.to_string())
+                .collect::<Vec<_>>()
+        });
+        let addresses = addresses.get_untracked();
+
+        async move {
+            let mut deliverability = Deliverability::default();
+
+            for member in members {
+                match HttpRequest::get(("/api/principal", &member))
+                    .with_authorization(&auth)
+                    .send::<Principal>()
+                    .await
+                {
+                    Ok(principal) => {
+                        for email in principal.emails {
+                            let email = email.to_lowercase();
+                            if addresses.contains(&email) {
+                                deliverability.internal.insert(email, member.clone());
+                            }
+                        }
+                    }
+                    Err(http::Error::NotFound) => {}
+                    Err(http::Error::Unauthorized) => {
+                        use_navigate()("/login", Default::default());
+                        return;
+                    }
+                    Err(err) => {
+                        alert.set(Alert::from(err));
+                        return;
+                    }
+                }
+            }
+
+            for addr in &addresses {
+                let Some((_, domain)) = addr
+                    .rsplit_once('@')
+                    .filter(|_| check_address(addr).is_ok())
+                else {
+                    continue;
+                };
+                if !deliverability.domains.contains_key(domain) {
+                    let status = lookup_mx(domain).await;
+                    deliverability.domains.insert(domain.to_string(), status);
+                }
+            }
+
+            results.set(Some(deliverability));
+        }
+    });
+
+    view! {
+        <FormItem
+            label="Checks"
+            tooltip="Problems found with the external addresses"
+            hide=Signal::derive(move || addresses.with(|addresses| addresses.is_empty()))
+        >
+            {move || {
+                let issues = issues.get();
+                if issues.is_empty() {
+                    return view! {
+                        <p class="text-sm text-gray-500">
+                            {move || {
+                                if results.with(|r| r.is_some()) {
+                                    "No problems found."
+                                } else {
+                                    "No problems found, verify deliverability to also check the domains."
+                                }
+                            }}
+
+                        </p>
+                    }
+                        .into_view();
+                }
+                view! {
+                    <ul class="space-y-1 text-sm">
+                        {issues
+                            .into_iter()
+                            .map(|(addr, color, message)| {
+                                view! {
+                                    <li class="flex items-center gap-x-2">
+                                        <Badge color=color>{message}</Badge>
+                                        <span class="font-mono text-gray-800 dark:text-gray-200 break-all">
+                                            {addr}
+                                        </span>
+                                    </li>
+                                }
+                            })
+                            .collect_view()}
+                    </ul>
+                }
+                    .into_view()
+            }}
+
+            <div class="mt-3">
+                <Button
+                    text=Signal::derive(move || {
+                        if verify_action.pending().get() {
+                            "Verifying...".to_string()
+                        } else {
+                            "Verify deliverability".to_string()
+                        }
+                    })
+
+                    color=Color::Gray
+                    on_click=move |_| verify_action.dispatch(())
+                    disabled=verify_action.pending()
+                />
+            </div>
+        </FormItem>
+    }
+}
+
+/// Checks that an address is syntactically usable as a recipient.
+fn check_address(addr: &str) -> Result<(), &'static str> {
+    let Some((local, domain)) = addr.rsplit_once('@') else {
+        return Err("Missing @");
+    };
+    if local.is_empty() || local.len() > 64 {
+        return Err("Invalid local part");
+    }
+    if local.starts_with('.')
+        || local.ends_with('.')
+        || local.contains("..")
+        || local
+            .chars()
+            .any(|ch| ch.is_whitespace() || ch.is_control() || "@<>()[],;:\\\"".contains(ch))
+    {
+        return Err("Invalid characters in local part");
+    }
+    if domain.len() > 253 || !domain.contains('.') {
+        return Err("Invalid domain");
+    }
+    for label in domain.split('.') {
+        if label.is_empty()
+            || label.len() > 63
+            || label.starts_with('-')
+            || label.ends_with('-')
+            || !label.chars().all(|ch| ch.is_alphanumeric() || ch == '-')
+        {
+            return Err("Invalid domain");
+        }
+    }
+    Ok(())
+}
+
+async fn lookup_mx(domain: &str) -> DomainStatus {
+    match doh_lookup(domain, "MX").await {
+        Ok(response) => match response.status {
+            RCODE_NO_ERROR if response.answer.iter().any(|answer| answer.typ == TYPE_MX) => {
+                DomainStatus::Mx
+            }
+            RCODE_NO_ERROR => DomainStatus::NoMx,
+            RCODE_NX_DOMAIN => DomainStatus::NotFound,
+            code => DomainStatus::Failed(format!("DNS error code {code}")),
+        },
+        Err(err) => DomainStatus::Failed(err),
+    }
+}
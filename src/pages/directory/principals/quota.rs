@@ -0,0 +1,135 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use humansize::{format_size, DECIMAL};
+use leptos::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::form::FormItem,
+    core::{
+        form::FormData,
+        http::{self, HttpRequest},
+        oauth::{use_authorization, AuthToken},
+    },
+    pages::{config::Settings, List},
+};
+
+pub const SELECT_CLASS: &str = "py-2 px-3 pe-9 block w-full border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaTemplate {
+    pub id: String,
+    pub name: String,
+    pub quota: u64,
+}
+
+impl QuotaTemplate {
+    pub fn label(&self) -> String {
+        format!("{} ({})", self.name, format_size(self.quota, DECIMAL))
+    }
+}
+
+/// Fetches the quota templates defined under Settings, sorted by size.
+pub async fn fetch_quota_templates(auth: &AuthToken) -> http::Result<Vec<QuotaTemplate>> {
+    let mut templates = HttpRequest::get("/api/settings/group")
+        .with_authorization(auth)
+        .with_parameter("prefix", "directory.quota-template")
+        .with_parameter("suffix", "quota")
+        .with_parameter("field", "_id")
+        .send::<List<Settings>>()
+        .await?
+        .items
+        .into_iter()
+        .filter_map(|mut template| {
+            let id = template.remove("_id")?;
+            Some(QuotaTemplate {
+                name: template.remove("name").unwrap_or_else(|| id.clone()),
+                quota: template.get("quota")?.parse().ok()?,
+                id,
+            })
+        })
+        .collect::<Vec<_>>();
+    templates.sort_by(|a, b| a.quota.cmp(&b.quota).then_with(|| a.name.cmp(&b.name)));
+
+    Ok(templates)
+}
+
+/// Lets the disk quota of an account be picked from the configured
+/// templates, hidden when no templates exist.
+#[component]
+pub fn QuotaTemplateSelect(data: RwSignal<FormData>) -> impl IntoView {
+    let auth = use_authorization();
+    let templates = create_resource(
+        || (),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move { fetch_quota_templates(&auth).await.unwrap_or_default() }
+        },
+    );
+    let quota = create_memo(move |_| data.with(|data| data.value::<u64>("quota")));
+
+    view! {
+        <Transition>
+            {move || {
+                templates
+                    .get()
+                    .filter(|templates| !templates.is_empty())
+                    .map(|templates| {
+                        let templates = store_value(templates);
+                        view! {
+                            <FormItem
+                                label="Quota template"
+                                tooltip="Sets the disk quota from one of the templates defined in Settings"
+                            >
+                                <select
+                                    class=SELECT_CLASS
+                                    on:change=move |ev| {
+                                        let id = event_target_value(&ev);
+                                        if let Some(template) = templates
+                                            .with_value(|t| t.iter().find(|t| t.id == id).cloned())
+                                        {
+                                            data.update(|data| {
+                                                data.update("quota", template.quota.to_string())
+                                            });
+                                        }
+                                    }
+                                >
+
+                                    <option value="" selected=move || {
+                                        templates
+                                            .with_value(|t| {
+                                                !t.iter().any(|t| Some(t.quota) == quota.get())
+                                            })
+                                    }>"Custom"</option>
+                                    {templates
+                                        .get_value()
+                                        .into_iter()
+                                        .map(|template| {
+                                            let template_quota = template.quota;
+                                            view! {
+                                                <option
+                                                    value=template.id.clone()
+                                                    selected=move || {
+                                                        quota.get() == Some(template_quota)
+                                                    }
+                                                >
+
+                                                    {template.label()}
+                                                </option>
+                                            }
+                                        })
+                                        .collect_view()}
+                                </select>
+                            </FormItem>
+                        }
+                    })
+            }}
+
+        </Transition>
+    }
+}
@@ -0,0 +1,238 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use ahash::AHashMap;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::{
+        http::{self, HttpRequest},
+        oauth::AuthToken,
+    },
+    pages::{
+        config::{edit::FetchSettings, UpdateSettings},
+        FormatDateTime,
+    },
+};
+
+use super::{Principal, PrincipalUpdate};
+
+const TRASH_PREFIX: &str = "directory.trash";
+pub const GRACE_PERIOD_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrashKind {
+    Account,
+    Domain,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrashEntry {
+    pub remove_at: DateTime<Utc>,
+    pub was_disabled: bool,
+}
+
+pub type Trash = AHashMap<String, TrashEntry>;
+
+impl TrashKind {
+    fn prefix(&self) -> String {
+        match self {
+            TrashKind::Account => format!("{TRASH_PREFIX}.account"),
+            TrashKind::Domain => format!("{TRASH_PREFIX}.domain"),
+        }
+    }
+
+    fn url(&self) -> &'static str {
+        match self {
+            TrashKind::Account => "/api/principal",
+            TrashKind::Domain => "/api/domain",
+        }
+    }
+
+    fn key(&self, name: &str) -> String {
+        format!("{}.{name}", self.prefix())
+    }
+}
+
+impl TrashEntry {
+    pub fn is_expired(&self) -> bool {
+        self.remove_at <= Utc::now()
+    }
+
+    /// Describes when the item is removed, expired items wait for an
+    /// administrator to purge them.
+    pub fn removal_text(&self) -> String {
+        if self.is_expired() {
+            "Removal pending".to_string()
+        } else {
+            format!("Removal on {}", self.remove_at.format_date())
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        let (date, flags) = value.split_once(';').unwrap_or((value, ""));
+        DateTime::parse_from_rfc3339(date.trim())
+            .ok()
+            .map(|date| TrashEntry {
+                remove_at: date.to_utc(),
+                was_disabled: flags.trim() == "disabled",
+            })
+    }
+
+    fn serialize(&self) -> String {
+        if self.was_disabled {
+            format!("{};disabled", self.remove_at.to_rfc3339())
+        } else {
+            self.remove_at.to_rfc3339()
+        }
+    }
+}
+
+/// Fetches the items pending permanent removal. Entries that cannot be
+/// parsed are skipped, expired ones are only removed by [`delete_permanently`].
+pub async fn fetch_trash(auth: &AuthToken, kind: TrashKind) -> http::Result<Trash> {
+    HttpRequest::get("/api/settings/list")
+        .with_authorization(auth)
+        .with_parameter("prefix", kind.prefix())
+        .send::<FetchSettings>()
+        .await
+        .map(|list| {
+            list.items
+                .into_iter()
+                .filter_map(|(name, value)| match TrashEntry::parse(&value) {
+                    Some(entry) => Some((name, entry)),
+                    None => {
+                        log::warn!("Ignoring invalid trash entry for {name}: {value:?}");
+                        None
+                    }
+                })
+                .collect()
+        })
+}
+
+/// Returns the names whose grace period has expired.
+pub fn expired_names(trash: &Trash) -> Vec<String> {
+    let mut names = trash
+        .iter()
+        .filter(|(_, entry)| entry.is_expired())
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<_>>();
+    names.sort_unstable();
+    names
+}
+
+/// Disables the given accounts and schedules their removal once the grace
+/// period expires. Domains cannot be disabled, they are only scheduled.
+pub async fn move_to_trash(
+    auth: &AuthToken,
+    kind: TrashKind,
+    names: &[String],
+) -> http::Result<DateTime<Utc>> {
+    let remove_at = Utc::now() + Duration::days(GRACE_PERIOD_DAYS);
+    let mut values = Vec::with_capacity(names.len());
+
+    for name in names {
+        let mut was_disabled = false;
+        if kind == TrashKind::Account {
+            was_disabled = HttpRequest::get(("/api/principal", name))
+                .with_authorization(auth)
+                .send::<Principal>()
+                .await?
+                .is_disabled();
+            if !was_disabled {
+                HttpRequest::patch(("/api/principal", name))
+                    .with_authorization(auth)
+                    .with_body(vec![PrincipalUpdate::set_disabled(true)])
+                    .unwrap()
+                    .send::<()>()
+                    .await?;
+            }
+        }
+        values.push((
+            kind.key(name),
+            TrashEntry {
+                remove_at,
+                was_disabled,
+            }
+            .serialize(),
+        ));
+    }
+
+    update_trash(
+        auth,
+        UpdateSettings::Insert {
+            prefix: None,
+            values,
+            assert_empty: false,
+        },
+    )
+    .await?;
+
+    Ok(remove_at)
+}
+
+/// Cancels the scheduled removal, re-enabling accounts that were not
+/// disabled before being moved to the trash.
+pub async fn restore(
+    auth: &AuthToken,
+    kind: TrashKind,
+    name: &str,
+    entry: &TrashEntry,
+) -> http::Result<()> {
+    if kind == TrashKind::Account && !entry.was_disabled {
+        HttpRequest::patch(("/api/principal", name))
+            .with_authorization(auth)
+            .with_body(vec![PrincipalUpdate::set_disabled(false)])
+            .unwrap()
+            .send::<()>()
+            .await?;
+    }
+
+    update_trash(
+        auth,
+        UpdateSettings::Delete {
+            keys: vec![kind.key(name)],
+        },
+    )
+    .await
+}
+
+/// Removes the given items from the directory along with their trash entries.
+pub async fn delete_permanently(
+    auth: &AuthToken,
+    kind: TrashKind,
+    names: &[String],
+) -> http::Result<()> {
+    for name in names {
+        match HttpRequest::delete((kind.url(), name))
+            .with_authorization(auth)
+            .send::<()>()
+            .await
+        {
+            Ok(_) | Err(http::Error::NotFound) => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    update_trash(
+        auth,
+        UpdateSettings::Delete {
+            keys: names.iter().map(|name| kind.key(name)).collect(),
+        },
+    )
+    .await
+}
+
+async fn update_trash(auth: &AuthToken, update: UpdateSettings) -> http::Result<()> {
+    HttpRequest::post("/api/settings")
+        .with_authorization(auth)
+        .with_body(vec![update])
+        .unwrap()
+        .send::<Option<String>>()
+        .await
+        .map(|_| ())
+}
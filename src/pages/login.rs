@@ -59,6 +59,8 @@ pub fn Login() -> impl IntoView {
                             auth_token.username = username.into();
                             auth_token.is_valid = true;
                             auth_token.is_admin = response.is_admin;
+                            auth_token.permissions = response.permissions;
+                            auth_token.is_locked = false;
 
                             if let Err(err) =
                                 SessionStorage::set(STATE_STORAGE_KEY, auth_token.clone())
@@ -87,7 +89,7 @@ pub fn Login() -> impl IntoView {
                         let url = if response.is_admin {
                             "/manage/directory/accounts"
                         } else {
-                            "/account/crypto"
+                            "/account/overview"
                         };
                         use_navigate()(url, Default::default());
                     }
@@ -0,0 +1,426 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use gloo_net::http::Method;
+use leptos::*;
+use leptos_router::use_navigate;
+use web_time::Instant;
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::{button::Button, Form, FormButtonBar, FormItem, FormSection},
+        icon::IconPaperAirplane,
+        messages::alert::{use_alerts, Alert},
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+    },
+};
+
+const INPUT_CLASS: &str = "py-2 px-3 block w-full border-gray-200 shadow-sm text-sm rounded-lg focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600";
+
+const METHODS: &[&str] = &["GET", "POST", "PUT", "PATCH", "DELETE"];
+
+/// Management API endpoints used by the webadmin, as method, path,
+/// description and an example request body.
+const ENDPOINTS: &[(&str, &str, &str, &str)] = &[
+    (
+        "GET",
+        "/api/principal?page=1&limit=10",
+        "List principal names",
+        "",
+    ),
+    ("GET", "/api/principal/{name}", "Fetch a principal", ""),
+    (
+        "POST",
+        "/api/principal",
+        "Create a principal",
+        r#"{"type": "individual", "name": "john", "emails": ["john@example.org"]}"#,
+    ),
+    (
+        "PATCH",
+        "/api/principal/{name}",
+        "Update a principal",
+        r#"[{"action": "set", "field": "quota", "value": 5368709120}]"#,
+    ),
+    ("DELETE", "/api/principal/{name}", "Delete a principal", ""),
+    ("GET", "/api/domain?page=1&limit=10", "List domains", ""),
+    ("GET", "/api/domain/{name}", "DNS records of a domain", ""),
+    ("POST", "/api/domain/{name}", "Create a domain", ""),
+    ("DELETE", "/api/domain/{name}", "Delete a domain", ""),
+    (
+        "POST",
+        "/api/dkim",
+        "Create a DKIM signature",
+        r#"{"algorithm": "Ed25519", "domain": "example.org"}"#,
+    ),
+    (
+        "GET",
+        "/api/settings/list?prefix=server.listener",
+        "List settings under a prefix",
+        "",
+    ),
+    (
+        "GET",
+        "/api/settings/group?prefix=store&suffix=type&field=_id",
+        "List settings grouped by identifier",
+        "",
+    ),
+    (
+        "GET",
+        "/api/settings/keys?keys=server.hostname",
+        "Fetch settings by key",
+        "",
+    ),
+    (
+        "POST",
+        "/api/settings",
+        "Update settings",
+        r#"[{"type": "Insert", "prefix": null, "values": [["server.hostname", "mx.example.org"]], "assert_empty": false}]"#,
+    ),
+    ("GET", "/api/reload/", "Reload the configuration", ""),
+    (
+        "GET",
+        "/api/queue/messages?page=1&limit=10",
+        "List queued messages",
+        "",
+    ),
+    (
+        "GET",
+        "/api/queue/messages/{id}",
+        "Fetch a queued message",
+        "",
+    ),
+    (
+        "PATCH",
+        "/api/queue/messages/{id}",
+        "Retry delivery of a queued message",
+        "",
+    ),
+    (
+        "DELETE",
+        "/api/queue/messages/{id}",
+        "Cancel delivery of a queued message",
+        "",
+    ),
+    (
+        "GET",
+        "/api/queue/reports?page=1&limit=10",
+        "List queued reports",
+        "",
+    ),
+    (
+        "GET",
+        "/api/reports/dmarc?page=1&limit=10",
+        "List DMARC reports",
+        "",
+    ),
+    (
+        "GET",
+        "/api/reports/tls?page=1&limit=10",
+        "List TLS reports",
+        "",
+    ),
+    (
+        "GET",
+        "/api/reports/arf?page=1&limit=10",
+        "List failure reports",
+        "",
+    ),
+    ("GET", "/api/logs?page=1&limit=50", "Fetch server logs", ""),
+    (
+        "GET",
+        "/api/telemetry/traces?page=1&limit=10",
+        "Search message traces",
+        "",
+    ),
+    (
+        "GET",
+        "/api/telemetry/metrics?after=2024-01-01T00:00:00Z",
+        "Fetch metrics history",
+        "",
+    ),
+    (
+        "GET",
+        "/api/telemetry/live/token",
+        "Obtain a live telemetry token",
+        "",
+    ),
+    (
+        "GET",
+        "/api/store/lookup?prefix=",
+        "Browse the in-memory store",
+        "",
+    ),
+    ("GET", "/api/cluster/nodes", "List cluster nodes", ""),
+    (
+        "GET",
+        "/api/troubleshoot/dns?domain=example.org",
+        "Validate DNS records",
+        "",
+    ),
+    (
+        "POST",
+        "/api/sieve/spam-filter?remote_ip=127.0.0.1",
+        "Classify a message",
+        "Subject: test\r\n\r\nHello",
+    ),
+    ("GET", "/api/restart", "Restart the server", ""),
+    ("GET", "/api/update/webadmin", "Update the web admin", ""),
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ApiResponse {
+    outcome: Result<String, String>,
+    elapsed: u128,
+}
+
+#[component]
+pub fn ApiExplorer() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let method = create_rw_signal("GET".to_string());
+    let path = create_rw_signal(String::new());
+    let body = create_rw_signal(String::new());
+    let filter = create_rw_signal(String::new());
+    let response = create_rw_signal(None::<ApiResponse>);
+
+    let send_request = create_action(move |(method, path, body): &(String, String, String)| {
+        let auth = auth.get();
+        let method = parse_method(method);
+        let path = path.clone();
+        let body = body.clone();
+
+        async move {
+            let mut request = HttpRequest::new(method, path).with_authorization(&auth);
+            if !body.trim().is_empty() {
+                if serde_json::from_str::<serde_json::Value>(&body).is_ok() {
+                    request = request.with_header("Content-Type", "application/json");
+                }
+                request = request.with_raw_body(body);
+            }
+            let start = Instant::now();
+            let outcome = match request.send_raw().await {
+                Ok(bytes) => Ok(pretty_print(&bytes)),
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                    return;
+                }
+                Err(err) => Err(Alert::from(err).message),
+            };
+            response.set(Some(ApiResponse {
+                outcome,
+                elapsed: start.elapsed().as_millis(),
+            }));
+        }
+    });
+
+    let endpoints = create_memo(move |_| {
+        let filter = filter.get().trim().to_lowercase();
+        ENDPOINTS
+            .iter()
+            .filter(|(method, path, description, _)| {
+                filter.is_empty()
+                    || method.to_lowercase().contains(&filter)
+                    || path.to_lowercase().contains(&filter)
+                    || description.to_lowercase().contains(&filter)
+            })
+            .copied()
+            .collect::<Vec<_>>()
+    });
+
+    view! {
+        <Form
+            title="API Explorer"
+            subtitle="Send requests to the management API using the current session"
+        >
+            <FormSection title="Request".to_string()>
+                <FormItem label="Method">
+                    <select
+                        class=INPUT_CLASS
+                        on:change=move |ev| method.set(event_target_value(&ev))
+                    >
+                        {METHODS
+                            .iter()
+                            .map(|name| {
+                                view! {
+                                    <option value=*name selected=move || method.get() == *name>
+                                        {*name}
+                                    </option>
+                                }
+                            })
+                            .collect_view()}
+                    </select>
+                </FormItem>
+                <FormItem
+                    label="Path"
+                    tooltip="Path and query string of the endpoint, replace the placeholders in braces before sending"
+                >
+                    <input
+                        type="text"
+                        class=INPUT_CLASS
+                        placeholder="/api/principal?page=1&limit=10"
+                        prop:value=move || path.get()
+                        on:input=move |ev| path.set(event_target_value(&ev))
+                    />
+                </FormItem>
+                <FormItem label="Body" tooltip="Request body, usually JSON, leave empty for none">
+                    <textarea
+                        class=format!("{INPUT_CLASS} font-mono")
+                        rows=6
+                        prop:value=move || body.get()
+                        on:input=move |ev| body.set(event_target_value(&ev))
+                    ></textarea>
+                    {move || {
+                        let body = body.get();
+                        (!body.trim().is_empty())
+                            .then(|| serde_json::from_str::<serde_json::Value>(&body).err())
+                            .flatten()
+                            .map(|err| {
+                                view! {
+                                    <p class="text-xs text-gray-500 mt-2">
+                                        {format!("Sent as plain text, not valid JSON: {err}")}
+                                    </p>
+                                }
+                            })
+                    }}
+
+                </FormItem>
+            </FormSection>
+
+            <FormButtonBar>
+                <Button
+                    text=Signal::derive(move || {
+                        if send_request.pending().get() { "Sending..." } else { "Send" }
+                            .to_string()
+                    })
+
+                    color=Color::Blue
+                    on_click=Callback::new(move |_| {
+                        let request_path = path.get().trim().to_string();
+                        if !request_path.starts_with("/api/") {
+                            alert.set(Alert::warning("The path must start with /api/."));
+                        } else if request_path.contains('{') {
+                            alert
+                                .set(
+                                    Alert::warning(
+                                        "Replace the placeholders in the path before sending.",
+                                    ),
+                                );
+                        } else {
+                            send_request.dispatch((method.get(), request_path, body.get()));
+                        }
+                    })
+
+                    disabled=send_request.pending()
+                >
+                    <IconPaperAirplane/>
+                </Button>
+            </FormButtonBar>
+
+            {move || {
+                response
+                    .get()
+                    .map(|response| {
+                        let (color, label, text) = match response.outcome {
+                            Ok(text) => (Color::Green, "Success", text),
+                            Err(text) => (Color::Red, "Failed", text),
+                        };
+                        view! {
+                            <FormSection title="Response".to_string()>
+                                <FormItem label="Status">
+                                    <div class="pt-2.5 flex items-center gap-x-2 text-sm text-gray-600 dark:text-gray-400">
+                                        <Badge color=color>{label}</Badge>
+                                        <span>{format!("{} ms", response.elapsed)}</span>
+                                    </div>
+                                </FormItem>
+                                <FormItem label="Body">
+                                    <pre class="p-3 max-h-96 overflow-auto text-xs whitespace-pre-wrap break-all bg-gray-50 border border-gray-200 rounded-lg text-gray-800 dark:bg-slate-800 dark:border-gray-700 dark:text-gray-200">
+                                        {text}
+                                    </pre>
+                                </FormItem>
+                            </FormSection>
+                        }
+                    })
+            }}
+
+            <FormSection title="Endpoints".to_string()>
+                <FormItem label="Search">
+                    <input
+                        type="text"
+                        class=INPUT_CLASS
+                        placeholder="principal"
+                        prop:value=move || filter.get()
+                        on:input=move |ev| filter.set(event_target_value(&ev))
+                    />
+                </FormItem>
+                <div class="sm:col-span-12 divide-y divide-gray-200 dark:divide-gray-700">
+                    {move || {
+                        endpoints
+                            .get()
+                            .into_iter()
+                            .map(|(endpoint_method, endpoint_path, description, example)| {
+                                view! {
+                                    <button
+                                        type="button"
+                                        class="w-full py-2 flex items-center gap-x-3 text-start text-sm hover:bg-gray-50 dark:hover:bg-gray-800"
+                                        on:click=move |_| {
+                                            method.set(endpoint_method.to_string());
+                                            path.set(endpoint_path.to_string());
+                                            body.set(example.to_string());
+                                            response.set(None);
+                                        }
+                                    >
+
+                                        <span class="w-16 shrink-0">
+                                            <Badge color=method_color(endpoint_method)>
+                                                {endpoint_method}
+                                            </Badge>
+                                        </span>
+                                        <span class="font-mono text-gray-800 dark:text-gray-200 break-all">
+                                            {endpoint_path}
+                                        </span>
+                                        <span class="ms-auto text-gray-500 text-end">{description}</span>
+                                    </button>
+                                }
+                            })
+                            .collect_view()
+                    }}
+
+                </div>
+            </FormSection>
+        </Form>
+    }
+}
+
+fn parse_method(method: &str) -> Method {
+    match method {
+        "POST" => Method::POST,
+        "PUT" => Method::PUT,
+        "PATCH" => Method::PATCH,
+        "DELETE" => Method::DELETE,
+        _ => Method::GET,
+    }
+}
+
+fn method_color(method: &str) -> Color {
+    match method {
+        "GET" => Color::Blue,
+        "POST" => Color::Green,
+        "DELETE" => Color::Red,
+        _ => Color::Yellow,
+    }
+}
+
+fn pretty_print(bytes: &[u8]) -> String {
+    serde_json::from_slice::<serde_json::Value>(bytes)
+        .ok()
+        .and_then(|value| serde_json::to_string_pretty(&value).ok())
+        .unwrap_or_else(|| String::from_utf8_lossy(bytes).into_owned())
+}
@@ -0,0 +1,476 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use leptos::*;
+use leptos_router::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        badge::Badge,
+        icon::{IconDocumentText, IconRefresh},
+        list::{
+            header::ColumnList, pagination::Pagination, toolbar::ToolbarButton, Footer, ListItem,
+            ListSection, ListTable, ListTextItem, Toolbar, ZeroResults,
+        },
+        messages::alert::{use_alerts, Alert},
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        file::download_file,
+        http::{self, HttpRequest},
+        oauth::{use_authorization, AuthToken},
+        url::UrlBuilder,
+    },
+    pages::{maybe_plural, queue::messages::deserialize_datetime, FormatDateTime, List},
+};
+
+const PAGE_SIZE: u32 = 50;
+const EXPORT_PAGE_SIZE: u32 = 1000;
+const EXPORT_MAX_EVENTS: u64 = 50_000;
+
+const ACTION_TYPES: &[(&str, &str)] = &[
+    ("", "All actions"),
+    ("principal", "Principals"),
+    ("settings", "Settings"),
+    ("auth", "Logins"),
+    ("permission", "Permissions"),
+];
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct AuditEvent {
+    id: String,
+    #[serde(rename = "type")]
+    typ: String,
+    #[serde(deserialize_with = "deserialize_datetime")]
+    created_at: DateTime<Utc>,
+    #[serde(default)]
+    actor: Option<String>,
+    #[serde(default)]
+    target: Option<String>,
+    #[serde(default)]
+    remote_ip: Option<String>,
+    #[serde(default)]
+    details: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct AuditFilter {
+    actor: Option<String>,
+    action: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+}
+
+#[component]
+pub fn AuditLog() -> impl IntoView {
+    let query = use_query_map();
+    let page = create_memo(move |_| {
+        query
+            .with(|q| q.get("page").and_then(|page| page.parse::<u32>().ok()))
+            .filter(|&page| page > 0)
+            .unwrap_or(1)
+    });
+    let filter = create_memo(move |_| {
+        query.with(|q| {
+            let param = |name: &str| {
+                q.get(name)
+                    .map(|value| value.trim())
+                    .filter(|value| !value.is_empty())
+                    .map(|value| value.to_string())
+            };
+            AuditFilter {
+                actor: param("actor"),
+                action: param("action"),
+                from: param("from"),
+                to: param("to"),
+            }
+        })
+    });
+
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let events = create_resource(
+        move || (page.get(), filter.get()),
+        move |(page, filter)| {
+            let auth = auth.get_untracked();
+
+            async move { fetch_events(&auth, &filter, page, PAGE_SIZE).await }
+        },
+    );
+
+    let export_action = create_action(move |_: &()| {
+        let auth = auth.get();
+        let filter = filter.get();
+
+        async move {
+            match export_events(&auth, &filter).await {
+                Ok((csv, count)) => {
+                    let file_name = format!("audit-log-{}.csv", Utc::now().format("%Y%m%d-%H%M%S"));
+                    match download_file(&file_name, "text/csv", &csv) {
+                        Ok(_) => {
+                            alert.set(Alert::success(format!(
+                                "Exported {}",
+                                maybe_plural(count, "event", "events")
+                            )));
+                        }
+                        Err(err) => {
+                            alert.set(Alert::error("Export failed").with_details(err));
+                        }
+                    }
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    let total_results = create_rw_signal(None::<u32>);
+    let navigate_with = move |update: &dyn Fn(&mut AuditFilter)| {
+        let mut filter = filter.get();
+        update(&mut filter);
+        for value in [
+            &mut filter.actor,
+            &mut filter.action,
+            &mut filter.from,
+            &mut filter.to,
+        ] {
+            *value = value.take().filter(|value| !value.trim().is_empty());
+        }
+        use_navigate()(&filter_url(&filter, None), Default::default());
+    };
+    let input_class = "py-2 px-3 block border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600";
+
+    view! {
+        <ListSection>
+            <ListTable
+                title="Audit log"
+                subtitle="Administrative actions such as principal changes, settings updates and logins"
+            >
+                <Toolbar slot>
+                    <input
+                        type="text"
+                        class=input_class
+                        placeholder="Actor"
+                        prop:value=move || filter.get().actor.unwrap_or_default()
+                        on:change=move |ev| {
+                            let value = event_target_value(&ev);
+                            navigate_with(&|filter| filter.actor = Some(value.clone()));
+                        }
+                    />
+
+                    <select
+                        class=format!("{input_class} pe-9")
+                        on:change=move |ev| {
+                            let value = event_target_value(&ev);
+                            navigate_with(&|filter| filter.action = Some(value.clone()));
+                        }
+                    >
+
+                        {ACTION_TYPES
+                            .iter()
+                            .map(|(value, label)| {
+                                view! {
+                                    <option
+                                        value=*value
+                                        selected=move || {
+                                            filter.get().action.as_deref().unwrap_or_default() == *value
+                                        }
+                                    >
+
+                                        {*label}
+                                    </option>
+                                }
+                            })
+                            .collect_view()}
+
+                    </select>
+                    <input
+                        type="date"
+                        class=input_class
+                        title="From"
+                        prop:value=move || filter.get().from.unwrap_or_default()
+                        on:change=move |ev| {
+                            let value = event_target_value(&ev);
+                            navigate_with(&|filter| filter.from = Some(value.clone()));
+                        }
+                    />
+
+                    <input
+                        type="date"
+                        class=input_class
+                        title="To"
+                        prop:value=move || filter.get().to.unwrap_or_default()
+                        on:change=move |ev| {
+                            let value = event_target_value(&ev);
+                            navigate_with(&|filter| filter.to = Some(value.clone()));
+                        }
+                    />
+
+                    <ToolbarButton
+                        text="Refresh"
+                        color=Color::Gray
+                        on_click=move |_| {
+                            events.refetch();
+                        }
+                    >
+
+                        <IconRefresh/>
+                    </ToolbarButton>
+                    <ToolbarButton
+                        text=Signal::derive(move || {
+                            if export_action.pending().get() {
+                                "Exporting...".to_string()
+                            } else {
+                                "Export CSV".to_string()
+                            }
+                        })
+
+                        color=Color::Blue
+                        on_click=move |_| {
+                            if !export_action.pending().get_untracked() {
+                                export_action.dispatch(());
+                            }
+                        }
+                    >
+
+                        <IconDocumentText/>
+                    </ToolbarButton>
+
+                </Toolbar>
+
+                <Transition fallback=Skeleton>
+                    {move || match events.get() {
+                        None => None,
+                        Some(Err(http::Error::Unauthorized)) => {
+                            use_navigate()("/login", Default::default());
+                            Some(view! { <div></div> }.into_view())
+                        }
+                        Some(Err(http::Error::NotFound)) => {
+                            total_results.set(Some(0));
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="Audit log not available"
+                                        subtitle="Enable the tracing history store to record administrative events."
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Err(err)) => {
+                            total_results.set(Some(0));
+                            alert.set(Alert::from(err));
+                            Some(view! { <Skeleton/> }.into_view())
+                        }
+                        Some(Ok(events)) if !events.items.is_empty() => {
+                            total_results.set(Some(events.total as u32));
+                            Some(
+                                view! {
+                                    <ColumnList headers=vec![
+                                        "Date".to_string(),
+                                        "Actor".to_string(),
+                                        "Action".to_string(),
+                                        "Target".to_string(),
+                                        "Details".to_string(),
+                                        "Remote IP".to_string(),
+                                    ]>
+
+                                        <For
+                                            each=move || events.items.clone()
+                                            key=|event| event.id.clone()
+                                            let:event
+                                        >
+                                            <AuditEventItem event/>
+                                        </For>
+
+                                    </ColumnList>
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Ok(_)) => {
+                            total_results.set(Some(0));
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="No results"
+                                        subtitle="No audit events were found with the selected criteria."
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                    }}
+
+                </Transition>
+
+                <Footer slot>
+
+                    <Pagination
+                        current_page=page
+                        total_results=total_results.read_only()
+                        page_size=PAGE_SIZE
+                        on_page_change=move |page: u32| {
+                            use_navigate()(&filter_url(&filter.get(), Some(page)), Default::default());
+                        }
+                    />
+
+                </Footer>
+            </ListTable>
+        </ListSection>
+    }
+}
+
+#[component]
+fn AuditEventItem(event: AuditEvent) -> impl IntoView {
+    let color = if event.typ.contains("fail")
+        || event.typ.contains("denied")
+        || event.typ.ends_with("deleted")
+    {
+        Color::Red
+    } else if event.typ.ends_with("created") || event.typ.ends_with("success") {
+        Color::Green
+    } else {
+        Color::Gray
+    };
+    let details = event.details_text();
+
+    view! {
+        <tr>
+            <ListItem>
+                <span class="text-sm text-gray-500">{event.created_at.format_date_time()}</span>
+            </ListItem>
+            <ListItem>
+                <span class="text-sm font-semibold text-gray-800 dark:text-gray-200">
+                    {event.actor.unwrap_or_else(|| "-".to_string())}
+                </span>
+            </ListItem>
+            <ListItem>
+                <Badge color=color>{event.typ}</Badge>
+            </ListItem>
+            <ListTextItem>{event.target.unwrap_or_default()}</ListTextItem>
+            <ListItem>
+                <span class="text-xs text-gray-500 text-wrap">{details}</span>
+            </ListItem>
+            <ListTextItem>{event.remote_ip.unwrap_or_default()}</ListTextItem>
+        </tr>
+    }
+}
+
+async fn fetch_events(
+    auth: &AuthToken,
+    filter: &AuditFilter,
+    page: u32,
+    limit: u32,
+) -> http::Result<List<AuditEvent>> {
+    HttpRequest::get("/api/telemetry/audit")
+        .with_authorization(auth)
+        .with_parameter("page", page.to_string())
+        .with_parameter("limit", limit.to_string())
+        .with_optional_parameter("actor", filter.actor.clone())
+        .with_optional_parameter("type", filter.action.clone())
+        .with_optional_parameter(
+            "after",
+            filter
+                .from
+                .as_deref()
+                .and_then(|date| date_bound(date, false)),
+        )
+        .with_optional_parameter(
+            "before",
+            filter.to.as_deref().and_then(|date| date_bound(date, true)),
+        )
+        .send::<List<AuditEvent>>()
+        .await
+}
+
+/// Fetches every event matching the filter and renders them as CSV.
+async fn export_events(auth: &AuthToken, filter: &AuditFilter) -> http::Result<(String, usize)> {
+    let mut csv = String::from("date,actor,action,target,remote_ip,details\r\n");
+    let mut count = 0;
+    let mut page = 1;
+
+    loop {
+        let events = fetch_events(auth, filter, page, EXPORT_PAGE_SIZE).await?;
+        let is_last = events.items.len() < EXPORT_PAGE_SIZE as usize;
+        for event in events.items {
+            let row = [
+                event.created_at.to_rfc3339(),
+                event.actor.clone().unwrap_or_default(),
+                event.typ.clone(),
+                event.target.clone().unwrap_or_default(),
+                event.remote_ip.clone().unwrap_or_default(),
+                event.details_text(),
+            ];
+            csv.push_str(
+                &row.iter()
+                    .map(|field| csv_escape(field))
+                    .collect::<Vec<_>>()
+                    .join(","),
+            );
+            csv.push_str("\r\n");
+            count += 1;
+        }
+        if is_last || count as u64 >= events.total.min(EXPORT_MAX_EVENTS) {
+            break;
+        }
+        page += 1;
+    }
+
+    Ok((csv, count))
+}
+
+impl AuditEvent {
+    fn details_text(&self) -> String {
+        self.details
+            .iter()
+            .map(|(key, value)| match value {
+                serde_json::Value::String(value) => format!("{key}: {value}"),
+                value => format!("{key}: {value}"),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+fn filter_url(filter: &AuditFilter, page: Option<u32>) -> String {
+    UrlBuilder::new("/manage/audit")
+        .with_optional_parameter("page", page.map(|page| page.to_string()))
+        .with_optional_parameter("actor", filter.actor.clone())
+        .with_optional_parameter("action", filter.action.clone())
+        .with_optional_parameter("from", filter.from.clone())
+        .with_optional_parameter("to", filter.to.clone())
+        .finish()
+}
+
+/// Converts a date picker value into an RFC3339 timestamp, using the end of
+/// the day for upper bounds so that the selected range is inclusive.
+fn date_bound(date: &str, end_of_day: bool) -> Option<String> {
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    let date = if end_of_day {
+        date.checked_add_days(Days::new(1))?
+    } else {
+        date
+    };
+    Some(date.and_hms_opt(0, 0, 0)?.and_utc().to_rfc3339())
+}
+
+pub(crate) fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
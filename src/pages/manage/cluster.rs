@@ -0,0 +1,274 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::Duration;
+
+use ahash::AHashMap;
+use chrono::{DateTime, Utc};
+use chrono_humanize::HumanTime;
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        badge::Badge,
+        icon::IconRefresh,
+        list::{
+            header::ColumnList, toolbar::ToolbarButton, Footer, ListItem, ListSection, ListTable,
+            ListTextItem, Toolbar, ZeroResults,
+        },
+        messages::alert::{use_alerts, Alert},
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+    },
+    pages::{maybe_plural, FormatDateTime},
+};
+
+/// Nodes that have not sent a heartbeat for this long are considered unreachable.
+const HEARTBEAT_TIMEOUT_SECS: i64 = 60;
+const REFRESH_INTERVAL: Duration = Duration::from_secs(15);
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct ClusterNode {
+    id: u64,
+    hostname: String,
+    #[serde(default)]
+    roles: Vec<String>,
+    version: String,
+    #[serde(default)]
+    last_heartbeat: Option<DateTime<Utc>>,
+    #[serde(default)]
+    queues: Vec<String>,
+    #[serde(default)]
+    is_local: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum NodeHealth {
+    Online,
+    VersionSkew(String),
+    Unreachable,
+}
+
+#[component]
+pub fn ClusterStatus() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+
+    let nodes = create_resource(
+        move || (),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move {
+                HttpRequest::get("/api/cluster/nodes")
+                    .with_authorization(&auth)
+                    .send::<Vec<ClusterNode>>()
+                    .await
+            }
+        },
+    );
+
+    if let Ok(handle) = set_interval_with_handle(move || nodes.refetch(), REFRESH_INTERVAL) {
+        on_cleanup(move || handle.clear());
+    }
+
+    let summary = create_rw_signal(String::new());
+
+    view! {
+        <ListSection>
+            <ListTable title="Cluster" subtitle="Runtime status of the nodes in this cluster">
+                <Toolbar slot>
+                    <ToolbarButton
+                        text="Refresh"
+                        color=Color::Gray
+                        on_click=move |_| {
+                            nodes.refetch();
+                        }
+                    >
+
+                        <IconRefresh attr:class="flex-shrink-0 size-4"/>
+                    </ToolbarButton>
+                </Toolbar>
+
+                <Transition fallback=Skeleton>
+                    {move || match nodes.get() {
+                        None => None,
+                        Some(Err(http::Error::Unauthorized)) => {
+                            use_navigate()("/login", Default::default());
+                            Some(view! { <div></div> }.into_view())
+                        }
+                        Some(Err(http::Error::NotFound)) => {
+                            summary.set(String::new());
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="Clustering not enabled"
+                                        subtitle="This server is running as a standalone node."
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Err(err)) => {
+                            summary.set(String::new());
+                            alert.set(Alert::from(err));
+                            Some(view! { <Skeleton/> }.into_view())
+                        }
+                        Some(Ok(nodes)) if !nodes.is_empty() => {
+                            let nodes = node_health(nodes);
+                            let unhealthy = nodes
+                                .iter()
+                                .filter(|(_, health)| *health != NodeHealth::Online)
+                                .count();
+                            summary.set(
+                                format!(
+                                    "{}, {} with issues",
+                                    maybe_plural(nodes.len(), "node", "nodes"),
+                                    unhealthy,
+                                ),
+                            );
+                            Some(
+                                view! {
+                                    <ColumnList headers=vec![
+                                        "Node".to_string(),
+                                        "Status".to_string(),
+                                        "Roles".to_string(),
+                                        "Version".to_string(),
+                                        "Last heartbeat".to_string(),
+                                        "Queues".to_string(),
+                                    ]>
+
+                                        <For
+                                            each=move || nodes.clone()
+                                            key=|(node, _)| node.id
+                                            let:node
+                                        >
+                                            <ClusterNodeItem node=node.0 health=node.1/>
+                                        </For>
+
+                                    </ColumnList>
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Ok(_)) => {
+                            summary.set(String::new());
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="No nodes"
+                                        subtitle="The server did not report any cluster members."
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                    }}
+
+                </Transition>
+
+                <Footer slot>
+                    <div class="px-6 py-4 border-t border-gray-200 dark:border-gray-700">
+                        <p class="text-sm text-gray-600 dark:text-gray-400">
+                            {move || summary.get()}
+                        </p>
+                    </div>
+                </Footer>
+            </ListTable>
+        </ListSection>
+    }
+}
+
+#[component]
+fn ClusterNodeItem(node: ClusterNode, health: NodeHealth) -> impl IntoView {
+    let (color, status) = match &health {
+        NodeHealth::Online => (Color::Green, "Online".to_string()),
+        NodeHealth::VersionSkew(expected) => (Color::Yellow, format!("Version skew ({expected})")),
+        NodeHealth::Unreachable => (Color::Red, "Unreachable".to_string()),
+    };
+    let name = if node.is_local {
+        format!("{} (this node)", node.hostname)
+    } else {
+        node.hostname
+    };
+    let heartbeat = node
+        .last_heartbeat
+        .map(|date| format!("{} ({})", HumanTime::from(date), date.format_date_time()))
+        .unwrap_or_else(|| "Never".to_string());
+    let queues = if node.queues.is_empty() {
+        "None".to_string()
+    } else {
+        node.queues.join(", ")
+    };
+
+    view! {
+        <tr>
+            <ListItem>
+                <div class="flex flex-col">
+                    <span class="text-sm font-semibold text-gray-800 dark:text-gray-200">
+                        {name}
+                    </span>
+                    <span class="text-xs text-gray-500">{format!("ID {}", node.id)}</span>
+                </div>
+            </ListItem>
+            <ListItem>
+                <Badge color=color>{status}</Badge>
+            </ListItem>
+            <ListItem>
+                <div class="flex flex-wrap gap-1">
+                    {node
+                        .roles
+                        .into_iter()
+                        .map(|role| view! { <Badge color=Color::Gray>{role}</Badge> })
+                        .collect_view()}
+                </div>
+            </ListItem>
+            <ListTextItem>{node.version}</ListTextItem>
+            <ListTextItem>{heartbeat}</ListTextItem>
+            <ListTextItem>{queues}</ListTextItem>
+        </tr>
+    }
+}
+
+/// Flags nodes whose heartbeat is stale or whose version differs from the
+/// version most nodes are running.
+fn node_health(nodes: Vec<ClusterNode>) -> Vec<(ClusterNode, NodeHealth)> {
+    let mut versions: AHashMap<&str, usize> = AHashMap::new();
+    for node in &nodes {
+        *versions.entry(node.version.as_str()).or_default() += 1;
+    }
+    let majority_version = versions
+        .into_iter()
+        .max_by(|(a_version, a_count), (b_version, b_count)| {
+            a_count.cmp(b_count).then_with(|| b_version.cmp(a_version))
+        })
+        .map(|(version, _)| version.to_string())
+        .unwrap_or_default();
+    let now = Utc::now();
+
+    nodes
+        .into_iter()
+        .map(|node| {
+            let health = if !node.is_local
+                && node.last_heartbeat.map_or(true, |date| {
+                    (now - date).num_seconds() > HEARTBEAT_TIMEOUT_SECS
+                }) {
+                NodeHealth::Unreachable
+            } else if node.version != majority_version {
+                NodeHealth::VersionSkew(majority_version.clone())
+            } else {
+                NodeHealth::Online
+            };
+            (node, health)
+        })
+        .collect()
+}
@@ -0,0 +1,648 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::collections::BTreeMap;
+
+use ahash::AHashMap;
+use chrono::{DateTime, Local, Utc};
+use gloo_storage::{SessionStorage, Storage};
+use humansize::{format_size, DECIMAL};
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        badge::Badge,
+        chart::{bar::BarChart, line::LineChart, ChartLegend, LazyChart, Series},
+        list::ZeroResults,
+        messages::alert::{use_alerts, Alert},
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::{use_authorization, AuthToken},
+    },
+    pages::{manage::security::SecurityPanel, FormatDateTime},
+};
+
+const DASHBOARD_STORAGE_KEY: &str = "webadmin_dashboard";
+
+const SELECT_CLASS: &str = "py-2 px-3 pe-9 block border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600";
+
+pub(crate) const PERIODS: &[(u32, &str)] = &[
+    (1, "Last hour"),
+    (24, "Last 24 hours"),
+    (168, "Last 7 days"),
+    (720, "Last 30 days"),
+];
+
+const NODE_COLORS: &[Color] = &[
+    Color::Blue,
+    Color::Green,
+    Color::Yellow,
+    Color::Red,
+    Color::Gray,
+];
+
+/// A node whose total deviates from the median of its peers by this factor
+/// is flagged on the per-node charts.
+const OUTLIER_FACTOR: f64 = 2.0;
+
+struct DashboardMetric {
+    id: &'static str,
+    title: &'static str,
+    subtitle: &'static str,
+}
+
+const DASHBOARD_METRICS: &[DashboardMetric] = &[
+    DashboardMetric {
+        id: "smtp.connection-start",
+        title: "SMTP connections",
+        subtitle: "Incoming SMTP sessions",
+    },
+    DashboardMetric {
+        id: "message-ingest.ham",
+        title: "Received messages",
+        subtitle: "Messages accepted for local delivery",
+    },
+    DashboardMetric {
+        id: "message-ingest.spam",
+        title: "Spam messages",
+        subtitle: "Incoming messages classified as spam",
+    },
+    DashboardMetric {
+        id: "delivery.attempt-start",
+        title: "Delivery attempts",
+        subtitle: "Outbound delivery attempts",
+    },
+    DashboardMetric {
+        id: "auth.failed",
+        title: "Authentication failures",
+        subtitle: "Failed login attempts",
+    },
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum MetricUnit {
+    Count,
+    Bytes,
+    Milliseconds,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct MetricInfo {
+    pub id: &'static str,
+    pub description: &'static str,
+    pub unit: MetricUnit,
+}
+
+/// Metrics that can be picked in alert conditions and live charts.
+pub(crate) const METRIC_CATALOG: &[MetricInfo] = &[
+    MetricInfo {
+        id: "auth.failed",
+        description: "Failed authentication attempts",
+        unit: MetricUnit::Count,
+    },
+    MetricInfo {
+        id: "auth.success",
+        description: "Successful authentications",
+        unit: MetricUnit::Count,
+    },
+    MetricInfo {
+        id: "delivery.attempt-start",
+        description: "Outbound delivery attempts",
+        unit: MetricUnit::Count,
+    },
+    MetricInfo {
+        id: "delivery.total-time",
+        description: "Time taken to deliver a message",
+        unit: MetricUnit::Milliseconds,
+    },
+    MetricInfo {
+        id: "dns.lookup-time",
+        description: "Time taken to resolve DNS queries",
+        unit: MetricUnit::Milliseconds,
+    },
+    MetricInfo {
+        id: "domain.count",
+        description: "Number of domains",
+        unit: MetricUnit::Count,
+    },
+    MetricInfo {
+        id: "imap.connection-start",
+        description: "Incoming IMAP connections",
+        unit: MetricUnit::Count,
+    },
+    MetricInfo {
+        id: "message-ingest.error",
+        description: "Messages that could not be ingested",
+        unit: MetricUnit::Count,
+    },
+    MetricInfo {
+        id: "message-ingest.ham",
+        description: "Messages accepted for local delivery",
+        unit: MetricUnit::Count,
+    },
+    MetricInfo {
+        id: "message-ingest.spam",
+        description: "Incoming messages classified as spam",
+        unit: MetricUnit::Count,
+    },
+    MetricInfo {
+        id: "message.size",
+        description: "Size of received messages",
+        unit: MetricUnit::Bytes,
+    },
+    MetricInfo {
+        id: "queue.count",
+        description: "Messages waiting in the outbound queue",
+        unit: MetricUnit::Count,
+    },
+    MetricInfo {
+        id: "security.authentication-ban",
+        description: "IP addresses banned after repeated authentication failures",
+        unit: MetricUnit::Count,
+    },
+    MetricInfo {
+        id: "server.memory",
+        description: "Memory used by the server",
+        unit: MetricUnit::Bytes,
+    },
+    MetricInfo {
+        id: "smtp.connection-start",
+        description: "Incoming SMTP connections",
+        unit: MetricUnit::Count,
+    },
+    MetricInfo {
+        id: "user.count",
+        description: "Number of user accounts",
+        unit: MetricUnit::Count,
+    },
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+enum NodeView {
+    Aggregate,
+    PerNode,
+    Node(u64),
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct MetricsHistory {
+    #[serde(default)]
+    pub timestamps: Vec<DateTime<Utc>>,
+    #[serde(default)]
+    pub metrics: Vec<MetricValues>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct MetricValues {
+    pub id: String,
+    #[serde(default)]
+    pub node_id: Option<u64>,
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    #[serde(default)]
+    pub values: Vec<f64>,
+}
+
+/// Last metrics fetched by the dashboard, shown while the server cannot be
+/// reached.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct CachedMetrics {
+    period: u32,
+    node_view: NodeView,
+    fetched_at: DateTime<Utc>,
+    history: MetricsHistory,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct NodeName {
+    id: u64,
+    hostname: String,
+}
+
+#[component]
+pub fn Dashboard() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+
+    let period = create_rw_signal(24u32);
+    let node_view = create_rw_signal(NodeView::Aggregate);
+    let stacked = create_rw_signal(true);
+
+    let nodes = create_resource(
+        move || (),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move {
+                match HttpRequest::get("/api/cluster/nodes")
+                    .with_authorization(&auth)
+                    .send::<Vec<NodeName>>()
+                    .await
+                {
+                    Err(http::Error::NotFound) => Ok(Vec::new()),
+                    result => result,
+                }
+            }
+        },
+    );
+
+    let metrics = create_resource(
+        move || (period.get(), node_view.get()),
+        move |(period, node_view)| {
+            let auth = auth.get_untracked();
+
+            async move {
+                match metrics_request(&auth, DASHBOARD_METRICS.iter().map(|m| m.id), period)
+                    .with_optional_parameter(
+                        "group_by",
+                        matches!(node_view, NodeView::PerNode).then_some("node"),
+                    )
+                    .with_optional_parameter(
+                        "node",
+                        match node_view {
+                            NodeView::Node(id) => Some(id.to_string()),
+                            _ => None,
+                        },
+                    )
+                    .send::<MetricsHistory>()
+                    .await
+                {
+                    Ok(history) => {
+                        let cached = CachedMetrics {
+                            period,
+                            node_view,
+                            fetched_at: Utc::now(),
+                            history,
+                        };
+                        if let Err(err) = SessionStorage::set(DASHBOARD_STORAGE_KEY, &cached) {
+                            log::error!("Failed to save state to session storage: {}", err);
+                        }
+                        Ok((cached.history, None))
+                    }
+                    Err(err @ (http::Error::Network(_) | http::Error::Unavailable(_))) => {
+                        match SessionStorage::get::<CachedMetrics>(DASHBOARD_STORAGE_KEY) {
+                            Ok(cached)
+                                if cached.period == period && cached.node_view == node_view =>
+                            {
+                                Ok((cached.history, Some(cached.fetched_at)))
+                            }
+                            _ => Err(err),
+                        }
+                    }
+                    Err(err) => Err(err),
+                }
+            }
+        },
+    );
+
+    let node_names =
+        create_memo(move |_| nodes.get().and_then(|nodes| nodes.ok()).unwrap_or_default());
+
+    view! {
+        <div class="max-w-[85rem] px-4 py-10 sm:px-6 lg:px-8 lg:py-14 mx-auto">
+            <div class="mb-6 grid gap-3 md:flex md:justify-between md:items-center">
+                <div>
+                    <h2 class="text-xl font-semibold text-gray-800 dark:text-gray-200">
+                        Dashboard
+                    </h2>
+                    <p class="text-sm text-gray-600 dark:text-gray-400">
+                        Server activity over time
+                    </p>
+                </div>
+                <div class="inline-flex gap-x-2">
+                    <Show when=move || !node_names.get().is_empty()>
+                        <select
+                            class=SELECT_CLASS
+                            on:change=move |ev| {
+                                node_view
+                                    .set(
+                                        match event_target_value(&ev).as_str() {
+                                            "all" => NodeView::Aggregate,
+                                            "each" => NodeView::PerNode,
+                                            id => {
+                                                id.parse().map(NodeView::Node).unwrap_or(NodeView::Aggregate)
+                                            }
+                                        },
+                                    );
+                            }
+                        >
+
+                            <option value="all" selected=move || node_view.get() == NodeView::Aggregate>
+                                All nodes
+                            </option>
+                            <option value="each" selected=move || node_view.get() == NodeView::PerNode>
+                                Per node
+                            </option>
+                            {move || {
+                                node_names
+                                    .get()
+                                    .into_iter()
+                                    .map(|node| {
+                                        let id = node.id;
+                                        view! {
+                                            <option
+                                                value=id.to_string()
+                                                selected=move || node_view.get() == NodeView::Node(id)
+                                            >
+                                                {node.hostname}
+                                            </option>
+                                        }
+                                    })
+                                    .collect_view()
+                            }}
+
+                        </select>
+                        <select
+                            class=SELECT_CLASS
+                            disabled=move || node_view.get() != NodeView::PerNode
+                            on:change=move |ev| {
+                                stacked.set(event_target_value(&ev) == "stacked");
+                            }
+                        >
+
+                            <option value="stacked" selected=move || stacked.get()>
+                                Stacked
+                            </option>
+                            <option value="side" selected=move || !stacked.get()>
+                                Side by side
+                            </option>
+                        </select>
+                    </Show>
+                    <select
+                        class=SELECT_CLASS
+                        on:change=move |ev| {
+                            period.set(event_target_value(&ev).parse().unwrap_or(24));
+                        }
+                    >
+
+                        {PERIODS
+                            .iter()
+                            .map(|(hours, label)| {
+                                let hours = *hours;
+                                view! {
+                                    <option
+                                        value=hours.to_string()
+                                        selected=move || period.get() == hours
+                                    >
+                                        {*label}
+                                    </option>
+                                }
+                            })
+                            .collect_view()}
+
+                    </select>
+                    <a
+                        class="py-2 px-3 inline-flex items-center gap-x-2 text-sm font-medium rounded-lg border border-gray-200 bg-white text-gray-800 shadow-sm hover:bg-gray-50 dark:bg-slate-900 dark:border-gray-700 dark:text-white dark:hover:bg-gray-800 dark:focus:outline-none dark:focus:ring-1 dark:focus:ring-gray-600"
+                        href="/manage/dashboard/live"
+                    >
+                        Live view
+                    </a>
+                </div>
+            </div>
+
+            <Transition fallback=Skeleton>
+                {move || match metrics.get() {
+                    None => None,
+                    Some(Err(http::Error::Unauthorized)) => {
+                        use_navigate()("/login", Default::default());
+                        Some(view! { <div></div> }.into_view())
+                    }
+                    Some(Err(http::Error::NotFound)) => {
+                        Some(
+                            view! {
+                                <ZeroResults
+                                    title="Metrics not available"
+                                    subtitle="Enable metrics collection in the telemetry settings to populate the dashboard."
+                                />
+                            }
+                                .into_view(),
+                        )
+                    }
+                    Some(Err(err)) => {
+                        alert.set(Alert::from(err));
+                        Some(view! { <Skeleton/> }.into_view())
+                    }
+                    Some(Ok((history, fetched_at))) => {
+                        let labels = format_labels(&history.timestamps, period.get_untracked());
+                        let node_view = node_view.get_untracked();
+                        let node_names = node_names
+                            .get_untracked()
+                            .into_iter()
+                            .map(|node| (node.id, node.hostname))
+                            .collect::<AHashMap<_, _>>();
+                        Some(
+                            view! {
+                                {fetched_at
+                                    .map(|fetched_at| {
+                                        view! {
+                                            <div
+                                                class="mb-4 bg-yellow-50 border border-yellow-200 text-sm text-yellow-800 rounded-lg p-4 dark:bg-yellow-800/10 dark:border-yellow-900 dark:text-yellow-500"
+                                                role="alert"
+                                            >
+                                                {format!(
+                                                    "The server cannot be reached, showing the metrics fetched on {}.",
+                                                    fetched_at.format_date_time(),
+                                                )}
+
+                                            </div>
+                                        }
+                                    })}
+
+                                <div class="grid lg:grid-cols-2 gap-4 sm:gap-6">
+                                    {DASHBOARD_METRICS
+                                        .iter()
+                                        .map(|metric| {
+                                            let series = build_series(
+                                                metric,
+                                                &history.metrics,
+                                                node_view,
+                                                &node_names,
+                                            );
+                                            let outliers = if node_view == NodeView::PerNode {
+                                                find_outliers(&series)
+                                            } else {
+                                                Vec::new()
+                                            };
+                                            view! {
+                                                <MetricChart
+                                                    metric=metric
+                                                    labels=labels.clone()
+                                                    series=series
+                                                    per_node=node_view == NodeView::PerNode
+                                                    stacked=stacked
+                                                    outliers=outliers
+                                                />
+                                            }
+                                        })
+                                        .collect_view()}
+                                </div>
+                            }
+                                .into_view(),
+                        )
+                    }
+                }}
+
+            </Transition>
+
+            <SecurityPanel period=period/>
+        </div>
+    }
+}
+
+#[component]
+fn MetricChart(
+    metric: &'static DashboardMetric,
+    labels: Vec<String>,
+    series: Vec<Series>,
+    per_node: bool,
+    stacked: RwSignal<bool>,
+    outliers: Vec<String>,
+) -> impl IntoView {
+    view! {
+        <LazyChart title=metric.title.to_string() subtitle=metric.subtitle.to_string()>
+            {if per_node {
+                let labels = labels.clone();
+                let series = series.clone();
+                (move || {
+                    view! {
+                        <BarChart
+                            labels=labels.clone()
+                            series=series.clone()
+                            stacked=stacked.get()
+                        />
+                    }
+                })
+                    .into_view()
+            } else {
+                view! { <LineChart labels=labels.clone() series=series.clone()/> }.into_view()
+            }}
+            <ChartLegend series=series.clone()/>
+            {(!outliers.is_empty())
+                .then(|| {
+                    let outliers = outliers.clone();
+                    view! {
+                        <div class="flex flex-wrap gap-2 mt-3">
+                            {outliers
+                                .into_iter()
+                                .map(|node| {
+                                    view! {
+                                        <Badge color=Color::Yellow>
+                                            {format!("Unusual activity on {node}")}
+                                        </Badge>
+                                    }
+                                })
+                                .collect_view()}
+                        </div>
+                    }
+                })}
+
+        </LazyChart>
+    }
+}
+
+/// Builds the chart series for a metric, one per node when grouping by node
+/// or a single aggregated series otherwise.
+fn build_series(
+    metric: &DashboardMetric,
+    values: &[MetricValues],
+    node_view: NodeView,
+    node_names: &AHashMap<u64, String>,
+) -> Vec<Series> {
+    let mut by_node: BTreeMap<Option<u64>, Vec<f64>> = BTreeMap::new();
+    for entry in values.iter().filter(|entry| entry.id == metric.id) {
+        let key = if node_view == NodeView::PerNode {
+            entry.node_id
+        } else {
+            None
+        };
+        let totals = by_node.entry(key).or_default();
+        if totals.len() < entry.values.len() {
+            totals.resize(entry.values.len(), 0.0);
+        }
+        for (total, value) in totals.iter_mut().zip(&entry.values) {
+            *total += value;
+        }
+    }
+
+    by_node
+        .into_iter()
+        .enumerate()
+        .map(|(pos, (node_id, values))| {
+            let name = match (node_id, node_view) {
+                (Some(id), _) | (None, NodeView::Node(id)) => node_names
+                    .get(&id)
+                    .cloned()
+                    .unwrap_or_else(|| format!("Node {id}")),
+                (None, _) => metric.title.to_string(),
+            };
+            Series {
+                name,
+                color: NODE_COLORS[pos % NODE_COLORS.len()],
+                values,
+            }
+        })
+        .collect()
+}
+
+/// Returns the names of the nodes whose total is far above or below the
+/// median of all nodes, which an aggregated graph would otherwise hide.
+fn find_outliers(series: &[Series]) -> Vec<String> {
+    if series.len() < 3 {
+        return Vec::new();
+    }
+    let totals = series
+        .iter()
+        .map(|s| s.values.iter().sum::<f64>())
+        .collect::<Vec<_>>();
+    let mut sorted = totals.clone();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let median = sorted[sorted.len() / 2];
+    if median <= 0.0 {
+        return Vec::new();
+    }
+
+    series
+        .iter()
+        .zip(totals)
+        .filter(|(_, total)| *total > median * OUTLIER_FACTOR || *total < median / OUTLIER_FACTOR)
+        .map(|(s, _)| s.name.clone())
+        .collect()
+}
+
+/// Builds a request for the history of the given metrics over the last
+/// `period` hours.
+pub(crate) fn metrics_request<'x>(
+    auth: &AuthToken,
+    metrics: impl IntoIterator<Item = &'x str>,
+    period: u32,
+) -> HttpRequest {
+    let after = Utc::now() - chrono::Duration::hours(period as i64);
+    HttpRequest::get("/api/telemetry/metrics")
+        .with_authorization(auth)
+        .with_parameter("after", after.to_rfc3339())
+        .with_parameter("metrics", metrics.into_iter().collect::<Vec<_>>().join(","))
+}
+
+pub(crate) fn format_labels(timestamps: &[DateTime<Utc>], period: u32) -> Vec<String> {
+    let format = if period <= 24 { "%H:%M" } else { "%d %b" };
+    timestamps
+        .iter()
+        .map(|date| date.with_timezone(&Local).format(format).to_string())
+        .collect()
+}
+
+pub(crate) fn format_metric(unit: MetricUnit, value: f64) -> String {
+    match unit {
+        MetricUnit::Count => format!("{value}"),
+        MetricUnit::Bytes => format_size(value as u64, DECIMAL),
+        MetricUnit::Milliseconds => format!("{value} ms"),
+    }
+}
@@ -0,0 +1,413 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::collections::{HashMap, HashSet};
+
+use leptos::*;
+use leptos_router::use_navigate;
+
+use crate::{
+    components::{
+        badge::Badge,
+        form::button::Button,
+        icon::{IconCheckCircle, IconExclamationTriangle},
+        list::table::{Table, TableRow},
+        messages::alert::{use_alerts, Alert, Alerts},
+        report::ReportView,
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::{use_authorization, AuthToken},
+        permissions::{use_capability, Capability},
+    },
+    pages::{
+        config::Settings,
+        directory::{
+            domains::edit::create_dkim_signatures, Principal, PrincipalField, PrincipalType,
+            PrincipalUpdate,
+        },
+        maybe_plural, List,
+    },
+};
+
+const PAGE_SIZE: u32 = 100;
+/// Roles defined by the server, which are not stored as principals.
+const BUILT_IN_ROLES: &[&str] = &["admin", "tenant-admin", "user"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Finding {
+    DanglingReference {
+        principal: String,
+        field: PrincipalField,
+        target: String,
+    },
+    MissingDkim {
+        domain: String,
+    },
+    MissingEmail {
+        principal: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FixStatus {
+    Open,
+    Fixing,
+    Fixed,
+}
+
+#[component]
+pub fn DirectoryCheck() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let findings = create_rw_signal(None::<Vec<(Finding, FixStatus)>>);
+
+    let run_check = create_action(move |_: &()| {
+        let auth = auth.get();
+
+        async move {
+            findings.set(None);
+            match check_directory(&auth).await {
+                Ok(result) => {
+                    findings.set(Some(
+                        result
+                            .into_iter()
+                            .map(|finding| (finding, FixStatus::Open))
+                            .collect(),
+                    ));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    let fix_action = create_action(move |idx: &usize| {
+        let auth = auth.get();
+        let idx = *idx;
+        let finding = findings.with_untracked(|findings| {
+            findings
+                .as_ref()
+                .and_then(|findings| findings.get(idx))
+                .map(|(finding, _)| finding.clone())
+        });
+        let set_status = move |status: FixStatus| {
+            findings.update(|findings| {
+                if let Some((_, current)) = findings.as_mut().and_then(|f| f.get_mut(idx)) {
+                    *current = status;
+                }
+            });
+        };
+
+        async move {
+            let result = match finding {
+                Some(Finding::DanglingReference {
+                    principal,
+                    field,
+                    target,
+                }) => {
+                    set_status(FixStatus::Fixing);
+                    HttpRequest::patch(("/api/principal", &principal))
+                        .with_authorization(&auth)
+                        .with_body(vec![PrincipalUpdate::remove_item(field, target)])
+                        .unwrap()
+                        .send::<()>()
+                        .await
+                }
+                Some(Finding::MissingDkim { domain }) => {
+                    set_status(FixStatus::Fixing);
+                    create_dkim_signatures(&auth, &domain).await
+                }
+                Some(Finding::MissingEmail { .. }) | None => return,
+            };
+
+            match result {
+                Ok(_) => {
+                    set_status(FixStatus::Fixed);
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    set_status(FixStatus::Open);
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    view! {
+        <Alerts/>
+        <ReportView>
+            <div class="gap-2 sm:gap-4 py-8 first:pt-0 last:pb-0 border-t first:border-transparent border-gray-200 dark:border-gray-700 dark:first:border-transparent">
+                <div class="sm:col-span-12 pb-4">
+                    <h2 class="text-lg font-semibold text-gray-800 dark:text-gray-200">
+                        Directory consistency check
+                    </h2>
+                    <p class="text-sm text-gray-500">
+                        Looks for group memberships and roles pointing to deleted principals, domains without DKIM signatures and accounts without an e-mail address.
+                    </p>
+                </div>
+                {move || {
+                    findings
+                        .get()
+                        .map(|list| {
+                            if list.is_empty() {
+                                view! {
+                                    <div class="flex items-center gap-x-2 text-sm text-gray-600 dark:text-gray-400">
+                                        <IconCheckCircle attr:class="flex-shrink-0 size-5 text-teal-500"/>
+                                        No problems were found in the directory.
+                                    </div>
+                                }
+                                    .into_view()
+                            } else {
+                                let total = list
+                                    .iter()
+                                    .filter(|(_, status)| *status != FixStatus::Fixed)
+                                    .count();
+                                view! {
+                                    <p class="pb-4 text-sm text-gray-600 dark:text-gray-400">
+                                        {maybe_plural(total, "open problem", "open problems")}
+                                    </p>
+                                    <Table headers=vec![
+                                        "".to_string(),
+                                        "Problem".to_string(),
+                                        "Details".to_string(),
+                                        "Action".to_string(),
+                                    ]>
+                                        {list
+                                            .into_iter()
+                                            .enumerate()
+                                            .map(|(idx, (finding, status))| {
+                                                view! {
+                                                    <FindingRow
+                                                        finding=finding
+                                                        status=status
+                                                        on_fix=Callback::new(move |_| fix_action.dispatch(idx))
+                                                    />
+                                                }
+                                            })
+                                            .collect_view()}
+                                    </Table>
+                                }
+                                    .into_view()
+                            }
+                        })
+                }}
+
+            </div>
+
+            <div class="flex justify-end gap-x-2">
+                <Button
+                    text="Back"
+                    color=Color::Gray
+                    on_click=move |_| {
+                        use_navigate()("/manage/maintenance", Default::default());
+                    }
+                />
+                <Button
+                    text=Signal::derive(move || {
+                        if run_check.pending().get() { "Checking..." } else { "Run check" }
+                            .to_string()
+                    })
+
+                    color=Color::Blue
+                    on_click=move |_| {
+                        run_check.dispatch(());
+                    }
+
+                    disabled=run_check.pending()
+                />
+            </div>
+        </ReportView>
+    }
+}
+
+#[component]
+fn FindingRow(finding: Finding, status: FixStatus, on_fix: Callback<()>) -> impl IntoView {
+    let capability = use_capability(if matches!(finding, Finding::MissingDkim { .. }) {
+        Capability::DkimSignatureCreate
+    } else {
+        Capability::PrincipalUpdate
+    });
+    let (problem, details) = match &finding {
+        Finding::DanglingReference {
+            principal,
+            field,
+            target,
+        } => (
+            match field {
+                PrincipalField::Members => "Deleted member",
+                PrincipalField::Roles => "Deleted role",
+                _ => "Deleted group",
+            },
+            format!("'{principal}' references missing principal '{target}'"),
+        ),
+        Finding::MissingDkim { domain } => (
+            "No DKIM signature",
+            format!("Messages from '{domain}' are not signed"),
+        ),
+        Finding::MissingEmail { principal } => (
+            "No e-mail address",
+            format!("Account '{principal}' cannot receive messages"),
+        ),
+    };
+    let icon = if status == FixStatus::Fixed {
+        view! { <IconCheckCircle attr:class="flex-shrink-0 size-5 text-teal-500"/> }
+    } else {
+        view! { <IconExclamationTriangle attr:class="flex-shrink-0 size-5 text-yellow-500"/> }
+    };
+    let action = match (status, finding) {
+        (FixStatus::Fixed, _) => view! { <Badge color=Color::Green>Fixed</Badge> }.into_view(),
+        (FixStatus::Fixing, _) => view! { <Badge color=Color::Gray>Fixing...</Badge> }.into_view(),
+        (FixStatus::Open, Finding::MissingEmail { principal }) => {
+            view! {
+                <a
+                    class="text-sm text-blue-600 decoration-2 hover:underline font-medium dark:text-blue-500"
+                    href=format!("/manage/directory/accounts/{principal}/edit")
+                >
+                    Edit account
+                </a>
+            }
+                .into_view()
+        }
+        (FixStatus::Open, finding) => {
+            view! {
+                <button
+                    type="button"
+                    class="text-sm text-blue-600 decoration-2 hover:underline font-medium dark:text-blue-500"
+                    disabled=move || capability.is_denied()
+                    title=move || capability.reason()
+                    on:click=move |_| on_fix.call(())
+                >
+                    {if matches!(finding, Finding::MissingDkim { .. }) {
+                        "Create signatures"
+                    } else {
+                        "Remove reference"
+                    }}
+
+                </button>
+            }
+                .into_view()
+        }
+    };
+
+    view! {
+        <TableRow>
+            {icon}
+            <span>{problem}</span>
+            <span class="whitespace-normal">{details}</span>
+            {action}
+        </TableRow>
+    }
+}
+
+async fn check_directory(auth: &AuthToken) -> http::Result<Vec<Finding>> {
+    let mut findings = Vec::new();
+
+    // Fetch all principals
+    let mut principals = HashMap::new();
+    let all_names = list_all(auth, "/api/principal").await?;
+    let existing = all_names.iter().cloned().collect::<HashSet<_>>();
+    for name in all_names {
+        match HttpRequest::get(("/api/principal", &name))
+            .with_authorization(auth)
+            .send::<Principal>()
+            .await
+        {
+            Ok(principal) => {
+                principals.insert(name, principal);
+            }
+            // Types unknown to the web admin, such as roles, are only
+            // checked for existence
+            Err(http::Error::NotFound | http::Error::Serializer { .. }) => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    // Memberships and roles pointing to principals that no longer exist
+    let mut names = principals.keys().cloned().collect::<Vec<_>>();
+    names.sort();
+    for name in &names {
+        let principal = &principals[name];
+        for (field, targets) in [
+            (PrincipalField::Members, &principal.members),
+            (PrincipalField::MemberOf, &principal.member_of),
+            (PrincipalField::Roles, &principal.roles),
+        ] {
+            for target in targets {
+                if !existing.contains(target)
+                    && (field != PrincipalField::Roles
+                        || !BUILT_IN_ROLES.contains(&target.as_str()))
+                {
+                    findings.push(Finding::DanglingReference {
+                        principal: name.clone(),
+                        field,
+                        target: target.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    // Domains without a DKIM signature
+    let signed_domains = HttpRequest::get("/api/settings/group")
+        .with_authorization(auth)
+        .with_parameter("prefix", "signature")
+        .with_parameter("suffix", "algorithm")
+        .with_parameter("field", "_id")
+        .send::<List<Settings>>()
+        .await?
+        .items
+        .into_iter()
+        .filter_map(|mut signature| signature.remove("domain"))
+        .map(|domain| domain.to_lowercase())
+        .collect::<HashSet<_>>();
+    let mut domains = list_all(auth, "/api/domain").await?;
+    domains.sort();
+    for domain in domains {
+        if !signed_domains.contains(&domain.to_lowercase()) {
+            findings.push(Finding::MissingDkim { domain });
+        }
+    }
+
+    // Accounts without an e-mail address
+    for name in &names {
+        let principal = &principals[name];
+        if principal.typ == Some(PrincipalType::Individual) && principal.emails.is_empty() {
+            findings.push(Finding::MissingEmail {
+                principal: name.clone(),
+            });
+        }
+    }
+
+    Ok(findings)
+}
+
+async fn list_all(auth: &AuthToken, url: &'static str) -> http::Result<Vec<String>> {
+    let mut items = Vec::new();
+    let mut page = 1;
+
+    loop {
+        let list = HttpRequest::get(url)
+            .with_authorization(auth)
+            .with_parameter("page", page.to_string())
+            .with_parameter("limit", PAGE_SIZE.to_string())
+            .send::<List<String>>()
+            .await?;
+        let is_last = list.items.len() < PAGE_SIZE as usize;
+        items.extend(list.items);
+        if is_last || items.len() as u64 >= list.total {
+            return Ok(items);
+        }
+        page += 1;
+    }
+}
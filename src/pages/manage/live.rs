@@ -0,0 +1,461 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::{cell::Cell, collections::VecDeque, rc::Rc, time::Duration};
+
+use ahash::AHashMap;
+use chrono::{DateTime, Local, Utc};
+use leptos::*;
+use leptos_router::{use_navigate, use_query_map};
+use serde::Deserialize;
+
+use crate::{
+    components::{
+        badge::Badge,
+        chart::{line::LineChart, ChartLegend, LazyChart, Series},
+        form::button::Button,
+        list::ZeroResults,
+        messages::alert::{use_alerts, Alert},
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        stream::EventStream,
+        url::UrlBuilder,
+    },
+    pages::manage::dashboard::{format_metric, MetricUnit, METRIC_CATALOG},
+};
+
+const LIVE_URL: &str = "/manage/dashboard/live";
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+const SELECT_CLASS: &str = "py-2 px-3 pe-9 block border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600";
+
+const INTERVALS: &[(u32, &str)] = &[
+    (1, "Every second"),
+    (5, "Every 5 seconds"),
+    (15, "Every 15 seconds"),
+    (30, "Every 30 seconds"),
+    (60, "Every minute"),
+];
+
+const WINDOWS: &[(u32, &str)] = &[
+    (300, "Last 5 minutes"),
+    (900, "Last 15 minutes"),
+    (3600, "Last hour"),
+    (14400, "Last 4 hours"),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum StreamStatus {
+    Idle,
+    Connecting,
+    Live,
+    Disconnected,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct LiveValue {
+    id: String,
+    value: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Sample {
+    received_at: DateTime<Utc>,
+    values: AHashMap<String, f64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct LiveConfig {
+    metrics: Vec<String>,
+    interval: u32,
+    window: u32,
+    kiosk: bool,
+}
+
+/// Streams the selected metrics and charts them as they arrive. The whole
+/// configuration lives in the URL so a view can be bookmarked for a wall
+/// display.
+#[component]
+pub fn LiveMetrics() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let query = use_query_map();
+
+    let config = create_memo(move |_| {
+        query.with(|q| LiveConfig {
+            metrics: q
+                .get("metrics")
+                .map(|metrics| {
+                    metrics
+                        .split(',')
+                        .map(|m| m.trim())
+                        .filter(|m| !m.is_empty())
+                        .map(|m| m.to_string())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            interval: q
+                .get("interval")
+                .and_then(|i| i.parse().ok())
+                .filter(|i| INTERVALS.iter().any(|(value, _)| value == i))
+                .unwrap_or(5),
+            window: q
+                .get("window")
+                .and_then(|w| w.parse().ok())
+                .filter(|w| WINDOWS.iter().any(|(value, _)| value == w))
+                .unwrap_or(900),
+            kiosk: q.get("kiosk").is_some_and(|k| k == "1"),
+        })
+    });
+    let update_config = move |update: &dyn Fn(&mut LiveConfig)| {
+        let mut config = config.get_untracked();
+        update(&mut config);
+        let url = UrlBuilder::new(LIVE_URL)
+            .with_parameter("metrics", config.metrics.join(","))
+            .with_parameter("interval", config.interval.to_string())
+            .with_parameter("window", config.window.to_string());
+        let url = if config.kiosk {
+            url.with_parameter("kiosk", "1")
+        } else {
+            url
+        };
+        use_navigate()(&url.finish(), Default::default());
+    };
+
+    let samples = create_rw_signal(VecDeque::<Sample>::new());
+    let status = create_rw_signal(StreamStatus::Idle);
+    let reconnect = create_rw_signal(0u64);
+    let stream = store_value(None::<EventStream>);
+    let is_active = Rc::new(Cell::new(true));
+    let is_active_ = is_active.clone();
+    on_cleanup(move || {
+        is_active_.set(false);
+        stream.set_value(None);
+    });
+
+    let connect = create_action(move |(metrics, interval): &(Vec<String>, u32)| {
+        let auth = auth.get();
+        let metrics = metrics.clone();
+        let interval = *interval;
+        let is_active = is_active.clone();
+
+        async move {
+            stream.set_value(None);
+            if metrics.is_empty() {
+                status.set(StreamStatus::Idle);
+                return;
+            }
+            status.set(StreamStatus::Connecting);
+
+            // Event streams cannot carry an Authorization header, obtain a
+            // short-lived token instead
+            let token = match HttpRequest::get("/api/telemetry/live/token")
+                .with_authorization(&auth)
+                .send::<String>()
+                .await
+            {
+                Ok(token) => token,
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                    return;
+                }
+                Err(err) => {
+                    status.set(StreamStatus::Disconnected);
+                    alert.set(Alert::from(err));
+                    return;
+                }
+            };
+            let url = UrlBuilder::new(format!("{}/api/telemetry/metrics/live", auth.base_url))
+                .with_parameter("metrics", metrics.join(","))
+                .with_parameter("interval", interval.to_string())
+                .with_parameter("token", token)
+                .finish();
+
+            stream.set_value(EventStream::open(
+                &url,
+                move |message| match serde_json::from_str::<Vec<LiveValue>>(&message) {
+                    Ok(values) => {
+                        let now = Utc::now();
+                        let window = config.get_untracked().window as i64;
+                        status.set(StreamStatus::Live);
+                        samples.update(|samples| {
+                            samples.push_back(Sample {
+                                received_at: now,
+                                values: values.into_iter().map(|v| (v.id, v.value)).collect(),
+                            });
+                            while samples
+                                .front()
+                                .is_some_and(|s| (now - s.received_at).num_seconds() > window)
+                            {
+                                samples.pop_front();
+                            }
+                        });
+                    }
+                    Err(err) => {
+                        log::warn!("Failed to parse live metrics {message:?}: {err}");
+                    }
+                },
+                move || {
+                    status.set(StreamStatus::Disconnected);
+                    stream.with_value(|stream| {
+                        if let Some(stream) = stream {
+                            stream.close();
+                        }
+                    });
+                    let is_active = is_active.clone();
+                    set_timeout(
+                        move || {
+                            if is_active.get() {
+                                reconnect.update(|n| *n += 1);
+                            }
+                        },
+                        RECONNECT_DELAY,
+                    );
+                },
+            ));
+            if stream.with_value(|stream| stream.is_none()) {
+                status.set(StreamStatus::Disconnected);
+                alert.set(Alert::error(
+                    "Failed to connect to the live metrics service",
+                ));
+            }
+        }
+    });
+
+    // Reconnect whenever the selection or the refresh interval changes
+    let stream_key = create_memo(move |_| {
+        let config = config.get();
+        (config.metrics, config.interval)
+    });
+    create_effect(move |_| {
+        reconnect.track();
+        let (metrics, interval) = stream_key.get();
+        samples.set(VecDeque::new());
+        connect.dispatch((metrics, interval));
+    });
+
+    let labels = Signal::derive(move || {
+        samples.with(|samples| {
+            samples
+                .iter()
+                .map(|s| {
+                    s.received_at
+                        .with_timezone(&Local)
+                        .format("%H:%M:%S")
+                        .to_string()
+                })
+                .collect::<Vec<_>>()
+        })
+    });
+
+    view! {
+        <div class="max-w-[85rem] px-4 py-10 sm:px-6 lg:px-8 lg:py-14 mx-auto">
+            <div class="mb-6 grid gap-3 md:flex md:justify-between md:items-center">
+                <div>
+                    <h2 class="text-xl font-semibold text-gray-800 dark:text-gray-200">
+                        Live metrics
+                    </h2>
+                    <p class="text-sm text-gray-600 dark:text-gray-400">
+                        {move || {
+                            match status.get() {
+                                StreamStatus::Idle => "Select the metrics to chart",
+                                StreamStatus::Connecting => "Connecting...",
+                                StreamStatus::Live => "Receiving updates",
+                                StreamStatus::Disconnected => "Disconnected, retrying...",
+                            }
+                        }}
+
+                    </p>
+                </div>
+                <div class="inline-flex gap-x-2">
+                    <Button
+                        text=Signal::derive(move || {
+                            if config.get().kiosk { "Show controls" } else { "Hide controls" }
+                                .to_string()
+                        })
+
+                        color=Color::Gray
+                        on_click=move |_| update_config(&|config| config.kiosk = !config.kiosk)
+                    />
+                    <Button
+                        text="Dashboard"
+                        color=Color::Gray
+                        on_click=move |_| use_navigate()("/manage/dashboard", Default::default())
+                    />
+                </div>
+            </div>
+
+            <Show when=move || !config.get().kiosk>
+                <div class="mb-6 bg-white border border-gray-200 rounded-xl shadow-sm p-4 md:p-5 dark:bg-slate-900 dark:border-gray-700">
+                    <div class="flex flex-wrap gap-2 mb-4">
+                        <select
+                            class=SELECT_CLASS
+                            on:change=move |ev| {
+                                let interval = event_target_value(&ev).parse().unwrap_or(5);
+                                update_config(&|config| config.interval = interval);
+                            }
+                        >
+
+                            {INTERVALS
+                                .iter()
+                                .map(|(value, label)| {
+                                    let value = *value;
+                                    view! {
+                                        <option
+                                            value=value.to_string()
+                                            selected=move || config.get().interval == value
+                                        >
+                                            {*label}
+                                        </option>
+                                    }
+                                })
+                                .collect_view()}
+                        </select>
+                        <select
+                            class=SELECT_CLASS
+                            on:change=move |ev| {
+                                let window = event_target_value(&ev).parse().unwrap_or(900);
+                                update_config(&|config| config.window = window);
+                            }
+                        >
+
+                            {WINDOWS
+                                .iter()
+                                .map(|(value, label)| {
+                                    let value = *value;
+                                    view! {
+                                        <option
+                                            value=value.to_string()
+                                            selected=move || config.get().window == value
+                                        >
+                                            {*label}
+                                        </option>
+                                    }
+                                })
+                                .collect_view()}
+                        </select>
+                    </div>
+                    <div class="grid sm:grid-cols-2 lg:grid-cols-3 gap-2">
+                        {METRIC_CATALOG
+                            .iter()
+                            .map(|info| {
+                                let id = info.id;
+                                view! {
+                                    <label class="flex gap-x-3 p-2 rounded-lg hover:bg-gray-50 dark:hover:bg-slate-800">
+                                        <input
+                                            type="checkbox"
+                                            class="mt-0.5 shrink-0 border-gray-200 rounded text-blue-600 focus:ring-blue-500 dark:bg-gray-800 dark:border-gray-700"
+                                            prop:checked=move || {
+                                                config.get().metrics.iter().any(|m| m == id)
+                                            }
+
+                                            on:change=move |_| {
+                                                update_config(
+                                                    &|config| {
+                                                        if let Some(pos) = config
+                                                            .metrics
+                                                            .iter()
+                                                            .position(|m| m == id)
+                                                        {
+                                                            config.metrics.remove(pos);
+                                                        } else {
+                                                            config.metrics.push(id.to_string());
+                                                        }
+                                                    },
+                                                );
+                                            }
+                                        />
+
+                                        <span class="block">
+                                            <span class="block text-sm font-mono text-gray-800 dark:text-gray-200">
+                                                {id}
+                                            </span>
+                                            <span class="block text-xs text-gray-500">
+                                                {info.description}
+                                            </span>
+                                        </span>
+                                    </label>
+                                }
+                            })
+                            .collect_view()}
+                    </div>
+                </div>
+            </Show>
+
+            {move || {
+                let metrics = config.get().metrics;
+                if metrics.is_empty() {
+                    return view! {
+                        <ZeroResults
+                            title="No metrics selected"
+                            subtitle="Pick one or more metrics to start charting them live."
+                        />
+                    }
+                        .into_view();
+                }
+                view! {
+                    <div class="grid lg:grid-cols-2 gap-4 sm:gap-6">
+                        {metrics
+                            .into_iter()
+                            .map(|id| view! { <LiveChart id=id samples=samples labels=labels/> })
+                            .collect_view()}
+                    </div>
+                }
+                    .into_view()
+            }}
+
+            <Show when=move || status.get() == StreamStatus::Disconnected>
+                <div class="fixed bottom-4 end-4 z-40">
+                    <Badge color=Color::Red large=true>
+                        Disconnected
+                    </Badge>
+                </div>
+            </Show>
+        </div>
+    }
+}
+
+#[component]
+fn LiveChart(
+    id: String,
+    samples: RwSignal<VecDeque<Sample>>,
+    labels: Signal<Vec<String>>,
+) -> impl IntoView {
+    let unit = METRIC_CATALOG
+        .iter()
+        .find(|info| info.id == id)
+        .map_or(MetricUnit::Count, |info| info.unit);
+    let id_ = id.clone();
+    let series = Signal::derive(move || {
+        vec![Series {
+            name: id_.clone(),
+            color: Color::Blue,
+            values: samples.with(|samples| {
+                samples
+                    .iter()
+                    .map(|s| s.values.get(&id_).copied().unwrap_or_default())
+                    .collect()
+            }),
+        }]
+    });
+    let id_ = id.clone();
+    let current = Signal::derive(move || {
+        samples
+            .with(|samples| samples.back().and_then(|s| s.values.get(&id_).copied()))
+            .map(|value| format_metric(unit, value))
+            .unwrap_or_else(|| "Waiting for data".to_string())
+    });
+
+    view! {
+        <LazyChart title=id subtitle=current>
+            <LineChart labels=labels series=series/>
+            <ChartLegend series=series/>
+        </LazyChart>
+    }
+}
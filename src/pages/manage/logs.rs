@@ -4,7 +4,7 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Local, SecondsFormat, Utc};
 use leptos::*;
 use leptos_router::*;
 use serde::{Deserialize, Serialize};
@@ -14,23 +14,42 @@ use crate::pages::queue::messages::deserialize_datetime;
 use crate::{
     components::{
         badge::Badge,
+        icon::{IconDocumentText, IconLink},
         list::{
-            header::ColumnList, pagination::Pagination, toolbar::SearchBox, Footer, ListItem,
-            ListSection, ListTable, Toolbar, ZeroResults,
+            header::ColumnList,
+            pagination::Pagination,
+            toolbar::{SearchBox, ToolbarButton},
+            Footer, ListItem, ListSection, ListTable, Toolbar, ZeroResults,
         },
         messages::alert::{use_alerts, Alert},
         skeleton::Skeleton,
         Color,
     },
     core::{
+        clipboard::copy_to_clipboard,
+        file::download_file,
         http::{self, HttpRequest},
-        oauth::use_authorization,
+        oauth::{use_authorization, AuthToken},
         url::UrlBuilder,
     },
-    pages::{FormatDateTime, List},
+    pages::{
+        manage::audit::csv_escape, maybe_plural, queue::messages::manage::parse_local_datetime,
+        FormatDateTime, List,
+    },
 };
 
 const PAGE_SIZE: u32 = 50;
+const EXPORT_PAGE_SIZE: u32 = 1000;
+const EXPORT_MAX_ENTRIES: u64 = 50_000;
+
+const LOG_LEVELS: &[(&str, &str)] = &[
+    ("", "All levels"),
+    ("ERROR", "Error"),
+    ("WARN", "Warning"),
+    ("INFO", "Info"),
+    ("DEBUG", "Debug"),
+    ("TRACE", "Trace"),
+];
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 struct LogEntry {
@@ -40,6 +59,22 @@ struct LogEntry {
     message: String,
 }
 
+/// Filters of the Logs page, all of them kept in the URL so that the view
+/// can be shared.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct LogFilter {
+    text: Option<String>,
+    level: Option<String>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ExportFormat {
+    Csv,
+    Text,
+}
+
 #[component]
 pub fn Logs() -> impl IntoView {
     let query = use_query_map();
@@ -51,16 +86,26 @@ pub fn Logs() -> impl IntoView {
     });
     let filter = create_memo(move |_| {
         query.with(|q| {
-            q.get("filter").and_then(|s| {
-                let s = s.trim();
-                if !s.is_empty() {
-                    Some(s.to_string())
-                } else {
-                    None
-                }
-            })
+            let param = |name: &str| {
+                q.get(name)
+                    .map(|value| value.trim())
+                    .filter(|value| !value.is_empty())
+                    .map(|value| value.to_string())
+            };
+            let date = |name: &str| {
+                param(name)
+                    .and_then(|value| DateTime::parse_from_rfc3339(&value).ok())
+                    .map(|value| value.to_utc())
+            };
+            LogFilter {
+                text: param("filter"),
+                level: param("level"),
+                from: date("from"),
+                to: date("to"),
+            }
         })
     });
+    let text_filter = create_memo(move |_| filter.with(|filter| filter.text.clone()));
 
     let auth = use_authorization();
     let alert = use_alerts();
@@ -69,36 +114,167 @@ pub fn Logs() -> impl IntoView {
         move |(page, filter)| {
             let auth = auth.get_untracked();
 
-            async move {
-                HttpRequest::get("/api/logs")
-                    .with_authorization(&auth)
-                    .with_parameter("page", page.to_string())
-                    .with_parameter("limit", PAGE_SIZE.to_string())
-                    .with_optional_parameter("filter", filter)
-                    .send::<List<LogEntry>>()
-                    .await
-            }
+            async move { fetch_logs(&auth, &filter, page, PAGE_SIZE).await }
         },
     );
 
+    let export_action = create_action(move |format: &ExportFormat| {
+        let auth = auth.get();
+        let filter = filter.get();
+        let format = *format;
+
+        async move {
+            match export_logs(&auth, &filter, format).await {
+                Ok((contents, count)) => {
+                    let (extension, content_type) = match format {
+                        ExportFormat::Csv => ("csv", "text/csv"),
+                        ExportFormat::Text => ("log", "text/plain"),
+                    };
+                    let file_name =
+                        format!("logs-{}.{extension}", Utc::now().format("%Y%m%d-%H%M%S"));
+                    match download_file(&file_name, content_type, &contents) {
+                        Ok(_) => {
+                            alert.set(Alert::success(format!(
+                                "Exported {}",
+                                maybe_plural(count, "log entry", "log entries")
+                            )));
+                        }
+                        Err(err) => {
+                            alert.set(Alert::error("Export failed").with_details(err));
+                        }
+                    }
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
     let total_results = create_rw_signal(None::<u32>);
+    let navigate_with = move |update: &dyn Fn(&mut LogFilter)| {
+        let mut filter = filter.get();
+        update(&mut filter);
+        for value in [&mut filter.text, &mut filter.level] {
+            *value = value.take().filter(|value| !value.trim().is_empty());
+        }
+        use_navigate()(&filter_url(&filter, None), Default::default());
+    };
+    let copy_permalink = move |_| {
+        let mut filter = filter.get_untracked();
+        // Pin the end of the range so that newer entries do not change the view
+        filter.to.get_or_insert_with(Utc::now);
+        let page = Some(page.get_untracked()).filter(|&page| page > 1);
+        let url = format!(
+            "{}{}",
+            window().location().origin().unwrap_or_default(),
+            filter_url(&filter, page)
+        );
+        if copy_to_clipboard(&url) {
+            alert.set(Alert::success("Link to this view copied to the clipboard"));
+        } else {
+            alert.set(Alert::error("Failed to copy to clipboard"));
+        }
+    };
+    let input_class = "py-2 px-3 block border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600";
 
     view! {
         <ListSection>
             <ListTable title="Log files" subtitle="View and search log entries">
                 <Toolbar slot>
                     <SearchBox
-                        value=filter
-                        on_search=move |value| {
-                            use_navigate()(
-                                &UrlBuilder::new("/manage/logs")
-                                    .with_parameter("filter", value)
-                                    .finish(),
-                                Default::default(),
-                            );
+                        value=text_filter
+                        on_search=move |value: String| {
+                            navigate_with(&|filter| filter.text = Some(value.clone()));
+                        }
+                    />
+
+                    <select
+                        class=format!("{input_class} pe-9")
+                        on:change=move |ev| {
+                            let value = event_target_value(&ev);
+                            navigate_with(&|filter| filter.level = Some(value.clone()));
+                        }
+                    >
+
+                        {LOG_LEVELS
+                            .iter()
+                            .map(|(value, label)| {
+                                view! {
+                                    <option
+                                        value=*value
+                                        selected=move || {
+                                            filter.get().level.as_deref().unwrap_or_default() == *value
+                                        }
+                                    >
+
+                                        {*label}
+                                    </option>
+                                }
+                            })
+                            .collect_view()}
+
+                    </select>
+                    <input
+                        type="datetime-local"
+                        class=input_class
+                        title="From"
+                        prop:value=move || filter.get().from.map(local_datetime).unwrap_or_default()
+                        on:change=move |ev| {
+                            let value = parse_local_datetime(&event_target_value(&ev));
+                            navigate_with(&|filter| filter.from = value);
+                        }
+                    />
+
+                    <input
+                        type="datetime-local"
+                        class=input_class
+                        title="To"
+                        prop:value=move || filter.get().to.map(local_datetime).unwrap_or_default()
+                        on:change=move |ev| {
+                            let value = parse_local_datetime(&event_target_value(&ev));
+                            navigate_with(&|filter| filter.to = value);
                         }
                     />
 
+                    <ToolbarButton text="Copy link" color=Color::Gray on_click=copy_permalink>
+                        <IconLink/>
+                    </ToolbarButton>
+                    <ToolbarButton
+                        text=Signal::derive(move || {
+                            if export_action.pending().get() {
+                                "Exporting...".to_string()
+                            } else {
+                                "Export CSV".to_string()
+                            }
+                        })
+
+                        color=Color::Blue
+                        on_click=move |_| {
+                            if !export_action.pending().get_untracked() {
+                                export_action.dispatch(ExportFormat::Csv);
+                            }
+                        }
+                    >
+
+                        <IconDocumentText/>
+                    </ToolbarButton>
+                    <ToolbarButton
+                        text="Export text"
+                        color=Color::Gray
+                        on_click=move |_| {
+                            if !export_action.pending().get_untracked() {
+                                export_action.dispatch(ExportFormat::Text);
+                            }
+                        }
+                    >
+
+                        <IconDocumentText/>
+                    </ToolbarButton>
+
                 </Toolbar>
 
                 <Transition fallback=Skeleton>
@@ -160,10 +336,7 @@ pub fn Logs() -> impl IntoView {
                         page_size=PAGE_SIZE
                         on_page_change=move |page: u32| {
                             use_navigate()(
-                                &UrlBuilder::new("/manage/logs")
-                                    .with_parameter("page", page.to_string())
-                                    .with_optional_parameter("filter", filter.get())
-                                    .finish(),
+                                &filter_url(&filter.get(), Some(page)),
                                 Default::default(),
                             );
                         }
@@ -217,3 +390,86 @@ impl LogEntry {
         hasher.finish().to_string()
     }
 }
+
+async fn fetch_logs(
+    auth: &AuthToken,
+    filter: &LogFilter,
+    page: u32,
+    limit: u32,
+) -> http::Result<List<LogEntry>> {
+    HttpRequest::get("/api/logs")
+        .with_authorization(auth)
+        .with_parameter("page", page.to_string())
+        .with_parameter("limit", limit.to_string())
+        .with_optional_parameter("filter", filter.text.clone())
+        .with_optional_parameter("level", filter.level.clone())
+        .with_optional_parameter("after", filter.from.map(|date| date.to_rfc3339()))
+        .with_optional_parameter("before", filter.to.map(|date| date.to_rfc3339()))
+        .send::<List<LogEntry>>()
+        .await
+}
+
+/// Fetches every log entry matching the filter and renders them in the
+/// requested format.
+async fn export_logs(
+    auth: &AuthToken,
+    filter: &LogFilter,
+    format: ExportFormat,
+) -> http::Result<(String, usize)> {
+    let mut contents = match format {
+        ExportFormat::Csv => String::from("date,level,message\r\n"),
+        ExportFormat::Text => String::new(),
+    };
+    let mut count = 0;
+    let mut page = 1;
+
+    loop {
+        let logs = fetch_logs(auth, filter, page, EXPORT_PAGE_SIZE).await?;
+        let is_last = logs.items.len() < EXPORT_PAGE_SIZE as usize;
+        for log in logs.items {
+            let timestamp = log.timestamp.to_rfc3339();
+            match format {
+                ExportFormat::Csv => {
+                    contents.push_str(
+                        &[timestamp, log.level, log.message]
+                            .iter()
+                            .map(|field| csv_escape(field))
+                            .collect::<Vec<_>>()
+                            .join(","),
+                    );
+                    contents.push_str("\r\n");
+                }
+                ExportFormat::Text => {
+                    contents.push_str(&format!("{timestamp} {} {}\n", log.level, log.message));
+                }
+            }
+            count += 1;
+        }
+        if is_last || count as u64 >= logs.total.min(EXPORT_MAX_ENTRIES) {
+            break;
+        }
+        page += 1;
+    }
+
+    Ok((contents, count))
+}
+
+fn filter_url(filter: &LogFilter, page: Option<u32>) -> String {
+    let date = |date: Option<DateTime<Utc>>| {
+        date.map(|date| date.to_rfc3339_opts(SecondsFormat::Secs, true))
+    };
+    UrlBuilder::new("/manage/logs")
+        .with_optional_parameter("page", page.map(|page| page.to_string()))
+        .with_optional_parameter("filter", filter.text.clone())
+        .with_optional_parameter("level", filter.level.clone())
+        .with_optional_parameter("from", date(filter.from))
+        .with_optional_parameter("to", date(filter.to))
+        .finish()
+}
+
+/// Formats a date as the value of a `datetime-local` input.
+fn local_datetime(date: DateTime<Utc>) -> String {
+    date.with_timezone(&Local)
+        .format("%Y-%m-%dT%H:%M")
+        .to_string()
+}
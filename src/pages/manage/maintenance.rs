@@ -4,21 +4,53 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
+use chrono::{DateTime, Utc};
+use chrono_humanize::HumanTime;
 use leptos::*;
 use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     components::{
-        icon::{IconCheckCircle, IconComputerDesktop, IconPower, IconRefresh, IconShieldCheck},
-        messages::alert::{use_alerts, Alert, Alerts},
+        badge::Badge,
+        icon::{
+            IconCheckCircle, IconClock, IconComputerDesktop, IconHeart, IconPlay, IconPower,
+            IconRefresh, IconShieldCheck, IconUserGroup, IconWrench,
+        },
+        messages::{
+            alert::{use_alerts, Alert, Alerts},
+            modal::{use_modals, Modal},
+        },
+        Color,
     },
     core::{
         http::{self, HttpRequest},
+        maintenance::{use_maintenance, MaintenanceMode},
         oauth::use_authorization,
+        permissions::{use_capability, Capability},
+        tasks::{use_tasks, TaskState, TaskStatus},
+    },
+    pages::{
+        config::ReloadSettings,
+        manage::{restart::RestartPanel, update::UpdateWebadminPanel},
+        FormatDateTime,
     },
-    pages::config::ReloadSettings,
 };
 
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct HousekeeperTask {
+    id: String,
+    name: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    last_run: Option<DateTime<Utc>>,
+    #[serde(default)]
+    next_run: Option<DateTime<Utc>>,
+    #[serde(default)]
+    status: Option<TaskStatus>,
+}
+
 #[derive(Debug, Clone, Copy)]
 struct Action {
     title: &'static str,
@@ -26,6 +58,7 @@ struct Action {
     icon: &'static str,
     url: &'static str,
     success_message: &'static str,
+    capability: Capability,
 }
 
 const ACTIONS: &[Action] = &[
@@ -35,6 +68,7 @@ const ACTIONS: &[Action] = &[
         icon: "refresh",
         url: "/api/reload",
         success_message: "Successfully reloaded configuration",
+        capability: Capability::SettingsReload,
     },
     Action {
         title: "Validate configuration",
@@ -42,13 +76,15 @@ const ACTIONS: &[Action] = &[
         icon: "check_circle",
         url: "/api/reload?dry-run=true",
         success_message: "Configuration is valid",
+        capability: Capability::SettingsReload,
     },
     Action {
         title: "Restart server",
-        description: "Restarts the server. This will interrupt any active connections.",
+        description: "Restarts the server, optionally waiting for active sessions and deliveries to finish first.",
         icon: "power",
         url: "/api/restart",
         success_message: "Restarting server, try reloading this page in a few seconds.",
+        capability: Capability::Restart,
     },
     Action {
         title: "Update SPAM rules",
@@ -56,22 +92,28 @@ const ACTIONS: &[Action] = &[
         icon: "shield_check",
         url: "/api/update/spam-filter",
         success_message: "Successfully updated SPAM rules to the latest version",
+        capability: Capability::SpamFilterUpdate,
     },
     Action {
         title: "Update Webadmin",
-        description: "Downloads and installs the latest version of the Stalwart Webadmin from the Github repository.",
+        description: "Downloads and installs a version of the Stalwart Webadmin from the Github repository.",
         icon: "computer_desktop",
         url: "/api/update/webadmin",
         success_message: "Successfully updated the web admin to the latest version",
+        capability: Capability::WebadminUpdate,
     },
 
 ];
 
 #[component]
 pub fn Maintenance() -> impl IntoView {
+    let maintenance_capability = use_capability(Capability::MaintenanceMode);
     let auth = use_authorization();
     let alert = use_alerts();
+    let modal = use_modals();
     let (pending, set_pending) = create_signal(false);
+    let show_restart = create_rw_signal(false);
+    let show_update = create_rw_signal(false);
 
     let execute = create_action(move |idx: &usize| {
         let auth = auth.get();
@@ -124,6 +166,136 @@ pub fn Maintenance() -> impl IntoView {
         }
     });
 
+    let maintenance = use_maintenance();
+    let reject_submissions = create_rw_signal(false);
+    let toggle_maintenance = create_action(move |enable: &bool| {
+        let auth = auth.get();
+        let mode = MaintenanceMode {
+            enabled: *enable,
+            reject_submissions: *enable && reject_submissions.get(),
+            since: None,
+        };
+
+        async move {
+            match mode.apply(&auth).await {
+                Ok(mode) => {
+                    alert.set(Alert::success(if mode.enabled {
+                        "Maintenance mode enabled, outbound delivery is paused."
+                    } else {
+                        "Maintenance mode lifted, outbound delivery resumed."
+                    }));
+                    maintenance.set(Some(mode));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+    let is_maintenance = create_memo(move |_| {
+        maintenance.with(|mode| mode.as_ref().is_some_and(|mode| mode.enabled))
+    });
+
+    let tasks = use_tasks();
+    let scheduled = create_resource(
+        move || (),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move {
+                HttpRequest::get("/api/housekeeper")
+                    .with_authorization(&auth)
+                    .send::<Vec<HousekeeperTask>>()
+                    .await
+            }
+        },
+    );
+
+    // Refresh the schedule whenever one of the tracked tasks finishes
+    let total_running = create_memo(move |_| tasks.with(|tasks| tasks.running().count()));
+    create_effect(move |prev: Option<usize>| {
+        let running = total_running.get();
+        if prev.map_or(false, |prev| prev > running) {
+            scheduled.refetch();
+        }
+        running
+    });
+
+    let trigger = create_action(move |task: &HousekeeperTask| {
+        let auth = auth.get();
+        let task = task.clone();
+
+        async move {
+            match HttpRequest::post(("/api/housekeeper", &task.id))
+                .with_authorization(&auth)
+                .send::<Option<String>>()
+                .await
+            {
+                Ok(status_url) => {
+                    tasks.update(|tasks| {
+                        tasks.insert(
+                            task.name.clone(),
+                            Some(
+                                status_url
+                                    .unwrap_or_else(|| format!("/api/housekeeper/{}", task.id)),
+                            ),
+                        );
+                    });
+                    scheduled.refetch();
+                    alert.set(Alert::success(format!("Started task \"{}\".", task.name)));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    // Purge tasks delete data permanently, ask for the task name first
+    let run_task = Callback::new(move |task: HousekeeperTask| {
+        if !task.id.contains("purge") {
+            trigger.dispatch(task);
+            return;
+        }
+        let mut impact = vec![format!(
+            "Data selected by \"{}\" is deleted permanently and cannot be recovered.",
+            task.name
+        )];
+        if let Some(description) = &task.description {
+            impact.push(description.clone());
+        }
+        impact.push(match task.last_run {
+            Some(last_run) => format!(
+                "The task last ran {}, everything eligible since then will be removed.",
+                HumanTime::from(last_run)
+            ),
+            None => "The task has never run, all eligible data will be removed.".to_string(),
+        });
+        modal.set(
+            Modal::with_title("Confirm purge")
+                .with_message(format!(
+                    "Are you sure you want to run \"{}\" now?",
+                    task.name
+                ))
+                .with_button("Run purge")
+                .with_confirmation_text(task.name.clone())
+                .with_impact(move || {
+                    let impact = impact.clone();
+                    async move { Ok(impact) }
+                })
+                .with_dangerous_callback(move || {
+                    trigger.dispatch(task.clone());
+                }),
+        );
+    });
+    let trigger_pending = trigger.pending();
+
     let actions = ACTIONS.iter().enumerate().map(|(idx, action)| {
         let icon_class = "mt-1 flex-shrink-0 size-5 text-gray-800 dark:text-gray-200";
         let icon = match action.icon {
@@ -134,13 +306,23 @@ pub fn Maintenance() -> impl IntoView {
             "computer_desktop" => view! { <IconComputerDesktop attr:class=icon_class/> },
             _ => unreachable!("No icon specified"),
         };
+        let capability = use_capability(action.capability);
 
         view! {
             <a
                 class="group flex flex-col bg-white border shadow-sm rounded-xl hover:shadow-md transition dark:bg-slate-900 dark:border-gray-800"
+                class=(["opacity-50", "cursor-not-allowed"], move || capability.is_denied())
                 href="#"
+                title=move || capability.reason()
                 on:click=move |_| {
-                    execute.dispatch(idx);
+                    if capability.is_denied() {
+                        return;
+                    }
+                    match action.url {
+                        "/api/restart" => show_restart.set(true),
+                        "/api/update/webadmin" => show_update.set(true),
+                        _ => execute.dispatch(idx),
+                    }
                 }
 
                 disabled=move || pending.get()
@@ -168,8 +350,276 @@ pub fn Maintenance() -> impl IntoView {
 
                 {actions}
 
+                <a
+                    class="group flex flex-col bg-white border shadow-sm rounded-xl hover:shadow-md transition dark:bg-slate-900 dark:border-gray-800"
+                    href="/manage/maintenance/self-test"
+                >
+                    <div class="p-4 md:p-5">
+                        <div class="flex">
+                            <IconHeart attr:class="mt-1 flex-shrink-0 size-5 text-gray-800 dark:text-gray-200"/>
+                            <div class="grow ms-5">
+                                <h3 class="group-hover:text-blue-600 font-semibold text-gray-800 dark:group-hover:text-gray-400 dark:text-gray-200">
+                                    Connectivity self-test
+                                </h3>
+                                <p class="text-sm text-gray-500">
+                                    Checks that clients can reach the JMAP, IMAP, POP3 and autoconfiguration endpoints.
+                                </p>
+                            </div>
+                        </div>
+                    </div>
+                </a>
+
+                <a
+                    class="group flex flex-col bg-white border shadow-sm rounded-xl hover:shadow-md transition dark:bg-slate-900 dark:border-gray-800"
+                    href="/manage/maintenance/directory-check"
+                >
+                    <div class="p-4 md:p-5">
+                        <div class="flex">
+                            <IconUserGroup attr:class="mt-1 flex-shrink-0 size-5 text-gray-800 dark:text-gray-200"/>
+                            <div class="grow ms-5">
+                                <h3 class="group-hover:text-blue-600 font-semibold text-gray-800 dark:group-hover:text-gray-400 dark:text-gray-200">
+                                    Directory consistency check
+                                </h3>
+                                <p class="text-sm text-gray-500">
+                                    Finds orphaned memberships, unsigned domains and accounts without an e-mail address.
+                                </p>
+                            </div>
+                        </div>
+                    </div>
+                </a>
+
             </div>
 
+            <Show when=move || show_restart.get()>
+                <RestartPanel on_close=move |_| show_restart.set(false)/>
+            </Show>
+            <Show when=move || show_update.get()>
+                <UpdateWebadminPanel on_close=move |_| show_update.set(false)/>
+            </Show>
+
+            <div class="mt-10 p-4 md:p-5 flex items-start gap-x-4 bg-white border shadow-sm rounded-xl dark:bg-slate-900 dark:border-gray-800">
+                <IconWrench attr:class="mt-1 flex-shrink-0 size-5 text-gray-800 dark:text-gray-200"/>
+                <div class="grow">
+                    <div class="flex items-center gap-x-2">
+                        <h3 class="font-semibold text-gray-800 dark:text-gray-200">
+                            Maintenance mode
+                        </h3>
+                        {move || {
+                            if is_maintenance.get() {
+                                view! { <Badge color=Color::Yellow>Enabled</Badge> }
+                            } else {
+                                view! { <Badge color=Color::Green>Disabled</Badge> }
+                            }
+                        }}
+
+                    </div>
+                    <p class="text-sm text-gray-500">
+                        Pauses outbound delivery so messages stay queued until maintenance mode is lifted.
+                    </p>
+                    {move || {
+                        maintenance
+                            .get()
+                            .filter(|mode| mode.enabled)
+                            .map(|mode| {
+                                view! {
+                                    <p class="mt-1 text-xs text-gray-500">
+                                        {mode
+                                            .since
+                                            .map(|since| format!("Enabled since {}. ", since.format_date_time()))}
+                                        {if mode.reject_submissions {
+                                            "New submissions are rejected with a temporary failure."
+                                        } else {
+                                            "New submissions are accepted and queued."
+                                        }}
+
+                                    </p>
+                                }
+                            })
+                    }}
+
+                    <label class="mt-3 flex items-center gap-x-2" class:hidden=is_maintenance>
+                        <input
+                            type="checkbox"
+                            class="shrink-0 border-gray-200 rounded text-blue-600 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:checked:bg-blue-500 dark:checked:border-blue-500"
+                            prop:checked=reject_submissions
+                            on:change=move |ev| reject_submissions.set(event_target_checked(&ev))
+                        />
+                        <span class="text-sm text-gray-500">
+                            Reject new submissions with a temporary (4xx) error
+                        </span>
+                    </label>
+                </div>
+                <button
+                    type="button"
+                    class="py-1.5 px-2.5 inline-flex items-center gap-x-1.5 text-sm font-medium rounded-lg border border-gray-200 bg-white text-gray-800 shadow-sm hover:bg-gray-50 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-white dark:hover:bg-gray-800"
+                    disabled=move || maintenance_capability.is_denied() || toggle_maintenance.pending().get()
+                    title=move || maintenance_capability.reason()
+                    class=(["disabled:pointer-events-auto", "disabled:cursor-not-allowed"], move || maintenance_capability.is_denied())
+                    on:click=move |_| toggle_maintenance.dispatch(!is_maintenance.get())
+                >
+                    {move || if is_maintenance.get() { "Lift" } else { "Enable" }}
+                </button>
+            </div>
+
+            <div class="mt-10 flex items-center justify-between">
+                <div>
+                    <h2 class="text-lg font-semibold text-gray-800 dark:text-gray-200">
+                        Scheduled tasks
+                    </h2>
+                    <p class="text-sm text-gray-500">
+                        Housekeeping jobs run periodically by the server.
+                    </p>
+                </div>
+                <button
+                    type="button"
+                    class="py-2 px-3 inline-flex items-center gap-x-2 text-sm font-medium rounded-lg border border-gray-200 bg-white text-gray-800 shadow-sm hover:bg-gray-50 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-white dark:hover:bg-gray-800"
+                    on:click=move |_| scheduled.refetch()
+                >
+                    <IconRefresh attr:class="flex-shrink-0 size-4"/>
+                    Refresh
+                </button>
+            </div>
+
+            <Transition>
+                {move || match scheduled.get() {
+                    None => None,
+                    Some(Err(http::Error::Unauthorized)) => {
+                        use_navigate()("/login", Default::default());
+                        None
+                    }
+                    Some(Err(http::Error::NotFound)) => {
+                        Some(
+                            view! {
+                                <p class="mt-4 text-sm text-gray-500">
+                                    This server does not report the status of its scheduled tasks.
+                                </p>
+                            }
+                                .into_view(),
+                        )
+                    }
+                    Some(Err(err)) => {
+                        alert.set(Alert::from(err));
+                        None
+                    }
+                    Some(Ok(items)) if items.is_empty() => {
+                        Some(
+                            view! {
+                                <p class="mt-4 text-sm text-gray-500">
+                                    There are no scheduled tasks.
+                                </p>
+                            }
+                                .into_view(),
+                        )
+                    }
+                    Some(Ok(items)) => {
+                        Some(
+                            view! {
+                                <div class="mt-4 flex flex-col divide-y divide-gray-200 bg-white border shadow-sm rounded-xl dark:bg-slate-900 dark:border-gray-800 dark:divide-gray-800">
+                                    {items
+                                        .into_iter()
+                                        .map(|task| {
+                                            view! {
+                                                <ScheduledTask
+                                                    task=task
+                                                    on_run=run_task
+                                                    pending=trigger_pending
+                                                />
+                                            }
+                                        })
+                                        .collect_view()}
+                                </div>
+                            }
+                                .into_view(),
+                        )
+                    }
+                }}
+
+            </Transition>
+
+        </div>
+    }
+}
+
+#[component]
+fn ScheduledTask(
+    task: HousekeeperTask,
+    on_run: Callback<HousekeeperTask>,
+    #[prop(into)] pending: Signal<bool>,
+) -> impl IntoView {
+    let capability = use_capability(Capability::HousekeeperRun);
+    let status = task.status.clone().unwrap_or(TaskStatus {
+        state: TaskState::Completed,
+        ..Default::default()
+    });
+    let is_running = status.state == TaskState::Running;
+    let progress = match (status.completed, status.total) {
+        (Some(completed), Some(total)) if total > 0 => Some((completed.min(total) * 100) / total),
+        _ => None,
+    };
+    let badge = match status.state {
+        TaskState::Running => view! { <Badge color=Color::Blue>Running</Badge> },
+        TaskState::Failed => view! { <Badge color=Color::Red>Failed</Badge> },
+        TaskState::Completed => view! { <Badge color=Color::Green>Idle</Badge> },
+    };
+    let last_run = task
+        .last_run
+        .map(|date| date.format_date_time())
+        .unwrap_or_else(|| "Never".to_string());
+    let next_run = task.next_run.map(|date| {
+        if date > Utc::now() {
+            format!("due {}", HumanTime::from(date))
+        } else {
+            "overdue".to_string()
+        }
+    });
+    let task_ = task.clone();
+
+    view! {
+        <div class="p-4 flex items-start gap-x-4">
+            <IconClock attr:class="mt-1 flex-shrink-0 size-5 text-gray-800 dark:text-gray-200"/>
+            <div class="grow">
+                <div class="flex items-center gap-x-2">
+                    <h3 class="font-semibold text-gray-800 dark:text-gray-200">{task.name}</h3>
+                    {badge}
+                </div>
+                {task
+                    .description
+                    .map(|description| {
+                        view! { <p class="text-sm text-gray-500">{description}</p> }
+                    })}
+                <p class="mt-1 text-xs text-gray-500">
+                    {format!("Last run: {last_run}")}
+                    {next_run.map(|next_run| format!(" · Next run {next_run}"))}
+                </p>
+                {status
+                    .message
+                    .map(|message| {
+                        view! { <p class="mt-1 text-xs text-gray-500">{message}</p> }
+                    })}
+                {progress
+                    .map(|progress| {
+                        view! {
+                            <div class="mt-2 flex w-full h-1.5 bg-gray-200 rounded-full overflow-hidden dark:bg-gray-700">
+                                <div
+                                    class="flex flex-col justify-center rounded-full overflow-hidden bg-blue-600 transition duration-500"
+                                    style=format!("width: {progress}%")
+                                ></div>
+                            </div>
+                        }
+                    })}
+
+            </div>
+            <button
+                type="button"
+                class="py-1.5 px-2.5 inline-flex items-center gap-x-1.5 text-sm font-medium rounded-lg border border-gray-200 bg-white text-gray-800 shadow-sm hover:bg-gray-50 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-white dark:hover:bg-gray-800"
+                disabled=move || capability.is_denied() || is_running || pending.get()
+                title=move || capability.reason()
+                class=(["disabled:pointer-events-auto", "disabled:cursor-not-allowed"], move || capability.is_denied())
+                on:click=move |_| on_run.call(task_.clone())
+            >
+                <IconPlay attr:class="flex-shrink-0 size-4"/>
+                Run now
+            </button>
         </div>
     }
 }
@@ -4,6 +4,22 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
+pub mod api_explorer;
+pub mod audit;
+pub mod cluster;
+pub mod dashboard;
+pub mod directory_check;
+pub mod live;
 pub mod logs;
 pub mod maintenance;
+pub mod quarantine;
+pub mod restart;
+pub mod security;
+pub mod self_test;
 pub mod spam;
+pub mod spam_explain;
+pub mod spans;
+pub mod store;
+pub mod tracking;
+pub mod troubleshoot;
+pub mod update;
@@ -0,0 +1,620 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use chrono::{DateTime, Utc};
+use humansize::{format_size, DECIMAL};
+use leptos::*;
+use leptos_router::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    components::{
+        icon::IconRefresh,
+        list::{
+            header::ColumnList,
+            pagination::Pagination,
+            toolbar::{SearchBox, ToolbarButton},
+            Footer, ListItem, ListSection, ListTable, ListTextItem, Toolbar, ZeroResults,
+        },
+        messages::{
+            alert::{use_alerts, Alert},
+            modal::{use_modals, Modal},
+        },
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest, ManagementApiError},
+        jmap::{account_id, jmap_download, jmap_request},
+        oauth::{use_authorization, AuthToken},
+        permissions::{use_capability, Capability},
+        url::UrlBuilder,
+    },
+    pages::{directory::Principal, manage::spam::Response, FormatDateTime},
+};
+
+const PAGE_SIZE: u32 = 25;
+const MAX_BODY_BYTES: u32 = 256 * 1024;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct Quarantine {
+    account_id: String,
+    inbox_id: Option<String>,
+    total: u64,
+    emails: Vec<QuarantinedEmail>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct QuarantinedEmail {
+    id: String,
+    blob_id: String,
+    from: String,
+    subject: String,
+    received_at: Option<DateTime<Utc>>,
+    size: u64,
+    preview: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum QuarantineAction {
+    Release,
+    TrainHam,
+    Delete,
+}
+
+/// Lists the messages in an account's junk mailbox. Messages are accessed
+/// over JMAP on behalf of the account, which requires the impersonate
+/// permission.
+#[component]
+pub fn SpamQuarantine() -> impl IntoView {
+    let query = use_query_map();
+    let page = create_memo(move |_| {
+        query
+            .with(|q| q.get("page").and_then(|page| page.parse::<u32>().ok()))
+            .filter(|&page| page > 0)
+            .unwrap_or(1)
+    });
+    let account = create_memo(move |_| {
+        query.with(|q| {
+            q.get("account").and_then(|s| {
+                let s = s.trim();
+                if !s.is_empty() {
+                    Some(s.to_string())
+                } else {
+                    None
+                }
+            })
+        })
+    });
+
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let modal = use_modals();
+    let quarantine = create_resource(
+        move || (account.get(), page.get()),
+        move |(account, page)| {
+            let auth = auth.get_untracked();
+
+            async move {
+                match account {
+                    Some(account) => fetch_quarantine(&auth, &account, page).await.map(Some),
+                    None => Ok(None),
+                }
+            }
+        },
+    );
+
+    let action = create_action(
+        move |(action, email): &(QuarantineAction, QuarantinedEmail)| {
+            let auth = auth.get();
+            let action = *action;
+            let email = email.clone();
+            let current = untrack(move || quarantine.get())
+                .and_then(|result| result.ok())
+                .flatten();
+
+            async move {
+                let Some(current) = current else {
+                    return;
+                };
+                match apply_action(&auth, &current, action, &email).await {
+                    Ok(_) => {
+                        alert.set(Alert::success(match action {
+                            QuarantineAction::Release => "Message released to the inbox",
+                            QuarantineAction::TrainHam => "Message trained as ham",
+                            QuarantineAction::Delete => "Message deleted",
+                        }));
+                        if action != QuarantineAction::TrainHam {
+                            quarantine.refetch();
+                        }
+                    }
+                    Err(http::Error::Unauthorized) => {
+                        use_navigate()("/login", Default::default());
+                    }
+                    Err(err) => {
+                        alert.set(Alert::from(err));
+                    }
+                }
+            }
+        },
+    );
+
+    let total_results = create_rw_signal(None::<u32>);
+    let on_action = Callback::new(move |(kind, email): (QuarantineAction, QuarantinedEmail)| {
+        if kind == QuarantineAction::Delete {
+            modal.set(
+                Modal::with_title("Confirm deletion")
+                    .with_message(format!(
+                        "Are you sure you want to delete the message \"{}\"? This action cannot be undone.",
+                        email.subject
+                    ))
+                    .with_button("Delete message")
+                    .with_dangerous_callback(move || {
+                        action.dispatch((QuarantineAction::Delete, email.clone()));
+                    }),
+            );
+        } else {
+            action.dispatch((kind, email));
+        }
+    });
+
+    view! {
+        <ListSection>
+            <ListTable
+                title="Spam quarantine"
+                subtitle="Messages classified as spam in an account's junk mailbox"
+            >
+                <Toolbar slot>
+                    <SearchBox
+                        value=account
+                        on_search=move |value| {
+                            use_navigate()(
+                                &UrlBuilder::new("/manage/spam/quarantine")
+                                    .with_parameter("account", value)
+                                    .finish(),
+                                Default::default(),
+                            );
+                        }
+                    />
+
+                    <ToolbarButton
+                        text="Refresh"
+                        color=Color::Gray
+                        on_click=move |_| {
+                            quarantine.refetch();
+                        }
+                    >
+
+                        <IconRefresh/>
+                    </ToolbarButton>
+
+                </Toolbar>
+
+                <Transition fallback=Skeleton>
+                    {move || match quarantine.get() {
+                        None => None,
+                        Some(Err(http::Error::Unauthorized)) => {
+                            use_navigate()("/login", Default::default());
+                            Some(view! { <div></div> }.into_view())
+                        }
+                        Some(Err(http::Error::NotFound)) => {
+                            total_results.set(Some(0));
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="Account not found"
+                                        subtitle="No account with this name exists."
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Err(err)) => {
+                            total_results.set(Some(0));
+                            alert.set(Alert::from(err));
+                            Some(view! { <Skeleton/> }.into_view())
+                        }
+                        Some(Ok(None)) => {
+                            total_results.set(Some(0));
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="No account selected"
+                                        subtitle="Enter an account name to browse its quarantined messages."
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Ok(Some(quarantine))) if !quarantine.emails.is_empty() => {
+                            total_results.set(Some(quarantine.total as u32));
+                            let account_id = quarantine.account_id.clone();
+                            let can_release = quarantine.inbox_id.is_some();
+                            Some(
+                                view! {
+                                    <ColumnList headers=vec![
+                                        "Received".to_string(),
+                                        "From".to_string(),
+                                        "Subject".to_string(),
+                                        "Size".to_string(),
+                                        "".to_string(),
+                                    ]>
+
+                                        <For
+                                            each=move || quarantine.emails.clone()
+                                            key=|email| email.id.clone()
+                                            let:email
+                                        >
+                                            <QuarantinedEmailItem
+                                                email
+                                                account_id=account_id.clone()
+                                                can_release
+                                                on_action
+                                                pending=action.pending()
+                                            />
+                                        </For>
+
+                                    </ColumnList>
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Ok(Some(_))) => {
+                            total_results.set(Some(0));
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="Quarantine is empty"
+                                        subtitle="This account has no messages in its junk mailbox."
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                    }}
+
+                </Transition>
+
+                <Footer slot>
+
+                    <Pagination
+                        current_page=page
+                        total_results=total_results.read_only()
+                        page_size=PAGE_SIZE
+                        on_page_change=move |page: u32| {
+                            use_navigate()(
+                                &UrlBuilder::new("/manage/spam/quarantine")
+                                    .with_parameter("page", page.to_string())
+                                    .with_optional_parameter("account", account.get())
+                                    .finish(),
+                                Default::default(),
+                            );
+                        }
+                    />
+
+                </Footer>
+            </ListTable>
+        </ListSection>
+    }
+}
+
+#[component]
+fn QuarantinedEmailItem(
+    email: QuarantinedEmail,
+    account_id: String,
+    can_release: bool,
+    on_action: Callback<(QuarantineAction, QuarantinedEmail), ()>,
+    pending: ReadSignal<bool>,
+) -> impl IntoView {
+    let update_capability = use_capability(Capability::QuarantineUpdate);
+    let train_capability = use_capability(Capability::SpamFilterTrain);
+    let auth = use_authorization();
+    let show_preview = create_rw_signal(false);
+    let email_id = email.id.clone();
+    let body = create_resource(
+        move || show_preview.get(),
+        move |show| {
+            let auth = auth.get_untracked();
+            let account_id = account_id.clone();
+            let email_id = email_id.clone();
+
+            async move {
+                if show {
+                    fetch_body(&auth, &account_id, &email_id).await.map(Some)
+                } else {
+                    Ok(None)
+                }
+            }
+        },
+    );
+    let email = store_value(email);
+    let link_class = "text-sm text-blue-600 decoration-2 hover:underline font-medium disabled:opacity-50 disabled:pointer-events-none dark:text-blue-500";
+
+    view! {
+        <tr>
+            <ListItem>
+                <span class="text-sm text-gray-500">
+                    {email
+                        .with_value(|email| email.received_at.map(|date| date.format_date_time()))
+                        .unwrap_or_default()}
+                </span>
+            </ListItem>
+            <ListTextItem>{email.with_value(|email| email.from.clone())}</ListTextItem>
+            <ListItem>
+                <div class="flex flex-col">
+                    <span class="text-sm font-semibold text-gray-800 dark:text-gray-200">
+                        {email.with_value(|email| email.subject.clone())}
+                    </span>
+                    <span class="text-xs text-gray-500 text-wrap">
+                        {email.with_value(|email| email.preview.clone())}
+                    </span>
+                </div>
+            </ListItem>
+            <ListTextItem>{email.with_value(|email| format_size(email.size, DECIMAL))}</ListTextItem>
+            <ListItem subclass="px-6 py-1.5">
+                <div class="flex gap-x-3">
+                    <button
+                        type="button"
+                        class=link_class
+                        on:click=move |_| show_preview.update(|show| *show = !*show)
+                    >
+                        {move || if show_preview.get() { "Hide" } else { "Preview" }}
+                    </button>
+                    <button
+                        type="button"
+                        class=link_class
+                        disabled=move || update_capability.is_denied() || pending.get() || !can_release
+                        title=move || update_capability.reason()
+                        class=(["disabled:pointer-events-auto", "disabled:cursor-not-allowed"], move || update_capability.is_denied())
+                        on:click=move |_| {
+                            on_action.call((QuarantineAction::Release, email.get_value()))
+                        }
+                    >
+
+                        Release
+                    </button>
+                    <button
+                        type="button"
+                        class=link_class
+                        disabled=move || train_capability.is_denied() || pending.get()
+                        title=move || train_capability.reason()
+                        class=(["disabled:pointer-events-auto", "disabled:cursor-not-allowed"], move || train_capability.is_denied())
+                        on:click=move |_| {
+                            on_action.call((QuarantineAction::TrainHam, email.get_value()))
+                        }
+                    >
+
+                        Train as ham
+                    </button>
+                    <button
+                        type="button"
+                        class="text-sm text-red-600 decoration-2 hover:underline font-medium disabled:opacity-50 disabled:pointer-events-none dark:text-red-500"
+                        disabled=move || update_capability.is_denied() || pending.get()
+                        title=move || update_capability.reason()
+                        class=(["disabled:pointer-events-auto", "disabled:cursor-not-allowed"], move || update_capability.is_denied())
+                        on:click=move |_| {
+                            on_action.call((QuarantineAction::Delete, email.get_value()))
+                        }
+                    >
+
+                        Delete
+                    </button>
+                </div>
+            </ListItem>
+        </tr>
+        <Show when=move || show_preview.get()>
+            <tr>
+                <td colspan="5" class="px-6 py-3 bg-gray-50 dark:bg-slate-800">
+                    {move || match body.get() {
+                        Some(Ok(Some(body))) => {
+                            view! {
+                                <pre class="max-h-96 overflow-auto text-xs text-gray-800 whitespace-pre-wrap dark:text-gray-200">
+                                    {body}
+                                </pre>
+                            }
+                                .into_view()
+                        }
+                        Some(Err(err)) => {
+                            view! {
+                                <p class="text-sm text-red-600">{format!("Failed to load message: {err:?}")}</p>
+                            }
+                                .into_view()
+                        }
+                        _ => view! { <p class="text-sm text-gray-500">Loading...</p> }.into_view(),
+                    }}
+
+                </td>
+            </tr>
+        </Show>
+    }
+}
+
+async fn fetch_quarantine(auth: &AuthToken, account: &str, page: u32) -> http::Result<Quarantine> {
+    let account_id = HttpRequest::get(("/api/principal", account))
+        .with_authorization(auth)
+        .send::<Principal>()
+        .await?
+        .id
+        .map(account_id)
+        .ok_or(http::Error::NotFound)?;
+
+    let mailboxes = jmap_request(
+        auth,
+        ["junk", "inbox"]
+            .into_iter()
+            .map(|role| {
+                (
+                    "Mailbox/query",
+                    json!({"accountId": account_id, "filter": {"role": role}}),
+                )
+            })
+            .collect(),
+    )
+    .await?;
+    let first_id = |response: &Value| response["ids"][0].as_str().map(|id| id.to_string());
+    let (junk_id, inbox_id) = (first_id(&mailboxes[0]), first_id(&mailboxes[1]));
+    let mut quarantine = Quarantine {
+        account_id,
+        inbox_id,
+        total: 0,
+        emails: Vec::new(),
+    };
+    let Some(junk_id) = junk_id else {
+        return Ok(quarantine);
+    };
+
+    let emails = jmap_request(
+        auth,
+        vec![
+            (
+                "Email/query",
+                json!({
+                    "accountId": quarantine.account_id,
+                    "filter": {"inMailbox": junk_id},
+                    "sort": [{"property": "receivedAt", "isAscending": false}],
+                    "position": (page - 1) * PAGE_SIZE,
+                    "limit": PAGE_SIZE,
+                    "calculateTotal": true,
+                }),
+            ),
+            (
+                "Email/get",
+                json!({
+                    "accountId": quarantine.account_id,
+                    "#ids": {"resultOf": "0", "name": "Email/query", "path": "/ids"},
+                    "properties": ["id", "blobId", "from", "subject", "receivedAt", "size", "preview"],
+                }),
+            ),
+        ],
+    )
+    .await?;
+    quarantine.total = emails[0]["total"].as_u64().unwrap_or_default();
+    quarantine.emails = emails[1]["list"]
+        .as_array()
+        .map(|list| list.iter().map(QuarantinedEmail::from).collect())
+        .unwrap_or_default();
+
+    Ok(quarantine)
+}
+
+async fn fetch_body(auth: &AuthToken, account_id: &str, email_id: &str) -> http::Result<String> {
+    let response = jmap_request(
+        auth,
+        vec![(
+            "Email/get",
+            json!({
+                "accountId": account_id,
+                "ids": [email_id],
+                "properties": ["textBody", "bodyValues"],
+                "fetchTextBodyValues": true,
+                "maxBodyValueBytes": MAX_BODY_BYTES,
+            }),
+        )],
+    )
+    .await?;
+    let email = &response[0]["list"][0];
+
+    Ok(email["textBody"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|part| part["partId"].as_str())
+        .filter_map(|part_id| email["bodyValues"][part_id]["value"].as_str())
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+async fn apply_action(
+    auth: &AuthToken,
+    quarantine: &Quarantine,
+    action: QuarantineAction,
+    email: &QuarantinedEmail,
+) -> http::Result<()> {
+    let account_id = quarantine.account_id.as_str();
+    let arguments = match action {
+        QuarantineAction::Release => {
+            let inbox_id = quarantine.inbox_id.as_deref().ok_or_else(|| {
+                http::Error::Server(ManagementApiError::Other {
+                    details: "This account has no inbox".to_string(),
+                })
+            })?;
+            json!({
+                "accountId": account_id,
+                "update": {
+                    email.id.as_str(): {
+                        "mailboxIds": {inbox_id: true},
+                        "keywords/$junk": null,
+                        "keywords/$notjunk": true,
+                    }
+                },
+            })
+        }
+        QuarantineAction::Delete => json!({"accountId": account_id, "destroy": [email.id]}),
+        QuarantineAction::TrainHam => {
+            let message = jmap_download(auth, account_id, &email.blob_id).await?;
+            return match HttpRequest::post("/api/sieve/train")
+                .with_authorization(auth)
+                .with_parameter("train", "ham")
+                .with_raw_body(String::from_utf8_lossy(&message).into_owned())
+                .send::<Response>()
+                .await?
+            {
+                Response::Reject { reason } => {
+                    Err(http::Error::Server(ManagementApiError::Other {
+                        details: reason,
+                    }))
+                }
+                _ => Ok(()),
+            };
+        }
+    };
+
+    let response = jmap_request(auth, vec![("Email/set", arguments)]).await?;
+    match response[0]["notUpdated"]
+        .get(&email.id)
+        .or_else(|| response[0]["notDestroyed"].get(&email.id))
+    {
+        Some(error) => Err(http::Error::Server(ManagementApiError::Other {
+            details: error["description"]
+                .as_str()
+                .or_else(|| error["type"].as_str())
+                .unwrap_or("Update failed")
+                .to_string(),
+        })),
+        None => Ok(()),
+    }
+}
+
+impl From<&Value> for QuarantinedEmail {
+    fn from(value: &Value) -> Self {
+        let from = value["from"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(
+                |address| match (address["name"].as_str(), address["email"].as_str()) {
+                    (Some(name), Some(email)) if !name.is_empty() => format!("{name} <{email}>"),
+                    (_, email) => email.unwrap_or_default().to_string(),
+                },
+            )
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        QuarantinedEmail {
+            id: value["id"].as_str().unwrap_or_default().to_string(),
+            blob_id: value["blobId"].as_str().unwrap_or_default().to_string(),
+            from,
+            subject: value["subject"].as_str().unwrap_or_default().to_string(),
+            received_at: value["receivedAt"]
+                .as_str()
+                .and_then(|date| DateTime::parse_from_rfc3339(date).ok())
+                .map(|date| date.to_utc()),
+            size: value["size"].as_u64().unwrap_or_default(),
+            preview: value["preview"].as_str().unwrap_or_default().to_string(),
+        }
+    }
+}
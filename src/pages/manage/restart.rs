@@ -0,0 +1,338 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use leptos::*;
+use leptos_router::use_navigate;
+
+use crate::{
+    components::{
+        icon::IconPower,
+        messages::alert::{use_alerts, Alert},
+    },
+    core::{
+        http::{self, HttpRequest},
+        maintenance::{use_maintenance, MaintenanceMode},
+        oauth::use_authorization,
+        permissions::{use_capability, Capability},
+    },
+    pages::{
+        manage::dashboard::{metrics_request, MetricsHistory},
+        maybe_plural,
+    },
+};
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Longest time to wait for activity to wind down before restarting anyway.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Gauges reporting the activity that a restart would interrupt.
+const ACTIVITY_METRICS: &[(&str, &str)] = &[
+    ("imap.active-connections", "IMAP sessions"),
+    ("smtp.active-connections", "SMTP sessions"),
+    ("http.active-connections", "HTTP sessions"),
+    ("delivery.active-connections", "In-flight deliveries"),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Drain {
+    started: DateTime<Utc>,
+    initial: u64,
+    enabled_maintenance: bool,
+}
+
+/// Confirmation shown before restarting the server, listing the sessions and
+/// deliveries a restart would interrupt. Draining places the server in
+/// maintenance mode and waits for that activity to wind down before the
+/// restart is requested.
+#[component]
+pub fn RestartPanel(#[prop(into)] on_close: Callback<()>) -> impl IntoView {
+    let capability = use_capability(Capability::Restart);
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let maintenance = use_maintenance();
+    let drain = create_rw_signal(None::<Drain>);
+
+    let activity = create_resource(
+        move || (),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move {
+                metrics_request(&auth, ACTIVITY_METRICS.iter().map(|(id, _)| *id), 1)
+                    .send::<MetricsHistory>()
+                    .await
+                    .map(|history| current_activity(&history))
+            }
+        },
+    );
+
+    if let Ok(handle) = set_interval_with_handle(move || activity.refetch(), REFRESH_INTERVAL) {
+        on_cleanup(move || handle.clear());
+    }
+
+    let total = create_memo(move |_| match activity.get() {
+        Some(Ok(counts)) => Some(counts.iter().sum::<u64>()),
+        _ => None,
+    });
+
+    let restart = create_action(move |_: &()| {
+        let auth = auth.get();
+
+        async move {
+            match HttpRequest::get("/api/restart")
+                .with_authorization(&auth)
+                .send::<Option<String>>()
+                .await
+            {
+                Ok(_) => {
+                    alert.set(
+                        Alert::success(
+                            "Restarting server, try reloading this page in a few seconds.",
+                        )
+                        .without_timeout(),
+                    );
+                    on_close.call(());
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    let start_drain = create_action(move |_: &()| {
+        let auth = auth.get();
+        let is_maintenance = maintenance.get_untracked().is_some_and(|mode| mode.enabled);
+        let initial = total.get_untracked().unwrap_or_default();
+
+        async move {
+            if !is_maintenance {
+                let mode = MaintenanceMode {
+                    enabled: true,
+                    reject_submissions: true,
+                    since: None,
+                };
+                match mode.apply(&auth).await {
+                    Ok(mode) => {
+                        maintenance.set(Some(mode));
+                    }
+                    Err(http::Error::Unauthorized) => {
+                        use_navigate()("/login", Default::default());
+                        return;
+                    }
+                    Err(err) => {
+                        alert.set(Alert::from(err));
+                        return;
+                    }
+                }
+            }
+            drain.set(Some(Drain {
+                started: Utc::now(),
+                initial,
+                enabled_maintenance: !is_maintenance,
+            }));
+            activity.refetch();
+        }
+    });
+
+    let cancel = create_action(move |_: &()| {
+        let auth = auth.get();
+        let state = drain.get_untracked();
+
+        async move {
+            drain.set(None);
+            // Only lift maintenance mode if it was enabled by this drain
+            if state.is_some_and(|state| state.enabled_maintenance) {
+                match MaintenanceMode::default().apply(&auth).await {
+                    Ok(mode) => {
+                        maintenance.set(Some(mode));
+                    }
+                    Err(http::Error::Unauthorized) => {
+                        use_navigate()("/login", Default::default());
+                        return;
+                    }
+                    Err(err) => {
+                        alert.set(Alert::from(err));
+                    }
+                }
+            }
+            on_close.call(());
+        }
+    });
+
+    // Restart once all activity has stopped or the drain has timed out
+    create_effect(move |_| {
+        let Some(state) = drain.get() else {
+            return;
+        };
+        let Some(Ok(counts)) = activity.get() else {
+            return;
+        };
+        let timed_out = (Utc::now() - state.started)
+            .to_std()
+            .is_ok_and(|elapsed| elapsed >= DRAIN_TIMEOUT);
+        if counts.iter().all(|count| *count == 0) || timed_out {
+            drain.set(None);
+            restart.dispatch(());
+        }
+    });
+
+    let is_busy = Signal::derive(move || {
+        restart.pending().get()
+            || start_drain.pending().get()
+            || cancel.pending().get()
+            || drain.with(|drain| drain.is_some())
+    });
+
+    view! {
+        <div class="mt-6 p-4 md:p-5 bg-white border shadow-sm rounded-xl dark:bg-slate-900 dark:border-gray-800">
+            <div class="flex items-start gap-x-4">
+                <IconPower attr:class="mt-1 flex-shrink-0 size-5 text-gray-800 dark:text-gray-200"/>
+                <div class="grow">
+                    <h3 class="font-semibold text-gray-800 dark:text-gray-200">Restart server</h3>
+                    {move || match activity.get() {
+                        None => {
+                            view! {
+                                <p class="text-sm text-gray-500">
+                                    Checking for active sessions...
+                                </p>
+                            }
+                                .into_view()
+                        }
+                        Some(Err(http::Error::Unauthorized)) => {
+                            use_navigate()("/login", Default::default());
+                            ().into_view()
+                        }
+                        Some(Err(http::Error::NotFound)) => {
+                            view! {
+                                <p class="text-sm text-gray-500">
+                                    This server does not report its active sessions, any open connections will be interrupted.
+                                </p>
+                            }
+                                .into_view()
+                        }
+                        Some(Err(err)) => {
+                            alert.set(Alert::from(err));
+                            ().into_view()
+                        }
+                        Some(Ok(counts)) => {
+                            view! {
+                                <p class="text-sm text-gray-500">
+                                    The following activity will be interrupted by a restart.
+                                </p>
+                                <div class="mt-3 grid grid-cols-2 sm:grid-cols-4 gap-3">
+                                    {ACTIVITY_METRICS
+                                        .iter()
+                                        .zip(counts)
+                                        .map(|((_, label), count)| {
+                                            view! {
+                                                <div class="p-3 border border-gray-200 rounded-lg dark:border-gray-700">
+                                                    <p class="text-xs uppercase tracking-wide text-gray-500">
+                                                        {*label}
+                                                    </p>
+                                                    <p class="mt-1 text-xl font-semibold text-gray-800 dark:text-gray-200">
+                                                        {count}
+                                                    </p>
+                                                </div>
+                                            }
+                                        })
+                                        .collect_view()}
+                                </div>
+                            }
+                                .into_view()
+                        }
+                    }}
+
+                    {move || {
+                        drain
+                            .get()
+                            .map(|state| {
+                                let remaining = total.get().unwrap_or_default();
+                                let progress = (state.initial.saturating_sub(remaining) * 100)
+                                    .checked_div(state.initial)
+                                    .unwrap_or(100);
+                                view! {
+                                    <p class="mt-4 text-sm text-gray-500">
+                                        {format!(
+                                            "Draining, waiting for {} to finish. The server will restart in at most {} minutes.",
+                                            maybe_plural(remaining as usize, "active session", "active sessions"),
+                                            DRAIN_TIMEOUT.as_secs() / 60,
+                                        )}
+
+                                    </p>
+                                    <div class="mt-2 flex w-full h-1.5 bg-gray-200 rounded-full overflow-hidden dark:bg-gray-700">
+                                        <div
+                                            class="flex flex-col justify-center rounded-full overflow-hidden bg-blue-600 transition duration-500"
+                                            style=format!("width: {progress}%")
+                                        ></div>
+                                    </div>
+                                }
+                            })
+                    }}
+
+                </div>
+            </div>
+            <div class="mt-4 flex justify-end gap-x-2">
+                <button
+                    type="button"
+                    class="py-1.5 px-2.5 inline-flex items-center gap-x-1.5 text-sm font-medium rounded-lg border border-gray-200 bg-white text-gray-800 shadow-sm hover:bg-gray-50 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-white dark:hover:bg-gray-800"
+                    disabled=move || capability.is_denied() || restart.pending().get() || cancel.pending().get()
+                    title=move || capability.reason()
+                    class=(["disabled:pointer-events-auto", "disabled:cursor-not-allowed"], move || capability.is_denied())
+                    on:click=move |_| cancel.dispatch(())
+                >
+                    Cancel
+                </button>
+                <button
+                    type="button"
+                    class="py-1.5 px-2.5 inline-flex items-center gap-x-1.5 text-sm font-medium rounded-lg border border-gray-200 bg-white text-gray-800 shadow-sm hover:bg-gray-50 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-white dark:hover:bg-gray-800"
+                    disabled=move || capability.is_denied() || is_busy.get() || total.get().is_none()
+                    title=move || capability.reason()
+                    class=(["disabled:pointer-events-auto", "disabled:cursor-not-allowed"], move || capability.is_denied())
+                    on:click=move |_| start_drain.dispatch(())
+                >
+                    Drain and restart
+                </button>
+                <button
+                    type="button"
+                    class="py-1.5 px-2.5 inline-flex items-center gap-x-1.5 text-sm font-semibold rounded-lg border border-transparent bg-red-600 text-white hover:bg-red-700 disabled:opacity-50 disabled:pointer-events-none"
+                    disabled=move || capability.is_denied() || restart.pending().get()
+                    title=move || capability.reason()
+                    class=(["disabled:pointer-events-auto", "disabled:cursor-not-allowed"], move || capability.is_denied())
+                    on:click=move |_| {
+                        drain.set(None);
+                        restart.dispatch(());
+                    }
+                >
+
+                    Restart now
+                </button>
+            </div>
+        </div>
+    }
+}
+
+/// Returns the latest value of each activity gauge, added up across nodes.
+fn current_activity(history: &MetricsHistory) -> Vec<u64> {
+    ACTIVITY_METRICS
+        .iter()
+        .map(|(id, _)| {
+            history
+                .metrics
+                .iter()
+                .filter(|entry| entry.id == *id)
+                .filter_map(|entry| entry.values.last())
+                .sum::<f64>() as u64
+        })
+        .collect()
+}
@@ -0,0 +1,768 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use ahash::AHashMap;
+use chrono::{DateTime, Utc};
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        chart::{bar::BarChart, line::LineChart, ChartLegend, LazyChart, Series},
+        messages::{
+            alert::{use_alerts, Alert},
+            modal::{use_modals, Modal},
+        },
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::{use_authorization, AuthToken},
+        permissions::{use_capability, Capability},
+    },
+    pages::{
+        config::{edit::FetchSettings, ReloadSettings, UpdateSettings},
+        manage::dashboard::{format_labels, metrics_request, MetricsHistory},
+        maybe_plural, FormatDateTime,
+    },
+};
+
+const METRIC_FAILED: &str = "auth.failed";
+const METRIC_BANNED: &str = "security.authentication-ban";
+const BLOCKED_IP_PREFIX: &str = "server.blocked-ip";
+const TOP_OFFENDERS: usize = 10;
+const CHART_SERIES: [Color; 4] = [Color::Red, Color::Yellow, Color::Blue, Color::Green];
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct FailureSource {
+    ip: String,
+    count: u64,
+    #[serde(default)]
+    asn: Option<u32>,
+    #[serde(default)]
+    as_name: Option<String>,
+    #[serde(default)]
+    country: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Offender {
+    name: String,
+    detail: Option<String>,
+    count: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+struct SmtpFailureHistory {
+    #[serde(default)]
+    timestamps: Vec<DateTime<Utc>>,
+    #[serde(default)]
+    sources: Vec<SmtpFailureSource>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct SmtpFailureSource {
+    #[serde(default)]
+    asn: Option<u32>,
+    #[serde(default)]
+    as_name: Option<String>,
+    #[serde(default)]
+    country: Option<String>,
+    #[serde(default)]
+    networks: Vec<String>,
+    #[serde(default)]
+    values: Vec<f64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FailureGrouping {
+    Network,
+    Country,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct FailureGroup {
+    name: String,
+    detail: Option<String>,
+    networks: Vec<String>,
+    values: Vec<f64>,
+    total: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct Ban {
+    ip: String,
+    banned_at: Option<DateTime<Utc>>,
+}
+
+/// Authentication failures, the sources behind them and the addresses that
+/// were automatically banned as a result.
+#[component]
+pub fn SecurityPanel(#[prop(into)] period: Signal<u32>) -> impl IntoView {
+    let capability = use_capability(Capability::SettingsApply);
+    let auth = use_authorization();
+    let alert = use_alerts();
+
+    let history = create_resource(
+        move || period.get(),
+        move |period| {
+            let auth = auth.get_untracked();
+
+            async move {
+                metrics_request(&auth, [METRIC_FAILED, METRIC_BANNED], period)
+                    .send::<MetricsHistory>()
+                    .await
+                    .map(|history| (format_labels(&history.timestamps, period), history))
+            }
+        },
+    );
+
+    let sources = create_resource(
+        move || period.get(),
+        move |period| {
+            let auth = auth.get_untracked();
+            let after = Utc::now() - chrono::Duration::hours(period as i64);
+
+            async move {
+                match HttpRequest::get("/api/telemetry/security/failures")
+                    .with_authorization(&auth)
+                    .with_parameter("after", after.to_rfc3339())
+                    .send::<Vec<FailureSource>>()
+                    .await
+                {
+                    Err(http::Error::NotFound) => Ok(Vec::new()),
+                    result => result,
+                }
+            }
+        },
+    );
+
+    let bans = create_resource(
+        move || (),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move {
+                HttpRequest::get("/api/settings/list")
+                    .with_authorization(&auth)
+                    .with_parameter("prefix", BLOCKED_IP_PREFIX)
+                    .send::<FetchSettings>()
+                    .await
+                    .map(|list| {
+                        let mut bans = list
+                            .items
+                            .into_iter()
+                            .map(|(ip, value)| Ban {
+                                ip,
+                                banned_at: DateTime::parse_from_rfc3339(&value)
+                                    .ok()
+                                    .map(|date| date.to_utc()),
+                            })
+                            .collect::<Vec<_>>();
+                        bans.sort_by(|a, b| b.banned_at.cmp(&a.banned_at).then(a.ip.cmp(&b.ip)));
+                        bans
+                    })
+            }
+        },
+    );
+
+    let unban_action = create_action(move |ip: &String| {
+        let auth = auth.get();
+        let ip = ip.clone();
+
+        async move {
+            let result = update_blocked_ips(
+                &auth,
+                UpdateSettings::Delete {
+                    keys: vec![format!("{BLOCKED_IP_PREFIX}.{ip}")],
+                },
+            )
+            .await;
+
+            match result {
+                Ok(reload) if reload.errors.is_empty() => {
+                    bans.refetch();
+                    alert.set(Alert::success(format!("{ip} has been unbanned")));
+                }
+                Ok(reload) => {
+                    bans.refetch();
+                    alert.set(Alert::from(reload));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    let block_action = create_action(move |(name, networks): &(String, Vec<String>)| {
+        let auth = auth.get();
+        let name = name.clone();
+        let banned_at = Utc::now().to_rfc3339();
+        let values = networks
+            .iter()
+            .map(|network| (format!("{BLOCKED_IP_PREFIX}.{network}"), banned_at.clone()))
+            .collect::<Vec<_>>();
+
+        async move {
+            let result = update_blocked_ips(
+                &auth,
+                UpdateSettings::Insert {
+                    prefix: None,
+                    values,
+                    assert_empty: false,
+                },
+            )
+            .await;
+
+            match result {
+                Ok(reload) if reload.errors.is_empty() => {
+                    bans.refetch();
+                    alert.set(Alert::success(format!("{name} has been blocked")));
+                }
+                Ok(reload) => {
+                    bans.refetch();
+                    alert.set(Alert::from(reload));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    let offenders = create_memo(move |_| {
+        let sources = sources
+            .get()
+            .and_then(|sources| sources.ok())
+            .unwrap_or_default();
+        let ips = top_offenders(
+            sources
+                .iter()
+                .map(|source| (source.ip.clone(), source.country.clone(), source.count)),
+        );
+        let networks = top_offenders(sources.iter().filter_map(|source| {
+            source
+                .asn
+                .map(|asn| (format!("AS{asn}"), source.as_name.clone(), source.count))
+        }));
+        let countries = top_offenders(sources.iter().filter_map(|source| {
+            source
+                .country
+                .clone()
+                .map(|country| (country, None, source.count))
+        }));
+        (ips, networks, countries)
+    });
+
+    view! {
+        <div class="mt-10 mb-6">
+            <h2 class="text-xl font-semibold text-gray-800 dark:text-gray-200">Security</h2>
+            <p class="text-sm text-gray-600 dark:text-gray-400">
+                Failed authentication attempts and automatic bans
+            </p>
+        </div>
+        <div class="grid lg:grid-cols-2 gap-4 sm:gap-6">
+            <LazyChart
+                title="Authentication failures"
+                subtitle="Failed login attempts and the bans they triggered"
+            >
+                {move || {
+                    match history.get() {
+                        Some(Ok((labels, history))) => {
+                            let series = [
+                                (METRIC_FAILED, "Failed attempts", Color::Yellow),
+                                (METRIC_BANNED, "Bans", Color::Red),
+                            ]
+                                .into_iter()
+                                .map(|(id, name, color)| Series {
+                                    name: name.to_string(),
+                                    color,
+                                    values: sum_values(&history, id),
+                                })
+                                .collect::<Vec<_>>();
+                            view! {
+                                <LineChart labels=labels series=series.clone()/>
+                                <ChartLegend series=series/>
+                            }
+                                .into_view()
+                        }
+                        Some(Err(http::Error::Unauthorized)) => {
+                            use_navigate()("/login", Default::default());
+                            ().into_view()
+                        }
+                        Some(Err(http::Error::NotFound)) => {
+                            view! {
+                                <p class="text-sm text-gray-500">
+                                    Metrics collection is disabled.
+                                </p>
+                            }
+                                .into_view()
+                        }
+                        Some(Err(err)) => {
+                            alert.set(Alert::from(err));
+                            ().into_view()
+                        }
+                        None => ().into_view(),
+                    }
+                }}
+
+            </LazyChart>
+
+            <LazyChart
+                title="Current bans"
+                subtitle=Signal::derive(move || {
+                    bans.get()
+                        .and_then(|bans| bans.ok())
+                        .map(|bans| maybe_plural(bans.len(), "blocked address", "blocked addresses"))
+                        .unwrap_or_default()
+                })
+            >
+
+                {move || {
+                    match bans.get() {
+                        Some(Ok(bans)) if !bans.is_empty() => {
+                            view! {
+                                <ul class="max-h-72 overflow-y-auto divide-y divide-gray-200 dark:divide-gray-700">
+                                    {bans
+                                        .into_iter()
+                                        .map(|ban| {
+                                            let ip = ban.ip.clone();
+                                            view! {
+                                                <li class="flex items-center justify-between gap-x-3 py-2">
+                                                    <div class="flex flex-col">
+                                                        <span class="text-sm font-mono text-gray-800 dark:text-gray-200">
+                                                            {ban.ip}
+                                                        </span>
+                                                        <span class="text-xs text-gray-500">
+                                                            {ban
+                                                                .banned_at
+                                                                .map(|date| format!("Banned {}", date.format_date_time()))}
+                                                        </span>
+                                                    </div>
+                                                    <button
+                                                        type="button"
+                                                        class="text-sm text-blue-600 decoration-2 hover:underline font-medium disabled:opacity-50 disabled:pointer-events-none dark:text-blue-500"
+                                                        disabled=move || capability.is_denied() || unban_action.pending().get()
+                                                        title=move || capability.reason()
+                                                        class=(["disabled:pointer-events-auto", "disabled:cursor-not-allowed"], move || capability.is_denied())
+                                                        on:click=move |_| unban_action.dispatch(ip.clone())
+                                                    >
+                                                        Unban
+                                                    </button>
+                                                </li>
+                                            }
+                                        })
+                                        .collect_view()}
+                                </ul>
+                            }
+                                .into_view()
+                        }
+                        Some(Ok(_)) => {
+                            view! {
+                                <p class="text-sm text-gray-500">No addresses are currently banned.</p>
+                            }
+                                .into_view()
+                        }
+                        Some(Err(http::Error::Unauthorized)) => {
+                            use_navigate()("/login", Default::default());
+                            ().into_view()
+                        }
+                        Some(Err(err)) => {
+                            alert.set(Alert::from(err));
+                            ().into_view()
+                        }
+                        None => ().into_view(),
+                    }
+                }}
+
+            </LazyChart>
+
+            <SmtpFailureChart
+                period=period
+                on_block=Callback::new(move |group: FailureGroup| {
+                    block_action.dispatch((group.name, group.networks));
+                })
+            />
+
+            <OffenderList title="Top offending IPs" offenders=Signal::derive(move || offenders.get().0)/>
+            <OffenderList
+                title="Top offending networks"
+                offenders=Signal::derive(move || offenders.get().1)
+            />
+            <OffenderList
+                title="Top offending countries"
+                offenders=Signal::derive(move || offenders.get().2)
+            />
+        </div>
+    }
+}
+
+#[component]
+fn OffenderList(title: &'static str, offenders: Signal<Vec<Offender>>) -> impl IntoView {
+    view! {
+        <LazyChart title=title subtitle="Failed authentication attempts by source">
+            {move || {
+                let offenders = offenders.get();
+                let max = offenders.first().map_or(1, |offender| offender.count.max(1));
+                if offenders.is_empty() {
+                    view! {
+                        <p class="text-sm text-gray-500">
+                            No failed authentication attempts were recorded in this period.
+                        </p>
+                    }
+                        .into_view()
+                } else {
+                    view! {
+                        <ul class="space-y-2">
+                            {offenders
+                                .into_iter()
+                                .map(|offender| {
+                                    let width = (offender.count as f64 / max as f64 * 100.0).max(1.0);
+                                    view! {
+                                        <li>
+                                            <div class="flex justify-between text-sm">
+                                                <span class="font-mono text-gray-800 dark:text-gray-200">
+                                                    {offender.name}
+                                                    {offender
+                                                        .detail
+                                                        .map(|detail| {
+                                                            view! {
+                                                                <span class="ms-2 font-sans text-xs text-gray-500">
+                                                                    {detail}
+                                                                </span>
+                                                            }
+                                                        })}
+
+                                                </span>
+                                                <span class="text-gray-500">{offender.count}</span>
+                                            </div>
+                                            <div class="mt-1 h-1.5 w-full bg-gray-200 rounded-full dark:bg-gray-700">
+                                                <div
+                                                    class="h-1.5 bg-red-500 rounded-full"
+                                                    style=format!("width: {width:.1}%")
+                                                ></div>
+                                            </div>
+                                        </li>
+                                    }
+                                })
+                                .collect_view()}
+                        </ul>
+                    }
+                        .into_view()
+                }
+            }}
+
+        </LazyChart>
+    }
+}
+
+/// Failed or abusive SMTP sessions over time, grouped by the network or
+/// country reported by the ASN and GeoIP enrichment of the server.
+#[component]
+fn SmtpFailureChart(
+    #[prop(into)] period: Signal<u32>,
+    on_block: Callback<FailureGroup>,
+) -> impl IntoView {
+    let capability = use_capability(Capability::SettingsApply);
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let modal = use_modals();
+    let grouping = create_rw_signal(FailureGrouping::Network);
+
+    let history = create_resource(
+        move || period.get(),
+        move |period| {
+            let auth = auth.get_untracked();
+            let after = Utc::now() - chrono::Duration::hours(period as i64);
+
+            async move {
+                HttpRequest::get("/api/telemetry/security/smtp-failures")
+                    .with_authorization(&auth)
+                    .with_parameter("after", after.to_rfc3339())
+                    .send::<SmtpFailureHistory>()
+                    .await
+                    .map(|history| (format_labels(&history.timestamps, period), history))
+            }
+        },
+    );
+
+    let groups = create_memo(move |_| {
+        history
+            .get()
+            .and_then(|history| history.ok())
+            .map(|(_, history)| group_failures(&history, grouping.get()))
+            .unwrap_or_default()
+    });
+
+    view! {
+        <LazyChart
+            title="SMTP connection failures"
+            subtitle="Failed and abusive SMTP sessions by source network"
+        >
+            <div class="flex justify-end gap-x-3 mb-3 text-sm">
+                {[(FailureGrouping::Network, "By network"), (FailureGrouping::Country, "By country")]
+                    .into_iter()
+                    .map(|(value, label)| {
+                        view! {
+                            <button
+                                type="button"
+                                class="font-medium decoration-2 hover:underline"
+                                class:text-blue-600=move || grouping.get() == value
+                                class:text-gray-500=move || grouping.get() != value
+                                on:click=move |_| grouping.set(value)
+                            >
+                                {label}
+                            </button>
+                        }
+                    })
+                    .collect_view()}
+            </div>
+            {move || {
+                match history.get() {
+                    Some(Ok((labels, _))) => {
+                        let groups = groups.get();
+                        if groups.is_empty() {
+                            return view! {
+                                <p class="text-sm text-gray-500">
+                                    No SMTP connection failures were recorded in this period.
+                                </p>
+                            }
+                                .into_view();
+                        }
+                        let mut series = groups
+                            .iter()
+                            .zip(CHART_SERIES)
+                            .map(|(group, color)| Series {
+                                name: group.name.clone(),
+                                color,
+                                values: group.values.clone(),
+                            })
+                            .collect::<Vec<_>>();
+                        if groups.len() > CHART_SERIES.len() {
+                            let mut other = vec![0.0; labels.len()];
+                            for group in &groups[CHART_SERIES.len()..] {
+                                for (total, value) in other.iter_mut().zip(&group.values) {
+                                    *total += value;
+                                }
+                            }
+                            series
+                                .push(Series {
+                                    name: "Other".to_string(),
+                                    color: Color::Gray,
+                                    values: other,
+                                });
+                        }
+                        let max = groups.first().map_or(1.0, |group| group.total.max(1.0));
+                        view! {
+                            <BarChart labels=labels series=series.clone() stacked=true/>
+                            <ChartLegend series=series/>
+                            <ul class="mt-4 space-y-2">
+                                {groups
+                                    .into_iter()
+                                    .take(TOP_OFFENDERS)
+                                    .map(|group| {
+                                        let width = (group.total / max * 100.0).max(1.0);
+                                        let can_block = !group.networks.is_empty();
+                                        let group = store_value(group);
+                                        view! {
+                                            <li>
+                                                <div class="flex justify-between gap-x-3 text-sm">
+                                                    <span class="font-mono text-gray-800 dark:text-gray-200">
+                                                        {group.with_value(|group| group.name.clone())}
+                                                        {group
+                                                            .with_value(|group| group.detail.clone())
+                                                            .map(|detail| {
+                                                                view! {
+                                                                    <span class="ms-2 font-sans text-xs text-gray-500">
+                                                                        {detail}
+                                                                    </span>
+                                                                }
+                                                            })}
+
+                                                    </span>
+                                                    <span class="ms-auto text-gray-500">
+                                                        {group.with_value(|group| group.total)}
+                                                    </span>
+                                                    <Show when=move || can_block>
+                                                        <button
+                                                            type="button"
+                                                            class="text-sm text-blue-600 decoration-2 hover:underline font-medium dark:text-blue-500"
+                                                            disabled=move || capability.is_denied()
+                                                            title=move || capability.reason()
+                                                            on:click=move |_| {
+                                                                let group = group.get_value();
+                                                                modal
+                                                                    .set(
+                                                                        Modal::with_title("Block network")
+                                                                            .with_message(
+                                                                                format!(
+                                                                                    "Are you sure you want to block all connections from {}? The following ranges will be added to the blocked addresses: {}.",
+                                                                                    group.name,
+                                                                                    group.networks.join(", "),
+                                                                                ),
+                                                                            )
+                                                                            .with_button(format!("Block {}", group.name))
+                                                                            .with_dangerous_callback(move || {
+                                                                                on_block.call(group.clone());
+                                                                            }),
+                                                                    );
+                                                            }
+                                                        >
+
+                                                            Block
+                                                        </button>
+                                                    </Show>
+                                                </div>
+                                                <div class="mt-1 h-1.5 w-full bg-gray-200 rounded-full dark:bg-gray-700">
+                                                    <div
+                                                        class="h-1.5 bg-red-500 rounded-full"
+                                                        style=format!("width: {width:.1}%")
+                                                    ></div>
+                                                </div>
+                                            </li>
+                                        }
+                                    })
+                                    .collect_view()}
+                            </ul>
+                        }
+                            .into_view()
+                    }
+                    Some(Err(http::Error::Unauthorized)) => {
+                        use_navigate()("/login", Default::default());
+                        ().into_view()
+                    }
+                    Some(Err(http::Error::NotFound)) => {
+                        view! {
+                            <p class="text-sm text-gray-500">
+                                This server does not report SMTP connection failures.
+                            </p>
+                        }
+                            .into_view()
+                    }
+                    Some(Err(err)) => {
+                        alert.set(Alert::from(err));
+                        ().into_view()
+                    }
+                    None => ().into_view(),
+                }
+            }}
+
+        </LazyChart>
+    }
+}
+
+/// Stores a change to the blocked addresses and reloads them.
+async fn update_blocked_ips(
+    auth: &AuthToken,
+    update: UpdateSettings,
+) -> http::Result<ReloadSettings> {
+    HttpRequest::post("/api/settings")
+        .with_authorization(auth)
+        .with_body(vec![update])
+        .unwrap()
+        .send::<Option<String>>()
+        .await?;
+    HttpRequest::get(format!("/api/reload/{BLOCKED_IP_PREFIX}"))
+        .with_authorization(auth)
+        .send::<ReloadSettings>()
+        .await
+}
+
+/// Merges the failure series of all sources sharing the same network or
+/// country, largest first.
+fn group_failures(history: &SmtpFailureHistory, grouping: FailureGrouping) -> Vec<FailureGroup> {
+    let mut grouped: AHashMap<String, FailureGroup> = AHashMap::new();
+    for source in &history.sources {
+        let (name, detail) = match grouping {
+            FailureGrouping::Network => (
+                source
+                    .asn
+                    .map_or_else(|| "Unknown".to_string(), |asn| format!("AS{asn}")),
+                source.as_name.clone(),
+            ),
+            FailureGrouping::Country => (
+                source
+                    .country
+                    .clone()
+                    .unwrap_or_else(|| "Unknown".to_string()),
+                None,
+            ),
+        };
+        let group = grouped.entry(name.clone()).or_insert_with(|| FailureGroup {
+            name,
+            detail: None,
+            networks: Vec::new(),
+            values: vec![0.0; history.timestamps.len()],
+            total: 0.0,
+        });
+        if group.values.len() < source.values.len() {
+            group.values.resize(source.values.len(), 0.0);
+        }
+        for (total, value) in group.values.iter_mut().zip(&source.values) {
+            *total += value;
+        }
+        group.total += source.values.iter().sum::<f64>();
+        if group.detail.is_none() {
+            group.detail = detail;
+        }
+        if grouping == FailureGrouping::Network && source.asn.is_some() {
+            for network in &source.networks {
+                if !group.networks.contains(network) {
+                    group.networks.push(network.clone());
+                }
+            }
+        }
+    }
+    let mut groups = grouped.into_values().collect::<Vec<_>>();
+    groups.sort_by(|a, b| {
+        b.total
+            .total_cmp(&a.total)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    groups
+}
+
+/// Adds up the per-node values of a metric into a single series.
+fn sum_values(history: &MetricsHistory, id: &str) -> Vec<f64> {
+    let mut totals = vec![0.0; history.timestamps.len()];
+    for entry in history.metrics.iter().filter(|entry| entry.id == id) {
+        if totals.len() < entry.values.len() {
+            totals.resize(entry.values.len(), 0.0);
+        }
+        for (total, value) in totals.iter_mut().zip(&entry.values) {
+            *total += value;
+        }
+    }
+    totals
+}
+
+/// Groups failure counts by key and returns the largest ones.
+fn top_offenders(entries: impl Iterator<Item = (String, Option<String>, u64)>) -> Vec<Offender> {
+    let mut grouped: AHashMap<String, Offender> = AHashMap::new();
+    for (name, detail, count) in entries {
+        let offender = grouped.entry(name.clone()).or_insert_with(|| Offender {
+            name,
+            detail: None,
+            count: 0,
+        });
+        offender.count += count;
+        if offender.detail.is_none() {
+            offender.detail = detail;
+        }
+    }
+    let mut offenders = grouped.into_values().collect::<Vec<_>>();
+    offenders.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    offenders.truncate(TOP_OFFENDERS);
+    offenders
+}
@@ -0,0 +1,342 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::*;
+use leptos_router::use_navigate;
+use web_time::Instant;
+
+use crate::{
+    components::{
+        form::button::Button,
+        icon::{IconCheckCircle, IconExclamationTriangle},
+        list::table::{Table, TableRow},
+        messages::alert::{use_alerts, Alert, Alerts},
+        report::ReportView,
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::{use_authorization, AuthToken},
+    },
+    pages::{
+        config::{listener::probe_bind, Settings, SettingsValues},
+        directory::domains::autoconfig::AUTODISCOVER_REQUEST,
+        List,
+    },
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TestResult {
+    name: String,
+    target: String,
+    elapsed: u128,
+    outcome: Result<String, String>,
+}
+
+#[component]
+pub fn SelfTest() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let results = create_rw_signal(Vec::<TestResult>::new());
+
+    let run_tests = create_action(move |_: &()| {
+        let auth = auth.get();
+
+        async move {
+            results.set(Vec::new());
+
+            // JMAP session
+            let start = Instant::now();
+            let outcome = fetch_json(HttpRequest::get("/jmap/session").with_authorization(&auth))
+                .await
+                .map(|session| {
+                    format!(
+                        "API URL {}, {} accessible accounts",
+                        session["apiUrl"].as_str().unwrap_or("missing"),
+                        session["accounts"].as_object().map_or(0, |a| a.len())
+                    )
+                });
+            results.update(|results| {
+                results.push(TestResult::new(
+                    "JMAP session",
+                    "/jmap/session",
+                    start,
+                    outcome,
+                ))
+            });
+
+            // OAuth metadata
+            let start = Instant::now();
+            let outcome = fetch_json(
+                HttpRequest::get("/.well-known/oauth-authorization-server").with_base_url(&auth),
+            )
+            .await
+            .map(|metadata| {
+                format!(
+                    "Issuer {}",
+                    metadata["issuer"].as_str().unwrap_or("missing")
+                )
+            });
+            results.update(|results| {
+                results.push(TestResult::new(
+                    "OAuth metadata",
+                    "/.well-known/oauth-authorization-server",
+                    start,
+                    outcome,
+                ))
+            });
+
+            // Autoconfig and Autodiscover
+            let email = match test_address(&auth).await {
+                Ok(email) => email,
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                    return;
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                    return;
+                }
+            };
+            let start = Instant::now();
+            let outcome = HttpRequest::get("/mail/config-v1.1.xml")
+                .with_base_url(&auth)
+                .with_parameter("emailaddress", email.as_str())
+                .send_raw()
+                .await
+                .map_err(|err| Alert::from(err).message)
+                .and_then(|response| {
+                    let response = String::from_utf8_lossy(&response);
+                    if response.contains("<clientConfig") {
+                        Ok(format!(
+                            "{} incoming and {} outgoing servers for {email}",
+                            response.matches("<incomingServer").count(),
+                            response.matches("<outgoingServer").count()
+                        ))
+                    } else {
+                        Err("Response is not a valid autoconfig document".to_string())
+                    }
+                });
+            results.update(|results| {
+                results.push(TestResult::new(
+                    "Autoconfig",
+                    "/mail/config-v1.1.xml",
+                    start,
+                    outcome,
+                ))
+            });
+
+            let start = Instant::now();
+            let outcome = HttpRequest::post("/autodiscover/autodiscover.xml")
+                .with_base_url(&auth)
+                .with_header("Content-Type", "text/xml")
+                .with_raw_body(AUTODISCOVER_REQUEST.replace("{email}", &email))
+                .send_raw()
+                .await
+                .map_err(|err| Alert::from(err).message)
+                .and_then(|response| {
+                    let response = String::from_utf8_lossy(&response);
+                    if response.contains("<Autodiscover") {
+                        Ok(format!(
+                            "{} protocols for {email}",
+                            response.matches("<Protocol>").count()
+                        ))
+                    } else {
+                        Err("Response is not a valid autodiscover document".to_string())
+                    }
+                });
+            results.update(|results| {
+                results.push(TestResult::new(
+                    "Autodiscover",
+                    "/autodiscover/autodiscover.xml",
+                    start,
+                    outcome,
+                ))
+            });
+
+            // Listener ports
+            let listeners = match HttpRequest::get("/api/settings/group")
+                .with_authorization(&auth)
+                .with_parameter("prefix", "server.listener")
+                .with_parameter("suffix", "protocol")
+                .send::<List<Settings>>()
+                .await
+            {
+                Ok(listeners) => listeners.items,
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                    return;
+                }
+            };
+            for listener in listeners {
+                let protocol = listener
+                    .get("protocol")
+                    .map(|p| p.to_uppercase())
+                    .unwrap_or_default();
+                let name = format!(
+                    "{protocol} listener '{}'",
+                    listener.get("_id").map(|s| s.as_str()).unwrap_or_default()
+                );
+                for (_, bind) in listener.array_values("bind") {
+                    let start = Instant::now();
+                    let (outcome, elapsed) = match probe_bind(&auth, bind).await {
+                        Ok(Some(probe)) if probe.reachable => {
+                            (Ok("Accepting connections".to_string()), probe.elapsed)
+                        }
+                        Ok(Some(probe)) => (
+                            Err(probe
+                                .error
+                                .unwrap_or_else(|| "Not accepting connections".to_string())),
+                            probe.elapsed,
+                        ),
+                        Ok(None) => {
+                            alert.set(Alert::warning("This server does not support port probes."));
+                            return;
+                        }
+                        Err(err) => (Err(Alert::from(err).message), None),
+                    };
+                    let mut result = TestResult::new(&name, bind, start, outcome);
+                    if let Some(elapsed) = elapsed {
+                        result.elapsed = elapsed as u128;
+                    }
+                    results.update(|results| results.push(result));
+                }
+            }
+        }
+    });
+
+    view! {
+        <Alerts/>
+        <ReportView>
+            <div class="gap-2 sm:gap-4 py-8 first:pt-0 last:pb-0 border-t first:border-transparent border-gray-200 dark:border-gray-700 dark:first:border-transparent">
+                <div class="sm:col-span-12 pb-4">
+                    <h2 class="text-lg font-semibold text-gray-800 dark:text-gray-200">
+                        Connectivity self-test
+                    </h2>
+                    <p class="text-sm text-gray-500">
+                        Checks that JMAP, IMAP, POP3, OAuth and client autoconfiguration endpoints are reachable.
+                    </p>
+                </div>
+                <Show when=move || !results.get().is_empty()>
+                    <Table headers=vec![
+                        "".to_string(),
+                        "Test".to_string(),
+                        "Target".to_string(),
+                        "Latency".to_string(),
+                        "Result".to_string(),
+                    ]>
+                        <For
+                            each=move || results.get().into_iter().enumerate()
+                            key=|(idx, _)| *idx
+                            children=move |(_, result)| {
+                                let (icon, text) = match result.outcome {
+                                    Ok(text) => {
+                                        (
+                                            view! {
+                                                <IconCheckCircle attr:class="flex-shrink-0 size-5 text-teal-500"/>
+                                            },
+                                            text,
+                                        )
+                                    }
+                                    Err(text) => {
+                                        (
+                                            view! {
+                                                <IconExclamationTriangle attr:class="flex-shrink-0 size-5 text-red-500"/>
+                                            },
+                                            text,
+                                        )
+                                    }
+                                };
+                                view! {
+                                    <TableRow>
+                                        {icon}
+                                        <span>{result.name}</span>
+                                        <span>{result.target}</span>
+                                        <span>{format!("{} ms", result.elapsed)}</span>
+                                        <span class="whitespace-normal">{text}</span>
+                                    </TableRow>
+                                }
+                            }
+                        />
+
+                    </Table>
+                </Show>
+            </div>
+
+            <div class="flex justify-end gap-x-2">
+                <Button
+                    text="Back"
+                    color=Color::Gray
+                    on_click=move |_| {
+                        use_navigate()("/manage/maintenance", Default::default());
+                    }
+                />
+                <Button
+                    text=Signal::derive(move || {
+                        if run_tests.pending().get() { "Running..." } else { "Run tests" }
+                            .to_string()
+                    })
+
+                    color=Color::Blue
+                    on_click=move |_| {
+                        run_tests.dispatch(());
+                    }
+
+                    disabled=run_tests.pending()
+                />
+            </div>
+        </ReportView>
+    }
+}
+
+impl TestResult {
+    fn new(
+        name: impl Into<String>,
+        target: impl Into<String>,
+        start: Instant,
+        outcome: Result<String, String>,
+    ) -> Self {
+        TestResult {
+            name: name.into(),
+            target: target.into(),
+            elapsed: start.elapsed().as_millis(),
+            outcome,
+        }
+    }
+}
+
+async fn fetch_json(request: HttpRequest) -> Result<serde_json::Value, String> {
+    let response = request
+        .send_raw()
+        .await
+        .map_err(|err| Alert::from(err).message)?;
+    serde_json::from_slice(&response).map_err(|err| format!("Invalid JSON response: {err}"))
+}
+
+/// Address used to request client configuration documents, either the
+/// logged in account or the postmaster of the first local domain.
+async fn test_address(auth: &AuthToken) -> http::Result<String> {
+    if auth.username.contains('@') {
+        return Ok(auth.username.to_string());
+    }
+
+    HttpRequest::get("/api/domain")
+        .with_authorization(auth)
+        .with_parameter("page", "1")
+        .with_parameter("limit", "1")
+        .send::<List<String>>()
+        .await
+        .map(|domains| {
+            format!(
+                "postmaster@{}",
+                domains
+                    .items
+                    .into_iter()
+                    .next()
+                    .unwrap_or_else(|| "localhost".to_string())
+            )
+        })
+}
@@ -13,6 +13,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     components::{
+        badge::Badge,
         form::{
             button::Button,
             input::{InputSwitch, InputText, TextArea},
@@ -20,13 +21,16 @@ use crate::{
             stacked_input::StackedInput,
             Form, FormButtonBar, FormElement, FormItem, FormSection,
         },
+        icon::IconDocumentText,
         messages::alert::{use_alerts, Alert},
         Color,
     },
     core::{
-        form::FormValue,
+        file::read_file,
+        form::{FormData, FormValue},
         http::{Error, HttpRequest},
         oauth::use_authorization,
+        permissions::Capability,
         schema::{Builder, Schemas, Source, Transformer, Type, Validator},
     },
 };
@@ -56,6 +60,21 @@ pub enum Modification {
     AddHeader { name: String, value: String },
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TrainStatus {
+    Queued,
+    Training,
+    Trained,
+    Failed(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TrainUpload {
+    name: String,
+    class: String,
+    status: TrainStatus,
+}
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum Envelope {
     From,
@@ -326,6 +345,8 @@ pub fn SpamTrain() -> impl IntoView {
 
             </FormSection>
 
+            <BulkTrain data=data/>
+
             <FormButtonBar>
 
                 <Button
@@ -344,6 +365,7 @@ pub fn SpamTrain() -> impl IntoView {
                     })
 
                     disabled=pending
+                    capability=Capability::SpamFilterTrain
                 />
             </FormButtonBar>
 
@@ -351,6 +373,234 @@ pub fn SpamTrain() -> impl IntoView {
     }
 }
 
+/// Trains the classifier with a batch of .eml files dropped on the page or
+/// picked from a folder, one request per message, reporting the result of
+/// each file as it is processed.
+#[component]
+fn BulkTrain(data: RwSignal<FormData>) -> impl IntoView {
+    let auth = use_authorization();
+    let uploads = create_rw_signal(Vec::<TrainUpload>::new());
+    let is_dragging = create_rw_signal(false);
+
+    let train_action = create_action(move |files: &Vec<web_sys::File>| {
+        let auth = auth.get();
+        let files = files.clone();
+        let class = data
+            .get_untracked()
+            .value::<String>("train")
+            .unwrap_or_else(|| "spam".to_string());
+
+        async move {
+            let offset = uploads.with_untracked(|uploads| uploads.len());
+            uploads.update(|uploads| {
+                uploads.extend(files.iter().map(|file| TrainUpload {
+                    name: file_name(file),
+                    class: class.clone(),
+                    status: TrainStatus::Queued,
+                }))
+            });
+
+            for (idx, file) in files.iter().enumerate() {
+                let set_status = |status| {
+                    uploads.update(|uploads| {
+                        if let Some(upload) = uploads.get_mut(offset + idx) {
+                            upload.status = status;
+                        }
+                    })
+                };
+                set_status(TrainStatus::Training);
+                let status = match read_file(file).await {
+                    Ok(contents) => {
+                        match HttpRequest::post("/api/sieve/train")
+                            .with_authorization(&auth)
+                            .with_parameter("train", &class)
+                            .with_raw_body(String::from_utf8_lossy(&contents).into_owned())
+                            .send::<Response>()
+                            .await
+                        {
+                            Ok(Response::Accept { .. }) => TrainStatus::Trained,
+                            Ok(Response::Reject { reason }) => TrainStatus::Failed(reason),
+                            Ok(_) => TrainStatus::Failed("Unexpected server response".to_string()),
+                            Err(Error::Unauthorized) => {
+                                use_navigate()("/login", Default::default());
+                                return;
+                            }
+                            Err(err) => TrainStatus::Failed(Alert::from(err).message),
+                        }
+                    }
+                    Err(err) => TrainStatus::Failed(err),
+                };
+                set_status(status);
+            }
+        }
+    });
+
+    let add_files = move |files: Option<web_sys::FileList>| {
+        let files = files
+            .map(|files| {
+                (0..files.length())
+                    .filter_map(|idx| files.get(idx))
+                    .filter(|file| file.name().to_lowercase().ends_with(".eml"))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        if !files.is_empty() {
+            train_action.dispatch(files);
+        }
+    };
+
+    let summary = create_memo(move |_| {
+        uploads.with(|uploads| {
+            let trained = uploads
+                .iter()
+                .filter(|upload| upload.status == TrainStatus::Trained)
+                .count();
+            let failed = uploads
+                .iter()
+                .filter(|upload| matches!(upload.status, TrainStatus::Failed(_)))
+                .count();
+            format!("{trained} of {} trained, {failed} failed", uploads.len())
+        })
+    });
+
+    view! {
+        <FormSection title="Bulk training".to_string()>
+            <FormItem
+                label="Messages"
+                tooltip="Drop .eml files here or select a folder, each message is trained with the class selected above."
+            >
+                <div
+                    class=move || {
+                        if is_dragging.get() {
+                            "p-6 flex flex-col items-center gap-y-3 border-2 border-dashed border-blue-500 rounded-xl bg-blue-50 dark:bg-slate-800"
+                        } else {
+                            "p-6 flex flex-col items-center gap-y-3 border-2 border-dashed border-gray-300 rounded-xl dark:border-gray-700"
+                        }
+                    }
+
+                    on:dragover=move |ev: ev::DragEvent| {
+                        ev.prevent_default();
+                        is_dragging.set(true);
+                    }
+
+                    on:dragleave=move |_| is_dragging.set(false)
+                    on:drop=move |ev: ev::DragEvent| {
+                        ev.prevent_default();
+                        is_dragging.set(false);
+                        add_files(ev.data_transfer().and_then(|transfer| transfer.files()));
+                    }
+                >
+
+                    <IconDocumentText attr:class="size-8 text-gray-400"/>
+                    <p class="text-sm text-gray-500">Drag and drop .eml files here</p>
+                    <div class="flex flex-wrap justify-center gap-2">
+                        <label class="py-1.5 px-2.5 inline-flex items-center text-sm font-medium rounded-lg border border-gray-200 bg-white text-gray-800 shadow-sm hover:bg-gray-50 cursor-pointer dark:bg-slate-900 dark:border-gray-700 dark:text-white dark:hover:bg-gray-800">
+                            Select files
+                            <input
+                                type="file"
+                                class="hidden"
+                                accept=".eml,message/rfc822"
+                                multiple
+                                on:change=move |ev| {
+                                    let input = event_target::<web_sys::HtmlInputElement>(&ev);
+                                    add_files(input.files());
+                                    input.set_value("");
+                                }
+                            />
+
+                        </label>
+                        <label class="py-1.5 px-2.5 inline-flex items-center text-sm font-medium rounded-lg border border-gray-200 bg-white text-gray-800 shadow-sm hover:bg-gray-50 cursor-pointer dark:bg-slate-900 dark:border-gray-700 dark:text-white dark:hover:bg-gray-800">
+                            Select folder
+                            <input
+                                type="file"
+                                class="hidden"
+                                webkitdirectory=""
+                                on:change=move |ev| {
+                                    let input = event_target::<web_sys::HtmlInputElement>(&ev);
+                                    add_files(input.files());
+                                    input.set_value("");
+                                }
+                            />
+
+                        </label>
+                    </div>
+                </div>
+            </FormItem>
+            <Show when=move || uploads.with(|uploads| !uploads.is_empty())>
+                <FormItem label="Results">
+                    <div class="flex flex-col gap-y-2">
+                        <div class="flex items-center justify-between text-sm text-gray-600 dark:text-gray-400">
+                            <span>{move || summary.get()}</span>
+                            <button
+                                type="button"
+                                class="text-sm text-blue-600 decoration-2 hover:underline font-medium disabled:opacity-50 disabled:pointer-events-none"
+                                disabled=move || train_action.pending().get()
+                                on:click=move |_| uploads.set(Vec::new())
+                            >
+                                Clear
+                            </button>
+                        </div>
+                        <ul class="max-h-80 overflow-y-auto divide-y divide-gray-200 border border-gray-200 rounded-lg dark:divide-gray-700 dark:border-gray-700">
+                            {move || {
+                                uploads
+                                    .get()
+                                    .into_iter()
+                                    .map(|upload| {
+                                        let (color, label, details) = match upload.status {
+                                            TrainStatus::Queued => (Color::Gray, "Queued", None),
+                                            TrainStatus::Training => {
+                                                (Color::Blue, "Training", None)
+                                            }
+                                            TrainStatus::Trained => {
+                                                (Color::Green, "Trained", None)
+                                            }
+                                            TrainStatus::Failed(err) => {
+                                                (Color::Red, "Failed", Some(err))
+                                            }
+                                        };
+                                        view! {
+                                            <li class="px-3 py-2 flex flex-col text-sm">
+                                                <div class="flex items-center gap-x-2">
+                                                    <Badge color=color>{label}</Badge>
+                                                    <span class="font-mono text-gray-800 dark:text-gray-200 break-all">
+                                                        {upload.name}
+                                                    </span>
+                                                    <span class="ms-auto text-xs uppercase text-gray-500">
+                                                        {upload.class}
+                                                    </span>
+                                                </div>
+                                                {details
+                                                    .map(|details| {
+                                                        view! {
+                                                            <p class="mt-1 text-xs text-red-600 dark:text-red-500">
+                                                                {details}
+                                                            </p>
+                                                        }
+                                                    })}
+
+                                            </li>
+                                        }
+                                    })
+                                    .collect_view()
+                            }}
+
+                        </ul>
+                    </div>
+                </FormItem>
+            </Show>
+        </FormSection>
+    }
+}
+
+/// Name of an uploaded file, including its path when picked from a folder.
+fn file_name(file: &web_sys::File) -> String {
+    web_sys::js_sys::Reflect::get(file, &"webkitRelativePath".into())
+        .ok()
+        .and_then(|path| path.as_string())
+        .filter(|path| !path.is_empty())
+        .unwrap_or_else(|| file.name())
+}
+
 fn to_reverse_name(ip: IpAddr) -> String {
     use std::fmt::Write;
 
@@ -0,0 +1,392 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use chrono::{DateTime, Utc};
+use leptos::*;
+use leptos_router::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        badge::Badge,
+        list::{
+            header::ColumnList, toolbar::SearchBox, Footer, ListItem, ListSection, ListTable,
+            Toolbar, ZeroResults,
+        },
+        messages::alert::{use_alerts, Alert},
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        url::UrlBuilder,
+    },
+    pages::{
+        manage::tracking::{search_spans, value_to_string, TraceEvent, SPAN_RECEIVED},
+        maybe_plural, FormatDateTime,
+    },
+};
+
+/// Keys under which the ingest event records the spam filter result, in the
+/// `TAG (score), ...` format of the X-Spam-Result header.
+const RESULT_KEYS: &[&str] = &["spamResult", "result", "tags"];
+
+/// Keys under which the final spam score is recorded.
+const SCORE_KEYS: &[&str] = &["spamScore", "score"];
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+enum Verdict {
+    Ham,
+    Spam,
+    Rejected,
+    Discarded,
+    Unknown,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct SpamReport {
+    span_id: String,
+    received_at: Option<DateTime<Utc>>,
+    from: Option<String>,
+    message_id: Option<String>,
+    verdict: Verdict,
+    score: Option<f64>,
+    rules: Vec<(String, f64)>,
+}
+
+/// Shows the spam filter result recorded when a message was received: the
+/// rules that fired, the score each one contributed and the final verdict.
+/// Messages are looked up by message-id, sender or recipient, or opened
+/// directly from a received-message span.
+#[component]
+pub fn SpamExplain() -> impl IntoView {
+    let query = use_query_map();
+    let filter = create_memo(move |_| {
+        query.with(|q| {
+            q.get("filter")
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+        })
+    });
+    let span_id =
+        create_memo(move |_| query.with(|q| q.get("span").cloned().filter(|s| !s.is_empty())));
+
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let reports = create_resource(
+        move || (filter.get(), span_id.get()),
+        move |(filter, span_id)| {
+            let auth = auth.get_untracked();
+
+            async move {
+                if let Some(span_id) = span_id {
+                    HttpRequest::get(("/api/telemetry/trace", &span_id))
+                        .with_authorization(&auth)
+                        .send::<Vec<TraceEvent>>()
+                        .await
+                        .map(|events| vec![SpamReport::new(span_id, &events)])
+                } else if let Some(filter) = filter {
+                    search_spans(&auth, SPAN_RECEIVED, &filter)
+                        .await
+                        .map(|spans| {
+                            spans
+                                .into_iter()
+                                .map(|span| SpamReport::new(span.id, &span.events))
+                                .collect()
+                        })
+                } else {
+                    Ok(Vec::new())
+                }
+            }
+        },
+    );
+
+    let total_results = create_rw_signal(None::<usize>);
+
+    view! {
+        <ListSection>
+            <ListTable
+                title="Spam score explanation"
+                subtitle="Find out why a message was classified as spam or ham by message-id, sender or recipient"
+            >
+                <Toolbar slot>
+                    <SearchBox
+                        value=filter
+                        on_search=move |value| {
+                            use_navigate()(
+                                &UrlBuilder::new("/manage/spam/explain")
+                                    .with_parameter("filter", value)
+                                    .finish(),
+                                Default::default(),
+                            );
+                        }
+                    />
+
+                </Toolbar>
+
+                <Transition fallback=Skeleton>
+                    {move || match reports.get() {
+                        None => None,
+                        Some(Err(http::Error::Unauthorized)) => {
+                            use_navigate()("/login", Default::default());
+                            Some(view! { <div></div> }.into_view())
+                        }
+                        Some(Err(http::Error::NotFound)) => {
+                            total_results.set(Some(0));
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="Tracing not available"
+                                        subtitle="Enable the tracing history store to look up spam filter results."
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Err(err)) => {
+                            total_results.set(Some(0));
+                            alert.set(Alert::from(err));
+                            Some(view! { <Skeleton/> }.into_view())
+                        }
+                        Some(Ok(reports)) if !reports.is_empty() => {
+                            total_results.set(Some(reports.len()));
+                            Some(
+                                view! {
+                                    <ColumnList headers=vec![
+                                        "Rule".to_string(),
+                                        "Score".to_string(),
+                                        "Contribution".to_string(),
+                                    ]>
+
+                                        <For
+                                            each=move || reports.clone()
+                                            key=|report| report.span_id.clone()
+                                            let:report
+                                        >
+                                            <SpamReportItem report/>
+                                        </For>
+
+                                    </ColumnList>
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Ok(_)) => {
+                            let subtitle = if filter.get().is_some() || span_id.get().is_some() {
+                                "No received messages were found matching the search criteria."
+                            } else {
+                                "Enter a message-id, sender or recipient address to search."
+                            };
+                            total_results.set(Some(0));
+                            Some(
+                                view! { <ZeroResults title="No results" subtitle=subtitle/> }
+                                    .into_view(),
+                            )
+                        }
+                    }}
+
+                </Transition>
+
+                <Footer slot>
+                    <div class="px-6 py-4 border-t border-gray-200 dark:border-gray-700">
+                        <p class="text-sm text-gray-600 dark:text-gray-400">
+                            {move || {
+                                total_results
+                                    .get()
+                                    .map(|total| maybe_plural(total, "message", "messages"))
+                                    .unwrap_or_default()
+                            }}
+
+                        </p>
+                    </div>
+                </Footer>
+            </ListTable>
+        </ListSection>
+    }
+}
+
+#[component]
+fn SpamReportItem(report: SpamReport) -> impl IntoView {
+    let (verdict_color, verdict_text) = match report.verdict {
+        Verdict::Ham => (Color::Green, "Ham"),
+        Verdict::Spam => (Color::Red, "Spam"),
+        Verdict::Rejected => (Color::Red, "Rejected"),
+        Verdict::Discarded => (Color::Yellow, "Discarded"),
+        Verdict::Unknown => (Color::Gray, "Unknown"),
+    };
+    let max_score = report
+        .rules
+        .iter()
+        .map(|(_, score)| score.abs())
+        .fold(0.0, f64::max)
+        .max(f64::EPSILON);
+    let summary = [
+        report.from.clone(),
+        report.message_id.as_ref().map(|id| format!("<{id}>")),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(" · ");
+
+    view! {
+        <tr class="bg-gray-50 dark:bg-slate-800">
+            <td colspan="3" class="px-6 py-3">
+                <div class="flex flex-wrap items-center gap-x-3">
+                    <Badge color=verdict_color>{verdict_text}</Badge>
+                    {report
+                        .score
+                        .map(|score| {
+                            view! {
+                                <span class="text-sm font-semibold text-gray-800 dark:text-gray-200">
+                                    {format!("Score {score:.2}")}
+                                </span>
+                            }
+                        })}
+                    <span class="text-sm text-gray-600 dark:text-gray-400">{summary}</span>
+                    {report
+                        .received_at
+                        .map(|date| {
+                            view! {
+                                <span class="ms-auto text-xs text-gray-500">
+                                    {date.format_date_time()}
+                                </span>
+                            }
+                        })}
+
+                </div>
+            </td>
+        </tr>
+
+        {if report.rules.is_empty() {
+            view! {
+                <tr>
+                    <td colspan="3" class="px-6 py-3 text-sm text-gray-500">
+                        No spam filter rules were recorded for this message.
+                    </td>
+                </tr>
+            }
+                .into_view()
+        } else {
+            report
+                .rules
+                .into_iter()
+                .map(|(rule, score)| {
+                    let width = (score.abs() / max_score * 100.0).max(1.0);
+                    let bar_class = if score > 0.0 {
+                        "h-1.5 bg-red-600 rounded-full"
+                    } else {
+                        "h-1.5 bg-green-600 rounded-full"
+                    };
+                    view! {
+                        <tr>
+                            <ListItem>
+                                <span class="text-sm font-mono text-gray-800 dark:text-gray-200">
+                                    {rule}
+                                </span>
+                            </ListItem>
+                            <ListItem>
+                                <span class="text-sm text-gray-500">{format!("{score:+.2}")}</span>
+                            </ListItem>
+                            <ListItem>
+                                <div class="w-40 h-1.5 bg-gray-200 rounded-full dark:bg-gray-700">
+                                    <div
+                                        class=bar_class
+                                        style=format!("width: {width:.1}%")
+                                    ></div>
+                                </div>
+                            </ListItem>
+                        </tr>
+                    }
+                })
+                .collect_view()
+        }}
+    }
+}
+
+impl SpamReport {
+    fn new(span_id: String, events: &[TraceEvent]) -> Self {
+        let mut report = SpamReport {
+            span_id,
+            received_at: events.iter().map(|event| event.created_at).min(),
+            from: None,
+            message_id: None,
+            verdict: Verdict::Unknown,
+            score: None,
+            rules: Vec::new(),
+        };
+        let result_re = Regex::new(r"([A-Za-z0-9_]+)\s*\(\s*(-?[0-9]+(?:\.[0-9]+)?)\s*\)").unwrap();
+
+        for event in events {
+            if report.from.is_none() {
+                report.from = event.value("from");
+            }
+            if report.message_id.is_none() {
+                report.message_id = event.value("messageId");
+            }
+            if report.score.is_none() {
+                report.score = SCORE_KEYS
+                    .iter()
+                    .find_map(|key| event.data.get(*key))
+                    .and_then(|value| value_to_string(value).parse().ok());
+            }
+
+            match event.typ.as_str() {
+                "message-ingest.ham" => report.verdict = Verdict::Ham,
+                "message-ingest.spam" => report.verdict = Verdict::Spam,
+                typ if typ.starts_with("spam.") => {
+                    // Individual rule matches
+                    if let (Some(rule), Some(score)) = (
+                        event.value("rule").or_else(|| event.value("tag")),
+                        event
+                            .data
+                            .get("score")
+                            .and_then(|value| value_to_string(value).parse().ok()),
+                    ) {
+                        report.rules.push((rule, score));
+                    }
+                    if typ.contains("reject") {
+                        report.verdict = Verdict::Rejected;
+                    } else if typ.contains("discard") {
+                        report.verdict = Verdict::Discarded;
+                    }
+                }
+                _ => {}
+            }
+
+            // Summary of all rules in the X-Spam-Result format
+            if let Some(result) = RESULT_KEYS.iter().find_map(|key| event.data.get(*key)) {
+                let result = match result {
+                    serde_json::Value::Array(items) => items
+                        .iter()
+                        .map(value_to_string)
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    value => value_to_string(value),
+                };
+                for captures in result_re.captures_iter(&result) {
+                    if let Ok(score) = captures[2].parse() {
+                        let rule = captures[1].to_string();
+                        if !report.rules.iter().any(|(name, _)| *name == rule) {
+                            report.rules.push((rule, score));
+                        }
+                    }
+                }
+            }
+        }
+
+        report
+            .rules
+            .sort_by(|a, b| b.1.abs().total_cmp(&a.1.abs()).then_with(|| a.0.cmp(&b.0)));
+        if report.score.is_none() && !report.rules.is_empty() {
+            report.score = Some(report.rules.iter().map(|(_, score)| score).sum());
+        }
+
+        report
+    }
+}
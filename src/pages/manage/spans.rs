@@ -0,0 +1,651 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use chrono::{DateTime, Utc};
+use leptos::*;
+use leptos_router::*;
+use serde::Serialize;
+
+use crate::{
+    components::{
+        badge::Badge,
+        icon::IconDocumentText,
+        list::{
+            header::ColumnList,
+            query::use_list_query,
+            toolbar::{SearchBox, ToolbarButton},
+            virtualized::VirtualRows,
+            Footer, ListItem, ListSection, ListTable, Toolbar, ZeroResults,
+        },
+        messages::alert::{use_alerts, Alert},
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{file::download_file, http, oauth::use_authorization},
+    pages::{
+        manage::{
+            audit::csv_escape,
+            tracking::{
+                event_status, search_spans, stage_name, value_to_string, Span, TraceEvent,
+                TrackingStatus, SPAN_DELIVERY, SPAN_RECEIVED,
+            },
+        },
+        maybe_plural, FormatDateTime,
+    },
+};
+
+/// Differences in the time an event happened after the start of the
+/// attempt below this threshold are not highlighted.
+const TIMING_THRESHOLD_MS: i64 = 1000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ExportFormat {
+    Csv,
+    Json,
+}
+
+/// Envelope fields and final status of a span, as included in exports.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+struct ExportedSpan {
+    id: String,
+    #[serde(rename = "type")]
+    typ: &'static str,
+    date: Option<String>,
+    queue_id: Option<String>,
+    message_id: Option<String>,
+    from: Option<String>,
+    to: Vec<String>,
+    status: &'static str,
+}
+
+/// Received messages or delivery attempts matching a message-id, queue id or
+/// address, any two of which can be compared side by side.
+#[component]
+pub fn SpanList() -> impl IntoView {
+    let list = use_list_query();
+    let typ = list.param("type");
+    let span_type = create_memo(move |_| {
+        if typ.get().is_some_and(|typ| typ == "received") {
+            SPAN_RECEIVED
+        } else {
+            SPAN_DELIVERY
+        }
+    });
+    let filter = list.param("filter");
+
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let selected = create_rw_signal(Vec::<String>::new());
+    let spans = create_resource(
+        move || (filter.get(), span_type.get()),
+        move |(filter, span_type)| {
+            let auth = auth.get_untracked();
+
+            async move {
+                match filter {
+                    Some(filter) => {
+                        let mut spans = search_spans(&auth, span_type, &filter).await?;
+                        spans.sort_by_key(|span| std::cmp::Reverse(span.started_at()));
+                        Ok(spans)
+                    }
+                    None => Ok(Vec::new()),
+                }
+            }
+        },
+    );
+
+    let total_results = create_rw_signal(None::<usize>);
+    let comparison = create_memo(move |_| {
+        let selected = selected.get();
+        if selected.len() != 2 {
+            return None;
+        }
+        let spans = spans.get().and_then(|spans| spans.ok())?;
+        let mut pair = selected
+            .iter()
+            .filter_map(|id| spans.iter().find(|span| &span.id == id).cloned())
+            .collect::<Vec<_>>();
+        pair.sort_by_key(|span| span.started_at());
+        let newer = pair.pop()?;
+        let older = pair.pop()?;
+        Some((older, newer))
+    });
+    let export = move |format: ExportFormat| {
+        let spans = spans.get().and_then(|spans| spans.ok()).unwrap_or_default();
+        if spans.is_empty() {
+            alert.set(Alert::warning("There are no results to export."));
+            return;
+        }
+        let (extension, content_type, contents) = export_spans(&spans, format);
+        let file_name = format!(
+            "{}-{}.{extension}",
+            span_type.get_untracked(),
+            Utc::now().format("%Y%m%d-%H%M%S")
+        );
+        match download_file(&file_name, content_type, &contents) {
+            Ok(_) => {
+                alert.set(Alert::success(format!(
+                    "Exported {}",
+                    maybe_plural(spans.len(), "span", "spans")
+                )));
+            }
+            Err(err) => {
+                alert.set(Alert::error("Export failed").with_details(err));
+            }
+        }
+    };
+
+    view! {
+        <ListSection>
+            <ListTable
+                title=Signal::derive(move || {
+                    if span_type.get() == SPAN_RECEIVED {
+                        "Received messages".to_string()
+                    } else {
+                        "Delivery attempts".to_string()
+                    }
+                })
+
+                subtitle="Select two entries to compare their timelines"
+            >
+                <Toolbar slot>
+                    <select
+                        class="py-2 px-3 pe-9 block border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400"
+                        on:change=move |ev| {
+                            selected.set(Vec::new());
+                            let typ = Some(event_target_value(&ev))
+                                .filter(|typ| typ == "received");
+                            list.set_filters([("type", typ)]);
+                        }
+                    >
+
+                        <option value="delivery" selected=move || span_type.get() == SPAN_DELIVERY>
+                            Delivery attempts
+                        </option>
+                        <option value="received" selected=move || span_type.get() == SPAN_RECEIVED>
+                            Received messages
+                        </option>
+                    </select>
+                    <SearchBox
+                        value=filter
+                        on_search=move |value: String| {
+                            selected.set(Vec::new());
+                            list.set_filters([("filter", Some(value))]);
+                        }
+                    />
+
+                    <ToolbarButton
+                        text="Export CSV"
+                        color=Color::Gray
+                        on_click=move |_| export(ExportFormat::Csv)
+                    >
+                        <IconDocumentText/>
+                    </ToolbarButton>
+                    <ToolbarButton
+                        text="Export JSON"
+                        color=Color::Gray
+                        on_click=move |_| export(ExportFormat::Json)
+                    >
+                        <IconDocumentText/>
+                    </ToolbarButton>
+
+                </Toolbar>
+
+                <Transition fallback=Skeleton>
+                    {move || match spans.get() {
+                        None => None,
+                        Some(Err(http::Error::Unauthorized)) => {
+                            use_navigate()("/login", Default::default());
+                            Some(view! { <div></div> }.into_view())
+                        }
+                        Some(Err(http::Error::NotFound)) => {
+                            total_results.set(Some(0));
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="Tracing not available"
+                                        subtitle="Enable the tracing history store to search for messages."
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Err(err)) => {
+                            total_results.set(Some(0));
+                            alert.set(Alert::from(err));
+                            Some(view! { <Skeleton/> }.into_view())
+                        }
+                        Some(Ok(spans)) if !spans.is_empty() => {
+                            total_results.set(Some(spans.len()));
+                            Some(
+                                view! {
+                                    <ColumnList headers=vec![
+                                        "".to_string(),
+                                        "Date".to_string(),
+                                        "Recipients".to_string(),
+                                        "Outcome".to_string(),
+                                        "Events".to_string(),
+                                    ]>
+
+                                        <VirtualRows
+                                            each=Signal::derive(move || spans.clone())
+                                            key=|span| span.id.clone()
+                                            let:span
+                                        >
+                                            <SpanItem span selected/>
+                                        </VirtualRows>
+
+                                    </ColumnList>
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Ok(_)) => {
+                            let subtitle = if filter.get().is_some() {
+                                "No results were found matching the search criteria."
+                            } else {
+                                "Enter a message-id, queue id or recipient address to search."
+                            };
+                            total_results.set(Some(0));
+                            Some(
+                                view! { <ZeroResults title="No results" subtitle=subtitle/> }
+                                    .into_view(),
+                            )
+                        }
+                    }}
+
+                </Transition>
+
+                <Footer slot>
+                    <div class="px-6 py-4 border-t border-gray-200 dark:border-gray-700">
+                        <p class="text-sm text-gray-600 dark:text-gray-400">
+                            {move || {
+                                total_results
+                                    .get()
+                                    .map(|total| {
+                                        maybe_plural(total, "result", "results")
+                                    })
+                                    .unwrap_or_default()
+                            }}
+
+                        </p>
+                    </div>
+                </Footer>
+            </ListTable>
+
+            {move || {
+                comparison
+                    .get()
+                    .map(|(older, newer)| view! { <SpanComparison older newer/> })
+            }}
+
+        </ListSection>
+    }
+}
+
+#[component]
+fn SpanItem(span: Span, selected: RwSignal<Vec<String>>) -> impl IntoView {
+    let id = span.id.clone();
+    let is_selected = {
+        let id = id.clone();
+        move || selected.with(|selected| selected.contains(&id))
+    };
+    let recipients = span.recipients().join(", ");
+    let outcome = span.outcome();
+    let num_events = span.events.len();
+
+    view! {
+        <tr>
+            <td class="size-px whitespace-nowrap">
+                <div class="ps-6 py-3">
+                    <input
+                        type="checkbox"
+                        class="shrink-0 border-gray-300 rounded text-blue-600 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-600 dark:checked:bg-blue-500 dark:checked:border-blue-500 dark:focus:ring-offset-gray-800"
+                        prop:checked=is_selected
+                        on:change=move |ev| {
+                            let checked = event_target_checked(&ev);
+                            selected
+                                .update(|selected| {
+                                    selected.retain(|item| item != &id);
+                                    if checked {
+                                        // Only two attempts can be compared, drop the oldest selection
+                                        if selected.len() == 2 {
+                                            selected.remove(0);
+                                        }
+                                        selected.push(id.clone());
+                                    }
+                                });
+                        }
+                    />
+
+                </div>
+            </td>
+            <ListItem>
+                <span class="text-sm text-gray-500">
+                    {span.started_at().map(|date| date.format_date_time()).unwrap_or_default()}
+                </span>
+            </ListItem>
+            <ListItem>
+                <span class="text-sm text-gray-800 dark:text-gray-200">{recipients}</span>
+            </ListItem>
+            <ListItem>
+                <OutcomeBadge outcome/>
+            </ListItem>
+            <ListItem>
+                <span class="text-sm text-gray-500">{num_events}</span>
+            </ListItem>
+        </tr>
+    }
+}
+
+#[component]
+fn OutcomeBadge(outcome: Option<TrackingStatus>) -> impl IntoView {
+    let (color, text) = match outcome {
+        Some(TrackingStatus::Delivered) => (Color::Green, "Delivered"),
+        Some(TrackingStatus::Deferred) => (Color::Yellow, "Deferred"),
+        Some(TrackingStatus::Bounced | TrackingStatus::Rejected) => (Color::Red, "Failed"),
+        Some(TrackingStatus::Accepted) => (Color::Blue, "Accepted"),
+        None => (Color::Gray, "In progress"),
+    };
+
+    view! { <Badge color=color>{text}</Badge> }
+}
+
+/// Renders two delivery attempts next to each other with their events
+/// aligned by type, highlighting events missing from one of the attempts,
+/// timing shifts and fields with different values.
+#[component]
+fn SpanComparison(older: Span, newer: Span) -> impl IntoView {
+    let older_start = older.started_at();
+    let newer_start = newer.started_at();
+    let rows = align_events(&older.events, &newer.events);
+
+    view! {
+        <div class="mt-6 bg-white border border-gray-200 rounded-xl shadow-sm overflow-hidden dark:bg-slate-900 dark:border-gray-700">
+            <div class="px-6 py-4 border-b border-gray-200 dark:border-gray-700">
+                <h2 class="text-xl font-semibold text-gray-800 dark:text-gray-200">Comparison</h2>
+                <p class="text-sm text-gray-600 dark:text-gray-400">
+                    Events are shown relative to the start of each attempt
+                </p>
+            </div>
+            <div class="overflow-x-auto">
+                <table class="min-w-full table-fixed divide-y divide-gray-200 dark:divide-gray-700">
+                    <thead class="bg-gray-50 dark:bg-slate-800">
+                        <tr>
+                            <AttemptHeader span=older.clone()/>
+                            <AttemptHeader span=newer.clone()/>
+                        </tr>
+                    </thead>
+                    <tbody class="divide-y divide-gray-200 dark:divide-gray-700">
+                        {rows
+                            .into_iter()
+                            .map(|(left, right)| {
+                                let left = left.map(|idx| &older.events[idx]);
+                                let right = right.map(|idx| &newer.events[idx]);
+                                let left_offset = left
+                                    .zip(older_start)
+                                    .map(|(event, start)| offset_ms(event, start));
+                                let right_offset = right
+                                    .zip(newer_start)
+                                    .map(|(event, start)| offset_ms(event, start));
+                                let slower = left_offset
+                                    .zip(right_offset)
+                                    .is_some_and(|(left, right)| {
+                                        (left - right).abs() >= TIMING_THRESHOLD_MS
+                                    });
+                                view! {
+                                    <tr class:bg-yellow-50=left.is_none() || right.is_none()>
+                                        <EventCell
+                                            event=left.cloned()
+                                            offset=left_offset
+                                            other=right.cloned()
+                                            slower=slower
+                                        />
+                                        <EventCell
+                                            event=right.cloned()
+                                            offset=right_offset
+                                            other=left.cloned()
+                                            slower=slower
+                                        />
+                                    </tr>
+                                }
+                            })
+                            .collect_view()}
+                    </tbody>
+                </table>
+            </div>
+        </div>
+    }
+}
+
+#[component]
+fn AttemptHeader(span: Span) -> impl IntoView {
+    view! {
+        <th scope="col" class="w-1/2 px-6 py-3 text-start">
+            <div class="flex items-center gap-x-2">
+                <span class="text-xs font-semibold uppercase tracking-wide text-gray-800 dark:text-gray-200">
+                    {span.started_at().map(|date| date.format_date_time()).unwrap_or_default()}
+                </span>
+                <OutcomeBadge outcome=span.outcome()/>
+            </div>
+        </th>
+    }
+}
+
+#[component]
+fn EventCell(
+    event: Option<TraceEvent>,
+    offset: Option<i64>,
+    other: Option<TraceEvent>,
+    slower: bool,
+) -> impl IntoView {
+    let Some(event) = event else {
+        return view! {
+            <td class="px-6 py-3 align-top">
+                <span class="text-sm italic text-gray-400">Not present</span>
+            </td>
+        }
+        .into_view();
+    };
+    let stage = stage_name(&event.typ).to_string();
+    let details = event
+        .data
+        .iter()
+        .map(|(key, value)| {
+            let changed = other
+                .as_ref()
+                .is_some_and(|other| other.data.get(key) != Some(value));
+            (format!("{key}: {}", value_to_string(value)), changed)
+        })
+        .collect::<Vec<_>>();
+    let text = if event.text.is_empty() {
+        event.typ
+    } else {
+        event.text
+    };
+
+    view! {
+        <td class="px-6 py-3 align-top">
+            <div class="flex items-center gap-x-2">
+                <span
+                    class="text-xs font-mono"
+                    class:text-gray-500=!slower
+                    class:text-red-600=slower
+                >
+                    {offset.map(format_offset).unwrap_or_default()}
+                </span>
+                <span class="text-xs text-gray-500">{stage}</span>
+            </div>
+            <p class="text-sm text-gray-800 dark:text-gray-200">{text}</p>
+            <ul class="text-xs text-gray-500 break-all">
+                {details
+                    .into_iter()
+                    .map(|(detail, changed)| {
+                        view! {
+                            <li
+                                class:text-red-600=changed
+                                class:font-semibold=changed
+                            >
+                                {detail}
+                            </li>
+                        }
+                    })
+                    .collect_view()}
+            </ul>
+        </td>
+    }
+    .into_view()
+}
+
+impl Span {
+    fn started_at(&self) -> Option<DateTime<Utc>> {
+        self.events.iter().map(|event| event.created_at).min()
+    }
+
+    fn recipients(&self) -> Vec<String> {
+        let mut recipients = Vec::new();
+        for event in &self.events {
+            for key in ["to", "rcptTo"] {
+                let addresses = match event.data.get(key) {
+                    Some(serde_json::Value::Array(list)) => {
+                        list.iter().map(value_to_string).collect()
+                    }
+                    Some(value) => vec![value_to_string(value)],
+                    None => Vec::new(),
+                };
+                for address in addresses {
+                    if !recipients.contains(&address) {
+                        recipients.push(address);
+                    }
+                }
+            }
+        }
+        recipients
+    }
+
+    fn value(&self, keys: &[&str]) -> Option<String> {
+        self.events
+            .iter()
+            .find_map(|event| keys.iter().find_map(|key| event.value(key)))
+    }
+
+    fn outcome(&self) -> Option<TrackingStatus> {
+        self.events
+            .iter()
+            .rev()
+            .find_map(|event| event_status(&event.typ))
+    }
+}
+
+/// Renders the spans as CSV or JSON, returning the file extension, content
+/// type and contents.
+fn export_spans(spans: &[Span], format: ExportFormat) -> (&'static str, &'static str, String) {
+    let spans = spans
+        .iter()
+        .map(|span| ExportedSpan {
+            id: span.id.clone(),
+            typ: if span
+                .events
+                .iter()
+                .any(|event| event.typ.starts_with("delivery."))
+            {
+                "delivery"
+            } else {
+                "received"
+            },
+            date: span.started_at().map(|date| date.to_rfc3339()),
+            queue_id: span.value(&["queueId"]),
+            message_id: span.value(&["messageId"]),
+            from: span.value(&["from"]),
+            to: span.recipients(),
+            status: match span.outcome() {
+                Some(TrackingStatus::Accepted) => "accepted",
+                Some(TrackingStatus::Deferred) => "deferred",
+                Some(TrackingStatus::Delivered) => "delivered",
+                Some(TrackingStatus::Bounced) => "bounced",
+                Some(TrackingStatus::Rejected) => "rejected",
+                None => "in-progress",
+            },
+        })
+        .collect::<Vec<_>>();
+
+    match format {
+        ExportFormat::Json => (
+            "json",
+            "application/json",
+            serde_json::to_string_pretty(&spans).unwrap_or_default(),
+        ),
+        ExportFormat::Csv => {
+            let mut csv = String::from("id,type,date,queue_id,message_id,from,to,status\r\n");
+            for span in spans {
+                let row = [
+                    span.id,
+                    span.typ.to_string(),
+                    span.date.unwrap_or_default(),
+                    span.queue_id.unwrap_or_default(),
+                    span.message_id.unwrap_or_default(),
+                    span.from.unwrap_or_default(),
+                    span.to.join(" "),
+                    span.status.to_string(),
+                ];
+                csv.push_str(
+                    &row.iter()
+                        .map(|field| csv_escape(field))
+                        .collect::<Vec<_>>()
+                        .join(","),
+                );
+                csv.push_str("\r\n");
+            }
+            ("csv", "text/csv", csv)
+        }
+    }
+}
+
+/// Pairs up the events of two attempts using the longest common
+/// subsequence of their types, unmatched events get an empty counterpart.
+fn align_events(left: &[TraceEvent], right: &[TraceEvent]) -> Vec<(Option<usize>, Option<usize>)> {
+    let mut lengths = vec![vec![0usize; right.len() + 1]; left.len() + 1];
+    for i in (0..left.len()).rev() {
+        for j in (0..right.len()).rev() {
+            lengths[i][j] = if left[i].typ == right[j].typ {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut rows = Vec::with_capacity(left.len().max(right.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < left.len() && j < right.len() {
+        if left[i].typ == right[j].typ {
+            rows.push((Some(i), Some(j)));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            rows.push((Some(i), None));
+            i += 1;
+        } else {
+            rows.push((None, Some(j)));
+            j += 1;
+        }
+    }
+    rows.extend((i..left.len()).map(|i| (Some(i), None)));
+    rows.extend((j..right.len()).map(|j| (None, Some(j))));
+    rows
+}
+
+fn offset_ms(event: &TraceEvent, start: DateTime<Utc>) -> i64 {
+    (event.created_at - start).num_milliseconds()
+}
+
+fn format_offset(offset: i64) -> String {
+    if offset >= 1000 {
+        format!("+{:.1}s", offset as f64 / 1000.0)
+    } else {
+        format!("+{offset}ms")
+    }
+}
@@ -0,0 +1,399 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use chrono::{DateTime, Utc};
+use chrono_humanize::HumanTime;
+use leptos::*;
+use leptos_router::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        badge::Badge,
+        icon::IconRefresh,
+        list::{
+            header::ColumnList, pagination::Pagination, toolbar::ToolbarButton, Footer, ListItem,
+            ListSection, ListTable, ListTextItem, Toolbar, ZeroResults,
+        },
+        messages::{
+            alert::{use_alerts, Alert},
+            modal::{use_modals, Modal},
+        },
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        permissions::{use_capability, Capability},
+        url::UrlBuilder,
+    },
+    pages::{config::Settings, FormatDateTime, List},
+};
+
+const PAGE_SIZE: u32 = 50;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct LookupEntry {
+    key: String,
+    #[serde(default)]
+    value: Option<String>,
+    #[serde(default)]
+    counter: Option<i64>,
+    #[serde(default)]
+    expires: Option<DateTime<Utc>>,
+}
+
+/// Browses the keys held in a lookup store, such as the ones set by
+/// `key_set` or incremented by `counter_incr` in expressions.
+#[component]
+pub fn StoreInspector() -> impl IntoView {
+    let query = use_query_map();
+    let page = create_memo(move |_| {
+        query
+            .with(|q| q.get("page").and_then(|page| page.parse::<u32>().ok()))
+            .filter(|&page| page > 0)
+            .unwrap_or(1)
+    });
+    let store = create_memo(move |_| {
+        query
+            .with(|q| q.get("store").cloned())
+            .filter(|store| !store.is_empty())
+    });
+    let pattern = create_memo(move |_| {
+        query
+            .with(|q| q.get("pattern").map(|pattern| pattern.trim().to_string()))
+            .filter(|pattern| !pattern.is_empty())
+    });
+
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let modal = use_modals();
+
+    let stores = create_resource(
+        move || (),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move {
+                HttpRequest::get("/api/settings/group")
+                    .with_authorization(&auth)
+                    .with_parameter("prefix", "store")
+                    .with_parameter("suffix", "type")
+                    .with_parameter("field", "_id")
+                    .send::<List<Settings>>()
+                    .await
+                    .map(|stores| {
+                        stores
+                            .items
+                            .into_iter()
+                            .filter_map(|mut store| store.remove("_id"))
+                            .collect::<Vec<_>>()
+                    })
+            }
+        },
+    );
+
+    let entries = create_resource(
+        move || (store.get(), pattern.get(), page.get()),
+        move |(store, pattern, page)| {
+            let auth = auth.get_untracked();
+
+            async move {
+                if let Some(pattern) = pattern {
+                    HttpRequest::get("/api/store/lookup")
+                        .with_authorization(&auth)
+                        .with_optional_parameter("store", store)
+                        .with_parameter("pattern", pattern)
+                        .with_parameter("page", page.to_string())
+                        .with_parameter("limit", PAGE_SIZE.to_string())
+                        .send::<List<LookupEntry>>()
+                        .await
+                        .map(Some)
+                } else {
+                    Ok(None)
+                }
+            }
+        },
+    );
+
+    let delete_action = create_action(move |key: &String| {
+        let auth = auth.get();
+        let key = key.clone();
+        let store = store.get();
+
+        async move {
+            match HttpRequest::delete("/api/store/lookup")
+                .with_authorization(&auth)
+                .with_optional_parameter("store", store)
+                .with_parameter("key", &key)
+                .send::<()>()
+                .await
+            {
+                Ok(_) => {
+                    alert.set(Alert::success(format!("Deleted key {key}.")));
+                    entries.refetch();
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+    let on_delete = Callback::new(move |key: String| {
+        modal.set(
+            Modal::with_title("Confirm deletion")
+                .with_message(format!(
+                    "Are you sure you want to delete the key \"{key}\"? Expressions relying on it will behave as if it was never set."
+                ))
+                .with_button("Delete key")
+                .with_dangerous_callback(move || {
+                    delete_action.dispatch(key.clone());
+                }),
+        );
+    });
+
+    let total_results = create_rw_signal(None::<u32>);
+    let navigate_with = move |store: Option<String>, pattern: Option<String>| {
+        use_navigate()(
+            &UrlBuilder::new("/manage/troubleshoot/store")
+                .with_optional_parameter("store", store.filter(|store| !store.is_empty()))
+                .with_optional_parameter(
+                    "pattern",
+                    pattern
+                        .map(|pattern| pattern.trim().to_string())
+                        .filter(|pattern| !pattern.is_empty()),
+                )
+                .finish(),
+            Default::default(),
+        );
+    };
+    let input_class = "py-2 px-3 block border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600";
+
+    view! {
+        <ListSection>
+            <ListTable
+                title="In-memory store"
+                subtitle="Inspect the keys and counters used by expressions"
+            >
+                <Toolbar slot>
+                    <select
+                        class=format!("{input_class} pe-9")
+                        on:change=move |ev| {
+                            navigate_with(Some(event_target_value(&ev)), pattern.get());
+                        }
+                    >
+
+                        <option value="" selected=move || store.get().is_none()>
+                            Default lookup store
+                        </option>
+                        {move || {
+                            stores
+                                .get()
+                                .and_then(|stores| stores.ok())
+                                .unwrap_or_default()
+                                .into_iter()
+                                .map(|id| {
+                                    let selected_id = id.clone();
+                                    view! {
+                                        <option
+                                            value=id.clone()
+                                            selected=move || {
+                                                store.get().as_deref() == Some(selected_id.as_str())
+                                            }
+                                        >
+
+                                            {id}
+                                        </option>
+                                    }
+                                })
+                                .collect_view()
+                        }}
+
+                    </select>
+                    <input
+                        type="text"
+                        class=input_class
+                        placeholder="Key or glob, e.g. rate:*"
+                        prop:value=move || pattern.get().unwrap_or_default()
+                        on:change=move |ev| {
+                            navigate_with(store.get(), Some(event_target_value(&ev)));
+                        }
+                    />
+
+                    <ToolbarButton
+                        text="Refresh"
+                        color=Color::Gray
+                        on_click=move |_| {
+                            entries.refetch();
+                        }
+                    >
+
+                        <IconRefresh/>
+                    </ToolbarButton>
+                </Toolbar>
+
+                <Transition fallback=Skeleton>
+                    {move || match entries.get() {
+                        None => None,
+                        Some(Err(http::Error::Unauthorized)) => {
+                            use_navigate()("/login", Default::default());
+                            Some(view! { <div></div> }.into_view())
+                        }
+                        Some(Err(http::Error::Forbidden)) => {
+                            total_results.set(Some(0));
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="Permission required"
+                                        subtitle="Inspecting the in-memory store requires the purge-in-memory-store permission."
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Err(err)) => {
+                            total_results.set(Some(0));
+                            alert.set(Alert::from(err));
+                            Some(view! { <Skeleton/> }.into_view())
+                        }
+                        Some(Ok(None)) => {
+                            total_results.set(Some(0));
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="Enter a key"
+                                        subtitle="Look up a single key or use * and ? to match several keys."
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Ok(Some(entries))) if !entries.items.is_empty() => {
+                            total_results.set(Some(entries.total as u32));
+                            Some(
+                                view! {
+                                    <ColumnList headers=vec![
+                                        "Key".to_string(),
+                                        "Value".to_string(),
+                                        "Expires".to_string(),
+                                        "".to_string(),
+                                    ]>
+
+                                        <For
+                                            each=move || entries.items.clone()
+                                            key=|entry| entry.key.clone()
+                                            let:entry
+                                        >
+                                            <LookupEntryItem
+                                                entry
+                                                on_delete=on_delete
+                                                pending=delete_action.pending()
+                                            />
+                                        </For>
+
+                                    </ColumnList>
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Ok(Some(_))) => {
+                            total_results.set(Some(0));
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="No results"
+                                        subtitle="No keys matching this pattern were found."
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                    }}
+
+                </Transition>
+
+                <Footer slot>
+
+                    <Pagination
+                        current_page=page
+                        total_results=total_results.read_only()
+                        page_size=PAGE_SIZE
+                        on_page_change=move |page: u32| {
+                            use_navigate()(
+                                &UrlBuilder::new("/manage/troubleshoot/store")
+                                    .with_parameter("page", page.to_string())
+                                    .with_optional_parameter("store", store.get())
+                                    .with_optional_parameter("pattern", pattern.get())
+                                    .finish(),
+                                Default::default(),
+                            );
+                        }
+                    />
+
+                </Footer>
+            </ListTable>
+        </ListSection>
+    }
+}
+
+#[component]
+fn LookupEntryItem(
+    entry: LookupEntry,
+    on_delete: Callback<String>,
+    #[prop(into)] pending: Signal<bool>,
+) -> impl IntoView {
+    let capability = use_capability(Capability::InMemoryStoreDelete);
+    let key = store_value(entry.key.clone());
+    let value = match (entry.counter, entry.value) {
+        (Some(counter), _) => view! {
+            <div class="flex items-center gap-x-2">
+                <Badge color=Color::Blue>Counter</Badge>
+                <span class="text-sm font-mono text-gray-800 dark:text-gray-200">{counter}</span>
+            </div>
+        }
+        .into_view(),
+        (None, Some(value)) => view! {
+            <span class="text-sm font-mono text-gray-800 text-wrap break-all dark:text-gray-200">
+                {value}
+            </span>
+        }
+        .into_view(),
+        (None, None) => view! { <Badge color=Color::Gray>Key only</Badge> }.into_view(),
+    };
+    let expires = entry
+        .expires
+        .map(|date| format!("{} ({})", date.format_date_time(), HumanTime::from(date)))
+        .unwrap_or_else(|| "Never".to_string());
+
+    view! {
+        <tr>
+            <ListItem>
+                <span class="text-sm font-mono font-semibold text-gray-800 dark:text-gray-200">
+                    {entry.key}
+                </span>
+            </ListItem>
+            <ListItem>{value}</ListItem>
+            <ListTextItem>{expires}</ListTextItem>
+            <ListItem subclass="px-6 py-1.5">
+                <button
+                    type="button"
+                    class="text-sm text-red-600 decoration-2 hover:underline font-medium disabled:opacity-50 disabled:pointer-events-none dark:text-red-500"
+                    disabled=move || capability.is_denied() || pending.get()
+                    title=move || capability.reason()
+                    class=(["disabled:pointer-events-auto", "disabled:cursor-not-allowed"], move || capability.is_denied())
+                    on:click=move |_| on_delete.call(key.get_value())
+                >
+
+                    Delete
+                </button>
+            </ListItem>
+        </tr>
+    }
+}
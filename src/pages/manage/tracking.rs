@@ -0,0 +1,478 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::collections::BTreeMap;
+
+use ahash::{AHashMap, AHashSet};
+use chrono::{DateTime, Utc};
+use leptos::*;
+use leptos_router::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        badge::Badge,
+        icon::IconArrowsRightLeft,
+        list::{
+            header::ColumnList,
+            toolbar::{SearchBox, ToolbarButton},
+            Footer, ListItem, ListSection, ListTable, Toolbar, ZeroResults,
+        },
+        messages::alert::{use_alerts, Alert},
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::{use_authorization, AuthToken},
+        url::UrlBuilder,
+    },
+    pages::{maybe_plural, queue::messages::deserialize_datetime, FormatDateTime, List},
+};
+
+const MAX_SPANS: u32 = 100;
+pub(crate) const SPAN_RECEIVED: &str = "message-ingest";
+pub(crate) const SPAN_DELIVERY: &str = "delivery";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub(crate) struct Span {
+    pub id: String,
+    pub events: Vec<TraceEvent>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub(crate) struct TraceEvent {
+    #[serde(rename = "type")]
+    pub typ: String,
+    #[serde(deserialize_with = "deserialize_datetime")]
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub text: String,
+    #[serde(default)]
+    pub data: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TrackingStatus {
+    Accepted,
+    Deferred,
+    Delivered,
+    Bounced,
+    Rejected,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct TrackedMessage {
+    id: String,
+    queue_id: Option<String>,
+    from: Option<String>,
+    to: Vec<String>,
+    status: TrackingStatus,
+    received_span: Option<String>,
+    events: Vec<TraceEvent>,
+}
+
+#[component]
+pub fn MessageTracking() -> impl IntoView {
+    let query = use_query_map();
+    let filter = create_memo(move |_| {
+        query.with(|q| {
+            q.get("filter").and_then(|s| {
+                let s = s.trim();
+                if !s.is_empty() {
+                    Some(s.to_string())
+                } else {
+                    None
+                }
+            })
+        })
+    });
+
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let messages = create_resource(
+        move || filter.get(),
+        move |filter| {
+            let auth = auth.get_untracked();
+
+            async move {
+                match filter {
+                    Some(filter) => track_messages(&auth, &filter).await,
+                    None => Ok(Vec::new()),
+                }
+            }
+        },
+    );
+
+    let total_results = create_rw_signal(None::<usize>);
+
+    view! {
+        <ListSection>
+            <ListTable
+                title="Message tracking"
+                subtitle="Follow a message from acceptance to final delivery by message-id, sender or recipient"
+            >
+                <Toolbar slot>
+                    <SearchBox
+                        value=filter
+                        on_search=move |value| {
+                            use_navigate()(
+                                &UrlBuilder::new("/manage/tracking")
+                                    .with_parameter("filter", value)
+                                    .finish(),
+                                Default::default(),
+                            );
+                        }
+                    />
+
+                    <ToolbarButton
+                        text="Compare attempts"
+
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| {
+                            use_navigate()(
+                                &UrlBuilder::new("/manage/tracking/attempts")
+                                    .with_optional_parameter("filter", filter.get())
+                                    .finish(),
+                                Default::default(),
+                            );
+                        })
+                    >
+
+                        <IconArrowsRightLeft/>
+                    </ToolbarButton>
+
+                </Toolbar>
+
+                <Transition fallback=Skeleton>
+                    {move || match messages.get() {
+                        None => None,
+                        Some(Err(http::Error::Unauthorized)) => {
+                            use_navigate()("/login", Default::default());
+                            Some(view! { <div></div> }.into_view())
+                        }
+                        Some(Err(http::Error::NotFound)) => {
+                            total_results.set(Some(0));
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="Tracing not available"
+                                        subtitle="Enable the tracing history store to search for messages."
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Err(err)) => {
+                            total_results.set(Some(0));
+                            alert.set(Alert::from(err));
+                            Some(view! { <Skeleton/> }.into_view())
+                        }
+                        Some(Ok(messages)) if !messages.is_empty() => {
+                            total_results.set(Some(messages.len()));
+                            Some(
+                                view! {
+                                    <ColumnList headers=vec![
+                                        "Date".to_string(),
+                                        "Stage".to_string(),
+                                        "Event".to_string(),
+                                    ]>
+
+                                        <For
+                                            each=move || messages.clone()
+                                            key=|message| message.id.clone()
+                                            let:message
+                                        >
+                                            <TrackedMessageItem message/>
+                                        </For>
+
+                                    </ColumnList>
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Ok(_)) => {
+                            let subtitle = if filter.get().is_some() {
+                                "No messages were found matching the search criteria."
+                            } else {
+                                "Enter a message-id, sender or recipient address to search."
+                            };
+                            total_results.set(Some(0));
+                            Some(
+                                view! { <ZeroResults title="No results" subtitle=subtitle/> }
+                                    .into_view(),
+                            )
+                        }
+                    }}
+
+                </Transition>
+
+                <Footer slot>
+                    <div class="px-6 py-4 border-t border-gray-200 dark:border-gray-700">
+                        <p class="text-sm text-gray-600 dark:text-gray-400">
+                            {move || {
+                                total_results
+                                    .get()
+                                    .map(|total| maybe_plural(total, "message", "messages"))
+                                    .unwrap_or_default()
+                            }}
+
+                        </p>
+                    </div>
+                </Footer>
+            </ListTable>
+        </ListSection>
+    }
+}
+
+#[component]
+fn TrackedMessageItem(message: TrackedMessage) -> impl IntoView {
+    let (status_color, status_text) = match message.status {
+        TrackingStatus::Accepted => (Color::Blue, "Accepted"),
+        TrackingStatus::Deferred => (Color::Yellow, "Deferred"),
+        TrackingStatus::Delivered => (Color::Green, "Delivered"),
+        TrackingStatus::Bounced => (Color::Red, "Bounced"),
+        TrackingStatus::Rejected => (Color::Red, "Rejected"),
+    };
+    let summary = match (&message.from, message.to.is_empty()) {
+        (Some(from), false) => format!("{from} → {}", message.to.join(", ")),
+        (Some(from), true) => from.clone(),
+        (None, false) => message.to.join(", "),
+        (None, true) => String::new(),
+    };
+    let queue_id = message.queue_id.clone();
+    let received_span = message.received_span.clone();
+
+    view! {
+        <tr class="bg-gray-50 dark:bg-slate-800">
+            <td colspan="3" class="px-6 py-3">
+                <div class="flex items-center gap-x-3">
+                    <Badge color=status_color>{status_text}</Badge>
+                    <span class="text-sm font-semibold text-gray-800 dark:text-gray-200">
+                        {summary}
+                    </span>
+
+                    {queue_id
+                        .map(|queue_id| {
+                            view! {
+                                <span class="text-xs text-gray-500">
+                                    {format!("Queue ID {queue_id}")}
+                                </span>
+                            }
+                        })}
+                    {received_span
+                        .map(|span_id| {
+                            view! {
+                                <a
+                                    class="ms-auto text-xs text-blue-600 decoration-2 hover:underline font-medium dark:text-blue-500"
+                                    href=UrlBuilder::new("/manage/spam/explain")
+                                        .with_parameter("span", span_id)
+                                        .finish()
+                                >
+                                    Spam score
+                                </a>
+                            }
+                        })}
+
+                </div>
+            </td>
+        </tr>
+
+        {message
+            .events
+            .into_iter()
+            .map(|event| {
+                let details = event
+                    .data
+                    .iter()
+                    .map(|(key, value)| format!("{key}: {}", value_to_string(value)))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let stage = stage_name(&event.typ).to_string();
+                let text = if event.text.is_empty() { event.typ } else { event.text };
+                view! {
+                    <tr>
+                        <ListItem>
+                            <span class="text-sm text-gray-500">
+                                {event.created_at.format_date_time()}
+                            </span>
+                        </ListItem>
+                        <ListItem>
+                            <span class="text-sm text-gray-500">{stage}</span>
+                        </ListItem>
+                        <ListItem>
+                            <div class="flex flex-col">
+                                <span class="text-sm text-gray-800 dark:text-gray-200">
+                                    {text}
+                                </span>
+                                <span class="text-xs text-gray-500 text-wrap">{details}</span>
+                            </div>
+                        </ListItem>
+                    </tr>
+                }
+            })
+            .collect_view()}
+    }
+}
+
+/// Searches received-message and delivery-attempt spans and merges those
+/// belonging to the same queue id into a single timeline.
+async fn track_messages(auth: &AuthToken, filter: &str) -> http::Result<Vec<TrackedMessage>> {
+    let mut spans = AHashMap::new();
+    for typ in [SPAN_RECEIVED, SPAN_DELIVERY] {
+        for span in search_spans(auth, typ, filter).await? {
+            spans.insert(span.id.clone(), span);
+        }
+    }
+
+    // A message-id search only matches the received span and an address search
+    // may only match some of the delivery attempts, fetch the missing halves
+    // using the queue id.
+    let queue_ids = spans
+        .values()
+        .filter_map(|span| span.queue_id())
+        .collect::<AHashSet<_>>();
+    for queue_id in queue_ids {
+        for typ in [SPAN_RECEIVED, SPAN_DELIVERY] {
+            for span in search_spans(auth, typ, &queue_id).await? {
+                if span.queue_id().as_deref() == Some(queue_id.as_str()) {
+                    spans.insert(span.id.clone(), span);
+                }
+            }
+        }
+    }
+
+    let mut messages: AHashMap<String, TrackedMessage> = AHashMap::new();
+    for (span_id, span) in spans {
+        let queue_id = span.queue_id();
+        let is_received = span
+            .events
+            .iter()
+            .any(|event| event.typ.starts_with("message-ingest."));
+        let message = messages
+            .entry(queue_id.clone().unwrap_or(span_id.clone()))
+            .or_insert_with(|| TrackedMessage {
+                id: queue_id.clone().unwrap_or(span_id.clone()),
+                queue_id,
+                from: None,
+                to: Vec::new(),
+                status: TrackingStatus::Accepted,
+                received_span: None,
+                events: Vec::new(),
+            });
+        if is_received {
+            message.received_span = Some(span_id);
+        }
+        message.events.extend(span.events);
+    }
+
+    let mut messages = messages
+        .into_values()
+        .map(|mut message| {
+            message.events.sort_by_key(|event| event.created_at);
+            for event in &message.events {
+                if message.from.is_none() {
+                    message.from = event.value("from");
+                }
+                for key in ["to", "rcptTo"] {
+                    if let Some(to) = event.data.get(key) {
+                        let addresses = match to {
+                            serde_json::Value::Array(list) => {
+                                list.iter().map(value_to_string).collect()
+                            }
+                            value => vec![value_to_string(value)],
+                        };
+                        for address in addresses {
+                            if !message.to.contains(&address) {
+                                message.to.push(address);
+                            }
+                        }
+                    }
+                }
+                if let Some(status) = event_status(&event.typ) {
+                    message.status = status;
+                }
+            }
+            message
+        })
+        .collect::<Vec<_>>();
+    messages.sort_by(|a, b| {
+        b.events
+            .first()
+            .map(|e| e.created_at)
+            .cmp(&a.events.first().map(|e| e.created_at))
+    });
+
+    Ok(messages)
+}
+
+pub(crate) async fn search_spans(
+    auth: &AuthToken,
+    typ: &str,
+    filter: &str,
+) -> http::Result<Vec<Span>> {
+    HttpRequest::get("/api/telemetry/traces")
+        .with_authorization(auth)
+        .with_parameter("type", typ)
+        .with_parameter("filter", filter)
+        .with_parameter("values", "1")
+        .with_parameter("limit", MAX_SPANS.to_string())
+        .send::<List<Span>>()
+        .await
+        .map(|spans| spans.items)
+}
+
+impl Span {
+    fn queue_id(&self) -> Option<String> {
+        self.events.iter().find_map(|event| event.value("queueId"))
+    }
+}
+
+impl TraceEvent {
+    pub fn value(&self, key: &str) -> Option<String> {
+        self.data.get(key).map(value_to_string)
+    }
+}
+
+pub(crate) fn event_status(typ: &str) -> Option<TrackingStatus> {
+    if typ.starts_with("delivery.") {
+        if typ.contains("delivered") || typ.ends_with("completed") {
+            Some(TrackingStatus::Delivered)
+        } else if typ.contains("permanent") || typ.contains("perm-fail") {
+            Some(TrackingStatus::Bounced)
+        } else if typ.contains("temporary") || typ.contains("temp-fail") {
+            Some(TrackingStatus::Deferred)
+        } else {
+            None
+        }
+    } else if typ.starts_with("message-ingest.") {
+        Some(TrackingStatus::Accepted)
+    } else if typ.contains("reject") {
+        Some(TrackingStatus::Rejected)
+    } else {
+        None
+    }
+}
+
+pub(crate) fn stage_name(typ: &str) -> &str {
+    match typ.split_once('.').map_or(typ, |(prefix, _)| prefix) {
+        "message-ingest" => "Received",
+        "smtp" => "SMTP session",
+        "queue" => "Queue",
+        "delivery" => "Delivery",
+        "dsn" => "DSN",
+        "spam" => "Spam filter",
+        "sieve" => "Sieve",
+        _ => typ,
+    }
+}
+
+pub(crate) fn value_to_string(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
@@ -0,0 +1,668 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::sync::Arc;
+
+use ahash::AHashMap;
+use leptos::*;
+use leptos_router::{use_navigate, use_query_map};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        form::{
+            button::Button, input::InputText, Form, FormButtonBar, FormElement, FormItem,
+            FormSection,
+        },
+        icon::{IconCheckCircle, IconClock, IconExclamationTriangle},
+        messages::alert::{use_alerts, Alert},
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        permissions::Capability,
+        schema::{Builder, Schemas, Transformer, Type, Validator},
+        stream::EventStream,
+        url::UrlBuilder,
+    },
+    pages::directory::domains::DnsRecord,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+struct DeliveryStage {
+    #[serde(rename = "type")]
+    typ: String,
+    #[serde(default)]
+    elapsed: Option<u64>,
+    #[serde(default)]
+    reason: Option<String>,
+    #[serde(flatten)]
+    details: AHashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Step {
+    id: usize,
+    stage: String,
+    details: Vec<String>,
+    elapsed: Option<u64>,
+    state: StepState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StepState {
+    Running,
+    Success,
+    Failed,
+}
+
+#[component]
+pub fn TroubleshootDelivery() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let steps = create_rw_signal(Vec::<Step>::new());
+    let is_running = create_rw_signal(false);
+    let stream = store_value(None::<EventStream>);
+    on_cleanup(move || stream.set_value(None));
+
+    let mut data = expect_context::<Arc<Schemas>>().build_form("troubleshoot-delivery");
+    data.apply_defaults(false);
+    let data = data.into_signal();
+
+    let start = create_action(move |target: &String| {
+        let auth = auth.get();
+        let target = target.trim().to_string();
+
+        async move {
+            stream.set_value(None);
+            steps.set(Vec::new());
+            is_running.set(true);
+
+            // Event streams cannot carry an Authorization header, obtain a
+            // short-lived token instead
+            let token = match HttpRequest::get("/api/troubleshoot/token")
+                .with_authorization(&auth)
+                .send::<String>()
+                .await
+            {
+                Ok(token) => token,
+                Err(err) => {
+                    is_running.set(false);
+                    match err {
+                        http::Error::Unauthorized => {
+                            use_navigate()("/login", Default::default());
+                        }
+                        err => {
+                            alert.set(Alert::from(err));
+                        }
+                    }
+                    return;
+                }
+            };
+            let url = UrlBuilder::new(format!("{}/api/troubleshoot/delivery", auth.base_url))
+                .with_subpath(&target)
+                .with_parameter("token", token)
+                .finish();
+
+            stream.set_value(EventStream::open(
+                &url,
+                move |message| {
+                    let stages =
+                        serde_json::from_str::<Vec<DeliveryStage>>(&message).or_else(|_| {
+                            serde_json::from_str::<DeliveryStage>(&message).map(|s| vec![s])
+                        });
+                    match stages {
+                        Ok(stages) => {
+                            for stage in stages {
+                                if stage.typ == "Completed" {
+                                    is_running.set(false);
+                                    stream.with_value(|stream| {
+                                        if let Some(stream) = stream {
+                                            stream.close();
+                                        }
+                                    });
+                                } else {
+                                    steps.update(|steps| stage.apply(steps));
+                                }
+                            }
+                        }
+                        Err(err) => {
+                            log::warn!("Failed to parse delivery stage {message:?}: {err}");
+                        }
+                    }
+                },
+                move || {
+                    // The server closes the stream once the test is finished
+                    is_running.set(false);
+                    stream.with_value(|stream| {
+                        if let Some(stream) = stream {
+                            stream.close();
+                        }
+                    });
+                },
+            ));
+            if stream.with_value(|stream| stream.is_none()) {
+                is_running.set(false);
+                alert.set(Alert::error(
+                    "Failed to connect to the troubleshooting service",
+                ));
+            }
+        }
+    });
+
+    view! {
+        <Form
+            title="Troubleshoot delivery"
+            subtitle="Trace each step of the delivery to a remote address"
+        >
+
+            <FormSection>
+                <FormItem label="Destination" tooltip="E-mail address or domain name to deliver to">
+                    <InputText
+                        element=FormElement::new("target", data)
+                        placeholder="user@example.org"
+                    />
+                </FormItem>
+            </FormSection>
+
+            <Show when=move || !steps.get().is_empty() || is_running.get()>
+                <FormSection title="Delivery steps".to_string()>
+                    <div class="sm:col-span-12">
+                        <ol class="flex flex-col gap-y-3">
+                            <For
+                                each=move || steps.get()
+                                key=|step| (step.id, step.state as u8, step.details.len())
+                                let:step
+                            >
+                                <DeliveryStep step/>
+                            </For>
+                        </ol>
+                        <Show when=move || is_running.get()>
+                            <p class="mt-3 text-sm text-gray-500 animate-pulse">
+                                Waiting for the server...
+                            </p>
+                        </Show>
+                    </div>
+                </FormSection>
+            </Show>
+
+            <FormButtonBar>
+                <Button
+                    text=Signal::derive(move || {
+                        if is_running.get() { "Running..." } else { "Start" }.to_string()
+                    })
+
+                    color=Color::Blue
+                    on_click=Callback::new(move |_| {
+                        data.update(|data| {
+                            if data.validate_form() {
+                                start.dispatch(data.value::<String>("target").unwrap_or_default());
+                            }
+                        });
+                    })
+
+                    disabled=is_running
+                    capability=Capability::Troubleshoot
+                />
+            </FormButtonBar>
+
+        </Form>
+    }
+}
+
+#[component]
+fn DeliveryStep(step: Step) -> impl IntoView {
+    let icon = match step.state {
+        StepState::Running => view! {
+            <IconClock attr:class="flex-shrink-0 size-5 text-gray-400 animate-pulse"/>
+        },
+        StepState::Success => view! {
+            <IconCheckCircle attr:class="flex-shrink-0 size-5 text-teal-500"/>
+        },
+        StepState::Failed => view! {
+            <IconExclamationTriangle attr:class="flex-shrink-0 size-5 text-red-500"/>
+        },
+    };
+    let details_class = if step.state == StepState::Failed {
+        "text-sm text-red-600 dark:text-red-500 break-all"
+    } else {
+        "text-sm text-gray-500 break-all"
+    };
+
+    view! {
+        <li class="flex gap-x-3">
+            {icon}
+            <div class="grow">
+                <div class="flex justify-between gap-x-2">
+                    <span class="text-sm font-medium text-gray-800 dark:text-gray-200">
+                        {step.stage}
+                    </span>
+                    <span class="text-xs text-gray-500">
+                        {step.elapsed.map(|elapsed| format!("{elapsed} ms"))}
+                    </span>
+                </div>
+                {step
+                    .details
+                    .into_iter()
+                    .map(|detail| view! { <p class=details_class>{detail}</p> })
+                    .collect_view()}
+            </div>
+        </li>
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct DnsCheck {
+    check: String,
+    name: String,
+    typ: String,
+    expected: String,
+    found: Vec<String>,
+    status: CheckStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum CheckStatus {
+    Pass,
+    Mismatch,
+    Missing,
+}
+
+#[component]
+pub fn TroubleshootDns() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let query = use_query_map();
+    let domain = create_memo(move |_| {
+        query.with(|q| {
+            q.get("domain")
+                .map(|domain| domain.trim().to_lowercase())
+                .filter(|domain| !domain.is_empty())
+        })
+    });
+
+    let mut data = expect_context::<Arc<Schemas>>().build_form("troubleshoot-dns");
+    if let Some(domain) = domain.get_untracked() {
+        data.set("domain", domain);
+    }
+    let data = data.into_signal();
+
+    let checks = create_resource(
+        move || domain.get(),
+        move |domain| {
+            let auth = auth.get_untracked();
+
+            async move {
+                let Some(domain) = domain else {
+                    return Ok(None);
+                };
+                let expected = HttpRequest::get(("/api/domain", &domain))
+                    .with_authorization(&auth)
+                    .send::<Vec<DnsRecord>>()
+                    .await?;
+
+                let mut checks = Vec::with_capacity(expected.len());
+                for record in expected {
+                    let found = match HttpRequest::get("/api/troubleshoot/dns")
+                        .with_authorization(&auth)
+                        .with_parameter("name", record.name.as_str())
+                        .with_parameter("type", record.typ.as_str())
+                        .send::<Vec<String>>()
+                        .await
+                    {
+                        Ok(found) => found,
+                        Err(http::Error::NotFound) => Vec::new(),
+                        Err(err) => return Err(err),
+                    };
+                    checks.push(DnsCheck::new(record, found));
+                }
+
+                Ok(Some(checks))
+            }
+        },
+    );
+
+    view! {
+        <Form title="Troubleshoot DNS" subtitle="Validate the DNS records of a local domain">
+
+            <FormSection>
+                <FormItem label="Domain" tooltip="Local domain name to validate">
+                    <InputText element=FormElement::new("domain", data) placeholder="example.org"/>
+                </FormItem>
+            </FormSection>
+
+            <Transition fallback=Skeleton>
+                {move || match checks.get() {
+                    None | Some(Ok(None)) => None,
+                    Some(Err(http::Error::Unauthorized)) => {
+                        use_navigate()("/login", Default::default());
+                        None
+                    }
+                    Some(Err(http::Error::NotFound)) => {
+                        alert.set(Alert::error("Domain not found"));
+                        None
+                    }
+                    Some(Err(err)) => {
+                        alert.set(Alert::from(err));
+                        None
+                    }
+                    Some(Ok(Some(checks))) => {
+                        let passed = checks
+                            .iter()
+                            .filter(|check| check.status == CheckStatus::Pass)
+                            .count();
+                        let title = format!("Results ({passed}/{} passed)", checks.len());
+                        Some(
+                            view! {
+                                <FormSection title=title>
+                                    <div class="sm:col-span-12">
+                                        <ol class="flex flex-col gap-y-4">
+                                            {checks
+                                                .into_iter()
+                                                .map(|check| view! { <DnsCheckItem check/> })
+                                                .collect_view()}
+                                        </ol>
+                                    </div>
+                                </FormSection>
+                            }
+                                .into_view(),
+                        )
+                    }
+                }}
+
+            </Transition>
+
+            <FormButtonBar>
+                <Button
+                    text="Check"
+                    color=Color::Blue
+                    on_click=Callback::new(move |_| {
+                        let target = data
+                            .try_update(|data| {
+                                data.validate_form()
+                                    .then(|| data.value::<String>("domain").unwrap_or_default())
+                            })
+                            .flatten();
+                        if let Some(target) = target {
+                            if domain.get_untracked().as_ref() == Some(&target) {
+                                checks.refetch();
+                            } else {
+                                use_navigate()(
+                                    &UrlBuilder::new("/manage/troubleshoot/dns")
+                                        .with_parameter("domain", target)
+                                        .finish(),
+                                    Default::default(),
+                                );
+                            }
+                        }
+                    })
+
+                    disabled=checks.loading()
+                />
+            </FormButtonBar>
+
+        </Form>
+    }
+}
+
+#[component]
+fn DnsCheckItem(check: DnsCheck) -> impl IntoView {
+    let (icon, hint) = match check.status {
+        CheckStatus::Pass => (
+            view! { <IconCheckCircle attr:class="flex-shrink-0 size-5 text-teal-500"/> },
+            None,
+        ),
+        CheckStatus::Mismatch => (
+            view! {
+                <IconExclamationTriangle attr:class="flex-shrink-0 size-5 text-yellow-500"/>
+            },
+            Some(format!(
+                "The published {} record differs from the recommended value:",
+                check.typ
+            )),
+        ),
+        CheckStatus::Missing => (
+            view! { <IconExclamationTriangle attr:class="flex-shrink-0 size-5 text-red-500"/> },
+            Some(format!(
+                "Publish a {} record for {} with the following value:",
+                check.typ, check.name
+            )),
+        ),
+    };
+    let found = if check.found.is_empty() {
+        "No records found".to_string()
+    } else {
+        format!("Found: {}", check.found.join(" | "))
+    };
+    let expected = check.expected;
+
+    view! {
+        <li class="flex gap-x-3">
+            {icon}
+            <div class="grow min-w-0">
+                <div class="flex justify-between gap-x-2">
+                    <span class="text-sm font-medium text-gray-800 dark:text-gray-200">
+                        {check.check}
+                    </span>
+                    <span class="text-xs text-gray-500 truncate">{check.name}</span>
+                </div>
+                <p class="text-sm text-gray-500 break-all">{found}</p>
+                {hint
+                    .map(|hint| {
+                        view! {
+                            <p class="mt-1 text-sm text-gray-800 dark:text-gray-200">{hint}</p>
+                            <pre class="mt-1 p-2 text-xs bg-gray-100 rounded-lg whitespace-pre-wrap break-all dark:bg-slate-800 dark:text-gray-300">
+                                {expected}
+                            </pre>
+                        }
+                    })}
+            </div>
+        </li>
+    }
+}
+
+impl DnsCheck {
+    fn new(record: DnsRecord, found: Vec<String>) -> Self {
+        let content = normalize_record(&record.content);
+        let txt_tag = |value: &str| {
+            value
+                .split(';')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_ascii_lowercase()
+        };
+        let name = record.name.trim_end_matches('.').to_lowercase();
+
+        let (check, status) = if record.typ == "TXT" && content.starts_with("v=spf1") {
+            // Every mechanism in the recommended policy must be published
+            let status = match found
+                .iter()
+                .map(|value| normalize_record(value))
+                .find(|value| value.starts_with("v=spf1"))
+            {
+                Some(value) => {
+                    let published = value.split_whitespace().collect::<Vec<_>>();
+                    if content
+                        .split_whitespace()
+                        .filter(|term| !term.ends_with("all"))
+                        .all(|term| published.contains(&term))
+                    {
+                        CheckStatus::Pass
+                    } else {
+                        CheckStatus::Mismatch
+                    }
+                }
+                None => CheckStatus::Missing,
+            };
+            ("SPF", status)
+        } else if record.typ == "TXT" && name.contains("._domainkey.") {
+            // Compare the public keys
+            let public_key = |value: &str| {
+                value
+                    .split(';')
+                    .find_map(|tag| tag.trim().strip_prefix("p="))
+                    .map(|key| key.split_whitespace().collect::<String>())
+            };
+            let expected_key = public_key(&content);
+            let status = if found.is_empty() {
+                CheckStatus::Missing
+            } else if found
+                .iter()
+                .any(|value| public_key(&normalize_record(value)) == expected_key)
+            {
+                CheckStatus::Pass
+            } else {
+                CheckStatus::Mismatch
+            };
+            ("DKIM", status)
+        } else if record.typ == "TXT" {
+            // DMARC, MTA-STS and TLS-RPT policies may be customized, only the
+            // record version is required to match
+            let check = if name.starts_with("_dmarc.") {
+                "DMARC"
+            } else if name.starts_with("_mta-sts.") {
+                "MTA-STS"
+            } else if name.starts_with("_smtp._tls.") {
+                "TLS-RPT"
+            } else {
+                "TXT"
+            };
+            let expected_tag = txt_tag(&content);
+            let status = if found.iter().any(|value| normalize_record(value) == content) {
+                CheckStatus::Pass
+            } else if found
+                .iter()
+                .any(|value| txt_tag(&normalize_record(value)) == expected_tag)
+            {
+                CheckStatus::Mismatch
+            } else {
+                CheckStatus::Missing
+            };
+            (check, status)
+        } else {
+            let status = if found
+                .iter()
+                .any(|value| normalize_record(value).eq_ignore_ascii_case(&content))
+            {
+                CheckStatus::Pass
+            } else if found.is_empty() {
+                CheckStatus::Missing
+            } else {
+                CheckStatus::Mismatch
+            };
+            (record.typ.as_str(), status)
+        };
+
+        DnsCheck {
+            check: check.to_string(),
+            name,
+            typ: record.typ.clone(),
+            expected: record.content,
+            found,
+            status,
+        }
+    }
+}
+
+fn normalize_record(value: &str) -> String {
+    value
+        .split(|c: char| c == '"' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .map(|part| part.trim_end_matches('.'))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl DeliveryStage {
+    fn apply(self, steps: &mut Vec<Step>) {
+        let (stage, state) = if let Some(stage) = self.typ.strip_suffix("Start") {
+            (stage, StepState::Running)
+        } else if let Some(stage) = self.typ.strip_suffix("Success") {
+            (stage, StepState::Success)
+        } else if let Some(stage) = self.typ.strip_suffix("Error") {
+            (stage, StepState::Failed)
+        } else {
+            (self.typ.as_str(), StepState::Success)
+        };
+        let stage = stage_name(stage);
+        let mut details = self
+            .details
+            .iter()
+            .map(|(key, value)| match value {
+                serde_json::Value::String(value) => format!("{key}: {value}"),
+                value => format!("{key}: {value}"),
+            })
+            .collect::<Vec<_>>();
+        details.sort();
+        details.extend(self.reason);
+
+        // Completion events update the step that was started last
+        if state != StepState::Running {
+            if let Some(step) = steps
+                .iter_mut()
+                .rev()
+                .find(|step| step.stage == stage && step.state == StepState::Running)
+            {
+                step.state = state;
+                step.elapsed = self.elapsed;
+                step.details.extend(details);
+                return;
+            }
+        }
+
+        steps.push(Step {
+            id: steps.len(),
+            stage,
+            details,
+            elapsed: self.elapsed,
+            state,
+        });
+    }
+}
+
+fn stage_name(stage: &str) -> String {
+    match stage {
+        "MxLookup" => "MX lookup",
+        "MxIpLookup" => "MX address lookup",
+        "MtaStsFetch" => "MTA-STS policy",
+        "TlsRptLookup" => "TLS reporting policy",
+        "DaneLookup" => "DANE TLSA lookup",
+        "DaneVerify" => "DANE verification",
+        "DeliveryAttempt" => "Delivery attempt",
+        "Connection" => "Connect",
+        "ReadGreeting" => "Greeting",
+        "Ehlo" => "EHLO",
+        "StartTls" => "STARTTLS",
+        "MailFrom" => "MAIL FROM",
+        "RcptTo" => "RCPT TO",
+        "Quit" => "QUIT",
+        stage => return stage.to_string(),
+    }
+    .to_string()
+}
+
+impl Builder<Schemas, ()> {
+    pub fn build_troubleshoot(self) -> Self {
+        self.new_schema("troubleshoot-delivery")
+            .new_field("target")
+            .typ(Type::Input)
+            .input_check([Transformer::Trim], [Validator::Required])
+            .build()
+            .build()
+            .new_schema("troubleshoot-dns")
+            .new_field("domain")
+            .typ(Type::Input)
+            .input_check(
+                [Transformer::Trim, Transformer::Lowercase],
+                [Validator::Required, Validator::IsDomain],
+            )
+            .build()
+            .build()
+    }
+}
@@ -0,0 +1,356 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use std::cmp::Ordering;
+
+use chrono::{DateTime, Utc};
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        badge::Badge,
+        icon::IconComputerDesktop,
+        messages::alert::{use_alerts, Alert},
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        permissions::{use_capability, Capability},
+        service_worker::check_for_update,
+    },
+    pages::FormatDateTime,
+};
+
+const SELECT_CLASS: &str = "py-2 px-3 pe-9 block border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600";
+
+/// Version of the webadmin currently running.
+const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Channel {
+    Stable,
+    Beta,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct WebadminRelease {
+    version: String,
+    #[serde(default)]
+    published: Option<DateTime<Utc>>,
+    #[serde(default)]
+    prerelease: bool,
+    #[serde(default)]
+    changelog: String,
+}
+
+/// Lists the webadmin releases available on the selected channel and installs
+/// the chosen one. The changelog of every release between the running version
+/// and the selected one is shown so the admin knows what is about to change.
+#[component]
+pub fn UpdateWebadminPanel(#[prop(into)] on_close: Callback<()>) -> impl IntoView {
+    let capability = use_capability(Capability::WebadminUpdate);
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let channel = create_rw_signal(Channel::Stable);
+    let selected = create_rw_signal(None::<String>);
+
+    let releases = create_resource(
+        move || channel.get(),
+        move |channel| {
+            let auth = auth.get_untracked();
+
+            async move {
+                HttpRequest::get("/api/update/webadmin/releases")
+                    .with_authorization(&auth)
+                    .with_parameter("channel", channel.as_str())
+                    .send::<Vec<WebadminRelease>>()
+                    .await
+                    .map(|mut releases| {
+                        releases.sort_by(|a, b| compare_versions(&b.version, &a.version));
+                        releases
+                    })
+            }
+        },
+    );
+
+    // Default to the newest release whenever the channel changes
+    create_effect(move |_| {
+        if let Some(Ok(releases)) = releases.get() {
+            selected.set(releases.first().map(|release| release.version.clone()));
+        }
+    });
+
+    let install = create_action(move |version: &Option<String>| {
+        let auth = auth.get();
+        let version = version.clone();
+
+        async move {
+            match HttpRequest::get("/api/update/webadmin")
+                .with_authorization(&auth)
+                .with_optional_parameter("version", version.clone())
+                .send::<Option<String>>()
+                .await
+            {
+                Ok(_) => {
+                    alert.set(
+                        Alert::success(match version {
+                            Some(version) => {
+                                format!("Successfully updated the web admin to version {version}")
+                            }
+                            None => "Successfully updated the web admin to the latest version"
+                                .to_string(),
+                        })
+                        .without_timeout(),
+                    );
+                    check_for_update();
+                    on_close.call(());
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    view! {
+        <div class="mt-6 p-4 md:p-5 bg-white border shadow-sm rounded-xl dark:bg-slate-900 dark:border-gray-800">
+            <div class="flex items-start gap-x-4">
+                <IconComputerDesktop attr:class="mt-1 flex-shrink-0 size-5 text-gray-800 dark:text-gray-200"/>
+                <div class="grow">
+                    <div class="flex flex-wrap items-center justify-between gap-2">
+                        <div>
+                            <h3 class="font-semibold text-gray-800 dark:text-gray-200">
+                                Update Webadmin
+                            </h3>
+                            <p class="text-sm text-gray-500">
+                                {format!("Currently running version {CURRENT_VERSION}.")}
+                            </p>
+                        </div>
+                        <select
+                            class=SELECT_CLASS
+                            on:change=move |ev| {
+                                channel
+                                    .set(
+                                        if event_target_value(&ev) == "beta" {
+                                            Channel::Beta
+                                        } else {
+                                            Channel::Stable
+                                        },
+                                    );
+                            }
+                        >
+
+                            <option value="stable" selected=move || channel.get() == Channel::Stable>
+                                Stable channel
+                            </option>
+                            <option value="beta" selected=move || channel.get() == Channel::Beta>
+                                Beta channel
+                            </option>
+                        </select>
+                    </div>
+
+                    <Transition>
+                        {move || match releases.get() {
+                            None => None,
+                            Some(Err(http::Error::Unauthorized)) => {
+                                use_navigate()("/login", Default::default());
+                                None
+                            }
+                            Some(Err(http::Error::NotFound)) => {
+                                Some(
+                                    view! {
+                                        <p class="mt-4 text-sm text-gray-500">
+                                            This server does not list the available releases, only the latest version can be installed.
+                                        </p>
+                                    }
+                                        .into_view(),
+                                )
+                            }
+                            Some(Err(err)) => {
+                                alert.set(Alert::from(err));
+                                None
+                            }
+                            Some(Ok(releases)) if releases.is_empty() => {
+                                Some(
+                                    view! {
+                                        <p class="mt-4 text-sm text-gray-500">
+                                            There are no releases available on this channel.
+                                        </p>
+                                    }
+                                        .into_view(),
+                                )
+                            }
+                            Some(Ok(releases)) => {
+                                let options = releases.clone();
+                                Some(
+                                    view! {
+                                        <div class="mt-4 flex items-center gap-x-2">
+                                            <label class="text-sm text-gray-500">Version</label>
+                                            <select
+                                                class=SELECT_CLASS
+                                                on:change=move |ev| {
+                                                    selected.set(Some(event_target_value(&ev)));
+                                                }
+                                            >
+
+                                                {options
+                                                    .into_iter()
+                                                    .map(|release| {
+                                                        let version = release.version.clone();
+                                                        let is_current = compare_versions(
+                                                                &version,
+                                                                CURRENT_VERSION,
+                                                            )
+                                                            .is_eq();
+                                                        view! {
+                                                            <option
+                                                                value=version.clone()
+                                                                selected=move || {
+                                                                    selected.with(|v| v.as_deref() == Some(version.as_str()))
+                                                                }
+                                                            >
+
+                                                                {release.version.clone()}
+                                                                {is_current.then_some(" (installed)")}
+                                                                {release.prerelease.then_some(" (beta)")}
+                                                            </option>
+                                                        }
+                                                    })
+                                                    .collect_view()}
+                                            </select>
+                                        </div>
+                                        {move || {
+                                            let target = selected.get().unwrap_or_default();
+                                            render_changelog(&releases, &target)
+                                        }}
+                                    }
+                                        .into_view(),
+                                )
+                            }
+                        }}
+
+                    </Transition>
+                </div>
+            </div>
+            <div class="mt-4 flex justify-end gap-x-2">
+                <button
+                    type="button"
+                    class="py-1.5 px-2.5 inline-flex items-center gap-x-1.5 text-sm font-medium rounded-lg border border-gray-200 bg-white text-gray-800 shadow-sm hover:bg-gray-50 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-white dark:hover:bg-gray-800"
+                    on:click=move |_| on_close.call(())
+                >
+                    Cancel
+                </button>
+                <button
+                    type="button"
+                    class="py-1.5 px-2.5 inline-flex items-center gap-x-1.5 text-sm font-semibold rounded-lg border border-transparent bg-blue-600 text-white hover:bg-blue-700 disabled:opacity-50 disabled:pointer-events-none"
+                    disabled=move || capability.is_denied() || install.pending().get()
+                    title=move || capability.reason()
+                    class=(["disabled:pointer-events-auto", "disabled:cursor-not-allowed"], move || capability.is_denied())
+                    on:click=move |_| install.dispatch(selected.get())
+                >
+                    {move || match selected.get() {
+                        Some(version) => format!("Install {version}"),
+                        None => "Install latest".to_string(),
+                    }}
+
+                </button>
+            </div>
+        </div>
+    }
+}
+
+/// Renders the changelog of the releases between the running version and
+/// `target`, or a notice when `target` is not newer than the running version.
+fn render_changelog(releases: &[WebadminRelease], target: &str) -> View {
+    let changes = releases
+        .iter()
+        .filter(|release| {
+            compare_versions(&release.version, CURRENT_VERSION).is_gt()
+                && compare_versions(&release.version, target).is_le()
+        })
+        .collect::<Vec<_>>();
+
+    if changes.is_empty() {
+        let message = if compare_versions(target, CURRENT_VERSION).is_lt() {
+            "The selected version is older than the running version, installing it will downgrade the web admin."
+        } else {
+            "The selected version is already installed."
+        };
+        return view! { <p class="mt-4 text-sm text-gray-500">{message}</p> }.into_view();
+    }
+
+    view! {
+        <div class="mt-4 max-h-96 overflow-y-auto divide-y divide-gray-200 border border-gray-200 rounded-lg dark:divide-gray-700 dark:border-gray-700">
+            {changes
+                .into_iter()
+                .map(|release| {
+                    view! {
+                        <div class="p-3">
+                            <div class="flex items-center gap-x-2">
+                                <h4 class="text-sm font-semibold text-gray-800 dark:text-gray-200">
+                                    {release.version.clone()}
+                                </h4>
+                                {release
+                                    .prerelease
+                                    .then(|| view! { <Badge color=Color::Yellow>Beta</Badge> })}
+                                {release
+                                    .published
+                                    .map(|published| {
+                                        view! {
+                                            <span class="text-xs text-gray-500">
+                                                {published.format_date_time()}
+                                            </span>
+                                        }
+                                    })}
+
+                            </div>
+                            <pre class="mt-2 text-xs whitespace-pre-wrap text-gray-600 dark:text-gray-400">
+                                {release.changelog.clone()}
+                            </pre>
+                        </div>
+                    }
+                })
+                .collect_view()}
+        </div>
+    }
+    .into_view()
+}
+
+/// Compares two version strings numerically component by component. A
+/// pre-release such as `0.2.0-beta.1` sorts before the `0.2.0` release.
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    fn parse(version: &str) -> (Vec<u64>, bool) {
+        let version = version.trim().trim_start_matches('v');
+        let (numbers, pre) = version
+            .split_once('-')
+            .map_or((version, None), |(numbers, pre)| (numbers, Some(pre)));
+        (
+            numbers
+                .split('.')
+                .map(|n| n.parse().unwrap_or_default())
+                .collect(),
+            pre.is_none(),
+        )
+    }
+
+    parse(a).cmp(&parse(b))
+}
+
+impl Channel {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Channel::Stable => "stable",
+            Channel::Beta => "beta",
+        }
+    }
+}
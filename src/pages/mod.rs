@@ -23,6 +23,13 @@ pub struct List<T> {
     pub total: u64,
 }
 
+pub const RETENTION_PERIODS: &[(i64, &str)] = &[
+    (30, "Older than 30 days"),
+    (90, "Older than 90 days"),
+    (180, "Older than 180 days"),
+    (365, "Older than 1 year"),
+];
+
 pub fn maybe_plural(items: usize, singular: &str, plural: &str) -> String {
     if items == 1 {
         format!("{} {}", items, singular)
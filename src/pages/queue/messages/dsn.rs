@@ -0,0 +1,389 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use leptos::*;
+use leptos_router::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        badge::Badge,
+        icon::IconRefresh,
+        list::{
+            header::ColumnList, toolbar::ToolbarButton, Footer, ListItem, ListSection, ListTable,
+            Toolbar, ZeroResults,
+        },
+        messages::alert::{use_alerts, Alert},
+        skeleton::Skeleton,
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::{use_authorization, AuthToken},
+        url::UrlBuilder,
+    },
+    pages::{maybe_plural, queue::messages::Message, FormatDateTime, List},
+};
+
+const PAGE_SIZE: u32 = 100;
+const MAX_MESSAGES: usize = 1000;
+const MAX_NOTIFICATIONS: usize = 50;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Notification {
+    message: Message,
+    report: DeliveryReport,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct DeliveryReport {
+    envelope_id: Option<String>,
+    message_id: Option<String>,
+    recipients: Vec<RecipientStatus>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct RecipientStatus {
+    recipient: String,
+    action: String,
+    status: String,
+    diagnostic: Option<String>,
+}
+
+#[component]
+pub fn DsnList() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let total_results = create_rw_signal(None::<usize>);
+
+    let notifications = create_resource(
+        || (),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move { fetch_notifications(&auth).await }
+        },
+    );
+
+    view! {
+        <ListSection>
+            <ListTable
+                title="Delivery status notifications"
+                subtitle="Bounces and delay notifications waiting in the queue"
+            >
+                <Toolbar slot>
+                    <ToolbarButton
+                        text="Reload"
+
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| {
+                            notifications.refetch();
+                        })
+                    >
+
+                        <IconRefresh/>
+                    </ToolbarButton>
+                </Toolbar>
+
+                <Transition fallback=Skeleton>
+                    {move || match notifications.get() {
+                        None => None,
+                        Some(Err(http::Error::Unauthorized)) => {
+                            use_navigate()("/login", Default::default());
+                            Some(view! { <div></div> }.into_view())
+                        }
+                        Some(Err(err)) => {
+                            total_results.set(Some(0));
+                            alert.set(Alert::from(err));
+                            Some(view! { <Skeleton/> }.into_view())
+                        }
+                        Some(Ok(notifications)) if !notifications.is_empty() => {
+                            total_results.set(Some(notifications.len()));
+                            Some(
+                                view! {
+                                    <ColumnList headers=vec![
+                                        "Recipient".to_string(),
+                                        "Action".to_string(),
+                                        "Status".to_string(),
+                                        "Diagnostic".to_string(),
+                                    ]>
+
+                                        <For
+                                            each=move || notifications.clone()
+                                            key=|notification| notification.message.id
+                                            let:notification
+                                        >
+                                            <NotificationItem notification/>
+                                        </For>
+
+                                    </ColumnList>
+                                }
+                                    .into_view(),
+                            )
+                        }
+                        Some(Ok(_)) => {
+                            total_results.set(Some(0));
+                            Some(
+                                view! {
+                                    <ZeroResults
+                                        title="No results"
+                                        subtitle="There are no delivery status notifications in the queue."
+                                    />
+                                }
+                                    .into_view(),
+                            )
+                        }
+                    }}
+
+                </Transition>
+
+                <Footer slot>
+                    <div class="px-6 py-4 border-t border-gray-200 dark:border-gray-700">
+                        <p class="text-sm text-gray-600 dark:text-gray-400">
+                            {move || {
+                                total_results
+                                    .get()
+                                    .map(|total| maybe_plural(total, "notification", "notifications"))
+                                    .unwrap_or_default()
+                            }}
+
+                        </p>
+                    </div>
+                </Footer>
+            </ListTable>
+        </ListSection>
+    }
+}
+
+#[component]
+fn NotificationItem(notification: Notification) -> impl IntoView {
+    let message = notification.message;
+    let report = notification.report;
+    let to = message
+        .domains
+        .iter()
+        .flat_map(|domain| domain.recipients.iter().map(|rcpt| rcpt.address.as_str()))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let trace_filter = report
+        .message_id
+        .clone()
+        .or_else(|| report.recipients.first().map(|rcpt| rcpt.recipient.clone()));
+    let is_empty = report.recipients.is_empty();
+
+    view! {
+        <tr class="bg-gray-50 dark:bg-slate-800">
+            <td colspan="4" class="px-6 py-3">
+                <div class="flex items-center gap-x-3">
+                    <span class="text-sm font-semibold text-gray-800 dark:text-gray-200">
+                        {format!("To {to}")}
+                    </span>
+                    <span class="text-xs text-gray-500">{message.created.format_date_time()}</span>
+                    {report
+                        .envelope_id
+                        .map(|envelope_id| {
+                            view! {
+                                <span class="text-xs text-gray-500">
+                                    {format!("Envelope ID {envelope_id}")}
+                                </span>
+                            }
+                        })}
+
+                    <div class="ms-auto flex items-center gap-x-3">
+                        {trace_filter
+                            .map(|filter| {
+                                view! {
+                                    <a
+                                        class="text-xs text-blue-600 decoration-2 hover:underline font-medium dark:text-blue-500"
+                                        href=UrlBuilder::new("/manage/tracking")
+                                            .with_parameter("filter", filter)
+                                            .finish()
+                                    >
+                                        Original message
+                                    </a>
+                                }
+                            })}
+                        <a
+                            class="text-xs text-blue-600 decoration-2 hover:underline font-medium dark:text-blue-500"
+                            href=format!("/manage/queue/message/{}", message.id)
+                        >
+                            Notification
+                        </a>
+                    </div>
+                </div>
+            </td>
+        </tr>
+
+        {report
+            .recipients
+            .into_iter()
+            .map(|rcpt| {
+                let color = match rcpt.action.to_lowercase().as_str() {
+                    "failed" => Color::Red,
+                    "delayed" => Color::Yellow,
+                    _ => Color::Green,
+                };
+                view! {
+                    <tr>
+                        <ListItem>
+                            <span class="text-sm text-gray-800 dark:text-gray-200">
+                                {rcpt.recipient}
+                            </span>
+                        </ListItem>
+                        <ListItem>
+                            <Badge color=color>{rcpt.action}</Badge>
+                        </ListItem>
+                        <ListItem>
+                            <span class="text-sm font-mono text-gray-500">{rcpt.status}</span>
+                        </ListItem>
+                        <ListItem>
+                            <span class="text-xs text-gray-500 text-wrap">
+                                {rcpt.diagnostic.unwrap_or_default()}
+                            </span>
+                        </ListItem>
+                    </tr>
+                }
+            })
+            .collect_view()}
+        {is_empty
+            .then(|| {
+                view! {
+                    <tr>
+                        <td colspan="4" class="px-6 py-3 text-sm text-gray-500">
+                            The notification does not include a delivery status report.
+                        </td>
+                    </tr>
+                }
+            })}
+    }
+}
+
+/// Fetches the most recent messages with a null sender in the queue and
+/// parses the delivery status report included in each of them.
+async fn fetch_notifications(auth: &AuthToken) -> http::Result<Vec<Notification>> {
+    let mut messages = Vec::new();
+    let mut total = 0;
+    let mut page = 1;
+
+    loop {
+        let list = HttpRequest::get("/api/queue/messages")
+            .with_authorization(auth)
+            .with_parameter("page", page.to_string())
+            .with_parameter("limit", PAGE_SIZE.to_string())
+            .with_parameter("values", "1")
+            .send::<List<Message>>()
+            .await?;
+        let is_last = list.items.len() < PAGE_SIZE as usize;
+        total += list.items.len();
+        messages.extend(
+            list.items
+                .into_iter()
+                .filter(|message| message.return_path.is_empty()),
+        );
+        if is_last || total as u64 >= list.total || total >= MAX_MESSAGES {
+            break;
+        }
+        page += 1;
+    }
+    messages.sort_by_key(|message| std::cmp::Reverse(message.created));
+    messages.truncate(MAX_NOTIFICATIONS);
+
+    let mut notifications = Vec::with_capacity(messages.len());
+    for message in messages {
+        let report = match HttpRequest::get(("/api/store/blobs", &message.blob_hash))
+            .with_authorization(auth)
+            .send_raw()
+            .await
+        {
+            Ok(bytes) => parse_delivery_report(&String::from_utf8_lossy(&bytes)),
+            Err(http::Error::NotFound) => DeliveryReport::default(),
+            Err(err) => return Err(err),
+        };
+        notifications.push(Notification { message, report });
+    }
+
+    Ok(notifications)
+}
+
+/// Extracts the per-recipient fields of the message/delivery-status part
+/// (RFC 3464) and the message-id of the returned message headers.
+fn parse_delivery_report(contents: &str) -> DeliveryReport {
+    let contents = contents.replace("\r\n", "\n");
+    let mut report = DeliveryReport::default();
+    let lower = contents.to_lowercase();
+
+    let Some(start) = lower.find("message/delivery-status") else {
+        return report;
+    };
+    // Skip the remaining headers of the part
+    let Some(body_start) = contents[start..].find("\n\n").map(|pos| start + pos + 2) else {
+        return report;
+    };
+    let body_end = contents[body_start..]
+        .find("\n--")
+        .map_or(contents.len(), |pos| body_start + pos);
+
+    for (group_num, group) in contents[body_start..body_end]
+        .split("\n\n")
+        .filter(|group| !group.trim().is_empty())
+        .enumerate()
+    {
+        let mut rcpt = RecipientStatus::default();
+        for (name, value) in unfold_fields(group) {
+            match name.as_str() {
+                "original-envelope-id" => report.envelope_id = Some(value),
+                "final-recipient" => rcpt.recipient = strip_address_type(&value),
+                "original-recipient" if rcpt.recipient.is_empty() => {
+                    rcpt.recipient = strip_address_type(&value)
+                }
+                "action" => rcpt.action = value,
+                "status" => rcpt.status = value,
+                "diagnostic-code" => rcpt.diagnostic = Some(strip_address_type(&value)),
+                _ => {}
+            }
+        }
+        if group_num > 0 || !rcpt.action.is_empty() {
+            report.recipients.push(rcpt);
+        }
+    }
+
+    // The returned message or its headers follow the delivery status part
+    report.message_id = unfold_fields(&contents[body_end..])
+        .into_iter()
+        .find(|(name, _)| name == "message-id")
+        .map(|(_, value)| value.trim_matches(['<', '>']).to_string());
+
+    report
+}
+
+/// Splits a block of header fields, joining folded lines and lowercasing the
+/// field names.
+fn unfold_fields(text: &str) -> Vec<(String, String)> {
+    let mut fields: Vec<(String, String)> = Vec::new();
+
+    for line in text.lines() {
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = fields.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            fields.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+
+    fields
+}
+
+/// Removes the type prefix of address and diagnostic fields, as in
+/// "rfc822; john@example.org" or "smtp; 550 5.1.1 User unknown".
+fn strip_address_type(value: &str) -> String {
+    value
+        .split_once(';')
+        .map_or(value, |(_, value)| value)
+        .trim()
+        .to_string()
+}
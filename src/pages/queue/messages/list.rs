@@ -4,21 +4,24 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
+use humansize::{format_size, DECIMAL};
 use leptos::*;
 use leptos_router::*;
-use std::collections::HashSet;
+use std::{cmp::Ordering, collections::HashSet};
 
 use crate::{
     components::{
         badge::Badge,
         icon::{
-            IconAlertTriangle, IconCancel, IconCheckCircle, IconClock, IconLaunch, IconRefresh,
+            IconAlertTriangle, IconCancel, IconCheckCircle, IconClock, IconLaunch, IconPause,
+            IconPlay, IconQueueList, IconRefresh,
         },
         list::{
             header::ColumnList,
-            pagination::Pagination,
+            query::use_list_query,
             row::SelectItem,
             toolbar::{SearchBox, ToolbarButton},
+            virtualized::{LoadedResults, VirtualRows},
             Footer, ListItem, ListSection, ListTable, Toolbar, ZeroResults,
         },
         messages::{
@@ -31,40 +34,26 @@ use crate::{
     core::{
         http::{self, HttpRequest},
         oauth::use_authorization,
-        url::UrlBuilder,
+        permissions::Capability,
     },
     pages::{
         maybe_plural,
-        queue::messages::{Message, Status},
+        queue::messages::{summary::QueueSummary, Message, Status},
         List,
     },
 };
 
 use chrono_humanize::HumanTime;
 
-const PAGE_SIZE: u32 = 10;
+const PAGE_SIZE: u32 = 50;
+const ROW_HEIGHT: f64 = 65.0;
 
 #[component]
 pub fn QueueList() -> impl IntoView {
-    let query = use_query_map();
-    let page = create_memo(move |_| {
-        query
-            .with(|q| q.get("page").and_then(|page| page.parse::<u32>().ok()))
-            .filter(|&page| page > 0)
-            .unwrap_or(1)
-    });
-    let filter = create_memo(move |_| {
-        query.with(|q| {
-            q.get("filter").and_then(|s| {
-                let s = s.trim();
-                if !s.is_empty() {
-                    Some(s.to_string())
-                } else {
-                    None
-                }
-            })
-        })
-    });
+    let list = use_list_query();
+    let filter = list.param("filter");
+    let status = list.param("status");
+    let on_hold = create_memo(move |_| status.get().is_some_and(|status| status == "hold"));
 
     let auth = use_authorization();
     let alert = use_alerts();
@@ -72,9 +61,23 @@ pub fn QueueList() -> impl IntoView {
     let selected = create_rw_signal::<HashSet<String>>(HashSet::new());
     provide_context(selected);
 
+    // Pages are appended to `items` as the list is scrolled
+    let page = create_rw_signal(1u32);
+    let items = create_rw_signal(Vec::<Message>::new());
+    let total_results = create_rw_signal(None::<u32>);
+    let sort = create_rw_signal(list.sort().get_untracked());
+    let sort_param = list.sort();
+    create_effect(move |_| sort.set(sort_param.get()));
+    create_effect(move |_| {
+        let sort = sort.get();
+        if sort != sort_param.get_untracked() {
+            list.set_sort(sort);
+        }
+    });
+
     let messages = create_resource(
-        move || (page.get(), filter.get()),
-        move |(page, filter)| {
+        move || (page.get(), filter.get(), on_hold.get()),
+        move |(page, filter, on_hold)| {
             let auth = auth.get_untracked();
 
             async move {
@@ -83,13 +86,67 @@ pub fn QueueList() -> impl IntoView {
                     .with_parameter("page", page.to_string())
                     .with_parameter("limit", PAGE_SIZE.to_string())
                     .with_parameter("values", "1")
-                    .with_parameter("max-total", "100")
                     .with_optional_parameter("text", filter)
+                    .with_optional_parameter("status", on_hold.then_some("hold"))
                     .send::<List<Message>>()
                     .await
+                    .map(|list| (page, list))
             }
         },
     );
+    let reload = move || {
+        if page.get_untracked() == 1 {
+            messages.refetch();
+        } else {
+            page.set(1);
+        }
+    };
+
+    create_effect(move |prev: Option<(Option<String>, bool)>| {
+        let current = (filter.get(), on_hold.get());
+        if prev.is_some_and(|prev| prev != current) {
+            page.set(1);
+        }
+        current
+    });
+    create_effect(move |_| match messages.get() {
+        None => {}
+        Some(Ok((page, list))) => {
+            total_results.set(Some(list.total as u32));
+            items.update(|items| {
+                if page == 1 {
+                    *items = list.items;
+                } else {
+                    items.extend(list.items);
+                }
+            });
+        }
+        Some(Err(http::Error::Unauthorized)) => {
+            use_navigate()("/login", Default::default());
+        }
+        Some(Err(err)) => {
+            total_results.update(|total| {
+                total.get_or_insert(0);
+            });
+            alert.set(Alert::from(err));
+        }
+    });
+
+    let sorted = create_memo(move |_| {
+        let mut messages = items.get();
+        if let Some(sort) = sort.get() {
+            messages.sort_by(|a, b| {
+                sort.apply(match sort.column {
+                    0 => a.return_path().cmp(b.return_path()),
+                    1 => a.hold.cmp(&b.hold).then_with(|| a.created.cmp(&b.created)),
+                    2 => a.next_retry().cmp(&b.next_retry()),
+                    3 => a.next_dsn().cmp(&b.next_dsn()),
+                    _ => Ordering::Equal,
+                })
+            });
+        }
+        messages
+    });
 
     let cancel_action = create_action(move |items: &HashSet<String>| {
         let items = items.clone();
@@ -115,7 +172,7 @@ pub fn QueueList() -> impl IntoView {
             }
 
             if total_deleted > 0 {
-                messages.refetch();
+                reload();
                 alert.set(Alert::success(format!(
                     "Removed {} from queue.",
                     maybe_plural(total_deleted, "message", "messages")
@@ -147,7 +204,7 @@ pub fn QueueList() -> impl IntoView {
             }
 
             if total_rescheduled > 0 {
-                messages.refetch();
+                reload();
                 alert.set(Alert::success(format!(
                     "Successfully requested immediate delivery of {}.",
                     maybe_plural(total_rescheduled, "message", "messages")
@@ -156,34 +213,144 @@ pub fn QueueList() -> impl IntoView {
         }
     });
 
-    let total_results = create_rw_signal(None::<u32>);
+    let hold_action = create_action(move |(items, hold): &(HashSet<String>, bool)| {
+        let items = items.clone();
+        let hold = *hold;
+        let auth = auth.get();
+
+        async move {
+            let mut total_updated = 0;
+            for id in items {
+                match HttpRequest::patch(("/api/queue/messages", &id))
+                    .with_authorization(&auth)
+                    .with_parameter("hold", hold.to_string())
+                    .send::<bool>()
+                    .await
+                {
+                    Ok(true) => {
+                        total_updated += 1;
+                    }
+                    Ok(false) | Err(http::Error::NotFound) => {}
+                    Err(err) => {
+                        alert.set(Alert::from(err));
+                        return;
+                    }
+                }
+            }
+
+            if total_updated > 0 {
+                reload();
+                let text = maybe_plural(total_updated, "message", "messages");
+                alert.set(Alert::success(if hold {
+                    format!("Placed {text} on hold.")
+                } else {
+                    format!("Released {text} for delivery.")
+                }));
+            } else {
+                reload();
+                alert.set(Alert::warning(
+                    "No messages were updated, they may have been delivered or removed.",
+                ));
+            }
+        }
+    });
 
     view! {
         <ListSection>
-            <ListTable title="Message Queue" subtitle="View, cancel or reschedule queued messages">
+            <QueueSummary/>
+            <ListTable title="Message Queue" subtitle="View, cancel, hold or reschedule queued messages">
                 <Toolbar slot>
                     <SearchBox
                         value=filter
                         on_search=move |value| {
-                            use_navigate()(
-                                &UrlBuilder::new("/manage/queue/messages")
-                                    .with_parameter("filter", value)
-                                    .finish(),
-                                Default::default(),
-                            );
+                            list.set_filters([("filter", Some(value))]);
                         }
                     />
 
                     <ToolbarButton
                         text="Refresh"
 
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| reload())
+                    >
+
+                        <IconRefresh/>
+                    </ToolbarButton>
+
+                    <ToolbarButton
+                        text=Signal::derive(move || {
+                            if on_hold.get() { "Show all" } else { "Show on hold" }.to_string()
+                        })
+
                         color=Color::Gray
                         on_click=Callback::new(move |_| {
-                            messages.refetch();
+                            list.set_filters([
+                                ("status", (!on_hold.get()).then(|| "hold".to_string())),
+                            ]);
                         })
                     >
 
-                        <IconRefresh/>
+                        <IconQueueList/>
+                    </ToolbarButton>
+
+                    <ToolbarButton
+                        text=Signal::derive(move || {
+                            let ns = selected.get().len();
+                            if ns > 0 { format!("Hold ({ns})") } else { "Hold".to_string() }
+                        })
+
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| {
+                            let to_hold = selected.get().len();
+                            if to_hold > 0 {
+                                let text = maybe_plural(to_hold, "message", "messages");
+                                modal
+                                    .set(
+                                        Modal::with_title("Confirm hold")
+                                            .with_message(
+                                                format!(
+                                                    "Are you sure you want to suspend delivery of {text}? Messages on hold will not be delivered until released.",
+                                                ),
+                                            )
+                                            .with_button(format!("Hold {text}"))
+                                            .with_callback(move || {
+                                                hold_action
+                                                    .dispatch((
+                                                        selected.try_update(std::mem::take).unwrap_or_default(),
+                                                        true,
+                                                    ));
+                                            }),
+                                    )
+                            }
+                        })
+
+                        capability=Capability::QueueUpdate
+                    >
+
+                        <IconPause/>
+                    </ToolbarButton>
+
+                    <ToolbarButton
+                        text=Signal::derive(move || {
+                            let ns = selected.get().len();
+                            if ns > 0 { format!("Release ({ns})") } else { "Release".to_string() }
+                        })
+
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| {
+                            if !selected.get().is_empty() {
+                                hold_action
+                                    .dispatch((
+                                        selected.try_update(std::mem::take).unwrap_or_default(),
+                                        false,
+                                    ));
+                            }
+                        })
+
+                        capability=Capability::QueueUpdate
+                    >
+
+                        <IconPlay/>
                     </ToolbarButton>
 
                     <ToolbarButton
@@ -202,6 +369,8 @@ pub fn QueueList() -> impl IntoView {
                                     );
                             }
                         })
+
+                        capability=Capability::QueueUpdate
                     >
 
                         <IconLaunch/>
@@ -215,9 +384,22 @@ pub fn QueueList() -> impl IntoView {
 
                         color=Color::Red
                         on_click=Callback::new(move |_| {
-                            let to_delete = selected.get().len();
-                            if to_delete > 0 {
-                                let text = maybe_plural(to_delete, "message", "messages");
+                            let to_delete = selected.get();
+                            if !to_delete.is_empty() {
+                                let text = maybe_plural(to_delete.len(), "message", "messages");
+                                let confirm_text = if to_delete.len() == 1 {
+                                    to_delete.iter().next().cloned().unwrap_or_default()
+                                } else {
+                                    text.clone()
+                                };
+                                let impact = items
+                                    .with(|items| {
+                                        cancel_impact(
+                                            items.iter().filter(|message| {
+                                                to_delete.contains(&message.id.to_string())
+                                            }),
+                                        )
+                                    });
                                 modal
                                     .set(
                                         Modal::with_title("Confirm deletion")
@@ -227,6 +409,11 @@ pub fn QueueList() -> impl IntoView {
                                                 ),
                                             )
                                             .with_button(format!("Delete {text}"))
+                                            .with_confirmation_text(confirm_text)
+                                            .with_impact(move || {
+                                                let impact = impact.clone();
+                                                async move { Ok(impact) }
+                                            })
                                             .with_dangerous_callback(move || {
                                                 cancel_action
                                                     .dispatch(
@@ -236,6 +423,8 @@ pub fn QueueList() -> impl IntoView {
                                     )
                             }
                         })
+
+                        capability=Capability::QueueDelete
                     >
 
                         <IconCancel/>
@@ -243,87 +432,69 @@ pub fn QueueList() -> impl IntoView {
 
                 </Toolbar>
 
-                <Transition fallback=Skeleton>
-                    {move || match messages.get() {
-                        None => None,
-                        Some(Err(http::Error::Unauthorized)) => {
-                            use_navigate()("/login", Default::default());
-                            Some(view! { <div></div> }.into_view())
-                        }
-                        Some(Err(err)) => {
-                            total_results.set(Some(0));
-                            alert.set(Alert::from(err));
-                            Some(view! { <Skeleton/> }.into_view())
-                        }
-                        Some(Ok(messages)) if !messages.items.is_empty() => {
-                            total_results.set(Some(messages.total as u32));
-                            let messages_ = messages.clone();
-                            Some(
-                                view! {
-                                    <ColumnList
-                                        headers=vec![
-                                            "Envelope".to_string(),
-                                            "Status".to_string(),
-                                            "Next Retry".to_string(),
-                                            "Next DSN".to_string(),
-                                            "".to_string(),
-                                        ]
-
-                                        select_all=Callback::new(move |_| {
-                                            messages_
-                                                .items
-                                                .iter()
-                                                .map(|p| p.id.to_string())
-                                                .collect::<Vec<_>>()
-                                        })
-                                    >
-
-                                        <For
-                                            each=move || messages.items.clone()
-                                            key=|message| message.id
-                                            let:message
-                                        >
-                                            <QueueItem message/>
-                                        </For>
-
-                                    </ColumnList>
-                                }
-                                    .into_view(),
-                            )
+                <Show when=move || total_results.get().is_some() fallback=Skeleton>
+                    <Show
+                        when=move || items.with(|items| !items.is_empty())
+                        fallback=move || {
+                            view! {
+                                <ZeroResults
+                                    title="No results"
+                                    subtitle=if on_hold.get() {
+                                        "No messages on hold were found with the selected criteria."
+                                    } else {
+                                        "No queued messages were found with the selected criteria."
+                                    }
+                                />
+                            }
                         }
-                        Some(Ok(_)) => {
-                            total_results.set(Some(0));
-                            Some(
-                                view! {
-                                    <ZeroResults
-                                        title="No results"
-                                        subtitle="No queued messages were found with the selected criteria."
-                                    />
+                    >
+
+                        <ColumnList
+                            headers=vec![
+                                "Envelope".to_string(),
+                                "Status".to_string(),
+                                "Next Retry".to_string(),
+                                "Next DSN".to_string(),
+                                "".to_string(),
+                            ]
+
+                            select_all=Callback::new(move |_| {
+                                items
+                                    .with(|items| {
+                                        items.iter().map(|p| p.id.to_string()).collect::<Vec<_>>()
+                                    })
+                            })
+
+                            sort=sort
+                        >
+
+                            <VirtualRows
+                                each=sorted
+                                key=|message| message.id
+                                row_height=ROW_HEIGHT
+                                on_end_reached=move |_| {
+                                    let loaded = items.with_untracked(|items| items.len());
+                                    if total_results.get_untracked().unwrap_or_default() as usize
+                                        > loaded && !messages.loading().get_untracked()
+                                    {
+                                        page.update(|page| *page += 1);
+                                    }
                                 }
-                                    .into_view(),
-                            )
-                        }
-                    }}
 
-                </Transition>
+                                let:message
+                            >
+                                <QueueItem message/>
+                            </VirtualRows>
 
-                <Footer slot>
+                        </ColumnList>
+                    </Show>
+                </Show>
 
-                    <Pagination
-                        current_page=page
-                        total_results=total_results.read_only()
-                        page_size=PAGE_SIZE
-                        on_page_change=move |page: u32| {
-                            use_navigate()(
-                                &UrlBuilder::new("/manage/queue/messages")
-                                    .with_parameter("page", page.to_string())
-                                    .with_optional_parameter("filter", filter.get())
-                                    .finish(),
-                                Default::default(),
-                            );
-                        }
+                <Footer slot>
+                    <LoadedResults
+                        loaded=Signal::derive(move || items.with(|items| items.len()))
+                        total_results=total_results
                     />
-
                 </Footer>
             </ListTable>
         </ListSection>
@@ -364,6 +535,7 @@ fn QueueItem(message: Message) -> impl IntoView {
         .map(|dt| HumanTime::from(dt).to_string());
     let next_dsn = message.next_dsn().map(|dt| HumanTime::from(dt).to_string());
     let return_path = message.return_path().to_string();
+    let on_hold = message.hold;
     let recipients = if total_recipients > 0 {
         format!("{first_recipient} and {total_recipients} more",)
     } else {
@@ -394,6 +566,12 @@ fn QueueItem(message: Message) -> impl IntoView {
             <td class="size-px whitespace-nowrap">
                 <div class="px-6 py-3">
                     <div class="inline-flex gap-2 p-1">
+                        <Show when=move || on_hold>
+                            <Badge color=Color::Yellow>
+                                <IconPause attr:class="flex-shrink-0 size-3"/>
+                                On hold
+                            </Badge>
+                        </Show>
                         <Show when=move || {
                             total_success > 0
                         }>{Status::Completed(format!("{total_success} Done"))}</Show>
@@ -456,3 +634,36 @@ impl IntoView for Status {
         }
     }
 }
+
+/// Summarizes the recipients and data affected by cancelling the delivery of
+/// the given messages.
+fn cancel_impact<'x>(messages: impl Iterator<Item = &'x Message>) -> Vec<String> {
+    let mut size = 0;
+    let mut recipients = 0;
+    let mut domains = HashSet::new();
+    for message in messages {
+        size += message.size;
+        for domain in &message.domains {
+            domains.insert(domain.name.as_str());
+            recipients += domain
+                .recipients
+                .iter()
+                .filter(|rcpt| {
+                    matches!(rcpt.status, Status::Scheduled | Status::TemporaryFailure(_))
+                })
+                .count();
+        }
+    }
+
+    vec![
+        format!(
+            "{} to {} will not be delivered.",
+            maybe_plural(recipients, "pending recipient", "pending recipients"),
+            maybe_plural(domains.len(), "domain", "domains")
+        ),
+        format!(
+            "{} of queued messages will be deleted.",
+            format_size(size as u64, DECIMAL)
+        ),
+    ]
+}
@@ -6,7 +6,7 @@
 
 use std::{collections::HashSet, vec};
 
-use chrono::Utc;
+use chrono::{DateTime, Duration, Local, NaiveDateTime, Utc};
 use chrono_humanize::HumanTime;
 use humansize::{format_size, DECIMAL};
 use leptos::*;
@@ -18,7 +18,7 @@ use crate::{
         form::button::Button,
         icon::{
             IconAlertTriangle, IconBell, IconCancel, IconClock, IconEnvelope, IconId, IconLaunch,
-            IconScale,
+            IconPause, IconPlay, IconScale,
         },
         list::{
             header::ColumnList, row::SelectItem, toolbar::ToolbarButton, Footer, ListItem,
@@ -26,7 +26,7 @@ use crate::{
         },
         messages::{
             alert::{use_alerts, Alert, Alerts},
-            modal::{use_modals, Modal},
+            modal::{use_modals, Modal, ModalStep},
         },
         skeleton::Skeleton,
         Color,
@@ -34,6 +34,7 @@ use crate::{
     core::{
         http::{self, HttpRequest},
         oauth::use_authorization,
+        permissions::{use_capability, Capability},
     },
     pages::{
         maybe_plural,
@@ -42,13 +43,31 @@ use crate::{
     },
 };
 
+const RETRY_DELAYS: &[(i64, &str)] = &[
+    (0, "Now"),
+    (5, "In 5 minutes"),
+    (15, "In 15 minutes"),
+    (30, "In 30 minutes"),
+    (60, "In 1 hour"),
+    (240, "In 4 hours"),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RecipientAction {
+    Retry(Option<DateTime<Utc>>),
+    Cancel,
+}
+
 #[component]
 pub fn QueueManage() -> impl IntoView {
+    let update_capability = use_capability(Capability::QueueUpdate);
+    let delete_capability = use_capability(Capability::QueueDelete);
     let auth = use_authorization();
     let alert = use_alerts();
     let modal = use_modals();
     let params = use_params_map();
     let blob_hash = RwSignal::new(String::new());
+    let retry_delay = RwSignal::new(0i64);
     let fetch_headers = RwSignal::new(true);
     let fetch_message = create_resource(
         move || params.get().get("id").cloned().unwrap_or_default(),
@@ -140,12 +159,15 @@ pub fn QueueManage() -> impl IntoView {
         let id = params.get().get("id").cloned().unwrap_or_default();
         let items = items.clone();
         let auth = auth.get();
+        let delay = retry_delay.get();
+        let retry_at = (delay > 0).then(|| (Utc::now() + Duration::minutes(delay)).to_rfc3339());
 
         async move {
             for item in items {
                 match HttpRequest::patch(("/api/queue/messages", &id))
                     .with_authorization(&auth)
                     .with_parameter("filter", item)
+                    .with_optional_parameter("at", retry_at.clone())
                     .send::<bool>()
                     .await
                 {
@@ -158,9 +180,133 @@ pub fn QueueManage() -> impl IntoView {
             }
 
             fetch_message.refetch();
-            alert.set(Alert::success("Successfully requested immediate delivery."));
+            alert.set(Alert::success(if delay > 0 {
+                format!(
+                    "Successfully rescheduled delivery in {}.",
+                    maybe_plural(delay as usize, "minute", "minutes")
+                )
+            } else {
+                "Successfully requested immediate delivery.".to_string()
+            }));
         }
     });
+    let hold_action = create_action(move |hold: &bool| {
+        let id = params.get().get("id").cloned().unwrap_or_default();
+        let hold = *hold;
+        let auth = auth.get();
+
+        async move {
+            match HttpRequest::patch(("/api/queue/messages", &id))
+                .with_authorization(&auth)
+                .with_parameter("hold", hold.to_string())
+                .send::<bool>()
+                .await
+            {
+                Ok(true) | Err(http::Error::NotFound) => {}
+                Ok(false) => {
+                    alert.set(Alert::error(if hold {
+                        "The message could not be placed on hold."
+                    } else {
+                        "The message could not be released for delivery."
+                    }));
+                    return;
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                    return;
+                }
+            }
+
+            fetch_message.refetch();
+            alert.set(Alert::success(if hold {
+                "Message placed on hold."
+            } else {
+                "Message released for delivery."
+            }));
+        }
+    });
+    let recipient_action = create_action(move |(address, action): &(String, RecipientAction)| {
+        let id = params.get().get("id").cloned().unwrap_or_default();
+        let address = address.clone();
+        let action = *action;
+        let auth = auth.get();
+
+        async move {
+            let result = match action {
+                RecipientAction::Retry(retry_at) => {
+                    HttpRequest::patch(("/api/queue/messages", &id))
+                        .with_authorization(&auth)
+                        .with_parameter("filter", &address)
+                        .with_optional_parameter("at", retry_at.map(|dt| dt.to_rfc3339()))
+                        .send::<bool>()
+                        .await
+                }
+                RecipientAction::Cancel => {
+                    HttpRequest::delete(("/api/queue/messages", &id))
+                        .with_authorization(&auth)
+                        .with_parameter("filter", &address)
+                        .send::<bool>()
+                        .await
+                }
+            };
+
+            match result {
+                Ok(_) | Err(http::Error::NotFound) => {
+                    fetch_message.refetch();
+                    alert.set(Alert::success(match action {
+                        RecipientAction::Retry(None) => {
+                            format!("Requested immediate delivery to {address}.")
+                        }
+                        RecipientAction::Retry(Some(retry_at)) => format!(
+                            "Rescheduled delivery to {address} for {}.",
+                            retry_at.format_date_time()
+                        ),
+                        RecipientAction::Cancel => {
+                            format!("Cancelled delivery to {address}.")
+                        }
+                    }));
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+    let reschedule = move |address: String| {
+        let retry_at = create_rw_signal(String::new());
+        let address_ = address.clone();
+        modal.set(
+            Modal::with_title("Reschedule recipient")
+                .with_step(
+                    ModalStep::new("Reschedule recipient")
+                        .with_message(format!("Choose when to retry delivery to {address}."))
+                        .with_content(move || {
+                            view! {
+                                <input
+                                    type="datetime-local"
+                                    class="mt-4 py-2 px-3 block w-full border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                                    prop:value=retry_at
+                                    on:input=move |ev| retry_at.set(event_target_value(&ev))
+                                />
+                            }
+                        })
+                        .with_validator(move || {
+                            parse_local_datetime(&retry_at.get_untracked())
+                                .map(|_| ())
+                                .ok_or_else(|| "Please enter a valid date and time.".to_string())
+                        })
+                        .with_button("Reschedule"),
+                )
+                .with_callback(move || {
+                    if let Some(retry_at) = parse_local_datetime(&retry_at.get_untracked()) {
+                        recipient_action.dispatch((
+                            address_.clone(),
+                            RecipientAction::Retry(Some(retry_at)),
+                        ));
+                    }
+                }),
+        );
+    };
     let selected = create_rw_signal::<HashSet<String>>(HashSet::new());
     provide_context(selected);
 
@@ -191,6 +337,7 @@ pub fn QueueManage() -> impl IntoView {
                         .map(|d| d.recipients.len())
                         .sum::<usize>();
                     let num_domains = message.domains.len();
+                    let on_hold = message.hold;
                     let next_retry = message.next_retry();
                     let next_dsn = message.next_dsn();
                     let expires = message.expires();
@@ -205,7 +352,11 @@ pub fn QueueManage() -> impl IntoView {
                                     if r.status == Status::Scheduled {
                                         r.status = d.status.clone();
                                     }
-                                    (r, d.next_retry)
+                                    let retry_after = r
+                                        .status
+                                        .retry_after()
+                                        .or_else(|| d.status.retry_after());
+                                    (r, d.next_retry, retry_after)
                                 })
                         });
                     Some(
@@ -257,9 +408,13 @@ pub fn QueueManage() -> impl IntoView {
                             <Card>
                                 <CardItem
                                     title="Next Retry"
-                                    contents=next_retry
-                                        .map(|dt| HumanTime::from(dt).to_string())
-                                        .unwrap_or("N/A".to_string())
+                                    contents=if on_hold {
+                                        "On hold".to_string()
+                                    } else {
+                                        next_retry
+                                            .map(|dt| HumanTime::from(dt).to_string())
+                                            .unwrap_or("N/A".to_string())
+                                    }
                                     subcontents=next_retry
                                         .map(|dt| { dt.format_date_time() })
                                         .unwrap_or("N/A".to_string())
@@ -310,16 +465,41 @@ pub fn QueueManage() -> impl IntoView {
                             </Card>
 
                             <div class="max-w-[85rem] px-4 py-8 sm:px-6 lg:px-8 lg:py-10 mx-auto">
-                                <ListTable title="Recipients" subtitle="Retry or cancel delivery">
+                                <ListTable title="Recipients" subtitle="Retry, reschedule, hold or cancel delivery">
                                     <Toolbar slot>
+                                        <select
+                                            class="py-2 px-3 pe-9 block border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                                            on:change=move |ev| {
+                                                retry_delay
+                                                    .set(event_target_value(&ev).parse().unwrap_or_default());
+                                            }
+                                        >
+
+                                            {RETRY_DELAYS
+                                                .iter()
+                                                .map(|(delay, label)| {
+                                                    let delay = *delay;
+                                                    view! {
+                                                        <option
+                                                            value=delay.to_string()
+                                                            selected=move || retry_delay.get() == delay
+                                                        >
+                                                            {*label}
+                                                        </option>
+                                                    }
+                                                })
+                                                .collect_view()}
+
+                                        </select>
                                         <ToolbarButton
                                             text=Signal::derive(move || {
                                                 let ns = selected.get().len();
-                                                if ns > 0 {
-                                                    format!("Retry ({ns})")
+                                                let text = if retry_delay.get() > 0 {
+                                                    "Reschedule"
                                                 } else {
-                                                    "Retry".to_string()
-                                                }
+                                                    "Retry"
+                                                };
+                                                if ns > 0 { format!("{text} ({ns})") } else { text.to_string() }
                                             })
 
                                             color=Color::Gray
@@ -348,6 +528,8 @@ pub fn QueueManage() -> impl IntoView {
                                                         );
                                                 }
                                             })
+
+                                            capability=Capability::QueueUpdate
                                         >
 
                                             <IconLaunch/>
@@ -394,10 +576,43 @@ pub fn QueueManage() -> impl IntoView {
                                                         )
                                                 }
                                             })
+
+                                            capability=Capability::QueueDelete
                                         >
 
                                             <IconCancel/>
                                         </ToolbarButton>
+                                        <ToolbarButton
+                                            text=if on_hold { "Release" } else { "Hold" }
+                                            color=Color::Gray
+                                            on_click=Callback::new(move |_| {
+                                                if on_hold {
+                                                    hold_action.dispatch(false);
+                                                } else {
+                                                    modal
+                                                        .set(
+                                                            Modal::with_title("Confirm hold")
+                                                                .with_message(
+                                                                    "Are you sure you want to suspend delivery of this message? It will not be delivered until released.",
+                                                                )
+                                                                .with_button("Hold message")
+                                                                .with_callback(move || {
+                                                                    hold_action.dispatch(true);
+                                                                }),
+                                                        )
+                                                }
+                                            })
+
+                                            capability=Capability::QueueUpdate
+                                        >
+
+                                            {if on_hold {
+                                                view! { <IconPlay/> }.into_view()
+                                            } else {
+                                                view! { <IconPause/> }.into_view()
+                                            }}
+
+                                        </ToolbarButton>
 
                                     </Toolbar>
                                     <ColumnList
@@ -406,6 +621,7 @@ pub fn QueueManage() -> impl IntoView {
                                             "Status".to_string(),
                                             "Server Response".to_string(),
                                             "Next/Last Retry".to_string(),
+                                            "".to_string(),
                                         ]
 
                                         select_all=Callback::new(move |_| {
@@ -422,9 +638,14 @@ pub fn QueueManage() -> impl IntoView {
                                         <For
                                             each=move || { recipients.clone() }
 
-                                            key=|(recipient, _)| recipient.address.clone()
-                                            children=move |(recipient, next_retry)| {
+                                            key=|(recipient, _, _)| recipient.address.clone()
+                                            children=move |(recipient, next_retry, retry_after)| {
                                                 let item_id = recipient.address.clone();
+                                                let address = store_value(recipient.address.clone());
+                                                let is_pending = matches!(
+                                                    recipient.status,
+                                                    Status::Scheduled | Status::TemporaryFailure(_)
+                                                );
                                                 let mut status_details = recipient
                                                     .status
                                                     .clone()
@@ -451,6 +672,13 @@ pub fn QueueManage() -> impl IntoView {
                                                         }
                                                     })
                                                     .unwrap_or_default();
+                                                let retry_hint = retry_after
+                                                    .map(|delay| {
+                                                        format!(
+                                                            "Remote server requested a retry {}",
+                                                            HumanTime::from(delay),
+                                                        )
+                                                    });
                                                 let display_status = match &recipient.status {
                                                     Status::Completed(_) => {
                                                         Status::Completed("Delivered".into())
@@ -493,11 +721,82 @@ pub fn QueueManage() -> impl IntoView {
                                                             <span class="block text-sm text-gray-500">
                                                                 {status_details}
                                                             </span>
+                                                            {retry_hint
+                                                                .map(|hint| {
+                                                                    view! {
+                                                                        <span class="inline-flex items-center gap-x-1 text-xs text-blue-600 dark:text-blue-500">
+                                                                            <IconClock attr:class="flex-shrink-0 size-3"/>
+                                                                            {hint}
+                                                                        </span>
+                                                                    }
+                                                                })}
 
                                                         </ListItem>
 
                                                         <ListTextItem>{next_retry}</ListTextItem>
 
+                                                        <ListItem subclass="px-6 py-1.5">
+                                                            <Show when=move || is_pending>
+                                                                <div class="inline-flex gap-x-3">
+                                                                    <button
+                                                                        type="button"
+                                                                        class="text-sm text-blue-600 decoration-2 hover:underline font-medium disabled:opacity-50 disabled:pointer-events-none"
+                                                                        disabled=move || update_capability.is_denied() || recipient_action.pending().get()
+                                                                        title=move || update_capability.reason()
+                                                                        class=(["disabled:pointer-events-auto", "disabled:cursor-not-allowed"], move || update_capability.is_denied())
+                                                                        on:click=move |_| {
+                                                                            recipient_action
+                                                                                .dispatch((
+                                                                                    address.get_value(),
+                                                                                    RecipientAction::Retry(None),
+                                                                                ));
+                                                                        }
+                                                                    >
+                                                                        Retry now
+                                                                    </button>
+                                                                    <button
+                                                                        type="button"
+                                                                        class="text-sm text-blue-600 decoration-2 hover:underline font-medium disabled:opacity-50 disabled:pointer-events-none"
+                                                                        disabled=move || update_capability.is_denied() || recipient_action.pending().get()
+                                                                        title=move || update_capability.reason()
+                                                                        class=(["disabled:pointer-events-auto", "disabled:cursor-not-allowed"], move || update_capability.is_denied())
+                                                                        on:click=move |_| reschedule(address.get_value())
+                                                                    >
+                                                                        Reschedule
+                                                                    </button>
+                                                                    <button
+                                                                        type="button"
+                                                                        class="text-sm text-red-600 decoration-2 hover:underline font-medium disabled:opacity-50 disabled:pointer-events-none"
+                                                                        disabled=move || delete_capability.is_denied() || recipient_action.pending().get()
+                                                                        title=move || delete_capability.reason()
+                                                                        class=(["disabled:pointer-events-auto", "disabled:cursor-not-allowed"], move || delete_capability.is_denied())
+                                                                        on:click=move |_| {
+                                                                            let address = address.get_value();
+                                                                            modal
+                                                                                .set(
+                                                                                    Modal::with_title("Confirm cancel")
+                                                                                        .with_message(
+                                                                                            format!(
+                                                                                                "Are you sure you want to cancel delivery to {address}? This action cannot be undone.",
+                                                                                            ),
+                                                                                        )
+                                                                                        .with_button("Cancel recipient")
+                                                                                        .with_dangerous_callback(move || {
+                                                                                            recipient_action
+                                                                                                .dispatch((
+                                                                                                    address.clone(),
+                                                                                                    RecipientAction::Cancel,
+                                                                                                ));
+                                                                                        }),
+                                                                                )
+                                                                        }
+                                                                    >
+                                                                        Cancel
+                                                                    </button>
+                                                                </div>
+                                                            </Show>
+                                                        </ListItem>
+
                                                     </tr>
                                                 }
                                             }
@@ -581,3 +880,13 @@ pub fn QueueManage() -> impl IntoView {
         </Transition>
     }
 }
+
+/// Parses the value of a `datetime-local` input, which is in the browser's
+/// time zone.
+pub(crate) fn parse_local_datetime(value: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M")
+        .ok()?
+        .and_local_timezone(Local)
+        .single()
+        .map(|dt| dt.with_timezone(&Utc))
+}
@@ -4,10 +4,12 @@
  * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
  */
 
+pub mod dsn;
 pub mod list;
 pub mod manage;
+pub mod summary;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Deserializer, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -22,6 +24,8 @@ pub struct Message {
     pub priority: i16,
     pub env_id: Option<String>,
     pub blob_hash: String,
+    #[serde(default)]
+    pub hold: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -44,6 +48,8 @@ pub struct Recipient {
     pub address: String,
     pub status: Status,
     pub orcpt: Option<String>,
+    #[serde(default)]
+    pub queue: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -138,6 +144,64 @@ impl Status {
             Status::Scheduled => "N/A".to_string(),
         }
     }
+
+    /// Parses the retry hint included by remote servers in temporary failure
+    /// responses (e.g. "450 4.7.1 Rate limited, try again in 4 hours").
+    pub fn retry_after(&self) -> Option<Duration> {
+        let message = if let Status::TemporaryFailure(message) = self {
+            message.to_lowercase()
+        } else {
+            return None;
+        };
+
+        for hint in [
+            "retry-after:",
+            "retry after",
+            "retry in",
+            "try again in",
+            "try again after",
+            "try later in",
+            "wait",
+        ] {
+            let mut offset = 0;
+            while let Some(pos) = message[offset..].find(hint) {
+                offset += pos + hint.len();
+                if let Some(duration) = parse_retry_hint(&message[offset..]) {
+                    return Some(duration);
+                }
+            }
+        }
+
+        None
+    }
+}
+
+fn parse_retry_hint(text: &str) -> Option<Duration> {
+    let mut words = text
+        .split(|c: char| c.is_whitespace() || matches!(c, ',' | ';' | '(' | ')' | '.'))
+        .filter(|w| !w.is_empty())
+        .skip_while(|w| matches!(*w, "at" | "least" | "about" | "approximately" | "for"));
+    let word = words.next()?;
+
+    // Support both "30 seconds" and "30s"
+    let (amount, unit) = match word.find(|c: char| !c.is_ascii_digit()) {
+        Some(0) => return None,
+        Some(pos) => (&word[..pos], &word[pos..]),
+        None => (word, words.next().unwrap_or("seconds")),
+    };
+    let amount = amount.parse::<i64>().ok()?;
+
+    // Out of range amounts are ignored rather than trusted
+    match unit {
+        "ms" | "msec" | "msecs" | "millisecond" | "milliseconds" => {
+            Duration::try_milliseconds(amount)
+        }
+        "s" | "sec" | "secs" | "second" | "seconds" => Duration::try_seconds(amount),
+        "m" | "min" | "mins" | "minute" | "minutes" => Duration::try_minutes(amount),
+        "h" | "hr" | "hrs" | "hour" | "hours" => Duration::try_hours(amount),
+        "d" | "day" | "days" => Duration::try_days(amount),
+        _ => None,
+    }
 }
 
 fn deserialize_maybe_datetime<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
@@ -169,3 +233,47 @@ where
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::{parse_retry_hint, Status};
+
+    #[test]
+    fn retry_hint_units() {
+        for (text, expected) in [
+            ("30", Duration::seconds(30)),
+            ("30s", Duration::seconds(30)),
+            ("30 seconds", Duration::seconds(30)),
+            ("500ms", Duration::milliseconds(500)),
+            ("500 ms", Duration::milliseconds(500)),
+            ("5m", Duration::minutes(5)),
+            ("5 mins", Duration::minutes(5)),
+            ("at least 2 hours", Duration::hours(2)),
+            ("1 day.", Duration::days(1)),
+        ] {
+            assert_eq!(parse_retry_hint(text), Some(expected), "{text:?}");
+        }
+
+        for text in ["", "later", "5 fortnights", "5 mss", "s30"] {
+            assert_eq!(parse_retry_hint(text), None, "{text:?}");
+        }
+    }
+
+    #[test]
+    fn retry_hint_out_of_range() {
+        for text in [
+            "9223372036854775807 hours",
+            "9223372036854775807 days",
+            "99999999999999999999 seconds",
+        ] {
+            assert_eq!(parse_retry_hint(text), None, "{text:?}");
+        }
+        assert_eq!(
+            Status::TemporaryFailure("451 Try again in 999999999999999 days".to_string())
+                .retry_after(),
+            None
+        );
+    }
+}
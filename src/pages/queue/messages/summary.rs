@@ -0,0 +1,275 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use ahash::AHashMap;
+use chrono::{DateTime, Utc};
+use humansize::{format_size, DECIMAL};
+use leptos::*;
+use leptos_router::use_navigate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        chart::{bar::BarChart, ChartLegend, LazyChart, Series},
+        messages::alert::{use_alerts, Alert},
+        Color,
+    },
+    core::{
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+    },
+    pages::{maybe_plural, queue::messages::Message, List},
+};
+
+/// Number of messages requested per page while sampling the queue.
+const PAGE_SIZE: usize = 100;
+
+/// Maximum number of messages sampled to build the summary.
+const MAX_SAMPLE: usize = 2000;
+
+const MAX_SENDER_DOMAINS: usize = 10;
+
+/// Upper bound in minutes and label of each message age bucket.
+const AGE_BUCKETS: &[(i64, &str)] = &[
+    (5, "< 5m"),
+    (30, "< 30m"),
+    (60, "< 1h"),
+    (360, "< 6h"),
+    (1440, "< 1d"),
+    (4320, "< 3d"),
+    (i64::MAX, "3d+"),
+];
+
+/// Upper bound in bytes and label of each message size bucket.
+const SIZE_BUCKETS: &[(usize, &str)] = &[
+    (10_000, "< 10KB"),
+    (100_000, "< 100KB"),
+    (1_000_000, "< 1MB"),
+    (10_000_000, "< 10MB"),
+    (usize::MAX, "10MB+"),
+];
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+struct QueueStats {
+    sampled: usize,
+    total: u64,
+    ages: Vec<f64>,
+    sizes: Vec<f64>,
+    queues: Vec<(String, f64)>,
+    senders: Vec<SenderDomain>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct SenderDomain {
+    name: String,
+    messages: usize,
+    size: usize,
+}
+
+/// Charts summarizing the queue contents. The queue is sampled in the
+/// background, one page at a time, so the message list is not held back.
+#[component]
+pub fn QueueSummary() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+
+    let stats = create_resource(
+        || (),
+        move |_| {
+            let auth = auth.get_untracked();
+
+            async move {
+                let mut messages = Vec::new();
+                let mut total = 0;
+                let mut page = 1;
+
+                while messages.len() < MAX_SAMPLE {
+                    let list = HttpRequest::get("/api/queue/messages")
+                        .with_authorization(&auth)
+                        .with_parameter("page", page.to_string())
+                        .with_parameter("limit", PAGE_SIZE.to_string())
+                        .with_parameter("values", "1")
+                        .send::<List<Message>>()
+                        .await?;
+                    let is_last = list.items.len() < PAGE_SIZE;
+                    total = list.total;
+                    messages.extend(list.items);
+                    if is_last || messages.len() as u64 >= total {
+                        break;
+                    }
+                    page += 1;
+                }
+
+                Ok(QueueStats::new(&messages, total, Utc::now()))
+            }
+        },
+    );
+
+    view! {
+        {move || match stats.get() {
+            None | Some(Err(http::Error::NotFound)) => None,
+            Some(Err(http::Error::Unauthorized)) => {
+                use_navigate()("/login", Default::default());
+                None
+            }
+            Some(Err(err)) => {
+                alert.set(Alert::from(err));
+                None
+            }
+            Some(Ok(stats)) if stats.sampled == 0 => None,
+            Some(Ok(stats)) => {
+                let subtitle = if stats.sampled as u64 >= stats.total {
+                    maybe_plural(stats.sampled, "queued message", "queued messages")
+                } else {
+                    format!("Sample of {} out of {} queued messages", stats.sampled, stats.total)
+                };
+                let max_size = stats.senders.first().map_or(1, |sender| sender.size.max(1));
+                let age_labels = AGE_BUCKETS
+                    .iter()
+                    .map(|(_, label)| label.to_string())
+                    .collect::<Vec<_>>();
+                let size_labels = SIZE_BUCKETS
+                    .iter()
+                    .map(|(_, label)| label.to_string())
+                    .collect::<Vec<_>>();
+                let (queue_labels, queue_values): (Vec<_>, Vec<_>) = stats
+                    .queues
+                    .into_iter()
+                    .unzip();
+                let age_series = vec![Series {
+                    name: "Messages".to_string(),
+                    color: Color::Blue,
+                    values: stats.ages.clone(),
+                }];
+                let size_series = vec![Series {
+                    name: "Messages".to_string(),
+                    color: Color::Yellow,
+                    values: stats.sizes.clone(),
+                }];
+                let queue_series = vec![Series {
+                    name: "Recipients".to_string(),
+                    color: Color::Green,
+                    values: queue_values,
+                }];
+                Some(
+                    view! {
+                        <div class="grid lg:grid-cols-2 gap-4 sm:gap-6 mb-6">
+                            <LazyChart title="Message age" subtitle=subtitle.clone()>
+                                <BarChart
+                                    labels=age_labels.clone()
+                                    series=age_series.clone()
+                                />
+                                <ChartLegend series=age_series.clone()/>
+                            </LazyChart>
+                            <LazyChart title="Message size" subtitle=subtitle.clone()>
+                                <BarChart
+                                    labels=size_labels.clone()
+                                    series=size_series.clone()
+                                />
+                                <ChartLegend series=size_series.clone()/>
+                            </LazyChart>
+                            <LazyChart title="Virtual queues" subtitle="Recipients by queue">
+                                <BarChart
+                                    labels=queue_labels.clone()
+                                    series=queue_series.clone()
+                                />
+                                <ChartLegend series=queue_series.clone()/>
+                            </LazyChart>
+                            <LazyChart title="Top sender domains" subtitle="By queued volume">
+                                <ul class="space-y-2">
+                                    {stats
+                                        .senders
+                                        .iter()
+                                        .map(|sender| {
+                                            let width = (sender.size as f64 / max_size as f64 * 100.0)
+                                                .max(1.0);
+                                            view! {
+                                                <li>
+                                                    <div class="flex justify-between text-sm">
+                                                        <span class="font-mono text-gray-800 dark:text-gray-200">
+                                                            {sender.name.clone()}
+                                                            <span class="ms-2 font-sans text-xs text-gray-500">
+                                                                {maybe_plural(sender.messages, "message", "messages")}
+                                                            </span>
+                                                        </span>
+                                                        <span class="text-gray-500">
+                                                            {format_size(sender.size, DECIMAL)}
+                                                        </span>
+                                                    </div>
+                                                    <div class="mt-1 h-1.5 w-full bg-gray-200 rounded-full dark:bg-gray-700">
+                                                        <div
+                                                            class="h-1.5 bg-blue-600 rounded-full"
+                                                            style=format!("width: {width:.1}%")
+                                                        ></div>
+                                                    </div>
+                                                </li>
+                                            }
+                                        })
+                                        .collect_view()}
+                                </ul>
+                            </LazyChart>
+                        </div>
+                    },
+                )
+            }
+        }}
+    }
+}
+
+impl QueueStats {
+    fn new(messages: &[Message], total: u64, now: DateTime<Utc>) -> Self {
+        let mut ages = vec![0.0; AGE_BUCKETS.len()];
+        let mut sizes = vec![0.0; SIZE_BUCKETS.len()];
+        let mut queues: AHashMap<&str, f64> = AHashMap::new();
+        let mut senders: AHashMap<&str, SenderDomain> = AHashMap::new();
+
+        for message in messages {
+            let age = (now - message.created).num_minutes();
+            if let Some(bucket) = AGE_BUCKETS.iter().position(|(max, _)| age < *max) {
+                ages[bucket] += 1.0;
+            }
+            if let Some(bucket) = SIZE_BUCKETS.iter().position(|(max, _)| message.size < *max) {
+                sizes[bucket] += 1.0;
+            }
+
+            for rcpt in message.domains.iter().flat_map(|d| d.recipients.iter()) {
+                *queues
+                    .entry(rcpt.queue.as_deref().unwrap_or("default"))
+                    .or_default() += 1.0;
+            }
+
+            let domain = message
+                .return_path
+                .rsplit_once('@')
+                .map_or("<>", |(_, domain)| domain);
+            let sender = senders.entry(domain).or_insert_with(|| SenderDomain {
+                name: domain.to_string(),
+                messages: 0,
+                size: 0,
+            });
+            sender.messages += 1;
+            sender.size += message.size;
+        }
+
+        let mut queues = queues
+            .into_iter()
+            .map(|(name, count)| (name.to_string(), count))
+            .collect::<Vec<_>>();
+        queues.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut senders = senders.into_values().collect::<Vec<_>>();
+        senders.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+        senders.truncate(MAX_SENDER_DOMAINS);
+
+        QueueStats {
+            sampled: messages.len(),
+            total,
+            ages,
+            sizes,
+            queues,
+            senders,
+        }
+    }
+}
@@ -30,18 +30,21 @@ use super::Feedback;
 #[allow(unused_parens)]
 pub fn ArfReportDisplay(
     report: Feedback,
-    received: DateTime<Utc>,
+    received: Option<DateTime<Utc>>,
     extra: Vec<(String, String)>,
     back_url: String,
 ) -> impl IntoView {
-    let received_date = received.format_date();
-    let received_time = received.format_time();
+    let received_date = received.map_or_else(|| "Unknown".to_string(), |date| date.format_date());
+    let received_time = received.map(|date| date.format_time()).unwrap_or_default();
     let arrival_date = report
         .arrival_date
         .and_then(|date| DateTime::from_timestamp(date, 0))
-        .unwrap_or(received);
-    let arrival_time = arrival_date.format_time();
-    let arrival_date = arrival_date.format_date();
+        .or(received);
+    let arrival_time = arrival_date
+        .map(|date| date.format_time())
+        .unwrap_or_default();
+    let arrival_date =
+        arrival_date.map_or_else(|| "Unknown".to_string(), |date| date.format_date());
     let has_port = report.source_port > 0;
     let extra = extra
         .into_iter()
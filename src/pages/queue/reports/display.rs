@@ -35,34 +35,6 @@ pub fn ReportDisplay() -> impl IntoView {
             let id = id.clone();
 
             async move {
-                #[cfg(feature = "demo")]
-                {
-                    if id == "dmarc_demo" {
-                        return Ok(AggregateReport::Dmarc {
-                            rua: vec![crate::pages::queue::reports::URI {
-                                uri: "rcpt@sender.org".to_string(),
-                                max_size: 0,
-                            }],
-                            id,
-                            domain: "foobar.net".to_string(),
-                            range_from: chrono::Utc::now(),
-                            range_to: chrono::Utc::now(),
-                            report: crate::pages::queue::reports::test_dmarc_report(),
-                        });
-                    } else if id == "tls_demo" {
-                        return Ok(AggregateReport::Tls {
-                            rua: vec![crate::pages::queue::reports::ReportUri::Mail(
-                                "rcpt@sender.org".to_string(),
-                            )],
-                            id,
-                            domain: "foobar.net".to_string(),
-                            range_from: chrono::Utc::now(),
-                            range_to: chrono::Utc::now(),
-                            report: crate::pages::queue::reports::test_tls_report(),
-                        });
-                    }
-                }
-
                 HttpRequest::get(("/api/queue/reports", &id))
                     .with_authorization(&auth)
                     .send::<AggregateReport>()
@@ -11,10 +11,11 @@ use std::collections::HashSet;
 use crate::{
     components::{
         badge::Badge,
-        icon::{IconCancel, IconEnvelope, IconRefresh, IconShieldCheck},
+        icon::{IconCancel, IconClock, IconEnvelope, IconRefresh, IconShieldCheck},
         list::{
             header::ColumnList,
             pagination::Pagination,
+            query::use_list_query,
             row::SelectItem,
             toolbar::{SearchBox, ToolbarButton},
             Footer, ListItem, ListSection, ListTable, ListTextItem, Toolbar, ZeroResults,
@@ -29,45 +30,31 @@ use crate::{
     core::{
         http::{self, HttpRequest},
         oauth::use_authorization,
-        url::UrlBuilder,
+        permissions::Capability,
     },
     pages::{
         maybe_plural,
         queue::reports::{AggregateReportId, AggregateReportType},
-        FormatDateTime, List,
+        FormatDateTime, List, RETENTION_PERIODS,
     },
 };
 
+use chrono::{Duration, Utc};
 use chrono_humanize::{Accuracy, HumanTime, Tense};
 
 const PAGE_SIZE: u32 = 10;
 
 #[component]
 pub fn ReportList() -> impl IntoView {
-    let query = use_query_map();
-    let page = create_memo(move |_| {
-        query
-            .with(|q| q.get("page").and_then(|page| page.parse::<u32>().ok()))
-            .filter(|&page| page > 0)
-            .unwrap_or(1)
-    });
-    let filter = create_memo(move |_| {
-        query.with(|q| {
-            q.get("filter").and_then(|s| {
-                let s = s.trim();
-                if !s.is_empty() {
-                    Some(s.to_string())
-                } else {
-                    None
-                }
-            })
-        })
-    });
+    let list = use_list_query();
+    let page = list.page();
+    let filter = list.param("filter");
 
     let auth = use_authorization();
     let alert = use_alerts();
     let modal = use_modals();
     let selected = create_rw_signal::<HashSet<String>>(HashSet::new());
+    let retention = create_rw_signal(90i64);
     provide_context(selected);
 
     let reports = create_resource(
@@ -135,6 +122,55 @@ pub fn ReportList() -> impl IntoView {
         }
     });
 
+    let purge_action = create_action(move |days: &i64| {
+        let days = *days;
+        let auth = auth.get();
+        let cutoff = Utc::now() - Duration::days(days);
+
+        async move {
+            match HttpRequest::get("/api/queue/reports")
+                .with_authorization(&auth)
+                .send::<List<String>>()
+                .await
+            {
+                Ok(ids) => {
+                    let to_cancel = ids
+                        .items
+                        .into_iter()
+                        .filter(|id| {
+                            AggregateReportId::parse(id.clone())
+                                .map_or(false, |report| report.created < cutoff)
+                        })
+                        .collect::<HashSet<_>>();
+                    if to_cancel.is_empty() {
+                        alert.set(Alert::warning(format!(
+                            "There are no reports older than {days} days."
+                        )));
+                        return;
+                    }
+
+                    let text = maybe_plural(to_cancel.len(), "report", "reports");
+                    modal.set(
+                        Modal::with_title("Confirm deletion")
+                            .with_message(format!(
+                                "Are you sure you want to cancel delivery of {text} created more than {days} days ago? This action cannot be undone.",
+                            ))
+                            .with_button(format!("Delete {text}"))
+                            .with_dangerous_callback(move || {
+                                cancel_action.dispatch(to_cancel.clone());
+                            }),
+                    );
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
     let total_results = create_rw_signal(None::<u32>);
 
     view! {
@@ -147,12 +183,7 @@ pub fn ReportList() -> impl IntoView {
                     <SearchBox
                         value=filter
                         on_search=move |value| {
-                            use_navigate()(
-                                &UrlBuilder::new("/manage/queue/reports")
-                                    .with_parameter("filter", value)
-                                    .finish(),
-                                Default::default(),
-                            );
+                            list.set_filters([("filter", Some(value))]);
                         }
                     />
 
@@ -197,11 +228,51 @@ pub fn ReportList() -> impl IntoView {
                                     )
                             }
                         })
+
+                        capability=Capability::OutgoingReportDelete
                     >
 
                         <IconCancel/>
                     </ToolbarButton>
 
+                    <select
+                        class="py-2 px-3 pe-9 block border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                        on:change=move |ev| {
+                            retention.set(event_target_value(&ev).parse().unwrap_or(90));
+                        }
+                    >
+
+                        {RETENTION_PERIODS
+                            .iter()
+                            .map(|(days, label)| {
+                                let days = *days;
+                                view! {
+                                    <option
+                                        value=days.to_string()
+                                        selected=move || retention.get() == days
+                                    >
+                                        {*label}
+                                    </option>
+                                }
+                            })
+                            .collect_view()}
+
+                    </select>
+
+                    <ToolbarButton
+                        text="Cancel older"
+
+                        color=Color::Red
+                        on_click=Callback::new(move |_| {
+                            purge_action.dispatch(retention.get());
+                        })
+
+                        capability=Capability::OutgoingReportDelete
+                    >
+
+                        <IconClock/>
+                    </ToolbarButton>
+
                 </Toolbar>
 
                 <Transition fallback=Skeleton>
@@ -275,13 +346,7 @@ pub fn ReportList() -> impl IntoView {
                         total_results=total_results.read_only()
                         page_size=PAGE_SIZE
                         on_page_change=move |page: u32| {
-                            use_navigate()(
-                                &UrlBuilder::new("/manage/queue/reports")
-                                    .with_parameter("page", page.to_string())
-                                    .with_optional_parameter("filter", filter.get())
-                                    .finish(),
-                                Default::default(),
-                            );
+                            list.set_page(page);
                         }
                     />
 
@@ -12,14 +12,24 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     components::{
-        messages::alert::{use_alerts, Alert, Alerts},
+        form::button::Button,
+        messages::{
+            alert::{use_alerts, Alert, Alerts},
+            modal::{use_modals, Modal},
+        },
+        report::{ReportItem, ReportSection, ReportView},
         skeleton::Skeleton,
+        Color,
     },
     core::{
         http::{self, HttpRequest},
         oauth::use_authorization,
+        permissions::Capability,
+        url::UrlBuilder,
     },
     pages::{
+        config::UpdateSettings,
+        manage::spam::Response,
         queue::reports::{
             arf::ArfReportDisplay, dmarc::DmarcReportDisplay, tls::TlsReportDisplay, Feedback,
             Report, TlsReport,
@@ -144,6 +154,7 @@ pub fn IncomingReportDisplay() -> impl IntoView {
                             );
                             Some(
                                 view! {
+                                    <ArfTriage report=report.clone()/>
                                     <ArfReportDisplay
                                         report=report
                                         received=received
@@ -174,3 +185,180 @@ impl<T> IncomingReport<T> {
         )
     }
 }
+
+#[component]
+fn ArfTriage(report: Feedback) -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let modal = use_modals();
+    let (pending, set_pending) = create_signal(false);
+
+    let source_ip = report.source_ip.map(|ip| ip.to_string());
+    let message = report.message.clone().or_else(|| report.headers.clone());
+    let trace_filter = message
+        .as_deref()
+        .and_then(parse_message_id)
+        .or_else(|| report.original_envelope_id.clone());
+
+    let train_action = create_action(move |message: &String| {
+        let auth = auth.get();
+        let message = message.clone();
+
+        async move {
+            set_pending.set(true);
+            let result = HttpRequest::post("/api/sieve/train")
+                .with_authorization(&auth)
+                .with_parameter("train", "spam")
+                .with_raw_body(message)
+                .send::<Response>()
+                .await;
+            set_pending.set(false);
+
+            match result {
+                Ok(Response::Accept { .. }) => {
+                    alert.set(Alert::success("Reported message added to the spam corpus."));
+                }
+                Ok(Response::Reject { reason }) => {
+                    alert.set(Alert::warning("Training failed").with_details(reason));
+                }
+                Ok(_) => {
+                    alert.set(Alert::error("Unexpected server response"));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    let block_action = create_action(move |ip: &String| {
+        let auth = auth.get();
+        let ip = ip.clone();
+
+        async move {
+            set_pending.set(true);
+            let result = HttpRequest::post("/api/settings")
+                .with_authorization(&auth)
+                .with_body(vec![UpdateSettings::Insert {
+                    prefix: Some("server.blocked-ip".to_string()),
+                    values: vec![(ip.clone(), String::new())],
+                    assert_empty: false,
+                }])
+                .unwrap()
+                .send::<Option<String>>()
+                .await;
+            set_pending.set(false);
+
+            match result {
+                Ok(_) => {
+                    alert.set(Alert::success(format!(
+                        "IP address {ip} added to the block list. Reload the configuration to apply the changes."
+                    )));
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    let has_message = message.is_some();
+    let has_source_ip = source_ip.is_some();
+    let has_trace = trace_filter.is_some();
+    let block_label = source_ip
+        .as_ref()
+        .map(|ip| format!("Block {ip}"))
+        .unwrap_or_default();
+
+    view! {
+        <ReportView>
+            <ReportSection title="Triage">
+                <ReportItem label="Spam training" hide=!has_message>
+                    <Button
+                        text="Train as spam"
+                        color=Color::Gray
+                        on_click=move |_| {
+                            if let Some(message) = message.clone() {
+                                train_action.dispatch(message);
+                            }
+                        }
+
+                        disabled=pending
+                        capability=Capability::SpamFilterTrain
+                    />
+                </ReportItem>
+                <ReportItem label="Originating IP" hide=!has_source_ip>
+                    <Button
+                        text=block_label
+                        color=Color::Red
+                        on_click=move |_| {
+                            if let Some(ip) = source_ip.clone() {
+                                modal
+                                    .set(
+                                        Modal::with_title("Confirm block")
+                                            .with_message(
+                                                format!(
+                                                    "Are you sure you want to block all connections from {ip}?",
+                                                ),
+                                            )
+                                            .with_button(format!("Block {ip}"))
+                                            .with_dangerous_callback(move || {
+                                                block_action.dispatch(ip.clone());
+                                            }),
+                                    );
+                            }
+                        }
+
+                        disabled=pending
+                        capability=Capability::SettingsUpdate
+                    />
+                </ReportItem>
+                <ReportItem label="Delivery trace" hide=!has_trace>
+                    <Button
+                        text="Open delivery trace"
+                        color=Color::Gray
+                        on_click=move |_| {
+                            if let Some(filter) = trace_filter.clone() {
+                                use_navigate()(
+                                    &UrlBuilder::new("/manage/tracking")
+                                        .with_parameter("filter", filter)
+                                        .finish(),
+                                    Default::default(),
+                                );
+                            }
+                        }
+                    />
+                </ReportItem>
+            </ReportSection>
+        </ReportView>
+    }
+}
+
+/// Returns the Message-ID of the reported message, without angle brackets.
+fn parse_message_id(message: &str) -> Option<String> {
+    let mut lines = message.lines();
+    while let Some(line) = lines.next() {
+        if line.is_empty() {
+            break;
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        if name.trim().eq_ignore_ascii_case("message-id") {
+            // The value may be folded onto the following line
+            let mut value = value.trim().to_string();
+            if value.is_empty() {
+                value = lines.next()?.trim().to_string();
+            }
+            let value = value.trim_start_matches('<').trim_end_matches('>');
+            return (!value.is_empty()).then(|| value.to_string());
+        }
+    }
+    None
+}
@@ -8,10 +8,10 @@ use leptos::*;
 use leptos_router::*;
 use std::collections::HashSet;
 
-use super::ReportType;
+use super::{parse_report_date, ReportType};
 use crate::{
     components::{
-        icon::{IconCancel, IconRefresh},
+        icon::{IconArrowUpTray, IconCancel, IconClock, IconRefresh},
         list::{
             header::ColumnList,
             pagination::Pagination,
@@ -29,15 +29,17 @@ use crate::{
     core::{
         http::{self, HttpRequest},
         oauth::use_authorization,
+        permissions::Capability,
         url::UrlBuilder,
     },
     pages::{
         maybe_plural,
         queue::reports::{Feedback, Report, TlsReport},
         reports::{IncomingReport, IncomingReportSummary},
-        FormatDateTime, List,
+        FormatDateTime, List, RETENTION_PERIODS,
     },
 };
+use chrono::{Duration, Utc};
 use chrono_humanize::{Accuracy, HumanTime, Tense};
 
 const PAGE_SIZE: u32 = 10;
@@ -82,6 +84,7 @@ pub fn IncomingReportList() -> impl IntoView {
     let alert = use_alerts();
     let modal = use_modals();
     let selected = create_rw_signal::<HashSet<String>>(HashSet::new());
+    let retention = create_rw_signal(90i64);
     provide_context(selected);
 
     let reports = create_resource(
@@ -166,6 +169,53 @@ pub fn IncomingReportList() -> impl IntoView {
         }
     });
 
+    let purge_action = create_action(move |days: &i64| {
+        let days = *days;
+        let auth = auth.get();
+        let report_type = report_type.get();
+        let cutoff = Utc::now() - Duration::days(days);
+
+        async move {
+            match HttpRequest::get(format!("/api/reports/{}", report_type.as_str()))
+                .with_authorization(&auth)
+                .send::<List<String>>()
+                .await
+            {
+                Ok(ids) => {
+                    let to_delete = ids
+                        .items
+                        .into_iter()
+                        .filter(|id| parse_report_date(id).is_some_and(|date| date < cutoff))
+                        .collect::<HashSet<_>>();
+                    if to_delete.is_empty() {
+                        alert.set(Alert::warning(format!(
+                            "There are no reports older than {days} days."
+                        )));
+                        return;
+                    }
+
+                    let text = maybe_plural(to_delete.len(), "report", "reports");
+                    modal.set(
+                        Modal::with_title("Confirm deletion")
+                            .with_message(format!(
+                                "Are you sure you want to delete {text} received more than {days} days ago? This action cannot be undone.",
+                            ))
+                            .with_button(format!("Delete {text}"))
+                            .with_dangerous_callback(move || {
+                                delete_action.dispatch(to_delete.clone());
+                            }),
+                    );
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
     let total_results = create_rw_signal(None::<u32>);
 
     let title = create_memo(move |_| {
@@ -203,6 +253,20 @@ pub fn IncomingReportList() -> impl IntoView {
                         }
                     />
 
+                    <ToolbarButton
+                        text="Upload"
+
+                        color=Color::Gray
+                        on_click=Callback::new(move |_| {
+                            use_navigate()("/manage/reports/upload", Default::default());
+                        })
+
+                        capability=Capability::IncomingReportCreate
+                    >
+
+                        <IconArrowUpTray/>
+                    </ToolbarButton>
+
                     <ToolbarButton
                         text="Reload"
 
@@ -244,11 +308,51 @@ pub fn IncomingReportList() -> impl IntoView {
                                     )
                             }
                         })
+
+                        capability=Capability::IncomingReportDelete
                     >
 
                         <IconCancel/>
                     </ToolbarButton>
 
+                    <select
+                        class="py-2 px-3 pe-9 block border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600"
+                        on:change=move |ev| {
+                            retention.set(event_target_value(&ev).parse().unwrap_or(90));
+                        }
+                    >
+
+                        {RETENTION_PERIODS
+                            .iter()
+                            .map(|(days, label)| {
+                                let days = *days;
+                                view! {
+                                    <option
+                                        value=days.to_string()
+                                        selected=move || retention.get() == days
+                                    >
+                                        {*label}
+                                    </option>
+                                }
+                            })
+                            .collect_view()}
+
+                    </select>
+
+                    <ToolbarButton
+                        text="Delete older"
+
+                        color=Color::Red
+                        on_click=Callback::new(move |_| {
+                            purge_action.dispatch(retention.get());
+                        })
+
+                        capability=Capability::IncomingReportDelete
+                    >
+
+                        <IconClock/>
+                    </ToolbarButton>
+
                 </Toolbar>
 
                 <Transition fallback=Skeleton>
@@ -460,7 +564,12 @@ fn ReportItem(report: IncomingReportSummary) -> impl IntoView {
             vec![
                 Item::Single(from),
                 Item::Single(typ.to_string()),
-                Item::Single(arrival_date.unwrap_or(received).format_date_time()),
+                Item::Single(
+                    arrival_date
+                        .or(received)
+                        .map(|date| date.format_date_time())
+                        .unwrap_or_else(|| "Unknown".to_string()),
+                ),
                 {
                     if domains.len() > 1 {
                         Item::Double((
@@ -11,6 +11,7 @@ use super::queue::reports::{ActionDisposition, Feedback, FeedbackType, Report, T
 
 pub mod display;
 pub mod list;
+pub mod upload;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ReportType {
@@ -23,7 +24,7 @@ pub enum ReportType {
 pub enum IncomingReportSummary {
     Dmarc {
         id: String,
-        received: DateTime<Utc>,
+        received: Option<DateTime<Utc>>,
         range_from: DateTime<Utc>,
         range_to: DateTime<Utc>,
         from: String,
@@ -34,7 +35,7 @@ pub enum IncomingReportSummary {
     },
     Tls {
         id: String,
-        received: DateTime<Utc>,
+        received: Option<DateTime<Utc>>,
         range_from: DateTime<Utc>,
         range_to: DateTime<Utc>,
         from: String,
@@ -44,7 +45,7 @@ pub enum IncomingReportSummary {
     },
     Arf {
         id: String,
-        received: DateTime<Utc>,
+        received: Option<DateTime<Utc>>,
         arrival_date: Option<DateTime<Utc>>,
         typ: FeedbackType,
         from: String,
@@ -160,12 +161,9 @@ impl IncomingReportSummary {
     }
 }
 
-pub(super) fn parse_report_date(id: &str) -> DateTime<Utc> {
-    DateTime::from_timestamp(
-        id.split_once('_')
-            .and_then(|(_, id)| id.parse::<i64>().ok())
-            .unwrap_or(0),
-        0,
-    )
-    .unwrap_or_else(Utc::now)
+/// Extracts the date a report was received from its ID.
+pub(super) fn parse_report_date(id: &str) -> Option<DateTime<Utc>> {
+    id.split_once('_')
+        .and_then(|(_, id)| id.parse::<i64>().ok())
+        .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0))
 }
@@ -0,0 +1,298 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use chrono::Utc;
+use leptos::*;
+use leptos_router::use_navigate;
+
+use crate::{
+    components::{
+        form::button::Button,
+        messages::alert::{use_alerts, Alert, Alerts},
+        report::ReportView,
+        Color,
+    },
+    core::{
+        file::read_file,
+        http::{self, HttpRequest},
+        oauth::use_authorization,
+        permissions::Capability,
+    },
+    pages::queue::reports::{
+        arf::ArfReportDisplay, dmarc::DmarcReportDisplay, tls::TlsReportDisplay, Feedback, Report,
+        TlsReport,
+    },
+};
+
+use super::ReportType;
+
+const SELECT_CLASS: &str = "py-2 px-3 pe-9 block w-full border-gray-200 rounded-lg text-sm focus:border-blue-500 focus:ring-blue-500 disabled:opacity-50 disabled:pointer-events-none dark:bg-slate-900 dark:border-gray-700 dark:text-gray-400 dark:focus:ring-gray-600";
+
+#[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
+enum UploadedReport {
+    Dmarc(Report),
+    Tls(TlsReport),
+    Arf(Feedback),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ReportFile {
+    name: String,
+    contents: String,
+}
+
+/// Renders a DMARC, TLS-RPT or ARF report file uploaded by the user with the
+/// same views used for stored reports, and optionally adds it to the
+/// report store.
+#[component]
+pub fn IncomingReportUpload() -> impl IntoView {
+    let auth = use_authorization();
+    let alert = use_alerts();
+    let report_type = create_rw_signal(ReportType::Dmarc);
+    let file = create_rw_signal(None::<ReportFile>);
+    let report = create_rw_signal(None::<UploadedReport>);
+
+    let parse_action = create_action(move |(typ, file): &(ReportType, ReportFile)| {
+        let auth = auth.get();
+        let typ = *typ;
+        let contents = file.contents.clone();
+
+        async move {
+            report.set(None);
+            let url = format!("/api/reports/{}/parse", typ.as_str());
+            let result = match typ {
+                ReportType::Tls => serde_json::from_str::<TlsReport>(&contents)
+                    .map(UploadedReport::Tls)
+                    .map_err(|err| {
+                        Alert::error("Invalid TLS report").with_details(err.to_string())
+                    }),
+                ReportType::Dmarc => HttpRequest::post(url)
+                    .with_authorization(&auth)
+                    .with_raw_body(contents)
+                    .send::<Report>()
+                    .await
+                    .map(UploadedReport::Dmarc)
+                    .map_err(parse_error),
+                ReportType::Arf => HttpRequest::post(url)
+                    .with_authorization(&auth)
+                    .with_raw_body(contents)
+                    .send::<Feedback>()
+                    .await
+                    .map(UploadedReport::Arf)
+                    .map_err(parse_error),
+            };
+
+            match result {
+                Ok(parsed) => report.set(Some(parsed)),
+                Err(err) => alert.set(err),
+            }
+        }
+    });
+
+    let save_action = create_action(move |(typ, file): &(ReportType, ReportFile)| {
+        let auth = auth.get();
+        let typ = *typ;
+        let contents = file.contents.clone();
+
+        async move {
+            match HttpRequest::post(format!("/api/reports/{}", typ.as_str()))
+                .with_authorization(&auth)
+                .with_raw_body(contents)
+                .send::<String>()
+                .await
+            {
+                Ok(id) => {
+                    use_navigate()(
+                        &format!("/manage/reports/{}/{id}", typ.as_str()),
+                        Default::default(),
+                    );
+                }
+                Err(http::Error::Unauthorized) => {
+                    use_navigate()("/login", Default::default());
+                }
+                Err(http::Error::NotFound) => {
+                    alert.set(Alert::warning(
+                        "This server does not support adding reports to the report store.",
+                    ));
+                }
+                Err(err) => {
+                    alert.set(Alert::from(err));
+                }
+            }
+        }
+    });
+
+    let load_file = create_action(move |input: &web_sys::File| {
+        let input = input.clone();
+
+        async move {
+            match read_file(&input).await {
+                Ok(contents) => {
+                    let name = input.name();
+                    let typ = detect_type(&name);
+                    let contents = ReportFile {
+                        name,
+                        contents: String::from_utf8_lossy(&contents).into_owned(),
+                    };
+                    report_type.set(typ);
+                    file.set(Some(contents.clone()));
+                    parse_action.dispatch((typ, contents));
+                }
+                Err(err) => {
+                    alert.set(Alert::error(err));
+                }
+            }
+        }
+    });
+
+    view! {
+        <Alerts/>
+        <ReportView>
+            <div class="gap-2 sm:gap-4 py-8 first:pt-0 last:pb-0 border-t first:border-transparent border-gray-200 dark:border-gray-700 dark:first:border-transparent">
+                <div class="sm:col-span-12 pb-4">
+                    <h2 class="text-lg font-semibold text-gray-800 dark:text-gray-200">
+                        Upload report
+                    </h2>
+                    <p class="text-sm text-gray-500">
+                        Analyze a DMARC aggregate (XML), TLS (JSON) or abuse (ARF) report received elsewhere. Compressed reports must be extracted first.
+                    </p>
+                </div>
+                <div class="grid sm:grid-cols-2 gap-4">
+                    <input
+                        type="file"
+                        class="block w-full text-sm text-gray-500 file:me-4 file:py-2 file:px-4 file:rounded-lg file:border-0 file:text-sm file:font-semibold file:bg-blue-600 file:text-white hover:file:bg-blue-700 dark:text-gray-400"
+                        accept=".xml,.json,.eml,message/rfc822,application/json,text/xml"
+                        on:change=move |ev| {
+                            let input = event_target::<web_sys::HtmlInputElement>(&ev);
+                            if let Some(selected) = input.files().and_then(|files| files.get(0)) {
+                                load_file.dispatch(selected);
+                            }
+                            input.set_value("");
+                        }
+                    />
+
+                    <select
+                        class=SELECT_CLASS
+                        on:change=move |ev| {
+                            let typ = match event_target_value(&ev).as_str() {
+                                "tls" => ReportType::Tls,
+                                "arf" => ReportType::Arf,
+                                _ => ReportType::Dmarc,
+                            };
+                            report_type.set(typ);
+                            if let Some(file) = file.get_untracked() {
+                                parse_action.dispatch((typ, file));
+                            }
+                        }
+                    >
+
+                        {[
+                            (ReportType::Dmarc, "DMARC aggregate report"),
+                            (ReportType::Tls, "TLS report"),
+                            (ReportType::Arf, "Abuse feedback report"),
+                        ]
+                            .into_iter()
+                            .map(|(typ, label)| {
+                                view! {
+                                    <option
+                                        value=typ.as_str()
+                                        selected=move || report_type.get() == typ
+                                    >
+                                        {label}
+                                    </option>
+                                }
+                            })
+                            .collect_view()}
+                    </select>
+                </div>
+            </div>
+
+            <div class="flex justify-end gap-x-2">
+                <Button
+                    text="Back"
+                    color=Color::Gray
+                    on_click=move |_| {
+                        use_navigate()(
+                            &format!("/manage/reports/{}", report_type.get().as_str()),
+                            Default::default(),
+                        );
+                    }
+                />
+                <Button
+                    text="Add to report store"
+                    color=Color::Blue
+                    on_click=move |_| {
+                        if let Some(file) = file.get() {
+                            save_action.dispatch((report_type.get(), file));
+                        }
+                    }
+
+                    disabled=Signal::derive(move || {
+                        report.with(|report| report.is_none()) || save_action.pending().get()
+                    })
+
+                    capability=Capability::IncomingReportCreate
+                />
+
+            </div>
+        </ReportView>
+
+        {move || {
+            let back_url = format!("/manage/reports/{}", report_type.get_untracked().as_str());
+            let extra = vec![
+                (
+                    "File".to_string(),
+                    file.with_untracked(|file| {
+                        file.as_ref().map(|file| file.name.clone()).unwrap_or_default()
+                    }),
+                ),
+            ];
+            report
+                .get()
+                .map(|report| match report {
+                    UploadedReport::Dmarc(report) => {
+                        view! { <DmarcReportDisplay report=report extra=extra back_url=back_url/> }
+                    }
+                    UploadedReport::Tls(report) => {
+                        view! { <TlsReportDisplay report=report extra=extra back_url=back_url/> }
+                    }
+                    UploadedReport::Arf(report) => {
+                        view! {
+                            <ArfReportDisplay
+                                report=report
+                                received=Some(Utc::now())
+                                extra=extra
+                                back_url=back_url
+                            />
+                        }
+                    }
+                })
+        }}
+    }
+}
+
+fn detect_type(file_name: &str) -> ReportType {
+    let file_name = file_name.to_lowercase();
+    if file_name.ends_with(".json") {
+        ReportType::Tls
+    } else if file_name.ends_with(".eml") {
+        ReportType::Arf
+    } else {
+        ReportType::Dmarc
+    }
+}
+
+fn parse_error(err: http::Error) -> Alert {
+    match err {
+        http::Error::Unauthorized => {
+            use_navigate()("/login", Default::default());
+            Alert::error("Unauthorized")
+        }
+        http::Error::NotFound => Alert::warning("This server does not support parsing reports."),
+        err => Alert::from(err),
+    }
+}
@@ -0,0 +1,65 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+use gloo_storage::{SessionStorage, Storage};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use crate::{core::oauth::AuthToken, STATE_STORAGE_KEY};
+
+use super::*;
+
+#[wasm_bindgen_test]
+async fn login() {
+    mount_app("/login", false);
+
+    fill(
+        &wait_for_selector("input[placeholder='user@example.org']").await,
+        "admin",
+    );
+    fill(&wait_for_selector("input[type='password']").await, "secret");
+    click(&wait_for_selector("button[type='submit']").await);
+
+    wait_for_path("/manage/directory/accounts").await;
+    let auth = SessionStorage::get::<AuthToken>(STATE_STORAGE_KEY).unwrap();
+    assert!(auth.is_valid);
+    assert!(auth.is_admin);
+    assert_eq!(auth.username.as_str(), "admin");
+}
+
+#[wasm_bindgen_test]
+async fn create_account() {
+    mount_app("/manage/directory/accounts/edit", true);
+
+    fill(
+        &wait_for_selector("input[placeholder='Login name']").await,
+        "newuser",
+    );
+    fill(
+        &wait_for_selector("input[placeholder='Full Name']").await,
+        "New User",
+    );
+    click(&wait_for_button("Save changes").await);
+
+    wait_for_path("/manage/directory/accounts").await;
+}
+
+#[wasm_bindgen_test]
+async fn save_settings() {
+    mount_app("/settings/network/edit", true);
+
+    click(&wait_for_button("Save changes").await);
+
+    wait_for_text("Your changes have been saved successfully.").await;
+}
+
+#[wasm_bindgen_test]
+async fn retry_queued_message() {
+    mount_app("/manage/queue/message/1000", true);
+
+    click(&wait_for_button("Retry now").await);
+
+    wait_for_text("Requested immediate delivery to john@gmail.com.").await;
+}
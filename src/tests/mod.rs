@@ -0,0 +1,156 @@
+/*
+ * SPDX-FileCopyrightText: 2020 Stalwart Labs Ltd <hello@stalw.art>
+ *
+ * SPDX-License-Identifier: AGPL-3.0-only OR LicenseRef-SEL
+ */
+
+//! Browser tests that mount the application against the mock API served by
+//! the `demo` feature. Run them with:
+//!
+//! `wasm-pack test --headless --firefox --features demo`
+
+pub mod flows;
+
+use std::{cell::RefCell, time::Duration};
+
+use gloo_storage::{SessionStorage, Storage};
+use leptos::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::wasm_bindgen_test_configure;
+use web_sys::{Event, EventInit, HtmlElement, HtmlInputElement};
+
+use crate::{
+    core::{connection::sleep, oauth::AuthToken},
+    App, STATE_STORAGE_KEY,
+};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+thread_local! {
+    static MOUNTED_APP: RefCell<Option<Disposer>> = const { RefCell::new(None) };
+}
+
+/// Mounts the application at `path`, optionally with an administrator
+/// already signed in. The application mounted by a previous test is
+/// disposed first, so its effects and listeners do not leak into this one.
+pub fn mount_app(path: &str, signed_in: bool) {
+    console_error_panic_hook::set_once();
+    unmount_app();
+
+    if signed_in {
+        SessionStorage::set(
+            STATE_STORAGE_KEY,
+            AuthToken {
+                access_token: "demo".to_string().into(),
+                refresh_token: "demo".to_string().into(),
+                username: "admin".to_string().into(),
+                is_valid: true,
+                is_admin: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    } else {
+        SessionStorage::clear();
+    }
+
+    window()
+        .history()
+        .unwrap()
+        .replace_state_with_url(&wasm_bindgen::JsValue::NULL, "", Some(path))
+        .unwrap();
+    let mount = as_child_of_current_owner(|_: ()| {
+        mount_to(document().body().unwrap(), || view! { <App/> })
+    });
+    let ((), disposer) = mount(());
+    MOUNTED_APP.with(|mounted| *mounted.borrow_mut() = Some(disposer));
+}
+
+/// Disposes the reactive owner of the mounted application, running its
+/// cleanups, and removes it from the page.
+pub fn unmount_app() {
+    if let Some(disposer) = MOUNTED_APP.with(|mounted| mounted.borrow_mut().take()) {
+        drop(disposer);
+    }
+    document().body().unwrap().set_inner_html("");
+}
+
+/// Polls `check` until it returns a value, panicking after a timeout.
+pub async fn wait_for<T>(what: &str, mut check: impl FnMut() -> Option<T>) -> T {
+    let mut waited = Duration::ZERO;
+    loop {
+        if let Some(value) = check() {
+            return value;
+        }
+        if waited >= TIMEOUT {
+            panic!("Timed out waiting for {what}");
+        }
+        sleep(POLL_INTERVAL).await;
+        waited += POLL_INTERVAL;
+    }
+}
+
+/// Waits for an element matching a CSS selector.
+pub async fn wait_for_selector(selector: &str) -> HtmlElement {
+    wait_for(selector, || {
+        document()
+            .query_selector(selector)
+            .ok()
+            .flatten()
+            .and_then(|element| element.dyn_into::<HtmlElement>().ok())
+    })
+    .await
+}
+
+/// Waits for an enabled button whose text is `text`.
+pub async fn wait_for_button(text: &str) -> HtmlElement {
+    wait_for(&format!("button {text:?}"), || {
+        let buttons = document().query_selector_all("button").ok()?;
+        (0..buttons.length())
+            .filter_map(|idx| buttons.item(idx)?.dyn_into::<HtmlElement>().ok())
+            .find(|button| {
+                button.text_content().unwrap_or_default().trim() == text
+                    && !button.has_attribute("disabled")
+            })
+    })
+    .await
+}
+
+/// Waits until `text` is displayed anywhere on the page.
+pub async fn wait_for_text(text: &str) {
+    wait_for(&format!("text {text:?}"), || {
+        document()
+            .body()?
+            .text_content()
+            .filter(|content| content.contains(text))
+    })
+    .await;
+}
+
+/// Waits until the router navigates to `path`.
+pub async fn wait_for_path(path: &str) {
+    wait_for(&format!("path {path:?}"), || {
+        window()
+            .location()
+            .pathname()
+            .ok()
+            .filter(|pathname| pathname == path)
+    })
+    .await;
+}
+
+/// Types `value` into an input and notifies the form of the change.
+pub fn fill(element: &HtmlElement, value: &str) {
+    element.unchecked_ref::<HtmlInputElement>().set_value(value);
+    for event in ["input", "change"] {
+        let event = Event::new_with_event_init_dict(event, EventInit::new().bubbles(true));
+        element.dispatch_event(&event.unwrap()).unwrap();
+    }
+}
+
+pub fn click(element: &HtmlElement) {
+    element.click();
+}